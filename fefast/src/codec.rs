@@ -102,22 +102,39 @@ impl Codec for i32 {
 }
 
 impl Codec for u64 {
-    fn deserialize(&mut self, _input: &mut impl io::Read) -> io::Result<usize> {
-        todo!();
+    fn deserialize(&mut self, input: &mut impl io::Read) -> io::Result<usize> {
+        *self = 0;
+        let bytes = decode_stop_bit_entity(input)?;
+        for byte in &bytes {
+            *self = (*self << 7) | u64::from(*byte);
+        }
+        Ok(bytes.len())
     }
 
-    fn serialize(&self, _output: &mut impl io::Write) -> io::Result<usize> {
-        todo!();
+    fn serialize(&self, output: &mut impl io::Write) -> io::Result<usize> {
+        let mut bytes = Vec::with_capacity(10);
+        write_stop_bit_uint(&mut bytes, i128::from(*self));
+        output.write_all(&bytes[..])?;
+        Ok(bytes.len())
     }
 }
 
 impl Codec for i64 {
-    fn deserialize(&mut self, _input: &mut impl io::Read) -> io::Result<usize> {
-        todo!();
+    fn deserialize(&mut self, input: &mut impl io::Read) -> io::Result<usize> {
+        let bytes = decode_stop_bit_entity(input)?;
+        let is_negative = (bytes[0] & NEGATIVE_SIGN_MASK) != 0;
+        *self = -(is_negative as i64);
+        for byte in &bytes {
+            *self = (*self << 7) | i64::from(*byte);
+        }
+        Ok(bytes.len())
     }
 
-    fn serialize(&self, _output: &mut impl io::Write) -> io::Result<usize> {
-        todo!();
+    fn serialize(&self, output: &mut impl io::Write) -> io::Result<usize> {
+        let mut bytes = Vec::with_capacity(10);
+        write_stop_bit_int(&mut bytes, i128::from(*self));
+        output.write_all(&bytes[..])?;
+        Ok(bytes.len())
     }
 }
 
@@ -174,7 +191,9 @@ fn _serialize_bitvec(bits: &BitSlice<Msb0, u8>, output: &mut impl io::Write) ->
     Ok(i)
 }
 
-#[derive(Debug, Clone)]
+/// A FAST *presence map*, i.e. a stop bit encoded sequence of bits which
+/// signal the presence of fields in the stream.
+#[derive(Debug, Clone, Default)]
 pub struct PresenceMap {
     bits: BitVec,
 }
@@ -183,43 +202,92 @@ impl PresenceMap {
     pub fn bits(&self) -> impl Iterator<Item = &bool> {
         self.bits.iter()
     }
+
+    /// Returns the `i`-th bit of `self`. Bits past the end of the presence map
+    /// are implicitly unset.
+    pub fn get(&self, i: usize) -> bool {
+        self.bits.get(i).copied().unwrap_or(false)
+    }
+
+    /// Appends `bit` to the end of `self`.
+    pub fn push(&mut self, bit: bool) {
+        self.bits.push(bit);
+    }
 }
 
 impl Codec for PresenceMap {
-    fn serialize(&self, _output: &mut impl io::Write) -> io::Result<usize> {
-        todo!();
+    /// Serializes `self` without trailing unset bits, which are implicit. An
+    /// empty presence map still takes one byte.
+    fn serialize(&self, output: &mut impl io::Write) -> io::Result<usize> {
+        let mut bytes: Vec<u8> = self
+            .bits
+            .chunks(7)
+            .map(|chunk| {
+                chunk
+                    .iter()
+                    .enumerate()
+                    .fold(0u8, |byte, (i, bit)| byte | ((*bit as u8) << (6 - i)))
+            })
+            .collect();
+        while bytes.len() > 1 && bytes.last() == Some(&0) {
+            bytes.pop();
+        }
+        match bytes.last_mut() {
+            Some(last) => *last |= STOP_BYTE,
+            None => bytes.push(STOP_BYTE),
+        }
+        output.write_all(&bytes[..])?;
+        Ok(bytes.len())
     }
 
     fn deserialize(&mut self, input: &mut impl io::Read) -> io::Result<usize> {
-        self.bits = BitVec::new();
-        let mut stop_bit = false;
-        while !stop_bit {
-            let mut buffer = [0u8; 1];
-            input.read_exact(&mut buffer[..])?;
-            let byte = buffer[0];
-            stop_bit = byte >= STOP_BYTE;
-            if !stop_bit {
-                self.bits.push(byte >> 7 == 1);
-            }
-            self.bits.push((byte >> 6) & 1 == 1);
-            self.bits.push((byte >> 5) & 1 == 1);
-            self.bits.push((byte >> 4) & 1 == 1);
-            self.bits.push((byte >> 4) & 1 == 1);
-            self.bits.push((byte >> 3) & 1 == 1);
-            self.bits.push((byte >> 2) & 1 == 1);
-            self.bits.push((byte >> 1) & 1 == 1);
-            self.bits.push((byte >> 0) & 1 == 1);
-        }
+        self.bits = decode_stop_bit_bitvec(input)?;
         Ok(self.bits.len())
     }
 }
 
-//pub fn encode_stop_bit_entity(target: &mut impl io::Write, buffer: &[u8]) -> io::Result<usize> {
-//    let bits = BitVec::from(buffer);
-//    for bit in bits {
-//        target:w
-//    }
-//}
+/// Writes `bytes`, which must only use the 7 least significant bits each, with
+/// the stop bit set on the last one.
+pub(crate) fn write_stop_bit_entity(output: &mut Vec<u8>, mut bytes: Vec<u8>) {
+    if let Some(last) = bytes.last_mut() {
+        *last |= STOP_BYTE;
+    }
+    output.extend_from_slice(&bytes[..]);
+}
+
+/// Writes the non-negative integer `int` with as few 7-bit groups as possible.
+pub(crate) fn write_stop_bit_uint(output: &mut Vec<u8>, mut int: i128) {
+    debug_assert!(int >= 0);
+    let mut groups = Vec::new();
+    loop {
+        groups.push((int & 0x7f) as u8);
+        int >>= 7;
+        if int == 0 {
+            break;
+        }
+    }
+    groups.reverse();
+    write_stop_bit_entity(output, groups);
+}
+
+/// Writes the two's complement integer `int` with as few 7-bit groups as
+/// possible.
+pub(crate) fn write_stop_bit_int(output: &mut Vec<u8>, mut int: i128) {
+    // The sign bit of the most significant group must agree with the sign of
+    // the integer.
+    let mut groups = Vec::new();
+    loop {
+        let group = (int & 0x7f) as u8;
+        groups.push(group);
+        int >>= 7;
+        let sign_bit = group & NEGATIVE_SIGN_MASK != 0;
+        if (int == 0 && !sign_bit) || (int == -1 && sign_bit) {
+            break;
+        }
+    }
+    groups.reverse();
+    write_stop_bit_entity(output, groups);
+}
 
 pub fn decode_stop_bit_entity(input: &mut impl io::Read) -> io::Result<Vec<u8>> {
     let mut bytes = Vec::new();
@@ -237,7 +305,6 @@ pub fn decode_stop_bit_entity(input: &mut impl io::Read) -> io::Result<Vec<u8>>
     Ok(bytes)
 }
 
-#[allow(dead_code)]
 pub fn decode_stop_bit_bitvec(input: &mut impl io::Read) -> io::Result<BitVec> {
     let mut bits = BitVec::new();
     let mut stop_bit = false;
@@ -246,17 +313,10 @@ pub fn decode_stop_bit_bitvec(input: &mut impl io::Read) -> io::Result<BitVec> {
         input.read_exact(&mut buffer[..])?;
         let byte = buffer[0];
         stop_bit = byte >= STOP_BYTE;
-        if !stop_bit {
-            bits.push(byte >> 7 == 1);
+        // The most significant bit is the stop bit, the other seven are data.
+        for i in (0..7).rev() {
+            bits.push((byte >> i) & 1 == 1);
         }
-        bits.push((byte >> 6) & 1 == 1);
-        bits.push((byte >> 5) & 1 == 1);
-        bits.push((byte >> 4) & 1 == 1);
-        bits.push((byte >> 4) & 1 == 1);
-        bits.push((byte >> 3) & 1 == 1);
-        bits.push((byte >> 2) & 1 == 1);
-        bits.push((byte >> 1) & 1 == 1);
-        bits.push((byte >> 0) & 1 == 1);
     }
     Ok(bits)
 }
@@ -324,4 +384,94 @@ mod test {
         value.deserialize(&mut &bytes[..]).unwrap();
         *value == expected_value
     }
+
+    #[test]
+    fn decode_i64_fast_doc_example() {
+        let bytes: Vec<u8> = vec![0x7c, 0x1b, 0x1b, 0x9d];
+        let mut value = 0i64;
+        value.deserialize(&mut &bytes[..]).unwrap();
+        assert_eq!(value, -794_2755);
+    }
+
+    #[quickcheck]
+    fn encode_then_decode_u64(expected_value: u64) -> bool {
+        let mut bytes: Vec<u8> = Vec::new();
+        let len = expected_value.serialize(&mut bytes).unwrap();
+        let mut value = 0u64;
+        value.deserialize(&mut &bytes[..]).unwrap();
+        len == bytes.len() && value == expected_value
+    }
+
+    #[quickcheck]
+    fn encode_then_decode_i64(expected_value: i64) -> bool {
+        let mut bytes: Vec<u8> = Vec::new();
+        let len = expected_value.serialize(&mut bytes).unwrap();
+        let mut value = 0i64;
+        value.deserialize(&mut &bytes[..]).unwrap();
+        len == bytes.len() && value == expected_value
+    }
+
+    #[test]
+    fn encode_i64_examples() {
+        let mut bytes: Vec<u8> = Vec::new();
+        (-794_2755_i64).serialize(&mut bytes).unwrap();
+        assert_eq!(bytes, vec![0x7c, 0x1b, 0x1b, 0x9d]);
+        for (value, expected) in [
+            (63i64, vec![0xbf]),
+            (64, vec![0x00, 0xc0]),
+            (-64, vec![0xc0]),
+        ] {
+            let mut bytes: Vec<u8> = Vec::new();
+            value.serialize(&mut bytes).unwrap();
+            assert_eq!(bytes, expected);
+        }
+        for value in [i64::MIN, i64::MAX] {
+            let mut bytes: Vec<u8> = Vec::new();
+            value.serialize(&mut bytes).unwrap();
+            let mut decoded = 0i64;
+            decoded.deserialize(&mut &bytes[..]).unwrap();
+            assert_eq!(decoded, value);
+        }
+    }
+
+    #[test]
+    fn encode_u64_bigger_than_u32() {
+        let mut bytes: Vec<u8> = Vec::new();
+        (1u64 << 35).serialize(&mut bytes).unwrap();
+        assert_eq!(bytes, vec![0x01, 0x00, 0x00, 0x00, 0x00, 0x80]);
+    }
+
+    #[test]
+    fn decode_u64_bigger_than_u32() {
+        let bytes: Vec<u8> = vec![0x01, 0x00, 0x00, 0x00, 0x00, 0x80];
+        let mut value = 0u64;
+        value.deserialize(&mut &bytes[..]).unwrap();
+        assert_eq!(value, 1 << 35);
+    }
+
+    #[test]
+    fn encode_presence_map_without_trailing_unset_bits() {
+        let mut pmap = PresenceMap::default();
+        for i in 0..20 {
+            pmap.push(i == 0 || i == 13);
+        }
+        let mut bytes: Vec<u8> = Vec::new();
+        pmap.serialize(&mut bytes).unwrap();
+        assert_eq!(bytes, vec![0b0100_0000, 0b1000_0001]);
+        let mut bytes: Vec<u8> = Vec::new();
+        PresenceMap::default().serialize(&mut bytes).unwrap();
+        assert_eq!(bytes, vec![0x80]);
+    }
+
+    #[test]
+    fn decode_presence_map() {
+        let bytes: Vec<u8> = vec![0b0100_0000, 0b1000_0001];
+        let mut pmap = PresenceMap::default();
+        pmap.deserialize(&mut &bytes[..]).unwrap();
+        assert_eq!(pmap.bits().count(), 14);
+        assert!(pmap.get(0));
+        assert!(!pmap.get(1));
+        assert!(pmap.get(13));
+        assert!(!pmap.get(100));
+    }
 }
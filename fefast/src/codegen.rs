@@ -19,7 +19,9 @@ pub fn template_struct(template: &Template, custom_derive_line: &str) -> String
                 primitive_fast_type_to_rust_type(*pt),
                 !field_instruction.is_mandatory(),
             ),
-            FieldType::Group(_group) => String::new(),
+            FieldType::Sequence(_) | FieldType::Group(_) | FieldType::TemplateRef(_) => {
+                String::new()
+            }
         })
         .collect::<Vec<String>>();
     format!(
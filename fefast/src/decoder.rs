@@ -0,0 +1,934 @@
+use super::codec::decode_stop_bit_entity;
+use super::errors::{DynamicError, Error, ReportableError};
+use super::field_operators::FieldOperatorInstruction;
+use super::template::{int_fits, RawValue};
+use super::{
+    Codec, Dictionary, FieldInstruction, FieldType, PresenceMap, PrimitiveType, ScaledNumber,
+    Template, TemplateSet,
+};
+use std::collections::HashMap;
+use std::io;
+
+/// The value of a decoded FAST field.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    I32(i32),
+    U32(u32),
+    I64(i64),
+    U64(u64),
+    /// A decimal, with the exact exponent and mantissa of the stream.
+    Decimal(ScaledNumber),
+    AsciiString(String),
+    Utf8String(String),
+    Bytes(Vec<u8>),
+    /// All entries of a sequence, in order.
+    Sequence(Vec<Vec<Field>>),
+    /// The fields of a group or of a dynamically referenced template.
+    Group(Vec<Field>),
+}
+
+/// A FAST field, i.e. a field instruction together with its value.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Field {
+    name: String,
+    id: Option<u32>,
+    value: Option<Value>,
+}
+
+impl Field {
+    /// Creates a new [`Field`] named `name`, e.g. to be encoded by an
+    /// [`Encoder`](crate::Encoder). `None` stands for an absent field.
+    pub fn new(name: impl Into<String>, value: Option<Value>) -> Self {
+        Self {
+            name: name.into(),
+            id: None,
+            value,
+        }
+    }
+
+    /// Returns the name of the field instruction of `self`.
+    pub fn name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    /// Returns the field ID of `self`, if any. For sequences, this is the ID
+    /// of the length field.
+    pub fn id(&self) -> Option<u32> {
+        self.id
+    }
+
+    /// Returns the value of `self`, or [`None`] if the field is absent.
+    pub fn value(&self) -> Option<&Value> {
+        self.value.as_ref()
+    }
+}
+
+/// A message decoded by a [`Decoder`] or to be encoded by an
+/// [`Encoder`](crate::Encoder).
+#[derive(Clone, Debug, PartialEq)]
+pub struct Message {
+    template_id: u32,
+    fields: Vec<Field>,
+}
+
+impl Message {
+    /// Creates a new [`Message`] with the given template ID and top-level
+    /// fields. Field order doesn't matter: an [`Encoder`](crate::Encoder)
+    /// looks fields up by name.
+    pub fn new(template_id: u32, fields: Vec<Field>) -> Self {
+        Self {
+            template_id,
+            fields,
+        }
+    }
+
+    /// Returns the ID of the template that was used to decode `self`.
+    pub fn template_id(&self) -> u32 {
+        self.template_id
+    }
+
+    /// Returns all top-level fields of `self`, in template order. Absent
+    /// fields are included.
+    pub fn fields(&self) -> &[Field] {
+        &self.fields[..]
+    }
+
+    /// Returns the value of the top-level field named `name`, if present.
+    pub fn value(&self, name: &str) -> Option<&Value> {
+        self.fields
+            .iter()
+            .find(|field| field.name == name)
+            .and_then(|field| field.value())
+    }
+}
+
+/// All templates known by a [`Decoder`] or an [`Encoder`](crate::Encoder),
+/// indexed by ID and by name.
+#[derive(Debug, Default)]
+pub(crate) struct Templates {
+    templates: Vec<Template>,
+    by_id: HashMap<u32, usize>,
+    by_name: HashMap<String, usize>,
+}
+
+impl Templates {
+    pub(crate) fn add(&mut self, template: Template) {
+        let i = self.templates.len();
+        if let Some(id) = template.id() {
+            self.by_id.insert(id, i);
+        }
+        self.by_name.insert(template.name().to_string(), i);
+        self.templates.push(template);
+    }
+
+    pub(crate) fn by_id(&self, id: u32) -> Option<&Template> {
+        self.by_id.get(&id).map(|i| &self.templates[*i])
+    }
+
+    pub(crate) fn by_name(&self, name: &str) -> Option<&Template> {
+        self.by_name.get(name).map(|i| &self.templates[*i])
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub(crate) enum DictionaryKey {
+    Global,
+    Template(u32),
+    Type(String),
+    Custom(String),
+}
+
+/// Returns the key under which the operator of `instruction` stores its
+/// previous value, within the template `template_id` of type `type_ref`.
+pub(crate) fn dictionary_key(
+    instruction: &FieldInstruction,
+    template_id: u32,
+    type_ref: Option<&str>,
+) -> (DictionaryKey, String) {
+    let dictionary = match instruction.dictionary() {
+        Dictionary::Global => DictionaryKey::Global,
+        Dictionary::Template => DictionaryKey::Template(template_id),
+        Dictionary::Type => DictionaryKey::Type(type_ref.unwrap_or_default().to_string()),
+        Dictionary::Custom(name) => DictionaryKey::Custom(name.clone()),
+    };
+    (dictionary, instruction.key().to_string())
+}
+
+/// The operator dictionaries of a FAST stream, shared by decoders and
+/// encoders.
+#[derive(Debug, Default)]
+pub(crate) struct OperatorState {
+    // A missing entry means that the previous value is *undefined*, a `None`
+    // entry that it's *empty*.
+    pub(crate) previous_values: HashMap<(DictionaryKey, String), Option<RawValue>>,
+    pub(crate) template_id: Option<u32>,
+}
+
+/// A template-driven FAST 1.1 decoder.
+///
+/// [`Decoder`] keeps the operator dictionaries across messages, so you should
+/// use a single [`Decoder`] per FAST stream and call [`Decoder::reset`]
+/// whenever the stream requires it (e.g. on FAST reset messages or at the start
+/// of every packet, depending on the venue).
+///
+/// # Examples
+///
+/// ```
+/// use fefast::{Decoder, Template, Value};
+///
+/// let template = Template::new(
+///     r#"<templates><template name="Trade" id="1">
+///         <uInt32 name="MsgSeqNum" id="34"><increment/></uInt32>
+///         <string name="Symbol" id="55"><copy/></string>
+///     </template></templates>"#,
+/// )
+/// .unwrap();
+/// let mut decoder = Decoder::new();
+/// decoder.add_template(template);
+/// let data: &[u8] = &[0xf0, 0x81, 0x81, 0x41, 0xc2, 0x80];
+/// let input = &mut &data[..];
+/// let first = decoder.decode(input).unwrap();
+/// assert_eq!(first.value("Symbol"), Some(&Value::AsciiString("AB".to_string())));
+/// let second = decoder.decode(input).unwrap();
+/// assert_eq!(second.value("MsgSeqNum"), Some(&Value::U32(2)));
+/// assert_eq!(second.value("Symbol"), Some(&Value::AsciiString("AB".to_string())));
+/// ```
+#[derive(Debug, Default)]
+pub struct Decoder {
+    templates: Templates,
+    state: OperatorState,
+}
+
+impl Decoder {
+    /// Creates a new [`Decoder`] without any templates.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a new [`Decoder`] which knows all templates in `templates`.
+    pub fn with_templates(templates: TemplateSet) -> Self {
+        let mut decoder = Self::new();
+        for template in templates {
+            decoder.add_template(template);
+        }
+        decoder
+    }
+
+    /// Adds `template` to the known templates of `self`. Templates without an
+    /// ID can only be used through static template references.
+    pub fn add_template(&mut self, template: Template) {
+        self.templates.add(template);
+    }
+
+    /// Resets all operator dictionaries to their initial state, i.e. all
+    /// previous values become undefined.
+    pub fn reset(&mut self) {
+        self.state.previous_values.clear();
+        self.state.template_id = None;
+    }
+
+    /// Decodes a single message from `input`.
+    pub fn decode(&mut self, input: &mut impl io::Read) -> Result<Message, Error> {
+        let mut context = DecodeContext {
+            templates: &self.templates,
+            state: &mut self.state,
+            template_id: 0,
+            type_ref: None,
+        };
+        context.decode_message(input)
+    }
+}
+
+/// A cursor over the bits of a [`PresenceMap`].
+#[derive(Debug, Default)]
+struct PresenceMapCursor {
+    pmap: PresenceMap,
+    i: usize,
+}
+
+impl PresenceMapCursor {
+    fn read(input: &mut impl io::Read) -> Result<Self, Error> {
+        let mut pmap = PresenceMap::default();
+        pmap.deserialize(input)?;
+        Ok(Self { pmap, i: 0 })
+    }
+
+    fn next_bit(&mut self) -> bool {
+        let bit = self.pmap.get(self.i);
+        self.i += 1;
+        bit
+    }
+}
+
+#[derive(Debug)]
+struct DecodeContext<'a> {
+    templates: &'a Templates,
+    state: &'a mut OperatorState,
+    template_id: u32,
+    type_ref: Option<&'a str>,
+}
+
+impl<'a> DecodeContext<'a> {
+    fn decode_message(&mut self, input: &mut impl io::Read) -> Result<Message, Error> {
+        let mut pmap = PresenceMapCursor::read(input)?;
+        // The template ID is implicitly encoded with a copy operator.
+        let template_id = if pmap.next_bit() {
+            let id = read_uint(input, false)?.unwrap_or_default();
+            if !int_fits(id, PrimitiveType::U32) {
+                return Err(DynamicError::D2.into());
+            }
+            self.state.template_id = Some(id as u32);
+            id as u32
+        } else {
+            self.state.template_id.ok_or(DynamicError::D5)?
+        };
+        let templates = self.templates;
+        let template = templates.by_id(template_id).ok_or(DynamicError::D9)?;
+        let outer = (self.template_id, self.type_ref);
+        self.template_id = template_id;
+        self.type_ref = template.type_ref();
+        let fields = self.decode_instructions(template.iter_items(), &mut pmap, input);
+        self.template_id = outer.0;
+        self.type_ref = outer.1;
+        Ok(Message {
+            template_id,
+            fields: fields?,
+        })
+    }
+
+    fn decode_instructions<'b>(
+        &mut self,
+        instructions: impl Iterator<Item = &'b FieldInstruction>,
+        pmap: &mut PresenceMapCursor,
+        input: &mut impl io::Read,
+    ) -> Result<Vec<Field>, Error> {
+        let mut fields = Vec::new();
+        for instruction in instructions {
+            let value = match instruction.kind() {
+                FieldType::Primitive(primitive_type) => self
+                    .decode_primitive(instruction, *primitive_type, pmap, input)?
+                    .map(|raw| to_value(raw, *primitive_type))
+                    .transpose()?,
+                FieldType::Sequence(sequence) => {
+                    let len =
+                        self.decode_primitive(sequence.length(), PrimitiveType::U32, pmap, input)?;
+                    match len {
+                        Some(RawValue::Int(len)) => {
+                            let needs_pmap =
+                                sequence.iter_items().any(|i| i.needs_presence_map_bit());
+                            let mut entries = Vec::with_capacity(len as usize);
+                            for _ in 0..len {
+                                let mut entry_pmap = if needs_pmap {
+                                    PresenceMapCursor::read(input)?
+                                } else {
+                                    PresenceMapCursor::default()
+                                };
+                                entries.push(self.decode_instructions(
+                                    sequence.iter_items(),
+                                    &mut entry_pmap,
+                                    input,
+                                )?);
+                            }
+                            Some(Value::Sequence(entries))
+                        }
+                        _ => None,
+                    }
+                }
+                FieldType::Group(instructions) => {
+                    if !instruction.is_mandatory() && !pmap.next_bit() {
+                        None
+                    } else {
+                        let needs_pmap = instructions.iter().any(|i| i.needs_presence_map_bit());
+                        let mut group_pmap = if needs_pmap {
+                            PresenceMapCursor::read(input)?
+                        } else {
+                            PresenceMapCursor::default()
+                        };
+                        Some(Value::Group(self.decode_instructions(
+                            instructions.iter(),
+                            &mut group_pmap,
+                            input,
+                        )?))
+                    }
+                }
+                FieldType::TemplateRef(Some(name)) => {
+                    // Static template references are equivalent to inlining
+                    // all instructions of the referenced template.
+                    let templates = self.templates;
+                    let template = templates.by_name(name).ok_or(DynamicError::D8)?;
+                    let inlined = self.decode_instructions(template.iter_items(), pmap, input)?;
+                    fields.extend(inlined);
+                    continue;
+                }
+                FieldType::TemplateRef(None) => {
+                    let message = self.decode_message(input)?;
+                    let name = self.templates.by_id(message.template_id);
+                    fields.push(Field {
+                        name: name.map(|t| t.name().to_string()).unwrap_or_default(),
+                        id: None,
+                        value: Some(Value::Group(message.fields)),
+                    });
+                    continue;
+                }
+            };
+            fields.push(Field {
+                name: instruction.name().to_string(),
+                id: match instruction.kind() {
+                    FieldType::Sequence(sequence) => instruction.id().or(sequence.length().id()),
+                    _ => instruction.id(),
+                },
+                value,
+            });
+        }
+        Ok(fields)
+    }
+
+    fn dictionary_key(&self, instruction: &FieldInstruction) -> (DictionaryKey, String) {
+        dictionary_key(instruction, self.template_id, self.type_ref)
+    }
+
+    fn previous_value(&self, instruction: &FieldInstruction) -> Option<Option<RawValue>> {
+        self.state
+            .previous_values
+            .get(&self.dictionary_key(instruction))
+            .cloned()
+    }
+
+    fn set_previous_value(&mut self, instruction: &FieldInstruction, value: Option<RawValue>) {
+        let key = self.dictionary_key(instruction);
+        self.state.previous_values.insert(key, value);
+    }
+
+    /// Decodes a primitive field according to its field operator. See section
+    /// 6.3 of the FAST 1.1 specification.
+    fn decode_primitive(
+        &mut self,
+        instruction: &FieldInstruction,
+        primitive_type: PrimitiveType,
+        pmap: &mut PresenceMapCursor,
+        input: &mut impl io::Read,
+    ) -> Result<Option<RawValue>, Error> {
+        let nullable = instruction.is_nullable();
+        let mandatory = instruction.is_mandatory();
+        let initial_value = instruction.initial_value().cloned();
+        match instruction.operator() {
+            FieldOperatorInstruction::None => read_raw(input, primitive_type, nullable),
+            FieldOperatorInstruction::Constant => {
+                if !mandatory && !pmap.next_bit() {
+                    Ok(None)
+                } else {
+                    Ok(initial_value)
+                }
+            }
+            FieldOperatorInstruction::Default => {
+                if pmap.next_bit() {
+                    read_raw(input, primitive_type, nullable)
+                } else {
+                    Ok(initial_value)
+                }
+            }
+            FieldOperatorInstruction::Copy | FieldOperatorInstruction::Increment => {
+                if pmap.next_bit() {
+                    let value = read_raw(input, primitive_type, nullable)?;
+                    self.set_previous_value(instruction, value.clone());
+                    return Ok(value);
+                }
+                match self.previous_value(instruction) {
+                    Some(Some(previous)) => {
+                        if instruction.operator() == &FieldOperatorInstruction::Increment {
+                            let value = match previous {
+                                RawValue::Int(int) if int_fits(int + 1, primitive_type) => {
+                                    RawValue::Int(int + 1)
+                                }
+                                _ => return Err(DynamicError::D2.into()),
+                            };
+                            self.set_previous_value(instruction, Some(value.clone()));
+                            Ok(Some(value))
+                        } else {
+                            Ok(Some(previous))
+                        }
+                    }
+                    None => match initial_value {
+                        Some(value) => {
+                            self.set_previous_value(instruction, Some(value.clone()));
+                            Ok(Some(value))
+                        }
+                        None if mandatory => Err(DynamicError::D5.into()),
+                        None => {
+                            self.set_previous_value(instruction, None);
+                            Ok(None)
+                        }
+                    },
+                    Some(None) if mandatory => Err(DynamicError::D6.into()),
+                    Some(None) => Ok(None),
+                }
+            }
+            FieldOperatorInstruction::Tail => {
+                if pmap.next_bit() {
+                    let tail = match read_raw(input, primitive_type, nullable)? {
+                        Some(RawValue::Bytes(tail)) => tail,
+                        _ => {
+                            self.set_previous_value(instruction, None);
+                            return Ok(None);
+                        }
+                    };
+                    let mut base = match self.previous_value(instruction) {
+                        Some(Some(RawValue::Bytes(previous))) => previous,
+                        None => match initial_value {
+                            Some(RawValue::Bytes(initial)) => initial,
+                            _ => Vec::new(),
+                        },
+                        _ => Vec::new(),
+                    };
+                    let value = if tail.len() >= base.len() {
+                        tail
+                    } else {
+                        base.truncate(base.len() - tail.len());
+                        base.extend_from_slice(&tail[..]);
+                        base
+                    };
+                    self.set_previous_value(instruction, Some(RawValue::Bytes(value.clone())));
+                    return Ok(Some(RawValue::Bytes(value)));
+                }
+                match self.previous_value(instruction) {
+                    Some(Some(previous)) => Ok(Some(previous)),
+                    None => match initial_value {
+                        Some(value) => {
+                            self.set_previous_value(instruction, Some(value.clone()));
+                            Ok(Some(value))
+                        }
+                        None if mandatory => Err(DynamicError::D6.into()),
+                        None => Ok(None),
+                    },
+                    Some(None) if mandatory => Err(DynamicError::D7.into()),
+                    Some(None) => Ok(None),
+                }
+            }
+            FieldOperatorInstruction::Delta => {
+                let base = match self.previous_value(instruction) {
+                    Some(Some(previous)) => Some(previous),
+                    Some(None) => None,
+                    None => Some(initial_value.unwrap_or_else(|| default_base(primitive_type))),
+                };
+                let value = match primitive_type {
+                    PrimitiveType::Decimal => {
+                        let exp_delta = match read_int(input, nullable)? {
+                            Some(delta) => delta,
+                            None => return Ok(None),
+                        };
+                        let mantissa_delta = read_int(input, false)?.unwrap_or_default();
+                        let (exp, mantissa) = match base.ok_or(DynamicError::D6)? {
+                            RawValue::Decimal { exp, mantissa } => (exp, mantissa),
+                            _ => return Err(DynamicError::D4.into()),
+                        };
+                        let exp = exp as i128 + exp_delta;
+                        let mantissa = mantissa as i128 + mantissa_delta;
+                        if !(-63..=63).contains(&exp) {
+                            return Err(ReportableError::R1.into());
+                        }
+                        if !int_fits(mantissa, PrimitiveType::I64) {
+                            return Err(ReportableError::R1.into());
+                        }
+                        RawValue::Decimal {
+                            exp: exp as i32,
+                            mantissa: mantissa as i64,
+                        }
+                    }
+                    PrimitiveType::AsciiString
+                    | PrimitiveType::Utf8String
+                    | PrimitiveType::Bytes => {
+                        let subtraction_len = match read_int(input, nullable)? {
+                            Some(len) => len,
+                            None => return Ok(None),
+                        };
+                        let diff = match read_raw(input, primitive_type, false)? {
+                            Some(RawValue::Bytes(diff)) => diff,
+                            _ => Vec::new(),
+                        };
+                        let mut base = match base.ok_or(DynamicError::D6)? {
+                            RawValue::Bytes(base) => base,
+                            _ => return Err(DynamicError::D4.into()),
+                        };
+                        // Negative subtraction lengths are encoded in excess
+                        // of one and remove characters from the front.
+                        let (removed, from_front) = if subtraction_len < 0 {
+                            (-(subtraction_len + 1), true)
+                        } else {
+                            (subtraction_len, false)
+                        };
+                        if removed as usize > base.len() {
+                            return Err(DynamicError::D7.into());
+                        }
+                        if from_front {
+                            let mut value = diff;
+                            value.extend_from_slice(&base[removed as usize..]);
+                            RawValue::Bytes(value)
+                        } else {
+                            base.truncate(base.len() - removed as usize);
+                            base.extend_from_slice(&diff[..]);
+                            RawValue::Bytes(base)
+                        }
+                    }
+                    _ => {
+                        let delta = match read_int(input, nullable)? {
+                            Some(delta) => delta,
+                            None => return Ok(None),
+                        };
+                        let value = match base.ok_or(DynamicError::D6)? {
+                            RawValue::Int(base) => base + delta,
+                            _ => return Err(DynamicError::D4.into()),
+                        };
+                        if !int_fits(value, primitive_type) {
+                            return Err(DynamicError::D2.into());
+                        }
+                        RawValue::Int(value)
+                    }
+                };
+                self.set_previous_value(instruction, Some(value.clone()));
+                Ok(Some(value))
+            }
+        }
+    }
+}
+
+/// The base value which delta operators use when the previous value is
+/// undefined and there's no initial value.
+pub(crate) fn default_base(primitive_type: PrimitiveType) -> RawValue {
+    match primitive_type {
+        PrimitiveType::Decimal => RawValue::Decimal {
+            exp: 0,
+            mantissa: 0,
+        },
+        PrimitiveType::AsciiString | PrimitiveType::Utf8String | PrimitiveType::Bytes => {
+            RawValue::Bytes(Vec::new())
+        }
+        _ => RawValue::Int(0),
+    }
+}
+
+fn to_value(raw: RawValue, primitive_type: PrimitiveType) -> Result<Value, Error> {
+    Ok(match (raw, primitive_type) {
+        (RawValue::Int(int), PrimitiveType::I32) => Value::I32(int as i32),
+        (RawValue::Int(int), PrimitiveType::U32) => Value::U32(int as u32),
+        (RawValue::Int(int), PrimitiveType::I64) => Value::I64(int as i64),
+        (RawValue::Int(int), PrimitiveType::U64) => Value::U64(int as u64),
+        (RawValue::Decimal { exp, mantissa }, PrimitiveType::Decimal) => {
+            Value::Decimal(ScaledNumber::new(mantissa, exp))
+        }
+        (RawValue::Bytes(bytes), PrimitiveType::AsciiString) => {
+            if !bytes.is_ascii() {
+                return Err(ReportableError::R3.into());
+            }
+            Value::AsciiString(String::from_utf8(bytes).map_err(|_| ReportableError::R3)?)
+        }
+        (RawValue::Bytes(bytes), PrimitiveType::Utf8String) => {
+            Value::Utf8String(String::from_utf8(bytes).map_err(|_| ReportableError::R2)?)
+        }
+        (RawValue::Bytes(bytes), PrimitiveType::Bytes) => Value::Bytes(bytes),
+        _ => return Err(DynamicError::D4.into()),
+    })
+}
+
+/// Reads a value of type `primitive_type` from `input`, without any field
+/// operator. `None` stands for NULL.
+fn read_raw(
+    input: &mut impl io::Read,
+    primitive_type: PrimitiveType,
+    nullable: bool,
+) -> Result<Option<RawValue>, Error> {
+    let int = match primitive_type {
+        PrimitiveType::U32 | PrimitiveType::U64 => read_uint(input, nullable)?,
+        PrimitiveType::I32 | PrimitiveType::I64 => read_int(input, nullable)?,
+        PrimitiveType::Decimal => {
+            let exp = match read_int(input, nullable)? {
+                Some(exp) => exp,
+                None => return Ok(None),
+            };
+            let mantissa = read_int(input, false)?.unwrap_or_default();
+            if !(-63..=63).contains(&exp) || !int_fits(mantissa, PrimitiveType::I64) {
+                return Err(ReportableError::R1.into());
+            }
+            return Ok(Some(RawValue::Decimal {
+                exp: exp as i32,
+                mantissa: mantissa as i64,
+            }));
+        }
+        PrimitiveType::AsciiString => return Ok(read_ascii(input, nullable)?.map(RawValue::Bytes)),
+        PrimitiveType::Utf8String | PrimitiveType::Bytes => {
+            return Ok(read_byte_vector(input, nullable)?.map(RawValue::Bytes));
+        }
+    };
+    match int {
+        Some(int) if !int_fits(int, primitive_type) => Err(DynamicError::D2.into()),
+        int => Ok(int.map(RawValue::Int)),
+    }
+}
+
+fn read_stop_bit_entity(input: &mut impl io::Read) -> Result<Vec<u8>, Error> {
+    let bytes = decode_stop_bit_entity(input)?;
+    // 10 bytes are enough for all 64-bit integers, plus the NULL offset.
+    if bytes.len() > 10 {
+        Err(DynamicError::D2.into())
+    } else {
+        Ok(bytes)
+    }
+}
+
+fn read_uint(input: &mut impl io::Read, nullable: bool) -> Result<Option<i128>, Error> {
+    let mut int = 0i128;
+    for byte in read_stop_bit_entity(input)? {
+        int = (int << 7) | i128::from(byte);
+    }
+    Ok(match (nullable, int) {
+        (true, 0) => None,
+        (true, int) => Some(int - 1),
+        (false, int) => Some(int),
+    })
+}
+
+fn read_int(input: &mut impl io::Read, nullable: bool) -> Result<Option<i128>, Error> {
+    let bytes = read_stop_bit_entity(input)?;
+    let mut int = -i128::from(bytes[0] & 0x40 != 0);
+    for byte in bytes {
+        int = (int << 7) | i128::from(byte);
+    }
+    Ok(match (nullable, int) {
+        (true, 0) => None,
+        (true, int) if int > 0 => Some(int - 1),
+        (_, int) => Some(int),
+    })
+}
+
+fn read_ascii(input: &mut impl io::Read, nullable: bool) -> Result<Option<Vec<u8>>, Error> {
+    fn mandatory(bytes: &[u8]) -> Vec<u8> {
+        match bytes {
+            [0] => Vec::new(),
+            [0, rest @ ..] => rest.to_vec(),
+            bytes => bytes.to_vec(),
+        }
+    }
+    let bytes = decode_stop_bit_entity(input)?;
+    Ok(match (nullable, &bytes[..]) {
+        (true, [0]) => None,
+        (true, [0, rest @ ..]) => Some(mandatory(rest)),
+        (_, bytes) => Some(mandatory(bytes)),
+    })
+}
+
+fn read_byte_vector(input: &mut impl io::Read, nullable: bool) -> Result<Option<Vec<u8>>, Error> {
+    let len = match read_uint(input, nullable)? {
+        Some(len) => len,
+        None => return Ok(None),
+    };
+    if !int_fits(len, PrimitiveType::U32) {
+        return Err(DynamicError::D2.into());
+    }
+    let mut bytes = vec![0u8; len as usize];
+    input.read_exact(&mut bytes[..])?;
+    Ok(Some(bytes))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn decoder(spec: &str) -> Decoder {
+        let mut decoder = Decoder::new();
+        decoder.add_template(Template::new(spec).unwrap());
+        decoder
+    }
+
+    fn ascii(s: &str) -> Value {
+        Value::AsciiString(s.to_string())
+    }
+
+    const INTEGERS: &str = r#"<templates><template name="T" id="1">
+        <uInt32 name="A" id="1"><copy/></uInt32>
+        <uInt32 name="B" id="2"><increment/></uInt32>
+        <string name="C" id="3"><constant value="X"/></string>
+        <int64 name="D" id="4"><delta/></int64>
+        <uInt32 name="E" id="5" presence="optional"/>
+    </template></templates>"#;
+
+    #[test]
+    fn integer_operators_keep_state_across_messages() {
+        let mut decoder = decoder(INTEGERS);
+        let data: &[u8] = &[0xf0, 0x81, 0x85, 0x8a, 0xfd, 0x88, 0x80, 0x84, 0x80];
+        let input = &mut &data[..];
+        let first = decoder.decode(input).unwrap();
+        assert_eq!(first.template_id(), 1);
+        assert_eq!(first.value("A"), Some(&Value::U32(5)));
+        assert_eq!(first.value("B"), Some(&Value::U32(10)));
+        assert_eq!(first.value("C"), Some(&ascii("X")));
+        assert_eq!(first.value("D"), Some(&Value::I64(-3)));
+        assert_eq!(first.value("E"), Some(&Value::U32(7)));
+        let second = decoder.decode(input).unwrap();
+        assert_eq!(second.template_id(), 1);
+        assert_eq!(second.value("A"), Some(&Value::U32(5)));
+        assert_eq!(second.value("B"), Some(&Value::U32(11)));
+        assert_eq!(second.value("D"), Some(&Value::I64(1)));
+        assert_eq!(second.value("E"), None);
+        assert!(input.is_empty());
+    }
+
+    #[test]
+    fn strings_decimals_and_sequences() {
+        let mut decoder = decoder(
+            r#"<templates><template name="S" id="2">
+                <string name="Symbol" id="55"><tail/></string>
+                <string name="Text" id="58"><delta/></string>
+                <sequence name="Entries">
+                    <length name="NoEntries" id="268"/>
+                    <uInt32 name="Px" id="270"><copy/></uInt32>
+                    <decimal name="Qty" id="271"/>
+                </sequence>
+            </template></templates>"#,
+        );
+        let data: &[u8] = &[
+            0xe0, 0x82, 0x41, 0x42, 0xc3, 0x80, 0x78, 0xf9, 0x82, 0xc0, 0xe4, 0xff, 0x8f, 0x80,
+            0x80, 0x82, 0xa0, 0xc4, 0xff, 0xf7, 0x80,
+        ];
+        let input = &mut &data[..];
+        let first = decoder.decode(input).unwrap();
+        assert_eq!(first.value("Symbol"), Some(&ascii("ABC")));
+        assert_eq!(first.value("Text"), Some(&ascii("xy")));
+        let entries = match first.value("Entries") {
+            Some(Value::Sequence(entries)) => entries,
+            _ => panic!("Expected a sequence"),
+        };
+        assert_eq!(first.fields()[2].id(), Some(268));
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0][0].value(), Some(&Value::U32(100)));
+        assert_eq!(
+            entries[0][1].value(),
+            Some(&Value::Decimal(ScaledNumber::new(15, -1)))
+        );
+        assert_eq!(entries[1][0].value(), Some(&Value::U32(100)));
+        assert_eq!(
+            entries[1][1].value(),
+            Some(&Value::Decimal(ScaledNumber::new(2, 0)))
+        );
+        let second = decoder.decode(input).unwrap();
+        assert_eq!(second.value("Symbol"), Some(&ascii("ABD")));
+        assert_eq!(second.value("Text"), Some(&ascii("wxy")));
+        assert_eq!(second.value("Entries"), Some(&Value::Sequence(vec![])));
+        assert!(input.is_empty());
+    }
+
+    #[test]
+    fn optional_constant_and_default() {
+        let mut decoder = decoder(
+            r#"<templates><template name="O" id="3">
+                <uInt32 name="K" id="1" presence="optional"><constant value="7"/></uInt32>
+                <uInt32 name="L" id="2"><default value="4"/></uInt32>
+            </template></templates>"#,
+        );
+        let data: &[u8] = &[0xc0, 0x83, 0xb0, 0x89];
+        let input = &mut &data[..];
+        let first = decoder.decode(input).unwrap();
+        assert_eq!(first.value("K"), None);
+        assert_eq!(first.value("L"), Some(&Value::U32(4)));
+        let second = decoder.decode(input).unwrap();
+        assert_eq!(second.value("K"), Some(&Value::U32(7)));
+        assert_eq!(second.value("L"), Some(&Value::U32(9)));
+    }
+
+    #[test]
+    fn nullable_ascii_strings() {
+        let mut decoder = decoder(
+            r#"<templates><template name="N" id="4">
+                <string name="A" id="1" presence="optional"/>
+                <string name="B" id="2" presence="optional"/>
+                <string name="C" id="3"/>
+            </template></templates>"#,
+        );
+        let data: &[u8] = &[0xc0, 0x84, 0x80, 0x00, 0x80, 0x80];
+        let message = decoder.decode(&mut &data[..]).unwrap();
+        assert_eq!(message.value("A"), None);
+        assert_eq!(message.value("B"), Some(&ascii("")));
+        assert_eq!(message.value("C"), Some(&ascii("")));
+    }
+
+    #[test]
+    fn optional_decimals_have_nullable_exponents() {
+        let mut decoder = decoder(
+            r#"<templates><template name="D" id="8">
+                <decimal name="A" id="1" presence="optional"/>
+                <decimal name="B" id="2" presence="optional"/>
+                <decimal name="C" id="3" presence="optional"/>
+            </template></templates>"#,
+        );
+        let data: &[u8] = &[0xc0, 0x88, 0x80, 0xfe, 0x01, 0x96, 0x81, 0x85];
+        let message = decoder.decode(&mut &data[..]).unwrap();
+        assert_eq!(message.value("A"), None);
+        // Trailing zeros are not normalized away.
+        assert_eq!(
+            message.value("B"),
+            Some(&Value::Decimal(ScaledNumber::new(150, -2)))
+        );
+        assert_eq!(
+            message.value("C"),
+            Some(&Value::Decimal(ScaledNumber::new(5, 0)))
+        );
+    }
+
+    #[test]
+    fn static_template_reference() {
+        let templates = TemplateSet::from_xml(
+            r#"<templates>
+                <template name="Header">
+                    <uInt32 name="MsgSeqNum" id="34"><increment/></uInt32>
+                </template>
+                <template name="Trade" id="5">
+                    <templateRef name="Header"/>
+                    <uInt32 name="Qty" id="53"/>
+                </template>
+            </templates>"#,
+        )
+        .unwrap();
+        let mut decoder = Decoder::with_templates(templates);
+        let data: &[u8] = &[0xe0, 0x85, 0x8a, 0x83, 0x80, 0x84];
+        let input = &mut &data[..];
+        let first = decoder.decode(input).unwrap();
+        assert_eq!(first.template_id(), 5);
+        assert_eq!(first.value("MsgSeqNum"), Some(&Value::U32(10)));
+        assert_eq!(first.value("Qty"), Some(&Value::U32(3)));
+        let second = decoder.decode(input).unwrap();
+        assert_eq!(second.value("MsgSeqNum"), Some(&Value::U32(11)));
+        assert_eq!(second.value("Qty"), Some(&Value::U32(4)));
+    }
+
+    #[test]
+    fn unknown_template_id_is_d9() {
+        let mut decoder = decoder(INTEGERS);
+        let data: &[u8] = &[0xc0, 0x89];
+        let result = decoder.decode(&mut &data[..]);
+        assert!(matches!(result, Err(Error::Dynamic(DynamicError::D9))));
+    }
+
+    #[test]
+    fn mandatory_copy_without_previous_value_is_d5() {
+        let mut decoder = decoder(INTEGERS);
+        let data: &[u8] = &[0xc0, 0x81];
+        let result = decoder.decode(&mut &data[..]);
+        assert!(matches!(result, Err(Error::Dynamic(DynamicError::D5))));
+    }
+
+    #[test]
+    fn reset_clears_previous_values() {
+        let mut decoder = decoder(INTEGERS);
+        let data: &[u8] = &[0xf0, 0x81, 0x85, 0x8a, 0xfd, 0x88];
+        decoder.decode(&mut &data[..]).unwrap();
+        decoder.reset();
+        let data: &[u8] = &[0xc0, 0x81];
+        assert!(decoder.decode(&mut &data[..]).is_err());
+    }
+
+    #[test]
+    fn truncated_input_is_io_error() {
+        let mut decoder = decoder(INTEGERS);
+        let data: &[u8] = &[0xf0, 0x81, 0x85];
+        let result = decoder.decode(&mut &data[..]);
+        assert!(matches!(result, Err(Error::Io(_))));
+    }
+}
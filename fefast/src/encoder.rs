@@ -0,0 +1,869 @@
+use super::codec::{write_stop_bit_entity, write_stop_bit_int, write_stop_bit_uint};
+use super::decoder::{default_base, dictionary_key, DictionaryKey, OperatorState, Templates};
+use super::errors::{DynamicError, Error, ReportableError};
+use super::field_operators::FieldOperatorInstruction;
+use super::template::{int_fits, RawValue};
+use super::{
+    Codec, Field, FieldInstruction, FieldType, Message, PresenceMap, PrimitiveType, Template,
+    TemplateSet, Value,
+};
+use std::io;
+
+/// A template-driven FAST 1.1 encoder.
+///
+/// [`Encoder`] is the counterpart of [`Decoder`](crate::Decoder): it keeps the
+/// operator dictionaries across messages and sets presence map bits
+/// accordingly, so that fields with a copy, increment, default or tail
+/// operator are omitted from the stream whenever the peer can infer them. Use a
+/// single [`Encoder`] per FAST stream and call [`Encoder::reset`] whenever the
+/// peer resets its decoder.
+///
+/// Fields are looked up by name within each [`Message`], sequence entry and
+/// group. Mandatory fields without a value result in [`DynamicError::D5`],
+/// values of the wrong type in [`DynamicError::D1`]. If encoding fails, the
+/// operator dictionaries might be left in an intermediate state and you should
+/// reset the stream.
+///
+/// # Examples
+///
+/// ```
+/// use fefast::{Decoder, Encoder, Field, Message, Template, Value};
+///
+/// let spec = r#"<templates><template name="Trade" id="1">
+///     <uInt32 name="MsgSeqNum" id="34"><increment/></uInt32>
+///     <string name="Symbol" id="55"><copy/></string>
+/// </template></templates>"#;
+/// let mut encoder = Encoder::new();
+/// encoder.add_template(Template::new(spec).unwrap());
+/// let mut data = Vec::new();
+/// for seq_num in 1..=2 {
+///     let message = Message::new(
+///         1,
+///         vec![
+///             Field::new("MsgSeqNum", Some(Value::U32(seq_num))),
+///             Field::new("Symbol", Some(Value::AsciiString("AB".to_string()))),
+///         ],
+///     );
+///     encoder.encode(&message, &mut data).unwrap();
+/// }
+/// assert_eq!(data, vec![0xf0, 0x81, 0x81, 0x41, 0xc2, 0x80]);
+///
+/// let mut decoder = Decoder::new();
+/// decoder.add_template(Template::new(spec).unwrap());
+/// let input = &mut &data[..];
+/// decoder.decode(input).unwrap();
+/// let second = decoder.decode(input).unwrap();
+/// assert_eq!(second.value("MsgSeqNum"), Some(&Value::U32(2)));
+/// ```
+#[derive(Debug, Default)]
+pub struct Encoder {
+    templates: Templates,
+    state: OperatorState,
+}
+
+impl Encoder {
+    /// Creates a new [`Encoder`] without any templates.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a new [`Encoder`] which knows all templates in `templates`.
+    pub fn with_templates(templates: TemplateSet) -> Self {
+        let mut encoder = Self::new();
+        for template in templates {
+            encoder.add_template(template);
+        }
+        encoder
+    }
+
+    /// Adds `template` to the known templates of `self`. Templates without an
+    /// ID can only be used through static template references.
+    pub fn add_template(&mut self, template: Template) {
+        self.templates.add(template);
+    }
+
+    /// Resets all operator dictionaries to their initial state, i.e. all
+    /// previous values become undefined.
+    pub fn reset(&mut self) {
+        self.state.previous_values.clear();
+        self.state.template_id = None;
+    }
+
+    /// Encodes `message` and writes it to `output`.
+    pub fn encode(&mut self, message: &Message, output: &mut impl io::Write) -> Result<(), Error> {
+        let mut context = EncodeContext {
+            templates: &self.templates,
+            state: &mut self.state,
+            template_id: 0,
+            type_ref: None,
+        };
+        let mut buffer = Vec::new();
+        context.encode_message(message.template_id(), message.fields(), &mut buffer)?;
+        output.write_all(&buffer[..])?;
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+struct EncodeContext<'a> {
+    templates: &'a Templates,
+    state: &'a mut OperatorState,
+    template_id: u32,
+    type_ref: Option<&'a str>,
+}
+
+impl<'a> EncodeContext<'a> {
+    fn encode_message(
+        &mut self,
+        template_id: u32,
+        fields: &[Field],
+        output: &mut Vec<u8>,
+    ) -> Result<(), Error> {
+        let templates = self.templates;
+        let template = templates.by_id(template_id).ok_or(DynamicError::D9)?;
+        let mut pmap = PresenceMap::default();
+        let mut body = Vec::new();
+        // The template ID is implicitly encoded with a copy operator.
+        if self.state.template_id == Some(template_id) {
+            pmap.push(false);
+        } else {
+            pmap.push(true);
+            write_uint(&mut body, Some(i128::from(template_id)), false);
+            self.state.template_id = Some(template_id);
+        }
+        let outer = (self.template_id, self.type_ref);
+        self.template_id = template_id;
+        self.type_ref = template.type_ref();
+        let result = self.encode_instructions(template.iter_items(), fields, &mut pmap, &mut body);
+        self.template_id = outer.0;
+        self.type_ref = outer.1;
+        result?;
+        pmap.serialize(output)?;
+        output.extend_from_slice(&body[..]);
+        Ok(())
+    }
+
+    fn encode_instructions<'b>(
+        &mut self,
+        instructions: impl Iterator<Item = &'b FieldInstruction>,
+        fields: &[Field],
+        pmap: &mut PresenceMap,
+        output: &mut Vec<u8>,
+    ) -> Result<(), Error> {
+        for instruction in instructions {
+            let value = fields
+                .iter()
+                .find(|field| field.name() == instruction.name())
+                .and_then(|field| field.value());
+            match instruction.kind() {
+                FieldType::Primitive(primitive_type) => {
+                    let raw = value.map(|v| to_raw(v, *primitive_type)).transpose()?;
+                    self.encode_primitive(instruction, *primitive_type, raw, pmap, output)?;
+                }
+                FieldType::Sequence(sequence) => {
+                    let entries = match value {
+                        Some(Value::Sequence(entries)) => Some(entries),
+                        None => None,
+                        Some(_) => return Err(DynamicError::D1.into()),
+                    };
+                    let len = entries.map(|entries| RawValue::Int(entries.len() as i128));
+                    self.encode_primitive(
+                        sequence.length(),
+                        PrimitiveType::U32,
+                        len,
+                        pmap,
+                        output,
+                    )?;
+                    let needs_pmap = sequence.iter_items().any(|i| i.needs_presence_map_bit());
+                    for entry in entries.into_iter().flatten() {
+                        self.encode_nested(sequence.iter_items(), entry, needs_pmap, output)?;
+                    }
+                }
+                FieldType::Group(instructions) => {
+                    let group = match value {
+                        Some(Value::Group(group)) => Some(group),
+                        None => None,
+                        Some(_) => return Err(DynamicError::D1.into()),
+                    };
+                    if !instruction.is_mandatory() {
+                        pmap.push(group.is_some());
+                    }
+                    match group {
+                        Some(group) => {
+                            let needs_pmap =
+                                instructions.iter().any(|i| i.needs_presence_map_bit());
+                            self.encode_nested(instructions.iter(), group, needs_pmap, output)?;
+                        }
+                        None if instruction.is_mandatory() => {
+                            return Err(DynamicError::D5.into());
+                        }
+                        None => (),
+                    }
+                }
+                FieldType::TemplateRef(Some(name)) => {
+                    // Static template references are equivalent to inlining
+                    // all instructions of the referenced template.
+                    let templates = self.templates;
+                    let template = templates.by_name(name).ok_or(DynamicError::D8)?;
+                    self.encode_instructions(template.iter_items(), fields, pmap, output)?;
+                }
+                FieldType::TemplateRef(None) => {
+                    // Just like the decoder output, dynamically referenced
+                    // messages are groups named after their template.
+                    let templates = self.templates;
+                    let (template_id, nested) = fields
+                        .iter()
+                        .find_map(|field| {
+                            let id = templates.by_name(field.name()).and_then(|t| t.id());
+                            match (id, field.value()) {
+                                (Some(id), Some(Value::Group(nested))) => Some((id, nested)),
+                                _ => None,
+                            }
+                        })
+                        .ok_or(DynamicError::D5)?;
+                    self.encode_message(template_id, nested, output)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Encodes the fields of a sequence entry or group, which come with their
+    /// own presence map if and only if `needs_pmap`.
+    fn encode_nested<'b>(
+        &mut self,
+        instructions: impl Iterator<Item = &'b FieldInstruction>,
+        fields: &[Field],
+        needs_pmap: bool,
+        output: &mut Vec<u8>,
+    ) -> Result<(), Error> {
+        let mut pmap = PresenceMap::default();
+        let mut body = Vec::new();
+        self.encode_instructions(instructions, fields, &mut pmap, &mut body)?;
+        if needs_pmap {
+            pmap.serialize(output)?;
+        }
+        output.extend_from_slice(&body[..]);
+        Ok(())
+    }
+
+    fn dictionary_key(&self, instruction: &FieldInstruction) -> (DictionaryKey, String) {
+        dictionary_key(instruction, self.template_id, self.type_ref)
+    }
+
+    fn previous_value(&self, instruction: &FieldInstruction) -> Option<Option<RawValue>> {
+        self.state
+            .previous_values
+            .get(&self.dictionary_key(instruction))
+            .cloned()
+    }
+
+    fn set_previous_value(&mut self, instruction: &FieldInstruction, value: Option<RawValue>) {
+        let key = self.dictionary_key(instruction);
+        self.state.previous_values.insert(key, value);
+    }
+
+    /// Encodes a primitive field according to its field operator, i.e. the
+    /// exact inverse of the decoding procedure. See section 6.3 of the FAST
+    /// 1.1 specification.
+    fn encode_primitive(
+        &mut self,
+        instruction: &FieldInstruction,
+        primitive_type: PrimitiveType,
+        value: Option<RawValue>,
+        pmap: &mut PresenceMap,
+        output: &mut Vec<u8>,
+    ) -> Result<(), Error> {
+        let nullable = instruction.is_nullable();
+        let mandatory = instruction.is_mandatory();
+        let initial_value = instruction.initial_value().cloned();
+        if instruction.operator() == &FieldOperatorInstruction::Constant {
+            // Mandatory constants can be omitted by the application, as
+            // they're never transmitted anyway.
+            if value.is_some() && value != initial_value {
+                return Err(DynamicError::D1.into());
+            }
+            if !mandatory {
+                pmap.push(value.is_some());
+            }
+            return Ok(());
+        }
+        if mandatory && value.is_none() {
+            return Err(DynamicError::D5.into());
+        }
+        match instruction.operator() {
+            FieldOperatorInstruction::None => {
+                write_raw(output, value.as_ref(), primitive_type, nullable)?;
+            }
+            FieldOperatorInstruction::Constant => unreachable!(),
+            FieldOperatorInstruction::Default => {
+                if value == initial_value {
+                    pmap.push(false);
+                } else {
+                    pmap.push(true);
+                    write_raw(output, value.as_ref(), primitive_type, nullable)?;
+                }
+            }
+            FieldOperatorInstruction::Copy | FieldOperatorInstruction::Increment => {
+                // What the decoder infers when the field is not present in the
+                // stream; `None` if it would fail instead.
+                let inferred = match self.previous_value(instruction) {
+                    Some(Some(previous)) => {
+                        if instruction.operator() == &FieldOperatorInstruction::Increment {
+                            match previous {
+                                RawValue::Int(int) if int_fits(int + 1, primitive_type) => {
+                                    Some(Some(RawValue::Int(int + 1)))
+                                }
+                                _ => None,
+                            }
+                        } else {
+                            Some(Some(previous))
+                        }
+                    }
+                    None => match initial_value {
+                        Some(initial) => Some(Some(initial)),
+                        None if mandatory => None,
+                        None => Some(None),
+                    },
+                    Some(None) if mandatory => None,
+                    Some(None) => Some(None),
+                };
+                if inferred.as_ref() == Some(&value) {
+                    pmap.push(false);
+                } else {
+                    pmap.push(true);
+                    write_raw(output, value.as_ref(), primitive_type, nullable)?;
+                }
+                self.set_previous_value(instruction, value);
+            }
+            FieldOperatorInstruction::Tail => {
+                let previous = self.previous_value(instruction);
+                let inferred = match &previous {
+                    Some(Some(previous)) => Some(Some(previous.clone())),
+                    None => match &initial_value {
+                        Some(initial) => Some(Some(initial.clone())),
+                        None if mandatory => None,
+                        None => Some(None),
+                    },
+                    Some(None) if mandatory => None,
+                    Some(None) => Some(None),
+                };
+                if inferred.as_ref() == Some(&value) {
+                    pmap.push(false);
+                    if value.is_some() {
+                        self.set_previous_value(instruction, value);
+                    }
+                    return Ok(());
+                }
+                pmap.push(true);
+                let bytes = match &value {
+                    Some(RawValue::Bytes(bytes)) => bytes,
+                    Some(_) => return Err(DynamicError::D4.into()),
+                    None => {
+                        write_raw(output, None, primitive_type, nullable)?;
+                        self.set_previous_value(instruction, None);
+                        return Ok(());
+                    }
+                };
+                let base = match (previous, initial_value) {
+                    (Some(Some(RawValue::Bytes(previous))), _) => previous,
+                    (None, Some(RawValue::Bytes(initial))) => initial,
+                    _ => Vec::new(),
+                };
+                // The tail operator can only replace the end of the base
+                // value, never shorten it.
+                let tail = if bytes.len() > base.len() {
+                    &bytes[..]
+                } else if bytes.len() == base.len() {
+                    &bytes[common_prefix_len(&base, bytes)..]
+                } else {
+                    return Err(DynamicError::D1.into());
+                };
+                let tail = RawValue::Bytes(tail.to_vec());
+                write_raw(output, Some(&tail), primitive_type, nullable)?;
+                self.set_previous_value(instruction, value);
+            }
+            FieldOperatorInstruction::Delta => {
+                let value = match value {
+                    Some(value) => value,
+                    None => {
+                        write_int(output, None, nullable);
+                        return Ok(());
+                    }
+                };
+                let base = match self.previous_value(instruction) {
+                    Some(Some(previous)) => previous,
+                    Some(None) => return Err(DynamicError::D6.into()),
+                    None => initial_value.unwrap_or_else(|| default_base(primitive_type)),
+                };
+                match (&value, base) {
+                    (
+                        RawValue::Decimal { exp, mantissa },
+                        RawValue::Decimal {
+                            exp: base_exp,
+                            mantissa: base_mantissa,
+                        },
+                    ) => {
+                        let exp_delta = i128::from(*exp) - i128::from(base_exp);
+                        let mantissa_delta = i128::from(*mantissa) - i128::from(base_mantissa);
+                        write_int(output, Some(exp_delta), nullable);
+                        write_int(output, Some(mantissa_delta), false);
+                    }
+                    (RawValue::Bytes(bytes), RawValue::Bytes(base)) => {
+                        // Either the end or the front of the base value is
+                        // replaced, whichever results in the shorter diff.
+                        let prefix = common_prefix_len(&base, bytes);
+                        let suffix = base
+                            .iter()
+                            .rev()
+                            .zip(bytes.iter().rev())
+                            .take_while(|(a, b)| a == b)
+                            .count();
+                        let (subtraction_len, diff) = if prefix >= suffix {
+                            ((base.len() - prefix) as i128, &bytes[prefix..])
+                        } else {
+                            // Negative subtraction lengths are encoded in
+                            // excess of one.
+                            (
+                                -((base.len() - suffix) as i128) - 1,
+                                &bytes[..bytes.len() - suffix],
+                            )
+                        };
+                        write_int(output, Some(subtraction_len), nullable);
+                        let diff = RawValue::Bytes(diff.to_vec());
+                        write_raw(output, Some(&diff), primitive_type, false)?;
+                    }
+                    (RawValue::Int(int), RawValue::Int(base)) => {
+                        write_int(output, Some(int - base), nullable);
+                    }
+                    _ => return Err(DynamicError::D4.into()),
+                }
+                self.set_previous_value(instruction, Some(value));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn common_prefix_len(a: &[u8], b: &[u8]) -> usize {
+    a.iter().zip(b.iter()).take_while(|(a, b)| a == b).count()
+}
+
+fn to_raw(value: &Value, primitive_type: PrimitiveType) -> Result<RawValue, Error> {
+    let int = match value {
+        Value::I32(int) => Some(i128::from(*int)),
+        Value::U32(int) => Some(i128::from(*int)),
+        Value::I64(int) => Some(i128::from(*int)),
+        Value::U64(int) => Some(i128::from(*int)),
+        _ => None,
+    };
+    Ok(match (value, primitive_type) {
+        (_, PrimitiveType::I32 | PrimitiveType::U32 | PrimitiveType::I64 | PrimitiveType::U64) => {
+            match int {
+                Some(int) if int_fits(int, primitive_type) => RawValue::Int(int),
+                Some(_) => return Err(ReportableError::R4.into()),
+                None => return Err(DynamicError::D1.into()),
+            }
+        }
+        (Value::Decimal(number), PrimitiveType::Decimal) => RawValue::Decimal {
+            exp: number.exp(),
+            mantissa: number.mantissa(),
+        },
+        (Value::AsciiString(s) | Value::Utf8String(s), PrimitiveType::AsciiString) => {
+            if !s.is_ascii() {
+                return Err(ReportableError::R3.into());
+            }
+            RawValue::Bytes(s.as_bytes().to_vec())
+        }
+        (Value::AsciiString(s) | Value::Utf8String(s), PrimitiveType::Utf8String) => {
+            RawValue::Bytes(s.as_bytes().to_vec())
+        }
+        (Value::Bytes(bytes), PrimitiveType::Bytes) => RawValue::Bytes(bytes.clone()),
+        _ => return Err(DynamicError::D1.into()),
+    })
+}
+
+/// Writes a value of type `primitive_type` to `output`, without any field
+/// operator. `None` stands for NULL.
+fn write_raw(
+    output: &mut Vec<u8>,
+    value: Option<&RawValue>,
+    primitive_type: PrimitiveType,
+    nullable: bool,
+) -> Result<(), Error> {
+    if value.is_none() && !nullable {
+        return Err(DynamicError::D5.into());
+    }
+    match (value, primitive_type) {
+        (_, PrimitiveType::U32 | PrimitiveType::U64) => match value {
+            Some(RawValue::Int(int)) => write_uint(output, Some(*int), nullable),
+            None => write_uint(output, None, nullable),
+            Some(_) => return Err(DynamicError::D4.into()),
+        },
+        (_, PrimitiveType::I32 | PrimitiveType::I64) => match value {
+            Some(RawValue::Int(int)) => write_int(output, Some(*int), nullable),
+            None => write_int(output, None, nullable),
+            Some(_) => return Err(DynamicError::D4.into()),
+        },
+        (Some(RawValue::Decimal { exp, mantissa }), PrimitiveType::Decimal) => {
+            if !(-63..=63).contains(exp) {
+                return Err(ReportableError::R1.into());
+            }
+            write_int(output, Some(i128::from(*exp)), nullable);
+            write_int(output, Some(i128::from(*mantissa)), false);
+        }
+        (None, PrimitiveType::Decimal) => write_int(output, None, nullable),
+        (Some(RawValue::Bytes(bytes)), PrimitiveType::AsciiString) => {
+            write_ascii(output, Some(bytes), nullable)
+        }
+        (None, PrimitiveType::AsciiString) => write_ascii(output, None, nullable),
+        (Some(RawValue::Bytes(bytes)), PrimitiveType::Utf8String | PrimitiveType::Bytes) => {
+            write_uint(output, Some(bytes.len() as i128), nullable);
+            output.extend_from_slice(&bytes[..]);
+        }
+        (None, PrimitiveType::Utf8String | PrimitiveType::Bytes) => {
+            write_uint(output, None, nullable)
+        }
+        _ => return Err(DynamicError::D4.into()),
+    }
+    Ok(())
+}
+
+fn write_uint(output: &mut Vec<u8>, int: Option<i128>, nullable: bool) {
+    let int = match (nullable, int) {
+        (true, None) => 0,
+        (true, Some(int)) => int + 1,
+        (false, int) => int.unwrap_or_default(),
+    };
+    write_stop_bit_uint(output, int);
+}
+
+fn write_int(output: &mut Vec<u8>, int: Option<i128>, nullable: bool) {
+    let int = match (nullable, int) {
+        (true, None) => 0,
+        (true, Some(int)) if int >= 0 => int + 1,
+        (_, int) => int.unwrap_or_default(),
+    };
+    write_stop_bit_int(output, int);
+}
+
+fn write_ascii(output: &mut Vec<u8>, bytes: Option<&[u8]>, nullable: bool) {
+    // Empty strings and strings with a leading zero byte are preceded by an
+    // additional zero byte, and nullable ones by yet another one.
+    let needs_prefix = |bytes: &[u8]| matches!(bytes, [] | [0, ..]);
+    let mandatory = |bytes: &[u8]| match bytes {
+        [] => vec![0],
+        [0, ..] => [&[0], bytes].concat(),
+        bytes => bytes.to_vec(),
+    };
+    let entity = match bytes {
+        None => vec![0],
+        Some(bytes) if nullable && needs_prefix(bytes) => [vec![0], mandatory(bytes)].concat(),
+        Some(bytes) => mandatory(bytes),
+    };
+    write_stop_bit_entity(output, entity);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Decoder, ScaledNumber};
+
+    const INTEGERS: &str = r#"<templates><template name="T" id="1">
+        <uInt32 name="A" id="1"><copy/></uInt32>
+        <uInt32 name="B" id="2"><increment/></uInt32>
+        <string name="C" id="3"><constant value="X"/></string>
+        <int64 name="D" id="4"><delta/></int64>
+        <uInt32 name="E" id="5" presence="optional"/>
+    </template></templates>"#;
+
+    fn codecs(spec: &str) -> (Encoder, Decoder) {
+        let mut encoder = Encoder::new();
+        encoder.add_template(Template::new(spec).unwrap());
+        let mut decoder = Decoder::new();
+        decoder.add_template(Template::new(spec).unwrap());
+        (encoder, decoder)
+    }
+
+    fn ascii(s: &str) -> Option<Value> {
+        Some(Value::AsciiString(s.to_string()))
+    }
+
+    /// Encodes all `messages` into a single stream and decodes them back. The
+    /// decoded messages must be encoded to the very same stream.
+    fn roundtrip(spec: &str, messages: &[Message]) -> (Vec<u8>, Vec<Message>) {
+        let (mut encoder, mut decoder) = codecs(spec);
+        let mut data = Vec::new();
+        for message in messages {
+            encoder.encode(message, &mut data).unwrap();
+        }
+        let input = &mut &data[..];
+        let decoded: Vec<Message> = messages
+            .iter()
+            .map(|_| decoder.decode(input).unwrap())
+            .collect();
+        assert!(input.is_empty());
+        let (mut encoder, _) = codecs(spec);
+        let mut reencoded = Vec::new();
+        for (message, decoded) in messages.iter().zip(decoded.iter()) {
+            assert_eq!(decoded.template_id(), message.template_id());
+            encoder.encode(decoded, &mut reencoded).unwrap();
+        }
+        assert_eq!(reencoded, data);
+        (data, decoded)
+    }
+
+    #[test]
+    fn integer_operators_produce_minimal_presence_maps() {
+        let message = |b: u32, d: i64, e: Option<u32>| {
+            Message::new(
+                1,
+                vec![
+                    Field::new("A", Some(Value::U32(5))),
+                    Field::new("B", Some(Value::U32(b))),
+                    Field::new("D", Some(Value::I64(d))),
+                    Field::new("E", e.map(Value::U32)),
+                ],
+            )
+        };
+        let (data, _) = roundtrip(INTEGERS, &[message(10, -3, Some(7)), message(11, 1, None)]);
+        assert_eq!(
+            data,
+            vec![0xf0, 0x81, 0x85, 0x8a, 0xfd, 0x88, 0x80, 0x84, 0x80]
+        );
+    }
+
+    #[test]
+    fn strings_decimals_and_sequences() {
+        let spec = r#"<templates><template name="S" id="2">
+            <string name="Symbol" id="55"><tail/></string>
+            <string name="Text" id="58"><delta/></string>
+            <sequence name="Entries">
+                <length name="NoEntries" id="268"/>
+                <uInt32 name="Px" id="270"><copy/></uInt32>
+                <decimal name="Qty" id="271"/>
+            </sequence>
+        </template></templates>"#;
+        let entry = |px: u32, qty: ScaledNumber| {
+            vec![
+                Field::new("Px", Some(Value::U32(px))),
+                Field::new("Qty", Some(Value::Decimal(qty))),
+            ]
+        };
+        let first = Message::new(
+            2,
+            vec![
+                Field::new("Symbol", ascii("ABC")),
+                Field::new("Text", ascii("xy")),
+                Field::new(
+                    "Entries",
+                    Some(Value::Sequence(vec![
+                        entry(100, ScaledNumber::new(15, -1)),
+                        entry(100, ScaledNumber::new(2, 0)),
+                    ])),
+                ),
+            ],
+        );
+        let second = Message::new(
+            2,
+            vec![
+                Field::new("Symbol", ascii("ABD")),
+                Field::new("Text", ascii("wxy")),
+                Field::new("Entries", Some(Value::Sequence(vec![]))),
+            ],
+        );
+        let third = Message::new(
+            2,
+            vec![
+                Field::new("Symbol", ascii("ABDE")),
+                Field::new("Text", ascii("w")),
+                Field::new("Entries", Some(Value::Sequence(vec![]))),
+            ],
+        );
+        let (data, decoded) = roundtrip(spec, &[first, second, third]);
+        // Same as the decoder test data.
+        assert_eq!(
+            &data[..21],
+            &[
+                0xe0, 0x82, 0x41, 0x42, 0xc3, 0x80, 0x78, 0xf9, 0x82, 0xc0, 0xe4, 0xff, 0x8f, 0x80,
+                0x80, 0x82, 0xa0, 0xc4, 0xff, 0xf7, 0x80,
+            ]
+        );
+        let entries = match decoded[0].value("Entries") {
+            Some(Value::Sequence(entries)) => entries,
+            _ => panic!("Expected a sequence"),
+        };
+        assert_eq!(
+            entries[1][1].value(),
+            Some(&Value::Decimal(ScaledNumber::new(2, 0)))
+        );
+        assert_eq!(decoded[2].value("Symbol"), ascii("ABDE").as_ref());
+        assert_eq!(decoded[2].value("Text"), ascii("w").as_ref());
+    }
+
+    #[test]
+    fn optional_constant_and_default() {
+        let spec = r#"<templates><template name="O" id="3">
+            <uInt32 name="K" id="1" presence="optional"><constant value="7"/></uInt32>
+            <uInt32 name="L" id="2"><default value="4"/></uInt32>
+        </template></templates>"#;
+        let first = Message::new(3, vec![Field::new("L", Some(Value::U32(4)))]);
+        let second = Message::new(
+            3,
+            vec![
+                Field::new("K", Some(Value::U32(7))),
+                Field::new("L", Some(Value::U32(9))),
+            ],
+        );
+        let (data, _) = roundtrip(spec, &[first, second]);
+        assert_eq!(data, vec![0xc0, 0x83, 0xb0, 0x89]);
+    }
+
+    #[test]
+    fn nullable_ascii_strings() {
+        let spec = r#"<templates><template name="N" id="4">
+            <string name="A" id="1" presence="optional"/>
+            <string name="B" id="2" presence="optional"/>
+            <string name="C" id="3"/>
+            <string name="D" id="4" presence="optional"/>
+            <string name="E" id="5"/>
+        </template></templates>"#;
+        let message = Message::new(
+            4,
+            vec![
+                Field::new("B", ascii("")),
+                Field::new("C", ascii("")),
+                Field::new("D", ascii("\0")),
+                Field::new("E", ascii("\0a")),
+            ],
+        );
+        let (data, decoded) = roundtrip(spec, &[message]);
+        assert_eq!(&data[..6], &[0xc0, 0x84, 0x80, 0x00, 0x80, 0x80]);
+        assert_eq!(decoded[0].value("A"), None);
+        assert_eq!(decoded[0].value("B"), ascii("").as_ref());
+        assert_eq!(decoded[0].value("D"), ascii("\0").as_ref());
+        assert_eq!(decoded[0].value("E"), ascii("\0a").as_ref());
+    }
+
+    #[test]
+    fn static_and_dynamic_template_references() {
+        let templates = r#"<templates>
+            <template name="Header">
+                <uInt32 name="MsgSeqNum" id="34"><increment/></uInt32>
+            </template>
+            <template name="Leg" id="6">
+                <int32 name="Ratio" id="623"><copy/></int32>
+            </template>
+            <template name="Trade" id="5">
+                <templateRef name="Header"/>
+                <uInt32 name="Qty" id="53"/>
+                <templateRef/>
+            </template>
+        </templates>"#;
+        let message = |seq_num: u32| {
+            Message::new(
+                5,
+                vec![
+                    Field::new("MsgSeqNum", Some(Value::U32(seq_num))),
+                    Field::new("Qty", Some(Value::U32(3))),
+                    Field::new(
+                        "Leg",
+                        Some(Value::Group(vec![Field::new(
+                            "Ratio",
+                            Some(Value::I32(-2)),
+                        )])),
+                    ),
+                ],
+            )
+        };
+        let mut encoder = Encoder::with_templates(TemplateSet::from_xml(templates).unwrap());
+        let mut decoder = Decoder::with_templates(TemplateSet::from_xml(templates).unwrap());
+        let mut data = Vec::new();
+        encoder.encode(&message(10), &mut data).unwrap();
+        encoder.encode(&message(11), &mut data).unwrap();
+        let input = &mut &data[..];
+        for expected in &[message(10), message(11)] {
+            let decoded = decoder.decode(input).unwrap();
+            assert_eq!(decoded.value("MsgSeqNum"), expected.value("MsgSeqNum"));
+            assert_eq!(decoded.value("Qty"), Some(&Value::U32(3)));
+            match decoded.value("Leg") {
+                Some(Value::Group(leg)) => assert_eq!(leg[0].value(), Some(&Value::I32(-2))),
+                _ => panic!("Expected a group"),
+            }
+        }
+        assert!(input.is_empty());
+    }
+
+    #[test]
+    fn optional_groups_and_byte_vectors() {
+        let spec = r#"<templates><template name="G" id="7">
+            <group name="Extra" presence="optional">
+                <byteVector name="Payload" id="1"><copy/></byteVector>
+                <uInt64 name="Big" id="2"><delta/></uInt64>
+            </group>
+        </template></templates>"#;
+        let with_group = |payload: &[u8]| {
+            Message::new(
+                7,
+                vec![Field::new(
+                    "Extra",
+                    Some(Value::Group(vec![
+                        Field::new("Payload", Some(Value::Bytes(payload.to_vec()))),
+                        Field::new("Big", Some(Value::U64(u64::MAX))),
+                    ])),
+                )],
+            )
+        };
+        roundtrip(
+            spec,
+            &[
+                with_group(b"ab"),
+                Message::new(7, vec![]),
+                with_group(b"ab"),
+                with_group(b""),
+            ],
+        );
+    }
+
+    #[test]
+    fn missing_mandatory_field_is_d5() {
+        let (mut encoder, _) = codecs(INTEGERS);
+        let message = Message::new(1, vec![Field::new("A", Some(Value::U32(5)))]);
+        let result = encoder.encode(&message, &mut Vec::new());
+        assert!(matches!(result, Err(Error::Dynamic(DynamicError::D5))));
+    }
+
+    #[test]
+    fn wrong_value_type_is_d1() {
+        let (mut encoder, _) = codecs(INTEGERS);
+        let message = Message::new(
+            1,
+            vec![
+                Field::new("A", ascii("5")),
+                Field::new("B", Some(Value::U32(1))),
+                Field::new("D", Some(Value::I64(1))),
+            ],
+        );
+        let result = encoder.encode(&message, &mut Vec::new());
+        assert!(matches!(result, Err(Error::Dynamic(DynamicError::D1))));
+    }
+
+    #[test]
+    fn reset_makes_previous_values_undefined() {
+        let (mut encoder, _) = codecs(INTEGERS);
+        let message = Message::new(
+            1,
+            vec![
+                Field::new("A", Some(Value::U32(5))),
+                Field::new("B", Some(Value::U32(1))),
+                Field::new("D", Some(Value::I64(1))),
+            ],
+        );
+        let mut first = Vec::new();
+        encoder.encode(&message, &mut first).unwrap();
+        encoder.reset();
+        let mut second = Vec::new();
+        encoder.encode(&message, &mut second).unwrap();
+        assert_eq!(first, second);
+    }
+}
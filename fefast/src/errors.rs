@@ -108,17 +108,41 @@ pub enum Error {
     Static(StaticError),
     Dynamic(DynamicError),
     Reportable(ReportableError),
+    /// The underlying reader failed, e.g. because the stream ended in the
+    /// middle of a message.
+    Io(io::Error),
 }
 
 impl From<io::Error> for Error {
-    fn from(_err: io::Error) -> Self {
-        Error::Dynamic(DynamicError::D1)
+    fn from(err: io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+impl From<StaticError> for Error {
+    fn from(err: StaticError) -> Self {
+        Error::Static(err)
+    }
+}
+
+impl From<DynamicError> for Error {
+    fn from(err: DynamicError) -> Self {
+        Error::Dynamic(err)
+    }
+}
+
+impl From<ReportableError> for Error {
+    fn from(err: ReportableError) -> Self {
+        Error::Reportable(err)
     }
 }
 
 impl std::error::Error for Error {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
-        None
+        match self {
+            Error::Io(e) => Some(e),
+            _ => None,
+        }
     }
 }
 
@@ -134,9 +158,10 @@ impl Display for Error {
                 (*e).fmt(f)
             }
             Error::Static(e) => {
-                write!(f, "Dynamic Error (D{}): ", *e as u8)?;
+                write!(f, "Static Error (S{}): ", *e as u8)?;
                 (*e).fmt(f)
             }
+            Error::Io(e) => write!(f, "I/O Error: {}", e),
         }
     }
 }
@@ -2,11 +2,15 @@ use super::Codec;
 use std::marker::PhantomData;
 use std::ops::Sub;
 
-#[derive(Clone, Debug)]
+/// The field operator of a field instruction. See section 6.3 of the FAST 1.1
+/// specification.
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum FieldOperatorInstruction {
     Constant,
     None,
+    Default,
     Delta,
+    Increment,
     Tail,
     Copy,
 }
@@ -22,19 +22,23 @@
 // Only enables the `doc_cfg` feature when its feature is defined.
 #![cfg_attr(doc_cfg, feature(doc_cfg))]
 
-use template::Template;
-
 mod codec;
 mod codegen;
 mod decimal;
+mod decoder;
 mod dtf;
+mod encoder;
 mod errors;
 mod field_operators;
+mod scaled_number;
 mod template;
 
 pub use self::decimal::Decimal;
 pub use codec::{Codec, PresenceMap};
 pub use codegen::template_struct as codegen_template_struct;
+pub use decoder::{Decoder, Field, Message, Value};
+pub use encoder::Encoder;
 pub use errors::{DynamicError, Error, ReportableError, StaticError};
 pub use field_operators::*;
+pub use scaled_number::ScaledNumber;
 pub use template::*;
@@ -0,0 +1,218 @@
+use super::Decimal;
+use std::fmt;
+
+/// The lossless value of a FAST `decimal` field, i.e. a *scaled number* with an
+/// integer mantissa and a base-10 exponent, exactly as it appears on the wire.
+///
+/// Contrary to [`Decimal`], [`ScaledNumber`] is never normalized, so that
+/// re-encoding it produces the very same bytes and precision information (e.g.
+/// `1.50` vs. `1.5`) is preserved. As a consequence, equality is structural.
+///
+/// # Examples
+///
+/// ```
+/// use fefast::ScaledNumber;
+///
+/// let price = ScaledNumber::new(150, -2);
+/// assert_eq!(price.to_string(), "1.50");
+/// assert_ne!(price, ScaledNumber::new(15, -1));
+/// assert_eq!(price.to_decimal(), ScaledNumber::new(15, -1).to_decimal());
+/// assert_eq!(ScaledNumber::new(-12, 3).to_string(), "-12000");
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ScaledNumber {
+    mantissa: i64,
+    exp: i32,
+}
+
+impl ScaledNumber {
+    /// Creates a new [`ScaledNumber`] with value `mantissa * 10^exp`. FAST
+    /// streams only allow exponents from -63 to 63 (including).
+    pub fn new(mantissa: i64, exp: i32) -> Self {
+        Self { mantissa, exp }
+    }
+
+    /// Returns the mantissa of `self`.
+    pub fn mantissa(&self) -> i64 {
+        self.mantissa
+    }
+
+    /// Returns the base-10 exponent of `self`.
+    pub fn exp(&self) -> i32 {
+        self.exp
+    }
+
+    /// Converts `self` to a normalized [`Decimal`].
+    pub fn to_decimal(&self) -> Decimal {
+        Decimal::new_unchecked(self.mantissa, self.exp)
+    }
+
+    /// Returns the closest [`f64`] to `self`. This conversion is lossy.
+    pub fn to_f64(&self) -> f64 {
+        self.mantissa as f64 * 10f64.powi(self.exp)
+    }
+
+    /// Converts `self` to a [`fefix::fix_values::Decimal`], which preserves
+    /// trailing zeros. It returns `None` if `self` has more than 18 digits
+    /// after the decimal point or it doesn't fit in an `i64` mantissa.
+    #[cfg(feature = "utils-fefix")]
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "utils-fefix")))]
+    pub fn to_fix_decimal(&self) -> Option<fefix::fix_values::Decimal> {
+        if self.exp > 0 {
+            let mantissa = 10i64
+                .checked_pow(self.exp as u32)?
+                .checked_mul(self.mantissa)?;
+            Some(fefix::fix_values::Decimal::new(mantissa, 0))
+        } else if self.exp >= -18 {
+            Some(fefix::fix_values::Decimal::new(
+                self.mantissa,
+                (-self.exp) as u32,
+            ))
+        } else {
+            None
+        }
+    }
+
+    /// Converts `self` to a [`rust_decimal::Decimal`], which preserves
+    /// trailing zeros. It returns `None` if `self` has more than 28 digits
+    /// after the decimal point or it's too large.
+    #[cfg(feature = "utils-rust-decimal")]
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "utils-rust-decimal")))]
+    pub fn to_rust_decimal(&self) -> Option<rust_decimal::Decimal> {
+        // The largest mantissa `rust_decimal` can represent.
+        const MAX_MANTISSA: i128 = (1 << 96) - 1;
+        if self.exp > 0 {
+            let mantissa = 10i128
+                .checked_pow(self.exp as u32)?
+                .checked_mul(i128::from(self.mantissa))?;
+            if mantissa.abs() > MAX_MANTISSA {
+                return None;
+            }
+            Some(rust_decimal::Decimal::from_i128_with_scale(mantissa, 0))
+        } else if self.exp >= -28 {
+            Some(rust_decimal::Decimal::from_i128_with_scale(
+                i128::from(self.mantissa),
+                (-self.exp) as u32,
+            ))
+        } else {
+            None
+        }
+    }
+
+    /// Converts `self` to a [`decimal::d128`]. All FAST decimals are
+    /// representable, so this conversion is lossless.
+    #[cfg(feature = "utils-decimal")]
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "utils-decimal")))]
+    pub fn to_d128(&self) -> decimal::d128 {
+        use std::str::FromStr;
+        let s = format!("{}E{}", self.mantissa, self.exp);
+        decimal::d128::from_str(&s[..]).expect("decimal::d128 should always parse without errors")
+    }
+}
+
+impl From<Decimal> for ScaledNumber {
+    fn from(decimal: Decimal) -> Self {
+        Self::new(decimal.mantissa(), decimal.exp())
+    }
+}
+
+impl From<ScaledNumber> for Decimal {
+    fn from(number: ScaledNumber) -> Self {
+        number.to_decimal()
+    }
+}
+
+#[cfg(feature = "utils-fefix")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "utils-fefix")))]
+impl From<fefix::fix_values::Decimal> for ScaledNumber {
+    fn from(decimal: fefix::fix_values::Decimal) -> Self {
+        Self::new(decimal.mantissa(), -(decimal.scale() as i32))
+    }
+}
+
+impl fmt::Display for ScaledNumber {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.exp >= 0 {
+            return write!(f, "{}{}", self.mantissa, "0".repeat(self.exp as usize));
+        }
+        let digits = self.mantissa.unsigned_abs().to_string();
+        let scale = (-self.exp) as usize;
+        let sign = if self.mantissa < 0 { "-" } else { "" };
+        if digits.len() > scale {
+            let (int, fract) = digits.split_at(digits.len() - scale);
+            write!(f, "{}{}.{}", sign, int, fract)
+        } else {
+            write!(
+                f,
+                "{}0.{}{}",
+                sign,
+                "0".repeat(scale - digits.len()),
+                digits
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn display_keeps_trailing_zeros() {
+        assert_eq!(ScaledNumber::new(0, 0).to_string(), "0");
+        assert_eq!(ScaledNumber::new(0, -2).to_string(), "0.00");
+        assert_eq!(ScaledNumber::new(-5, -3).to_string(), "-0.005");
+        assert_eq!(ScaledNumber::new(12345, -2).to_string(), "123.45");
+        assert_eq!(
+            ScaledNumber::new(i64::MIN, -1).to_string(),
+            "-922337203685477580.8"
+        );
+    }
+
+    #[test]
+    fn decimal_conversion_normalizes() {
+        let number = ScaledNumber::new(1500, -3);
+        assert_eq!(Decimal::from(number), Decimal::new(15, -1));
+        assert_eq!(
+            ScaledNumber::from(Decimal::new(15, -1)),
+            ScaledNumber::new(15, -1)
+        );
+    }
+
+    #[cfg(feature = "utils-fefix")]
+    #[test]
+    fn fix_decimal_conversion_preserves_scale() {
+        use fefix::FixValue;
+        let decimal = ScaledNumber::new(-150, -2).to_fix_decimal().unwrap();
+        assert_eq!(&decimal.to_bytes()[..], b"-1.50");
+        assert_eq!(ScaledNumber::from(decimal), ScaledNumber::new(-150, -2));
+        let decimal = ScaledNumber::new(7, 3).to_fix_decimal().unwrap();
+        assert_eq!(&decimal.to_bytes()[..], b"7000");
+        assert!(ScaledNumber::new(1, -19).to_fix_decimal().is_none());
+        assert!(ScaledNumber::new(1, 19).to_fix_decimal().is_none());
+    }
+
+    #[cfg(feature = "utils-rust-decimal")]
+    #[test]
+    fn rust_decimal_conversion_preserves_scale() {
+        let decimal = ScaledNumber::new(150, -2).to_rust_decimal().unwrap();
+        assert_eq!(decimal.to_string(), "1.50");
+        let decimal = ScaledNumber::new(i64::MAX, 9).to_rust_decimal().unwrap();
+        assert_eq!(
+            decimal.to_string(),
+            ScaledNumber::new(i64::MAX, 9).to_string()
+        );
+        assert!(ScaledNumber::new(1, -29).to_rust_decimal().is_none());
+        assert!(ScaledNumber::new(i64::MAX, 10).to_rust_decimal().is_none());
+        assert!(ScaledNumber::new(1, 63).to_rust_decimal().is_none());
+    }
+
+    #[cfg(feature = "utils-decimal")]
+    #[test]
+    fn d128_conversion_is_exact() {
+        let decimal = ScaledNumber::new(-314, -2).to_d128();
+        assert_eq!(decimal, decimal::d128!(-3.14));
+        let decimal = ScaledNumber::new(1, 63).to_d128();
+        assert_eq!(decimal, decimal::d128!(1E63));
+    }
+}
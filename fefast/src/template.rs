@@ -14,7 +14,7 @@ pub enum PrimitiveValue<'a> {
     Bytes(&'a [u8]),
 }
 
-#[derive(Clone, Debug, Copy)]
+#[derive(Clone, Debug, Copy, PartialEq, Eq)]
 pub enum PrimitiveType {
     I32,
     U32,
@@ -26,13 +26,111 @@ pub enum PrimitiveType {
     Bytes,
 }
 
+/// The value of a field as stored in operator dictionaries and as initial
+/// value of field instructions. Integers of all sizes share a single
+/// representation and decimals keep their original exponent and mantissa, which
+/// delta operators need.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum RawValue {
+    Int(i128),
+    Decimal { exp: i32, mantissa: i64 },
+    Bytes(Vec<u8>),
+}
+
+impl RawValue {
+    fn parse(s: &str, primitive_type: PrimitiveType) -> Result<Self, StaticError> {
+        let s = s.trim();
+        match primitive_type {
+            PrimitiveType::I32 | PrimitiveType::U32 | PrimitiveType::I64 | PrimitiveType::U64 => {
+                let int: i128 = s.parse().map_err(|_| StaticError::S3)?;
+                if int_fits(int, primitive_type) {
+                    Ok(RawValue::Int(int))
+                } else {
+                    Err(StaticError::S3)
+                }
+            }
+            PrimitiveType::Decimal => parse_decimal(s).ok_or(StaticError::S3),
+            PrimitiveType::AsciiString | PrimitiveType::Utf8String | PrimitiveType::Bytes => {
+                Ok(RawValue::Bytes(s.as_bytes().to_vec()))
+            }
+        }
+    }
+}
+
+/// Returns `true` if and only if `int` is within the legal range of
+/// `primitive_type`, which must be an integer type.
+pub(crate) fn int_fits(int: i128, primitive_type: PrimitiveType) -> bool {
+    match primitive_type {
+        PrimitiveType::I32 => i32::MIN as i128 <= int && int <= i32::MAX as i128,
+        PrimitiveType::U32 => 0 <= int && int <= u32::MAX as i128,
+        PrimitiveType::I64 => i64::MIN as i128 <= int && int <= i64::MAX as i128,
+        PrimitiveType::U64 => 0 <= int && int <= u64::MAX as i128,
+        _ => false,
+    }
+}
+
+/// Parses decimal numbers in the form `-12.345` or `12345E-3`.
+fn parse_decimal(s: &str) -> Option<RawValue> {
+    let (number, mut exp) = match s.find(|c| c == 'e' || c == 'E') {
+        Some(i) => (&s[..i], s[i + 1..].parse::<i32>().ok()?),
+        None => (s, 0),
+    };
+    let mut digits = match number.find('.') {
+        Some(i) => {
+            exp -= (number.len() - i - 1) as i32;
+            [&number[..i], &number[i + 1..]].concat()
+        }
+        None => number.to_string(),
+    };
+    // Trailing zeros would otherwise overflow the mantissa of very large
+    // values, e.g. `9223372036854775807000000`.
+    while digits.len() > 1 && digits.ends_with('0') {
+        digits.pop();
+        exp += 1;
+    }
+    let mantissa = digits.parse().ok()?;
+    if (-63..=63).contains(&exp) {
+        Some(RawValue::Decimal { exp, mantissa })
+    } else {
+        None
+    }
+}
+
+/// The *dictionary* in which field operators store previous values. See
+/// section 6.6 of the FAST 1.1 specification.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Dictionary {
+    /// Shared by all templates. This is the default dictionary.
+    Global,
+    /// Local to the template in which the instruction appears.
+    Template,
+    /// Shared by all templates with the same application type.
+    Type,
+    /// A user defined dictionary.
+    Custom(String),
+}
+
+impl Dictionary {
+    fn from_attribute(attribute: &str) -> Self {
+        match attribute {
+            "global" => Dictionary::Global,
+            "template" => Dictionary::Template,
+            "type" => Dictionary::Type,
+            custom => Dictionary::Custom(custom.to_string()),
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct FieldInstruction {
     field_type: FieldType,
     name: String,
-    id: u32,
+    id: Option<u32>,
     mandatory: bool,
     operator: FieldOperatorInstruction,
+    initial_value: Option<RawValue>,
+    dictionary: Dictionary,
+    key: String,
 }
 
 impl FieldInstruction {
@@ -40,35 +138,263 @@ impl FieldInstruction {
         &self.field_type
     }
 
+    /// Returns the name of `self`.
+    pub fn name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    /// Returns the field ID of `self`, if any. Field IDs usually correspond
+    /// to FIX tags.
+    pub fn id(&self) -> Option<u32> {
+        self.id
+    }
+
     pub fn is_mandatory(&self) -> bool {
         self.mandatory
     }
-}
 
-#[derive(Clone, Debug)]
-pub enum FieldType {
-    Primitive(PrimitiveType),
-    Group(u32),
-}
+    /// Returns the field operator of `self`.
+    pub fn operator(&self) -> &FieldOperatorInstruction {
+        &self.operator
+    }
 
-impl FieldInstruction {
-    fn from_template(node: roxmltree::Node) -> Result<Self, StaticError> {
+    /// Returns the dictionary in which the field operator of `self` stores
+    /// previous values.
+    pub fn dictionary(&self) -> &Dictionary {
+        &self.dictionary
+    }
+
+    /// Returns the key used to look up previous values in
+    /// [`FieldInstruction::dictionary`]. It defaults to the field name.
+    pub fn key(&self) -> &str {
+        self.key.as_str()
+    }
+
+    pub(crate) fn initial_value(&self) -> Option<&RawValue> {
+        self.initial_value.as_ref()
+    }
+
+    /// Returns `true` if and only if `self` uses a bit in the presence map of
+    /// the enclosing template, sequence or group. See section 6.3.1 of the
+    /// FAST 1.1 specification.
+    pub fn needs_presence_map_bit(&self) -> bool {
+        match &self.field_type {
+            FieldType::Sequence(sequence) => sequence.length().needs_presence_map_bit(),
+            FieldType::Primitive(_) => match self.operator {
+                FieldOperatorInstruction::None | FieldOperatorInstruction::Delta => false,
+                FieldOperatorInstruction::Constant => !self.mandatory,
+                FieldOperatorInstruction::Default
+                | FieldOperatorInstruction::Copy
+                | FieldOperatorInstruction::Increment
+                | FieldOperatorInstruction::Tail => true,
+            },
+            FieldType::Group(_) => !self.mandatory,
+            FieldType::TemplateRef(_) => false,
+        }
+    }
+
+    /// Returns `true` if and only if `self` can encode NULL values. See
+    /// section 10.5 of the FAST 1.1 specification.
+    pub(crate) fn is_nullable(&self) -> bool {
+        !self.mandatory && self.operator != FieldOperatorInstruction::Constant
+    }
+
+    fn from_template(node: roxmltree::Node, dictionary: &Dictionary) -> Result<Self, StaticError> {
         let name = node.attribute("name").ok_or(StaticError::S1)?;
-        let id = node.attribute("id").unwrap().parse().unwrap();
-        let mandatory = {
-            let attr = node.attribute("presence").unwrap_or("true");
-            attr == "true"
+        let id = match node.attribute("id") {
+            Some(id) => Some(id.parse().map_err(|_| StaticError::S1)?),
+            None => None,
+        };
+        let mandatory = match node.attribute("presence").unwrap_or("mandatory") {
+            "mandatory" => true,
+            "optional" => false,
+            _ => return Err(StaticError::S1),
+        };
+        let field_type = match node.tag_name().name() {
+            "sequence" => FieldType::Sequence(Sequence::from_template(node, dictionary)?),
+            "group" => FieldType::Group(instructions_from_template(node, dictionary)?),
+            type_name => Template::xml_tag_to_instruction(type_name, node.attribute("charset"))?,
         };
-        let type_name = node.tag_name().name();
-        let instruction = FieldInstruction {
-            field_type: Template::xml_tag_to_instruction(type_name)?,
+        let mut instruction = FieldInstruction {
+            field_type,
             name: name.to_string(),
             id,
             mandatory,
-            operator: FieldOperatorInstruction::Constant,
+            operator: FieldOperatorInstruction::None,
+            initial_value: None,
+            dictionary: dictionary.clone(),
+            key: name.to_string(),
         };
+        if let FieldType::Primitive(primitive_type) = instruction.field_type {
+            if let Some(operator_node) = node.children().find(|child| child.is_element()) {
+                instruction.set_operator(operator_node, primitive_type)?;
+            }
+        }
         Ok(instruction)
     }
+
+    fn set_operator(
+        &mut self,
+        node: roxmltree::Node,
+        primitive_type: PrimitiveType,
+    ) -> Result<(), StaticError> {
+        self.operator = match node.tag_name().name() {
+            "constant" => FieldOperatorInstruction::Constant,
+            "default" => FieldOperatorInstruction::Default,
+            "copy" => FieldOperatorInstruction::Copy,
+            "increment" => FieldOperatorInstruction::Increment,
+            "delta" => FieldOperatorInstruction::Delta,
+            "tail" => FieldOperatorInstruction::Tail,
+            // Decimals with individual operators for exponent and mantissa.
+            _ => return Err(StaticError::S1),
+        };
+        let is_integer = match primitive_type {
+            PrimitiveType::I32 | PrimitiveType::U32 | PrimitiveType::I64 | PrimitiveType::U64 => {
+                true
+            }
+            _ => false,
+        };
+        let is_string = match primitive_type {
+            PrimitiveType::AsciiString | PrimitiveType::Utf8String | PrimitiveType::Bytes => true,
+            _ => false,
+        };
+        match self.operator {
+            FieldOperatorInstruction::Increment if !is_integer => return Err(StaticError::S2),
+            FieldOperatorInstruction::Tail if !is_string => return Err(StaticError::S2),
+            _ => (),
+        }
+        if let Some(value) = node.attribute("value") {
+            self.initial_value = Some(RawValue::parse(value, primitive_type)?);
+        }
+        match self.operator {
+            FieldOperatorInstruction::Constant if self.initial_value.is_none() => {
+                return Err(StaticError::S4);
+            }
+            FieldOperatorInstruction::Default if self.mandatory && self.initial_value.is_none() => {
+                return Err(StaticError::S5);
+            }
+            _ => (),
+        }
+        if let Some(dictionary) = node.attribute("dictionary") {
+            self.dictionary = Dictionary::from_attribute(dictionary);
+        }
+        if let Some(key) = node.attribute("key") {
+            self.key = key.to_string();
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum FieldType {
+    Primitive(PrimitiveType),
+    Sequence(Sequence),
+    Group(Vec<FieldInstruction>),
+    /// A static (i.e. with a template name) or dynamic template reference.
+    TemplateRef(Option<String>),
+}
+
+/// A *sequence* instruction, i.e. a repeating group of field instructions
+/// preceded by a length field.
+#[derive(Clone, Debug)]
+pub struct Sequence {
+    length: Box<FieldInstruction>,
+    instructions: Vec<FieldInstruction>,
+}
+
+impl Sequence {
+    /// Returns the `uInt32` length field instruction of `self`.
+    pub fn length(&self) -> &FieldInstruction {
+        &self.length
+    }
+
+    pub fn iter_items(&self) -> impl Iterator<Item = &FieldInstruction> {
+        self.instructions.iter()
+    }
+
+    fn from_template(node: roxmltree::Node, dictionary: &Dictionary) -> Result<Self, StaticError> {
+        let dictionary = node
+            .attribute("dictionary")
+            .map(Dictionary::from_attribute)
+            .unwrap_or_else(|| dictionary.clone());
+        let name = node.attribute("name").ok_or(StaticError::S1)?;
+        // The length field inherits the presence of the sequence.
+        let presence = node.attribute("presence").unwrap_or("mandatory");
+        let length = match node
+            .children()
+            .find(|child| child.is_element() && child.tag_name().name() == "length")
+        {
+            Some(length_node) => {
+                let mut length = FieldInstruction {
+                    field_type: FieldType::Primitive(PrimitiveType::U32),
+                    name: length_node
+                        .attribute("name")
+                        .map(|s| s.to_string())
+                        .unwrap_or_else(|| format!("{}Length", name)),
+                    id: match length_node.attribute("id") {
+                        Some(id) => Some(id.parse().map_err(|_| StaticError::S1)?),
+                        None => None,
+                    },
+                    mandatory: presence == "mandatory",
+                    operator: FieldOperatorInstruction::None,
+                    initial_value: None,
+                    dictionary: dictionary.clone(),
+                    key: String::new(),
+                };
+                length.key = length.name.clone();
+                if let Some(operator_node) = length_node.children().find(|c| c.is_element()) {
+                    length.set_operator(operator_node, PrimitiveType::U32)?;
+                }
+                length
+            }
+            None => FieldInstruction {
+                field_type: FieldType::Primitive(PrimitiveType::U32),
+                name: format!("{}Length", name),
+                id: None,
+                mandatory: presence == "mandatory",
+                operator: FieldOperatorInstruction::None,
+                initial_value: None,
+                dictionary: dictionary.clone(),
+                key: format!("{}Length", name),
+            },
+        };
+        Ok(Sequence {
+            length: Box::new(length),
+            instructions: instructions_from_template(node, &dictionary)?,
+        })
+    }
+}
+
+/// Parses all field instructions within a `<template>`, `<sequence>`, or
+/// `<group>` element.
+fn instructions_from_template(
+    node: roxmltree::Node,
+    dictionary: &Dictionary,
+) -> Result<Vec<FieldInstruction>, StaticError> {
+    let dictionary = node
+        .attribute("dictionary")
+        .map(Dictionary::from_attribute)
+        .unwrap_or_else(|| dictionary.clone());
+    let mut instructions = Vec::new();
+    for child in node.children().filter(|child| child.is_element()) {
+        match child.tag_name().name() {
+            "typeRef" | "length" => (),
+            "templateRef" => instructions.push(FieldInstruction {
+                field_type: FieldType::TemplateRef(
+                    child.attribute("name").map(|name| name.to_string()),
+                ),
+                name: child.attribute("name").unwrap_or_default().to_string(),
+                id: None,
+                mandatory: true,
+                operator: FieldOperatorInstruction::None,
+                initial_value: None,
+                dictionary: dictionary.clone(),
+                key: String::new(),
+            }),
+            _ => instructions.push(FieldInstruction::from_template(child, &dictionary)?),
+        }
+    }
+    Ok(instructions)
 }
 
 /// Templates are used to represent the structure of the data that is to be
@@ -89,20 +415,29 @@ pub struct Template {
     id: Option<u32>,
     /// Used for code generation.
     name: String,
+    /// The application type of this template, if any.
+    type_ref: Option<String>,
     instructions: Vec<FieldInstruction>,
 }
 
 impl Template {
+    /// Parses the first template within a `<templates>` XML document. See
+    /// [`TemplateSet::from_xml`] to parse all of them.
     pub fn new(xml_document: &str) -> Result<Template, StaticError> {
-        let document = roxmltree::Document::parse(xml_document).unwrap();
-        let container = document.root().first_element_child().unwrap();
-        let root = container.first_element_child().unwrap();
-        Template::from_xml(root)
+        let document = roxmltree::Document::parse(xml_document).map_err(|_| StaticError::S1)?;
+        let container = document
+            .root()
+            .first_element_child()
+            .ok_or(StaticError::S1)?;
+        let root = container.first_element_child().ok_or(StaticError::S1)?;
+        Template::from_xml(root, &Dictionary::Global)
     }
 
-    fn from_xml(root: roxmltree::Node) -> Result<Self, StaticError> {
-        debug_assert_eq!(root.tag_name().name(), "template");
-        let name = root.attribute("name").unwrap();
+    fn from_xml(root: roxmltree::Node, dictionary: &Dictionary) -> Result<Self, StaticError> {
+        if root.tag_name().name() != "template" {
+            return Err(StaticError::S1);
+        }
+        let name = root.attribute("name").ok_or(StaticError::S1)?;
         let id = {
             let id = root.attribute("id");
             match id {
@@ -110,30 +445,16 @@ impl Template {
                 None => None,
             }
         };
-        let mut instructions = Vec::new();
-        for node in root.children() {
-            if node.is_element() {
-                match node.tag_name().name() {
-                    "sequence" => {
-                        for child in node.children() {
-                            if child.is_element() {
-                                let instruction = FieldInstruction::from_template(child)?;
-                                instructions.push(instruction);
-                            }
-                        }
-                    }
-                    "typeRef" => (),
-                    _ => {
-                        let instruction = FieldInstruction::from_template(node)?;
-                        instructions.push(instruction);
-                    }
-                }
-            }
-        }
+        let type_ref = root
+            .children()
+            .find(|node| node.is_element() && node.tag_name().name() == "typeRef")
+            .and_then(|node| node.attribute("name"))
+            .map(|name| name.to_string());
         let template = Template {
             id,
             name: name.to_string(),
-            instructions,
+            type_ref,
+            instructions: instructions_from_template(root, dictionary)?,
         };
         Ok(template)
     }
@@ -146,30 +467,119 @@ impl Template {
         self.name.as_str()
     }
 
+    /// Returns the name of the application type of `self`, if any.
+    pub fn type_ref(&self) -> Option<&str> {
+        self.type_ref.as_deref()
+    }
+
     pub fn iter_items(&self) -> impl Iterator<Item = &FieldInstruction> {
         self.instructions.iter()
     }
 
-    fn xml_tag_to_instruction(tag: &str) -> Result<FieldType, StaticError> {
-        Ok(match tag {
-            "string" => FieldType::Primitive(PrimitiveType::AsciiString),
-            "uInt32" => FieldType::Primitive(PrimitiveType::U32),
-            "int32" => FieldType::Primitive(PrimitiveType::I32),
-            "uInt64" => FieldType::Primitive(PrimitiveType::U64),
-            "int64" => FieldType::Primitive(PrimitiveType::I64),
-            "decimal" => FieldType::Primitive(PrimitiveType::Decimal),
-            "byteVector" => FieldType::Primitive(PrimitiveType::Decimal),
-            "length" => FieldType::Primitive(PrimitiveType::U32),
+    fn xml_tag_to_instruction(tag: &str, charset: Option<&str>) -> Result<FieldType, StaticError> {
+        Ok(match (tag, charset) {
+            ("string", Some("unicode")) => FieldType::Primitive(PrimitiveType::Utf8String),
+            ("string", _) => FieldType::Primitive(PrimitiveType::AsciiString),
+            ("uInt32", _) => FieldType::Primitive(PrimitiveType::U32),
+            ("int32", _) => FieldType::Primitive(PrimitiveType::I32),
+            ("uInt64", _) => FieldType::Primitive(PrimitiveType::U64),
+            ("int64", _) => FieldType::Primitive(PrimitiveType::I64),
+            ("decimal", _) => FieldType::Primitive(PrimitiveType::Decimal),
+            ("byteVector", _) => FieldType::Primitive(PrimitiveType::Bytes),
+            ("length", _) => FieldType::Primitive(PrimitiveType::U32),
             _ => return Err(StaticError::S1),
         })
     }
+}
 
-    fn _xml_presence_attribute_to_bool(attribute: &str) -> bool {
-        match attribute {
-            "true" => true,
-            "false" => false,
-            _ => unimplemented!(),
+/// All templates within a FAST template definition file, i.e. a `<templates>`
+/// XML document.
+#[derive(Clone, Debug, Default)]
+pub struct TemplateSet {
+    templates: Vec<Template>,
+}
+
+impl TemplateSet {
+    /// Parses all templates within `xml_document`, which must have a
+    /// `<templates>` root element as defined by Appendix 1 of the FAST 1.1
+    /// specification.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefast::TemplateSet;
+    ///
+    /// let templates = TemplateSet::from_xml(
+    ///     r#"<templates xmlns="http://www.fixprotocol.org/ns/fast/td/1.1">
+    ///         <template name="Heartbeat" id="1">
+    ///             <string name="MessageType" id="35"><constant value="0"/></string>
+    ///         </template>
+    ///         <template name="Trade" id="2">
+    ///             <uInt32 name="MsgSeqNum" id="34"><increment/></uInt32>
+    ///         </template>
+    ///     </templates>"#,
+    /// )
+    /// .unwrap();
+    /// assert_eq!(templates.len(), 2);
+    /// assert_eq!(templates.template_by_id(2).unwrap().name(), "Trade");
+    /// ```
+    pub fn from_xml(xml_document: &str) -> Result<Self, StaticError> {
+        let document = roxmltree::Document::parse(xml_document).map_err(|_| StaticError::S1)?;
+        let root = document.root_element();
+        if root.tag_name().name() != "templates" {
+            return Err(StaticError::S1);
+        }
+        let dictionary = root
+            .attribute("dictionary")
+            .map(Dictionary::from_attribute)
+            .unwrap_or(Dictionary::Global);
+        let mut templates: Vec<Template> = Vec::new();
+        for node in root.children().filter(|node| node.is_element()) {
+            let template = Template::from_xml(node, &dictionary)?;
+            let is_duplicate = templates.iter().any(|other| {
+                other.name() == template.name()
+                    || (other.id().is_some() && other.id() == template.id())
+            });
+            if is_duplicate {
+                return Err(StaticError::S1);
+            }
+            templates.push(template);
         }
+        Ok(Self { templates })
+    }
+
+    /// Returns the number of templates in `self`.
+    pub fn len(&self) -> usize {
+        self.templates.len()
+    }
+
+    /// Returns `true` if and only if `self` contains no templates.
+    pub fn is_empty(&self) -> bool {
+        self.templates.is_empty()
+    }
+
+    /// Returns the template with ID `id`, if any.
+    pub fn template_by_id(&self, id: u32) -> Option<&Template> {
+        self.templates.iter().find(|t| t.id() == Some(id))
+    }
+
+    /// Returns the template named `name`, if any.
+    pub fn template_by_name(&self, name: &str) -> Option<&Template> {
+        self.templates.iter().find(|t| t.name() == name)
+    }
+
+    /// Returns an [`Iterator`] over all templates in `self`, in document order.
+    pub fn iter(&self) -> impl Iterator<Item = &Template> {
+        self.templates.iter()
+    }
+}
+
+impl IntoIterator for TemplateSet {
+    type Item = Template;
+    type IntoIter = std::vec::IntoIter<Template>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.templates.into_iter()
     }
 }
 
@@ -185,4 +595,88 @@ mod test {
         let first_field_instruction = template.instructions.get(0).unwrap();
         assert_eq!(first_field_instruction.name, "BeginString");
     }
+
+    #[test]
+    fn sequences_are_nested() {
+        let template = Template::new(SIMPLE_TEMPLATE).unwrap();
+        assert_eq!(template.type_ref(), Some("MarketDataIncrementalRefresh"));
+        assert_eq!(template.iter_items().count(), 5);
+        let sequence = match template.iter_items().last().unwrap().kind() {
+            FieldType::Sequence(sequence) => sequence.clone(),
+            _ => panic!("Expected a sequence"),
+        };
+        assert_eq!(sequence.length().name(), "NoMDEntries");
+        assert_eq!(sequence.length().id(), Some(268));
+        assert_eq!(sequence.iter_items().count(), 9);
+    }
+
+    #[test]
+    fn operators_and_initial_values() {
+        for spec in &[
+            std::include_str!("templates/unittest_mandatory.xml"),
+            std::include_str!("templates/unittest_optional.xml"),
+            std::include_str!("templates/biggest_value.xml"),
+            std::include_str!("templates/smallest_value.xml"),
+        ] {
+            let template = Template::new(spec).unwrap();
+            let int32_const = template
+                .iter_items()
+                .find(|field| field.name() == "int32_const")
+                .unwrap();
+            assert_eq!(int32_const.operator(), &FieldOperatorInstruction::Constant);
+            assert!(int32_const.initial_value().is_some());
+        }
+    }
+
+    #[test]
+    fn constant_without_initial_value_is_s4() {
+        let spec = r#"<templates><template name="T" id="1">
+            <uInt32 name="A" id="1"><constant/></uInt32>
+        </template></templates>"#;
+        assert!(matches!(Template::new(spec), Err(StaticError::S4)));
+    }
+
+    #[test]
+    fn template_set_with_multiple_templates() {
+        let spec = r#"<templates dictionary="template">
+            <template name="A" id="1">
+                <uInt32 name="X" id="1"><copy/></uInt32>
+            </template>
+            <template name="B" id="2">
+                <uInt32 name="X" id="1"><copy dictionary="global" key="Y"/></uInt32>
+                <templateRef name="A"/>
+            </template>
+        </templates>"#;
+        let templates = TemplateSet::from_xml(spec).unwrap();
+        assert_eq!(templates.len(), 2);
+        let a = templates.template_by_name("A").unwrap();
+        let x = a.iter_items().next().unwrap();
+        assert_eq!(x.dictionary(), &Dictionary::Template);
+        assert_eq!(x.key(), "X");
+        let b = templates.template_by_id(2).unwrap();
+        let x = b.iter_items().next().unwrap();
+        assert_eq!(x.dictionary(), &Dictionary::Global);
+        assert_eq!(x.key(), "Y");
+        assert!(matches!(
+            b.iter_items().nth(1).unwrap().kind(),
+            FieldType::TemplateRef(Some(name)) if name == "A"
+        ));
+    }
+
+    #[test]
+    fn template_set_with_duplicate_ids_is_s1() {
+        let spec = r#"<templates>
+            <template name="A" id="1"/>
+            <template name="B" id="1"/>
+        </templates>"#;
+        assert!(matches!(TemplateSet::from_xml(spec), Err(StaticError::S1)));
+    }
+
+    #[test]
+    fn template_set_from_bundled_specs() {
+        let templates = TemplateSet::from_xml(SIMPLE_TEMPLATE).unwrap();
+        assert_eq!(templates.len(), 1);
+        assert!(templates.template_by_name("MDRefreshSample").is_some());
+        assert!(TemplateSet::from_xml("<foo/>").is_err());
+    }
 }
@@ -1,6 +1,11 @@
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use fefix::fix_values::{CheckSum, Decimal};
 use fefix::tagvalue::{Config, Decoder};
-use fefix::Dictionary;
+use fefix::{Dictionary, FixValue};
+
+// Typical `Price <44>` and `OrderQty <38>` values of market data.
+const PRICES: &[&[u8]] = &[b"1.37215", b"104.25", b"0.000125", b"38250.5", b"99.9999"];
+const QUANTITIES: &[&[u8]] = &[b"2500000", b"100", b"1", b"15000", b"123456789012"];
 
 const FIX_MESSAGE: &[u8] = b"8=FIX.4.4|9=122|35=D|34=215|49=CLIENT12|52=20100225-19:41:57.316|56=B|1=Marcel|11=13346|21=1|40=2|44=5|54=1|59=0|60=20100225-19:39:52.020|10=072|";
 
@@ -16,6 +21,48 @@ fn criterion_benchmark(c: &mut Criterion) {
     c.bench_function("FIX tag-value decoding", |b| {
         b.iter(|| decode_fix_message(black_box(fix_decoder), black_box(FIX_MESSAGE)))
     });
+    // Roughly the size of a large market data snapshot.
+    let data = FIX_MESSAGE.repeat(64);
+    c.bench_function("FIX checksum computation", |b| {
+        b.iter(|| CheckSum::compute(black_box(&data[..])))
+    });
+    c.bench_function("FIX integer parsing", |b| {
+        b.iter(|| {
+            for qty in QUANTITIES {
+                black_box(u64::deserialize(black_box(qty)).unwrap());
+            }
+        })
+    });
+    c.bench_function("FIX integer parsing (std baseline)", |b| {
+        b.iter(|| {
+            for qty in QUANTITIES {
+                let s = std::str::from_utf8(black_box(qty)).unwrap();
+                black_box(s.parse::<u64>().unwrap());
+            }
+        })
+    });
+    c.bench_function("FIX decimal parsing", |b| {
+        b.iter(|| {
+            for price in PRICES {
+                black_box(Decimal::deserialize(black_box(price)).unwrap());
+            }
+        })
+    });
+    c.bench_function("FIX float parsing", |b| {
+        b.iter(|| {
+            for price in PRICES {
+                black_box(f64::deserialize(black_box(price)).unwrap());
+            }
+        })
+    });
+    c.bench_function("FIX float parsing (std baseline)", |b| {
+        b.iter(|| {
+            for price in PRICES {
+                let s = std::str::from_utf8(black_box(price)).unwrap();
+                black_box(s.parse::<f64>().unwrap());
+            }
+        })
+    });
 }
 
 criterion_group!(benches, criterion_benchmark);
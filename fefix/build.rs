@@ -5,46 +5,55 @@
 #[path = "src/fefix_core/build_rs_mod.rs"]
 mod fefix_core;
 
+// `fefix_core` is `no_std`-friendly and names `alloc` paths explicitly.
+extern crate alloc;
+
 use fefix_core::{codegen, dict::Dictionary};
 use std::env::var;
-use std::fs::File;
+use std::fs;
 use std::io;
-use std::io::Write;
-use std::path::PathBuf;
+use std::path::Path;
 
 fn main() -> io::Result<()> {
     println!("cargo:rerun-if-changed=src/fefix_core");
+    // Tells `fefix_core` apart from its copy within this build script, e.g. to
+    // load the built-in dictionaries from `OUT_DIR`.
+    println!("cargo:rustc-check-cfg=cfg(fefix_lib)");
+    println!("cargo:rustc-cfg=fefix_lib");
     #[cfg(feature = "fix40")]
-    codegen(Dictionary::fix40(), "fix40.rs")?;
+    codegen(Dictionary::fix40(), "fix40")?;
     #[cfg(feature = "fix41")]
-    codegen(Dictionary::fix41(), "fix41.rs")?;
+    codegen(Dictionary::fix41(), "fix41")?;
     #[cfg(feature = "fix42")]
-    codegen(Dictionary::fix42(), "fix42.rs")?;
+    codegen(Dictionary::fix42(), "fix42")?;
     #[cfg(feature = "fix43")]
-    codegen(Dictionary::fix43(), "fix43.rs")?;
+    codegen(Dictionary::fix43(), "fix43")?;
     // FIX 4.4 is always available.
-    codegen(Dictionary::fix44(), "fix44.rs")?;
+    codegen(Dictionary::fix44(), "fix44")?;
     #[cfg(feature = "fix50")]
-    codegen(Dictionary::fix50(), "fix50.rs")?;
+    codegen(Dictionary::fix50(), "fix50")?;
     #[cfg(feature = "fix50sp1")]
-    codegen(Dictionary::fix50sp1(), "fix50sp1.rs")?;
+    codegen(Dictionary::fix50sp1(), "fix50sp1")?;
     #[cfg(feature = "fix50sp2")]
-    codegen(Dictionary::fix50sp2(), "fix50sp2.rs")?;
+    codegen(Dictionary::fix50sp2(), "fix50sp2")?;
     #[cfg(feature = "fixt11")]
-    codegen(Dictionary::fixt11(), "fixt11.rs")?;
+    codegen(Dictionary::fixt11(), "fixt11")?;
     Ok(())
 }
 
-fn codegen(fix_dictionary: Dictionary, filename: &str) -> io::Result<()> {
+fn codegen(fix_dictionary: Dictionary, module_name: &str) -> io::Result<()> {
     // All generated code must go in `OUT_DIR`. We avoid writing directly to
     // `src/` to avoid compilation issues on `crates.io`, which disallows
     // writing.
-    let dir = PathBuf::from(var("OUT_DIR").unwrap());
+    let dir = var("OUT_DIR").unwrap();
     let codegen_settings = &mut codegen::Settings::default();
     codegen_settings.set_fefix_crate_name("crate");
-    let code = codegen::gen_definitions(fix_dictionary, &codegen_settings);
-    let path = dir.join(filename);
-    let file = &mut File::create(path)?;
-    file.write_all(code.as_bytes())?;
+    codegen_settings.set_generate_messages(true);
+    codegen_settings.set_generate_message_fields(true);
+    codegen_settings.set_module_name(module_name);
+    codegen::generate_to(&dir, &fix_dictionary, codegen_settings)?;
+    // Parsed once here, so that the library itself doesn't need to parse XML.
+    let path = Path::new(&dir).join(format!("{}.dict", module_name));
+    fs::write(path, fix_dictionary.to_bytes())?;
     Ok(())
 }
@@ -0,0 +1,8 @@
+//! Application-level utilities on top of FIX messages, e.g. order state
+//! tracking and market data books.
+
+mod order_book;
+mod order_tracker;
+
+pub use order_book::{BookKind, BookSide, BookUpdate, Level, OrderBook, OrderBookError};
+pub use order_tracker::{Order, OrderTracker, OrderTrackerError};
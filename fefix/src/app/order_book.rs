@@ -0,0 +1,674 @@
+use crate::definitions::{decode_optional, decode_required, fix44, TypedMessageError};
+use crate::dict::IsFieldDefinition;
+use crate::fix_values::Decimal;
+use crate::tagvalue::{FieldAccess, RepeatingGroup};
+use std::collections::HashMap;
+use std::fmt;
+
+type UpdateHandler<'h> = Box<dyn FnMut(&BookUpdate) + 'h>;
+
+/// How an [`OrderBook`] identifies its entries.
+///
+/// Please note that [`BookKind`] is marked with `#[non_exhaustive]`, which
+/// future-proofs the enumeration type in case more variants are added.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum BookKind {
+    /// Every entry is an aggregated price level, identified by its side and
+    /// `MDEntryPx <270>`.
+    PriceLevels,
+    /// Every entry is a single order, identified by its `MDEntryID <278>`.
+    /// Price levels are aggregated by the [`OrderBook`] itself.
+    OrderByOrder,
+}
+
+/// The side of an [`OrderBook`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum BookSide {
+    /// `MDEntryType <269>` equal to `0`.
+    Bid,
+    /// `MDEntryType <269>` equal to `1`.
+    Offer,
+}
+
+/// A price level of an [`OrderBook`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Level {
+    price: Decimal,
+    size: Decimal,
+    num_orders: Option<u32>,
+}
+
+impl Level {
+    /// Returns the price of `self`.
+    pub fn price(&self) -> Decimal {
+        self.price
+    }
+
+    /// Returns the total size of `self`.
+    pub fn size(&self) -> Decimal {
+        self.size
+    }
+
+    /// Returns the number of orders at `self`, if known. It's always known in
+    /// order-by-order books, and otherwise only if the counterparty sends
+    /// `NumberOfOrders <346>`.
+    pub fn num_orders(&self) -> Option<u32> {
+        self.num_orders
+    }
+}
+
+/// A change to an [`OrderBook`], as passed to the handler of
+/// [`OrderBook::set_update_handler`].
+///
+/// Please note that [`BookUpdate`] is marked with `#[non_exhaustive]`, which
+/// future-proofs the enumeration type in case more variants are added.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum BookUpdate {
+    /// A `MarketDataSnapshotFullRefresh <W>` replaced all levels.
+    Snapshot,
+    /// A level within the depth of the book changed.
+    Level {
+        /// The side of the level.
+        side: BookSide,
+        /// The position of the level within `side`, starting from 0 for the
+        /// best price.
+        position: usize,
+        /// The price of the level.
+        price: Decimal,
+        /// The new total size of the level, zero if it was removed.
+        size: Decimal,
+    },
+}
+
+/// The error type returned by [`OrderBook`] methods.
+///
+/// Please note that [`OrderBookError`] is marked with `#[non_exhaustive]`,
+/// which future-proofs the enumeration type in case more variants are added.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum OrderBookError {
+    /// A field is missing or invalid.
+    Message(TypedMessageError),
+    /// An entry changes or deletes an order that isn't in the book.
+    UnknownEntry {
+        /// The `MDEntryID <278>` of the entry.
+        entry_id: String,
+    },
+    /// An entry deletes a price level that isn't in the book.
+    UnknownLevel {
+        /// The side of the entry.
+        side: BookSide,
+        /// The `MDEntryPx <270>` of the entry.
+        price: Decimal,
+    },
+}
+
+impl fmt::Display for OrderBookError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Message(err) => write!(f, "{}", err),
+            Self::UnknownEntry { entry_id } => write!(f, "Unknown MDEntryID '{}'", entry_id),
+            Self::UnknownLevel { side, price } => {
+                write!(f, "Unknown {:?} level at price {}", side, price)
+            }
+        }
+    }
+}
+
+impl std::error::Error for OrderBookError {}
+
+impl From<TypedMessageError> for OrderBookError {
+    fn from(err: TypedMessageError) -> Self {
+        Self::Message(err)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct BookOrder {
+    side: BookSide,
+    price: Decimal,
+    size: Decimal,
+}
+
+/// Maintains the bids and offers of a single instrument from
+/// `MarketDataSnapshotFullRefresh <W>` and `MarketDataIncrementalRefresh <X>`
+/// messages.
+///
+/// Depending on its [`BookKind`], an [`OrderBook`] either keeps the price
+/// levels that the counterparty sends, or aggregates single orders into price
+/// levels. Entries of other `MDEntryType <269>` values (e.g. trades) and, if
+/// [`OrderBook::set_symbol`] was called, of other instruments are ignored.
+///
+/// The book always keeps all levels, but [`OrderBook::set_depth`] limits how
+/// many of them [`OrderBook::bids`] and [`OrderBook::offers`] return and which
+/// changes are passed to the handler of [`OrderBook::set_update_handler`].
+///
+/// If an entry can't be applied, the previous entries of the same message stay
+/// applied and the book is likely out of sync with the counterparty: a new
+/// snapshot should then be requested.
+///
+/// # Examples
+///
+/// ```
+/// use fefix::app::{BookKind, BookSide, BookUpdate, OrderBook};
+/// use fefix::fix_values::Decimal;
+/// use fefix::tagvalue::{Config, Decoder};
+/// use fefix::Dictionary;
+///
+/// let mut updates = Vec::new();
+/// let mut book = OrderBook::new(BookKind::PriceLevels);
+/// book.set_depth(5);
+/// book.set_update_handler(|update| updates.push(*update));
+///
+/// let mut decoder = Decoder::<Config>::new(Dictionary::fix44());
+/// decoder.config_mut().set_separator(b'|');
+/// let snapshot = b"8=FIX.4.4|9=62|35=W|55=EUR/USD|268=2|269=0|270=1.1|271=5|269=1|270=1.2|271=7|10=000|";
+/// book.on_snapshot(&decoder.decode(&snapshot[..]).unwrap()).unwrap();
+/// let refresh = b"8=FIX.4.4|9=37|35=X|268=1|279=1|269=0|270=1.1|271=8|10=000|";
+/// book.on_incremental_refresh(&decoder.decode(&refresh[..]).unwrap()).unwrap();
+/// drop(book);
+///
+/// assert_eq!(
+///     updates,
+///     vec![
+///         BookUpdate::Snapshot,
+///         BookUpdate::Level {
+///             side: BookSide::Bid,
+///             position: 0,
+///             price: Decimal::new(11, 1),
+///             size: Decimal::new(8, 0),
+///         },
+///     ]
+/// );
+/// ```
+pub struct OrderBook<'h> {
+    kind: BookKind,
+    depth: Option<usize>,
+    symbol: Option<String>,
+    // Best price first.
+    bids: Vec<Level>,
+    offers: Vec<Level>,
+    orders: HashMap<String, BookOrder>,
+    update_handler: Option<UpdateHandler<'h>>,
+}
+
+impl<'h> OrderBook<'h> {
+    /// Creates a new, empty [`OrderBook`] of the given `kind`, without any
+    /// depth limit.
+    pub fn new(kind: BookKind) -> Self {
+        Self {
+            kind,
+            depth: None,
+            symbol: None,
+            bids: Vec::new(),
+            offers: Vec::new(),
+            orders: HashMap::new(),
+            update_handler: None,
+        }
+    }
+
+    /// Returns the [`BookKind`] of `self`.
+    pub fn kind(&self) -> BookKind {
+        self.kind
+    }
+
+    /// Returns the depth limit of `self`, if any.
+    pub fn depth(&self) -> Option<usize> {
+        self.depth
+    }
+
+    /// Limits `self` to the best `depth` levels of each side.
+    pub fn set_depth(&mut self, depth: usize) {
+        self.depth = Some(depth);
+    }
+
+    /// Returns the `Symbol <55>` of `self`, if any.
+    pub fn symbol(&self) -> Option<&str> {
+        self.symbol.as_deref()
+    }
+
+    /// Ignores all entries with a `Symbol <55>` other than `symbol`. Messages
+    /// and entries without `Symbol <55>` are always applied.
+    pub fn set_symbol(&mut self, symbol: impl Into<String>) {
+        self.symbol = Some(symbol.into());
+    }
+
+    /// Calls `handler` on every change to `self`, replacing any previous
+    /// handler.
+    pub fn set_update_handler<F>(&mut self, handler: F)
+    where
+        F: FnMut(&BookUpdate) + 'h,
+    {
+        self.update_handler = Some(Box::new(handler));
+    }
+
+    /// Returns the bid levels of `self` within its depth, best price first.
+    pub fn bids(&self) -> &[Level] {
+        self.within_depth(&self.bids[..])
+    }
+
+    /// Returns the offer levels of `self` within its depth, best price first.
+    pub fn offers(&self) -> &[Level] {
+        self.within_depth(&self.offers[..])
+    }
+
+    /// Returns the best bid level of `self`, if any.
+    pub fn best_bid(&self) -> Option<&Level> {
+        self.bids().first()
+    }
+
+    /// Returns the best offer level of `self`, if any.
+    pub fn best_offer(&self) -> Option<&Level> {
+        self.offers().first()
+    }
+
+    /// Removes all levels and orders from `self`.
+    pub fn clear(&mut self) {
+        self.bids.clear();
+        self.offers.clear();
+        self.orders.clear();
+    }
+
+    /// Replaces all levels of `self` with the entries of `msg`, a
+    /// `MarketDataSnapshotFullRefresh <W>`.
+    pub fn on_snapshot<M>(&mut self, msg: &M) -> Result<(), OrderBookError>
+    where
+        M: FieldAccess,
+    {
+        let symbol: Option<&str> = decode_optional(msg.fv_opt(fix44::SYMBOL), fix44::SYMBOL)?;
+        if !self.is_own_symbol(symbol) {
+            return Ok(());
+        }
+        let group = md_entries(msg)?;
+        self.clear();
+        for entry in group.entries() {
+            let entry = Entry::decode(&entry, fix44::MdUpdateAction::New)?;
+            self.apply(entry, false)?;
+        }
+        self.notify(BookUpdate::Snapshot);
+        Ok(())
+    }
+
+    /// Applies the entries of `msg`, a `MarketDataIncrementalRefresh <X>`, to
+    /// `self`.
+    pub fn on_incremental_refresh<M>(&mut self, msg: &M) -> Result<(), OrderBookError>
+    where
+        M: FieldAccess,
+    {
+        let group = md_entries(msg)?;
+        for entry in group.entries() {
+            let action =
+                decode_required(entry.fv(fix44::MD_UPDATE_ACTION), fix44::MD_UPDATE_ACTION)?;
+            let entry = Entry::decode(&entry, action)?;
+            if self.is_own_symbol(entry.symbol.as_deref()) {
+                self.apply(entry, true)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn is_own_symbol(&self, symbol: Option<&str>) -> bool {
+        match (&self.symbol, symbol) {
+            (Some(own), Some(symbol)) => own == symbol,
+            _ => true,
+        }
+    }
+
+    fn within_depth<'a>(&self, levels: &'a [Level]) -> &'a [Level] {
+        &levels[..self
+            .depth
+            .map_or(levels.len(), |depth| depth.min(levels.len()))]
+    }
+
+    fn notify(&mut self, update: BookUpdate) {
+        if let Some(handler) = self.update_handler.as_mut() {
+            handler(&update);
+        }
+    }
+
+    fn apply(&mut self, entry: Entry, notify: bool) -> Result<(), OrderBookError> {
+        match self.kind {
+            BookKind::PriceLevels => self.apply_level(entry, notify),
+            BookKind::OrderByOrder => self.apply_order(entry, notify),
+        }
+    }
+
+    fn apply_level(&mut self, entry: Entry, notify: bool) -> Result<(), OrderBookError> {
+        let side = match entry.side()? {
+            Some(side) => side,
+            None => return Ok(()),
+        };
+        let price = entry.price()?;
+        let levels = self.levels_mut(side);
+        let position = find_level(levels, side, price);
+        let size = match (entry.action, position) {
+            (fix44::MdUpdateAction::Delete, Ok(i)) => {
+                levels.remove(i);
+                Decimal::ZERO
+            }
+            (fix44::MdUpdateAction::Delete, Err(_)) => {
+                return Err(OrderBookError::UnknownLevel { side, price });
+            }
+            (_, position) => {
+                let level = Level {
+                    price,
+                    size: entry.size()?,
+                    num_orders: entry.num_orders,
+                };
+                match position {
+                    Ok(i) => levels[i] = level,
+                    Err(i) => levels.insert(i, level),
+                }
+                level.size
+            }
+        };
+        if notify {
+            self.notify_level(side, position.unwrap_or_else(|i| i), price, size);
+        }
+        Ok(())
+    }
+
+    fn apply_order(&mut self, entry: Entry, notify: bool) -> Result<(), OrderBookError> {
+        let entry_id: &str = entry
+            .entry_id
+            .as_deref()
+            .ok_or_else(|| TypedMessageError::MissingField(fix44::MD_ENTRY_ID.tag()))?;
+        let previous = self.orders.get(entry_id).cloned();
+        let order = match (entry.action, previous) {
+            (fix44::MdUpdateAction::New, _) => match entry.side()? {
+                Some(side) => Some(BookOrder {
+                    side,
+                    price: entry.price()?,
+                    size: entry.size()?,
+                }),
+                None => return Ok(()),
+            },
+            (fix44::MdUpdateAction::Change, Some(previous)) => Some(BookOrder {
+                side: previous.side,
+                price: entry.price.unwrap_or(previous.price),
+                size: entry.size.unwrap_or(previous.size),
+            }),
+            (fix44::MdUpdateAction::Delete, Some(_)) => None,
+            (_, None) => {
+                return Err(OrderBookError::UnknownEntry {
+                    entry_id: entry_id.to_string(),
+                });
+            }
+        };
+        if let Some(previous) = self.orders.remove(entry_id) {
+            self.add_to_level(&previous, false, notify)?;
+        }
+        if let Some(order) = order {
+            self.add_to_level(&order, true, notify)?;
+            self.orders.insert(entry_id.to_string(), order);
+        }
+        Ok(())
+    }
+
+    fn add_to_level(
+        &mut self,
+        order: &BookOrder,
+        is_added: bool,
+        notify: bool,
+    ) -> Result<(), OrderBookError> {
+        let overflow = || TypedMessageError::InvalidField(fix44::MD_ENTRY_SIZE.tag());
+        let levels = self.levels_mut(order.side);
+        let position = find_level(levels, order.side, order.price);
+        let size = match (position, is_added) {
+            (Ok(i), true) => {
+                let level = &mut levels[i];
+                level.size = level.size.checked_add(order.size).ok_or_else(overflow)?;
+                level.num_orders = level.num_orders.map(|n| n + 1);
+                level.size
+            }
+            (Ok(i), false) => {
+                let level = &mut levels[i];
+                level.size = level.size.checked_sub(order.size).ok_or_else(overflow)?;
+                level.num_orders = level.num_orders.map(|n| n - 1);
+                if level.num_orders == Some(0) {
+                    levels.remove(i);
+                    Decimal::ZERO
+                } else {
+                    level.size
+                }
+            }
+            (Err(i), true) => {
+                levels.insert(
+                    i,
+                    Level {
+                        price: order.price,
+                        size: order.size,
+                        num_orders: Some(1),
+                    },
+                );
+                order.size
+            }
+            (Err(_), false) => Decimal::ZERO,
+        };
+        if notify {
+            let position = position.unwrap_or_else(|i| i);
+            self.notify_level(order.side, position, order.price, size);
+        }
+        Ok(())
+    }
+
+    fn notify_level(&mut self, side: BookSide, position: usize, price: Decimal, size: Decimal) {
+        let is_within_depth = match self.depth {
+            Some(depth) => position < depth,
+            None => true,
+        };
+        if is_within_depth {
+            self.notify(BookUpdate::Level {
+                side,
+                position,
+                price,
+                size,
+            });
+        }
+    }
+
+    fn levels_mut(&mut self, side: BookSide) -> &mut Vec<Level> {
+        match side {
+            BookSide::Bid => &mut self.bids,
+            BookSide::Offer => &mut self.offers,
+        }
+    }
+}
+
+impl<'h> fmt::Debug for OrderBook<'h> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("OrderBook")
+            .field("kind", &self.kind)
+            .field("depth", &self.depth)
+            .field("symbol", &self.symbol)
+            .field("bids", &self.bids)
+            .field("offers", &self.offers)
+            .finish()
+    }
+}
+
+fn md_entries<M>(msg: &M) -> Result<M::Group, TypedMessageError>
+where
+    M: FieldAccess,
+{
+    match msg.group_opt(fix44::NO_MD_ENTRIES) {
+        Some(Ok(group)) => Ok(group),
+        Some(Err(_)) => Err(TypedMessageError::InvalidField(fix44::NO_MD_ENTRIES.tag())),
+        None => Err(TypedMessageError::MissingField(fix44::NO_MD_ENTRIES.tag())),
+    }
+}
+
+// Bids are sorted by descending price, offers by ascending price.
+fn find_level(levels: &[Level], side: BookSide, price: Decimal) -> Result<usize, usize> {
+    levels.binary_search_by(|level| match side {
+        BookSide::Bid => price.value_cmp(&level.price),
+        BookSide::Offer => level.price.value_cmp(&price),
+    })
+}
+
+/// A `NoMDEntries <268>` entry, whose fields may or may not be required
+/// depending on its `MDUpdateAction <279>` and the [`BookKind`].
+struct Entry {
+    action: fix44::MdUpdateAction,
+    entry_type: Option<fix44::MdEntryType>,
+    price: Option<Decimal>,
+    size: Option<Decimal>,
+    entry_id: Option<String>,
+    num_orders: Option<u32>,
+    symbol: Option<String>,
+}
+
+impl Entry {
+    fn decode<E>(entry: &E, action: fix44::MdUpdateAction) -> Result<Self, TypedMessageError>
+    where
+        E: FieldAccess,
+    {
+        Ok(Self {
+            action,
+            entry_type: decode_optional(entry.fv_opt(fix44::MD_ENTRY_TYPE), fix44::MD_ENTRY_TYPE)?,
+            price: decode_optional(entry.fv_opt(fix44::MD_ENTRY_PX), fix44::MD_ENTRY_PX)?,
+            size: decode_optional(entry.fv_opt(fix44::MD_ENTRY_SIZE), fix44::MD_ENTRY_SIZE)?,
+            entry_id: decode_optional(entry.fv_opt(fix44::MD_ENTRY_ID), fix44::MD_ENTRY_ID)?
+                .map(|id: &str| id.to_string()),
+            num_orders: decode_optional(
+                entry.fv_opt(fix44::NUMBER_OF_ORDERS),
+                fix44::NUMBER_OF_ORDERS,
+            )?,
+            symbol: decode_optional(entry.fv_opt(fix44::SYMBOL), fix44::SYMBOL)?
+                .map(|symbol: &str| symbol.to_string()),
+        })
+    }
+
+    /// Returns [`None`] for entries that aren't bids or offers.
+    fn side(&self) -> Result<Option<BookSide>, TypedMessageError> {
+        match self.entry_type {
+            Some(fix44::MdEntryType::Bid) => Ok(Some(BookSide::Bid)),
+            Some(fix44::MdEntryType::Offer) => Ok(Some(BookSide::Offer)),
+            Some(_) => Ok(None),
+            None => Err(TypedMessageError::MissingField(fix44::MD_ENTRY_TYPE.tag())),
+        }
+    }
+
+    fn price(&self) -> Result<Decimal, TypedMessageError> {
+        self.price
+            .ok_or_else(|| TypedMessageError::MissingField(fix44::MD_ENTRY_PX.tag()))
+    }
+
+    fn size(&self) -> Result<Decimal, TypedMessageError> {
+        self.size
+            .ok_or_else(|| TypedMessageError::MissingField(fix44::MD_ENTRY_SIZE.tag()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tagvalue::test_decoder;
+    use std::cell::RefCell;
+
+    // Fills in `BodyLength <9>`, which must be right for the decoder.
+    fn message(body: &str) -> Vec<u8> {
+        format!("8=FIX.4.4|9={}|{}10=000|", body.len(), body).into_bytes()
+    }
+
+    fn level(price: i64, size: i64, num_orders: Option<u32>) -> Level {
+        Level {
+            price: Decimal::new(price, 0),
+            size: Decimal::new(size, 0),
+            num_orders,
+        }
+    }
+
+    #[test]
+    fn price_levels_are_sorted() {
+        let mut decoder = test_decoder();
+        let mut book = OrderBook::new(BookKind::PriceLevels);
+        let data = message("35=W|268=4|269=0|270=99|271=1|269=0|270=100|271=2|269=1|270=102|271=3|269=1|270=101|271=4|");
+        book.on_snapshot(&decoder.decode(&data[..]).unwrap())
+            .unwrap();
+        assert_eq!(book.bids(), &[level(100, 2, None), level(99, 1, None)]);
+        assert_eq!(book.offers(), &[level(101, 4, None), level(102, 3, None)]);
+
+        let data = message("35=X|268=3|279=2|269=0|270=100|279=0|269=1|270=100|271=5|346=2|279=1|269=1|270=102|271=6|");
+        book.on_incremental_refresh(&decoder.decode(&data[..]).unwrap())
+            .unwrap();
+        assert_eq!(book.best_bid(), Some(&level(99, 1, None)));
+        assert_eq!(
+            book.offers(),
+            &[
+                level(100, 5, Some(2)),
+                level(101, 4, None),
+                level(102, 6, None)
+            ]
+        );
+
+        let data = message("35=X|268=1|279=2|269=0|270=100|");
+        assert_eq!(
+            book.on_incremental_refresh(&decoder.decode(&data[..]).unwrap()),
+            Err(OrderBookError::UnknownLevel {
+                side: BookSide::Bid,
+                price: Decimal::new(100, 0)
+            })
+        );
+    }
+
+    #[test]
+    fn orders_are_aggregated() {
+        let mut decoder = test_decoder();
+        let updates = RefCell::new(Vec::new());
+        let mut book = OrderBook::new(BookKind::OrderByOrder);
+        book.set_update_handler(|update| updates.borrow_mut().push(*update));
+        let data = message("35=X|268=3|279=0|269=0|278=a|270=10|271=1|279=0|269=0|278=b|270=10|271=2|279=0|269=2|278=c|270=10|271=3|");
+        book.on_incremental_refresh(&decoder.decode(&data[..]).unwrap())
+            .unwrap();
+        assert_eq!(book.bids(), &[level(10, 3, Some(2))]);
+
+        let data = message("35=X|268=2|279=1|278=a|270=11|279=2|278=b|");
+        book.on_incremental_refresh(&decoder.decode(&data[..]).unwrap())
+            .unwrap();
+        assert_eq!(book.bids(), &[level(11, 1, Some(1))]);
+        assert!(book.offers().is_empty());
+
+        let data = message("35=X|268=1|279=2|278=b|");
+        assert_eq!(
+            book.on_incremental_refresh(&decoder.decode(&data[..]).unwrap()),
+            Err(OrderBookError::UnknownEntry {
+                entry_id: "b".to_string()
+            })
+        );
+        drop(book);
+        let sizes: Vec<(usize, i64)> = updates
+            .into_inner()
+            .into_iter()
+            .map(|update| match update {
+                BookUpdate::Level { position, size, .. } => (position, size.mantissa()),
+                BookUpdate::Snapshot => unreachable!(),
+            })
+            .collect();
+        // "a" is moved from 10 to 11, then "b" is deleted.
+        assert_eq!(sizes, vec![(0, 1), (0, 3), (0, 2), (0, 1), (1, 0)]);
+    }
+
+    #[test]
+    fn depth_and_symbol() {
+        let mut decoder = test_decoder();
+        let updates = RefCell::new(Vec::new());
+        let mut book = OrderBook::new(BookKind::PriceLevels);
+        book.set_depth(1);
+        book.set_symbol("A");
+        book.set_update_handler(|update| updates.borrow_mut().push(*update));
+        let data = message("35=W|55=B|268=1|269=0|270=1|271=1|");
+        book.on_snapshot(&decoder.decode(&data[..]).unwrap())
+            .unwrap();
+        let data = message(
+            "35=X|268=3|279=0|269=0|270=2|271=1|55=A|279=0|269=0|270=1|271=1|279=0|269=0|270=3|271=1|55=B|",
+        );
+        book.on_incremental_refresh(&decoder.decode(&data[..]).unwrap())
+            .unwrap();
+        assert_eq!(book.bids(), &[level(2, 1, None)]);
+        assert_eq!(updates.borrow().len(), 1);
+        assert_eq!(book.best_offer(), None);
+    }
+}
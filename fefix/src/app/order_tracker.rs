@@ -0,0 +1,761 @@
+use crate::definitions::{decode_optional, decode_required, fix44, TypedMessageError};
+use crate::dict::IsFieldDefinition;
+use crate::fix_values::Decimal;
+use crate::tagvalue::FieldAccess;
+use nohash_hasher::IntMap;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+/// The state of a single order, as maintained by an [`OrderTracker`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Order {
+    // Oldest first.
+    cl_ord_ids: Vec<String>,
+    pending_cl_ord_id: Option<String>,
+    pending_order_qty: Option<Decimal>,
+    order_id: Option<String>,
+    symbol: Option<String>,
+    side: Option<fix44::Side>,
+    ord_status: fix44::OrdStatus,
+    order_qty: Option<Decimal>,
+    cum_qty: Decimal,
+    leaves_qty: Decimal,
+    avg_px: Decimal,
+    exec_ids: HashSet<String>,
+}
+
+impl Order {
+    fn new() -> Self {
+        Self {
+            cl_ord_ids: Vec::new(),
+            pending_cl_ord_id: None,
+            pending_order_qty: None,
+            order_id: None,
+            symbol: None,
+            side: None,
+            ord_status: fix44::OrdStatus::PendingNew,
+            order_qty: None,
+            cum_qty: Decimal::ZERO,
+            leaves_qty: Decimal::ZERO,
+            avg_px: Decimal::ZERO,
+            exec_ids: HashSet::new(),
+        }
+    }
+
+    /// Returns the current `ClOrdID <11>` of `self`, i.e. the last one in
+    /// [`Order::cl_ord_ids`].
+    pub fn cl_ord_id(&self) -> Option<&str> {
+        self.cl_ord_ids.last().map(String::as_str)
+    }
+
+    /// Returns all the `ClOrdID <11>` values that `self` went through because
+    /// of cancels and replaces, oldest first.
+    pub fn cl_ord_ids(&self) -> &[String] {
+        &self.cl_ord_ids[..]
+    }
+
+    /// Returns the `ClOrdID <11>` of the `OrderCancelReplaceRequest <G>` of
+    /// `self` that is still waiting for a response, if any.
+    pub fn pending_cl_ord_id(&self) -> Option<&str> {
+        self.pending_cl_ord_id.as_deref()
+    }
+
+    /// Returns the `OrderID <37>` that the counterparty assigned to `self`, if
+    /// known.
+    pub fn order_id(&self) -> Option<&str> {
+        self.order_id.as_deref()
+    }
+
+    /// Returns the `Symbol <55>` of `self`, if known.
+    pub fn symbol(&self) -> Option<&str> {
+        self.symbol.as_deref()
+    }
+
+    /// Returns the `Side <54>` of `self`, if known.
+    pub fn side(&self) -> Option<fix44::Side> {
+        self.side
+    }
+
+    /// Returns the `OrdStatus <39>` of `self`, as of the last
+    /// `ExecutionReport <8>`.
+    pub fn ord_status(&self) -> fix44::OrdStatus {
+        self.ord_status
+    }
+
+    /// Returns the `OrderQty <38>` of `self`, if known. It changes when a
+    /// replace is accepted.
+    pub fn order_qty(&self) -> Option<Decimal> {
+        self.order_qty
+    }
+
+    /// Returns the total quantity filled so far, i.e. `CumQty <14>`.
+    pub fn cum_qty(&self) -> Decimal {
+        self.cum_qty
+    }
+
+    /// Returns the quantity still open for execution, i.e. `LeavesQty <151>`.
+    pub fn leaves_qty(&self) -> Decimal {
+        self.leaves_qty
+    }
+
+    /// Returns the average price of all fills so far, i.e. `AvgPx <6>`.
+    pub fn avg_px(&self) -> Decimal {
+        self.avg_px
+    }
+
+    /// Returns `true` if `self` is filled, canceled, rejected or expired, and
+    /// thus won't change anymore (except for trade corrections).
+    pub fn is_terminal(&self) -> bool {
+        is_terminal(self.ord_status)
+    }
+}
+
+/// The error type returned by [`OrderTracker`] methods. The [`Order`] that the
+/// offending message refers to is left untouched.
+///
+/// Please note that [`OrderTrackerError`] is marked with `#[non_exhaustive]`,
+/// which future-proofs the enumeration type in case more variants are added.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum OrderTrackerError {
+    /// A field is missing or invalid.
+    Message(TypedMessageError),
+    /// A request refers to an order that the [`OrderTracker`] doesn't know
+    /// about.
+    UnknownOrder {
+        /// The `OrigClOrdID <41>` of the request.
+        orig_cl_ord_id: String,
+    },
+    /// The `ExecID <17>` of an `ExecutionReport <8>` has already been
+    /// processed, e.g. because the report was resent.
+    DuplicateExecId {
+        /// The duplicate `ExecID <17>`.
+        exec_id: String,
+    },
+    /// The order state model doesn't allow the order to change its
+    /// `OrdStatus <39>`, e.g. because the order was already filled.
+    IllegalTransition {
+        /// The current `OrdStatus <39>` of the order.
+        from: fix44::OrdStatus,
+        /// The `OrdStatus <39>` of the report.
+        to: fix44::OrdStatus,
+    },
+    /// The `CumQty <14>` of an `ExecutionReport <8>` doesn't add up with the
+    /// previous `CumQty <14>` and `LastQty <32>`.
+    CumQtyMismatch {
+        /// The `CumQty <14>` that the order state model expects.
+        expected: Decimal,
+        /// The `CumQty <14>` of the report.
+        actual: Decimal,
+    },
+    /// The `LeavesQty <151>` of an `ExecutionReport <8>` differs from
+    /// `OrderQty <38>` minus `CumQty <14>`, or it's not zero for an order
+    /// that's no longer working.
+    LeavesQtyMismatch {
+        /// The `LeavesQty <151>` that the order state model expects.
+        expected: Decimal,
+        /// The `LeavesQty <151>` of the report.
+        actual: Decimal,
+    },
+}
+
+impl fmt::Display for OrderTrackerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Message(err) => write!(f, "{}", err),
+            Self::UnknownOrder { orig_cl_ord_id } => {
+                write!(f, "Unknown order with ClOrdID '{}'", orig_cl_ord_id)
+            }
+            Self::DuplicateExecId { exec_id } => write!(f, "Duplicate ExecID '{}'", exec_id),
+            Self::IllegalTransition { from, to } => {
+                write!(
+                    f,
+                    "Illegal OrdStatus transition from {:?} to {:?}",
+                    from, to
+                )
+            }
+            Self::CumQtyMismatch { expected, actual } => {
+                write!(f, "CumQty is {}, but {} was expected", actual, expected)
+            }
+            Self::LeavesQtyMismatch { expected, actual } => {
+                write!(f, "LeavesQty is {}, but {} was expected", actual, expected)
+            }
+        }
+    }
+}
+
+impl std::error::Error for OrderTrackerError {}
+
+impl From<TypedMessageError> for OrderTrackerError {
+    fn from(err: TypedMessageError) -> Self {
+        Self::Message(err)
+    }
+}
+
+/// Maintains the state of orders from the `ExecutionReport <8>` messages the
+/// counterparty sends about them, according to the FIX order state model.
+///
+/// Orders are identified by any of their `ClOrdID <11>` values, which change
+/// over time with `OrderCancelReplaceRequest <G>` and cancel requests: an
+/// [`OrderTracker`] follows such chains through `OrigClOrdID <41>`. Every
+/// report is verified against the current state of its order (e.g. `CumQty
+/// <14>` must grow by `LastQty <32>` on each fill, and filled orders can't
+/// become working again); inconsistent reports result in an
+/// [`OrderTrackerError`] and don't affect the order.
+///
+/// All fields are read with FIX 4.4 definitions, which later versions share.
+/// [`OrderTracker`] is sans-IO: it never sends any message on its own.
+///
+/// # Examples
+///
+/// ```
+/// use fefix::app::OrderTracker;
+/// use fefix::definitions::{fix44, Execution, ExecutionReportBuilder};
+/// use fefix::fix_values::Decimal;
+/// use fefix::tagvalue::{Config, Decoder, Encoder};
+/// use fefix::Dictionary;
+///
+/// let mut encoder = Encoder::<Config>::default();
+/// let mut decoder = Decoder::<Config>::new(Dictionary::fix44());
+/// let mut tracker = OrderTracker::new();
+/// let fill = Execution::Trade {
+///     last_qty: Decimal::new(30, 0),
+///     last_px: Decimal::new(995, 1),
+///     leaves_qty: Decimal::new(70, 0),
+/// };
+/// let mut report = ExecutionReportBuilder::new("O-1", "E-1", "AAPL", fix44::Side::Buy, fill);
+/// report.set_cl_ord_id("C-1");
+/// report.set_order_qty(Decimal::new(100, 0));
+/// report.set_cum_qty(Decimal::new(30, 0), Decimal::new(995, 1));
+///
+/// let mut buffer = Vec::new();
+/// let mut msg = encoder.start_message(b"FIX.4.4", &mut buffer, ExecutionReportBuilder::MSG_TYPE);
+/// report.encode(&mut msg);
+/// let data = msg.wrap().to_vec();
+/// tracker.on_execution_report(&decoder.decode(&data[..]).unwrap()).unwrap();
+///
+/// let order = tracker.get("C-1").unwrap();
+/// assert_eq!(order.ord_status(), fix44::OrdStatus::PartiallyFilled);
+/// assert_eq!(order.leaves_qty(), Decimal::new(70, 0));
+/// // The same report again.
+/// assert!(tracker.on_execution_report(&decoder.decode(&data[..]).unwrap()).is_err());
+/// ```
+#[derive(Debug, Clone)]
+pub struct OrderTracker {
+    orders: IntMap<u64, Order>,
+    next_key: u64,
+    keys_by_cl_ord_id: HashMap<String, u64>,
+    keys_by_order_id: HashMap<String, u64>,
+}
+
+impl OrderTracker {
+    /// Creates a new [`OrderTracker`] without any orders.
+    pub fn new() -> Self {
+        Self {
+            orders: IntMap::default(),
+            next_key: 0,
+            keys_by_cl_ord_id: HashMap::new(),
+            keys_by_order_id: HashMap::new(),
+        }
+    }
+
+    /// Returns the number of orders in `self`.
+    pub fn len(&self) -> usize {
+        self.orders.len()
+    }
+
+    /// Returns `true` if `self` has no orders.
+    pub fn is_empty(&self) -> bool {
+        self.orders.is_empty()
+    }
+
+    /// Returns the order with any `ClOrdID <11>` equal to `cl_ord_id`, if any.
+    pub fn get(&self, cl_ord_id: &str) -> Option<&Order> {
+        self.keys_by_cl_ord_id
+            .get(cl_ord_id)
+            .and_then(|key| self.orders.get(key))
+    }
+
+    /// Returns the order with `OrderID <37>` equal to `order_id`, if any.
+    pub fn get_by_order_id(&self, order_id: &str) -> Option<&Order> {
+        self.keys_by_order_id
+            .get(order_id)
+            .and_then(|key| self.orders.get(key))
+    }
+
+    /// Returns an [`Iterator`] over all orders in `self`, in no particular
+    /// order.
+    pub fn iter(&self) -> impl Iterator<Item = &Order> {
+        self.orders.values()
+    }
+
+    /// Only keeps the orders for which `f` returns `true`, e.g. to forget
+    /// about terminal orders at the end of the day.
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&Order) -> bool,
+    {
+        self.orders.retain(|_, order| f(order));
+        let orders = &self.orders;
+        self.keys_by_cl_ord_id
+            .retain(|_, key| orders.contains_key(key));
+        self.keys_by_order_id
+            .retain(|_, key| orders.contains_key(key));
+    }
+
+    /// Updates the order that `msg`, an `ExecutionReport <8>`, refers to and
+    /// returns it. Reports about unknown orders create new ones.
+    pub fn on_execution_report<M>(&mut self, msg: &M) -> Result<&Order, OrderTrackerError>
+    where
+        M: FieldAccess,
+    {
+        let report = Report::decode(msg)?;
+        let key = report
+            .cl_ord_id
+            .into_iter()
+            .chain(report.orig_cl_ord_id)
+            .find_map(|cl_ord_id| self.keys_by_cl_ord_id.get(cl_ord_id))
+            .or_else(|| self.keys_by_order_id.get(report.order_id))
+            .copied();
+        let key = match key {
+            Some(key) => {
+                let order = &self.orders[&key];
+                if order.exec_ids.contains(report.exec_id) {
+                    return Err(OrderTrackerError::DuplicateExecId {
+                        exec_id: report.exec_id.to_string(),
+                    });
+                }
+                report.check(Some(order))?;
+                key
+            }
+            None => {
+                report.check(None)?;
+                let key = self.next_key;
+                self.next_key += 1;
+                self.orders.insert(key, Order::new());
+                key
+            }
+        };
+        let order = self.orders.get_mut(&key).unwrap();
+        report.apply(order);
+        if let Some(cl_ord_id) = report.cl_ord_id {
+            self.keys_by_cl_ord_id.insert(cl_ord_id.to_string(), key);
+        }
+        self.keys_by_order_id
+            .insert(report.order_id.to_string(), key);
+        Ok(order)
+    }
+
+    /// Registers the new `ClOrdID <11>` and `OrderQty <38>` of `msg`, an
+    /// `OrderCancelReplaceRequest <G>` for a known order. They take effect
+    /// once the counterparty confirms the replace.
+    pub fn on_order_cancel_replace_request<M>(
+        &mut self,
+        msg: &M,
+    ) -> Result<&Order, OrderTrackerError>
+    where
+        M: FieldAccess,
+    {
+        let cl_ord_id: &str = decode_required(msg.fv(fix44::CL_ORD_ID), fix44::CL_ORD_ID)?;
+        let orig_cl_ord_id: &str =
+            decode_required(msg.fv(fix44::ORIG_CL_ORD_ID), fix44::ORIG_CL_ORD_ID)?;
+        let order_qty = decode_optional(msg.fv_opt(fix44::ORDER_QTY), fix44::ORDER_QTY)?;
+        let key = *self.keys_by_cl_ord_id.get(orig_cl_ord_id).ok_or_else(|| {
+            OrderTrackerError::UnknownOrder {
+                orig_cl_ord_id: orig_cl_ord_id.to_string(),
+            }
+        })?;
+        let order = self.orders.get_mut(&key).unwrap();
+        order.pending_cl_ord_id = Some(cl_ord_id.to_string());
+        order.pending_order_qty = order_qty;
+        self.keys_by_cl_ord_id.insert(cl_ord_id.to_string(), key);
+        Ok(order)
+    }
+
+    /// Discards the pending replace that `msg`, an `OrderCancelReject <9>`,
+    /// refuses, if any.
+    pub fn on_order_cancel_reject<M>(&mut self, msg: &M) -> Result<&Order, OrderTrackerError>
+    where
+        M: FieldAccess,
+    {
+        let cl_ord_id: &str = decode_required(msg.fv(fix44::CL_ORD_ID), fix44::CL_ORD_ID)?;
+        let orig_cl_ord_id: &str =
+            decode_required(msg.fv(fix44::ORIG_CL_ORD_ID), fix44::ORIG_CL_ORD_ID)?;
+        let key = *self
+            .keys_by_cl_ord_id
+            .get(cl_ord_id)
+            .or_else(|| self.keys_by_cl_ord_id.get(orig_cl_ord_id))
+            .ok_or_else(|| OrderTrackerError::UnknownOrder {
+                orig_cl_ord_id: orig_cl_ord_id.to_string(),
+            })?;
+        let order = self.orders.get_mut(&key).unwrap();
+        if order.pending_cl_ord_id.as_deref() == Some(cl_ord_id) {
+            order.pending_cl_ord_id = None;
+            order.pending_order_qty = None;
+        }
+        Ok(order)
+    }
+}
+
+impl Default for OrderTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The fields of an `ExecutionReport <8>` that matter to the order state.
+struct Report<'a> {
+    cl_ord_id: Option<&'a str>,
+    orig_cl_ord_id: Option<&'a str>,
+    order_id: &'a str,
+    exec_id: &'a str,
+    exec_type: fix44::ExecType,
+    ord_status: fix44::OrdStatus,
+    symbol: Option<&'a str>,
+    side: fix44::Side,
+    order_qty: Option<Decimal>,
+    last_qty: Option<Decimal>,
+    leaves_qty: Decimal,
+    cum_qty: Decimal,
+    avg_px: Decimal,
+}
+
+impl<'a> Report<'a> {
+    fn decode<M>(msg: &'a M) -> Result<Self, TypedMessageError>
+    where
+        M: FieldAccess,
+    {
+        let report = Self {
+            cl_ord_id: decode_optional(msg.fv_opt(fix44::CL_ORD_ID), fix44::CL_ORD_ID)?,
+            orig_cl_ord_id: decode_optional(
+                msg.fv_opt(fix44::ORIG_CL_ORD_ID),
+                fix44::ORIG_CL_ORD_ID,
+            )?,
+            order_id: decode_required(msg.fv(fix44::ORDER_ID), fix44::ORDER_ID)?,
+            exec_id: decode_required(msg.fv(fix44::EXEC_ID), fix44::EXEC_ID)?,
+            exec_type: decode_required(msg.fv(fix44::EXEC_TYPE), fix44::EXEC_TYPE)?,
+            ord_status: decode_required(msg.fv(fix44::ORD_STATUS), fix44::ORD_STATUS)?,
+            symbol: decode_optional(msg.fv_opt(fix44::SYMBOL), fix44::SYMBOL)?,
+            side: decode_required(msg.fv(fix44::SIDE), fix44::SIDE)?,
+            order_qty: decode_optional(msg.fv_opt(fix44::ORDER_QTY), fix44::ORDER_QTY)?,
+            last_qty: decode_optional(msg.fv_opt(fix44::LAST_QTY), fix44::LAST_QTY)?,
+            leaves_qty: decode_required(msg.fv(fix44::LEAVES_QTY), fix44::LEAVES_QTY)?,
+            cum_qty: decode_required(msg.fv(fix44::CUM_QTY), fix44::CUM_QTY)?,
+            avg_px: decode_required(msg.fv(fix44::AVG_PX), fix44::AVG_PX)?,
+        };
+        if report.exec_type == fix44::ExecType::Trade && report.last_qty.is_none() {
+            return Err(TypedMessageError::MissingField(fix44::LAST_QTY.tag()));
+        }
+        Ok(report)
+    }
+
+    /// The `OrderQty <38>` of the order after this report.
+    fn order_qty(&self, order: Option<&Order>) -> Option<Decimal> {
+        let pending = order
+            .filter(|_| self.exec_type == fix44::ExecType::Replace)
+            .and_then(|order| order.pending_order_qty);
+        self.order_qty
+            .or(pending)
+            .or_else(|| order.and_then(|order| order.order_qty))
+    }
+
+    /// Verifies `self` against the current state of its order, if known.
+    fn check(&self, order: Option<&Order>) -> Result<(), OrderTrackerError> {
+        use fix44::ExecType;
+        let is_correction = matches!(
+            self.exec_type,
+            ExecType::TradeCorrect | ExecType::TradeCancel | ExecType::Restated
+        );
+        if let Some(order) = order {
+            if order.is_terminal() && self.ord_status != order.ord_status && !is_correction {
+                return Err(OrderTrackerError::IllegalTransition {
+                    from: order.ord_status,
+                    to: self.ord_status,
+                });
+            }
+            let expected_cum_qty = match self.last_qty {
+                Some(last_qty) if self.exec_type == ExecType::Trade => {
+                    order.cum_qty.checked_add(last_qty)
+                }
+                _ if is_correction => None,
+                _ => Some(order.cum_qty),
+            };
+            if let Some(expected) = expected_cum_qty {
+                if expected.value_cmp(&self.cum_qty) != Ordering::Equal {
+                    return Err(OrderTrackerError::CumQtyMismatch {
+                        expected,
+                        actual: self.cum_qty,
+                    });
+                }
+            }
+        }
+        let expected_leaves_qty = if is_done(self.ord_status) {
+            Some(Decimal::ZERO)
+        } else {
+            self.order_qty(order)
+                .and_then(|order_qty| order_qty.checked_sub(self.cum_qty))
+        };
+        if let Some(expected) = expected_leaves_qty {
+            if expected.value_cmp(&self.leaves_qty) != Ordering::Equal {
+                return Err(OrderTrackerError::LeavesQtyMismatch {
+                    expected,
+                    actual: self.leaves_qty,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    fn apply(&self, order: &mut Order) {
+        use fix44::ExecType;
+        order.order_qty = self.order_qty(Some(order));
+        if let Some(cl_ord_id) = self.cl_ord_id {
+            // The `ClOrdID <11>` of a pending request only becomes current
+            // once the request succeeds.
+            let is_current = order.cl_ord_ids.is_empty()
+                || matches!(self.exec_type, ExecType::Replace | ExecType::Canceled);
+            if is_current && order.cl_ord_id() != Some(cl_ord_id) {
+                order.cl_ord_ids.push(cl_ord_id.to_string());
+            }
+        }
+        if self.exec_type == ExecType::Replace || is_terminal(self.ord_status) {
+            order.pending_cl_ord_id = None;
+            order.pending_order_qty = None;
+        }
+        order.order_id = Some(self.order_id.to_string());
+        if let Some(symbol) = self.symbol {
+            order.symbol = Some(symbol.to_string());
+        }
+        order.side = Some(self.side);
+        order.ord_status = self.ord_status;
+        order.cum_qty = self.cum_qty;
+        order.leaves_qty = self.leaves_qty;
+        order.avg_px = self.avg_px;
+        order.exec_ids.insert(self.exec_id.to_string());
+    }
+}
+
+fn is_terminal(ord_status: fix44::OrdStatus) -> bool {
+    use fix44::OrdStatus;
+    matches!(
+        ord_status,
+        OrdStatus::Filled | OrdStatus::Canceled | OrdStatus::Rejected | OrdStatus::Expired
+    )
+}
+
+// Orders that are no longer working for the day have no `LeavesQty <151>`.
+fn is_done(ord_status: fix44::OrdStatus) -> bool {
+    use fix44::OrdStatus;
+    is_terminal(ord_status) || matches!(ord_status, OrdStatus::DoneForDay | OrdStatus::Calculated)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::definitions::{Execution, ExecutionReportBuilder};
+    use crate::tagvalue::{Config, Decoder, Encoder, EncoderHandle};
+    use crate::Dictionary;
+
+    struct Counterparty {
+        encoder: Encoder<Config>,
+        decoder: Decoder<Config>,
+        tracker: OrderTracker,
+        exec_ids: u32,
+    }
+
+    fn qty(n: i64) -> Decimal {
+        Decimal::new(n, 0)
+    }
+
+    impl Counterparty {
+        fn new() -> Self {
+            Self {
+                encoder: Encoder::default(),
+                decoder: Decoder::new(Dictionary::fix44()),
+                tracker: OrderTracker::new(),
+                exec_ids: 0,
+            }
+        }
+
+        fn encode<F>(&mut self, msg_type: &[u8], f: F) -> Vec<u8>
+        where
+            F: FnOnce(&mut EncoderHandle<Vec<u8>>),
+        {
+            let mut buffer = Vec::new();
+            let mut msg = self
+                .encoder
+                .start_message(b"FIX.4.4", &mut buffer, msg_type);
+            f(&mut msg);
+            msg.wrap().to_vec()
+        }
+
+        fn report(
+            &mut self,
+            cl_ord_id: &str,
+            execution: Execution,
+            order_qty: i64,
+            cum_qty: i64,
+        ) -> Result<Order, OrderTrackerError> {
+            self.exec_ids += 1;
+            let exec_id = format!("E-{}", self.exec_ids);
+            let mut report =
+                ExecutionReportBuilder::new("O-1", exec_id, "AAPL", fix44::Side::Buy, execution);
+            report.set_cl_ord_id(cl_ord_id);
+            report.set_order_qty(qty(order_qty));
+            report.set_cum_qty(qty(cum_qty), qty(10));
+            let data = self.encode(ExecutionReportBuilder::MSG_TYPE, |msg| report.encode(msg));
+            let msg = self.decoder.decode(&data[..]).unwrap();
+            self.tracker.on_execution_report(&msg).cloned()
+        }
+
+        fn replace(&mut self, cl_ord_id: &str, orig_cl_ord_id: &str, order_qty: i64) {
+            let data = self.encode(b"G", |msg| {
+                msg.set(fix44::CL_ORD_ID, cl_ord_id);
+                msg.set(fix44::ORIG_CL_ORD_ID, orig_cl_ord_id);
+                msg.set(fix44::ORDER_QTY, qty(order_qty));
+            });
+            let msg = self.decoder.decode(&data[..]).unwrap();
+            self.tracker.on_order_cancel_replace_request(&msg).unwrap();
+        }
+    }
+
+    fn trade(last_qty: i64, leaves_qty: i64) -> Execution {
+        Execution::Trade {
+            last_qty: qty(last_qty),
+            last_px: qty(10),
+            leaves_qty: qty(leaves_qty),
+        }
+    }
+
+    #[test]
+    fn fills_and_replaces() {
+        let mut cp = Counterparty::new();
+        let new = Execution::New {
+            leaves_qty: qty(100),
+        };
+        assert_eq!(
+            cp.report("C-1", new, 100, 0).unwrap().ord_status(),
+            fix44::OrdStatus::New
+        );
+        cp.report("C-1", trade(40, 60), 100, 40).unwrap();
+
+        cp.replace("C-2", "C-1", 150);
+        let order = cp.tracker.get("C-2").unwrap();
+        assert_eq!(order.pending_cl_ord_id(), Some("C-2"));
+        assert_eq!(order.cl_ord_id(), Some("C-1"));
+        let pending = Execution::PendingReplace {
+            orig_cl_ord_id: "C-1".to_string(),
+            leaves_qty: qty(60),
+        };
+        let order = cp.report("C-2", pending, 100, 40).unwrap();
+        assert_eq!(order.cl_ord_id(), Some("C-1"));
+
+        let replaced = Execution::Replaced {
+            orig_cl_ord_id: "C-1".to_string(),
+            leaves_qty: qty(110),
+        };
+        let data = {
+            cp.exec_ids += 1;
+            let mut report =
+                ExecutionReportBuilder::new("O-1", "E-R", "AAPL", fix44::Side::Buy, replaced);
+            report.set_cl_ord_id("C-2");
+            report.set_cum_qty(qty(40), qty(10));
+            // No `OrderQty <38>`: the one of the request applies.
+            cp.encode(ExecutionReportBuilder::MSG_TYPE, |msg| report.encode(msg))
+        };
+        let msg = cp.decoder.decode(&data[..]).unwrap();
+        let order = cp.tracker.on_execution_report(&msg).unwrap();
+        assert_eq!(order.cl_ord_ids(), &["C-1".to_string(), "C-2".to_string()]);
+        assert_eq!(order.pending_cl_ord_id(), None);
+        assert_eq!(order.order_qty(), Some(qty(150)));
+        assert_eq!(order.ord_status(), fix44::OrdStatus::PartiallyFilled);
+
+        let order = cp.report("C-2", trade(110, 0), 150, 150).unwrap();
+        assert_eq!(order.ord_status(), fix44::OrdStatus::Filled);
+        assert!(order.is_terminal());
+        assert_eq!(cp.tracker.len(), 1);
+        assert!(cp.tracker.get("C-1").is_some());
+        assert!(cp.tracker.get_by_order_id("O-1").is_some());
+
+        cp.tracker.retain(|order| !order.is_terminal());
+        assert!(cp.tracker.is_empty());
+        assert!(cp.tracker.get("C-2").is_none());
+    }
+
+    #[test]
+    fn inconsistent_reports_are_refused() {
+        let mut cp = Counterparty::new();
+        cp.report("C-1", trade(40, 60), 100, 40).unwrap();
+        assert_eq!(
+            cp.report("C-1", trade(10, 50), 100, 60),
+            Err(OrderTrackerError::CumQtyMismatch {
+                expected: qty(50),
+                actual: qty(60)
+            })
+        );
+        assert_eq!(
+            cp.report("C-1", trade(10, 40), 100, 50),
+            Err(OrderTrackerError::LeavesQtyMismatch {
+                expected: qty(50),
+                actual: qty(40)
+            })
+        );
+        let order = cp.tracker.get("C-1").unwrap();
+        assert_eq!(order.cum_qty(), qty(40));
+
+        cp.report("C-1", trade(60, 0), 100, 100).unwrap();
+        let new = Execution::New { leaves_qty: qty(0) };
+        assert_eq!(
+            cp.report("C-1", new, 100, 100),
+            Err(OrderTrackerError::IllegalTransition {
+                from: fix44::OrdStatus::Filled,
+                to: fix44::OrdStatus::New
+            })
+        );
+    }
+
+    #[test]
+    fn duplicate_and_unknown_ids() {
+        let mut cp = Counterparty::new();
+        cp.report("C-1", trade(40, 60), 100, 40).unwrap();
+        cp.exec_ids -= 1;
+        assert_eq!(
+            cp.report("C-1", trade(10, 50), 100, 50),
+            Err(OrderTrackerError::DuplicateExecId {
+                exec_id: "E-1".to_string()
+            })
+        );
+
+        let data = cp.encode(b"G", |msg| {
+            msg.set(fix44::CL_ORD_ID, "C-3");
+            msg.set(fix44::ORIG_CL_ORD_ID, "C-2");
+        });
+        let msg = cp.decoder.decode(&data[..]).unwrap();
+        assert_eq!(
+            cp.tracker
+                .on_order_cancel_replace_request(&msg)
+                .unwrap_err(),
+            OrderTrackerError::UnknownOrder {
+                orig_cl_ord_id: "C-2".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn cancel_rejects_discard_pending_replaces() {
+        let mut cp = Counterparty::new();
+        cp.report("C-1", trade(40, 60), 100, 40).unwrap();
+        cp.replace("C-2", "C-1", 50);
+        let data = cp.encode(b"9", |msg| {
+            msg.set(fix44::ORDER_ID, "O-1");
+            msg.set(fix44::CL_ORD_ID, "C-2");
+            msg.set(fix44::ORIG_CL_ORD_ID, "C-1");
+            msg.set(fix44::ORD_STATUS, fix44::OrdStatus::PartiallyFilled);
+        });
+        let msg = cp.decoder.decode(&data[..]).unwrap();
+        let order = cp.tracker.on_order_cancel_reject(&msg).unwrap();
+        assert_eq!(order.pending_cl_ord_id(), None);
+        assert_eq!(order.order_qty(), Some(qty(100)));
+    }
+}
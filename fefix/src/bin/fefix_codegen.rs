@@ -0,0 +1,195 @@
+//! Generates Rust code from a QuickFIX or FIX Orchestra specification file,
+//! just like [`fefix::codegen::generate_to`] does in build scripts.
+
+use fefix::codegen::{self, ModuleLayout, Settings};
+use fefix::Dictionary;
+use std::path::PathBuf;
+use std::process;
+
+const USAGE: &str = "\
+Generates Rust code from a QuickFIX or FIX Orchestra specification file.
+
+USAGE:
+    fefix-codegen [OPTIONS] <SPEC>
+
+ARGS:
+    <SPEC>    The QuickFIX (default) or FIX Orchestra specification file
+
+OPTIONS:
+    -o, --out-dir <DIR>         The output directory [default: .]
+        --module-name <NAME>    The name of the module and of its output file
+                                [default: derived from the FIX version]
+        --transport <SPEC>      A QuickFIX transport layer specification file
+                                (e.g. FIXT11.xml), if <SPEC> is an application
+                                layer specification
+        --orchestra             Reads <SPEC> as a FIX Orchestra file
+        --crate-name <NAME>     The name of the `fefix` crate [default: fefix]
+        --messages              Generates strongly-typed message structs
+        --message-fields        Generates message-scoped field definitions
+        --separate-messages     Writes message structs to a separate file
+    -h, --help                  Prints this help message";
+
+#[derive(Debug, Clone, PartialEq, Default)]
+struct Args {
+    spec: PathBuf,
+    out_dir: Option<PathBuf>,
+    module_name: Option<String>,
+    transport: Option<PathBuf>,
+    orchestra: bool,
+    crate_name: Option<String>,
+    messages: bool,
+    message_fields: bool,
+    separate_messages: bool,
+}
+
+impl Args {
+    /// Parses command line arguments, excluding the program name. Returns
+    /// `Ok(None)` if the help message was requested.
+    fn parse<I>(args: I) -> Result<Option<Self>, String>
+    where
+        I: IntoIterator<Item = String>,
+    {
+        let mut parsed = Args::default();
+        let mut spec = None;
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let mut value = |name: &str| {
+                args.next()
+                    .ok_or_else(|| format!("Missing value for '{}'", name))
+            };
+            match arg.as_str() {
+                "-h" | "--help" => return Ok(None),
+                "-o" | "--out-dir" => parsed.out_dir = Some(value(&arg)?.into()),
+                "--module-name" => parsed.module_name = Some(value(&arg)?),
+                "--transport" => parsed.transport = Some(value(&arg)?.into()),
+                "--crate-name" => parsed.crate_name = Some(value(&arg)?),
+                "--orchestra" => parsed.orchestra = true,
+                "--messages" => parsed.messages = true,
+                "--message-fields" => parsed.message_fields = true,
+                "--separate-messages" => parsed.separate_messages = true,
+                s if s.starts_with('-') => return Err(format!("Unknown option '{}'", s)),
+                _ if spec.is_some() => return Err(format!("Unexpected argument '{}'", arg)),
+                _ => spec = Some(PathBuf::from(arg)),
+            }
+        }
+        if parsed.orchestra && parsed.transport.is_some() {
+            return Err("'--transport' is only valid for QuickFIX files".to_string());
+        }
+        parsed.spec = spec.ok_or("Missing <SPEC> argument")?;
+        Ok(Some(parsed))
+    }
+
+    fn settings(&self) -> Settings {
+        let mut settings = Settings::default();
+        if let Some(name) = &self.module_name {
+            settings.set_module_name(name.as_str());
+        }
+        if let Some(name) = &self.crate_name {
+            settings.set_fefix_crate_name(name.as_str());
+        }
+        settings.set_generate_messages(self.messages);
+        settings.set_generate_message_fields(self.message_fields);
+        if self.separate_messages {
+            settings.set_module_layout(ModuleLayout::SeparateMessages);
+        }
+        settings
+    }
+}
+
+fn run(args: &Args) -> Result<PathBuf, String> {
+    let read = |path: &PathBuf| {
+        std::fs::read_to_string(path)
+            .map_err(|err| format!("Can't read '{}': {}", path.display(), err))
+    };
+    let spec = read(&args.spec)?;
+    let dictionary = if args.orchestra {
+        Dictionary::from_orchestra_spec(spec)
+    } else if let Some(transport) = &args.transport {
+        Dictionary::from_quickfix_specs(read(transport)?, spec)
+    } else {
+        Dictionary::from_quickfix_spec(spec)
+    }
+    .map_err(|err| format!("Can't parse '{}': {}", args.spec.display(), err))?;
+    let out_dir = args.out_dir.clone().unwrap_or_else(|| PathBuf::from("."));
+    codegen::generate_to(&out_dir, &dictionary, &args.settings())
+        .map_err(|err| format!("Can't write to '{}': {}", out_dir.display(), err))
+}
+
+fn main() {
+    let args = match Args::parse(std::env::args().skip(1)) {
+        Ok(Some(args)) => args,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return;
+        }
+        Err(err) => {
+            eprintln!("error: {}\n\n{}", err, USAGE);
+            process::exit(2);
+        }
+    };
+    match run(&args) {
+        Ok(path) => println!("{}", path.display()),
+        Err(err) => {
+            eprintln!("error: {}", err);
+            process::exit(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Option<Args>, String> {
+        Args::parse(args.iter().map(|s| s.to_string()))
+    }
+
+    #[test]
+    fn parse_flags_and_options() {
+        let args = parse(&[
+            "FIX50SP2.xml",
+            "--transport",
+            "FIXT11.xml",
+            "-o",
+            "src",
+            "--messages",
+        ])
+        .unwrap()
+        .unwrap();
+        assert_eq!(args.spec, PathBuf::from("FIX50SP2.xml"));
+        assert_eq!(args.transport, Some(PathBuf::from("FIXT11.xml")));
+        assert_eq!(args.out_dir, Some(PathBuf::from("src")));
+        assert!(args.messages && !args.message_fields && !args.orchestra);
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(parse(&["--help", "--bogus"]), Ok(None));
+        assert!(parse(&[]).is_err());
+        assert!(parse(&["a.xml", "b.xml"]).is_err());
+        assert!(parse(&["a.xml", "--bogus"]).is_err());
+        assert!(parse(&["a.xml", "--out-dir"]).is_err());
+        assert!(parse(&["a.xml", "--orchestra", "--transport", "b.xml"]).is_err());
+    }
+
+    #[test]
+    fn generate_from_quickfix_spec() {
+        let dir = std::env::temp_dir().join(format!("fefix-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let spec = dir.join("FIX44.xml");
+        let fix44 = include_str!("../fefix_core/resources/quickfix/FIX-4.4.xml");
+        std::fs::write(&spec, fix44).unwrap();
+        let args = Args {
+            spec,
+            out_dir: Some(dir.clone()),
+            module_name: Some("venue".to_string()),
+            message_fields: true,
+            ..Args::default()
+        };
+        assert_eq!(run(&args), Ok(dir.join("venue.rs")));
+        let code = std::fs::read_to_string(dir.join("venue.rs")).unwrap();
+        assert!(code.contains("pub mod message_fields {"));
+        assert!(!code.contains("pub mod messages {"));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -1,5 +1,7 @@
 //! Zero-copy buffering utilities.
 
+use alloc::vec::Vec;
+
 /// Operations on a growable in-memory buffer.
 ///
 /// [`Buffer`] allows common data operations on in-memory data buffers. While
@@ -20,9 +22,22 @@ pub trait Buffer {
     /// Returns the number of bytes that `self` can hold without reallocating.
     fn capacity(&self) -> usize;
 
+    /// Reserves capacity for at least `additional` more bytes, if `self` can
+    /// grow. Fixed-capacity buffers ignore this.
+    fn reserve(&mut self, additional: usize) {
+        let _ = additional;
+    }
+
     /// Erases the contents of `self`.
     fn clear(&mut self);
 
+    /// Returns `true` if and only if some write operation didn't fit into
+    /// `self` since the last call to [`Buffer::clear`], so that its contents
+    /// are truncated. Buffers that can grow never overflow.
+    fn has_overflowed(&self) -> bool {
+        false
+    }
+
     /// Appends the contents of `extend` onto `self`, growing the buffer if
     /// necessary.
     fn extend_from_slice(&mut self, extend: &[u8]);
@@ -53,6 +68,10 @@ impl Buffer for Vec<u8> {
         self.capacity()
     }
 
+    fn reserve(&mut self, additional: usize) {
+        self.reserve(additional)
+    }
+
     fn clear(&mut self) {
         self.clear()
     }
@@ -81,6 +100,10 @@ impl Buffer for bytes::BytesMut {
         bytes::BytesMut::capacity(self)
     }
 
+    fn reserve(&mut self, additional: usize) {
+        bytes::BytesMut::reserve(self, additional)
+    }
+
     fn clear(&mut self) {
         bytes::BytesMut::clear(self)
     }
@@ -94,6 +117,154 @@ impl Buffer for bytes::BytesMut {
     }
 }
 
+/// A fixed-capacity [`Buffer`] backed by a `&mut [u8]` slice, e.g. a
+/// pre-allocated arena or a region of a network buffer.
+///
+/// [`SliceBuffer`] can't grow past the length of the underlying slice.
+/// [`SliceBuffer::try_extend_from_slice`] reports this condition with an
+/// error; [`Buffer::extend_from_slice`] and [`Buffer::resize`], which can't
+/// fail, write as many bytes as they can and raise an overflow flag instead
+/// (see [`SliceBuffer::has_overflowed`]). The contents of an overflowed
+/// [`SliceBuffer`] are truncated and should be discarded.
+///
+/// # Examples
+///
+/// ```
+/// use fefix::tagvalue::{Config, Encoder};
+/// use fefix::{Buffer, SliceBuffer};
+///
+/// let mut arena = [0u8; 256];
+/// let mut buffer = SliceBuffer::new(&mut arena[..]);
+/// let mut encoder = Encoder::<Config>::default();
+/// let msg = encoder.start_message(b"FIX.4.4", &mut buffer, b"0");
+/// let data = msg.wrap().to_vec();
+/// assert!(!buffer.has_overflowed());
+/// assert_eq!(buffer.as_slice(), &data[..]);
+/// ```
+#[derive(Debug)]
+pub struct SliceBuffer<'a> {
+    data: &'a mut [u8],
+    len: usize,
+    overflowed: bool,
+}
+
+impl<'a> SliceBuffer<'a> {
+    /// Creates a new, empty [`SliceBuffer`] with `data` as storage. Its
+    /// capacity is `data.len()`.
+    pub fn new(data: &'a mut [u8]) -> Self {
+        Self {
+            data,
+            len: 0,
+            overflowed: false,
+        }
+    }
+
+    /// Returns the contents of `self`, which borrows for the whole lifetime of
+    /// the underlying slice.
+    pub fn into_slice(self) -> &'a [u8] {
+        &self.data[..self.len]
+    }
+
+    /// Returns the number of bytes that can still be written to `self`.
+    pub fn remaining(&self) -> usize {
+        self.data.len() - self.len
+    }
+
+    /// Returns `true` if and only if some write operation didn't fit into
+    /// `self` since the last call to [`Buffer::clear`].
+    pub fn has_overflowed(&self) -> bool {
+        self.overflowed
+    }
+
+    /// Appends the contents of `extend` onto `self` if there's enough room
+    /// for all of it; otherwise, `self` is left unchanged.
+    pub fn try_extend_from_slice(&mut self, extend: &[u8]) -> Result<(), CapacityError> {
+        if extend.len() > self.remaining() {
+            return Err(CapacityError {
+                required: self.len + extend.len(),
+                capacity: self.data.len(),
+            });
+        }
+        self.data[self.len..self.len + extend.len()].copy_from_slice(extend);
+        self.len += extend.len();
+        Ok(())
+    }
+}
+
+impl<'a> From<&'a mut [u8]> for SliceBuffer<'a> {
+    fn from(data: &'a mut [u8]) -> Self {
+        Self::new(data)
+    }
+}
+
+impl<'a> Buffer for SliceBuffer<'a> {
+    fn as_slice(&self) -> &[u8] {
+        &self.data[..self.len]
+    }
+
+    fn as_mut_slice(&mut self) -> &mut [u8] {
+        &mut self.data[..self.len]
+    }
+
+    fn capacity(&self) -> usize {
+        self.data.len()
+    }
+
+    fn clear(&mut self) {
+        self.len = 0;
+        self.overflowed = false;
+    }
+
+    fn has_overflowed(&self) -> bool {
+        self.overflowed
+    }
+
+    fn extend_from_slice(&mut self, extend: &[u8]) {
+        if self.try_extend_from_slice(extend).is_err() {
+            let remaining = self.remaining();
+            self.data[self.len..].copy_from_slice(&extend[..remaining]);
+            self.len = self.data.len();
+            self.overflowed = true;
+        }
+    }
+
+    fn resize(&mut self, new_len: usize, filler: u8) {
+        if new_len > self.data.len() {
+            self.overflowed = true;
+        }
+        let new_len = new_len.min(self.data.len());
+        if new_len > self.len {
+            for byte in self.data[self.len..new_len].iter_mut() {
+                *byte = filler;
+            }
+        }
+        self.len = new_len;
+    }
+}
+
+/// The error type returned when a fixed-capacity [`Buffer`] can't hold the
+/// requested data.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct CapacityError {
+    /// The number of bytes that the operation would have required.
+    pub required: usize,
+    /// The total capacity of the buffer.
+    pub capacity: usize,
+}
+
+impl core::fmt::Display for CapacityError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(
+            f,
+            "Buffer capacity exceeded ({} bytes required, {} available)",
+            self.required, self.capacity
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for CapacityError {}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -112,4 +283,54 @@ mod test {
         Buffer::clear(&mut vec);
         vec.len() == 0
     }
+
+    #[test]
+    fn slice_buffer_refuses_to_grow_past_capacity() {
+        let mut data = [0u8; 8];
+        let mut buffer = SliceBuffer::new(&mut data[..]);
+        buffer.try_extend_from_slice(b"8=FIX").unwrap();
+        assert_eq!(
+            buffer.try_extend_from_slice(b".4.4|"),
+            Err(CapacityError {
+                required: 10,
+                capacity: 8
+            })
+        );
+        assert_eq!(buffer.as_slice(), b"8=FIX");
+        assert!(!buffer.has_overflowed());
+        Buffer::extend_from_slice(&mut buffer, b".4.4|");
+        assert!(buffer.has_overflowed());
+        assert_eq!(buffer.as_slice(), b"8=FIX.4.");
+        buffer.clear();
+        assert!(!buffer.has_overflowed());
+        assert_eq!(buffer.len(), 0);
+    }
+
+    #[test]
+    fn slice_buffer_resize() {
+        let mut data = [0u8; 4];
+        let mut buffer = SliceBuffer::new(&mut data[..]);
+        buffer.resize(2, b'x');
+        assert_eq!(buffer.as_slice(), b"xx");
+        buffer.resize(1, b'y');
+        assert_eq!(buffer.as_slice(), b"x");
+        buffer.resize(10, b'z');
+        assert_eq!(buffer.as_slice(), b"xzzz");
+        assert!(buffer.has_overflowed());
+    }
+
+    #[cfg(feature = "utils-bytes")]
+    #[test]
+    fn encoding_into_bytes_mut() {
+        use crate::tagvalue::{Config, Encoder};
+        let mut encoder = Encoder::<Config>::default();
+        let mut vec = Vec::new();
+        let expected = encoder
+            .start_message(b"FIX.4.4", &mut vec, b"0")
+            .wrap()
+            .to_vec();
+        let mut bytes = bytes::BytesMut::new();
+        let data = encoder.start_message(b"FIX.4.4", &mut bytes, b"0").wrap();
+        assert_eq!(data, &expected[..]);
+    }
 }
@@ -8,8 +8,72 @@
 //! FIX Dictionary specifications. Although this approach works quite well, it
 //! can become daunting to query a [`Dictionary`](crate::Dictionary) for
 //! everything.
+//!
+//! # Enumerated values
+//!
+//! Every field with enumerated values comes with a Rust `enum` (e.g.
+//! [`fix44::OrdType`], [`fix44::ExecType`]) that implements
+//! [`FixValue`](crate::FixValue), so that it can be both encoded and decoded
+//! directly. Values outside of the enumeration result in decoding errors.
+//!
+//! ```
+//! use fefix::definitions::fix44;
+//! use fefix::tagvalue::{Config, Decoder, FieldAccess};
+//! use fefix::Dictionary;
+//!
+//! let mut decoder = Decoder::<Config>::new(Dictionary::fix44());
+//! decoder.config_mut().set_separator(b'|');
+//! let data = b"8=FIX.4.4|9=16|35=D|40=2|150=F|10=000|";
+//! let message = decoder.decode(&data[..]).unwrap();
+//! let description = match message.fv(fix44::ORD_TYPE) {
+//!     Ok(fix44::OrdType::Market) => "market",
+//!     Ok(fix44::OrdType::Limit) => "limit",
+//!     _ => "other",
+//! };
+//! assert_eq!(description, "limit");
+//! assert_eq!(message.fv(fix44::EXEC_TYPE), Ok(fix44::ExecType::Trade));
+//! ```
+//!
+//! # Message-scoped fields
+//!
+//! The `message_fields` module of each FIX version has one module per message
+//! type, with only the fields of that message and their requiredness. This
+//! allows for lightweight validation without a [`Dictionary`](crate::Dictionary).
+//!
+//! ```
+//! use fefix::definitions::fix44::message_fields::new_order_single;
+//! use fefix::tagvalue::{Config, Decoder, FieldAccess};
+//! use fefix::Dictionary;
+//!
+//! let mut decoder = Decoder::<Config>::new(Dictionary::fix44());
+//! decoder.config_mut().set_separator(b'|');
+//! let data = b"8=FIX.4.4|9=22|35=D|11=FOO|54=1|40=1|10=000|";
+//! let message = decoder.decode(&data[..]).unwrap();
+//! let missing: Vec<&str> = new_order_single::REQUIRED_FIELDS
+//!     .iter()
+//!     .filter(|field| message.fv_raw(**field).is_none())
+//!     .map(|field| field.name)
+//!     .collect();
+//! assert_eq!(missing, vec!["TransactTime"]);
+//! let price = new_order_single::PRICE;
+//! assert!(new_order_single::OPTIONAL_FIELDS.iter().any(|f| f.tag == price.tag));
+//! ```
+//!
+//! # Sell-side responses
+//!
+//! [`ExecutionReportBuilder`] and [`OrderCancelRejectBuilder`] write the
+//! bodies of the most common responses to orders. Unlike the strongly-typed
+//! message structs, they take care of the fields that FIX requires only in
+//! some circumstances, e.g. `OrigClOrdID <41>` of canceled orders: see
+//! [`Execution`].
 
-use crate::{dict, dict::FixDatatype, TagU16};
+use crate::dict::IsFieldDefinition;
+use crate::{dict, dict::FixDatatype, OptError, OptResult, TagU16};
+use core::fmt;
+
+mod builders;
+
+pub use builders::{Execution, ExecutionReportBuilder, OrderCancelRejectBuilder};
 
 #[derive(Debug, Clone)]
 #[doc(hidden)]
@@ -38,6 +102,55 @@ impl dict::IsFieldDefinition for HardCodedFixFieldDefinition {
     }
 }
 
+/// The error type returned by the `decode` methods of strongly-typed message
+/// structs, e.g. [`fix44::messages::NewOrderSingle`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TypedMessageError {
+    /// A required field is missing.
+    MissingField(TagU16),
+    /// A field is present, but its value is invalid.
+    InvalidField(TagU16),
+}
+
+impl fmt::Display for TypedMessageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingField(tag) => write!(f, "Missing required field <{}>", tag),
+            Self::InvalidField(tag) => write!(f, "Invalid value for field <{}>", tag),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for TypedMessageError {}
+
+#[doc(hidden)]
+pub fn decode_required<T, E, F>(result: OptResult<T, E>, field: &F) -> Result<T, TypedMessageError>
+where
+    F: IsFieldDefinition,
+{
+    match result {
+        Ok(value) => Ok(value),
+        Err(OptError::None) => Err(TypedMessageError::MissingField(field.tag())),
+        Err(OptError::Other(_)) => Err(TypedMessageError::InvalidField(field.tag())),
+    }
+}
+
+#[doc(hidden)]
+pub fn decode_optional<T, E, F>(
+    result: Option<Result<T, E>>,
+    field: &F,
+) -> Result<Option<T>, TypedMessageError>
+where
+    F: IsFieldDefinition,
+{
+    match result {
+        Some(Ok(value)) => Ok(Some(value)),
+        Some(Err(_)) => Err(TypedMessageError::InvalidField(field.tag())),
+        None => Ok(None),
+    }
+}
+
 #[cfg(feature = "fix40")]
 #[cfg_attr(doc_cfg, doc(cfg(feature = "fix40")))]
 #[allow(dead_code, unused, warnings)]
@@ -116,3 +229,89 @@ pub mod fix50sp2 {
 pub mod fixt11 {
     include!(concat!(env!("OUT_DIR"), "/fixt11.rs"));
 }
+
+#[cfg(test)]
+mod test {
+    use super::fix44;
+    use super::*;
+    use crate::tagvalue::{Config, Decoder, Encoder, EncoderHandle};
+    use crate::Dictionary;
+
+    fn encode<F>(f: F) -> Vec<u8>
+    where
+        F: FnOnce(&mut EncoderHandle<Vec<u8>>),
+    {
+        let mut encoder = Encoder::<Config>::default();
+        let mut buffer = Vec::new();
+        let mut msg = encoder.start_message(
+            b"FIX.4.4",
+            &mut buffer,
+            fix44::messages::NewOrderSingle::MSG_TYPE,
+        );
+        f(&mut msg);
+        msg.wrap().to_vec()
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn typed_message_roundtrip() {
+        let bytes = encode(|msg| {
+            msg.set(fix44::CL_ORD_ID, "FOOBAR");
+            msg.set(fix44::SYMBOL, "AAPL");
+            msg.set(fix44::SIDE, fix44::Side::Buy);
+            msg.set(
+                fix44::TRANSACT_TIME,
+                crate::fix_values::Timestamp::utc_now(),
+            );
+            msg.set(fix44::ORD_TYPE, fix44::OrdType::Limit);
+            msg.set(fix44::PRICE, "150.08");
+        });
+        let mut decoder = Decoder::<Config>::new(Dictionary::fix44());
+        let msg = decoder.decode(&bytes[..]).unwrap();
+        let order = fix44::messages::NewOrderSingle::decode(&msg).unwrap();
+        assert_eq!(order.cl_ord_id, "FOOBAR");
+        assert_eq!(order.side, fix44::Side::Buy);
+        assert_eq!(order.price, Some("150.08"));
+        assert_eq!(order.order_qty, None);
+        let bytes = encode(|msg| order.encode(msg));
+        let mut decoder = Decoder::<Config>::new(Dictionary::fix44());
+        let msg = decoder.decode(&bytes[..]).unwrap();
+        let decoded = fix44::messages::NewOrderSingle::decode(&msg).unwrap();
+        assert_eq!(decoded, order);
+    }
+
+    #[test]
+    fn typed_message_missing_field() {
+        let bytes = encode(|msg| {
+            msg.set(fix44::SIDE, fix44::Side::Buy);
+        });
+        let mut decoder = Decoder::<Config>::new(Dictionary::fix44());
+        let msg = decoder.decode(&bytes[..]).unwrap();
+        assert_eq!(
+            fix44::messages::NewOrderSingle::decode(&msg),
+            Err(TypedMessageError::MissingField(fix44::CL_ORD_ID.tag()))
+        );
+    }
+
+    #[test]
+    fn unknown_enum_variants_are_invalid() {
+        use crate::tagvalue::FieldAccess;
+        use crate::OptError;
+
+        let bytes = encode(|msg| {
+            msg.set(fix44::ORD_TYPE, "?");
+            msg.set(fix44::SIDE, fix44::Side::Sell);
+        });
+        let mut decoder = Decoder::<Config>::new(Dictionary::fix44());
+        let msg = decoder.decode(&bytes[..]).unwrap();
+        assert_eq!(
+            msg.fv::<fix44::OrdType, _>(fix44::ORD_TYPE),
+            Err(OptError::Other(()))
+        );
+        assert_eq!(msg.fv(fix44::SIDE), Ok(fix44::Side::Sell));
+        assert_eq!(
+            msg.fv::<fix44::TimeInForce, _>(fix44::TIME_IN_FORCE),
+            Err(OptError::None)
+        );
+    }
+}
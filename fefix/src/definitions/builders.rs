@@ -0,0 +1,584 @@
+use super::fix44;
+use crate::fix_values::{Decimal, Timestamp};
+use crate::tagvalue::{Configure, EncoderHandle};
+use crate::Buffer;
+use alloc::string::String;
+
+/// The event that an [`ExecutionReportBuilder`] reports, i.e. its `ExecType
+/// <150>`, together with the fields that FIX requires for it.
+///
+/// The `OrdStatus <39>` of the report follows from the event and, at most, the
+/// order quantities, so that the two can't disagree. Events that end the life
+/// of the order have no `leaves_qty`: `LeavesQty <151>` is always zero for
+/// them.
+///
+/// Please note that [`Execution`] is marked with `#[non_exhaustive]`, which
+/// future-proofs the enumeration type in case more variants are added.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Execution {
+    /// The order has been received, but not yet accepted.
+    PendingNew {
+        /// The open quantity of the order.
+        leaves_qty: Decimal,
+    },
+    /// The order has been accepted.
+    New {
+        /// The open quantity of the order.
+        leaves_qty: Decimal,
+    },
+    /// The order has been (partially) filled. `OrdStatus <39>` is `Filled`
+    /// if nothing is left open, `PartiallyFilled` otherwise.
+    Trade {
+        /// The quantity of this fill.
+        last_qty: Decimal,
+        /// The price of this fill.
+        last_px: Decimal,
+        /// The open quantity of the order after this fill.
+        leaves_qty: Decimal,
+    },
+    /// A cancel request has been received, but not yet processed.
+    PendingCancel {
+        /// The `ClOrdID <11>` of the order to be canceled.
+        orig_cl_ord_id: String,
+        /// The open quantity of the order.
+        leaves_qty: Decimal,
+    },
+    /// The order has been canceled.
+    Canceled {
+        /// The `ClOrdID <11>` of the canceled order.
+        orig_cl_ord_id: String,
+    },
+    /// A cancel/replace request has been received, but not yet processed.
+    PendingReplace {
+        /// The `ClOrdID <11>` of the order to be replaced.
+        orig_cl_ord_id: String,
+        /// The open quantity of the order.
+        leaves_qty: Decimal,
+    },
+    /// The order has been replaced. `OrdStatus <39>` depends on the fills so
+    /// far, i.e. `New`, `PartiallyFilled` or `Filled`.
+    Replaced {
+        /// The `ClOrdID <11>` of the replaced order.
+        orig_cl_ord_id: String,
+        /// The open quantity of the replacing order.
+        leaves_qty: Decimal,
+    },
+    /// The order has been rejected.
+    Rejected {
+        /// The reason for the rejection.
+        reason: fix44::OrdRejReason,
+    },
+    /// The order has expired, e.g. at the end of its `TimeInForce <59>`.
+    Expired,
+    /// The order won't receive further executions today.
+    DoneForDay,
+}
+
+impl Execution {
+    fn leaves_qty(&self) -> Decimal {
+        match self {
+            Self::PendingNew { leaves_qty }
+            | Self::New { leaves_qty }
+            | Self::Trade { leaves_qty, .. }
+            | Self::PendingCancel { leaves_qty, .. }
+            | Self::PendingReplace { leaves_qty, .. }
+            | Self::Replaced { leaves_qty, .. } => *leaves_qty,
+            Self::Canceled { .. } | Self::Rejected { .. } | Self::Expired | Self::DoneForDay => {
+                Decimal::ZERO
+            }
+        }
+    }
+
+    fn orig_cl_ord_id(&self) -> Option<&str> {
+        match self {
+            Self::PendingCancel { orig_cl_ord_id, .. }
+            | Self::Canceled { orig_cl_ord_id }
+            | Self::PendingReplace { orig_cl_ord_id, .. }
+            | Self::Replaced { orig_cl_ord_id, .. } => Some(orig_cl_ord_id.as_str()),
+            _ => None,
+        }
+    }
+
+    fn exec_type(&self) -> fix44::ExecType {
+        use fix44::ExecType;
+        match self {
+            Self::PendingNew { .. } => ExecType::PendingNew,
+            Self::New { .. } => ExecType::New,
+            Self::Trade { .. } => ExecType::Trade,
+            Self::PendingCancel { .. } => ExecType::PendingCancel,
+            Self::Canceled { .. } => ExecType::Canceled,
+            Self::PendingReplace { .. } => ExecType::PendingReplace,
+            Self::Replaced { .. } => ExecType::Replace,
+            Self::Rejected { .. } => ExecType::Rejected,
+            Self::Expired => ExecType::Expired,
+            Self::DoneForDay => ExecType::DoneForDay,
+        }
+    }
+
+    fn ord_status(&self, cum_qty: Decimal) -> fix44::OrdStatus {
+        use fix44::OrdStatus;
+        let fill_status = |leaves_qty: &Decimal| {
+            if leaves_qty.mantissa() <= 0 {
+                OrdStatus::Filled
+            } else if cum_qty.mantissa() > 0 {
+                OrdStatus::PartiallyFilled
+            } else {
+                OrdStatus::New
+            }
+        };
+        match self {
+            Self::PendingNew { .. } => OrdStatus::PendingNew,
+            Self::New { .. } => OrdStatus::New,
+            Self::Trade { leaves_qty, .. } | Self::Replaced { leaves_qty, .. } => {
+                fill_status(leaves_qty)
+            }
+            Self::PendingCancel { .. } => OrdStatus::PendingCancel,
+            Self::Canceled { .. } => OrdStatus::Canceled,
+            Self::PendingReplace { .. } => OrdStatus::PendingReplace,
+            Self::Rejected { .. } => OrdStatus::Rejected,
+            Self::Expired => OrdStatus::Expired,
+            Self::DoneForDay => OrdStatus::DoneForDay,
+        }
+    }
+}
+
+/// The body of an `ExecutionReport <8>`, with `ExecType <150>`, `OrdStatus
+/// <39>` and the conditionally required fields determined by an
+/// [`Execution`].
+///
+/// # Examples
+///
+/// ```
+/// use fefix::definitions::{fix44, Execution, ExecutionReportBuilder};
+/// use fefix::fix_values::Decimal;
+/// use fefix::tagvalue::{Config, Encoder};
+///
+/// let fill = Execution::Trade {
+///     last_qty: Decimal::new(40, 0),
+///     last_px: Decimal::new(15008, 2),
+///     leaves_qty: Decimal::new(60, 0),
+/// };
+/// let mut report = ExecutionReportBuilder::new("O-1", "E-2", "AAPL", fix44::Side::Buy, fill);
+/// report.set_cl_ord_id("C-1");
+/// report.set_cum_qty(Decimal::new(40, 0), Decimal::new(15008, 2));
+///
+/// let mut encoder = Encoder::<Config>::default();
+/// encoder.config_mut().set_separator(b'|');
+/// let mut buffer = Vec::new();
+/// let mut msg = encoder.start_message(b"FIX.4.4", &mut buffer, ExecutionReportBuilder::MSG_TYPE);
+/// report.encode(&mut msg);
+/// let data = msg.wrap();
+/// assert_eq!(report.ord_status(), fix44::OrdStatus::PartiallyFilled);
+/// assert!(data.windows(12).any(|window| window == b"|150=F|39=1|"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExecutionReportBuilder {
+    order_id: String,
+    exec_id: String,
+    symbol: String,
+    side: fix44::Side,
+    execution: Execution,
+    cl_ord_id: Option<String>,
+    order_qty: Option<Decimal>,
+    cum_qty: Decimal,
+    avg_px: Decimal,
+    transact_time: Option<Timestamp>,
+    text: Option<String>,
+}
+
+impl ExecutionReportBuilder {
+    /// The `MsgType <35>` of `ExecutionReport <8>`.
+    pub const MSG_TYPE: &'static [u8] = b"8";
+
+    /// Creates a new [`ExecutionReportBuilder`] for the order with `OrderID
+    /// <37>` `order_id`, `Symbol <55>` `symbol` and `Side <54>` `side`, which
+    /// reports `execution` as `ExecID <17>` `exec_id`.
+    ///
+    /// `CumQty <14>` and `AvgPx <6>` are zero, see
+    /// [`ExecutionReportBuilder::set_cum_qty`].
+    pub fn new<S1, S2, S3>(
+        order_id: S1,
+        exec_id: S2,
+        symbol: S3,
+        side: fix44::Side,
+        execution: Execution,
+    ) -> Self
+    where
+        S1: Into<String>,
+        S2: Into<String>,
+        S3: Into<String>,
+    {
+        Self {
+            order_id: order_id.into(),
+            exec_id: exec_id.into(),
+            symbol: symbol.into(),
+            side,
+            execution,
+            cl_ord_id: None,
+            order_qty: None,
+            cum_qty: Decimal::ZERO,
+            avg_px: Decimal::ZERO,
+            transact_time: None,
+            text: None,
+        }
+    }
+
+    /// Returns the [`Execution`] of `self`.
+    pub fn execution(&self) -> &Execution {
+        &self.execution
+    }
+
+    /// Returns the `ExecType <150>` of `self`.
+    pub fn exec_type(&self) -> fix44::ExecType {
+        self.execution.exec_type()
+    }
+
+    /// Returns the `OrdStatus <39>` of `self`, which follows from its
+    /// [`Execution`] and `CumQty <14>`.
+    pub fn ord_status(&self) -> fix44::OrdStatus {
+        self.execution.ord_status(self.cum_qty)
+    }
+
+    /// Returns the `LeavesQty <151>` of `self`.
+    pub fn leaves_qty(&self) -> Decimal {
+        self.execution.leaves_qty()
+    }
+
+    /// Sets `ClOrdID <11>` to `cl_ord_id`.
+    pub fn set_cl_ord_id<S>(&mut self, cl_ord_id: S)
+    where
+        S: Into<String>,
+    {
+        self.cl_ord_id = Some(cl_ord_id.into());
+    }
+
+    /// Sets `OrderQty <38>` to `order_qty`.
+    pub fn set_order_qty(&mut self, order_qty: Decimal) {
+        self.order_qty = Some(order_qty);
+    }
+
+    /// Sets `CumQty <14>` to `cum_qty` and `AvgPx <6>` to `avg_px`, i.e. the
+    /// total quantity filled so far and its average price.
+    pub fn set_cum_qty(&mut self, cum_qty: Decimal, avg_px: Decimal) {
+        self.cum_qty = cum_qty;
+        self.avg_px = avg_px;
+    }
+
+    /// Sets `TransactTime <60>` to `transact_time`.
+    pub fn set_transact_time(&mut self, transact_time: Timestamp) {
+        self.transact_time = Some(transact_time);
+    }
+
+    /// Sets `Text <58>` to `text`.
+    pub fn set_text<S>(&mut self, text: S)
+    where
+        S: Into<String>,
+    {
+        self.text = Some(text.into());
+    }
+
+    /// Writes all fields of `self` to `msg`, which must be an
+    /// `ExecutionReport <8>` with its standard header already in place.
+    pub fn encode<B, C>(&self, msg: &mut EncoderHandle<B, C>)
+    where
+        B: Buffer,
+        C: Configure,
+    {
+        msg.set(fix44::ORDER_ID, self.order_id.as_str());
+        if let Some(cl_ord_id) = &self.cl_ord_id {
+            msg.set(fix44::CL_ORD_ID, cl_ord_id.as_str());
+        }
+        if let Some(orig_cl_ord_id) = self.execution.orig_cl_ord_id() {
+            msg.set(fix44::ORIG_CL_ORD_ID, orig_cl_ord_id);
+        }
+        msg.set(fix44::EXEC_ID, self.exec_id.as_str());
+        msg.set(fix44::EXEC_TYPE, self.exec_type());
+        msg.set(fix44::ORD_STATUS, self.ord_status());
+        if let Execution::Rejected { reason } = &self.execution {
+            msg.set(fix44::ORD_REJ_REASON, *reason);
+        }
+        msg.set(fix44::SYMBOL, self.symbol.as_str());
+        msg.set(fix44::SIDE, self.side);
+        if let Some(order_qty) = self.order_qty {
+            msg.set(fix44::ORDER_QTY, order_qty);
+        }
+        if let Execution::Trade {
+            last_qty, last_px, ..
+        } = &self.execution
+        {
+            msg.set(fix44::LAST_QTY, *last_qty);
+            msg.set(fix44::LAST_PX, *last_px);
+        }
+        msg.set(fix44::LEAVES_QTY, self.leaves_qty());
+        msg.set(fix44::CUM_QTY, self.cum_qty);
+        msg.set(fix44::AVG_PX, self.avg_px);
+        if let Some(transact_time) = &self.transact_time {
+            msg.set(fix44::TRANSACT_TIME, transact_time.clone());
+        }
+        if let Some(text) = &self.text {
+            msg.set(fix44::TEXT, text.as_str());
+        }
+    }
+}
+
+/// The body of an `OrderCancelReject <9>`, which refuses an `OrderCancelRequest
+/// <F>` or an `OrderCancelReplaceRequest <G>`.
+///
+/// # Examples
+///
+/// ```
+/// use fefix::definitions::{fix44, OrderCancelRejectBuilder};
+///
+/// let reject = OrderCancelRejectBuilder::unknown_order(
+///     "C-2",
+///     "C-1",
+///     fix44::CxlRejResponseTo::OrderCancelRequest,
+/// );
+/// assert_eq!(reject.order_id(), "NONE");
+/// assert_eq!(reject.ord_status(), fix44::OrdStatus::Rejected);
+/// assert_eq!(reject.reason(), Some(fix44::CxlRejReason::UnknownOrder));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrderCancelRejectBuilder {
+    order_id: String,
+    cl_ord_id: String,
+    orig_cl_ord_id: String,
+    ord_status: fix44::OrdStatus,
+    response_to: fix44::CxlRejResponseTo,
+    reason: Option<fix44::CxlRejReason>,
+    text: Option<String>,
+}
+
+impl OrderCancelRejectBuilder {
+    /// The `MsgType <35>` of `OrderCancelReject <9>`.
+    pub const MSG_TYPE: &'static [u8] = b"9";
+
+    /// Creates a new [`OrderCancelRejectBuilder`] that refuses the request
+    /// with `ClOrdID <11>` `cl_ord_id`, i.e. `response_to`, about the order
+    /// with `OrderID <37>` `order_id` and `ClOrdID` `orig_cl_ord_id`.
+    /// `ord_status` is the current, unchanged status of the order.
+    pub fn new<S1, S2, S3>(
+        order_id: S1,
+        cl_ord_id: S2,
+        orig_cl_ord_id: S3,
+        ord_status: fix44::OrdStatus,
+        response_to: fix44::CxlRejResponseTo,
+    ) -> Self
+    where
+        S1: Into<String>,
+        S2: Into<String>,
+        S3: Into<String>,
+    {
+        Self {
+            order_id: order_id.into(),
+            cl_ord_id: cl_ord_id.into(),
+            orig_cl_ord_id: orig_cl_ord_id.into(),
+            ord_status,
+            response_to,
+            reason: None,
+            text: None,
+        }
+    }
+
+    /// Creates a new [`OrderCancelRejectBuilder`] for a request about an order
+    /// that doesn't exist. As FIX prescribes, `OrderID <37>` is `NONE`,
+    /// `OrdStatus <39>` is `Rejected` and `CxlRejReason <102>` is
+    /// `UnknownOrder`.
+    pub fn unknown_order<S1, S2>(
+        cl_ord_id: S1,
+        orig_cl_ord_id: S2,
+        response_to: fix44::CxlRejResponseTo,
+    ) -> Self
+    where
+        S1: Into<String>,
+        S2: Into<String>,
+    {
+        let mut reject = Self::new(
+            "NONE",
+            cl_ord_id,
+            orig_cl_ord_id,
+            fix44::OrdStatus::Rejected,
+            response_to,
+        );
+        reject.reason = Some(fix44::CxlRejReason::UnknownOrder);
+        reject
+    }
+
+    /// Returns the `OrderID <37>` of `self`.
+    pub fn order_id(&self) -> &str {
+        self.order_id.as_str()
+    }
+
+    /// Returns the `OrdStatus <39>` of `self`.
+    pub fn ord_status(&self) -> fix44::OrdStatus {
+        self.ord_status
+    }
+
+    /// Returns the `CxlRejReason <102>` of `self`, if any.
+    pub fn reason(&self) -> Option<fix44::CxlRejReason> {
+        self.reason
+    }
+
+    /// Sets `CxlRejReason <102>` to `reason`.
+    pub fn set_reason(&mut self, reason: fix44::CxlRejReason) {
+        self.reason = Some(reason);
+    }
+
+    /// Sets `Text <58>` to `text`.
+    pub fn set_text<S>(&mut self, text: S)
+    where
+        S: Into<String>,
+    {
+        self.text = Some(text.into());
+    }
+
+    /// Writes all fields of `self` to `msg`, which must be an
+    /// `OrderCancelReject <9>` with its standard header already in place.
+    pub fn encode<B, C>(&self, msg: &mut EncoderHandle<B, C>)
+    where
+        B: Buffer,
+        C: Configure,
+    {
+        msg.set(fix44::ORDER_ID, self.order_id.as_str());
+        msg.set(fix44::CL_ORD_ID, self.cl_ord_id.as_str());
+        msg.set(fix44::ORIG_CL_ORD_ID, self.orig_cl_ord_id.as_str());
+        msg.set(fix44::ORD_STATUS, self.ord_status);
+        msg.set(fix44::CXL_REJ_RESPONSE_TO, self.response_to);
+        if let Some(reason) = self.reason {
+            msg.set(fix44::CXL_REJ_REASON, reason);
+        }
+        if let Some(text) = &self.text {
+            msg.set(fix44::TEXT, text.as_str());
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tagvalue::{test_decoder, Config, Encoder, Validator};
+    use crate::Dictionary;
+
+    fn encode<F>(msg_type: &[u8], f: F) -> Vec<u8>
+    where
+        F: FnOnce(&mut EncoderHandle<Vec<u8>>),
+    {
+        let mut encoder = Encoder::<Config>::default();
+        encoder.config_mut().set_separator(b'|');
+        let mut buffer = Vec::new();
+        let mut msg = encoder.start_message(b"FIX.4.4", &mut buffer, msg_type);
+        msg.set(fix44::SENDER_COMP_ID, "SELL");
+        msg.set(fix44::TARGET_COMP_ID, "BUY");
+        msg.set(fix44::MSG_SEQ_NUM, 2u64);
+        msg.set(fix44::SENDING_TIME, "20210101-00:00:00");
+        f(&mut msg);
+        msg.wrap().to_vec()
+    }
+
+    #[test]
+    fn execution_reports_are_valid() {
+        let qty = |n| Decimal::new(n, 0);
+        let executions = vec![
+            Execution::New {
+                leaves_qty: qty(100),
+            },
+            Execution::Trade {
+                last_qty: qty(100),
+                last_px: Decimal::new(9925, 2),
+                leaves_qty: qty(0),
+            },
+            Execution::Canceled {
+                orig_cl_ord_id: "C-1".to_string(),
+            },
+            Execution::Replaced {
+                orig_cl_ord_id: "C-1".to_string(),
+                leaves_qty: qty(50),
+            },
+            Execution::Rejected {
+                reason: fix44::OrdRejReason::UnknownSymbol,
+            },
+        ];
+        let validator = Validator::new(Dictionary::fix44());
+        let mut decoder = test_decoder();
+        for execution in executions {
+            let mut report =
+                ExecutionReportBuilder::new("O-1", "E-1", "AAPL", fix44::Side::Sell, execution);
+            report.set_cl_ord_id("C-2");
+            let data = encode(ExecutionReportBuilder::MSG_TYPE, |msg| report.encode(msg));
+            let msg = decoder.decode(&data[..]).unwrap();
+            assert_eq!(validator.validate(&msg), vec![]);
+            let decoded = fix44::messages::ExecutionReport::decode(&msg).unwrap();
+            assert_eq!(decoded.exec_type, report.exec_type());
+            assert_eq!(decoded.ord_status, report.ord_status());
+            assert_eq!(decoded.orig_cl_ord_id, report.execution().orig_cl_ord_id());
+            assert_eq!(decoded.leaves_qty, report.leaves_qty().to_string());
+        }
+    }
+
+    #[test]
+    fn ord_status_follows_from_execution() {
+        let qty = |n| Decimal::new(n, 0);
+        let trade = |leaves_qty| Execution::Trade {
+            last_qty: qty(10),
+            last_px: qty(1),
+            leaves_qty,
+        };
+        let report = |execution, cum_qty| {
+            let mut report =
+                ExecutionReportBuilder::new("O-1", "E-1", "AAPL", fix44::Side::Buy, execution);
+            report.set_cum_qty(qty(cum_qty), qty(1));
+            report
+        };
+        assert_eq!(
+            report(trade(qty(90)), 10).ord_status(),
+            fix44::OrdStatus::PartiallyFilled
+        );
+        assert_eq!(
+            report(trade(qty(0)), 100).ord_status(),
+            fix44::OrdStatus::Filled
+        );
+        let replaced = |cum_qty| {
+            let execution = Execution::Replaced {
+                orig_cl_ord_id: "C-1".to_string(),
+                leaves_qty: qty(50),
+            };
+            report(execution, cum_qty).ord_status()
+        };
+        assert_eq!(replaced(0), fix44::OrdStatus::New);
+        assert_eq!(replaced(10), fix44::OrdStatus::PartiallyFilled);
+        let expired = report(Execution::Expired, 10);
+        assert_eq!(expired.ord_status(), fix44::OrdStatus::Expired);
+        assert_eq!(expired.leaves_qty(), Decimal::ZERO);
+    }
+
+    #[test]
+    fn order_cancel_reject_roundtrip() {
+        let mut reject = OrderCancelRejectBuilder::new(
+            "O-1",
+            "C-2",
+            "C-1",
+            fix44::OrdStatus::Filled,
+            fix44::CxlRejResponseTo::OrderCancelReplaceRequest,
+        );
+        reject.set_reason(fix44::CxlRejReason::TooLateToCancel);
+        reject.set_text("Already filled");
+        let data = encode(OrderCancelRejectBuilder::MSG_TYPE, |msg| reject.encode(msg));
+        let mut decoder = test_decoder();
+        let msg = decoder.decode(&data[..]).unwrap();
+        assert_eq!(Validator::new(Dictionary::fix44()).validate(&msg), vec![]);
+        let decoded = fix44::messages::OrderCancelReject::decode(&msg).unwrap();
+        assert_eq!(decoded.order_id, "O-1");
+        assert_eq!(decoded.orig_cl_ord_id, "C-1");
+        assert_eq!(decoded.ord_status, fix44::OrdStatus::Filled);
+        assert_eq!(
+            decoded.cxl_rej_response_to,
+            fix44::CxlRejResponseTo::OrderCancelReplaceRequest
+        );
+        assert_eq!(
+            decoded.cxl_rej_reason,
+            Some(fix44::CxlRejReason::TooLateToCancel)
+        );
+        assert_eq!(decoded.text, Some("Already filled"));
+    }
+}
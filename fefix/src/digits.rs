@@ -0,0 +1,224 @@
+//! Number parsing primitives for the hot paths of field value decoding, i.e.
+//! `int`, `Qty`, `Price`, and similar fields.
+//!
+//! Digits are parsed eight at a time with SWAR (SIMD within a register)
+//! arithmetic. With the `fast-float` feature, [`parse_f64`] also takes an exact
+//! shortcut for the common case of short decimals, and otherwise falls back to
+//! the standard library; all code paths give identical results.
+
+use core::convert::TryFrom;
+
+// 10^0 to 10^22, which are all exactly representable as `f64`.
+#[cfg(feature = "fast-float")]
+const F64_POWERS_OF_TEN: [f64; 23] = [
+    1e0, 1e1, 1e2, 1e3, 1e4, 1e5, 1e6, 1e7, 1e8, 1e9, 1e10, 1e11, 1e12, 1e13, 1e14, 1e15, 1e16,
+    1e17, 1e18, 1e19, 1e20, 1e21, 1e22,
+];
+
+// Scales larger than this don't fit in a `u64` power of ten.
+const MAX_SCALE: usize = 18;
+
+/// Parses `data` as an unsigned integer, with an optional `+` sign (just like
+/// [`str::parse`]). Returns [`None`] on invalid digits and overflow.
+#[inline]
+pub fn parse_u64(data: &[u8]) -> Option<u64> {
+    let digits = data.strip_prefix(b"+").unwrap_or(data);
+    if digits.is_empty() {
+        None
+    } else {
+        parse_digits(digits)
+    }
+}
+
+/// Parses `data` as a signed integer, with an optional `+` or `-` sign (just
+/// like [`str::parse`]). Returns [`None`] on invalid digits and overflow.
+#[inline]
+pub fn parse_i64(data: &[u8]) -> Option<i64> {
+    match data.split_first() {
+        Some((b'-', digits)) if !digits.is_empty() => negate(parse_digits(digits)?),
+        _ => i64::try_from(parse_u64(data)?).ok(),
+    }
+}
+
+/// Parses `data` as a FIX `float`, i.e. an optional minus sign followed by
+/// digits and at most one decimal point, into a mantissa and a scale. Returns
+/// [`None`] on invalid syntax, if the mantissa doesn't fit in an [`i64`], or if
+/// there are more than 18 decimal digits.
+#[inline]
+pub fn parse_decimal(data: &[u8]) -> Option<(i64, u32)> {
+    let (is_negative, digits) = match data.split_first() {
+        Some((b'-', rest)) => (true, rest),
+        _ => (false, data),
+    };
+    let mantissa = parse_unsigned_decimal(digits)?;
+    let value = if is_negative {
+        negate(mantissa.0)?
+    } else {
+        i64::try_from(mantissa.0).ok()?
+    };
+    Some((value, mantissa.1))
+}
+
+/// Parses `data` as a FIX `float` into the closest [`f64`]. Returns [`None`] on
+/// invalid syntax.
+#[inline]
+pub fn parse_f64(data: &[u8]) -> Option<f64> {
+    #[cfg(feature = "fast-float")]
+    {
+        let (is_negative, digits) = match data.split_first() {
+            Some((b'-', rest)) => (true, rest),
+            _ => (false, data),
+        };
+        // Both the mantissa and the power of ten are exact, so a single
+        // division is correctly rounded.
+        if let Some((mantissa, scale)) = parse_unsigned_decimal(digits) {
+            if mantissa <= 1 << 53 {
+                let value = mantissa as f64 / F64_POWERS_OF_TEN[scale as usize];
+                return Some(if is_negative { -value } else { value });
+            }
+        }
+    }
+    crate::fix_values::validate_float(data).ok()?;
+    // Only ASCII characters.
+    core::str::from_utf8(data).ok()?.parse().ok()
+}
+
+fn negate(magnitude: u64) -> Option<i64> {
+    if magnitude <= i64::MAX as u64 + 1 {
+        Some((magnitude as i64).wrapping_neg())
+    } else {
+        None
+    }
+}
+
+// Parses unsigned digits with at most one decimal point.
+fn parse_unsigned_decimal(digits: &[u8]) -> Option<(u64, u32)> {
+    let (integer, fraction) = match digits.iter().position(|byte| *byte == b'.') {
+        Some(i) => (&digits[..i], &digits[i + 1..]),
+        None => (digits, &[][..]),
+    };
+    if integer.is_empty() && fraction.is_empty() || fraction.len() > MAX_SCALE {
+        return None;
+    }
+    let integer = if integer.is_empty() {
+        0
+    } else {
+        parse_digits(integer)?
+    };
+    if fraction.is_empty() {
+        return Some((integer, 0));
+    }
+    let mantissa = integer
+        .checked_mul(10u64.pow(fraction.len() as u32))?
+        .checked_add(parse_digits(fraction)?)?;
+    Some((mantissa, fraction.len() as u32))
+}
+
+// Parses a non-empty sequence of ASCII digits.
+#[inline]
+fn parse_digits(digits: &[u8]) -> Option<u64> {
+    let mut n = 0u64;
+    let mut chunks = digits.chunks_exact(8);
+    for chunk in &mut chunks {
+        n = n
+            .checked_mul(100_000_000)?
+            .checked_add(parse_eight_digits(chunk)? as u64)?;
+    }
+    for byte in chunks.remainder() {
+        if !byte.is_ascii_digit() {
+            return None;
+        }
+        n = n.checked_mul(10)?.checked_add((byte - b'0') as u64)?;
+    }
+    Some(n)
+}
+
+// See <https://lemire.me/blog/2022/01/21/swar-explained-parsing-eight-digits/>.
+#[inline]
+fn parse_eight_digits(chunk: &[u8]) -> Option<u32> {
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(chunk);
+    let n = u64::from_le_bytes(bytes);
+    // Every byte must be within `0x30..=0x39`.
+    let high_nibbles = n & 0xF0F0_F0F0_F0F0_F0F0;
+    let carries = (n.wrapping_add(0x0606_0606_0606_0606) & 0xF0F0_F0F0_F0F0_F0F0) >> 4;
+    if high_nibbles | carries != 0x3333_3333_3333_3333 {
+        return None;
+    }
+    let n = n - 0x3030_3030_3030_3030;
+    let n = n.wrapping_mul(10) + (n >> 8);
+    let n = ((n & 0x0000_00FF_0000_00FF).wrapping_mul(100 + (1_000_000 << 32))
+        + ((n >> 16) & 0x0000_00FF_0000_00FF).wrapping_mul(1 + (10_000 << 32)))
+        >> 32;
+    Some(n as u32)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use quickcheck_macros::quickcheck;
+
+    #[test]
+    fn eight_digits() {
+        assert_eq!(parse_eight_digits(b"12345678"), Some(12_345_678));
+        assert_eq!(parse_eight_digits(b"00000000"), Some(0));
+        assert_eq!(parse_eight_digits(b"99999999"), Some(99_999_999));
+        for invalid in &[b"1234567:", b"/1234567", b"1234 678", b"12345\x0078"] {
+            assert_eq!(parse_eight_digits(&invalid[..]), None);
+        }
+    }
+
+    #[test]
+    fn integers_overflow() {
+        assert_eq!(parse_u64(b"18446744073709551615"), Some(u64::MAX));
+        assert_eq!(parse_u64(b"18446744073709551616"), None);
+        assert_eq!(parse_i64(b"-9223372036854775808"), Some(i64::MIN));
+        assert_eq!(parse_i64(b"-9223372036854775809"), None);
+        assert_eq!(parse_i64(b"9223372036854775808"), None);
+        assert_eq!(parse_u64(b"0000000000000000000000001"), Some(1));
+    }
+
+    #[test]
+    fn floats() {
+        assert_eq!(parse_f64(b"1.37215"), Some(1.37215));
+        assert_eq!(parse_f64(b"-0.5"), Some(-0.5));
+        assert_eq!(parse_f64(b".25"), Some(0.25));
+        assert_eq!(parse_f64(b"3."), Some(3.0));
+        assert_eq!(
+            parse_f64(b"123456789012345678901234567890"),
+            Some(1.2345678901234568e29)
+        );
+        for invalid in &[
+            &b""[..],
+            b".",
+            b"-",
+            b"1e5",
+            b"+1",
+            b"1.2.3",
+            b"inf",
+            b"NaN",
+        ] {
+            assert_eq!(parse_f64(invalid), None);
+        }
+    }
+
+    #[quickcheck]
+    fn integers_match_std(s: String) -> bool {
+        parse_u64(s.as_bytes()) == s.parse().ok() && parse_i64(s.as_bytes()) == s.parse().ok()
+    }
+
+    #[quickcheck]
+    fn integer_roundtrip(n: i64, m: u64) -> bool {
+        parse_i64(n.to_string().as_bytes()) == Some(n)
+            && parse_u64(m.to_string().as_bytes()) == Some(m)
+    }
+
+    #[quickcheck]
+    fn floats_match_std(mantissa: u64, scale: u8) -> bool {
+        let digits = mantissa.to_string();
+        let scale = (scale as usize % 20).min(digits.len());
+        let (integer, fraction) = digits.split_at(digits.len() - scale);
+        let s = format!("{}.{}", integer, fraction);
+        parse_f64(s.as_bytes()) == s.parse().ok()
+    }
+}
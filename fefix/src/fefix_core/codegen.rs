@@ -2,8 +2,11 @@
 
 use super::{dict, TagU16};
 use fnv::FnvHashSet;
-use heck::{CamelCase, ShoutySnakeCase};
+use heck::{CamelCase, ShoutySnakeCase, SnakeCase};
 use indoc::indoc;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
 
 const FEFIX_VERSION: &str = env!("CARGO_PKG_VERSION");
 const FOUR_SPACES: &str = "    ";
@@ -23,18 +26,26 @@ const FOUR_SPACES: &str = "    ";
 /// // ALL CHANGES WILL BE OVERWRITTEN.
 /// ```
 pub fn generated_code_notice() -> String {
+    generated_code_notice_with(true)
+}
+
+fn generated_code_notice_with(timestamp: bool) -> String {
     use chrono::prelude::*;
+    let timestamp = if timestamp {
+        format!(" on {}", Utc::now().to_rfc2822())
+    } else {
+        String::new()
+    };
     format!(
         indoc!(
             r#"
-            // Generated automatically by FerrumFIX {} on {}.
+            // Generated automatically by FerrumFIX {}{}.
             //
             // DO NOT MODIFY MANUALLY.
             // DO NOT COMMIT TO VERSION CONTROL.
             // ALL CHANGES WILL BE OVERWRITTEN."#
         ),
-        FEFIX_VERSION,
-        Utc::now().to_rfc2822(),
+        FEFIX_VERSION, timestamp,
     )
 }
 
@@ -106,6 +117,32 @@ pub struct Settings {
     derives_for_allowed_values: Vec<String>,
     attributes_for_allowed_values: Vec<String>,
     custom_derive_lines: Vec<String>,
+    generate_messages: bool,
+    generate_message_fields: bool,
+    module_name: Option<String>,
+    module_layout: ModuleLayout,
+    timestamp_in_notice: bool,
+}
+
+/// How [`generate_to`] splits generated code across files.
+///
+/// Please note that [`ModuleLayout`] is marked with `#[non_exhaustive]`, which
+/// future-proofs the enumeration type in case more variants are added.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ModuleLayout {
+    /// All code goes in `{module_name}.rs`, including the `messages` module.
+    SingleFile,
+    /// Message structs go in `{module_name}_messages.rs`, which
+    /// `{module_name}.rs` includes as its `messages` module. Useful to keep
+    /// file sizes manageable for big dictionaries.
+    SeparateMessages,
+}
+
+impl Default for ModuleLayout {
+    fn default() -> Self {
+        Self::SingleFile
+    }
 }
 
 impl Settings {
@@ -145,6 +182,36 @@ impl Settings {
         self.fefix_crate_name = name.into();
     }
 
+    /// Enables or disables the generation of strongly-typed message structs
+    /// by [`gen_definitions`]. Disabled by default.
+    pub fn set_generate_messages(&mut self, generate: bool) {
+        self.generate_messages = generate;
+    }
+
+    /// Enables or disables the generation of a `message_fields` module by
+    /// [`gen_definitions`], with the fields of each message
+    /// ([`gen_message_fields`]). Disabled by default.
+    pub fn set_generate_message_fields(&mut self, generate: bool) {
+        self.generate_message_fields = generate;
+    }
+
+    /// Sets the name of the module generated by [`generate_to`], which is also
+    /// the stem of its output file(s). By default, it's derived from the
+    /// version string of the [`Dictionary`](dict::Dictionary), e.g. `fix44` for
+    /// `FIX.4.4` and `fix50sp2` for `FIX.5.0-SP2`.
+    pub fn set_module_name<S>(&mut self, name: S)
+    where
+        S: Into<String>,
+    {
+        self.module_name = Some(name.into());
+    }
+
+    /// Changes how [`generate_to`] splits generated code across files.
+    /// [`ModuleLayout::SingleFile`] by default.
+    pub fn set_module_layout(&mut self, layout: ModuleLayout) {
+        self.module_layout = layout;
+    }
+
     fn fefix_crate_name(&self) -> &str {
         self.fefix_crate_name.as_str()
     }
@@ -167,6 +234,11 @@ impl Default for Settings {
             attributes_for_allowed_values: vec![],
             fefix_crate_name: "fefix".to_string(),
             custom_derive_lines: vec![],
+            generate_messages: false,
+            generate_message_fields: false,
+            module_name: None,
+            module_layout: ModuleLayout::default(),
+            timestamp_in_notice: true,
         }
     }
 }
@@ -205,11 +277,121 @@ pub fn gen_field_definition(fix_dictionary: dict::Dictionary, field: dict::Field
 /// - `enum` definitions for FIX fields.
 /// - A constant implementor of
 /// [`IsFieldDefinition`](super::dict::IsFieldDefinition) for each FIX field.
+/// - If enabled via [`Settings::set_generate_messages`], a `messages` module
+/// with strongly-typed message structs ([`gen_message_struct`]).
+/// - If enabled via [`Settings::set_generate_message_fields`], a
+/// `message_fields` module with the fields of each message
+/// ([`gen_message_fields`]).
 ///
 /// The Rust code will be free of any leading and trailing whitespace.
 /// An effort is made to provide good formatting, but users shouldn't rely on it
 /// and assume that formatting might be bad.
 pub fn gen_definitions(fix_dictionary: dict::Dictionary, settings: &Settings) -> String {
+    let messages = if settings.generate_messages {
+        gen_messages_module(&fix_dictionary, settings)
+    } else {
+        String::new()
+    };
+    gen_definitions_with_messages(fix_dictionary, settings, messages)
+}
+
+/// Generates the same code as [`gen_definitions`] and writes it to `out_dir`,
+/// ready for [`include!`]. This is meant for build scripts, with `OUT_DIR` as
+/// `out_dir`, so that downstream crates can generate definitions for their
+/// custom dictionaries at build time:
+///
+/// ```no_run
+/// // build.rs
+/// use fefix::codegen::{generate_to, Settings};
+/// use fefix::Dictionary;
+///
+/// fn main() -> std::io::Result<()> {
+///     let spec = std::fs::read_to_string("VENUE44.xml")?;
+///     let dict = Dictionary::from_quickfix_spec(spec).unwrap();
+///     let mut settings = Settings::default();
+///     settings.set_module_name("venue");
+///     settings.set_generate_messages(true);
+///     generate_to(std::env::var("OUT_DIR").unwrap(), &dict, &settings)?;
+///     Ok(())
+/// }
+/// ```
+///
+/// ```ignore
+/// // src/lib.rs
+/// pub mod venue {
+///     include!(concat!(env!("OUT_DIR"), "/venue.rs"));
+/// }
+/// ```
+///
+/// Unlike [`gen_definitions`], the output is deterministic (the generated code
+/// notice has no timestamp) and files are only rewritten when their contents
+/// change, so that unchanged dictionaries don't trigger rebuilds. See
+/// [`Settings::set_module_name`] and [`Settings::set_module_layout`] for file
+/// naming. Returns the path of the file to include.
+pub fn generate_to<P>(
+    out_dir: P,
+    fix_dictionary: &dict::Dictionary,
+    settings: &Settings,
+) -> io::Result<PathBuf>
+where
+    P: AsRef<Path>,
+{
+    let settings = &Settings {
+        timestamp_in_notice: false,
+        ..settings.clone()
+    };
+    let module_name = settings
+        .module_name
+        .clone()
+        .unwrap_or_else(|| default_module_name(fix_dictionary.get_version()));
+    let path = out_dir.as_ref().join(format!("{}.rs", module_name));
+    let code = match settings.module_layout {
+        ModuleLayout::SeparateMessages if settings.generate_messages => {
+            let filename = format!("{}_messages.rs", module_name);
+            let messages_code = format!(
+                "{}\n\n{}",
+                generated_code_notice_with(false),
+                gen_messages_items(fix_dictionary, settings).trim_end(),
+            );
+            write_if_changed(&out_dir.as_ref().join(&filename), &messages_code)?;
+            let messages = format!(
+                indoc!(
+                    r#"
+                    /// Strongly-typed message structs.
+                    pub mod messages {{
+                        include!("{}");
+                    }}"#
+                ),
+                filename
+            );
+            gen_definitions_with_messages(fix_dictionary.clone(), settings, messages)
+        }
+        _ => gen_definitions(fix_dictionary.clone(), settings),
+    };
+    write_if_changed(&path, &code)?;
+    Ok(path)
+}
+
+fn default_module_name(fix_version: &str) -> String {
+    fix_version
+        .chars()
+        .filter(char::is_ascii_alphanumeric)
+        .collect::<String>()
+        .to_lowercase()
+}
+
+fn write_if_changed(path: &Path, contents: &str) -> io::Result<()> {
+    match fs::read(path) {
+        Ok(existing) if existing == contents.as_bytes() => Ok(()),
+        _ => fs::write(path, contents),
+    }
+}
+
+fn gen_definitions_with_messages(
+    fix_dictionary: dict::Dictionary,
+    settings: &Settings,
+    messages: String,
+) -> String {
     let enums = fix_dictionary
         .iter_fields()
         .filter_map(|field| gen_enum_of_allowed_values(field, settings))
@@ -220,6 +402,11 @@ pub fn gen_definitions(fix_dictionary: dict::Dictionary, settings: &Settings) ->
         .map(|field| gen_field_definition(fix_dictionary.clone(), field))
         .collect::<Vec<String>>()
         .join("\n");
+    let message_fields = if settings.generate_message_fields {
+        gen_message_fields_module(&fix_dictionary, settings)
+    } else {
+        String::new()
+    };
     let top_comment =
         onixs_link_to_dictionary(fix_dictionary.get_version()).unwrap_or(String::new());
     let code = format!(
@@ -236,21 +423,382 @@ pub fn gen_definitions(fix_dictionary: dict::Dictionary, settings: &Settings) ->
 
             {enum_definitions}
 
-            {field_defs}"#
+            {field_defs}
+
+            {message_fields}
+
+            {messages}"#
         ),
-        notice = generated_code_notice(),
+        notice = generated_code_notice_with(settings.timestamp_in_notice),
         top_comment = top_comment,
         enum_definitions = enums,
         field_defs = field_defs,
+        message_fields = message_fields,
+        messages = messages,
         fefix_path = settings.fefix_crate_name(),
     );
-    code
+    code.trim_end().to_string()
+}
+
+fn gen_messages_module(fix_dictionary: &dict::Dictionary, settings: &Settings) -> String {
+    format!(
+        indoc!(
+            r#"
+            /// Strongly-typed message structs.
+            pub mod messages {{
+            {items}}}"#
+        ),
+        items = indent_string(
+            gen_messages_items(fix_dictionary, settings),
+            settings.indentation.as_str()
+        ),
+    )
+}
+
+/// Generates the contents of the `messages` module.
+fn gen_messages_items(fix_dictionary: &dict::Dictionary, settings: &Settings) -> String {
+    let structs = fix_dictionary
+        .iter_messages()
+        .filter(|message| message.scenario() == "base")
+        .map(|message| gen_message_struct(message, settings))
+        .collect::<Vec<String>>()
+        .join("\n\n");
+    format!(
+        indoc!(
+            r#"
+            use {fefix_path}::definitions::{{decode_optional, decode_required, TypedMessageError}};
+            use {fefix_path}::fix_values::{{Date, DayOfMonth, LocalMktDate, MonthYear, Time, Timestamp}};
+            use {fefix_path}::tagvalue::{{Configure, EncoderHandle, FieldAccess}};
+            use {fefix_path}::Buffer;
+
+            {structs}"#
+        ),
+        fefix_path = settings.fefix_crate_name(),
+        structs = structs,
+    )
+}
+
+fn gen_message_fields_module(fix_dictionary: &dict::Dictionary, settings: &Settings) -> String {
+    let modules = fix_dictionary
+        .iter_messages()
+        .filter(|message| message.scenario() == "base")
+        .map(|message| gen_message_fields(message, settings))
+        .collect::<Vec<String>>()
+        .join("\n\n");
+    format!(
+        indoc!(
+            r#"
+            /// Field definitions scoped by message type.
+            pub mod message_fields {{
+            {modules}}}"#
+        ),
+        modules = indent_string(modules, settings.indentation.as_str()),
+    )
+}
+
+/// Generates the Rust code for a module named after `message`, which
+/// re-exports the definitions of all fields of `message` and lists them in
+/// `REQUIRED_FIELDS` and `OPTIONAL_FIELDS`. These allow for lightweight
+/// validation without a [`Dictionary`](dict::Dictionary).
+///
+/// Fields within components are flattened into the module. Repeating groups
+/// are represented by their `NumInGroup` field only. Standard header and
+/// trailer fields are not included.
+///
+/// The generated code expects to live in a grandchild module of the output
+/// of [`gen_definitions`], from which it references field definitions.
+pub fn gen_message_fields(message: dict::Message, settings: &Settings) -> String {
+    let mut tags = FnvHashSet::default();
+    let mut fields = Vec::new();
+    collect_message_field_definitions(message.layout(), true, &mut tags, &mut fields);
+    let list = |required: bool| {
+        fields
+            .iter()
+            .filter(|(_, r)| *r == required)
+            .map(|(definition, _)| format!("{},", definition))
+            .collect::<Vec<String>>()
+            .join("\n")
+    };
+    let mut identifier = message.name().to_snake_case();
+    if RUST_KEYWORDS.contains(&identifier.as_str()) {
+        identifier = format!("r#{}", identifier);
+    }
+    let reexports = fields
+        .iter()
+        .map(|(definition, _)| format!("pub use super::super::{};", definition))
+        .collect::<Vec<String>>()
+        .join("\n");
+    format!(
+        indoc!(
+            r#"
+            /// Body fields of `{name} <{msg_type}>` messages.
+            pub mod {identifier} {{
+                use {fefix_path}::definitions::HardCodedFixFieldDefinition;
+
+            {reexports}
+
+                /// The `MsgType <35>` of this message.
+                pub const MSG_TYPE: &[u8] = b"{msg_type}";
+
+                /// All fields that must be present in this message.
+                pub const REQUIRED_FIELDS: &[&HardCodedFixFieldDefinition] = &[
+            {required}
+                ];
+
+                /// All fields that may be present in this message.
+                pub const OPTIONAL_FIELDS: &[&HardCodedFixFieldDefinition] = &[
+            {optional}
+                ];
+            }}"#
+        ),
+        name = message.name(),
+        msg_type = message.msg_type(),
+        identifier = identifier,
+        fefix_path = settings.fefix_crate_name(),
+        reexports = indent_string(reexports, settings.indentation.as_str()).trim_end(),
+        required = indent_string(list(true), &settings.indentation.repeat(2)).trim_end(),
+        optional = indent_string(list(false), &settings.indentation.repeat(2)).trim_end(),
+    )
+}
+
+fn collect_message_field_definitions<'a>(
+    layout: impl Iterator<Item = dict::LayoutItem<'a>>,
+    required: bool,
+    tags: &mut FnvHashSet<TagU16>,
+    fields: &mut Vec<(String, bool)>,
+) {
+    for item in layout {
+        let item_required = required && item.required();
+        match item.kind() {
+            dict::LayoutItemKind::Field(field) | dict::LayoutItemKind::Group(field, _) => {
+                if tags.insert(field.tag()) {
+                    fields.push((field.name().to_shouty_snake_case(), item_required));
+                }
+            }
+            dict::LayoutItemKind::Component(component) => {
+                collect_message_field_definitions(component.items(), item_required, tags, fields);
+            }
+        }
+    }
+}
+
+/// Generates the Rust code for a strongly-typed `struct` with one member for
+/// each field of `message`, together with `decode` and `encode` methods.
+///
+/// Fields within components are flattened into the `struct`, while repeating
+/// groups are left out. Optional fields are wrapped in [`Option`]. Decimal
+/// values (e.g. prices and quantities) are kept as `&str` to avoid any loss of
+/// precision.
+///
+/// The generated code expects to live in a child module of the output of
+/// [`gen_definitions`], from which it references field definitions and
+/// `enum`s.
+pub fn gen_message_struct(message: dict::Message, settings: &Settings) -> String {
+    let mut fields = Vec::new();
+    let mut tags = FnvHashSet::default();
+    collect_message_fields(message.layout(), true, &mut tags, &mut fields);
+    let has_lifetime = fields.iter().any(|f| f.rust_type.contains("'a"));
+    let lifetime = if has_lifetime { "<'a>" } else { "" };
+    let identifier = message.name().to_camel_case();
+    let members = fields
+        .iter()
+        .map(|f| {
+            format!(
+                "/// Field `{} <{}>`.\npub {}: {},",
+                f.name,
+                f.tag,
+                f.identifier,
+                f.member_type()
+            )
+        })
+        .collect::<Vec<String>>()
+        .join("\n");
+    let decoders = fields
+        .iter()
+        .map(|f| {
+            if f.required {
+                format!(
+                    "{ident}: decode_required(msg.fv(super::{def}), super::{def})?,",
+                    ident = f.identifier,
+                    def = f.definition,
+                )
+            } else {
+                format!(
+                    "{ident}: decode_optional(msg.fv_opt(super::{def}), super::{def})?,",
+                    ident = f.identifier,
+                    def = f.definition,
+                )
+            }
+        })
+        .collect::<Vec<String>>()
+        .join("\n");
+    let encoders = fields
+        .iter()
+        .map(|f| {
+            let clone = if f.is_copy { "" } else { ".clone()" };
+            if f.required {
+                format!(
+                    "msg.set(super::{}, self.{}{});",
+                    f.definition, f.identifier, clone
+                )
+            } else {
+                format!(
+                    "if let Some(value) = self.{}{} {{\n{}msg.set(super::{}, value);\n}}",
+                    f.identifier, clone, settings.indentation, f.definition
+                )
+            }
+        })
+        .collect::<Vec<String>>()
+        .join("\n");
+    let indentation = settings.indentation.repeat(3);
+    format!(
+        indoc!(
+            r#"
+            /// Strongly-typed `{name} <{msg_type}>` message. Repeating groups
+            /// are not included.
+            #[derive(Debug, Clone, PartialEq)]
+            pub struct {identifier}{lifetime} {{
+            {members}
+            }}
+
+            impl{lifetime} {identifier}{lifetime} {{
+                /// The `MsgType <35>` of this message.
+                pub const MSG_TYPE: &'static [u8] = b"{msg_type}";
+
+                /// Reads all fields of `Self` from `msg`.
+                pub fn decode<M>(msg: &{msg_lifetime}M) -> Result<Self, TypedMessageError>
+                where
+                    M: FieldAccess,
+                {{
+                    Ok(Self {{
+            {decoders}
+                    }})
+                }}
+
+                /// Writes all fields of `self` to `msg`. Standard header fields
+                /// are not included.
+                pub fn encode<B, C>(&self, msg: &mut EncoderHandle<B, C>)
+                where
+                    B: Buffer,
+                    C: Configure,
+                {{
+            {encoders}
+                }}
+            }}"#
+        ),
+        name = message.name(),
+        msg_type = message.msg_type(),
+        identifier = identifier,
+        lifetime = lifetime,
+        msg_lifetime = if has_lifetime { "'a " } else { "" },
+        members = indent_string(members, settings.indentation.as_str()),
+        decoders = indent_string(decoders, indentation.as_str()),
+        encoders = indent_string(encoders, settings.indentation.repeat(2).as_str()),
+    )
+}
+
+fn collect_message_fields<'a>(
+    layout: impl Iterator<Item = dict::LayoutItem<'a>>,
+    required: bool,
+    tags: &mut FnvHashSet<TagU16>,
+    fields: &mut Vec<MessageStructField>,
+) {
+    for item in layout {
+        let item_required = required && item.required();
+        match item.kind() {
+            dict::LayoutItemKind::Field(field) => {
+                if tags.insert(field.tag()) {
+                    fields.push(MessageStructField::new(field, item_required));
+                }
+            }
+            dict::LayoutItemKind::Component(component) => {
+                collect_message_fields(component.items(), item_required, tags, fields);
+            }
+            dict::LayoutItemKind::Group(_, _) => {}
+        }
+    }
+}
+
+struct MessageStructField {
+    name: String,
+    tag: u16,
+    identifier: String,
+    definition: String,
+    rust_type: String,
+    is_copy: bool,
+    required: bool,
+}
+
+impl MessageStructField {
+    fn new(field: dict::Field, required: bool) -> Self {
+        let (rust_type, is_copy) = message_struct_field_type(field);
+        let mut identifier = field.name().to_snake_case();
+        if RUST_KEYWORDS.contains(&identifier.as_str()) {
+            identifier = format!("r#{}", identifier);
+        }
+        Self {
+            name: field.name().to_string(),
+            tag: field.tag().get(),
+            identifier,
+            definition: field.name().to_shouty_snake_case(),
+            rust_type,
+            is_copy,
+            required,
+        }
+    }
+
+    fn member_type(&self) -> String {
+        if self.required {
+            self.rust_type.clone()
+        } else {
+            format!("Option<{}>", self.rust_type)
+        }
+    }
+}
+
+const RUST_KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "do", "dyn",
+    "else", "enum", "extern", "false", "final", "fn", "for", "if", "impl", "in", "let", "loop",
+    "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref", "return", "static",
+    "struct", "trait", "true", "try", "type", "typeof", "unsafe", "unsized", "use", "virtual",
+    "where", "while", "yield",
+];
+
+/// Returns the Rust type of `field` within generated message structs, and
+/// whether it's [`Copy`] or not.
+fn message_struct_field_type(field: dict::Field) -> (String, bool) {
+    use dict::FixDatatype as Dt;
+    let datatype = field.data_type().basetype();
+    let is_multiple_value = match datatype {
+        Dt::MultipleCharValue | Dt::MultipleStringValue => true,
+        _ => false,
+    };
+    if field.enums().is_some() && !is_multiple_value {
+        return (format!("super::{}", field.name().to_camel_case()), true);
+    }
+    let (rust_type, is_copy) = match datatype {
+        Dt::Int => ("i64", true),
+        Dt::Length | Dt::NumInGroup => ("usize", true),
+        Dt::SeqNum => ("u64", true),
+        Dt::TagNum => ("u32", true),
+        Dt::DayOfMonth => ("DayOfMonth", true),
+        Dt::Char => ("u8", true),
+        Dt::Boolean => ("bool", true),
+        Dt::Data | Dt::XmlData => ("&'a [u8]", true),
+        Dt::UtcTimestamp => ("Timestamp", false),
+        Dt::UtcDateOnly => ("Date", true),
+        Dt::LocalMktDate => ("LocalMktDate", true),
+        Dt::UtcTimeOnly => ("Time", true),
+        Dt::MonthYear => ("MonthYear", true),
+        _ => ("&'a str", true),
+    };
+    (rust_type.to_string(), is_copy)
 }
 
 #[doc(hidden)]
 pub fn indent_lines<'a>(lines: impl Iterator<Item = &'a str>, prefix: &str) -> String {
     lines.fold(String::new(), |mut s, line| {
-        if line.contains(char::is_whitespace) {
+        if !line.trim().is_empty() {
             s.push_str(prefix);
         }
         s.push_str(line);
@@ -356,9 +904,103 @@ mod test {
         }
     }
 
+    #[test]
+    fn syntax_of_message_structs_is_ok() {
+        let mut codegen_settings = Settings::default();
+        codegen_settings.set_generate_messages(true);
+        codegen_settings.set_generate_message_fields(true);
+        for dict in dict::Dictionary::all().into_iter() {
+            let code = gen_definitions(dict, &codegen_settings);
+            syn::parse_file(code.as_str()).unwrap();
+        }
+    }
+
+    #[test]
+    fn message_struct_members() {
+        let dict = dict::Dictionary::fix44();
+        let code = gen_message_struct(
+            dict.message_by_name("NewOrderSingle").unwrap(),
+            &Settings::default(),
+        );
+        assert!(code.contains("pub struct NewOrderSingle<'a> {"));
+        assert!(code.contains("pub cl_ord_id: &'a str,"));
+        assert!(code.contains("pub side: super::Side,"));
+        assert!(code.contains("pub price: Option<&'a str>,"));
+        assert!(code.contains("pub transact_time: Timestamp,"));
+        // Repeating groups are left out.
+        assert!(!code.contains("no_party_i_ds"));
+    }
+
     #[test]
     fn generated_code_notice_is_trimmed() {
         let notice = generated_code_notice();
         assert_eq!(notice, notice.trim());
     }
+
+    fn temp_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("fefix-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn generate_to_is_deterministic() {
+        let dir = temp_dir();
+        let dict = dict::Dictionary::fix44();
+        let path = generate_to(&dir, &dict, &Settings::default()).unwrap();
+        assert_eq!(path, dir.join("fix44.rs"));
+        let code = fs::read_to_string(&path).unwrap();
+        let modified = fs::metadata(&path).unwrap().modified().unwrap();
+        generate_to(&dir, &dict, &Settings::default()).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), code);
+        // Unchanged files are left alone.
+        assert_eq!(fs::metadata(&path).unwrap().modified().unwrap(), modified);
+        syn::parse_file(code.as_str()).unwrap();
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn generate_to_with_separate_messages() {
+        let dir = temp_dir();
+        let mut settings = Settings::default();
+        settings.set_generate_messages(true);
+        settings.set_module_name("venue");
+        settings.set_module_layout(ModuleLayout::SeparateMessages);
+        let path = generate_to(&dir, &dict::Dictionary::fix44(), &settings).unwrap();
+        assert_eq!(path, dir.join("venue.rs"));
+        let code = fs::read_to_string(path).unwrap();
+        assert!(code.contains("include!(\"venue_messages.rs\");"));
+        assert!(!code.contains("pub struct NewOrderSingle"));
+        let messages = fs::read_to_string(dir.join("venue_messages.rs")).unwrap();
+        assert!(messages.contains("pub struct NewOrderSingle<'a> {"));
+        syn::parse_file(code.as_str()).unwrap();
+        syn::parse_file(messages.as_str()).unwrap();
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn default_module_names() {
+        assert_eq!(default_module_name("FIX.4.4"), "fix44");
+        assert_eq!(default_module_name("FIX.5.0-SP2"), "fix50sp2");
+        assert_eq!(default_module_name("FIXT.1.1"), "fixt11");
+    }
+
+    #[test]
+    fn message_fields_of_new_order_single() {
+        let dict = dict::Dictionary::fix44();
+        let code = gen_message_fields(
+            dict.message_by_name("NewOrderSingle").unwrap(),
+            &Settings::default(),
+        );
+        assert!(code.contains("pub mod new_order_single {"));
+        assert!(code.contains("pub use super::super::CL_ORD_ID;"));
+        assert!(code.contains("pub const MSG_TYPE: &[u8] = b\"D\";"));
+        let required =
+            &code[code.find("REQUIRED_FIELDS").unwrap()..code.find("OPTIONAL_FIELDS").unwrap()];
+        assert!(required.contains("CL_ORD_ID,"));
+        assert!(!required.contains("PRICE,"));
+        // Repeating groups are only represented by their length field.
+        assert!(code.contains("NO_PARTY_I_DS,"));
+        assert!(!code.contains("PARTY_ID,"));
+    }
 }
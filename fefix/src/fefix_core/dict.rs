@@ -2,36 +2,20 @@
 
 use self::symbol_table::{Key, KeyRef, SymbolTable, SymbolTableIndex};
 use super::TagU16;
-use fnv::FnvHashMap;
-use lazy_static::lazy_static;
-use quickfix::{ParseDictionaryError, QuickFixReader};
-use std::fmt;
-use std::sync::Arc;
+use alloc::boxed::Box;
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::string::{String, ToString};
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::fmt;
+use once_cell::race::OnceBox;
+#[cfg(any(feature = "std", not(fefix_lib)))]
+use orchestra::OrchestraReader;
+#[cfg(any(feature = "std", not(fefix_lib)))]
+use quickfix::QuickFixReader;
 
 pub use datatype::FixDatatype;
 
-const SPEC_FIX_40: &str = include_str!("resources/quickfix/FIX-4.0.xml");
-const SPEC_FIX_41: &str = include_str!("resources/quickfix/FIX-4.1.xml");
-const SPEC_FIX_42: &str = include_str!("resources/quickfix/FIX-4.2.xml");
-const SPEC_FIX_43: &str = include_str!("resources/quickfix/FIX-4.3.xml");
-const SPEC_FIX_44: &str = include_str!("resources/quickfix/FIX-4.4.xml");
-const SPEC_FIX_50: &str = include_str!("resources/quickfix/FIX-5.0.xml");
-const SPEC_FIX_50SP1: &str = include_str!("resources/quickfix/FIX-5.0-SP1.xml");
-const SPEC_FIX_50SP2: &str = include_str!("resources/quickfix/FIX-5.0-SP2.xml");
-const SPEC_FIXT_11: &str = include_str!("resources/quickfix/FIXT-1.1.xml");
-
-lazy_static! {
-    static ref DICT_FIX_40: Dictionary = Dictionary::from_quickfix_spec(SPEC_FIX_40).unwrap();
-    static ref DICT_FIX_41: Dictionary = Dictionary::from_quickfix_spec(SPEC_FIX_41).unwrap();
-    static ref DICT_FIX_42: Dictionary = Dictionary::from_quickfix_spec(SPEC_FIX_42).unwrap();
-    static ref DICT_FIX_43: Dictionary = Dictionary::from_quickfix_spec(SPEC_FIX_43).unwrap();
-    static ref DICT_FIX_44: Dictionary = Dictionary::from_quickfix_spec(SPEC_FIX_44).unwrap();
-    static ref DICT_FIX_50: Dictionary = Dictionary::from_quickfix_spec(SPEC_FIX_50).unwrap();
-    static ref DICT_FIX_50SP1: Dictionary = Dictionary::from_quickfix_spec(SPEC_FIX_50SP1).unwrap();
-    static ref DICT_FIX_50SP2: Dictionary = Dictionary::from_quickfix_spec(SPEC_FIX_50SP2).unwrap();
-    static ref DICT_FIXT_11: Dictionary = Dictionary::from_quickfix_spec(SPEC_FIXT_11).unwrap();
-}
-
 /// The expected location of a field within a FIX message (i.e. header, body, or
 /// trailer).
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -46,6 +30,39 @@ pub enum FieldLocation {
 
 type InternalId = u32;
 
+/// The name of the default scenario of all messages.
+const BASE_SCENARIO: &str = "base";
+
+type FnvHashMap<K, V> = hashbrown::HashMap<K, V, fnv::FnvBuildHasher>;
+
+/// Returns a built-in [`Dictionary`], loading it the first time it's
+/// requested so that [`Dictionary::fix44`] and friends are cheap to call
+/// repeatedly.
+///
+/// The build script parses the QuickFIX specifications and, besides generating
+/// [`crate::definitions`], stores them in `OUT_DIR` as
+/// [`Dictionary::to_bytes`] does. The library loads those instead, so it
+/// needs neither an XML parser nor `std`.
+macro_rules! builtin {
+    ($spec:literal, $module:literal) => {{
+        static DICT: OnceBox<Dictionary> = OnceBox::new();
+        DICT.get_or_init(|| {
+            #[cfg(fefix_lib)]
+            let dict = Dictionary::from_bytes(include_bytes!(concat!(
+                env!("OUT_DIR"),
+                "/",
+                $module,
+                ".dict"
+            )));
+            #[cfg(not(fefix_lib))]
+            let dict =
+                Dictionary::from_quickfix_spec(include_str!(concat!("resources/quickfix/", $spec)));
+            Box::new(dict.unwrap())
+        })
+        .clone()
+    }};
+}
+
 /// Specifies business semantics for application-level entities within the FIX
 /// Protocol.
 ///
@@ -181,10 +198,62 @@ impl Dictionary {
 
     /// Attempts to read a QuickFIX-style specification file and convert it into
     /// a [`Dictionary`].
+    ///
+    /// This is useful for loading customized dictionaries at runtime, e.g.
+    /// the ones that many venues distribute:
+    ///
+    /// ```no_run
+    /// use fefix::Dictionary;
+    ///
+    /// let spec = std::fs::read_to_string("FIX44.xml").unwrap();
+    /// let dict = Dictionary::from_quickfix_spec(spec).unwrap();
+    /// ```
+    #[cfg(any(feature = "std", not(fefix_lib)))]
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "std")))]
     pub fn from_quickfix_spec<S: AsRef<str>>(input: S) -> Result<Self, ParseDictionaryError> {
         let xml_document = roxmltree::Document::parse(input.as_ref())
             .map_err(|_| ParseDictionaryError::InvalidFormat)?;
-        QuickFixReader::new(&xml_document)
+        QuickFixReader::parse(&[&xml_document])
+    }
+
+    /// Attempts to read a pair of QuickFIX-style specification files, i.e. a
+    /// transport layer (e.g. `FIXT11.xml`) and an application layer (e.g.
+    /// `FIX50SP2.xml`), and merge them into a single [`Dictionary`].
+    ///
+    /// `StandardHeader` and `StandardTrailer` come from the transport layer,
+    /// while the version string comes from the application layer. Fields
+    /// defined by both specifications are imported only once.
+    #[cfg(any(feature = "std", not(fefix_lib)))]
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "std")))]
+    pub fn from_quickfix_specs<S1, S2>(
+        transport: S1,
+        application: S2,
+    ) -> Result<Self, ParseDictionaryError>
+    where
+        S1: AsRef<str>,
+        S2: AsRef<str>,
+    {
+        let transport = roxmltree::Document::parse(transport.as_ref())
+            .map_err(|_| ParseDictionaryError::InvalidFormat)?;
+        let application = roxmltree::Document::parse(application.as_ref())
+            .map_err(|_| ParseDictionaryError::InvalidFormat)?;
+        QuickFixReader::parse(&[&transport, &application])
+    }
+
+    /// Attempts to read a FIX Orchestra repository file and convert it into a
+    /// [`Dictionary`].
+    ///
+    /// Messages with a non-`base` scenario are available via
+    /// [`Dictionary::message_by_msgtype_and_scenario`] and
+    /// [`Dictionary::iter_messages`], while lookups by name and message type
+    /// always return `base` scenarios. Response workflows are available via
+    /// [`Message::responses`].
+    #[cfg(any(feature = "std", not(fefix_lib)))]
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "std")))]
+    pub fn from_orchestra_spec<S: AsRef<str>>(input: S) -> Result<Self, ParseDictionaryError> {
+        let xml_document = roxmltree::Document::parse(input.as_ref())
+            .map_err(|_| ParseDictionaryError::InvalidFormat)?;
+        OrchestraReader::parse(&xml_document)
     }
 
     /// Creates a new empty FIX Dictionary with `FIX.???` as its version string.
@@ -209,61 +278,61 @@ impl Dictionary {
     #[cfg(feature = "fix40")]
     #[cfg_attr(doc_cfg, doc(cfg(feature = "fix40")))]
     pub fn fix40() -> Self {
-        DICT_FIX_40.clone()
+        builtin!("FIX-4.0.xml", "fix40")
     }
 
     /// Creates a new [`Dictionary`] for FIXT 4.1.
     #[cfg(feature = "fix41")]
     #[cfg_attr(doc_cfg, doc(cfg(feature = "fix41")))]
     pub fn fix41() -> Self {
-        DICT_FIX_41.clone()
+        builtin!("FIX-4.1.xml", "fix41")
     }
 
     /// Creates a new [`Dictionary`] for FIXT 4.2.
     #[cfg(feature = "fix42")]
     #[cfg_attr(doc_cfg, doc(cfg(feature = "fix42")))]
     pub fn fix42() -> Self {
-        DICT_FIX_42.clone()
+        builtin!("FIX-4.2.xml", "fix42")
     }
 
     /// Creates a new [`Dictionary`] for FIXT 4.3.
     #[cfg(feature = "fix43")]
     #[cfg_attr(doc_cfg, doc(cfg(feature = "fix43")))]
     pub fn fix43() -> Self {
-        DICT_FIX_43.clone()
+        builtin!("FIX-4.3.xml", "fix43")
     }
 
     /// Creates a new [`Dictionary`] for FIX 4.4.
     pub fn fix44() -> Self {
-        DICT_FIX_44.clone()
+        builtin!("FIX-4.4.xml", "fix44")
     }
 
     /// Creates a new [`Dictionary`] for FIX 5.0.
     #[cfg(feature = "fix50")]
     #[cfg_attr(doc_cfg, doc(cfg(feature = "fix50")))]
     pub fn fix50() -> Self {
-        DICT_FIX_50.clone()
+        builtin!("FIX-5.0.xml", "fix50")
     }
 
     /// Creates a new [`Dictionary`] for FIX 5.0 SP1.
     #[cfg(feature = "fix50sp1")]
     #[cfg_attr(doc_cfg, doc(cfg(feature = "fix50sp1")))]
     pub fn fix50sp1() -> Self {
-        DICT_FIX_50SP1.clone()
+        builtin!("FIX-5.0-SP1.xml", "fix50sp1")
     }
 
     /// Creates a new [`Dictionary`] for FIX 5.0 SP2.
     #[cfg(feature = "fix50sp2")]
     #[cfg_attr(doc_cfg, doc(cfg(feature = "fix50sp1")))]
     pub fn fix50sp2() -> Self {
-        DICT_FIX_50SP2.clone()
+        builtin!("FIX-5.0-SP2.xml", "fix50sp2")
     }
 
     /// Creates a new [`Dictionary`] for FIXT 1.1.
     #[cfg(feature = "fixt11")]
     #[cfg_attr(doc_cfg, doc(cfg(feature = "fixt11")))]
     pub fn fixt11() -> Self {
-        DICT_FIXT_11.clone()
+        builtin!("FIXT-1.1.xml", "fixt11")
     }
 
     #[cfg(test)]
@@ -335,6 +404,24 @@ impl Dictionary {
             .map(|data| Message(self, data))
     }
 
+    /// Returns the [`Message`] that has the given `msgtype` and FIX Orchestra
+    /// `scenario`, if any.
+    pub fn message_by_msgtype_and_scenario<S1, S2>(
+        &self,
+        msgtype: S1,
+        scenario: S2,
+    ) -> Option<Message<'_>>
+    where
+        S1: AsRef<str>,
+        S2: AsRef<str>,
+    {
+        self.inner
+            .messages
+            .iter()
+            .find(|data| data.msg_type == msgtype.as_ref() && data.scenario == scenario.as_ref())
+            .map(|data| Message(self, data))
+    }
+
     /// Returns the [`Component`] named `name`, if any.
     pub fn component_by_name<S: AsRef<str>>(&self, name: S) -> Option<Component> {
         self.symbol(KeyRef::ComponentByName(name.as_ref()))
@@ -437,9 +524,270 @@ impl Dictionary {
             .iter()
             .map(move |data| Component(&self, data))
     }
+
+    /// Returns an [`Iterator`] over the enumerated values of all fields of this
+    /// [`Dictionary`], together with their [`Field`]. Items are in no
+    /// particular order.
+    ///
+    /// ```
+    /// use fefix::Dictionary;
+    ///
+    /// let dict = Dictionary::fix44();
+    /// let (field, value) = dict
+    ///     .iter_field_enums()
+    ///     .find(|(_, value)| value.description() == "LIMIT")
+    ///     .unwrap();
+    /// assert_eq!(field.name(), "OrdType");
+    /// assert_eq!(value.value(), "2");
+    /// ```
+    pub fn iter_field_enums(&self) -> impl Iterator<Item = (Field<'_>, FieldEnum<'_>)> {
+        self.inner.fields.iter().flat_map(move |field_data| {
+            field_data
+                .value_restrictions
+                .iter()
+                .flatten()
+                .map(move |data| (Field(self, field_data), FieldEnum(self, data)))
+        })
+    }
+
+    /// Serializes `self` into a compact binary format, which
+    /// [`Dictionary::from_bytes`] loads much faster than parsing QuickFIX or
+    /// FIX Orchestra files. The format is only guaranteed to be readable by the
+    /// same version of this crate, so it's best used as a cache of the
+    /// original specification.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix::Dictionary;
+    ///
+    /// let bytes = Dictionary::fix44().to_bytes();
+    /// let dict = Dictionary::from_bytes(&bytes).unwrap();
+    /// assert_eq!(dict.get_version(), "FIX.4.4");
+    /// assert_eq!(dict.message_by_msgtype("D").unwrap().name(), "NewOrderSingle");
+    /// ```
+    pub fn to_bytes(&self) -> Vec<u8> {
+        binary::encode(&self.inner)
+    }
+
+    /// Reads a [`Dictionary`] serialized by [`Dictionary::to_bytes`]. Fails if
+    /// `bytes` is malformed or comes from an incompatible version of this
+    /// crate.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ParseDictionaryError> {
+        Ok(Dictionary {
+            inner: Arc::new(binary::decode(bytes)?),
+        })
+    }
+
+    /// Returns a [`DictionaryBuilder`] with a copy of all definitions of
+    /// `self`, for customization.
+    pub fn to_builder(&self) -> DictionaryBuilder {
+        let data = &*self.inner;
+        DictionaryBuilder {
+            version: data.version.clone(),
+            symbol_table: data.symbol_table.clone(),
+            abbreviations: data.abbreviations.clone(),
+            data_types: data.data_types.clone(),
+            fields: data.fields.clone(),
+            components: data.components.clone(),
+            messages: data.messages.clone(),
+            //layout_items: data.layout_items.clone(),
+            categories: data.categories.clone(),
+            header: data.header.clone(),
+        }
+    }
+
+    /// Layers all definitions of `extension` (e.g. a venue-specific
+    /// customization) over `base` (e.g. a standard FIX version) and returns
+    /// the resulting [`Dictionary`].
+    ///
+    /// Fields are matched by tag, components by name, and messages by message
+    /// type and scenario. Definitions found in both dictionaries are replaced
+    /// entirely by the ones in `extension`, while all others are kept as they
+    /// are. The version string comes from `base`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix::dict::FixDatatype;
+    /// use fefix::Dictionary;
+    ///
+    /// let mut builder = Dictionary::fix44().to_builder();
+    /// builder
+    ///     .add_custom_field(5001, "VenueOrderTag", FixDatatype::String)
+    ///     .unwrap();
+    /// builder.add_field_to_message("D", 5001, true).unwrap();
+    /// let venue = builder.build();
+    ///
+    /// let merged = Dictionary::merge(&Dictionary::fix44(), &venue);
+    /// assert!(merged.field_by_tag(5001).is_some());
+    /// assert!(Dictionary::diff(&venue, &merged).is_empty());
+    /// ```
+    pub fn merge(base: &Dictionary, extension: &Dictionary) -> Dictionary {
+        let mut builder = base.to_builder();
+        builder.merge_from(extension);
+        builder.build()
+    }
+
+    /// Compares `a` to `b` and returns a [`DictionaryDiff`] with all fields and
+    /// messages that `b` adds, removes, or changes with respect to `a`.
+    ///
+    /// Fields are compared by name, datatype, and enumerated values; messages
+    /// by name and layout, with components expanded into their fields. Only
+    /// `base` scenarios are compared.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix::Dictionary;
+    ///
+    /// let mut builder = Dictionary::fix44().to_builder();
+    /// builder.add_enum_value(40, "Z", "VenueSpecialOrder").unwrap();
+    /// let venue = builder.build();
+    ///
+    /// let diff = Dictionary::diff(&Dictionary::fix44(), &venue);
+    /// assert_eq!(diff.changed_fields(), &[40]);
+    /// assert!(diff.added_messages().is_empty());
+    /// println!("{}", diff);
+    /// ```
+    pub fn diff(a: &Dictionary, b: &Dictionary) -> DictionaryDiff {
+        let mut diff = DictionaryDiff::default();
+        for field in a.inner.fields.iter() {
+            match b.symbol(KeyRef::FieldByTag(field.tag)) {
+                Some(iid) => {
+                    if a.field_signature(field) != b.field_signature(&b.inner.fields[*iid as usize])
+                    {
+                        diff.changed_fields.push(field.tag);
+                    }
+                }
+                None => diff.removed_fields.push(field.tag),
+            }
+        }
+        for field in b.inner.fields.iter() {
+            if a.symbol(KeyRef::FieldByTag(field.tag)).is_none() {
+                diff.added_fields.push(field.tag);
+            }
+        }
+        fn base_messages(dict: &Dictionary) -> BTreeMap<&str, &MessageData> {
+            dict.inner
+                .messages
+                .iter()
+                .filter(|data| data.scenario == BASE_SCENARIO)
+                .map(|data| (data.msg_type.as_str(), data))
+                .collect()
+        }
+        let (messages_a, messages_b) = (base_messages(a), base_messages(b));
+        for (msg_type, message_a) in messages_a.iter() {
+            match messages_b.get(msg_type) {
+                Some(message_b) => {
+                    let is_changed = message_a.name != message_b.name
+                        || a.layout_signature(&message_a.layout_items, true)
+                            != b.layout_signature(&message_b.layout_items, true);
+                    if is_changed {
+                        diff.changed_messages.push(msg_type.to_string());
+                    }
+                }
+                None => diff.removed_messages.push(msg_type.to_string()),
+            }
+        }
+        for msg_type in messages_b.keys() {
+            if !messages_a.contains_key(msg_type) {
+                diff.added_messages.push(msg_type.to_string());
+            }
+        }
+        diff.added_fields.sort_unstable();
+        diff.removed_fields.sort_unstable();
+        diff.changed_fields.sort_unstable();
+        diff
+    }
+
+    fn field_signature<'a>(
+        &'a self,
+        field: &'a FieldData,
+    ) -> (&'a str, FixDatatype, Option<BTreeSet<&'a str>>) {
+        let datatype = self.inner.data_types[field.data_type_iid as usize].datatype;
+        let enums = field.value_restrictions.as_ref().map(|enums| {
+            enums
+                .iter()
+                .map(|e| e.value.as_str())
+                .collect::<BTreeSet<_>>()
+        });
+        (field.name.as_str(), datatype, enums)
+    }
+
+    /// Flattens `items` into a list of fields and groups, recursively expanding
+    /// components. Items are required only if all their containers are.
+    fn layout_signature(&self, items: &[LayoutItemData], required: bool) -> Vec<LayoutSignature> {
+        let mut signature = Vec::new();
+        for item in items {
+            let required = required && item.required;
+            match &item.kind {
+                LayoutItemKindData::Field { iid } => signature.push(LayoutSignature::Field {
+                    tag: self.inner.fields[*iid as usize].tag,
+                    required,
+                }),
+                LayoutItemKindData::Group {
+                    len_field_iid,
+                    items,
+                } => signature.push(LayoutSignature::Group {
+                    tag: self.inner.fields[*len_field_iid as usize].tag,
+                    required,
+                    items: self.layout_signature(items, true),
+                }),
+                LayoutItemKindData::Component { iid } => {
+                    let component = &self.inner.components[*iid as usize];
+                    signature.extend(self.layout_signature(&component.layout_items, required));
+                }
+            }
+        }
+        signature
+    }
 }
 
-struct DictionaryBuilder {
+#[derive(Debug, PartialEq, Eq)]
+enum LayoutSignature {
+    Field {
+        tag: u32,
+        required: bool,
+    },
+    Group {
+        tag: u32,
+        required: bool,
+        items: Vec<LayoutSignature>,
+    },
+}
+
+/// A mutable [`Dictionary`], mostly useful for customizations: user-defined
+/// fields, additional enumerated values, and so on. Almost every venue has
+/// some. [`DictionaryBuilder::build`] produces a regular [`Dictionary`], so
+/// customizations are seen by validation, code generation, and all other
+/// [`Dictionary`] users.
+///
+/// # Examples
+///
+/// ```
+/// use fefix::dict::{DictionaryBuilder, FixDatatype};
+/// use fefix::Dictionary;
+///
+/// let mut builder = Dictionary::fix44().to_builder();
+/// builder
+///     .add_custom_field(5001, "VenueOrderTag", FixDatatype::String)
+///     .unwrap();
+/// builder.add_field_to_message("D", 5001, false).unwrap();
+/// builder.add_enum_value(40, "Z", "VenueSpecialOrder").unwrap();
+/// let dict = builder.build();
+///
+/// let field = dict.field_by_tag(5001).unwrap();
+/// assert_eq!(field.name(), "VenueOrderTag");
+/// let new_order_single = dict.message_by_msgtype("D").unwrap();
+/// assert!(new_order_single
+///     .layout()
+///     .any(|item| item.tag_text() == "VenueOrderTag"));
+/// let ord_type = dict.field_by_tag(40).unwrap();
+/// assert!(ord_type.enums().unwrap().any(|e| e.value() == "Z"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct DictionaryBuilder {
     version: String,
     symbol_table: FnvHashMap<Key, InternalId>,
     abbreviations: Vec<AbbreviatonData>,
@@ -452,10 +800,20 @@ struct DictionaryBuilder {
     header: Vec<FieldData>,
 }
 
+/// The smallest tag number of user-defined fields.
+const MIN_CUSTOM_TAG: u32 = 5000;
+
+/// Tags are stored as [`u32`], but only tags that fit [`TagU16`] are valid.
+fn is_valid_tag(tag: u32) -> bool {
+    tag != 0 && tag <= u16::MAX as u32
+}
+
 impl DictionaryBuilder {
-    pub fn new(version: String) -> Self {
+    /// Creates a new, empty [`DictionaryBuilder`] with `version` as its version
+    /// string. See also [`Dictionary::to_builder`].
+    pub fn new<S: Into<String>>(version: S) -> Self {
         Self {
-            version,
+            version: version.into(),
             symbol_table: FnvHashMap::default(),
             abbreviations: Vec::new(),
             data_types: Vec::new(),
@@ -468,21 +826,121 @@ impl DictionaryBuilder {
         }
     }
 
-    pub fn symbol(&self, pkey: KeyRef) -> Option<&InternalId> {
+    /// Changes the version string of the resulting [`Dictionary`].
+    pub fn set_version<S: Into<String>>(&mut self, version: S) {
+        self.version = version.into();
+    }
+
+    /// Registers a new user-defined field with the given `tag`, `name`, and
+    /// `datatype`. User-defined fields are numbered from 5000 upwards; the
+    /// resulting tag must also fit [`TagU16`].
+    pub fn add_custom_field<S: Into<String>>(
+        &mut self,
+        tag: u32,
+        name: S,
+        datatype: FixDatatype,
+    ) -> Result<(), DictionaryBuilderError> {
+        let name = name.into();
+        if tag < MIN_CUSTOM_TAG || !is_valid_tag(tag) {
+            return Err(DictionaryBuilderError::TagOutOfRange(tag));
+        }
+        if self.symbol(KeyRef::FieldByTag(tag)).is_some() {
+            return Err(DictionaryBuilderError::DuplicateTag(tag));
+        }
+        if self.symbol(KeyRef::FieldByName(&name)).is_some() {
+            return Err(DictionaryBuilderError::DuplicateName(name));
+        }
+        let data_type_iid = self.datatype_iid(datatype);
+        self.add_field(FieldData {
+            name,
+            tag,
+            data_type_iid,
+            associated_data_tag: None,
+            value_restrictions: None,
+            required: false,
+            abbr_name: None,
+            base_category_abbr_name: None,
+            base_category_id: None,
+            description: None,
+        });
+        Ok(())
+    }
+
+    /// Adds `value` to the allowed values of the field with the given `tag`,
+    /// which can be either a standard or a user-defined field.
+    ///
+    /// Please note that fields without enumerated values allow any value:
+    /// adding one restricts the field to that value alone.
+    pub fn add_enum_value<S1, S2>(
+        &mut self,
+        tag: u32,
+        value: S1,
+        description: S2,
+    ) -> Result<(), DictionaryBuilderError>
+    where
+        S1: Into<String>,
+        S2: Into<String>,
+    {
+        let iid = *self
+            .symbol(KeyRef::FieldByTag(tag))
+            .ok_or(DictionaryBuilderError::UnknownField(tag))?;
+        let value = value.into();
+        let enums = self.fields[iid as usize]
+            .value_restrictions
+            .get_or_insert_with(Vec::new);
+        if enums.iter().any(|e| e.value == value) {
+            return Err(DictionaryBuilderError::DuplicateEnumValue { tag, value });
+        }
+        enums.push(FieldEnumData {
+            value,
+            description: description.into(),
+        });
+        Ok(())
+    }
+
+    /// Appends the field with the given `tag` to the body of the message with
+    /// `msg_type` (its `base` scenario, for FIX Orchestra dictionaries). Fails
+    /// if the field is already a direct member of the message.
+    pub fn add_field_to_message(
+        &mut self,
+        msg_type: &str,
+        tag: u32,
+        required: bool,
+    ) -> Result<(), DictionaryBuilderError> {
+        let field_iid = *self
+            .symbol(KeyRef::FieldByTag(tag))
+            .ok_or(DictionaryBuilderError::UnknownField(tag))?;
+        let message_iid = *self
+            .symbol(KeyRef::MessageByMsgType(msg_type))
+            .ok_or_else(|| DictionaryBuilderError::UnknownMessage(msg_type.to_string()))?;
+        let layout_items = &mut self.messages[message_iid as usize].layout_items;
+        let is_duplicate = layout_items
+            .iter()
+            .any(|item| matches!(item.kind, LayoutItemKindData::Field { iid } if iid == field_iid));
+        if is_duplicate {
+            return Err(DictionaryBuilderError::DuplicateTag(tag));
+        }
+        layout_items.push(LayoutItemData {
+            required,
+            kind: LayoutItemKindData::Field { iid: field_iid },
+        });
+        Ok(())
+    }
+
+    fn symbol(&self, pkey: KeyRef) -> Option<&InternalId> {
         self.symbol_table.get(&pkey as &dyn SymbolTableIndex)
     }
 
-    pub fn add_field(&mut self, field: FieldData) -> InternalId {
+    fn add_field(&mut self, field: FieldData) -> InternalId {
         let iid = self.fields.len() as InternalId;
         self.symbol_table
             .insert(Key::FieldByName(field.name.clone()), iid);
-        self.symbol_table
-            .insert(Key::FieldByTag(field.tag as u32), iid);
+        self.symbol_table.insert(Key::FieldByTag(field.tag), iid);
         self.fields.push(field);
         iid
     }
 
-    pub fn add_message(&mut self, message: MessageData) -> InternalId {
+    fn add_message(&mut self, message: MessageData) -> InternalId {
         let iid = self.messages.len() as InternalId;
         self.symbol_table
             .insert(Key::MessageByName(message.name.clone()), iid);
@@ -492,7 +950,7 @@ impl DictionaryBuilder {
         iid
     }
 
-    pub fn add_component(&mut self, component: ComponentData) -> InternalId {
+    fn add_component(&mut self, component: ComponentData) -> InternalId {
         let iid = self.components.len() as InternalId;
         self.symbol_table
             .insert(Key::ComponentByName(component.name.to_string()), iid);
@@ -500,6 +958,171 @@ impl DictionaryBuilder {
         iid
     }
 
+    /// Returns the internal ID of `datatype`, registering it if necessary.
+    fn datatype_iid(&mut self, datatype: FixDatatype) -> InternalId {
+        // Get the official (not QuickFIX's) name of `datatype`.
+        let name = datatype.name();
+        match self.symbol(KeyRef::DatatypeByName(name)) {
+            Some(x) => *x,
+            None => {
+                let iid = self.data_types.len() as u32;
+                let data = DatatypeData {
+                    datatype,
+                    description: String::new(),
+                    examples: Vec::new(),
+                };
+                self.data_types.push(data);
+                self.symbol_table
+                    .insert(Key::DatatypeByName(name.to_string()), iid);
+                iid
+            }
+        }
+    }
+
+    /// Returns the internal ID of the category named `name`, registering it if
+    /// necessary.
+    fn category_iid(&mut self, name: &str) -> InternalId {
+        match self.symbol(KeyRef::CategoryByName(name)) {
+            Some(x) => *x,
+            None => {
+                let iid = self.categories.len() as u32;
+                self.categories.push(CategoryData {
+                    name: name.to_string(),
+                    fixml_filename: String::new(),
+                });
+                self.symbol_table
+                    .insert(Key::CategoryByName(name.to_string()), iid);
+                iid
+            }
+        }
+    }
+
+    /// Imports all definitions of `other`, replacing existing ones. See
+    /// [`Dictionary::merge`].
+    fn merge_from(&mut self, other: &Dictionary) {
+        let other_data = &*other.inner;
+        for field in other_data.fields.iter() {
+            let datatype = other_data.data_types[field.data_type_iid as usize].datatype;
+            let field = FieldData {
+                data_type_iid: self.datatype_iid(datatype),
+                ..field.clone()
+            };
+            match self.symbol(KeyRef::FieldByTag(field.tag)).copied() {
+                Some(iid) => {
+                    let old_name = self.fields[iid as usize].name.clone();
+                    self.symbol_table.remove(&Key::FieldByName(old_name));
+                    self.symbol_table
+                        .insert(Key::FieldByName(field.name.clone()), iid);
+                    self.fields[iid as usize] = field;
+                }
+                None => {
+                    self.add_field(field);
+                }
+            }
+        }
+        // Components can reference each other, so they must all be registered
+        // before importing their layouts.
+        for component in other_data.components.iter() {
+            if self
+                .symbol(KeyRef::ComponentByName(&component.name))
+                .is_none()
+            {
+                self.add_component(ComponentData {
+                    layout_items: Vec::new(),
+                    ..component.clone()
+                });
+            }
+        }
+        for component in other_data.components.iter() {
+            let iid = *self
+                .symbol(KeyRef::ComponentByName(&component.name))
+                .unwrap();
+            self.components[iid as usize] = ComponentData {
+                layout_items: self.import_layout_items(other, &component.layout_items),
+                category_iid: self.import_category(other, component.category_iid),
+                ..component.clone()
+            };
+        }
+        for message in other_data.messages.iter() {
+            let message = MessageData {
+                layout_items: self.import_layout_items(other, &message.layout_items),
+                category_iid: self.import_category(other, message.category_iid),
+                ..message.clone()
+            };
+            let existing = self
+                .messages
+                .iter()
+                .position(|m| m.msg_type == message.msg_type && m.scenario == message.scenario);
+            match existing {
+                Some(i) => {
+                    let old_name = Key::MessageByName(self.messages[i].name.clone());
+                    if self.symbol_table.get(&old_name) == Some(&(i as InternalId)) {
+                        self.symbol_table.remove(&old_name);
+                        self.symbol_table
+                            .insert(Key::MessageByName(message.name.clone()), i as InternalId);
+                    }
+                    self.messages[i] = message;
+                }
+                // Just like when reading FIX Orchestra files, non-base
+                // scenarios must not shadow base scenarios in the symbol table.
+                None if message.scenario != BASE_SCENARIO => {
+                    self.messages.push(message);
+                }
+                None => {
+                    self.add_message(message);
+                }
+            }
+        }
+    }
+
+    /// Translates the internal ID of a category of `other` to the internal ID
+    /// of `self`. Unknown categories are left as they are.
+    fn import_category(&mut self, other: &Dictionary, iid: InternalId) -> InternalId {
+        match other.inner.categories.get(iid as usize) {
+            Some(category) => self.category_iid(&category.name),
+            None => iid,
+        }
+    }
+
+    /// Translates the internal IDs within `items`, which belong to `other`, to
+    /// the internal IDs of `self`.
+    fn import_layout_items(
+        &self,
+        other: &Dictionary,
+        items: &[LayoutItemData],
+    ) -> Vec<LayoutItemData> {
+        let field_iid = |iid: InternalId| {
+            let tag = other.inner.fields[iid as usize].tag;
+            *self.symbol(KeyRef::FieldByTag(tag)).unwrap()
+        };
+        items
+            .iter()
+            .map(|item| LayoutItemData {
+                required: item.required,
+                kind: match &item.kind {
+                    LayoutItemKindData::Field { iid } => LayoutItemKindData::Field {
+                        iid: field_iid(*iid),
+                    },
+                    LayoutItemKindData::Group {
+                        len_field_iid,
+                        items,
+                    } => LayoutItemKindData::Group {
+                        len_field_iid: field_iid(*len_field_iid),
+                        items: self.import_layout_items(other, items),
+                    },
+                    LayoutItemKindData::Component { iid } => {
+                        let name = &other.inner.components[*iid as usize].name;
+                        LayoutItemKindData::Component {
+                            iid: *self.symbol(KeyRef::ComponentByName(name)).unwrap(),
+                        }
+                    }
+                },
+            })
+            .collect()
+    }
+
+    /// Consumes `self` and creates a new [`Dictionary`] with all its
+    /// definitions.
     pub fn build(self) -> Dictionary {
         Dictionary {
             inner: Arc::new(DictionaryData {
@@ -518,6 +1141,137 @@ impl DictionaryBuilder {
     }
 }
 
+/// The error type of failed [`DictionaryBuilder`] operations.
+///
+/// Please note that [`DictionaryBuilderError`] is marked with
+/// `#[non_exhaustive]`, which future-proofs the enumeration type in case more
+/// variants are added.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DictionaryBuilderError {
+    /// User-defined fields must have tags from 5000 to 65535.
+    TagOutOfRange(u32),
+    /// The tag is already defined (or already part of the message).
+    DuplicateTag(u32),
+    /// A field with the same name is already defined.
+    DuplicateName(String),
+    /// The same value is already allowed for the field with this tag.
+    DuplicateEnumValue {
+        /// The tag of the field.
+        tag: u32,
+        /// The duplicate value.
+        value: String,
+    },
+    /// No field is defined with this tag.
+    UnknownField(u32),
+    /// No message is defined with this message type.
+    UnknownMessage(String),
+}
+
+impl fmt::Display for DictionaryBuilderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TagOutOfRange(tag) => write!(f, "Tag {} is outside the user-defined range", tag),
+            Self::DuplicateTag(tag) => write!(f, "Tag {} is already defined", tag),
+            Self::DuplicateName(name) => write!(f, "Field '{}' is already defined", name),
+            Self::DuplicateEnumValue { tag, value } => {
+                write!(f, "Value '{}' is already allowed for tag {}", value, tag)
+            }
+            Self::UnknownField(tag) => write!(f, "Unknown field with tag {}", tag),
+            Self::UnknownMessage(msg_type) => write!(f, "Unknown message type '{}'", msg_type),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DictionaryBuilderError {}
+
+/// A structured report of the differences between two [`Dictionary`]-s, as
+/// produced by [`Dictionary::diff`]. Fields are identified by tag and messages
+/// by message type, both in ascending order.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct DictionaryDiff {
+    added_fields: Vec<u32>,
+    removed_fields: Vec<u32>,
+    changed_fields: Vec<u32>,
+    added_messages: Vec<String>,
+    removed_messages: Vec<String>,
+    changed_messages: Vec<String>,
+}
+
+impl DictionaryDiff {
+    /// Returns `true` if the two dictionaries have the same fields and
+    /// messages, `false` otherwise.
+    pub fn is_empty(&self) -> bool {
+        self.added_fields.is_empty()
+            && self.removed_fields.is_empty()
+            && self.changed_fields.is_empty()
+            && self.added_messages.is_empty()
+            && self.removed_messages.is_empty()
+            && self.changed_messages.is_empty()
+    }
+
+    /// Returns the tags of all fields that are only defined by the second
+    /// dictionary.
+    pub fn added_fields(&self) -> &[u32] {
+        &self.added_fields
+    }
+
+    /// Returns the tags of all fields that are only defined by the first
+    /// dictionary.
+    pub fn removed_fields(&self) -> &[u32] {
+        &self.removed_fields
+    }
+
+    /// Returns the tags of all fields that have a different name, datatype, or
+    /// set of enumerated values.
+    pub fn changed_fields(&self) -> &[u32] {
+        &self.changed_fields
+    }
+
+    /// Returns the message types of all messages that are only defined by the
+    /// second dictionary.
+    pub fn added_messages(&self) -> &[String] {
+        &self.added_messages
+    }
+
+    /// Returns the message types of all messages that are only defined by the
+    /// first dictionary.
+    pub fn removed_messages(&self) -> &[String] {
+        &self.removed_messages
+    }
+
+    /// Returns the message types of all messages that have a different name
+    /// or layout.
+    pub fn changed_messages(&self) -> &[String] {
+        &self.changed_messages
+    }
+}
+
+impl fmt::Display for DictionaryDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for tag in self.added_fields.iter() {
+            writeln!(f, "+ field {}", tag)?;
+        }
+        for tag in self.removed_fields.iter() {
+            writeln!(f, "- field {}", tag)?;
+        }
+        for tag in self.changed_fields.iter() {
+            writeln!(f, "~ field {}", tag)?;
+        }
+        for msg_type in self.added_messages.iter() {
+            writeln!(f, "+ message {}", msg_type)?;
+        }
+        for msg_type in self.removed_messages.iter() {
+            writeln!(f, "- message {}", msg_type)?;
+        }
+        for msg_type in self.changed_messages.iter() {
+            writeln!(f, "~ message {}", msg_type)?;
+        }
+        Ok(())
+    }
+}
+
 #[derive(Clone, Debug)]
 struct AbbreviatonData {
     abbreviation: String,
@@ -551,6 +1305,13 @@ struct CategoryData {
 #[derive(Clone, Debug)]
 pub struct Category<'a>(&'a Dictionary, &'a CategoryData);
 
+impl<'a> Category<'a> {
+    /// Returns the name of `self`, which is unique within its [`Dictionary`].
+    pub fn name(&self) -> &str {
+        self.1.name.as_str()
+    }
+}
+
 #[derive(Clone, Debug)]
 struct ComponentData {
     /// **Primary key.** The unique integer identifier of this component
@@ -587,6 +1348,12 @@ impl<'a> Component<'a> {
         self.1.name.as_str()
     }
 
+    /// Returns the name of `self` when used in an XML context (e.g. FIXML), if
+    /// available.
+    pub fn abbr_name(&self) -> Option<&str> {
+        self.1.abbr_name.as_deref()
+    }
+
     /// Returns `true` if and only if `self` is a "group" component; `false`
     /// otherwise.
     pub fn is_group(&self) -> bool {
@@ -670,12 +1437,10 @@ impl<'a> Datatype<'a> {
 }
 
 mod datatype {
-    use strum::IntoEnumIterator;
-    use strum_macros::{EnumIter, IntoStaticStr};
 
     /// Sum type for all possible FIX data types ever defined across all FIX
     /// application versions.
-    #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, EnumIter, IntoStaticStr)]
+    #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
     #[repr(u8)]
     #[non_exhaustive]
     pub enum FixDatatype {
@@ -1011,30 +1776,106 @@ mod datatype {
         /// Returns an [`Iterator`] over all variants of
         /// [`Datatype`](super::Datatype).
         pub fn iter_all() -> impl Iterator<Item = Self> {
-            <Self as IntoEnumIterator>::iter()
+            ALL.iter().copied()
         }
     }
 
-    #[cfg(test)]
-    mod test {
-        use super::*;
-        use std::collections::HashSet;
-
-        #[test]
-        fn iter_all_unique() {
-            let as_vec = FixDatatype::iter_all().collect::<Vec<FixDatatype>>();
-            let as_set = FixDatatype::iter_all().collect::<HashSet<FixDatatype>>();
-            assert_eq!(as_vec.len(), as_set.len());
-        }
-
-        #[test]
-        fn more_than_20_datatypes() {
-            // According to the official documentation, FIX has "about 20 data
-            // types". Including recent revisions, we should well exceed that
-            // number.
-            assert!(FixDatatype::iter_all().count() > 20);
-        }
-
+    /// Returns the name of the variant, e.g. `"UtcTimestamp"`.
+    impl From<FixDatatype> for &'static str {
+        fn from(datatype: FixDatatype) -> Self {
+            match datatype {
+                FixDatatype::Char => "Char",
+                FixDatatype::Boolean => "Boolean",
+                FixDatatype::Float => "Float",
+                FixDatatype::Amt => "Amt",
+                FixDatatype::Price => "Price",
+                FixDatatype::PriceOffset => "PriceOffset",
+                FixDatatype::Qty => "Qty",
+                FixDatatype::Percentage => "Percentage",
+                FixDatatype::Int => "Int",
+                FixDatatype::DayOfMonth => "DayOfMonth",
+                FixDatatype::Length => "Length",
+                FixDatatype::NumInGroup => "NumInGroup",
+                FixDatatype::SeqNum => "SeqNum",
+                FixDatatype::TagNum => "TagNum",
+                FixDatatype::String => "String",
+                FixDatatype::Data => "Data",
+                FixDatatype::MonthYear => "MonthYear",
+                FixDatatype::MultipleCharValue => "MultipleCharValue",
+                FixDatatype::Currency => "Currency",
+                FixDatatype::Exchange => "Exchange",
+                FixDatatype::Language => "Language",
+                FixDatatype::LocalMktDate => "LocalMktDate",
+                FixDatatype::MultipleStringValue => "MultipleStringValue",
+                FixDatatype::UtcDateOnly => "UtcDateOnly",
+                FixDatatype::UtcTimeOnly => "UtcTimeOnly",
+                FixDatatype::UtcTimestamp => "UtcTimestamp",
+                FixDatatype::XmlData => "XmlData",
+                FixDatatype::Country => "Country",
+            }
+        }
+    }
+
+    const ALL: [FixDatatype; 28] = [
+        FixDatatype::Char,
+        FixDatatype::Boolean,
+        FixDatatype::Float,
+        FixDatatype::Amt,
+        FixDatatype::Price,
+        FixDatatype::PriceOffset,
+        FixDatatype::Qty,
+        FixDatatype::Percentage,
+        FixDatatype::Int,
+        FixDatatype::DayOfMonth,
+        FixDatatype::Length,
+        FixDatatype::NumInGroup,
+        FixDatatype::SeqNum,
+        FixDatatype::TagNum,
+        FixDatatype::String,
+        FixDatatype::Data,
+        FixDatatype::MonthYear,
+        FixDatatype::MultipleCharValue,
+        FixDatatype::Currency,
+        FixDatatype::Exchange,
+        FixDatatype::Language,
+        FixDatatype::LocalMktDate,
+        FixDatatype::MultipleStringValue,
+        FixDatatype::UtcDateOnly,
+        FixDatatype::UtcTimeOnly,
+        FixDatatype::UtcTimestamp,
+        FixDatatype::XmlData,
+        FixDatatype::Country,
+    ];
+
+    #[cfg(test)]
+    mod test {
+        use super::*;
+        use std::collections::HashSet;
+
+        #[test]
+        fn iter_all_unique() {
+            let as_vec = FixDatatype::iter_all().collect::<Vec<FixDatatype>>();
+            let as_set = FixDatatype::iter_all().collect::<HashSet<FixDatatype>>();
+            assert_eq!(as_vec.len(), as_set.len());
+        }
+
+        #[test]
+        fn variant_names() {
+            assert_eq!(<&'static str>::from(FixDatatype::Char), "Char");
+            assert_eq!(
+                <&'static str>::from(FixDatatype::UtcTimestamp),
+                "UtcTimestamp"
+            );
+        }
+
+        #[test]
+        fn more_than_20_datatypes() {
+            // According to the official documentation, FIX has "about 20 data
+            // types". Including recent revisions, we should well exceed that
+            // number.
+            assert!(FixDatatype::iter_all().count() > 20);
+        }
+
         #[test]
         fn names_are_unique() {
             let as_vec = FixDatatype::iter_all()
@@ -1153,10 +1994,16 @@ impl<'a> Field<'a> {
 
     /// Returns the name of `self`. Field names are unique across each FIX
     /// [`Dictionary`].
-    pub fn name(&self) -> &str {
+    pub fn name(&self) -> &'a str {
         self.1.name.as_str()
     }
 
+    /// Returns the name of `self` when used in an XML context (e.g. FIXML), if
+    /// available.
+    pub fn abbr_name(&self) -> Option<&'a str> {
+        self.1.abbr_name.as_deref()
+    }
+
     /// Returns the numeric tag of `self`. Field tags are unique across each FIX
     /// [`Dictionary`].
     pub fn tag(&self) -> TagU16 {
@@ -1338,6 +2185,41 @@ struct MessageData {
     required: bool,
     description: String,
     elaboration: Option<String>,
+    /// The FIX Orchestra scenario of this message, i.e. a variation of its
+    /// layout for a specific use case. `base` unless specified otherwise.
+    scenario: String,
+    /// Messages that can be sent in response to this message.
+    responses: Vec<MessageResponseData>,
+}
+
+#[derive(Clone, Debug)]
+struct MessageResponseData {
+    name: String,
+    msg_type: String,
+    scenario: String,
+}
+
+/// A message that can be sent in response to some other [`Message`], according
+/// to FIX Orchestra workflow metadata.
+#[derive(Debug)]
+pub struct MessageResponse<'a>(&'a MessageResponseData);
+
+impl<'a> MessageResponse<'a> {
+    /// Returns the human-readable name of `self`, which identifies the
+    /// circumstances of the response (e.g. `orderAck`).
+    pub fn name(&self) -> &str {
+        self.0.name.as_str()
+    }
+
+    /// Returns the message type of the response.
+    pub fn msg_type(&self) -> &str {
+        self.0.msg_type.as_str()
+    }
+
+    /// Returns the scenario of the response message.
+    pub fn scenario(&self) -> &str {
+        self.0.scenario.as_str()
+    }
 }
 
 /// A [`Message`] is a unit of information sent on the wire between
@@ -1351,6 +2233,12 @@ impl<'a> Message<'a> {
         self.1.name.as_str()
     }
 
+    /// Returns the name of `self` when used in an XML context (e.g. FIXML), if
+    /// available.
+    pub fn abbr_name(&self) -> Option<&str> {
+        self.1.abbr_name.as_deref()
+    }
+
     /// Returns the message type of `self`.
     pub fn msg_type(&self) -> &str {
         self.1.msg_type.as_str()
@@ -1361,6 +2249,18 @@ impl<'a> Message<'a> {
         &self.1.description
     }
 
+    /// Returns the FIX Orchestra scenario of `self`. Dictionaries that don't
+    /// come from FIX Orchestra only have `base` scenarios.
+    pub fn scenario(&self) -> &str {
+        self.1.scenario.as_str()
+    }
+
+    /// Returns an [`Iterator`] over all possible responses to `self`, as
+    /// specified by FIX Orchestra workflow metadata.
+    pub fn responses(&self) -> impl Iterator<Item = MessageResponse<'a>> {
+        self.1.responses.iter().map(MessageResponse)
+    }
+
     pub fn group_info(&self, num_in_group_tag: TagU16) -> Option<TagU16> {
         self.layout().find_map(|layout_item| {
             if let LayoutItemKind::Group(field, items) = layout_item.kind() {
@@ -1401,10 +2301,11 @@ impl<'a> Message<'a> {
 pub struct Section {}
 
 mod symbol_table {
+    use super::FnvHashMap;
     use super::InternalId;
-    use fnv::FnvHashMap;
-    use std::borrow::Borrow;
-    use std::hash::Hash;
+    use alloc::string::String;
+    use core::borrow::Borrow;
+    use core::hash::Hash;
 
     pub type SymbolTable = FnvHashMap<Key, InternalId>;
 
@@ -1465,7 +2366,7 @@ mod symbol_table {
     }
 
     impl<'a> Hash for dyn SymbolTableIndex + 'a {
-        fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
             self.to_key().hash(state);
         }
     }
@@ -1485,54 +2386,74 @@ mod symbol_table {
     }
 }
 
+#[cfg(any(feature = "std", not(fefix_lib)))]
 mod quickfix {
     use super::*;
 
     pub struct QuickFixReader<'a> {
+        version: String,
         node_with_header: roxmltree::Node<'a, 'a>,
         node_with_trailer: roxmltree::Node<'a, 'a>,
         node_with_components: roxmltree::Node<'a, 'a>,
         node_with_messages: roxmltree::Node<'a, 'a>,
         node_with_fields: roxmltree::Node<'a, 'a>,
-        builder: DictionaryBuilder,
     }
 
     impl<'a> QuickFixReader<'a> {
-        pub fn new(xml_document: &'a roxmltree::Document<'a>) -> ParseResult<Dictionary> {
-            let mut reader = Self::empty(&xml_document)?;
-            for child in reader.node_with_fields.children() {
-                if child.is_element() {
-                    import_field(&mut reader.builder, child)?;
+        /// Imports all `xml_documents` into a single [`Dictionary`]. The
+        /// version string is taken from the last document, while
+        /// `StandardHeader` and `StandardTrailer` are taken from the first
+        /// one.
+        pub fn parse(xml_documents: &[&'a roxmltree::Document<'a>]) -> ParseResult<Dictionary> {
+            let readers = xml_documents
+                .iter()
+                .map(|xml_document| Self::empty(xml_document))
+                .collect::<ParseResult<Vec<Self>>>()?;
+            let (first, last) = match (readers.first(), readers.last()) {
+                (Some(first), Some(last)) => (first, last),
+                _ => return Err(ParseDictionaryError::InvalidFormat),
+            };
+            let mut builder = DictionaryBuilder::new(last.version.clone());
+            for reader in readers.iter() {
+                for child in reader.node_with_fields.children() {
+                    if child.is_element() {
+                        import_field(&mut builder, child)?;
+                    }
                 }
             }
-            for child in reader.node_with_components.children() {
-                if child.is_element() {
-                    let name = child
-                        .attribute("name")
-                        .ok_or(ParseDictionaryError::InvalidFormat)?
-                        .to_string();
-                    import_component(&mut reader.builder, child, name)?;
-                }
+            // Components can reference other components regardless of their
+            // order of definition, so we must register all of them before
+            // importing their layouts.
+            let component_nodes: Vec<roxmltree::Node> = readers
+                .iter()
+                .flat_map(|reader| reader.node_with_components.children())
+                .filter(|child| child.is_element())
+                .collect();
+            for node in component_nodes.iter() {
+                let name = node
+                    .attribute("name")
+                    .ok_or(ParseDictionaryError::InvalidFormat)?;
+                builder.add_component(component_data(name, LayoutItems::new()));
+            }
+            for node in component_nodes {
+                let name = node.attribute("name").unwrap();
+                let iid = *builder.symbol(KeyRef::ComponentByName(name)).unwrap();
+                let layout_items = import_layout_items(&mut builder, node)?;
+                builder.components[iid as usize].layout_items = layout_items;
             }
-            for child in reader.node_with_messages.children() {
-                if child.is_element() {
-                    import_message(&mut reader.builder, child)?;
+            for reader in readers.iter() {
+                for child in reader.node_with_messages.children() {
+                    if child.is_element() {
+                        import_message(&mut builder, child)?;
+                    }
                 }
             }
             // `StandardHeader` and `StandardTrailer` are defined in ad-hoc
             // sections of the XML files. They're always there, even if
             // potentially empty (e.g. FIX 5.0+).
-            import_component(
-                &mut reader.builder,
-                reader.node_with_header,
-                "StandardHeader",
-            )?;
-            import_component(
-                &mut reader.builder,
-                reader.node_with_trailer,
-                "StandardTrailer",
-            )?;
-            Ok(reader.builder.build())
+            import_component(&mut builder, first.node_with_header, "StandardHeader")?;
+            import_component(&mut builder, first.node_with_trailer, "StandardTrailer")?;
+            Ok(builder.build())
         }
 
         fn empty(xml_document: &'a roxmltree::Document<'a>) -> ParseResult<Self> {
@@ -1573,7 +2494,7 @@ mod quickfix {
                 }
             );
             Ok(QuickFixReader {
-                builder: DictionaryBuilder::new(version),
+                version,
                 node_with_header: find_tagged_child("header")?,
                 node_with_trailer: find_tagged_child("trailer")?,
                 node_with_messages: find_tagged_child("messages")?,
@@ -1590,8 +2511,6 @@ mod quickfix {
         if node.tag_name().name() != "field" {
             return Err(ParseDictionaryError::InvalidFormat);
         }
-        let data_type_iid = import_datatype(builder, node);
-        let value_restrictions = value_restrictions_from_node(node, data_type_iid);
         let name = node
             .attribute("name")
             .ok_or(ParseDictionaryError::InvalidFormat)?
@@ -1601,6 +2520,19 @@ mod quickfix {
             .ok_or(ParseDictionaryError::InvalidFormat)?
             .parse()
             .map_err(|_| ParseDictionaryError::InvalidFormat)?;
+        if !is_valid_tag(tag) {
+            return Err(ParseDictionaryError::InvalidData(format!(
+                "field {} has out-of-range tag {}",
+                name, tag
+            )));
+        }
+        // Transport and application layer specifications might both define
+        // the same fields.
+        if let Some(iid) = builder.symbol(KeyRef::FieldByTag(tag)) {
+            return Ok(*iid);
+        }
+        let data_type_iid = import_datatype(builder, node)?;
+        let value_restrictions = value_restrictions_from_node(node, data_type_iid)?;
         let field = FieldData {
             name,
             tag,
@@ -1647,6 +2579,8 @@ mod quickfix {
             required: true,
             elaboration: None,
             description: String::new(),
+            scenario: BASE_SCENARIO.to_string(),
+            responses: Vec::new(),
         };
         Ok(builder.add_message(message))
     }
@@ -1656,13 +2590,12 @@ mod quickfix {
         node: roxmltree::Node,
         name: S,
     ) -> ParseResult<InternalId> {
-        let mut layout_items = LayoutItems::new();
-        for child in node.children() {
-            if child.is_element() {
-                layout_items.push(import_layout_item(builder, child)?);
-            }
-        }
-        let component = ComponentData {
+        let layout_items = import_layout_items(builder, node)?;
+        Ok(builder.add_component(component_data(name.as_ref(), layout_items)))
+    }
+
+    fn component_data(name: &str, layout_items: LayoutItems) -> ComponentData {
+        ComponentData {
             id: 0,
             component_type: FixmlComponentAttributes::Block {
                 // FIXME
@@ -1672,59 +2605,55 @@ mod quickfix {
             },
             layout_items,
             category_iid: 0, // FIXME
-            name: name.as_ref().to_string(),
+            name: name.to_string(),
             abbr_name: None,
-        };
-        let iid = builder.add_component(component);
-        match builder.symbol(KeyRef::ComponentByName(name.as_ref())) {
-            Some(x) => Ok(*x),
-            None => {
-                builder
-                    .symbol_table
-                    .insert(Key::ComponentByName(name.as_ref().to_string()), iid);
-                Ok(iid)
+        }
+    }
+
+    fn import_layout_items(
+        builder: &mut DictionaryBuilder,
+        node: roxmltree::Node,
+    ) -> ParseResult<LayoutItems> {
+        let mut layout_items = LayoutItems::new();
+        for child in node.children() {
+            if child.is_element() {
+                layout_items.push(import_layout_item(builder, child)?);
             }
         }
+        Ok(layout_items)
     }
 
-    fn import_datatype(builder: &mut DictionaryBuilder, node: roxmltree::Node) -> InternalId {
+    fn import_datatype(
+        builder: &mut DictionaryBuilder,
+        node: roxmltree::Node,
+    ) -> ParseResult<InternalId> {
         // References should only happen at <field> tags.
         debug_assert_eq!(node.tag_name().name(), "field");
         let datatype = {
             // The idenfier that QuickFIX uses for this type.
-            let quickfix_name = node.attribute("type").unwrap();
+            let quickfix_name = node
+                .attribute("type")
+                .ok_or(ParseDictionaryError::InvalidFormat)?;
             // Translate that into a real datatype.
-            FixDatatype::from_quickfix_name(quickfix_name).unwrap()
+            FixDatatype::from_quickfix_name(quickfix_name).ok_or_else(|| {
+                ParseDictionaryError::InvalidData(format!("Unknown datatype '{}'", quickfix_name))
+            })?
         };
-        // Get the official (not QuickFIX's) name of `datatype`.
-        let name = datatype.name();
-        match builder.symbol(KeyRef::DatatypeByName(name)) {
-            Some(x) => *x,
-            None => {
-                let iid = builder.data_types.len() as u32;
-                let data = DatatypeData {
-                    datatype,
-                    description: String::new(),
-                    examples: Vec::new(),
-                };
-                builder.data_types.push(data);
-                builder
-                    .symbol_table
-                    .insert(Key::DatatypeByName(name.to_string()), iid);
-                iid
-            }
-        }
+        Ok(builder.datatype_iid(datatype))
     }
 
     fn value_restrictions_from_node(
         node: roxmltree::Node,
         _datatype: InternalId,
-    ) -> Option<Vec<FieldEnumData>> {
+    ) -> ParseResult<Option<Vec<FieldEnumData>>> {
         let mut values = Vec::new();
         for child in node.children() {
             if child.is_element() {
-                let variant = child.attribute("enum").unwrap().to_string();
-                let description = child.attribute("description").unwrap().to_string();
+                let variant = child
+                    .attribute("enum")
+                    .ok_or(ParseDictionaryError::InvalidFormat)?
+                    .to_string();
+                let description = child.attribute("description").unwrap_or("").to_string();
                 let enum_value = FieldEnumData {
                     value: variant,
                     description,
@@ -1733,9 +2662,9 @@ mod quickfix {
             }
         }
         if values.len() == 0 {
-            None
+            Ok(None)
         } else {
-            Some(values)
+            Ok(Some(values))
         }
     }
 
@@ -1746,21 +2675,36 @@ mod quickfix {
         // This processing step requires on fields being already present in
         // the dictionary.
         debug_assert_ne!(builder.fields.len(), 0);
-        let name = node.attribute("name").unwrap();
-        let required = node.attribute("required").unwrap() == "Y";
+        let name = node
+            .attribute("name")
+            .ok_or(ParseDictionaryError::InvalidFormat)?;
+        let required = node.attribute("required") == Some("Y");
         let tag = node.tag_name().name();
+        let unknown_field =
+            || ParseDictionaryError::InvalidData(format!("Unknown field '{}'", name));
         let kind = match tag {
             "field" => {
-                let field_iid = builder.symbol(KeyRef::FieldByName(name)).unwrap();
+                let field_iid = builder
+                    .symbol(KeyRef::FieldByName(name))
+                    .ok_or_else(unknown_field)?;
                 LayoutItemKindData::Field { iid: *field_iid }
             }
             "component" => {
-                // Components may *not* be already present.
-                let component_iid = import_component(builder, node, name)?;
+                let component_iid =
+                    *builder
+                        .symbol(KeyRef::ComponentByName(name))
+                        .ok_or_else(|| {
+                            ParseDictionaryError::InvalidData(format!(
+                                "Unknown component '{}'",
+                                name
+                            ))
+                        })?;
                 LayoutItemKindData::Component { iid: component_iid }
             }
             "group" => {
-                let len_field_iid = *builder.symbol(KeyRef::FieldByName(name)).unwrap();
+                let len_field_iid = *builder
+                    .symbol(KeyRef::FieldByName(name))
+                    .ok_or_else(unknown_field)?;
                 let mut items = Vec::new();
                 for child in node.children().filter(|n| n.is_element()) {
                     items.push(import_layout_item(builder, child)?);
@@ -1802,12 +2746,915 @@ mod quickfix {
 
     type ParseError = ParseDictionaryError;
     type ParseResult<T> = Result<T, ParseError>;
+}
 
-    /// The error type that can arise when decoding a QuickFIX Dictionary.
-    #[derive(Clone, Debug)]
-    pub enum ParseDictionaryError {
-        InvalidFormat,
-        InvalidData(String),
+/// The error type that can arise when decoding a QuickFIX, FIX Orchestra,
+/// or binary (see [`Dictionary::from_bytes`]) Dictionary.
+#[derive(Clone, Debug)]
+pub enum ParseDictionaryError {
+    InvalidFormat,
+    InvalidData(String),
+}
+
+impl fmt::Display for ParseDictionaryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidFormat => write!(f, "Invalid FIX dictionary format"),
+            Self::InvalidData(msg) => write!(f, "Invalid FIX dictionary: {}", msg),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseDictionaryError {}
+
+#[cfg(any(feature = "std", not(fefix_lib)))]
+mod orchestra {
+    use super::*;
+
+    const STANDARD_HEADER: &str = "StandardHeader";
+    const STANDARD_TRAILER: &str = "StandardTrailer";
+
+    pub struct OrchestraReader<'a> {
+        nodes: FnvHashMap<&'static str, roxmltree::Node<'a, 'a>>,
+        components_by_id: FnvHashMap<&'a str, &'a str>,
+        groups_by_id: FnvHashMap<&'a str, roxmltree::Node<'a, 'a>>,
+        code_sets: FnvHashMap<&'a str, roxmltree::Node<'a, 'a>>,
+        builder: DictionaryBuilder,
+    }
+
+    impl<'a> OrchestraReader<'a> {
+        pub fn parse(xml_document: &'a roxmltree::Document<'a>) -> ParseResult<Dictionary> {
+            let mut reader = Self::empty(xml_document)?;
+            for node in reader.children_of("fields") {
+                if is_base_scenario(node) {
+                    reader.import_field(node)?;
+                }
+            }
+            // Components and groups can reference each other regardless of
+            // their order of definition, so we must register all components
+            // before importing their layouts.
+            let component_nodes: Vec<roxmltree::Node> = reader.children_of("components");
+            for node in component_nodes.iter() {
+                let name = attribute(*node, "name")?;
+                let mut component = component_data(name);
+                component.id = attribute(*node, "id")?.parse().unwrap_or(0);
+                component.abbr_name = node.attribute("abbrName").map(str::to_string);
+                reader
+                    .components_by_id
+                    .insert(attribute(*node, "id")?, name);
+                reader.builder.add_component(component);
+            }
+            for node in reader.children_of("groups") {
+                reader.groups_by_id.insert(attribute(node, "id")?, node);
+            }
+            for node in component_nodes {
+                let name = attribute(node, "name")?;
+                let iid = *reader
+                    .builder
+                    .symbol(KeyRef::ComponentByName(name))
+                    .unwrap();
+                let layout_items = reader.import_layout_items(node, 0)?;
+                reader.builder.components[iid as usize].layout_items = layout_items;
+            }
+            for name in [STANDARD_HEADER, STANDARD_TRAILER].iter() {
+                if reader
+                    .builder
+                    .symbol(KeyRef::ComponentByName(name))
+                    .is_none()
+                {
+                    reader.builder.add_component(component_data(name));
+                }
+            }
+            // Non-base scenarios must not shadow base scenarios in the symbol
+            // table, so they're imported last.
+            let (base, scenarios): (Vec<roxmltree::Node>, Vec<roxmltree::Node>) = reader
+                .children_of("messages")
+                .into_iter()
+                .partition(|node| is_base_scenario(*node));
+            for node in scenarios {
+                let message = reader.import_message(node)?;
+                reader.builder.messages.push(message);
+            }
+            for node in base {
+                let message = reader.import_message(node)?;
+                reader.builder.add_message(message);
+            }
+            Ok(reader.builder.build())
+        }
+
+        fn empty(xml_document: &'a roxmltree::Document<'a>) -> ParseResult<Self> {
+            let root = xml_document.root_element();
+            if root.tag_name().name() != "repository" {
+                return Err(ParseDictionaryError::InvalidData(
+                    "<repository> tag not found".to_string(),
+                ));
+            }
+            let version = root
+                .attribute("version")
+                .or_else(|| root.attribute("name"))
+                .ok_or_else(|| {
+                    ParseDictionaryError::InvalidData("No version attribute.".to_string())
+                })?;
+            let mut nodes = FnvHashMap::default();
+            for tag in ["codeSets", "fields", "components", "groups", "messages"].iter() {
+                if let Some(node) = root.children().find(|n| n.tag_name().name() == *tag) {
+                    nodes.insert(*tag, node);
+                }
+            }
+            for tag in ["fields", "messages"].iter() {
+                if !nodes.contains_key(tag) {
+                    return Err(ParseDictionaryError::InvalidData(format!(
+                        "<{}> tag not found",
+                        tag
+                    )));
+                }
+            }
+            let mut code_sets = FnvHashMap::default();
+            if let Some(node) = nodes.get("codeSets") {
+                for code_set in node.children().filter(|n| n.is_element()) {
+                    if is_base_scenario(code_set) {
+                        code_sets.insert(attribute(code_set, "name")?, code_set);
+                    }
+                }
+            }
+            Ok(Self {
+                nodes,
+                components_by_id: FnvHashMap::default(),
+                groups_by_id: FnvHashMap::default(),
+                code_sets,
+                builder: DictionaryBuilder::new(version.to_string()),
+            })
+        }
+
+        fn children_of(&self, tag: &str) -> Vec<roxmltree::Node<'a, 'a>> {
+            self.nodes
+                .get(tag)
+                .map(|node| node.children().filter(|n| n.is_element()).collect())
+                .unwrap_or_default()
+        }
+
+        fn import_field(&mut self, node: roxmltree::Node) -> ParseResult<InternalId> {
+            let tag = attribute(node, "id")?
+                .parse()
+                .map_err(|_| ParseDictionaryError::InvalidFormat)?;
+            if !is_valid_tag(tag) {
+                return Err(ParseDictionaryError::InvalidData(format!(
+                    "field {} has out-of-range tag {}",
+                    attribute(node, "name")?,
+                    tag
+                )));
+            }
+            let type_name = attribute(node, "type")?;
+            // Field types are either datatypes or code sets.
+            let (datatype_name, value_restrictions) = match self.code_sets.get(type_name) {
+                Some(code_set) => (attribute(*code_set, "type")?, Some(codes(*code_set)?)),
+                None => (type_name, None),
+            };
+            let data_type_iid = self.import_datatype(datatype_name);
+            let field = FieldData {
+                name: attribute(node, "name")?.to_string(),
+                tag,
+                data_type_iid,
+                associated_data_tag: node.attribute("lengthId").and_then(|id| id.parse().ok()),
+                value_restrictions,
+                required: true,
+                abbr_name: node.attribute("abbrName").map(str::to_string),
+                base_category_abbr_name: node.attribute("baseCategoryAbbrName").map(str::to_string),
+                base_category_id: None,
+                description: documentation(node),
+            };
+            Ok(self.builder.add_field(field))
+        }
+
+        fn import_datatype(&mut self, name: &str) -> InternalId {
+            // Orchestra datatype names mostly match the official ones, but
+            // newer datatypes (e.g. `Tenor`) are unknown and treated as
+            // strings.
+            let datatype = FixDatatype::iter_all()
+                .find(|dt| dt.name() == name)
+                .or_else(|| FixDatatype::from_quickfix_name(name))
+                .unwrap_or(FixDatatype::String);
+            self.builder.datatype_iid(datatype)
+        }
+
+        fn import_message(&mut self, node: roxmltree::Node) -> ParseResult<MessageData> {
+            let structure = node
+                .children()
+                .find(|n| n.tag_name().name() == "structure")
+                .ok_or(ParseDictionaryError::InvalidFormat)?;
+            // Just like QuickFIX dictionaries, message layouts don't include
+            // `StandardHeader` and `StandardTrailer`.
+            let layout_items = self
+                .import_layout_items(structure, 0)?
+                .into_iter()
+                .filter(|item| match item.kind {
+                    LayoutItemKindData::Component { iid } => {
+                        let name = self.builder.components[iid as usize].name.as_str();
+                        name != STANDARD_HEADER && name != STANDARD_TRAILER
+                    }
+                    _ => true,
+                })
+                .collect();
+            let mut responses = Vec::new();
+            for response in node
+                .children()
+                .filter(|n| n.tag_name().name() == "responses")
+                .flat_map(|n| n.children())
+                .filter(|n| n.tag_name().name() == "response")
+            {
+                let name = response.attribute("name").unwrap_or("");
+                for message_ref in response
+                    .children()
+                    .filter(|n| n.tag_name().name() == "messageRef")
+                {
+                    responses.push(MessageResponseData {
+                        name: name.to_string(),
+                        msg_type: attribute(message_ref, "msgType")?.to_string(),
+                        scenario: message_ref
+                            .attribute("scenario")
+                            .unwrap_or(BASE_SCENARIO)
+                            .to_string(),
+                    });
+                }
+            }
+            Ok(MessageData {
+                component_id: attribute(node, "id")?.parse().unwrap_or(0),
+                msg_type: attribute(node, "msgType")?.to_string(),
+                name: attribute(node, "name")?.to_string(),
+                category_iid: self.import_category(node),
+                section_id: String::new(),
+                layout_items,
+                abbr_name: node.attribute("abbrName").map(str::to_string),
+                required: true,
+                description: documentation(node).unwrap_or_default(),
+                elaboration: None,
+                scenario: node
+                    .attribute("scenario")
+                    .unwrap_or(BASE_SCENARIO)
+                    .to_string(),
+                responses,
+            })
+        }
+
+        fn import_category(&mut self, node: roxmltree::Node) -> InternalId {
+            let name = node.attribute("category").unwrap_or("");
+            match self.builder.symbol(KeyRef::CategoryByName(name)) {
+                Some(x) => *x,
+                None => {
+                    let iid = self.builder.categories.len() as u32;
+                    self.builder.categories.push(CategoryData {
+                        name: name.to_string(),
+                        fixml_filename: String::new(),
+                    });
+                    self.builder
+                        .symbol_table
+                        .insert(Key::CategoryByName(name.to_string()), iid);
+                    iid
+                }
+            }
+        }
+
+        fn import_layout_items(
+            &mut self,
+            node: roxmltree::Node,
+            depth: usize,
+        ) -> ParseResult<LayoutItems> {
+            // Protects against cyclic group references.
+            if depth > 32 {
+                return Err(ParseDictionaryError::InvalidData(
+                    "Groups are nested too deeply".to_string(),
+                ));
+            }
+            let mut layout_items = LayoutItems::new();
+            for child in node.children().filter(|n| n.is_element()) {
+                if !is_base_scenario(child) {
+                    continue;
+                }
+                let id = || attribute(child, "id");
+                let kind = match child.tag_name().name() {
+                    "fieldRef" => LayoutItemKindData::Field {
+                        iid: self.field_iid(id()?)?,
+                    },
+                    "componentRef" => {
+                        let name = self.components_by_id.get(id()?).ok_or_else(|| {
+                            ParseDictionaryError::InvalidData(format!(
+                                "Unknown component ID '{}'",
+                                child.attribute("id").unwrap_or("")
+                            ))
+                        })?;
+                        LayoutItemKindData::Component {
+                            iid: *self.builder.symbol(KeyRef::ComponentByName(name)).unwrap(),
+                        }
+                    }
+                    "groupRef" => {
+                        let group = *self.groups_by_id.get(id()?).ok_or_else(|| {
+                            ParseDictionaryError::InvalidData(format!(
+                                "Unknown group ID '{}'",
+                                child.attribute("id").unwrap_or("")
+                            ))
+                        })?;
+                        let num_in_group = group
+                            .children()
+                            .find(|n| n.tag_name().name() == "numInGroup")
+                            .ok_or(ParseDictionaryError::InvalidFormat)?;
+                        LayoutItemKindData::Group {
+                            len_field_iid: self.field_iid(attribute(num_in_group, "id")?)?,
+                            items: self.import_layout_items(group, depth + 1)?,
+                        }
+                    }
+                    // Other elements (e.g. `<numInGroup>`, `<annotation>`) are
+                    // not layout items.
+                    _ => continue,
+                };
+                layout_items.push(LayoutItemData {
+                    required: child.attribute("presence") == Some("required"),
+                    kind,
+                });
+            }
+            Ok(layout_items)
+        }
+
+        fn field_iid(&self, id: &str) -> ParseResult<InternalId> {
+            id.parse()
+                .ok()
+                .and_then(|tag| self.builder.symbol(KeyRef::FieldByTag(tag)))
+                .copied()
+                .ok_or_else(|| {
+                    ParseDictionaryError::InvalidData(format!("Unknown field ID '{}'", id))
+                })
+        }
+    }
+
+    fn component_data(name: &str) -> ComponentData {
+        ComponentData {
+            id: 0,
+            component_type: FixmlComponentAttributes::Block {
+                is_implicit: false,
+                is_repeating: false,
+                is_optimized: false,
+            },
+            layout_items: LayoutItems::new(),
+            category_iid: 0,
+            name: name.to_string(),
+            abbr_name: None,
+        }
+    }
+
+    fn codes(code_set: roxmltree::Node) -> ParseResult<Vec<FieldEnumData>> {
+        code_set
+            .children()
+            .filter(|n| n.tag_name().name() == "code" && is_base_scenario(*n))
+            .map(|code| {
+                Ok(FieldEnumData {
+                    value: attribute(code, "value")?.to_string(),
+                    description: attribute(code, "name")?.to_string(),
+                })
+            })
+            .collect()
+    }
+
+    fn documentation(node: roxmltree::Node) -> Option<String> {
+        node.children()
+            .filter(|n| n.tag_name().name() == "annotation")
+            .flat_map(|n| n.children())
+            .find(|n| n.tag_name().name() == "documentation")
+            .and_then(|n| n.text())
+            .map(|text| text.trim().to_string())
+    }
+
+    fn is_base_scenario(node: roxmltree::Node) -> bool {
+        node.attribute("scenario").unwrap_or(BASE_SCENARIO) == BASE_SCENARIO
+    }
+
+    fn attribute<'a>(node: roxmltree::Node<'a, '_>, name: &str) -> ParseResult<&'a str> {
+        node.attribute(name).ok_or_else(|| {
+            ParseDictionaryError::InvalidData(format!(
+                "<{}> has no '{}' attribute",
+                node.tag_name().name(),
+                name
+            ))
+        })
+    }
+
+    type ParseResult<T> = Result<T, ParseDictionaryError>;
+}
+
+mod binary {
+    //! A compact binary format for [`Dictionary`], with no attempt at
+    //! compatibility across format versions: it's meant as a cache, not as an
+    //! interchange format.
+
+    use super::*;
+    use core::convert::TryFrom;
+
+    const MAGIC: &[u8] = b"FEFIXDICT";
+    const FORMAT_VERSION: u8 = 1;
+
+    pub fn encode(data: &DictionaryData) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        buffer.extend_from_slice(MAGIC);
+        buffer.push(FORMAT_VERSION);
+        data.encode(&mut buffer);
+        buffer
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<DictionaryData, ParseDictionaryError> {
+        let bytes = bytes
+            .strip_prefix(MAGIC)
+            .ok_or(ParseDictionaryError::InvalidFormat)?;
+        match bytes.split_first() {
+            Some((&FORMAT_VERSION, rest)) => {
+                let mut reader = Reader(rest);
+                let data = DictionaryData::decode(&mut reader)
+                    .filter(|_| reader.0.is_empty())
+                    .ok_or(ParseDictionaryError::InvalidFormat)?;
+                if is_consistent(&data) {
+                    Ok(data)
+                } else {
+                    Err(ParseDictionaryError::InvalidData(
+                        "dangling internal reference".to_string(),
+                    ))
+                }
+            }
+            Some((version, _)) => Err(ParseDictionaryError::InvalidData(format!(
+                "unsupported binary format version {}",
+                version
+            ))),
+            None => Err(ParseDictionaryError::InvalidFormat),
+        }
+    }
+
+    /// Checks that all internal IDs are in bounds, so that lookups on decoded
+    /// data can't panic.
+    fn is_consistent(data: &DictionaryData) -> bool {
+        fn items_are_consistent(data: &DictionaryData, items: &[LayoutItemData]) -> bool {
+            items.iter().all(|item| match &item.kind {
+                LayoutItemKindData::Component { iid } => (*iid as usize) < data.components.len(),
+                LayoutItemKindData::Field { iid } => (*iid as usize) < data.fields.len(),
+                LayoutItemKindData::Group {
+                    len_field_iid,
+                    items,
+                } => {
+                    (*len_field_iid as usize) < data.fields.len()
+                        && items_are_consistent(data, items)
+                }
+            })
+        }
+        let symbols_are_consistent = data.symbol_table.iter().all(|(key, iid)| {
+            let len = match key {
+                Key::Abbreviation(_) => data.abbreviations.len(),
+                Key::CategoryByName(_) => data.categories.len(),
+                Key::ComponentByName(_) => data.components.len(),
+                Key::DatatypeByName(_) => data.data_types.len(),
+                Key::FieldByTag(_) | Key::FieldByName(_) => data.fields.len(),
+                Key::MessageByName(_) | Key::MessageByMsgType(_) => data.messages.len(),
+            };
+            (*iid as usize) < len
+        });
+        symbols_are_consistent
+            && data.fields.iter().all(|field| {
+                is_valid_tag(field.tag) && (field.data_type_iid as usize) < data.data_types.len()
+            })
+            && data
+                .components
+                .iter()
+                .all(|component| items_are_consistent(data, &component.layout_items))
+            && data
+                .messages
+                .iter()
+                .all(|message| items_are_consistent(data, &message.layout_items))
+    }
+
+    struct Reader<'a>(&'a [u8]);
+
+    impl<'a> Reader<'a> {
+        fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+            if self.0.len() < len {
+                None
+            } else {
+                let (bytes, rest) = self.0.split_at(len);
+                self.0 = rest;
+                Some(bytes)
+            }
+        }
+
+        fn byte(&mut self) -> Option<u8> {
+            self.take(1).map(|bytes| bytes[0])
+        }
+    }
+
+    trait Binary: Sized {
+        fn encode(&self, buffer: &mut Vec<u8>);
+
+        fn decode(reader: &mut Reader) -> Option<Self>;
+    }
+
+    impl Binary for u32 {
+        fn encode(&self, buffer: &mut Vec<u8>) {
+            buffer.extend_from_slice(&self.to_le_bytes());
+        }
+
+        fn decode(reader: &mut Reader) -> Option<Self> {
+            let mut bytes = [0; 4];
+            bytes.copy_from_slice(reader.take(4)?);
+            Some(u32::from_le_bytes(bytes))
+        }
+    }
+
+    impl Binary for usize {
+        fn encode(&self, buffer: &mut Vec<u8>) {
+            buffer.extend_from_slice(&(*self as u64).to_le_bytes());
+        }
+
+        fn decode(reader: &mut Reader) -> Option<Self> {
+            let mut bytes = [0; 8];
+            bytes.copy_from_slice(reader.take(8)?);
+            usize::try_from(u64::from_le_bytes(bytes)).ok()
+        }
+    }
+
+    impl Binary for bool {
+        fn encode(&self, buffer: &mut Vec<u8>) {
+            buffer.push(*self as u8);
+        }
+
+        fn decode(reader: &mut Reader) -> Option<Self> {
+            match reader.byte()? {
+                0 => Some(false),
+                1 => Some(true),
+                _ => None,
+            }
+        }
+    }
+
+    impl Binary for String {
+        fn encode(&self, buffer: &mut Vec<u8>) {
+            (self.len() as u32).encode(buffer);
+            buffer.extend_from_slice(self.as_bytes());
+        }
+
+        fn decode(reader: &mut Reader) -> Option<Self> {
+            let len = u32::decode(reader)? as usize;
+            let bytes = reader.take(len)?;
+            String::from_utf8(bytes.to_vec()).ok()
+        }
+    }
+
+    impl<T: Binary> Binary for Option<T> {
+        fn encode(&self, buffer: &mut Vec<u8>) {
+            self.is_some().encode(buffer);
+            if let Some(value) = self {
+                value.encode(buffer);
+            }
+        }
+
+        fn decode(reader: &mut Reader) -> Option<Self> {
+            if bool::decode(reader)? {
+                T::decode(reader).map(Some)
+            } else {
+                Some(None)
+            }
+        }
+    }
+
+    impl<T: Binary> Binary for Vec<T> {
+        fn encode(&self, buffer: &mut Vec<u8>) {
+            (self.len() as u32).encode(buffer);
+            for item in self {
+                item.encode(buffer);
+            }
+        }
+
+        fn decode(reader: &mut Reader) -> Option<Self> {
+            let len = u32::decode(reader)? as usize;
+            // Don't trust `len` for preallocation: every item takes at least
+            // one byte.
+            let mut items = Vec::with_capacity(len.min(reader.0.len()));
+            for _ in 0..len {
+                items.push(T::decode(reader)?);
+            }
+            Some(items)
+        }
+    }
+
+    impl Binary for FixDatatype {
+        fn encode(&self, buffer: &mut Vec<u8>) {
+            self.name().to_string().encode(buffer);
+        }
+
+        fn decode(reader: &mut Reader) -> Option<Self> {
+            let name = String::decode(reader)?;
+            FixDatatype::iter_all().find(|datatype| datatype.name() == name)
+        }
+    }
+
+    impl Binary for Key {
+        fn encode(&self, buffer: &mut Vec<u8>) {
+            let (discriminant, name) = match self {
+                Key::Abbreviation(s) => (0, s),
+                Key::CategoryByName(s) => (1, s),
+                Key::ComponentByName(s) => (2, s),
+                Key::DatatypeByName(s) => (3, s),
+                Key::FieldByName(s) => (5, s),
+                Key::MessageByName(s) => (6, s),
+                Key::MessageByMsgType(s) => (7, s),
+                Key::FieldByTag(tag) => {
+                    buffer.push(4);
+                    tag.encode(buffer);
+                    return;
+                }
+            };
+            buffer.push(discriminant);
+            name.encode(buffer);
+        }
+
+        fn decode(reader: &mut Reader) -> Option<Self> {
+            Some(match reader.byte()? {
+                0 => Key::Abbreviation(String::decode(reader)?),
+                1 => Key::CategoryByName(String::decode(reader)?),
+                2 => Key::ComponentByName(String::decode(reader)?),
+                3 => Key::DatatypeByName(String::decode(reader)?),
+                4 => Key::FieldByTag(u32::decode(reader)?),
+                5 => Key::FieldByName(String::decode(reader)?),
+                6 => Key::MessageByName(String::decode(reader)?),
+                7 => Key::MessageByMsgType(String::decode(reader)?),
+                _ => return None,
+            })
+        }
+    }
+
+    impl Binary for DictionaryData {
+        fn encode(&self, buffer: &mut Vec<u8>) {
+            self.version.encode(buffer);
+            (self.symbol_table.len() as u32).encode(buffer);
+            for (key, iid) in self.symbol_table.iter() {
+                key.encode(buffer);
+                iid.encode(buffer);
+            }
+            self.abbreviations.encode(buffer);
+            self.data_types.encode(buffer);
+            self.fields.encode(buffer);
+            self.components.encode(buffer);
+            self.messages.encode(buffer);
+            self.categories.encode(buffer);
+            self.header.encode(buffer);
+        }
+
+        fn decode(reader: &mut Reader) -> Option<Self> {
+            let version = String::decode(reader)?;
+            let symbols_count = u32::decode(reader)?;
+            let mut symbol_table = SymbolTable::default();
+            for _ in 0..symbols_count {
+                let key = Key::decode(reader)?;
+                symbol_table.insert(key, u32::decode(reader)?);
+            }
+            Some(Self {
+                version,
+                symbol_table,
+                abbreviations: Vec::decode(reader)?,
+                data_types: Vec::decode(reader)?,
+                fields: Vec::decode(reader)?,
+                components: Vec::decode(reader)?,
+                messages: Vec::decode(reader)?,
+                categories: Vec::decode(reader)?,
+                header: Vec::decode(reader)?,
+            })
+        }
+    }
+
+    impl Binary for AbbreviatonData {
+        fn encode(&self, buffer: &mut Vec<u8>) {
+            self.abbreviation.encode(buffer);
+            self.is_last.encode(buffer);
+        }
+
+        fn decode(reader: &mut Reader) -> Option<Self> {
+            Some(Self {
+                abbreviation: String::decode(reader)?,
+                is_last: bool::decode(reader)?,
+            })
+        }
+    }
+
+    impl Binary for CategoryData {
+        fn encode(&self, buffer: &mut Vec<u8>) {
+            self.name.encode(buffer);
+            self.fixml_filename.encode(buffer);
+        }
+
+        fn decode(reader: &mut Reader) -> Option<Self> {
+            Some(Self {
+                name: String::decode(reader)?,
+                fixml_filename: String::decode(reader)?,
+            })
+        }
+    }
+
+    impl Binary for DatatypeData {
+        fn encode(&self, buffer: &mut Vec<u8>) {
+            self.datatype.encode(buffer);
+            self.description.encode(buffer);
+            self.examples.encode(buffer);
+        }
+
+        fn decode(reader: &mut Reader) -> Option<Self> {
+            Some(Self {
+                datatype: FixDatatype::decode(reader)?,
+                description: String::decode(reader)?,
+                examples: Vec::decode(reader)?,
+            })
+        }
+    }
+
+    impl Binary for FieldEnumData {
+        fn encode(&self, buffer: &mut Vec<u8>) {
+            self.value.encode(buffer);
+            self.description.encode(buffer);
+        }
+
+        fn decode(reader: &mut Reader) -> Option<Self> {
+            Some(Self {
+                value: String::decode(reader)?,
+                description: String::decode(reader)?,
+            })
+        }
+    }
+
+    impl Binary for FieldData {
+        fn encode(&self, buffer: &mut Vec<u8>) {
+            self.name.encode(buffer);
+            self.tag.encode(buffer);
+            self.data_type_iid.encode(buffer);
+            self.associated_data_tag.encode(buffer);
+            self.value_restrictions.encode(buffer);
+            self.abbr_name.encode(buffer);
+            self.base_category_id.encode(buffer);
+            self.base_category_abbr_name.encode(buffer);
+            self.required.encode(buffer);
+            self.description.encode(buffer);
+        }
+
+        fn decode(reader: &mut Reader) -> Option<Self> {
+            Some(Self {
+                name: String::decode(reader)?,
+                tag: u32::decode(reader)?,
+                data_type_iid: u32::decode(reader)?,
+                associated_data_tag: Option::decode(reader)?,
+                value_restrictions: Option::decode(reader)?,
+                abbr_name: Option::decode(reader)?,
+                base_category_id: Option::decode(reader)?,
+                base_category_abbr_name: Option::decode(reader)?,
+                required: bool::decode(reader)?,
+                description: Option::decode(reader)?,
+            })
+        }
+    }
+
+    impl Binary for LayoutItemData {
+        fn encode(&self, buffer: &mut Vec<u8>) {
+            self.required.encode(buffer);
+            match &self.kind {
+                LayoutItemKindData::Component { iid } => {
+                    buffer.push(0);
+                    iid.encode(buffer);
+                }
+                LayoutItemKindData::Group {
+                    len_field_iid,
+                    items,
+                } => {
+                    buffer.push(1);
+                    len_field_iid.encode(buffer);
+                    items.encode(buffer);
+                }
+                LayoutItemKindData::Field { iid } => {
+                    buffer.push(2);
+                    iid.encode(buffer);
+                }
+            }
+        }
+
+        fn decode(reader: &mut Reader) -> Option<Self> {
+            let required = bool::decode(reader)?;
+            let kind = match reader.byte()? {
+                0 => LayoutItemKindData::Component {
+                    iid: u32::decode(reader)?,
+                },
+                1 => LayoutItemKindData::Group {
+                    len_field_iid: u32::decode(reader)?,
+                    items: Vec::decode(reader)?,
+                },
+                2 => LayoutItemKindData::Field {
+                    iid: u32::decode(reader)?,
+                },
+                _ => return None,
+            };
+            Some(Self { required, kind })
+        }
+    }
+
+    impl Binary for FixmlComponentAttributes {
+        fn encode(&self, buffer: &mut Vec<u8>) {
+            match self {
+                Self::Xml => buffer.push(0),
+                Self::Block {
+                    is_repeating,
+                    is_implicit,
+                    is_optimized,
+                } => {
+                    buffer.push(1);
+                    is_repeating.encode(buffer);
+                    is_implicit.encode(buffer);
+                    is_optimized.encode(buffer);
+                }
+                Self::Message => buffer.push(2),
+            }
+        }
+
+        fn decode(reader: &mut Reader) -> Option<Self> {
+            Some(match reader.byte()? {
+                0 => Self::Xml,
+                1 => Self::Block {
+                    is_repeating: bool::decode(reader)?,
+                    is_implicit: bool::decode(reader)?,
+                    is_optimized: bool::decode(reader)?,
+                },
+                2 => Self::Message,
+                _ => return None,
+            })
+        }
+    }
+
+    impl Binary for ComponentData {
+        fn encode(&self, buffer: &mut Vec<u8>) {
+            self.id.encode(buffer);
+            self.component_type.encode(buffer);
+            self.layout_items.encode(buffer);
+            self.category_iid.encode(buffer);
+            self.name.encode(buffer);
+            self.abbr_name.encode(buffer);
+        }
+
+        fn decode(reader: &mut Reader) -> Option<Self> {
+            Some(Self {
+                id: usize::decode(reader)?,
+                component_type: FixmlComponentAttributes::decode(reader)?,
+                layout_items: Vec::decode(reader)?,
+                category_iid: u32::decode(reader)?,
+                name: String::decode(reader)?,
+                abbr_name: Option::decode(reader)?,
+            })
+        }
+    }
+
+    impl Binary for MessageResponseData {
+        fn encode(&self, buffer: &mut Vec<u8>) {
+            self.name.encode(buffer);
+            self.msg_type.encode(buffer);
+            self.scenario.encode(buffer);
+        }
+
+        fn decode(reader: &mut Reader) -> Option<Self> {
+            Some(Self {
+                name: String::decode(reader)?,
+                msg_type: String::decode(reader)?,
+                scenario: String::decode(reader)?,
+            })
+        }
+    }
+
+    impl Binary for MessageData {
+        fn encode(&self, buffer: &mut Vec<u8>) {
+            self.component_id.encode(buffer);
+            self.msg_type.encode(buffer);
+            self.name.encode(buffer);
+            self.category_iid.encode(buffer);
+            self.section_id.encode(buffer);
+            self.layout_items.encode(buffer);
+            self.abbr_name.encode(buffer);
+            self.required.encode(buffer);
+            self.description.encode(buffer);
+            self.elaboration.encode(buffer);
+            self.scenario.encode(buffer);
+            self.responses.encode(buffer);
+        }
+
+        fn decode(reader: &mut Reader) -> Option<Self> {
+            Some(Self {
+                component_id: u32::decode(reader)?,
+                msg_type: String::decode(reader)?,
+                name: String::decode(reader)?,
+                category_iid: u32::decode(reader)?,
+                section_id: String::decode(reader)?,
+                layout_items: Vec::decode(reader)?,
+                abbr_name: Option::decode(reader)?,
+                required: bool::decode(reader)?,
+                description: String::decode(reader)?,
+                elaboration: Option::decode(reader)?,
+                scenario: String::decode(reader)?,
+                responses: Vec::decode(reader)?,
+            })
+        }
     }
 }
 
@@ -1883,6 +3730,7 @@ mod test {
         assert!(field_167.enums().unwrap().any(|e| e.value() == "EUCORP"));
     }
 
+    #[cfg(feature = "std")]
     const INVALID_QUICKFIX_SPECS: &[&str] = &[
         include_str!("test_data/quickfix_specs/empty_file.xml"),
         include_str!("test_data/quickfix_specs/missing_components.xml"),
@@ -1893,8 +3741,12 @@ mod test {
         include_str!("test_data/quickfix_specs/root_has_no_type_attr.xml"),
         include_str!("test_data/quickfix_specs/root_has_no_version_attrs.xml"),
         include_str!("test_data/quickfix_specs/root_is_not_fix.xml"),
+        include_str!("test_data/quickfix_specs/tag_out_of_range.xml"),
+        include_str!("test_data/quickfix_specs/unknown_datatype.xml"),
+        include_str!("test_data/quickfix_specs/unknown_field_reference.xml"),
     ];
 
+    #[cfg(feature = "std")]
     #[test]
     fn invalid_quickfix_specs() {
         for spec in INVALID_QUICKFIX_SPECS.iter() {
@@ -1902,4 +3754,330 @@ mod test {
             assert!(dict.is_err(), "{}", spec);
         }
     }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn custom_quickfix_spec() {
+        let spec = include_str!("test_data/quickfix_specs/custom_fields.xml");
+        let dict = Dictionary::from_quickfix_spec(spec).unwrap();
+        assert_eq!(dict.get_version(), "FIX.4.4");
+        let field = dict.field_by_name("VenueStrategy").unwrap();
+        assert_eq!(field.tag().get(), 20001);
+        assert_eq!(field.enums().unwrap().count(), 2);
+        let msg = dict.message_by_msgtype("D").unwrap();
+        assert!(msg.layout().any(|item| match item.kind() {
+            LayoutItemKind::Field(f) => f.tag().get() == 20001,
+            _ => false,
+        }));
+        let parties = dict.component_by_name("Parties").unwrap();
+        assert!(parties.items().any(|item| match item.kind() {
+            LayoutItemKind::Group(len_field, _) => len_field.tag().get() == 453,
+            _ => false,
+        }));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn tags_up_to_u16_max_are_valid() {
+        let spec = include_str!("test_data/quickfix_specs/tag_out_of_range.xml");
+        let dict = Dictionary::from_quickfix_spec(spec.replace("65536", "65535")).unwrap();
+        assert_eq!(dict.field_by_tag(65535).unwrap().tag().get(), 65535);
+        assert!(matches!(
+            Dictionary::from_quickfix_spec(spec),
+            Err(ParseDictionaryError::InvalidData(_))
+        ));
+        let mut builder = dict.to_builder();
+        builder
+            .add_custom_field(39999, "VenueStrategy", FixDatatype::String)
+            .unwrap();
+        assert!(builder
+            .add_custom_field(65536, "TooLarge", FixDatatype::String)
+            .is_err());
+        let dict = builder.build();
+        assert_eq!(
+            dict.field_by_name("VenueStrategy").unwrap().tag().get(),
+            39999
+        );
+        let roundtrip = Dictionary::from_bytes(&dict.to_bytes()).unwrap();
+        assert!(roundtrip.field_by_tag(65535).is_some());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn fixt_and_application_specs_are_merged() {
+        let dict = Dictionary::from_quickfix_specs(
+            include_str!("resources/quickfix/FIXT-1.1.xml"),
+            include_str!("resources/quickfix/FIX-5.0-SP2.xml"),
+        )
+        .unwrap();
+        assert_eq!(dict.get_version(), "FIX.5.0-SP2");
+        assert!(dict.message_by_msgtype("A").is_some());
+        assert!(dict.message_by_msgtype("D").is_some());
+        let std_header = dict.component_by_name("StandardHeader").unwrap();
+        assert!(std_header.items().any(|item| match item.kind() {
+            LayoutItemKind::Field(f) => f.name() == "BeginString",
+            _ => false,
+        }));
+        assert_eq!(dict.field_by_name("ApplVerID").unwrap().tag().get(), 1128);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn orchestra_repository() {
+        let spec = include_str!("test_data/orchestra/repository.xml");
+        let dict = Dictionary::from_orchestra_spec(spec).unwrap();
+        assert_eq!(dict.get_version(), "FIX.4.4");
+        let side = dict.field_by_tag(54).unwrap();
+        assert_eq!(side.data_type().basetype(), FixDatatype::Char);
+        assert_eq!(side.enums().unwrap().count(), 2);
+        assert_eq!(
+            dict.field_by_name("VenueTenor")
+                .unwrap()
+                .data_type()
+                .basetype(),
+            FixDatatype::String
+        );
+        let std_header = dict.component_by_name("StandardHeader").unwrap();
+        assert_eq!(std_header.items().count(), 2);
+        let msg = dict.message_by_msgtype("D").unwrap();
+        assert_eq!(msg.scenario(), "base");
+        assert_eq!(msg.layout().count(), 7);
+        assert!(msg.description().starts_with("The new order message"));
+        let responses: Vec<(String, String)> = msg
+            .responses()
+            .map(|r| (r.name().to_string(), r.scenario().to_string()))
+            .collect();
+        assert_eq!(
+            responses,
+            vec![
+                ("orderAck".to_string(), "base".to_string()),
+                ("orderReject".to_string(), "Rejected".to_string())
+            ]
+        );
+        let market = dict
+            .message_by_msgtype_and_scenario("D", "MarketOrder")
+            .unwrap();
+        assert_eq!(market.layout().count(), 3);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn orchestra_nested_groups() {
+        let spec = include_str!("test_data/orchestra/repository.xml");
+        let dict = Dictionary::from_orchestra_spec(spec).unwrap();
+        let parties = dict.component_by_name("Parties").unwrap();
+        let items: Vec<LayoutItem> = parties.items().collect();
+        match items[0].kind() {
+            LayoutItemKind::Group(len_field, items) => {
+                assert_eq!(len_field.tag().get(), 453);
+                assert!(items.iter().any(|item| match item.kind() {
+                    LayoutItemKind::Group(len_field, _) => len_field.tag().get() == 802,
+                    _ => false,
+                }));
+            }
+            _ => panic!("Expected a repeating group"),
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn orchestra_unknown_field_ref() {
+        let spec = include_str!("test_data/orchestra/unknown_field_ref.xml");
+        assert!(Dictionary::from_orchestra_spec(spec).is_err());
+        assert!(
+            Dictionary::from_orchestra_spec(include_str!("resources/quickfix/FIX-4.4.xml"))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn builder_rejects_invalid_customizations() {
+        let mut builder = Dictionary::fix44().to_builder();
+        assert_eq!(
+            builder.add_custom_field(4999, "Foo", FixDatatype::Int),
+            Err(DictionaryBuilderError::TagOutOfRange(4999))
+        );
+        assert_eq!(
+            builder.add_custom_field(70000, "Foo", FixDatatype::Int),
+            Err(DictionaryBuilderError::TagOutOfRange(70000))
+        );
+        assert_eq!(
+            builder.add_custom_field(5000, "Symbol", FixDatatype::String),
+            Err(DictionaryBuilderError::DuplicateName("Symbol".to_string()))
+        );
+        builder
+            .add_custom_field(5000, "Foo", FixDatatype::Int)
+            .unwrap();
+        assert_eq!(
+            builder.add_custom_field(5000, "Bar", FixDatatype::Int),
+            Err(DictionaryBuilderError::DuplicateTag(5000))
+        );
+        assert_eq!(
+            builder.add_enum_value(54, "1", "Buy"),
+            Err(DictionaryBuilderError::DuplicateEnumValue {
+                tag: 54,
+                value: "1".to_string()
+            })
+        );
+        assert_eq!(
+            builder.add_field_to_message("ZZ", 5000, true),
+            Err(DictionaryBuilderError::UnknownMessage("ZZ".to_string()))
+        );
+        assert_eq!(
+            builder.add_field_to_message("D", 5999, true),
+            Err(DictionaryBuilderError::UnknownField(5999))
+        );
+        assert_eq!(
+            builder.add_field_to_message("D", 11, true),
+            Err(DictionaryBuilderError::DuplicateTag(11))
+        );
+    }
+
+    #[test]
+    fn builder_leaves_original_dictionary_untouched() {
+        let dict = Dictionary::fix44();
+        let mut builder = dict.to_builder();
+        builder.set_version("FIX.4.4-VENUE");
+        builder
+            .add_custom_field(9000, "VenueFlag", FixDatatype::Boolean)
+            .unwrap();
+        let custom = builder.build();
+        assert_eq!(custom.get_version(), "FIX.4.4-VENUE");
+        assert_eq!(
+            custom.field_by_name("VenueFlag").unwrap().fix_datatype(),
+            FixDatatype::Boolean
+        );
+        assert!(dict.field_by_tag(9000).is_none());
+        assert_eq!(dict.get_version(), "FIX.4.4");
+    }
+
+    #[test]
+    fn iterators_agree_with_lookups() {
+        let dict = Dictionary::fix44();
+        for field in dict.iter_fields() {
+            let tag = field.tag().get() as u32;
+            assert_eq!(dict.field_by_tag(tag).unwrap().name(), field.name());
+            assert_eq!(dict.field_by_name(field.name()).unwrap().tag(), field.tag());
+        }
+        for message in dict.iter_messages() {
+            let by_msgtype = dict.message_by_msgtype(message.msg_type()).unwrap();
+            assert_eq!(by_msgtype.name(), message.name());
+        }
+        for component in dict.iter_components() {
+            assert!(dict.component_by_name(component.name()).is_some());
+        }
+        for datatype in dict.iter_datatypes() {
+            assert!(dict.datatype_by_name(datatype.name()).is_some());
+        }
+        for category in dict.iter_categories() {
+            assert!(!category.name().is_empty());
+        }
+        let enums_count: usize = dict
+            .iter_fields()
+            .filter_map(|field| field.enums().map(|enums| enums.count()))
+            .sum();
+        assert_eq!(dict.iter_field_enums().count(), enums_count);
+        assert!(dict
+            .iter_field_enums()
+            .any(|(field, value)| field.tag().get() == 28 && value.value() == "N"));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn builtin_dictionaries_match_their_specs() {
+        let spec = include_str!("resources/quickfix/FIX-4.4.xml");
+        let dict = Dictionary::from_quickfix_spec(spec).unwrap();
+        assert!(Dictionary::diff(&dict, &Dictionary::fix44()).is_empty());
+        assert_eq!(dict.to_bytes().len(), Dictionary::fix44().to_bytes().len());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn binary_format_roundtrips() {
+        for dict in Dictionary::all().iter() {
+            let decoded = Dictionary::from_bytes(&dict.to_bytes()).unwrap();
+            assert_eq!(decoded.get_version(), dict.get_version());
+            assert!(Dictionary::diff(dict, &decoded).is_empty());
+            assert_eq!(decoded.to_bytes().len(), dict.to_bytes().len());
+        }
+        let spec = include_str!("test_data/orchestra/repository.xml");
+        let dict = Dictionary::from_orchestra_spec(spec).unwrap();
+        let decoded = Dictionary::from_bytes(&dict.to_bytes()).unwrap();
+        assert!(decoded
+            .message_by_msgtype_and_scenario("D", "MarketOrder")
+            .is_some());
+    }
+
+    #[test]
+    fn binary_format_rejects_malformed_input() {
+        let bytes = Dictionary::fix44().to_bytes();
+        assert!(Dictionary::from_bytes(&[]).is_err());
+        assert!(Dictionary::from_bytes(&bytes[1..]).is_err());
+        assert!(Dictionary::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert!(Dictionary::from_bytes(&trailing).is_err());
+        let mut future = bytes;
+        future[9] = u8::MAX;
+        assert!(matches!(
+            Dictionary::from_bytes(&future),
+            Err(ParseDictionaryError::InvalidData(_))
+        ));
+    }
+
+    #[test]
+    fn diff_of_same_dictionary_is_empty() {
+        for dict in Dictionary::all().iter() {
+            assert!(Dictionary::diff(dict, dict).is_empty());
+        }
+        let dict = Dictionary::fix44();
+        let diff = Dictionary::diff(&dict, &Dictionary::empty());
+        assert_eq!(diff.removed_fields().len(), dict.iter_fields().count());
+        assert!(diff.removed_messages().contains(&"AE".to_string()));
+        let reverse = Dictionary::diff(&Dictionary::empty(), &dict);
+        assert_eq!(diff.removed_fields(), reverse.added_fields());
+        assert_eq!(diff.removed_messages(), reverse.added_messages());
+    }
+
+    #[test]
+    fn merge_layers_extension_over_base() {
+        let mut builder = Dictionary::fix44().to_builder();
+        builder.set_version("FIX.4.4-VENUE");
+        builder
+            .add_custom_field(5001, "VenueOrderTag", FixDatatype::String)
+            .unwrap();
+        builder.add_field_to_message("D", 5001, true).unwrap();
+        builder
+            .add_enum_value(40, "Z", "VenueSpecialOrder")
+            .unwrap();
+        let venue = builder.build();
+
+        let diff = Dictionary::diff(&Dictionary::fix44(), &venue);
+        assert_eq!(diff.added_fields(), &[5001]);
+        assert_eq!(diff.changed_fields(), &[40]);
+        assert_eq!(diff.changed_messages(), &["D".to_string()]);
+        assert!(diff.removed_fields().is_empty() && diff.removed_messages().is_empty());
+
+        // Internal IDs of `base` and `venue` don't match.
+        let mut builder = DictionaryBuilder::new("FIX.4.4");
+        builder
+            .add_custom_field(6000, "BaseOnlyTag", FixDatatype::Char)
+            .unwrap();
+        let base = builder.build();
+        let merged = Dictionary::merge(&base, &venue);
+        assert_eq!(merged.get_version(), "FIX.4.4");
+        assert!(merged
+            .message_by_msgtype("D")
+            .unwrap()
+            .layout()
+            .any(|item| item.required() && item.tag_text() == "VenueOrderTag"));
+        let diff = Dictionary::diff(&venue, &merged);
+        assert_eq!(diff.added_fields(), &[6000]);
+        assert!(diff.changed_fields().is_empty());
+        assert!(diff.added_messages().is_empty() && diff.changed_messages().is_empty());
+
+        // Merging again is a no-op.
+        let merged_twice = Dictionary::merge(&merged, &venue);
+        assert!(Dictionary::diff(&merged, &merged_twice).is_empty());
+    }
 }
@@ -6,4 +6,7 @@ pub mod codegen;
 pub mod dict;
 
 /// Type alias for FIX tags: 16-bit unsigned integers, strictly positive.
-pub type TagU16 = std::num::NonZeroU16;
+///
+/// This covers all tags in use, including user-defined ranges (e.g.
+/// 20000–39999) up to 65535.
+pub type TagU16 = core::num::NonZeroU16;
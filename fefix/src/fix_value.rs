@@ -1,12 +1,21 @@
+use crate::digits;
 use crate::fix_values::*;
 use crate::{Buffer, TagU16};
-use std::convert::TryInto;
-use std::string::ToString;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::convert::{TryFrom, TryInto};
 
 const ERR_BOOL_LENGTH: &str = "Invalid length; a boolean is Y or N (1 char).";
 const ERR_BOOL_CHAR: &str = "Invalid character for boolean. Only Y and N are valid.";
 const ERR_UTF8: &str = "Invalid byte sequence; expected UTF-8 valid bytes.";
 const ERR_INT_INVALID: &str = "Invalid integer digits.";
+const ERR_FLOAT_INVALID: &str = "Invalid float digits.";
+#[cfg(any(feature = "utils-chrono", feature = "utils-time"))]
+const ERR_TIMESTAMP: &str = "Invalid timestamp format.";
+#[cfg(any(feature = "utils-chrono", feature = "utils-time"))]
+const ERR_TIME: &str = "Invalid time format.";
+#[cfg(any(feature = "utils-chrono", feature = "utils-time"))]
+const ERR_TIME_RANGE: &str = "Invalid time range.";
 
 /// Provides (de)serialization logic for a Rust type as FIX field values.
 pub trait FixValue<'a>
@@ -66,60 +75,67 @@ where
 #[derive(Debug, Copy, Clone, Default)]
 pub struct ZeroPadding(pub usize);
 
-/// Specifies whether a timestamp should have millisecond or second precision.
+/// The number of fractional second digits of `UTCTimestamp` and
+/// `UTCTimeOnly` field values.
 ///
-/// Enabled by [`Default`].
-#[derive(Debug, Copy, Clone)]
-pub struct WithMilliseconds(pub bool);
-
-impl Default for WithMilliseconds {
-    fn default() -> Self {
-        Self(true)
-    }
+/// [`TimePrecision::Millis`] by [`Default`].
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum TimePrecision {
+    /// No fractional seconds, e.g. `12:30:00`.
+    Seconds,
+    /// Three digits, e.g. `12:30:00.123`.
+    #[default]
+    Millis,
+    /// Six digits, e.g. `12:30:00.123456`.
+    Micros,
+    /// Nine digits, e.g. `12:30:00.123456789`.
+    Nanos,
 }
 
 #[cfg(feature = "utils-chrono")]
 #[cfg_attr(doc_cfg, doc(cfg(feature = "utils-chrono")))]
 impl<'a> FixValue<'a> for chrono::DateTime<chrono::Utc> {
     type Error = &'static str;
-    type SerializeSettings = WithMilliseconds;
+    type SerializeSettings = TimePrecision;
 
     #[inline]
-    fn serialize<B>(&self, buffer: &mut B) -> usize
+    fn serialize_with<B>(&self, buffer: &mut B, precision: Self::SerializeSettings) -> usize
     where
         B: Buffer,
     {
-        // Serialize with milliseconds by default.
-        self.serialize_with(buffer, WithMilliseconds(true))
+        self.naive_utc().serialize_with(buffer, precision)
     }
 
     #[inline]
-    fn serialize_with<B>(&self, buffer: &mut B, settings: Self::SerializeSettings) -> usize
+    fn deserialize(data: &'a [u8]) -> Result<Self, Self::Error> {
+        let naive = chrono::NaiveDateTime::deserialize(data)?;
+        Ok(chrono::DateTime::from_utc(naive, chrono::Utc))
+    }
+}
+
+#[cfg(feature = "utils-chrono")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "utils-chrono")))]
+impl<'a> FixValue<'a> for chrono::NaiveDateTime {
+    type Error = &'static str;
+    type SerializeSettings = TimePrecision;
+
+    #[inline]
+    fn serialize_with<B>(&self, buffer: &mut B, precision: Self::SerializeSettings) -> usize
     where
         B: Buffer,
     {
-        use chrono::{Datelike, Timelike};
-        (self.year() as u32).serialize_with(buffer, ZeroPadding(4));
-        (self.month() as u32).serialize_with(buffer, ZeroPadding(2));
-        (self.day() as u32).serialize_with(buffer, ZeroPadding(2));
-        buffer.extend_from_slice(b"-");
-        (self.hour() as u32).serialize_with(buffer, ZeroPadding(2));
-        buffer.extend_from_slice(b":");
-        (self.minute() as u32).serialize_with(buffer, ZeroPadding(2));
-        buffer.extend_from_slice(b":");
-        (self.second() as u32).serialize_with(buffer, ZeroPadding(2));
-        if settings.0 {
-            buffer.extend_from_slice(b".");
-            (self.nanosecond() / 10E6 as u32).serialize_with(buffer, ZeroPadding(3));
-            21
-        } else {
-            17
-        }
+        self.date().serialize(buffer)
+            + b"-".serialize(buffer)
+            + self.time().serialize_with(buffer, precision)
     }
 
     #[inline]
-    fn deserialize(_data: &'a [u8]) -> Result<Self, Self::Error> {
-        Err("TODO")
+    fn deserialize(data: &'a [u8]) -> Result<Self, Self::Error> {
+        let (date, time) = split_timestamp(data).ok_or(ERR_TIMESTAMP)?;
+        Ok(chrono::NaiveDateTime::new(
+            chrono::NaiveDate::deserialize(date)?,
+            chrono::NaiveTime::deserialize(time)?,
+        ))
     }
 }
 
@@ -135,10 +151,7 @@ impl<'a> FixValue<'a> for chrono::NaiveDate {
         B: Buffer,
     {
         use chrono::Datelike;
-        (self.year() as u32).serialize_with(buffer, ZeroPadding(4));
-        (self.month() as u32).serialize_with(buffer, ZeroPadding(2));
-        (self.day() as u32).serialize_with(buffer, ZeroPadding(2));
-        8
+        serialize_date(buffer, self.year(), self.month(), self.day())
     }
 
     #[inline]
@@ -154,6 +167,141 @@ impl<'a> FixValue<'a> for chrono::NaiveDate {
     }
 }
 
+#[cfg(feature = "utils-chrono")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "utils-chrono")))]
+impl<'a> FixValue<'a> for chrono::NaiveTime {
+    type Error = &'static str;
+    type SerializeSettings = TimePrecision;
+
+    #[inline]
+    fn serialize_with<B>(&self, buffer: &mut B, precision: Self::SerializeSettings) -> usize
+    where
+        B: Buffer,
+    {
+        use chrono::Timelike;
+        // `chrono` represents leap seconds as nanoseconds overflowing into the
+        // next second.
+        let (second, nanos) = if self.nanosecond() >= 1_000_000_000 {
+            (60, self.nanosecond() - 1_000_000_000)
+        } else {
+            (self.second(), self.nanosecond())
+        };
+        serialize_time(buffer, self.hour(), self.minute(), second, nanos, precision)
+    }
+
+    #[inline]
+    fn deserialize(data: &'a [u8]) -> Result<Self, Self::Error> {
+        let (hour, minute, second, nanos) = parse_time(data).ok_or(ERR_TIME)?;
+        if second == 60 {
+            chrono::NaiveTime::from_hms_nano_opt(hour, minute, 59, nanos + 1_000_000_000)
+        } else {
+            chrono::NaiveTime::from_hms_nano_opt(hour, minute, second, nanos)
+        }
+        .ok_or(ERR_TIME_RANGE)
+    }
+}
+
+#[cfg(feature = "utils-time")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "utils-time")))]
+impl<'a> FixValue<'a> for time::OffsetDateTime {
+    type Error = &'static str;
+    type SerializeSettings = TimePrecision;
+
+    /// Writes `self` to `buffer` after converting it to UTC.
+    #[inline]
+    fn serialize_with<B>(&self, buffer: &mut B, precision: Self::SerializeSettings) -> usize
+    where
+        B: Buffer,
+    {
+        let utc = self.to_offset(time::UtcOffset::UTC);
+        time::PrimitiveDateTime::new(utc.date(), utc.time()).serialize_with(buffer, precision)
+    }
+
+    #[inline]
+    fn deserialize(data: &'a [u8]) -> Result<Self, Self::Error> {
+        Ok(time::PrimitiveDateTime::deserialize(data)?.assume_utc())
+    }
+}
+
+#[cfg(feature = "utils-time")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "utils-time")))]
+impl<'a> FixValue<'a> for time::PrimitiveDateTime {
+    type Error = &'static str;
+    type SerializeSettings = TimePrecision;
+
+    #[inline]
+    fn serialize_with<B>(&self, buffer: &mut B, precision: Self::SerializeSettings) -> usize
+    where
+        B: Buffer,
+    {
+        self.date().serialize(buffer)
+            + b"-".serialize(buffer)
+            + self.time().serialize_with(buffer, precision)
+    }
+
+    #[inline]
+    fn deserialize(data: &'a [u8]) -> Result<Self, Self::Error> {
+        let (date, time) = split_timestamp(data).ok_or(ERR_TIMESTAMP)?;
+        Ok(time::PrimitiveDateTime::new(
+            time::Date::deserialize(date)?,
+            time::Time::deserialize(time)?,
+        ))
+    }
+}
+
+#[cfg(feature = "utils-time")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "utils-time")))]
+impl<'a> FixValue<'a> for time::Date {
+    type Error = &'static str;
+    type SerializeSettings = ();
+
+    #[inline]
+    fn serialize_with<B>(&self, buffer: &mut B, _settings: ()) -> usize
+    where
+        B: Buffer,
+    {
+        serialize_date(buffer, self.year(), self.month() as u32, self.day() as u32)
+    }
+
+    #[inline]
+    fn deserialize(data: &'a [u8]) -> Result<Self, Self::Error> {
+        let date = Date::deserialize(data).map_err(|_| "Invalid date format.")?;
+        time::Date::try_from_ymd(date.year() as i32, date.month() as u8, date.day() as u8)
+            .map_err(|_| "Invalid date range.")
+    }
+}
+
+#[cfg(feature = "utils-time")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "utils-time")))]
+impl<'a> FixValue<'a> for time::Time {
+    type Error = &'static str;
+    type SerializeSettings = TimePrecision;
+
+    #[inline]
+    fn serialize_with<B>(&self, buffer: &mut B, precision: Self::SerializeSettings) -> usize
+    where
+        B: Buffer,
+    {
+        serialize_time(
+            buffer,
+            self.hour() as u32,
+            self.minute() as u32,
+            self.second() as u32,
+            self.nanosecond(),
+            precision,
+        )
+    }
+
+    /// Parses a `UTCTimeOnly` value. Leap seconds are not supported by the
+    /// `time` crate and result in an error.
+    #[inline]
+    fn deserialize(data: &'a [u8]) -> Result<Self, Self::Error> {
+        let (hour, minute, second, nanos) = parse_time(data).ok_or(ERR_TIME)?;
+        time::Time::try_from_hms_nano(hour as u8, minute as u8, second as u8, nanos)
+            .map_err(|_| ERR_TIME_RANGE)
+    }
+}
+
 #[cfg(feature = "utils-rust-decimal")]
 #[cfg_attr(doc_cfg, doc(cfg(feature = "utils-rust-decimal")))]
 impl<'a> FixValue<'a> for rust_decimal::Decimal {
@@ -173,9 +321,15 @@ impl<'a> FixValue<'a> for rust_decimal::Decimal {
 
     #[inline]
     fn deserialize(data: &'a [u8]) -> Result<Self, Self::Error> {
+        validate_float(data)?;
+        Self::deserialize_lossy(data)
+    }
+
+    #[inline]
+    fn deserialize_lossy(data: &'a [u8]) -> Result<Self, Self::Error> {
         const ERR_DECIMAL_INVALID: &str = "Invalid decimal number.";
-        use std::str::FromStr;
-        let s = std::str::from_utf8(data).map_err(|_| ERR_UTF8)?;
+        use core::str::FromStr;
+        let s = core::str::from_utf8(data).map_err(|_| ERR_UTF8)?;
         rust_decimal::Decimal::from_str(s).map_err(|_| ERR_DECIMAL_INVALID)
     }
 }
@@ -198,9 +352,12 @@ impl<'a> FixValue<'a> for decimal::d128 {
     }
 
     fn deserialize(data: &'a [u8]) -> Result<Self, Self::Error> {
-        use std::str::FromStr;
+        use core::str::FromStr;
+        if validate_float(data).is_err() {
+            return Err(decimal::Status::CONVERSION_SYNTAX);
+        }
         decimal::d128::set_status(decimal::Status::empty());
-        let s = std::str::from_utf8(data).unwrap_or("invalid UTF-8");
+        let s = core::str::from_utf8(data).unwrap_or("invalid UTF-8");
         let number =
             decimal::d128::from_str(s).expect("decimal::d128 should always parse without errors");
         let status = decimal::d128::get_status();
@@ -250,7 +407,7 @@ impl<'a> FixValue<'a> for bool {
 }
 
 impl<'a> FixValue<'a> for &'a str {
-    type Error = std::str::Utf8Error;
+    type Error = core::str::Utf8Error;
     type SerializeSettings = ();
 
     #[inline]
@@ -264,7 +421,26 @@ impl<'a> FixValue<'a> for &'a str {
 
     #[inline]
     fn deserialize(data: &'a [u8]) -> Result<Self, Self::Error> {
-        std::str::from_utf8(data)
+        core::str::from_utf8(data)
+    }
+}
+
+impl<'a> FixValue<'a> for String {
+    type Error = core::str::Utf8Error;
+    type SerializeSettings = ();
+
+    #[inline]
+    fn serialize_with<B>(&self, buffer: &mut B, _settings: ()) -> usize
+    where
+        B: Buffer,
+    {
+        buffer.extend_from_slice(self.as_bytes());
+        self.len()
+    }
+
+    #[inline]
+    fn deserialize(data: &'a [u8]) -> Result<Self, Self::Error> {
+        core::str::from_utf8(data).map(str::to_string)
     }
 }
 
@@ -359,7 +535,7 @@ impl<'a> FixValue<'a> for TagU16 {
 
     #[inline]
     fn deserialize(data: &'a [u8]) -> Result<Self, Self::Error> {
-        let s = std::str::from_utf8(data).map_err(|_| ERR_UTF8)?;
+        let s = core::str::from_utf8(data).map_err(|_| ERR_UTF8)?;
         s.parse().map_err(|_| ERR_INT_INVALID)
     }
 
@@ -403,8 +579,9 @@ impl<'a> FixValue<'a> for u32 {
 
     #[inline]
     fn deserialize(data: &'a [u8]) -> Result<Self, Self::Error> {
-        let s = std::str::from_utf8(data).map_err(|_| ERR_UTF8)?;
-        s.parse().map_err(|_| ERR_INT_INVALID)
+        digits::parse_u64(data)
+            .and_then(|n| u32::try_from(n).ok())
+            .ok_or(ERR_INT_INVALID)
     }
 
     #[inline]
@@ -436,8 +613,9 @@ impl<'a> FixValue<'a> for i32 {
 
     #[inline]
     fn deserialize(data: &'a [u8]) -> Result<Self, Self::Error> {
-        let s = std::str::from_utf8(data).map_err(|_| ERR_UTF8)?;
-        s.parse().map_err(|_| ERR_INT_INVALID)
+        digits::parse_i64(data)
+            .and_then(|n| i32::try_from(n).ok())
+            .ok_or(ERR_INT_INVALID)
     }
 
     #[inline]
@@ -470,8 +648,7 @@ impl<'a> FixValue<'a> for u64 {
 
     #[inline]
     fn deserialize(data: &'a [u8]) -> Result<Self, Self::Error> {
-        let s = std::str::from_utf8(data).map_err(|_| ERR_UTF8)?;
-        s.parse().map_err(|_| ERR_INT_INVALID)
+        digits::parse_u64(data).ok_or(ERR_INT_INVALID)
     }
 
     #[inline]
@@ -503,8 +680,7 @@ impl<'a> FixValue<'a> for i64 {
 
     #[inline]
     fn deserialize(data: &'a [u8]) -> Result<Self, Self::Error> {
-        let s = std::str::from_utf8(data).map_err(|_| ERR_UTF8)?;
-        s.parse().map_err(|_| ERR_INT_INVALID)
+        digits::parse_i64(data).ok_or(ERR_INT_INVALID)
     }
 
     #[inline]
@@ -537,8 +713,9 @@ impl<'a> FixValue<'a> for usize {
 
     #[inline]
     fn deserialize(data: &'a [u8]) -> Result<Self, Self::Error> {
-        let s = std::str::from_utf8(data).map_err(|_| ERR_UTF8)?;
-        s.parse().map_err(|_| ERR_INT_INVALID)
+        digits::parse_u64(data)
+            .and_then(|n| usize::try_from(n).ok())
+            .ok_or(ERR_INT_INVALID)
     }
 
     #[inline]
@@ -554,6 +731,113 @@ impl<'a> FixValue<'a> for usize {
     }
 }
 
+/// Lossy by nature; prefer [`Decimal`] for exact prices and quantities.
+/// Serialization never uses exponent notation, which FIX doesn't allow, and
+/// non-finite values have no valid representation.
+impl<'a> FixValue<'a> for f64 {
+    type Error = &'static str;
+    type SerializeSettings = ();
+
+    #[inline]
+    fn serialize_with<B>(&self, buffer: &mut B, _settings: ()) -> usize
+    where
+        B: Buffer,
+    {
+        let s = ToString::to_string(self);
+        buffer.extend_from_slice(s.as_bytes());
+        s.len()
+    }
+
+    #[inline]
+    fn deserialize(data: &'a [u8]) -> Result<Self, Self::Error> {
+        digits::parse_f64(data).ok_or(ERR_FLOAT_INVALID)
+    }
+}
+
+#[cfg(any(feature = "utils-chrono", feature = "utils-time"))]
+fn serialize_date<B>(buffer: &mut B, year: i32, month: u32, day: u32) -> usize
+where
+    B: Buffer,
+{
+    (year as u32).serialize_with(buffer, ZeroPadding(4))
+        + month.serialize_with(buffer, ZeroPadding(2))
+        + day.serialize_with(buffer, ZeroPadding(2))
+}
+
+#[cfg(any(feature = "utils-chrono", feature = "utils-time"))]
+fn serialize_time<B>(
+    buffer: &mut B,
+    hour: u32,
+    minute: u32,
+    second: u32,
+    nanos: u32,
+    precision: TimePrecision,
+) -> usize
+where
+    B: Buffer,
+{
+    hour.serialize_with(buffer, ZeroPadding(2));
+    buffer.extend_from_slice(b":");
+    minute.serialize_with(buffer, ZeroPadding(2));
+    buffer.extend_from_slice(b":");
+    second.serialize_with(buffer, ZeroPadding(2));
+    let (divisor, digits) = match precision {
+        TimePrecision::Seconds => return 8,
+        TimePrecision::Millis => (1_000_000, 3),
+        TimePrecision::Micros => (1_000, 6),
+        TimePrecision::Nanos => (1, 9),
+    };
+    buffer.extend_from_slice(b".");
+    (nanos / divisor).serialize_with(buffer, ZeroPadding(digits));
+    9 + digits
+}
+
+/// Splits a `UTCTimestamp` value into its date and time parts.
+#[cfg(any(feature = "utils-chrono", feature = "utils-time"))]
+fn split_timestamp(data: &[u8]) -> Option<(&[u8], &[u8])> {
+    if data.len() >= 17 && data[8] == b'-' {
+        Some((&data[..8], &data[9..]))
+    } else {
+        None
+    }
+}
+
+/// Parses a `UTCTimeOnly` value into hours, minutes, seconds, and
+/// nanoseconds, without any range checking. Fractional seconds can have
+/// millisecond, microsecond, nanosecond, or picosecond precision; picoseconds
+/// are truncated.
+#[cfg(any(feature = "utils-chrono", feature = "utils-time"))]
+fn parse_time(data: &[u8]) -> Option<(u32, u32, u32, u32)> {
+    if data.len() < 8 || data[2] != b':' || data[5] != b':' {
+        return None;
+    }
+    let hour = parse_digits(&data[0..2])?;
+    let minute = parse_digits(&data[3..5])?;
+    let second = parse_digits(&data[6..8])?;
+    let nanos = match &data[8..] {
+        [] => 0,
+        [b'.', fraction @ ..] if matches!(fraction.len(), 3 | 6 | 9 | 12) => {
+            let digits = &fraction[..fraction.len().min(9)];
+            parse_digits(&fraction[digits.len()..])?;
+            parse_digits(digits)? * 10u32.pow(9 - digits.len() as u32)
+        }
+        _ => return None,
+    };
+    Some((hour, minute, second, nanos))
+}
+
+#[cfg(any(feature = "utils-chrono", feature = "utils-time"))]
+fn parse_digits(data: &[u8]) -> Option<u32> {
+    let mut n = 0u32;
+    for byte in data {
+        if !byte.is_ascii_digit() {
+            return None;
+        }
+        n = n * 10 + (byte - b'0') as u32;
+    }
+    Some(n)
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -585,6 +869,20 @@ mod test {
         bytes == buffer.as_slice() && len == bytes.len()
     }
 
+    #[quickcheck]
+    fn f64_roundtrip(n: f64) -> bool {
+        !n.is_finite() || f64::deserialize(FixValue::to_string(&n).as_bytes()) == Ok(n)
+    }
+
+    #[test]
+    fn integers_reject_what_std_rejects() {
+        assert_eq!(u32::deserialize(b"4294967296"), Err(ERR_INT_INVALID));
+        assert_eq!(i32::deserialize(b"-2147483648"), Ok(i32::MIN));
+        assert_eq!(u64::deserialize(b"+42"), Ok(42));
+        assert_eq!(i64::deserialize(b"-"), Err(ERR_INT_INVALID));
+        assert_eq!(usize::deserialize(b"1 "), Err(ERR_INT_INVALID));
+    }
+
     #[test]
     fn serialize_country() {
         let mut buffer = Vec::new();
@@ -598,4 +896,96 @@ mod test {
         assert_eq!(b"USD".serialize(&mut buffer), 3);
         assert_eq!(&buffer[..], b"USD" as &[u8]);
     }
+
+    #[test]
+    #[cfg(feature = "utils-rust-decimal")]
+    fn rust_decimal_round_trip_preserves_scale() {
+        for bytes in &[&b"1.50"[..], b"-0.0125", b"1200", b"0.000"] {
+            let decimal = <rust_decimal::Decimal as FixValue>::deserialize(bytes).unwrap();
+            assert_eq!(&decimal.to_bytes()[..], *bytes);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "utils-rust-decimal")]
+    fn rust_decimal_rejects_non_fix_syntax() {
+        for bytes in &[&b"+1"[..], b"1e5", b"1_000", b""] {
+            assert!(<rust_decimal::Decimal as FixValue>::deserialize(bytes).is_err());
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "utils-chrono")]
+    fn chrono_timestamps_with_any_precision() {
+        use chrono::{NaiveDate, NaiveDateTime, Timelike};
+        let dt = NaiveDate::from_ymd(2021, 3, 4).and_hms_nano(5, 6, 7, 123_456_789);
+        let cases = &[
+            (TimePrecision::Seconds, &b"20210304-05:06:07"[..], 0),
+            (TimePrecision::Millis, b"20210304-05:06:07.123", 123_000_000),
+            (
+                TimePrecision::Micros,
+                b"20210304-05:06:07.123456",
+                123_456_000,
+            ),
+            (
+                TimePrecision::Nanos,
+                b"20210304-05:06:07.123456789",
+                123_456_789,
+            ),
+        ];
+        for (precision, bytes, nanos) in cases {
+            let mut buffer = Vec::new();
+            assert_eq!(dt.serialize_with(&mut buffer, *precision), bytes.len());
+            assert_eq!(&buffer[..], *bytes);
+            let parsed = NaiveDateTime::deserialize(bytes).unwrap();
+            assert_eq!(parsed, dt.with_nanosecond(*nanos).unwrap());
+        }
+        let utc = chrono::DateTime::<chrono::Utc>::from_utc(dt, chrono::Utc);
+        assert_eq!(utc.to_bytes(), b"20210304-05:06:07.123");
+    }
+
+    #[test]
+    #[cfg(feature = "utils-chrono")]
+    fn chrono_time_only_and_leap_seconds() {
+        let time = chrono::NaiveTime::deserialize(b"23:59:60.500").unwrap();
+        assert_eq!(time.to_bytes(), b"23:59:60.500");
+        let time = chrono::NaiveTime::deserialize(b"12:00:00.123456789012").unwrap();
+        assert_eq!(
+            time.serialize_with(&mut Vec::new(), TimePrecision::Nanos),
+            18
+        );
+        for invalid in &[
+            &b"24:00:00"[..],
+            b"12:00",
+            b"12:00:00.1",
+            b"12:00:00.12a",
+            b"12-00-00",
+        ] {
+            assert!(chrono::NaiveTime::deserialize(invalid).is_err());
+        }
+        assert!(chrono::NaiveDateTime::deserialize(b"20210230-00:00:00").is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "utils-time")]
+    fn time_crate_interop() {
+        let dt = time::PrimitiveDateTime::deserialize(b"20210304-05:06:07.123456").unwrap();
+        assert_eq!(dt.nanosecond(), 123_456_000);
+        let mut buffer = Vec::new();
+        dt.serialize_with(&mut buffer, TimePrecision::Micros);
+        assert_eq!(&buffer[..], b"20210304-05:06:07.123456");
+        let offset = dt
+            .assume_offset(time::UtcOffset::hours(2))
+            .serialize_with(&mut Vec::new(), TimePrecision::Seconds);
+        assert_eq!(offset, 17);
+        let utc = time::OffsetDateTime::deserialize(b"20210304-05:06:07").unwrap();
+        assert_eq!(utc.offset(), time::UtcOffset::UTC);
+        let shifted = utc.to_offset(time::UtcOffset::hours(-8));
+        assert_eq!(shifted.to_bytes(), b"20210304-05:06:07.000");
+        assert_eq!(
+            time::Date::deserialize(b"20210304").unwrap().to_bytes(),
+            b"20210304"
+        );
+        assert!(time::Time::deserialize(b"23:59:60").is_err());
+    }
 }
@@ -1,6 +1,6 @@
 use crate::Buffer;
 use crate::FixValue;
-use std::convert::TryInto;
+use core::convert::TryInto;
 
 const LEN_IN_BYTES: usize = 3;
 
@@ -18,11 +18,7 @@ impl CheckSum {
     /// Returns the [`CheckSum`] of `data`. The result is always the sum of each
     /// byte in `data` wrapped at 0xFF, as per the FIX specification.
     pub fn compute(data: &[u8]) -> Self {
-        let mut value = 0u8;
-        for byte in data {
-            value = value.wrapping_add(*byte);
-        }
-        Self(value)
+        Self(crate::scan::checksum(data))
     }
 }
 
@@ -1,6 +1,6 @@
 use crate::Buffer;
 use crate::FixValue;
-use std::convert::{TryFrom, TryInto};
+use core::convert::{TryFrom, TryInto};
 
 const LEN_IN_BYTES: usize = 8;
 
@@ -0,0 +1,82 @@
+use super::FixValue;
+use crate::Buffer;
+
+const ERR_INVALID: &str = "Invalid day of month; expected an integer from 1 to 31.";
+
+/// Representation for `DayOfMonth`, i.e. an integer from 1 to 31.
+///
+/// # Examples
+///
+/// ```
+/// use fefix::FixValue;
+/// use fefix::fix_values::DayOfMonth;
+///
+/// let day = DayOfMonth::deserialize(b"15").unwrap();
+/// assert_eq!(day.get(), 15);
+/// assert!(DayOfMonth::deserialize(b"0").is_err());
+/// assert!(DayOfMonth::deserialize(b"32").is_err());
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DayOfMonth {
+    day: u8,
+}
+
+impl DayOfMonth {
+    /// Creates a new [`DayOfMonth`]. Returns [`None`] if `day` is not within 1
+    /// and 31 (including).
+    pub fn new(day: u32) -> Option<Self> {
+        if (1..=31).contains(&day) {
+            Some(Self { day: day as u8 })
+        } else {
+            None
+        }
+    }
+
+    /// Returns the day of the month of `self`, from 1 to 31.
+    pub fn get(&self) -> u32 {
+        u32::from(self.day)
+    }
+}
+
+impl<'a> FixValue<'a> for DayOfMonth {
+    type Error = &'static str;
+    type SerializeSettings = ();
+
+    fn serialize_with<B>(&self, buffer: &mut B, _settings: ()) -> usize
+    where
+        B: Buffer,
+    {
+        self.get().serialize(buffer)
+    }
+
+    fn deserialize(data: &'a [u8]) -> Result<Self, Self::Error> {
+        match data {
+            [a] if a.is_ascii_digit() => Self::new(u32::from(a - b'0')),
+            [a, b] if a.is_ascii_digit() && b.is_ascii_digit() => {
+                Self::new(u32::from(a - b'0') * 10 + u32::from(b - b'0'))
+            }
+            _ => None,
+        }
+        .ok_or(ERR_INVALID)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn every_day_roundtrips() {
+        for n in 1..=31 {
+            let day = DayOfMonth::new(n).unwrap();
+            assert_eq!(DayOfMonth::deserialize(&day.to_bytes()), Ok(day));
+        }
+    }
+
+    #[test]
+    fn non_digits_are_invalid() {
+        for s in ["", "+1", "1a", "001", " 1"] {
+            assert!(DayOfMonth::deserialize(s.as_bytes()).is_err(), "{}", s);
+        }
+    }
+}
@@ -0,0 +1,317 @@
+use crate::digits;
+use crate::Buffer;
+use crate::FixValue;
+use alloc::string::ToString;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::fmt;
+
+const MAX_SCALE: u32 = 18;
+
+const ERR_EMPTY: &str = "Expected at least one digit, found none.";
+const ERR_INVALID: &str = "Invalid character for decimal number.";
+const ERR_OVERFLOW: &str = "Decimal number out of range.";
+
+/// An exact, fixed-point decimal number for `float` -like FIX fields (e.g.
+/// `Price`, `Qty`, `Amt`).
+///
+/// A [`Decimal`] is stored as an integer `mantissa` and a `scale`, i.e. the
+/// number of digits after the decimal point, so that its value is
+/// `mantissa * 10^-scale`. Trailing zeros are preserved: `1.50` deserializes
+/// with a scale of 2 and it serializes back to `1.50`. As a consequence,
+/// equality is structural, i.e. `1.5 != 1.50`.
+///
+/// # Examples
+///
+/// ```
+/// use fefix::FixValue;
+/// use fefix::fix_values::Decimal;
+///
+/// let price = Decimal::deserialize(b"-0.0125").unwrap();
+/// assert_eq!(price.mantissa(), -125);
+/// assert_eq!(price.scale(), 4);
+/// assert_eq!(price.to_bytes(), b"-0.0125");
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Decimal {
+    mantissa: i64,
+    scale: u32,
+}
+
+impl Decimal {
+    /// The number zero, with no decimal digits.
+    pub const ZERO: Self = Self {
+        mantissa: 0,
+        scale: 0,
+    };
+
+    /// Creates a new [`Decimal`] with value `mantissa * 10^-scale`.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `scale` is greater than 18.
+    pub fn new(mantissa: i64, scale: u32) -> Self {
+        assert!(scale <= MAX_SCALE, "Decimal scale must be at most 18");
+        Self { mantissa, scale }
+    }
+
+    /// Returns the integer mantissa of `self`.
+    pub fn mantissa(&self) -> i64 {
+        self.mantissa
+    }
+
+    /// Returns the number of digits after the decimal point of `self`.
+    pub fn scale(&self) -> u32 {
+        self.scale
+    }
+
+    /// Returns the closest [`f64`] to `self`. This conversion is lossy.
+    pub fn to_f64(&self) -> f64 {
+        // `f64::powi` is not available without `std`.
+        let divisor = (0..self.scale).fold(1f64, |acc, _| acc * 10.0);
+        self.mantissa as f64 / divisor
+    }
+
+    /// Returns `self + rhs` with the larger scale of the two, or [`None`] on
+    /// overflow.
+    ///
+    /// ```
+    /// use fefix::fix_values::Decimal;
+    ///
+    /// let sum = Decimal::new(15, 1).checked_add(Decimal::new(25, 2));
+    /// assert_eq!(sum, Some(Decimal::new(175, 2)));
+    /// ```
+    pub fn checked_add(self, rhs: Self) -> Option<Self> {
+        let scale = self.scale.max(rhs.scale);
+        let mantissa = self
+            .mantissa_at(scale)?
+            .checked_add(rhs.mantissa_at(scale)?)?;
+        Some(Self { mantissa, scale })
+    }
+
+    /// Returns `self - rhs` with the larger scale of the two, or [`None`] on
+    /// overflow.
+    pub fn checked_sub(self, rhs: Self) -> Option<Self> {
+        let scale = self.scale.max(rhs.scale);
+        let mantissa = self
+            .mantissa_at(scale)?
+            .checked_sub(rhs.mantissa_at(scale)?)?;
+        Some(Self { mantissa, scale })
+    }
+
+    /// Compares the values of `self` and `other`, regardless of their scales;
+    /// unlike [`PartialEq`], `1.5` and `1.50` are equal.
+    ///
+    /// ```
+    /// use fefix::fix_values::Decimal;
+    /// use std::cmp::Ordering;
+    ///
+    /// assert_eq!(Decimal::new(15, 1).value_cmp(&Decimal::new(150, 2)), Ordering::Equal);
+    /// assert_eq!(Decimal::new(-1, 0).value_cmp(&Decimal::ZERO), Ordering::Less);
+    /// ```
+    pub fn value_cmp(&self, other: &Self) -> Ordering {
+        let scale = self.scale.max(other.scale);
+        // Scales are at most 18, so this can't overflow.
+        let widen = |decimal: &Self| decimal.mantissa as i128 * 10i128.pow(scale - decimal.scale);
+        widen(self).cmp(&widen(other))
+    }
+
+    fn mantissa_at(self, scale: u32) -> Option<i64> {
+        self.mantissa
+            .checked_mul(10i64.checked_pow(scale - self.scale)?)
+    }
+}
+
+impl Default for Decimal {
+    fn default() -> Self {
+        Self::ZERO
+    }
+}
+
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut buffer = Vec::new();
+        self.serialize(&mut buffer);
+        // Only ASCII characters.
+        f.write_str(core::str::from_utf8(&buffer).unwrap())
+    }
+}
+
+impl<'a> FixValue<'a> for Decimal {
+    type Error = &'static str;
+    type SerializeSettings = ();
+
+    fn serialize_with<B>(&self, buffer: &mut B, _settings: ()) -> usize
+    where
+        B: Buffer,
+    {
+        let mut digits = ToString::to_string(&self.mantissa.unsigned_abs()).into_bytes();
+        let scale = self.scale as usize;
+        if digits.len() <= scale {
+            let mut padded = vec![b'0'; scale + 1 - digits.len()];
+            padded.extend_from_slice(&digits);
+            digits = padded;
+        }
+        let mut len = 0;
+        if self.mantissa < 0 {
+            buffer.extend_from_slice(b"-");
+            len += 1;
+        }
+        let (integer, fraction) = digits.split_at(digits.len() - scale);
+        buffer.extend_from_slice(integer);
+        len += integer.len();
+        if !fraction.is_empty() {
+            buffer.extend_from_slice(b".");
+            buffer.extend_from_slice(fraction);
+            len += fraction.len() + 1;
+        }
+        len
+    }
+
+    fn deserialize(data: &'a [u8]) -> Result<Self, Self::Error> {
+        match digits::parse_decimal(data) {
+            Some((mantissa, scale)) => Ok(Self { mantissa, scale }),
+            // Takes the slow path for an accurate error.
+            None => {
+                validate_float(data)?;
+                Self::deserialize_lossy(data)
+            }
+        }
+    }
+
+    fn deserialize_lossy(data: &'a [u8]) -> Result<Self, Self::Error> {
+        let (is_negative, digits) = match data.split_first() {
+            Some((b'-', rest)) => (true, rest),
+            _ => (false, data),
+        };
+        let mut mantissa: i64 = 0;
+        let mut scale: Option<u32> = None;
+        for byte in digits {
+            if *byte == b'.' {
+                scale = Some(0);
+                continue;
+            }
+            let digit = byte.wrapping_sub(b'0') as i64;
+            mantissa = mantissa
+                .checked_mul(10)
+                .and_then(|m| {
+                    if is_negative {
+                        m.checked_sub(digit)
+                    } else {
+                        m.checked_add(digit)
+                    }
+                })
+                .ok_or(ERR_OVERFLOW)?;
+            scale = scale.map(|s| s + 1);
+        }
+        let scale = scale.unwrap_or(0);
+        if scale > MAX_SCALE {
+            return Err(ERR_OVERFLOW);
+        }
+        Ok(Self { mantissa, scale })
+    }
+}
+
+/// Checks that `data` conforms to the FIX `float` syntax, i.e. an optional
+/// minus sign followed by digits and at most one decimal point.
+pub(crate) fn validate_float(data: &[u8]) -> Result<(), &'static str> {
+    let digits = data.strip_prefix(b"-").unwrap_or(data);
+    let mut found_digit = false;
+    let mut found_point = false;
+    for byte in digits {
+        match byte {
+            b'0'..=b'9' => found_digit = true,
+            b'.' if !found_point => found_point = true,
+            _ => return Err(ERR_INVALID),
+        }
+    }
+    if found_digit {
+        Ok(())
+    } else {
+        Err(ERR_EMPTY)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use quickcheck::{Arbitrary, Gen};
+    use quickcheck_macros::quickcheck;
+
+    impl Arbitrary for Decimal {
+        fn arbitrary(g: &mut Gen) -> Self {
+            Self::new(i64::arbitrary(g), u32::arbitrary(g) % (MAX_SCALE + 1))
+        }
+    }
+
+    #[test]
+    fn valid_decimals() {
+        let cases: &[(&[u8], i64, u32)] = &[
+            (b"0", 0, 0),
+            (b"1.50", 150, 2),
+            (b"-1.5", -15, 1),
+            (b"00.001", 1, 3),
+            (b".5", 5, 1),
+            (b"5.", 5, 0),
+            (b"-9223372036854775808", i64::MIN, 0),
+        ];
+        for (bytes, mantissa, scale) in cases {
+            let decimal = Decimal::deserialize(bytes).unwrap();
+            assert_eq!(decimal, Decimal::new(*mantissa, *scale));
+        }
+    }
+
+    #[test]
+    fn invalid_decimals() {
+        for bytes in &[
+            &b""[..],
+            b"-",
+            b".",
+            b"+1",
+            b"1e5",
+            b"1.2.3",
+            b"1,5",
+            b" 1",
+            b"9223372036854775808",
+            b"0.0000000000000000001",
+        ] {
+            assert!(Decimal::deserialize(bytes).is_err());
+        }
+    }
+
+    #[test]
+    fn serialize_pads_fraction() {
+        assert_eq!(Decimal::new(5, 3).to_bytes(), b"0.005");
+        assert_eq!(Decimal::new(-5, 1).to_bytes(), b"-0.5");
+        assert_eq!(Decimal::new(1200, 0).to_bytes(), b"1200");
+        assert_eq!(
+            format!("{}", Decimal::new(i64::MIN, 2)),
+            "-92233720368547758.08"
+        );
+    }
+
+    #[test]
+    fn arithmetic_across_scales() {
+        let a = Decimal::new(105, 1);
+        let b = Decimal::new(25, 2);
+        assert_eq!(a.checked_add(b), Some(Decimal::new(1075, 2)));
+        assert_eq!(b.checked_sub(a), Some(Decimal::new(-1025, 2)));
+        assert_eq!(Decimal::new(i64::MAX, 0).checked_add(b), None);
+        assert_eq!(Decimal::new(i64::MAX, 0).value_cmp(&b), Ordering::Greater);
+        assert_eq!(
+            Decimal::new(1, 0).value_cmp(&Decimal::new(10i64.pow(18), 18)),
+            Ordering::Equal
+        );
+    }
+
+    #[quickcheck]
+    fn serialized_length_is_correct(decimal: Decimal) -> bool {
+        let mut buffer = Vec::new();
+        decimal.serialize(&mut buffer) == buffer.len()
+    }
+
+    #[quickcheck]
+    fn verify_serialization_behavior(decimal: Decimal) -> bool {
+        super::super::verify_serialization_behavior(decimal)
+    }
+}
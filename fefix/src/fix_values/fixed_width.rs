@@ -0,0 +1,116 @@
+use super::FixValue;
+use crate::Buffer;
+
+const ERR_INVALID: &str = "Invalid fixed-width integer; expected exactly N digits.";
+
+/// An unsigned integer which is always encoded with exactly `N` zero-padded
+/// digits, e.g. `MsgSeqNum <34>` as `00000042` with `N = 8`.
+///
+/// FIX allows leading zeros in `int`-like fields, so a field slot reserved
+/// with a [`FixedWidth`] value can later be patched in place with
+/// [`FixedWidth::to_bytes`] without shifting the rest of the message.
+///
+/// # Examples
+///
+/// ```
+/// use fefix::FixValue;
+/// use fefix::fix_values::FixedWidth;
+///
+/// let seq_num = FixedWidth::<8>::new(42).unwrap();
+/// assert_eq!(&seq_num.to_bytes(), b"00000042");
+/// assert_eq!(FixedWidth::<8>::deserialize(b"00000042"), Ok(seq_num));
+/// assert!(FixedWidth::<8>::deserialize(b"42").is_err());
+/// assert!(FixedWidth::<2>::new(100).is_none());
+///
+/// // Patching a reserved slot.
+/// let mut message = b"34=00000000|".to_vec();
+/// message[3..11].copy_from_slice(&FixedWidth::<8>::new(7).unwrap().to_bytes());
+/// assert_eq!(&message[..], b"34=00000007|");
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FixedWidth<const N: usize> {
+    value: u64,
+}
+
+impl<const N: usize> FixedWidth<N> {
+    /// Creates a new [`FixedWidth`]. Returns [`None`] if `value` doesn't fit in
+    /// `N` digits.
+    pub fn new(value: u64) -> Option<Self> {
+        let fits = match 10u64.checked_pow(N as u32) {
+            Some(max) => value < max,
+            // More digits than any `u64` has.
+            None => true,
+        };
+        if fits {
+            Some(Self { value })
+        } else {
+            None
+        }
+    }
+
+    /// Returns the value of `self`.
+    pub fn get(&self) -> u64 {
+        self.value
+    }
+
+    /// Encodes `self` as exactly `N` ASCII digits.
+    pub fn to_bytes(&self) -> [u8; N] {
+        let mut bytes = [b'0'; N];
+        let mut value = self.value;
+        for byte in bytes.iter_mut().rev() {
+            *byte = b'0' + (value % 10) as u8;
+            value /= 10;
+        }
+        bytes
+    }
+}
+
+impl<'a, const N: usize> FixValue<'a> for FixedWidth<N> {
+    type Error = &'static str;
+    type SerializeSettings = ();
+
+    fn serialize_with<B>(&self, buffer: &mut B, _settings: ()) -> usize
+    where
+        B: Buffer,
+    {
+        buffer.extend_from_slice(&self.to_bytes());
+        N
+    }
+
+    fn deserialize(data: &'a [u8]) -> Result<Self, Self::Error> {
+        if data.len() != N || !data.iter().all(u8::is_ascii_digit) {
+            return Err(ERR_INVALID);
+        }
+        u64::deserialize(data)
+            .ok()
+            .and_then(Self::new)
+            .ok_or(ERR_INVALID)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use quickcheck_macros::quickcheck;
+
+    #[quickcheck]
+    fn roundtrip(n: u32) -> bool {
+        let value = FixedWidth::<10>::new(u64::from(n)).unwrap();
+        value.to_bytes().len() == 10 && FixedWidth::deserialize(&value.to_bytes()) == Ok(value)
+    }
+
+    #[test]
+    fn width_limits() {
+        assert_eq!(
+            FixedWidth::<3>::new(999).map(|n| n.to_bytes()),
+            Some(*b"999")
+        );
+        assert!(FixedWidth::<3>::new(1000).is_none());
+        assert_eq!(
+            FixedWidth::<24>::new(u64::MAX).map(|n| n.to_bytes()),
+            Some(*b"000018446744073709551615")
+        );
+        assert!(FixedWidth::<24>::deserialize(b"999999999999999999999999").is_err());
+        assert!(FixedWidth::<3>::deserialize(b"+12").is_err());
+    }
+}
@@ -0,0 +1,72 @@
+use super::{Date, FixValue};
+use crate::Buffer;
+
+/// Representation for `LocalMktDate`, i.e. a date of the local market (as
+/// opposed to UTC) in `YYYYMMDD` format.
+///
+/// [`LocalMktDate`] has the same validation rules as [`Date`], but it's a
+/// distinct type so that local dates don't get mistaken for UTC dates.
+///
+/// # Examples
+///
+/// ```
+/// use fefix::FixValue;
+/// use fefix::fix_values::LocalMktDate;
+///
+/// let settlement = LocalMktDate::deserialize(b"20210430").unwrap();
+/// assert_eq!(settlement.date().month(), 4);
+/// assert!(LocalMktDate::deserialize(b"20211301").is_err());
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct LocalMktDate {
+    date: Date,
+}
+
+impl LocalMktDate {
+    /// Creates a new [`LocalMktDate`] from its components. It returns `None` if
+    /// any of the three components is outside the legal range (see
+    /// [`Date::new`]).
+    pub fn new(year: u32, month: u32, day: u32) -> Option<Self> {
+        Date::new(year, month, day).map(Self::from)
+    }
+
+    /// Returns the local [`Date`] of `self`.
+    pub fn date(&self) -> Date {
+        self.date
+    }
+
+    /// Converts `self` to [`chrono::NaiveDate`]. [`chrono`] might impose
+    /// additional constraints and checks on date components (e.g. leap year,
+    /// day 31 in 30-day months); this function will return `None` for invalid dates.
+    #[cfg(feature = "utils-chrono")]
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "utils-chrono")))]
+    pub fn to_chrono_naive(&self) -> Option<chrono::NaiveDate> {
+        self.date.to_chrono_naive()
+    }
+}
+
+impl From<Date> for LocalMktDate {
+    fn from(date: Date) -> Self {
+        Self { date }
+    }
+}
+
+impl<'a> FixValue<'a> for LocalMktDate {
+    type Error = &'static str;
+    type SerializeSettings = ();
+
+    fn serialize_with<B>(&self, buffer: &mut B, settings: ()) -> usize
+    where
+        B: Buffer,
+    {
+        self.date.serialize_with(buffer, settings)
+    }
+
+    fn deserialize(data: &'a [u8]) -> Result<Self, Self::Error> {
+        Date::deserialize(data).map(Self::from)
+    }
+
+    fn deserialize_lossy(data: &'a [u8]) -> Result<Self, Self::Error> {
+        Date::deserialize_lossy(data).map(Self::from)
+    }
+}
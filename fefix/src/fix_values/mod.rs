@@ -3,28 +3,28 @@
 //! | FIX datatype               | Relevant [`FixValue`] implementors                                                 |
 //! |----------------------------|------------------------------------------------------------------------------------|
 //! | `int`                      | Any Rust primitive integer type.                                                   |
-//! | `Length`                   | [`usize`]                                                                          |
+//! | `Length`                   | [`usize`], [`FixedWidth`]                                                          |
 //! | `NumInGroup`               | [`usize`]                                                                          |
-//! | `SeqNum`                   | [`u64`]                                                                            |
+//! | `SeqNum`                   | [`u64`], [`FixedWidth`]                                                            |
 //! | `TagNum`                   | [`TagU16`](crate::TagU16)                                                          |
-//! | `DayOfMonth`               | [`u32`]                                                                            |
-//! | `float` and `float` -like  | [`f32`], [`f64`], `rust_decimal::Decimal`, `decimal::d128`, or other custom types. |
+//! | `DayOfMonth`               | [`DayOfMonth`], [`u32`]                                                            |
+//! | `float` and `float` -like  | [`Decimal`], `rust_decimal::Decimal`, `decimal::d128`, or other custom types.       |
 //! | `Boolean`                  | [`bool`]                                                                           |
 //! | `char`                     | [`u8`] [^1]                                                                        |
 //! | `String`                   | `Vec<u8>`, `&[u8]`.[^1]                                                            |
 //! | `data`                     | `Vec<u8>`, `&[u8]` (also [`String`], [`str`] for UTF-8 content).                   |
-//! | `MultipleCharValue`        | [`MultipleChars`] [^1]                                                             |
-//! | `MultipleValueString`      | [`MultipleStrings`] [^1]                                                           |
+//! | `MultipleCharValue`        | [`MultipleValues`], [`MultipleChars`] [^1]                                         |
+//! | `MultipleValueString`      | [`MultipleValues`], [`MultipleStrings`] [^1]                                       |
 //! | `Country`                  | [`Country`]                                                                        |
 //! | `Currency`                 | [`Currency`]                                                                       |
 //! | `Exchange`                 | [`Exchange`]                                                                       |
 //! | `month-year`               | [`MonthYear`]                                                                      |
-//! | `UTCTimestamp`             | [`Timestamp`]                                                                      |
-//! | `LocalMktDate`             | [`Timestamp`]                                                                      |
-//! | `UTCTimeOnly`              | [`Time`]                                                                           |
+//! | `UTCTimestamp`             | [`Timestamp`], `chrono::DateTime<Utc>`, `chrono::NaiveDateTime`, `time::OffsetDateTime`, `time::PrimitiveDateTime` |
+//! | `LocalMktDate`             | [`LocalMktDate`], [`Date`]                                                         |
+//! | `UTCTimeOnly`              | [`Time`], `chrono::NaiveTime`, `time::Time`                                        |
 //! | `TZTimestamp`              | [`TzTimestamp`]                                                                    |
 //! | `TZTimeOnly`               | [`TzTime`]                                                                         |
-//! | `UTCDateOnly`              | [`Date`]                                                                           |
+//! | `UTCDateOnly`              | [`Date`], `chrono::NaiveDate`, `time::Date`                                        |
 //!
 //! # Quick tour of [`FixValue`]
 //!
@@ -55,9 +55,14 @@
 
 mod checksum;
 mod date;
+mod day_of_month;
+mod decimal;
+mod fixed_width;
+mod local_mkt_date;
 mod monthyear;
 mod multiple_chars;
 mod multiple_strings;
+mod multiple_values;
 mod time;
 mod timestamp;
 mod tz;
@@ -66,11 +71,18 @@ mod tz_timestamp;
 
 use crate::FixValue;
 
+pub use crate::fix_value::TimePrecision;
 pub use checksum::CheckSum;
 pub use date::Date;
+pub use day_of_month::DayOfMonth;
+pub(crate) use decimal::validate_float;
+pub use decimal::Decimal;
+pub use fixed_width::FixedWidth;
+pub use local_mkt_date::LocalMktDate;
 pub use monthyear::MonthYear;
 pub use multiple_chars::MultipleChars;
 pub use multiple_strings::MultipleStrings;
+pub use multiple_values::MultipleValues;
 pub use time::Time;
 pub use timestamp::Timestamp;
 pub use tz::Tz;
@@ -1,46 +1,80 @@
 use crate::Buffer;
 use crate::FixValue;
 
-const LEN_IN_BYTES: usize = 8;
-
 const ERR_GENERIC: &str = "Invalid day or week format.";
 
 /// Canonical data field (DTF) for
-/// [`FixDatatype::MonthYear`](crate::dict::FixDatatype::MonthYear).
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+/// [`FixDatatype::MonthYear`](crate::dict::FixDatatype::MonthYear), in either
+/// `YYYYMM`, `YYYYMMDD`, or `YYYYMMwN` format (with week codes from `w1` to
+/// `w5`).
+///
+/// # Examples
+///
+/// ```
+/// use fefix::FixValue;
+/// use fefix::fix_values::MonthYear;
+///
+/// let maturity = MonthYear::deserialize(b"202112").unwrap();
+/// assert_eq!(maturity, MonthYear::new(2021, 12).unwrap());
+/// assert_eq!(maturity.day(), None);
+/// assert_eq!(maturity.week(), None);
+///
+/// let maturity = MonthYear::with_week(2021, 12, 3).unwrap();
+/// assert_eq!(&maturity.to_bytes()[..], b"202112w3");
+/// assert!(MonthYear::deserialize(b"202112w6").is_err());
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub struct MonthYear {
     year: u32,
     month: u32,
     day_or_week: DayOrWeek,
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 enum DayOrWeek {
+    Neither,
     Day(u32),
     Week(u32),
 }
 
 impl MonthYear {
-    /// Converts `self` to a byte array.
-    pub fn to_bytes(&self) -> [u8; LEN_IN_BYTES] {
-        let day_or_week_1 = match self.day_or_week {
-            DayOrWeek::Day(day) => (day / 10) as u8 + b'0',
-            DayOrWeek::Week(_) => b'w',
-        };
-        let day_or_week_2 = match self.day_or_week {
-            DayOrWeek::Day(day) => (day % 10) as u8 + b'0',
-            DayOrWeek::Week(week) => week as u8 + b'0',
-        };
-        [
-            (self.year() / 1000) as u8 + b'0',
-            ((self.year() / 100) % 10) as u8 + b'0',
-            ((self.year() / 10) % 10) as u8 + b'0',
-            (self.year() % 10) as u8 + b'0',
-            (self.month() / 10) as u8 + b'0',
-            (self.month() % 10) as u8 + b'0',
-            day_or_week_1,
-            day_or_week_2,
-        ]
+    /// Creates a new [`MonthYear`] without day nor week, i.e. `YYYYMM`.
+    /// Returns [`None`] if any component is outside the legal range.
+    pub fn new(year: u32, month: u32) -> Option<Self> {
+        Self::from_parts(year, month, DayOrWeek::Neither)
+    }
+
+    /// Creates a new [`MonthYear`] with a `day` of the month, i.e. `YYYYMMDD`.
+    /// Returns [`None`] if any component is outside the legal range.
+    pub fn with_day(year: u32, month: u32, day: u32) -> Option<Self> {
+        if (1..=31).contains(&day) {
+            Self::from_parts(year, month, DayOrWeek::Day(day))
+        } else {
+            None
+        }
+    }
+
+    /// Creates a new [`MonthYear`] with an intra-month `week` code from 1 to
+    /// 5, i.e. `YYYYMMwN`. Returns [`None`] if any component is outside the
+    /// legal range.
+    pub fn with_week(year: u32, month: u32, week: u32) -> Option<Self> {
+        if (1..=5).contains(&week) {
+            Self::from_parts(year, month, DayOrWeek::Week(week))
+        } else {
+            None
+        }
+    }
+
+    fn from_parts(year: u32, month: u32, day_or_week: DayOrWeek) -> Option<Self> {
+        if year <= 9999 && (1..=12).contains(&month) {
+            Some(Self {
+                year,
+                month,
+                day_or_week,
+            })
+        } else {
+            None
+        }
     }
 
     /// Returns the year of `self`.
@@ -145,9 +179,21 @@ impl<'a> FixValue<'a> for MonthYear {
     where
         B: Buffer,
     {
-        let bytes = self.to_bytes();
-        buffer.extend_from_slice(&bytes[..]);
-        bytes.len()
+        let digit = |n: u32| (n % 10) as u8 + b'0';
+        buffer.extend_from_slice(&[
+            digit(self.year() / 1000),
+            digit(self.year() / 100),
+            digit(self.year() / 10),
+            digit(self.year()),
+            digit(self.month() / 10),
+            digit(self.month()),
+        ]);
+        match self.day_or_week {
+            DayOrWeek::Neither => return 6,
+            DayOrWeek::Day(day) => buffer.extend_from_slice(&[digit(day / 10), digit(day)]),
+            DayOrWeek::Week(week) => buffer.extend_from_slice(&[b'w', digit(week)]),
+        }
+        8
     }
 
     fn deserialize(data: &'a [u8]) -> Result<Self, Self::Error> {
@@ -159,12 +205,17 @@ impl<'a> FixValue<'a> for MonthYear {
     }
 
     fn deserialize_lossy(data: &'a [u8]) -> Result<Self, Self::Error> {
+        if data.len() != 6 && data.len() != 8 {
+            return Err(ERR_GENERIC);
+        }
         let year = from_digit(data[0]) as u32 * 1000
             + from_digit(data[1]) as u32 * 100
             + from_digit(data[2]) as u32 * 10
             + from_digit(data[3]) as u32;
         let month = from_digit(data[4]) as u32 * 10 + from_digit(data[5]) as u32;
-        let day_or_week = if data[6] == b'w' {
+        let day_or_week = if data.len() == 6 {
+            DayOrWeek::Neither
+        } else if data[6] == b'w' {
             DayOrWeek::Week(from_digit(data[7]) as u32)
         } else {
             DayOrWeek::Day(from_digit(data[6]) as u32 * 10 + from_digit(data[7]) as u32)
@@ -186,13 +237,13 @@ fn from_digit(digit: u8) -> u8 {
 }
 
 fn validate(data: &[u8]) -> bool {
-    if data.len() != 8 {
+    if data.len() != 6 && data.len() != 8 {
         return false;
     }
     if !validate_year(data) || !validate_month(data) {
         return false;
     }
-    validate_week(data) || validate_day(data)
+    data.len() == 6 || validate_week(data) || validate_day(data)
 }
 
 fn validate_year(data: &[u8]) -> bool {
@@ -203,8 +254,7 @@ fn validate_year(data: &[u8]) -> bool {
 }
 
 fn validate_month(data: &[u8]) -> bool {
-    ((data[4] == b'0' && data[5] <= b'9') || (data[4] == b'1' && data[5] <= b'2'))
-        && data[5] >= b'0'
+    (data[4] == b'0' && is_digit(data[5], 1, 9)) || (data[4] == b'1' && is_digit(data[5], 0, 2))
 }
 
 fn validate_week(data: &[u8]) -> bool {
@@ -212,10 +262,9 @@ fn validate_week(data: &[u8]) -> bool {
 }
 
 fn validate_day(data: &[u8]) -> bool {
-    (data[6] == b'0' && data[7] >= b'0' && data[7] <= b'9')
-        || (data[6] == b'1' && data[7] >= b'0' && data[7] <= b'9')
-        || (data[6] == b'2' && data[7] >= b'0' && data[7] <= b'9')
-        || (data[6] == b'3' && data[7] >= b'0' && data[7] <= b'1')
+    (data[6] == b'0' && is_digit(data[7], 1, 9))
+        || (is_digit(data[6], 1, 2) && is_digit(data[7], 0, 9))
+        || (data[6] == b'3' && is_digit(data[7], 0, 1))
 }
 
 #[cfg(test)]
@@ -228,10 +277,10 @@ mod test {
         fn arbitrary(g: &mut Gen) -> Self {
             let year = u32::arbitrary(g) % 10000;
             let month = (u32::arbitrary(g) % 12) + 1;
-            let day_or_week = if bool::arbitrary(g) {
-                format!("{:02}", (u32::arbitrary(g) % 31) + 1)
-            } else {
-                format!("w{}", (u32::arbitrary(g) % 5) + 1)
+            let day_or_week = match u32::arbitrary(g) % 3 {
+                0 => format!("{:02}", (u32::arbitrary(g) % 31) + 1),
+                1 => format!("w{}", (u32::arbitrary(g) % 5) + 1),
+                _ => String::new(),
             };
             let s = format!("{:04}{:02}{}", year, month, day_or_week);
             MonthYear::deserialize(s.as_bytes()).unwrap()
@@ -250,4 +299,14 @@ mod test {
         let deserialized_lossy = MonthYear::deserialize_lossy(&serialized[..]).unwrap();
         deserialized == my && deserialized_lossy == my
     }
+
+    #[test]
+    fn out_of_range_components_are_invalid() {
+        for s in [
+            "202100", "202113", "20211200", "20211232", "202112w0", "2021121", "2021",
+        ] {
+            assert!(MonthYear::deserialize(s.as_bytes()).is_err(), "{}", s);
+        }
+        assert!(MonthYear::deserialize_lossy(b"2021").is_err());
+    }
 }
@@ -1,4 +1,4 @@
-use std::iter::FusedIterator;
+use core::iter::FusedIterator;
 
 /// An [`Iterator`] over space-delimited bytes in a
 /// `MultipleCharValue` FIX field.
@@ -0,0 +1,127 @@
+use super::MultipleStrings;
+use crate::{Buffer, FixValue};
+
+const ERR_SPACING: &str = "Invalid spacing; values must be separated by single spaces.";
+
+/// The space-separated values of a `MultipleCharValue` or
+/// `MultipleStringValue` FIX field, e.g. `ExecInst <18>`.
+///
+/// Use [`EncoderHandle::set_multiple`](crate::tagvalue::EncoderHandle::set_multiple)
+/// to encode several values as a single field.
+///
+/// # Examples
+///
+/// ```
+/// use fefix::fix_values::MultipleValues;
+/// use fefix::FixValue;
+///
+/// let values = MultipleValues::deserialize(b"1 G 6").unwrap();
+/// assert_eq!(values.len(), 3);
+/// assert!(values.contains(b"G"));
+/// assert_eq!(values.iter().collect::<Vec<_>>(), vec![b"1", b"G", b"6"]);
+///
+/// // Lossy deserialization tolerates extra spaces.
+/// assert!(MultipleValues::deserialize(b"1  G").is_err());
+/// assert_eq!(MultipleValues::deserialize_lossy(b"1  G ").unwrap().len(), 2);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub struct MultipleValues<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> MultipleValues<'a> {
+    /// Creates a new [`MultipleValues`] from raw `data`, without any
+    /// validation.
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    /// Returns the raw field value of `self`.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// Returns an [`Iterator`] over all values within `self`.
+    pub fn iter(&self) -> MultipleStrings<'a> {
+        MultipleStrings::new(self.data)
+    }
+
+    /// Returns the number of values within `self`.
+    pub fn len(&self) -> usize {
+        self.iter().count()
+    }
+
+    /// Returns `true` if `self` contains no values at all; `false` otherwise.
+    pub fn is_empty(&self) -> bool {
+        self.iter().next().is_none()
+    }
+
+    /// Returns `true` if `value` is one of the values within `self`; `false`
+    /// otherwise.
+    pub fn contains(&self, value: &[u8]) -> bool {
+        self.iter().any(|v| v == value)
+    }
+}
+
+impl<'a> IntoIterator for MultipleValues<'a> {
+    type Item = &'a [u8];
+    type IntoIter = MultipleStrings<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a> FixValue<'a> for MultipleValues<'a> {
+    type Error = &'static str;
+    type SerializeSettings = ();
+
+    #[inline]
+    fn serialize_with<B>(&self, buffer: &mut B, _settings: ()) -> usize
+    where
+        B: Buffer,
+    {
+        buffer.extend_from_slice(self.data);
+        self.data.len()
+    }
+
+    #[inline]
+    fn deserialize(data: &'a [u8]) -> Result<Self, Self::Error> {
+        let is_well_spaced = data.split(|byte| *byte == b' ').all(|v| !v.is_empty());
+        if is_well_spaced {
+            Ok(Self::new(data))
+        } else {
+            Err(ERR_SPACING)
+        }
+    }
+
+    #[inline]
+    fn deserialize_lossy(data: &'a [u8]) -> Result<Self, Self::Error> {
+        Ok(Self::new(data))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn empty_field_is_invalid() {
+        assert!(MultipleValues::deserialize(b"").is_err());
+        assert!(MultipleValues::deserialize_lossy(b"").unwrap().is_empty());
+    }
+
+    #[test]
+    fn leading_and_trailing_spaces_are_invalid() {
+        assert!(MultipleValues::deserialize(b" A").is_err());
+        assert!(MultipleValues::deserialize(b"A ").is_err());
+    }
+
+    #[test]
+    fn serialization_is_verbatim() {
+        let values = MultipleValues::deserialize(b"AB CD").unwrap();
+        assert_eq!(values.to_bytes(), b"AB CD".to_vec());
+        assert!(!values.contains(b"A"));
+        assert_eq!(values.into_iter().last(), Some(&b"CD"[..]));
+    }
+}
@@ -1,20 +1,19 @@
+use super::TimePrecision;
 use crate::Buffer;
 use crate::FixValue;
 
 const ERR_INVALID: &str = "Invalid time.";
 
-const LEN_IN_BYTES_NO_MILLI: usize = 8;
 const LEN_IN_BYTES_WITH_MILLI: usize = 12;
 
 const MAX_HOUR: u32 = 23;
 const MAX_MINUTE: u32 = 59;
 const MAX_SECOND: u32 = 60; // Leap seconds.
-const MAX_MILLISECOND: u32 = 999;
+const MAX_NANOSECOND: u32 = 999_999_999;
 
 const MIN_HOUR: u32 = 0;
 const MIN_MINUTE: u32 = 0;
 const MIN_SECOND: u32 = 0;
-const MIN_MILLISECOND: u32 = 0;
 
 /// Canonical data field (DTF) for
 /// [`FixDatatype::UtcTimeOnly`](crate::dict::FixDatatype::UtcTimeOnly).
@@ -23,33 +22,51 @@ pub struct Time {
     hour: u32,
     minute: u32,
     second: u32,
-    milli: u32,
+    nanos: u32,
 }
 
 impl Time {
     /// Creates a new time value from its components, with milliseconds.
     pub fn from_hmsm(hour: u32, minute: u32, second: u32, milli: u32) -> Option<Self> {
-        if hour >= MIN_HOUR
-            && hour <= MAX_HOUR
-            && minute >= MIN_MINUTE
-            && minute <= MAX_MINUTE
-            && second >= MIN_SECOND
-            && second <= MAX_SECOND
-            && milli >= MIN_MILLISECOND
-            && milli <= MAX_MILLISECOND
+        if milli <= 999 {
+            Self::from_hms_nano(hour, minute, second, milli * 1_000_000)
+        } else {
+            None
+        }
+    }
+
+    /// Creates a new time value from its components, with nanoseconds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix::fix_values::{Time, TimePrecision};
+    /// use fefix::FixValue;
+    ///
+    /// let time = Time::from_hms_nano(12, 30, 0, 123_456_789).unwrap();
+    /// let mut buffer = Vec::new();
+    /// time.serialize_with(&mut buffer, TimePrecision::Micros);
+    /// assert_eq!(&buffer[..], b"12:30:00.123456");
+    /// ```
+    pub fn from_hms_nano(hour: u32, minute: u32, second: u32, nanos: u32) -> Option<Self> {
+        if (MIN_HOUR..=MAX_HOUR).contains(&hour)
+            && (MIN_MINUTE..=MAX_MINUTE).contains(&minute)
+            && (MIN_SECOND..=MAX_SECOND).contains(&second)
+            && nanos <= MAX_NANOSECOND
         {
             Some(Self {
                 hour,
                 minute,
                 second,
-                milli,
+                nanos,
             })
         } else {
             None
         }
     }
 
-    /// Encodes `self` as a FIX field value in byte array.
+    /// Encodes `self` as a FIX field value in byte array, with millisecond
+    /// precision.
     pub const fn to_bytes(&self) -> [u8; LEN_IN_BYTES_WITH_MILLI] {
         [
             (self.hour() / 10) as u8 + b'0',
@@ -122,7 +139,7 @@ impl Time {
         self.second
     }
 
-    /// Returns the milliecond of `self`.
+    /// Returns the millisecond of `self`.
     ///
     /// # Examples
     ///
@@ -134,7 +151,27 @@ impl Time {
     /// assert_eq!(dtf.milli(), 328)
     /// ```
     pub const fn milli(&self) -> u32 {
-        self.milli
+        self.nanos / 1_000_000
+    }
+
+    /// Returns the fractional seconds of `self`, in microseconds.
+    pub const fn micro(&self) -> u32 {
+        self.nanos / 1_000
+    }
+
+    /// Returns the fractional seconds of `self`, in nanoseconds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix::prelude::*;
+    /// use fefix::fix_values::Time;
+    ///
+    /// let dtf = Time::deserialize(b"12:45:00.328110").unwrap();
+    /// assert_eq!(dtf.nano(), 328_110_000)
+    /// ```
+    pub const fn nano(&self) -> u32 {
+        self.nanos
     }
 
     /// Converts `self` to a [`chrono::NaiveTime`]. `chrono` might perform
@@ -143,40 +180,76 @@ impl Time {
     #[cfg(feature = "utils-chrono")]
     #[cfg_attr(doc_cfg, doc(cfg(feature = "utils-chrono")))]
     pub fn to_chrono_naive(&self) -> Option<chrono::NaiveTime> {
-        chrono::NaiveTime::from_hms_milli_opt(
-            self.hour(),
-            self.minute(),
-            self.second(),
-            self.milli(),
-        )
+        if self.second() == 60 {
+            // `chrono` represents leap seconds as nanoseconds overflowing into
+            // the next second.
+            chrono::NaiveTime::from_hms_nano_opt(
+                self.hour(),
+                self.minute(),
+                59,
+                self.nano() + 1_000_000_000,
+            )
+        } else {
+            chrono::NaiveTime::from_hms_nano_opt(
+                self.hour(),
+                self.minute(),
+                self.second(),
+                self.nano(),
+            )
+        }
     }
 }
 
+/// Serialization precision is [`TimePrecision::Millis`] by default.
+/// Deserialization accepts no fractional seconds at all, or 3, 6, 9, and 12
+/// digits; picoseconds are truncated.
 impl<'a> FixValue<'a> for Time {
     type Error = &'static str;
-    type SerializeSettings = ();
+    type SerializeSettings = TimePrecision;
 
-    fn serialize_with<B>(&self, buffer: &mut B, _settings: ()) -> usize
+    fn serialize_with<B>(&self, buffer: &mut B, precision: TimePrecision) -> usize
     where
         B: Buffer,
     {
         let bytes = self.to_bytes();
-        buffer.extend_from_slice(&bytes[..]);
-        bytes.len()
+        let (fraction, digits) = match precision {
+            TimePrecision::Seconds => {
+                buffer.extend_from_slice(&bytes[..8]);
+                return 8;
+            }
+            TimePrecision::Millis => {
+                buffer.extend_from_slice(&bytes[..]);
+                return bytes.len();
+            }
+            TimePrecision::Micros => (self.micro(), 6),
+            TimePrecision::Nanos => (self.nano(), 9),
+        };
+        buffer.extend_from_slice(&bytes[..9]);
+        let mut digits_buffer = [b'0'; 9];
+        let mut n = fraction;
+        for byte in digits_buffer[..digits].iter_mut().rev() {
+            *byte = (n % 10) as u8 + b'0';
+            n /= 10;
+        }
+        buffer.extend_from_slice(&digits_buffer[..digits]);
+        9 + digits
     }
 
     fn deserialize(data: &'a [u8]) -> Result<Self, Self::Error> {
-        let mut milli = 0;
-        if data.len() == LEN_IN_BYTES_WITH_MILLI {
-            milli = ascii_digit_to_u32(data[9], 100)
-                + ascii_digit_to_u32(data[10], 10)
-                + ascii_digit_to_u32(data[11], 1);
-            if data[8] != b'.' {
-                return Err(ERR_INVALID);
+        let nanos = match data.get(8..) {
+            Some([]) => 0,
+            Some([b'.', fraction @ ..]) if matches!(fraction.len(), 3 | 6 | 9 | 12) => {
+                if !fraction.iter().copied().all(is_ascii_digit) {
+                    return Err(ERR_INVALID);
+                }
+                let digits = &fraction[..fraction.len().min(9)];
+                let nanos = digits
+                    .iter()
+                    .fold(0, |n, digit| ascii_digit_to_u32(*digit, 1) + n * 10);
+                nanos * 10u32.pow(9 - digits.len() as u32)
             }
-        } else if data.len() != LEN_IN_BYTES_NO_MILLI {
-            return Err(ERR_INVALID);
-        }
+            _ => return Err(ERR_INVALID),
+        };
         let digits_are_ok = data[2] == b':'
             && data[5] == b':'
             && is_ascii_digit(data[0])
@@ -191,7 +264,7 @@ impl<'a> FixValue<'a> for Time {
         let hour = ascii_digit_to_u32(data[0], 10) + ascii_digit_to_u32(data[1], 1);
         let minute = ascii_digit_to_u32(data[3], 10) + ascii_digit_to_u32(data[4], 1);
         let second = ascii_digit_to_u32(data[6], 10) + ascii_digit_to_u32(data[7], 1);
-        Self::from_hmsm(hour, minute, second, milli).ok_or(ERR_INVALID)
+        Self::from_hms_nano(hour, minute, second, nanos).ok_or(ERR_INVALID)
     }
 }
 
@@ -272,4 +345,41 @@ mod test {
     fn verify_serialization_behavior(time: Time) -> bool {
         super::super::verify_serialization_behavior(time)
     }
+
+    #[test]
+    fn all_precisions_are_decoded() {
+        let cases: &[(&[u8], u32)] = &[
+            (b"10:20:30", 0),
+            (b"10:20:30.100", 100_000_000),
+            (b"10:20:30.000101", 101_000),
+            (b"10:20:30.000000007", 7),
+            (b"10:20:30.000000007999", 7),
+        ];
+        for (bytes, nanos) in cases {
+            assert_eq!(Time::deserialize(bytes).unwrap().nano(), *nanos);
+        }
+        for bytes in [
+            &b"10:20:30.1"[..],
+            b"10:20:30.1234",
+            b"10:20:30.12a",
+            b"10:20:30.",
+        ] {
+            assert!(Time::deserialize(bytes).is_err());
+        }
+    }
+
+    #[test]
+    fn serialization_precision_is_configurable() {
+        let time = Time::from_hms_nano(1, 2, 3, 4_005_006).unwrap();
+        let serialize = |precision| {
+            let mut buffer = Vec::new();
+            let len = time.serialize_with(&mut buffer, precision);
+            assert_eq!(len, buffer.len());
+            String::from_utf8(buffer).unwrap()
+        };
+        assert_eq!(serialize(TimePrecision::Seconds), "01:02:03");
+        assert_eq!(serialize(TimePrecision::Millis), "01:02:03.004");
+        assert_eq!(serialize(TimePrecision::Micros), "01:02:03.004005");
+        assert_eq!(serialize(TimePrecision::Nanos), "01:02:03.004005006");
+    }
 }
@@ -1,7 +1,8 @@
-use crate::{fix_values::Date, fix_values::Time, Buffer, FixValue};
+use crate::fix_values::{Date, Time, TimePrecision};
+use crate::{Buffer, FixValue};
 
 /// Representation for `UtcTimestamp`.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Timestamp {
     date: Date,
     time: Time,
@@ -13,7 +14,7 @@ impl Timestamp {
         Self { date, time }
     }
 
-    /// Parses from a `"YYYYMMDD"` format.
+    /// Parses from a `YYYYMMDD-HH:MM:SS[.sss[sss[sss]]]` format.
     pub fn parse(data: &[u8]) -> Option<Self> {
         if data.len() < 12 || data[8] != b'-' {
             return None;
@@ -23,18 +24,26 @@ impl Timestamp {
         Some(Self::new(date, time))
     }
 
-    /// Returns the current UTC system time with millisecond precision.
+    /// Returns the current UTC system time, with nanosecond precision. The
+    /// serialization precision is chosen with [`FixValue::serialize_with`].
+    #[cfg(feature = "std")]
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "std")))]
     pub fn utc_now() -> Self {
+        Self::from_utc(chrono::Utc::now())
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn from_utc(utc: chrono::DateTime<chrono::Utc>) -> Self {
         use chrono::{Datelike, Timelike};
-        let utc: chrono::DateTime<chrono::Utc> = chrono::Utc::now();
         let date = Date::new(utc.year() as u32, utc.month() as u32, utc.day() as u32);
-        let time = Time::from_hmsm(
-            utc.hour() as u32,
-            utc.minute() as u32,
-            utc.second() as u32,
-            utc.nanosecond() as u32 / 1_000_000,
-        )
-        .unwrap();
+        // `chrono` represents leap seconds as nanoseconds overflowing into the
+        // next second.
+        let (second, nanos) = if utc.nanosecond() >= 1_000_000_000 {
+            (60, utc.nanosecond() - 1_000_000_000)
+        } else {
+            (utc.second(), utc.nanosecond())
+        };
+        let time = Time::from_hms_nano(utc.hour(), utc.minute(), second, nanos).unwrap();
         Self::new(date.unwrap(), time)
     }
 
@@ -70,15 +79,19 @@ impl Timestamp {
     }
 }
 
+/// Serialization precision is [`TimePrecision::Millis`] by default, just like
+/// [`Time`].
 impl<'a> FixValue<'a> for Timestamp {
     type Error = &'static str;
-    type SerializeSettings = ();
+    type SerializeSettings = TimePrecision;
 
-    fn serialize_with<B>(&self, buffer: &mut B, _settings: ()) -> usize
+    fn serialize_with<B>(&self, buffer: &mut B, precision: TimePrecision) -> usize
     where
         B: Buffer,
     {
-        self.date().serialize(buffer) + b"-".serialize(buffer) + self.time().serialize(buffer)
+        self.date().serialize(buffer)
+            + b"-".serialize(buffer)
+            + self.time().serialize_with(buffer, precision)
     }
 
     fn deserialize(data: &'a [u8]) -> Result<Self, Self::Error> {
@@ -105,4 +118,14 @@ mod test {
     fn verify_serialization_behavior(timestamp: Timestamp) -> bool {
         super::super::verify_serialization_behavior(timestamp)
     }
+
+    #[test]
+    fn microseconds_roundtrip() {
+        let timestamp = Timestamp::deserialize(b"20210601-09:00:01.000250").unwrap();
+        assert_eq!(timestamp.time().micro(), 250);
+        let mut buffer = Vec::new();
+        timestamp.serialize_with(&mut buffer, TimePrecision::Micros);
+        assert_eq!(&buffer[..], b"20210601-09:00:01.000250");
+        assert_eq!(&timestamp.to_bytes()[..], b"20210601-09:00:01.000");
+    }
 }
@@ -1,7 +1,7 @@
 use super::FixValue;
 use crate::Buffer;
-use std::hash::Hash;
-use std::time::Duration;
+use core::hash::{Hash, Hasher};
+use core::time::Duration;
 
 const ERR_INVALID: &str = "Invalid timezone.";
 
@@ -30,23 +30,57 @@ const MINUTE: u32 = 60;
 /// let timezone = Tz::deserialize(b"+04:30").unwrap();
 /// assert_eq!(timezone.offset(), (1, Duration::from_secs(4 * 3600 + 30 * 60)));
 /// ```
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Copy, Clone)]
 pub struct Tz {
     offset_from_utc_in_seconds: i32,
+    format: TzFormat,
+}
+
+/// The original representation of a [`Tz`], for exact re-encoding.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum TzFormat {
+    // `Z`
+    Utc,
+    // `+hh`
+    Hours,
+    // `+hh:mm`
+    HoursAndMinutes,
 }
 
 impl Tz {
     /// The UTC timezone.
     pub const UTC: Self = Self {
         offset_from_utc_in_seconds: 0,
+        format: TzFormat::Utc,
     };
 
+    /// Creates a [`Tz`] with an offset from UTC of `secs` seconds, rounded
+    /// down to whole minutes. Returns [`None`] if the offset is a day or
+    /// longer.
+    pub fn from_offset_secs(secs: i32) -> Option<Self> {
+        if secs.abs() >= 24 * HOUR as i32 {
+            return None;
+        }
+        let secs = secs - secs % MINUTE as i32;
+        let format = if secs == 0 {
+            TzFormat::Utc
+        } else if secs % HOUR as i32 == 0 {
+            TzFormat::Hours
+        } else {
+            TzFormat::HoursAndMinutes
+        };
+        Some(Self {
+            offset_from_utc_in_seconds: secs,
+            format,
+        })
+    }
+
     /// Calculates the offset information of `self` as compared to UTC. The
     /// return value is in the form of a sign (-1, 0, or +1) and a [`Duration`].
     pub fn offset(&self) -> (i32, Duration) {
         (
             self.offset_from_utc_in_seconds.signum(),
-            Duration::from_secs(self.offset_from_utc_in_seconds.abs() as u64),
+            Duration::from_secs(self.offset_from_utc_in_seconds.unsigned_abs() as u64),
         )
     }
 
@@ -59,16 +93,56 @@ impl Tz {
     #[cfg(feature = "utils-chrono")]
     #[cfg_attr(doc_cfg, doc(cfg(feature = "utils-chrono")))]
     pub fn to_chrono_offset(&self) -> chrono::FixedOffset {
-        chrono::FixedOffset::east(self.offset().1.as_secs() as i32)
+        chrono::FixedOffset::east(self.offset_from_utc_in_seconds)
     }
 
     /// Creates a [`Tz`] from a [`chrono::FixedOffset`].
     #[cfg(feature = "utils-chrono")]
     #[cfg_attr(doc_cfg, doc(cfg(feature = "utils-chrono")))]
     pub fn from_chrono_offset(offset: chrono::FixedOffset) -> Self {
-        Self {
-            offset_from_utc_in_seconds: offset.local_minus_utc(),
+        // `chrono` offsets are always within a day.
+        Self::from_offset_secs(offset.local_minus_utc()).unwrap()
+    }
+
+    fn parse(data: &[u8], lossy: bool) -> Option<Self> {
+        let sign = match data.first()? {
+            b'Z' if data.len() == 1 => return Some(Self::UTC),
+            b'+' => 1,
+            b'-' => -1,
+            _ if lossy => 1,
+            _ => return None,
+        };
+        let (hour, minute, format) = match data.len() {
+            3 => (two_digits(&data[1..3])?, 0, TzFormat::Hours),
+            6 if data[3] == b':' || lossy => (
+                two_digits(&data[1..3])?,
+                two_digits(&data[4..6])?,
+                TzFormat::HoursAndMinutes,
+            ),
+            _ => return None,
+        };
+        if hour > 23 || minute > 59 {
+            return None;
         }
+        Some(Self {
+            offset_from_utc_in_seconds: sign * (hour * HOUR + minute * MINUTE) as i32,
+            format,
+        })
+    }
+}
+
+// Equality disregards the original representation, i.e. `+00` equals `Z`.
+impl PartialEq for Tz {
+    fn eq(&self, other: &Self) -> bool {
+        self.offset_from_utc_in_seconds == other.offset_from_utc_in_seconds
+    }
+}
+
+impl Eq for Tz {}
+
+impl Hash for Tz {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.offset_from_utc_in_seconds.hash(state);
     }
 }
 
@@ -80,93 +154,58 @@ impl<'a> FixValue<'a> for Tz {
     where
         B: Buffer,
     {
-        if self.offset_from_utc_in_seconds == 0 {
+        if self.offset_from_utc_in_seconds == 0 && self.format == TzFormat::Utc {
             buffer.extend_from_slice(b"Z");
-            1
+            return 1;
+        }
+        let sign = if self.offset_from_utc_in_seconds < 0 {
+            b'-'
         } else {
-            let sign = if self.offset_from_utc_in_seconds > 0 {
-                b'+'
-            } else {
-                b'-'
-            };
-            let hour = self.offset().1.as_secs() as u32 / HOUR;
+            b'+'
+        };
+        let secs = self.offset().1.as_secs() as u32;
+        let hour = secs / HOUR;
+        buffer.extend_from_slice(&[
+            sign,
+            u32_digit_to_ascii(hour / 10),
+            u32_digit_to_ascii(hour % 10),
+        ]);
+        let minutes = (secs % HOUR) / MINUTE;
+        if minutes != 0 || self.format == TzFormat::HoursAndMinutes {
             buffer.extend_from_slice(&[
-                sign,
-                u32_digit_to_ascii(hour / 10),
-                u32_digit_to_ascii(hour % 10),
+                b':',
+                u32_digit_to_ascii(minutes / 10),
+                u32_digit_to_ascii(minutes % 10),
             ]);
-            let minutes = (self.offset().1.as_secs() as u32 % 3600) / 60;
-            if minutes != 0 {
-                buffer.extend_from_slice(&[
-                    b':',
-                    u32_digit_to_ascii(minutes / 10),
-                    u32_digit_to_ascii(minutes % 10),
-                ]);
-                6
-            } else {
-                3
-            }
+            6
+        } else {
+            3
         }
     }
 
     fn deserialize(data: &'a [u8]) -> Result<Self, Self::Error> {
-        if data.len() == 0 {
-            return Err(ERR_INVALID);
-        }
-        let sign: i32;
-        match data[0] {
-            b'Z' => {
-                return Ok(Self::UTC);
-            }
-            b'+' => {
-                sign = 1;
-            }
-            b'-' => {
-                sign = -1;
-            }
-            _ => return Err(ERR_INVALID),
-        }
-        match data.len() {
-            3 => {
-                let hour = ascii_digit_to_u32(data[1], 10) + ascii_digit_to_u32(data[2], 1);
-                Ok(Self {
-                    offset_from_utc_in_seconds: sign * (hour * HOUR) as i32,
-                })
-            }
-            6 => {
-                let hour = ascii_digit_to_u32(data[1], 10) + ascii_digit_to_u32(data[2], 1);
-                let minute = ascii_digit_to_u32(data[4], 10) + ascii_digit_to_u32(data[5], 1);
-                Ok(Self {
-                    offset_from_utc_in_seconds: sign * (hour * HOUR + minute * MINUTE) as i32,
-                })
-            }
-            _ => Err(ERR_INVALID),
-        }
+        Self::parse(data, false).ok_or(ERR_INVALID)
     }
 
     fn deserialize_lossy(data: &'a [u8]) -> Result<Self, Self::Error> {
-        match data.len() {
-            1 => Ok(Self::UTC),
-            3 => {
-                let sign = if data[0] == b'+' { 1 } else { -1 };
-                let hour = ascii_digit_to_u32(data[1], 10) + ascii_digit_to_u32(data[2], 1);
-                Ok(Self {
-                    offset_from_utc_in_seconds: sign * (hour * HOUR) as i32,
-                })
-            }
-            6 => {
-                let sign = if data[0] == b'+' { 1 } else { -1 };
-                let hour = ascii_digit_to_u32(data[1], 10) + ascii_digit_to_u32(data[2], 1);
-                let minute = ascii_digit_to_u32(data[4], 10) + ascii_digit_to_u32(data[5], 1);
-                Ok(Self {
-                    offset_from_utc_in_seconds: sign * (hour * HOUR + minute * MINUTE) as i32,
-                })
-            }
-            _ => Err(ERR_INVALID),
+        if data.len() == 1 {
+            Ok(Self::UTC)
+        } else {
+            Self::parse(data, true).ok_or(ERR_INVALID)
         }
     }
 }
 
+/// Parses exactly two ASCII digits.
+pub(super) fn two_digits(data: &[u8]) -> Option<u32> {
+    match data {
+        [a, b] if a.is_ascii_digit() && b.is_ascii_digit() => {
+            Some(ascii_digit_to_u32(*a, 10) + ascii_digit_to_u32(*b, 1))
+        }
+        _ => None,
+    }
+}
+
 const fn u32_digit_to_ascii(digit: u32) -> u8 {
     digit as u8 + b'0'
 }
@@ -204,4 +243,28 @@ mod test {
         assert_eq!(&tz.to_bytes()[..], "-01".as_bytes());
         assert_eq!(tz.offset_as_secs(), -3600);
     }
+
+    #[test]
+    fn explicit_zero_offsets_are_retained() {
+        for s in ["+00", "+00:00", "+05:00", "-02"] {
+            let tz = Tz::deserialize(s.as_bytes()).unwrap();
+            assert_eq!(&tz.to_bytes()[..], s.as_bytes());
+        }
+        assert_eq!(Tz::deserialize(b"+00").unwrap(), Tz::UTC);
+    }
+
+    #[test]
+    fn invalid_digits_and_ranges_are_err() {
+        for s in ["+0a", "+24", "+05:60", "+05-30", "05", "Z0"] {
+            assert!(Tz::deserialize(s.as_bytes()).is_err(), "{}", s);
+        }
+    }
+
+    #[test]
+    fn from_offset_secs_picks_shortest_format() {
+        let tz = Tz::from_offset_secs(-(5 * 3600 + 45 * 60)).unwrap();
+        assert_eq!(&tz.to_bytes()[..], b"-05:45");
+        assert_eq!(&Tz::from_offset_secs(7200).unwrap().to_bytes()[..], b"+02");
+        assert_eq!(Tz::from_offset_secs(24 * 3600), None);
+    }
 }
@@ -1,9 +1,14 @@
+use super::tz::two_digits;
 use super::{FixValue, Tz};
 use crate::Buffer;
 
 const ERR_INVALID: &str = "Invalid time.";
 
-/// Timezone-aware intra-day timestamp.
+/// Timezone-aware intra-day timestamp, i.e. `TZTimeOnly` in the
+/// `HH:MM[:SS][Z|+hh[:mm]]` format.
+///
+/// The original representation (optional seconds, timezone format) is
+/// retained, so that serialization reproduces the exact input.
 ///
 /// # Examples
 ///
@@ -16,6 +21,10 @@ const ERR_INVALID: &str = "Invalid time.";
 /// assert_eq!(tztime.minute(), 39);
 /// assert_eq!(tztime.second(), 20);
 /// assert_eq!(tztime.timezone(), Tz::UTC);
+///
+/// let tztime = TzTime::deserialize(b"13:09+05:30").unwrap();
+/// assert_eq!(tztime.timezone().offset_as_secs(), 5 * 3600 + 30 * 60);
+/// assert_eq!(&tztime.to_bytes()[..], b"13:09+05:30");
 /// ```
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub struct TzTime {
@@ -68,50 +77,64 @@ impl<'a> FixValue<'a> for TzTime {
     where
         B: Buffer,
     {
-        let bytes = &[
-            self.hour() as u8 / 10,
-            self.hour() as u8 % 10,
+        let bytes = [
+            digit_to_ascii(self.hour() / 10),
+            digit_to_ascii(self.hour() % 10),
             b':',
-            self.minute() as u8 / 10,
-            self.minute() as u8 % 10,
+            digit_to_ascii(self.minute() / 10),
+            digit_to_ascii(self.minute() % 10),
             b':',
-            self.second() as u8 / 10,
-            self.second() as u8 % 10,
+            digit_to_ascii(self.second() / 10),
+            digit_to_ascii(self.second() % 10),
         ];
-        buffer.extend_from_slice(bytes);
-        bytes.len() + self.timezone().serialize(buffer)
+        let len = if self.second_is_explicit { 8 } else { 5 };
+        buffer.extend_from_slice(&bytes[..len]);
+        len + self.timezone().serialize(buffer)
     }
 
     fn deserialize(data: &'a [u8]) -> Result<Self, Self::Error> {
+        let (time, tz) = Self::parse(data).ok_or(ERR_INVALID)?;
+        let tz = Tz::deserialize(tz)?;
+        Ok(Self { tz, ..time })
+    }
+
+    fn deserialize_lossy(data: &'a [u8]) -> Result<Self, Self::Error> {
+        let (time, tz) = Self::parse(data).ok_or(ERR_INVALID)?;
+        let tz = Tz::deserialize_lossy(tz)?;
+        Ok(Self { tz, ..time })
+    }
+}
+
+impl TzTime {
+    /// Parses the `HH:MM[:SS]` portion of `data`, returning it alongside the
+    /// rest of `data`, which must contain the timezone.
+    pub(super) fn parse(data: &[u8]) -> Option<(Self, &[u8])> {
         if data.len() < 6 || data[2] != b':' {
-            return Err(ERR_INVALID);
+            return None;
         }
-        let hour = ascii_digit_to_u32(data[0], 10) + ascii_digit_to_u32(data[1], 1);
-        let minute = ascii_digit_to_u32(data[3], 10) + ascii_digit_to_u32(data[4], 1);
-        match data[5] {
-            b':' => {
-                let second = ascii_digit_to_u32(data[6], 10) + ascii_digit_to_u32(data[7], 1);
-                Ok(TzTime {
-                    hour,
-                    minute,
-                    second,
-                    second_is_explicit: true,
-                    tz: Tz::deserialize(&data[8..]).map_err(|_| ERR_INVALID)?,
-                })
-            }
-            _ => Ok(TzTime {
-                hour,
-                minute,
-                second: 0,
-                second_is_explicit: false,
-                tz: Tz::deserialize(&data[5..]).map_err(|_| ERR_INVALID)?,
-            }),
+        let hour = two_digits(&data[0..2])?;
+        let minute = two_digits(&data[3..5])?;
+        let (second, second_is_explicit, rest) = if data[5] == b':' {
+            (two_digits(data.get(6..8)?)?, true, &data[8..])
+        } else {
+            (0, false, &data[5..])
+        };
+        if hour > 23 || minute > 59 || second > 60 {
+            return None;
         }
+        let time = Self {
+            hour,
+            minute,
+            second,
+            second_is_explicit,
+            tz: Tz::UTC,
+        };
+        Some((time, rest))
     }
 }
 
-const fn ascii_digit_to_u32(digit: u8, multiplier: u32) -> u32 {
-    (digit as u32).wrapping_sub(b'0' as u32) * multiplier
+const fn digit_to_ascii(digit: u32) -> u8 {
+    digit as u8 + b'0'
 }
 
 #[cfg(test)]
@@ -153,4 +176,23 @@ mod test {
             assert_eq!(tz_time.second(), test_case.second);
         }
     }
+
+    #[test]
+    fn serialization_is_exact() {
+        for test_case in TEST_CASES.iter() {
+            let tz_time = TzTime::deserialize(test_case.bytes).unwrap();
+            assert_eq!(&tz_time.to_bytes()[..], test_case.bytes);
+        }
+        let tz_time = TzTime::deserialize(b"23:59:60-03").unwrap();
+        assert_eq!(&tz_time.to_bytes()[..], b"23:59:60-03");
+    }
+
+    #[test]
+    fn invalid_test_cases() {
+        for s in [
+            "", "07:39", "07:3", "7:39Z", "24:00Z", "07:60Z", "07:39:Z", "07:39:1Z",
+        ] {
+            assert!(TzTime::deserialize(s.as_bytes()).is_err(), "{}", s);
+        }
+    }
 }
@@ -1,22 +1,89 @@
-use super::{FixValue, Timestamp, Tz};
+use super::{Date, FixValue, Time, Timestamp, Tz, TzTime};
 use crate::Buffer;
 
-/// A time and date combination representing local time with an offset from UTC.
-#[derive(Debug, Clone, PartialEq, Eq)]
+const ERR_INVALID: &str = "Invalid timestamp.";
+
+/// A time and date combination representing local time with an offset from
+/// UTC, i.e. `TZTimestamp` in the `YYYYMMDD-HH:MM[:SS][Z|+hh[:mm]]` format.
+///
+/// Just like [`TzTime`], the original representation is retained for exact
+/// re-encoding.
+///
+/// # Examples
+///
+/// ```
+/// use fefix::FixValue;
+/// use fefix::fix_values::TzTimestamp;
+///
+/// let tz_timestamp = TzTimestamp::deserialize(b"20060901-07:39Z").unwrap();
+/// assert_eq!(tz_timestamp.date().day(), 1);
+/// assert_eq!(tz_timestamp.time().minute(), 39);
+///
+/// let tz_timestamp = TzTimestamp::deserialize(b"20060901-02:39:10-05").unwrap();
+/// assert_eq!(tz_timestamp.timezone().offset_as_secs(), -5 * 3600);
+/// assert_eq!(&tz_timestamp.to_bytes()[..], b"20060901-02:39:10-05");
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub struct TzTimestamp {
-    timestamp: Timestamp,
-    tz: Tz,
+    date: Date,
+    time: TzTime,
 }
 
 impl TzTimestamp {
+    /// Returns the date of `self`, in local time.
+    pub fn date(&self) -> Date {
+        self.date
+    }
+
+    /// Returns the time of `self`, in local time and with timezone
+    /// information.
+    pub fn time(&self) -> TzTime {
+        self.time
+    }
+
     /// Returns the [`Timestamp`] (without timezone information) of `self`.
     pub fn timestamp(&self) -> Timestamp {
-        self.timestamp.clone()
+        // Both times are validated with the same rules.
+        let time = Time::from_hmsm(self.time.hour(), self.time.minute(), self.time.second(), 0);
+        Timestamp::new(self.date, time.unwrap())
     }
 
     /// Returns the [`Tz`] timezone information of `self`.
     pub fn timezone(&self) -> Tz {
-        self.tz
+        self.time.timezone()
+    }
+
+    /// Converts `self` into a [`chrono::DateTime`] with a fixed offset. As
+    /// `chrono` might perform additional checks that make such conversion
+    /// impossible, the return value of this function might be `None`.
+    #[cfg(feature = "utils-chrono")]
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "utils-chrono")))]
+    pub fn to_chrono(&self) -> Option<chrono::DateTime<chrono::FixedOffset>> {
+        use chrono::TimeZone;
+        let naive = self.timestamp().to_chrono_naive()?;
+        self.timezone()
+            .to_chrono_offset()
+            .from_local_datetime(&naive)
+            .single()
+    }
+
+    fn parse(data: &[u8], lossy: bool) -> Option<Self> {
+        if data.len() < 15 || data[8] != b'-' {
+            return None;
+        }
+        let date = if lossy {
+            Date::deserialize_lossy(&data[..8])
+        } else {
+            Date::deserialize(&data[..8])
+        }
+        .ok()?;
+        let time = if lossy {
+            TzTime::deserialize_lossy(&data[9..])
+        } else {
+            TzTime::deserialize(&data[9..])
+        }
+        .ok()?;
+        Some(Self { date, time })
     }
 }
 
@@ -28,10 +95,55 @@ impl<'a> FixValue<'a> for TzTimestamp {
     where
         B: Buffer,
     {
-        self.timestamp().serialize(buffer)
+        self.date.serialize(buffer) + b"-".serialize(buffer) + self.time.serialize(buffer)
+    }
+
+    fn deserialize(data: &'a [u8]) -> Result<Self, Self::Error> {
+        Self::parse(data, false).ok_or(ERR_INVALID)
+    }
+
+    fn deserialize_lossy(data: &'a [u8]) -> Result<Self, Self::Error> {
+        Self::parse(data, true).ok_or(ERR_INVALID)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn serialization_is_exact() {
+        for s in [
+            "20060901-07:39Z",
+            "20060901-02:39-05",
+            "20060901-15:39:00+08",
+            "20060901-13:09:59+05:30",
+            "20060901-13:09+00:00",
+        ] {
+            let tz_timestamp = TzTimestamp::deserialize(s.as_bytes()).unwrap();
+            assert_eq!(&tz_timestamp.to_bytes()[..], s.as_bytes());
+        }
+    }
+
+    #[test]
+    fn invalid_timestamps_are_err() {
+        for s in [
+            "20060901-07:39",
+            "20060901T07:39Z",
+            "20061301-07:39Z",
+            "0901-07:39Z",
+        ] {
+            assert!(TzTimestamp::deserialize(s.as_bytes()).is_err(), "{}", s);
+        }
     }
 
-    fn deserialize(_data: &'a [u8]) -> Result<Self, Self::Error> {
-        unimplemented!()
+    #[cfg(feature = "utils-chrono")]
+    #[test]
+    fn chrono_conversion_keeps_offset() {
+        use chrono::{TimeZone, Utc};
+        let tz_timestamp = TzTimestamp::deserialize(b"20060901-02:39:10-05").unwrap();
+        let datetime = tz_timestamp.to_chrono().unwrap();
+        assert_eq!(datetime.offset().local_minus_utc(), -5 * 3600);
+        assert_eq!(datetime, Utc.ymd(2006, 9, 1).and_hms(7, 39, 10));
     }
 }
@@ -0,0 +1,62 @@
+//! Encoding and decoding of FIX messages using FIXML.
+
+use std::error::Error;
+use std::fmt;
+
+mod transcoder;
+
+pub use transcoder::Transcoder;
+
+/// The type returned in the event of an error when encoding a FIXML document.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum EncodeError {
+    /// The message type, or one of the fields, is unknown to the dictionary,
+    /// or the message doesn't conform to it (e.g. bad `NumInGroup` values).
+    Dictionary,
+}
+
+impl fmt::Display for EncodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Inconsistency between the FIX message and encoding rules as established by the dictionary."
+        )
+    }
+}
+
+impl Error for EncodeError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        None
+    }
+}
+
+/// The type returned in the event of an error when decoding a FIXML document.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DecodeError {
+    /// Bad XML syntax.
+    Syntax,
+    /// The document is valid XML, but not a valid FIXML document.
+    Schema,
+    /// Unrecognized message type.
+    InvalidMsgType,
+    /// The data does not conform to the specified message type.
+    InvalidData,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let err = match self {
+            Self::Syntax => "Bad XML syntax.",
+            Self::Schema => "The document is valid XML, but not a valid FIXML document.",
+            Self::InvalidMsgType => "Unrecognized message type.",
+            Self::InvalidData => "The data does not conform to the specified message type.",
+        };
+        write!(f, "{}", err)
+    }
+}
+
+impl Error for DecodeError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        None
+    }
+}
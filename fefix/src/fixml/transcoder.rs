@@ -0,0 +1,613 @@
+use super::{DecodeError, EncodeError};
+use crate::dict::{self, LayoutItemKind};
+use crate::tagvalue;
+use crate::{Dictionary, TagU16};
+use nohash_hasher::IntMap;
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+const BEGIN_STRING: u16 = 8;
+const BODY_LENGTH: u16 = 9;
+const CHECK_SUM: u16 = 10;
+const MSG_TYPE: u16 = 35;
+
+/// A converter between the FIX tag-value encoding and FIXML, i.e. the XML
+/// encoding of FIX.
+///
+/// Every FIXML document has a `<FIXML>` root element, whose `v` attribute is
+/// the FIX version (e.g. `4.4` for `FIX.4.4`), and which wraps a single
+/// message element. Fields are attributes; components and repeating group
+/// entries are nested elements, the header being the `<Hdr>` element.
+/// `BeginString <8>`, `BodyLength <9>`, `MsgType <35>`, and `CheckSum <10>`
+/// are not encoded as attributes, as they're implied by the document itself.
+///
+/// Message structure and element and attribute names come from a
+/// [`Dictionary`]. XML-specific abbreviations (e.g. `Order` for
+/// `NewOrderSingle <D>`) are used when available, as is the case for FIX
+/// Orchestra dictionaries; otherwise, full names are used. Components which
+/// only consist of a repeating group are repeating elements themselves, and
+/// other groups are named after their `NumInGroup` field without the `No`
+/// prefix.
+///
+/// # Examples
+///
+/// ```
+/// use fefix::fixml::Transcoder;
+/// use fefix::tagvalue::{Config, Decoder, Encoder};
+/// use fefix::Dictionary;
+///
+/// let mut transcoder = Transcoder::new(Dictionary::fix44());
+/// let mut decoder = Decoder::<Config>::new(Dictionary::fix44());
+/// decoder.config_mut().set_separator(b'|');
+/// let data = b"8=FIX.4.4|9=27|35=0|49=A|56=B|34=12|112=X|10=061|";
+/// let message = decoder.decode(data).unwrap();
+///
+/// let fixml = transcoder.to_fixml(&message).unwrap().to_string();
+/// assert_eq!(
+///     fixml,
+///     r#"<FIXML v="4.4"><Heartbeat TestReqID="X"><Hdr SenderCompID="A" TargetCompID="B" MsgSeqNum="12"/></Heartbeat></FIXML>"#
+/// );
+///
+/// let mut encoder = Encoder::<Config>::default();
+/// encoder.config_mut().set_separator(b'|');
+/// let mut buffer = Vec::new();
+/// let tagvalue = transcoder
+///     .to_tagvalue(&fixml, &mut encoder, &mut buffer)
+///     .unwrap();
+/// assert_eq!(tagvalue, b"8=FIX.4.4|9=000027|35=0|49=A|56=B|34=12|112=X|10=253|");
+/// ```
+#[derive(Debug, Clone)]
+pub struct Transcoder {
+    dict: Dictionary,
+    // Keyed by `MsgType <35>`.
+    messages: HashMap<String, Element>,
+    msg_types_by_name: HashMap<String, String>,
+    tags_by_name: HashMap<String, TagU16>,
+    header_name: Option<String>,
+    buffer: String,
+}
+
+impl Transcoder {
+    /// Creates a new [`Transcoder`] which takes names and message structure
+    /// from `dict`.
+    pub fn new(dict: Dictionary) -> Self {
+        let header = dict.component_by_name("StandardHeader");
+        let mut messages = HashMap::new();
+        let mut msg_types_by_name = HashMap::new();
+        for message in dict.iter_messages() {
+            let msg_type = message.msg_type().to_string();
+            // Only the first scenario of each message type is used.
+            if messages.contains_key(&msg_type) {
+                continue;
+            }
+            let mut element = Element::new(message.abbr_name().unwrap_or(message.name()));
+            if let Some(header) = &header {
+                element.add_component(header);
+            }
+            element.add_items(&message.layout().collect::<Vec<_>>()[..]);
+            msg_types_by_name.insert(element.name.clone(), msg_type.clone());
+            messages.insert(msg_type, element);
+        }
+        let tags_by_name = dict
+            .iter_fields()
+            .map(|field| (field_name(&field).to_string(), field.tag()))
+            .collect();
+        let header_name = header.map(|header| component_name(&header).to_string());
+        Self {
+            dict,
+            messages,
+            msg_types_by_name,
+            tags_by_name,
+            header_name,
+            buffer: String::new(),
+        }
+    }
+
+    /// Returns an immutable reference to the [`Dictionary`] used by `self`.
+    pub fn dict(&self) -> &Dictionary {
+        &self.dict
+    }
+
+    /// Encodes a decoded tag-value `message` as a FIXML document.
+    ///
+    /// Fields which are known to the [`Dictionary`] but don't belong to the
+    /// layout of the message are attributes of the message element. Fields
+    /// which are unknown to the [`Dictionary`] can't be named and result in
+    /// an error.
+    pub fn to_fixml<'a, T>(
+        &mut self,
+        message: &'a tagvalue::Message<'a, T>,
+    ) -> Result<&str, EncodeError>
+    where
+        T: AsRef<[u8]>,
+    {
+        let mut buffer = std::mem::take(&mut self.buffer);
+        buffer.clear();
+        let result = self.write_fixml(message, &mut buffer);
+        self.buffer = buffer;
+        result.map(move |()| self.buffer.as_str())
+    }
+
+    /// Decodes the FIXML document `xml` and re-encodes it in the tag-value
+    /// format with `encoder`, into `buffer`.
+    ///
+    /// Header fields are written first; all other fields are written in
+    /// document order, with attributes before nested elements.
+    pub fn to_tagvalue<'a, C>(
+        &self,
+        xml: &str,
+        encoder: &'a mut tagvalue::Encoder<C>,
+        buffer: &'a mut Vec<u8>,
+    ) -> Result<&'a [u8], DecodeError>
+    where
+        C: tagvalue::Configure,
+    {
+        let document = roxmltree::Document::parse(xml).map_err(|_| DecodeError::Syntax)?;
+        let root = document.root_element();
+        if root.tag_name().name() != "FIXML" {
+            return Err(DecodeError::Schema);
+        }
+        let version = root.attribute("v").ok_or(DecodeError::Schema)?;
+        let begin_string = if version.starts_with(|c: char| c.is_ascii_digit()) {
+            format!("FIX.{}", version)
+        } else {
+            version.to_string()
+        };
+        let node = root
+            .children()
+            .find(|node| node.is_element())
+            .ok_or(DecodeError::Schema)?;
+        let msg_type = self
+            .msg_types_by_name
+            .get(node.tag_name().name())
+            .ok_or(DecodeError::InvalidMsgType)?;
+        let mut fields = Vec::new();
+        self.flatten(
+            &self.messages[msg_type],
+            node,
+            &mut fields,
+            self.header_name.as_deref(),
+        )?;
+        buffer.clear();
+        let mut msg = encoder.start_message(begin_string.as_bytes(), buffer, msg_type.as_bytes());
+        for (tag, value) in fields.iter() {
+            msg.set_any(*tag, value.as_bytes());
+        }
+        Ok(msg.wrap())
+    }
+
+    fn write_fixml<T>(
+        &self,
+        message: &tagvalue::Message<T>,
+        buffer: &mut String,
+    ) -> Result<(), EncodeError>
+    where
+        T: AsRef<[u8]>,
+    {
+        let fields: Vec<(TagU16, &[u8])> = message.fields().collect();
+        let field_value = |wanted: u16| {
+            fields
+                .iter()
+                .find(|(tag, _)| tag.get() == wanted)
+                .map(|(_, value)| String::from_utf8_lossy(value))
+                .ok_or(EncodeError::Dictionary)
+        };
+        let begin_string = field_value(BEGIN_STRING)?;
+        let msg_type = field_value(MSG_TYPE)?;
+        let schema = self
+            .messages
+            .get(msg_type.as_ref())
+            .ok_or(EncodeError::Dictionary)?;
+        let fields: Vec<(TagU16, &[u8])> = fields
+            .iter()
+            .copied()
+            .filter(|(tag, _)| {
+                !matches!(tag.get(), BEGIN_STRING | BODY_LENGTH | MSG_TYPE | CHECK_SUM)
+            })
+            .collect();
+        let mut element = XmlElement::new(schema.name.as_str());
+        self.fill(schema, &mut element, &fields[..], &mut 0, None)?;
+        buffer.push_str(r#"<FIXML v=""#);
+        let version = begin_string.strip_prefix("FIX.").unwrap_or(&begin_string);
+        write_escaped(buffer, version);
+        buffer.push_str(r#"">"#);
+        element.write(buffer);
+        buffer.push_str("</FIXML>");
+        Ok(())
+    }
+
+    // Moves fields from `fields[*i..]` into `element`, until the end of the
+    // group entry that starts with `delimiter` (if any).
+    fn fill<'s>(
+        &'s self,
+        schema: &'s Element,
+        element: &mut XmlElement<'s>,
+        fields: &[(TagU16, &[u8])],
+        i: &mut usize,
+        delimiter: Option<TagU16>,
+    ) -> Result<(), EncodeError> {
+        let start = *i;
+        while *i < fields.len() {
+            let (tag, value) = fields[*i];
+            let location = schema.locations.get(&tag.get());
+            if delimiter.is_some() && (location.is_none() || (*i > start && Some(tag) == delimiter))
+            {
+                break;
+            }
+            *i += 1;
+            let field = self
+                .dict
+                .field_by_tag(tag.get() as u32)
+                .ok_or(EncodeError::Dictionary)?;
+            let value = String::from_utf8_lossy(value).into_owned();
+            let location = match location {
+                Some(location) => location,
+                None => {
+                    element.attributes.push((field_name(&field), value));
+                    continue;
+                }
+            };
+            let (schema, target) = navigate(schema, element, &location.path[..]);
+            let (_, group) = match location.group {
+                Some(index) => &schema.groups[index],
+                None => {
+                    target.attributes.push((field_name(&field), value));
+                    continue;
+                }
+            };
+            let num_entries: usize = value.parse().map_err(|_| EncodeError::Dictionary)?;
+            let delimiter = fields.get(*i).map(|field| field.0);
+            for _ in 0..num_entries {
+                let entry_start = *i;
+                let mut entry = XmlElement::new(group.name.as_str());
+                self.fill(group, &mut entry, fields, i, delimiter)?;
+                if *i == entry_start {
+                    break;
+                }
+                target.children.push((None, entry));
+            }
+        }
+        Ok(())
+    }
+
+    fn flatten<'a>(
+        &self,
+        schema: &Element,
+        node: roxmltree::Node<'a, '_>,
+        fields: &mut Vec<(TagU16, Cow<'a, str>)>,
+        header_name: Option<&str>,
+    ) -> Result<(), DecodeError> {
+        let children: Vec<roxmltree::Node> =
+            node.children().filter(|child| child.is_element()).collect();
+        let is_header = |child: &roxmltree::Node| Some(child.tag_name().name()) == header_name;
+        for child in children.iter().filter(|child| is_header(child)) {
+            let header = schema
+                .component(child.tag_name().name())
+                .ok_or(DecodeError::Schema)?;
+            self.flatten(header, *child, fields, None)?;
+        }
+        for attribute in node.attributes() {
+            let tag = self
+                .tags_by_name
+                .get(attribute.name())
+                .ok_or(DecodeError::InvalidData)?;
+            fields.push((*tag, Cow::Borrowed(attribute.value())));
+        }
+        let mut j = 0;
+        while j < children.len() {
+            let child = children[j];
+            let name = child.tag_name().name();
+            j += 1;
+            if is_header(&child) {
+                continue;
+            } else if let Some(component) = schema.component(name) {
+                self.flatten(component, child, fields, None)?;
+            } else if let Some((tag, group)) = schema.groups.iter().find(|(_, g)| g.name == name) {
+                let mut entries = vec![child];
+                while j < children.len() && children[j].tag_name().name() == name {
+                    entries.push(children[j]);
+                    j += 1;
+                }
+                fields.push((*tag, Cow::Owned(entries.len().to_string())));
+                for entry in entries {
+                    self.flatten(group, entry, fields, None)?;
+                }
+            } else {
+                return Err(DecodeError::Schema);
+            }
+        }
+        Ok(())
+    }
+}
+
+// The FIXML layout of a message, a component, or a group entry.
+#[derive(Debug, Clone)]
+struct Element {
+    name: String,
+    // Where each field that can appear within `self` goes, child components
+    // included.
+    locations: IntMap<u16, Location>,
+    components: Vec<Element>,
+    // Group entries, together with their `NumInGroup` field.
+    groups: Vec<(TagU16, Element)>,
+}
+
+#[derive(Debug, Clone)]
+struct Location {
+    // Indices of the nested components which contain the field.
+    path: Vec<usize>,
+    // The index of the group within the innermost component, if the field is
+    // a `NumInGroup` field rather than an attribute.
+    group: Option<usize>,
+}
+
+impl Element {
+    fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            locations: IntMap::default(),
+            components: Vec::new(),
+            groups: Vec::new(),
+        }
+    }
+
+    fn component(&self, name: &str) -> Option<&Element> {
+        self.components
+            .iter()
+            .find(|component| component.name == name)
+    }
+
+    fn add_items(&mut self, items: &[dict::LayoutItem]) {
+        for item in items {
+            match item.kind() {
+                LayoutItemKind::Field(field) => {
+                    self.locations.entry(field.tag().get()).or_insert(Location {
+                        path: Vec::new(),
+                        group: None,
+                    });
+                }
+                LayoutItemKind::Component(component) => self.add_component(&component),
+                LayoutItemKind::Group(field, group_items) => {
+                    let name = field
+                        .abbr_name()
+                        .unwrap_or_else(|| field.name().strip_prefix("No").unwrap_or(field.name()));
+                    self.add_group(field.tag(), name, &group_items[..]);
+                }
+            }
+        }
+    }
+
+    fn add_component(&mut self, component: &dict::Component) {
+        let name = component_name(component);
+        let items: Vec<dict::LayoutItem> = component.items().collect();
+        if let [item] = &items[..] {
+            if let LayoutItemKind::Group(field, group_items) = item.kind() {
+                self.add_group(field.tag(), name, &group_items[..]);
+                return;
+            }
+        }
+        let mut child = Element::new(name);
+        child.add_items(&items[..]);
+        let index = self.components.len();
+        for (tag, location) in child.locations.iter() {
+            let mut path = vec![index];
+            path.extend_from_slice(&location.path[..]);
+            self.locations.entry(*tag).or_insert(Location {
+                path,
+                group: location.group,
+            });
+        }
+        self.components.push(child);
+    }
+
+    fn add_group(&mut self, tag: TagU16, name: &str, items: &[dict::LayoutItem]) {
+        let mut entry = Element::new(name);
+        entry.add_items(items);
+        let index = self.groups.len();
+        self.locations.entry(tag.get()).or_insert(Location {
+            path: Vec::new(),
+            group: Some(index),
+        });
+        self.groups.push((tag, entry));
+    }
+}
+
+// An element of a FIXML document that's being encoded.
+struct XmlElement<'a> {
+    name: &'a str,
+    attributes: Vec<(&'a str, String)>,
+    // Child components are keyed by their index within the schema; group
+    // entries aren't.
+    children: Vec<(Option<usize>, XmlElement<'a>)>,
+}
+
+impl<'a> XmlElement<'a> {
+    fn new(name: &'a str) -> Self {
+        Self {
+            name,
+            attributes: Vec::new(),
+            children: Vec::new(),
+        }
+    }
+
+    fn write(&self, buffer: &mut String) {
+        buffer.push('<');
+        buffer.push_str(self.name);
+        for (name, value) in self.attributes.iter() {
+            buffer.push(' ');
+            buffer.push_str(name);
+            buffer.push_str("=\"");
+            write_escaped(buffer, value);
+            buffer.push('"');
+        }
+        if self.children.is_empty() {
+            buffer.push_str("/>");
+        } else {
+            buffer.push('>');
+            for (_, child) in self.children.iter() {
+                child.write(buffer);
+            }
+            buffer.push_str("</");
+            buffer.push_str(self.name);
+            buffer.push('>');
+        }
+    }
+}
+
+// Finds (and creates, if necessary) the nested component at `path`.
+fn navigate<'s, 'e>(
+    mut schema: &'s Element,
+    mut element: &'e mut XmlElement<'s>,
+    path: &[usize],
+) -> (&'s Element, &'e mut XmlElement<'s>) {
+    for &index in path {
+        schema = &schema.components[index];
+        let position = element
+            .children
+            .iter()
+            .position(|(key, _)| *key == Some(index));
+        let position = match position {
+            Some(position) => position,
+            None => {
+                let child = XmlElement::new(schema.name.as_str());
+                element.children.push((Some(index), child));
+                element.children.len() - 1
+            }
+        };
+        element = &mut element.children[position].1;
+    }
+    (schema, element)
+}
+
+fn field_name<'a>(field: &dict::Field<'a>) -> &'a str {
+    field.abbr_name().unwrap_or(field.name())
+}
+
+fn component_name<'a>(component: &'a dict::Component) -> &'a str {
+    component.abbr_name().unwrap_or(match component.name() {
+        "StandardHeader" => "Hdr",
+        name => name,
+    })
+}
+
+fn write_escaped(buffer: &mut String, s: &str) {
+    for c in s.chars() {
+        match c {
+            '&' => buffer.push_str("&amp;"),
+            '<' => buffer.push_str("&lt;"),
+            '>' => buffer.push_str("&gt;"),
+            '"' => buffer.push_str("&quot;"),
+            c => buffer.push(c),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::definitions::fix44;
+    use crate::tagvalue::{test_decoder, Config, Encoder};
+
+    fn encoder() -> Encoder<Config> {
+        let mut encoder = Encoder::<Config>::default();
+        encoder.config_mut().set_separator(b'|');
+        encoder
+    }
+
+    fn new_order_single() -> Vec<u8> {
+        let mut encoder = encoder();
+        let mut buffer = Vec::new();
+        let mut msg = encoder.start_message(b"FIX.4.4", &mut buffer, b"D");
+        msg.set(fix44::SENDER_COMP_ID, "A");
+        msg.set(fix44::TARGET_COMP_ID, "B");
+        msg.set(fix44::MSG_SEQ_NUM, 2u32);
+        msg.set(fix44::SENDING_TIME, "20210101-00:00:00");
+        msg.set(fix44::CL_ORD_ID, "X&<\"Y\">");
+        msg.set(fix44::SIDE, "1");
+        msg.set(fix44::TRANSACT_TIME, "20210101-00:00:00");
+        msg.set(fix44::ORD_TYPE, "1");
+        msg.set(fix44::NO_PARTY_I_DS, 2u32);
+        msg.set(fix44::PARTY_ID, "P1");
+        msg.set(fix44::PARTY_ROLE, 1u32);
+        msg.set(fix44::PARTY_ID, "P2");
+        msg.set(fix44::PARTY_ID_SOURCE, "D");
+        msg.set(fix44::PARTY_ROLE, 3u32);
+        msg.set(fix44::SYMBOL, "EUR/USD");
+        msg.wrap().to_vec()
+    }
+
+    const NEW_ORDER_SINGLE: &str = concat!(
+        r#"<FIXML v="4.4"><NewOrderSingle ClOrdID="X&amp;&lt;&quot;Y&quot;&gt;" Side="1" TransactTime="20210101-00:00:00" OrdType="1">"#,
+        r#"<Hdr SenderCompID="A" TargetCompID="B" MsgSeqNum="2" SendingTime="20210101-00:00:00"/>"#,
+        r#"<Parties PartyID="P1" PartyRole="1"/><Parties PartyID="P2" PartyIDSource="D" PartyRole="3"/>"#,
+        r#"<Instrument Symbol="EUR/USD"/>"#,
+        r#"</NewOrderSingle></FIXML>"#
+    );
+
+    #[test]
+    fn components_and_groups_are_nested_elements() {
+        let mut transcoder = Transcoder::new(Dictionary::fix44());
+        let mut decoder = test_decoder();
+        let data = new_order_single();
+        let message = decoder.decode(&data[..]).unwrap();
+        assert_eq!(transcoder.to_fixml(&message), Ok(NEW_ORDER_SINGLE));
+    }
+
+    #[test]
+    fn fixml_survives_round_trip() {
+        let mut transcoder = Transcoder::new(Dictionary::fix44());
+        let mut encoder = encoder();
+        let mut buffer = Vec::new();
+        let tagvalue = transcoder
+            .to_tagvalue(NEW_ORDER_SINGLE, &mut encoder, &mut buffer)
+            .unwrap();
+        assert_eq!(tagvalue, &new_order_single()[..]);
+        let mut decoder = test_decoder();
+        let message = decoder.decode(tagvalue).unwrap();
+        assert_eq!(transcoder.to_fixml(&message), Ok(NEW_ORDER_SINGLE));
+    }
+
+    #[test]
+    fn pretty_printed_fixml_is_accepted() {
+        let transcoder = Transcoder::new(Dictionary::fix44());
+        let mut encoder = encoder();
+        let mut buffer = Vec::new();
+        let xml = r#"
+            <FIXML v="4.4">
+                <Heartbeat>
+                    <Hdr SenderCompID="A" TargetCompID="B" MsgSeqNum="12"/>
+                </Heartbeat>
+            </FIXML>
+        "#;
+        let tagvalue = transcoder
+            .to_tagvalue(xml, &mut encoder, &mut buffer)
+            .unwrap();
+        assert_eq!(tagvalue, b"8=FIX.4.4|9=000021|35=0|49=A|56=B|34=12|10=082|");
+    }
+
+    #[test]
+    fn invalid_fixml_is_refused() {
+        let transcoder = Transcoder::new(Dictionary::fix44());
+        let mut encoder = encoder();
+        let mut buffer = Vec::new();
+        let mut to_tagvalue = |xml: &str| {
+            transcoder
+                .to_tagvalue(xml, &mut encoder, &mut buffer)
+                .map(|_| ())
+        };
+        assert_eq!(to_tagvalue("<FIXML v=4.4/>"), Err(DecodeError::Syntax));
+        assert_eq!(to_tagvalue("<FIX v=\"4.4\"/>"), Err(DecodeError::Schema));
+        assert_eq!(
+            to_tagvalue("<FIXML v=\"4.4\"><Foo/></FIXML>"),
+            Err(DecodeError::InvalidMsgType)
+        );
+        assert_eq!(
+            to_tagvalue("<FIXML v=\"4.4\"><Heartbeat Foo=\"1\"/></FIXML>"),
+            Err(DecodeError::InvalidData)
+        );
+        assert_eq!(
+            to_tagvalue("<FIXML v=\"4.4\"><Heartbeat><Foo/></Heartbeat></FIXML>"),
+            Err(DecodeError::Schema)
+        );
+    }
+}
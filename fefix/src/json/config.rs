@@ -37,6 +37,36 @@ pub trait Configure: Clone + Default {
     fn pretty_print(&self) -> bool {
         false
     }
+
+    /// Decides what [`Decoder`](super::Decoder) does with fields that aren't
+    /// in the [`Dictionary`](crate::Dictionary), e.g. custom fields added by a
+    /// venue. Fields can be named either by their name or by their tag.
+    ///
+    /// This is [`UnknownFields::Preserve`] by default.
+    ///
+    /// This setting has no effect when encoding messages.
+    #[inline]
+    fn unknown_fields(&self) -> UnknownFields {
+        UnknownFields::Preserve
+    }
+}
+
+/// What to do with fields that aren't in the [`Dictionary`](crate::Dictionary)
+/// when decoding, see [`Configure::unknown_fields`].
+///
+/// Please note that [`UnknownFields`] is marked with `#[non_exhaustive]`,
+/// which future-proofs the enumeration type in case more variants are added.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum UnknownFields {
+    /// Unknown fields are an error, i.e. [`DecodeError::InvalidData`](super::DecodeError::InvalidData).
+    Strict,
+    /// Unknown fields are kept apart, in
+    /// [`Message::unknown_fields`](super::Message::unknown_fields), and are
+    /// written out again by [`Message::to_json`](super::Message::to_json).
+    Preserve,
+    /// Unknown fields are silently discarded.
+    Drop,
 }
 
 /// The canonical implementor of [`Configure`]. It simply stores configuration
@@ -44,6 +74,7 @@ pub trait Configure: Clone + Default {
 #[derive(Debug, Clone)]
 pub struct Config {
     pretty_print: bool,
+    unknown_fields: UnknownFields,
 }
 
 impl Config {
@@ -57,18 +88,28 @@ impl Config {
     pub fn set_pretty_print(&mut self, pretty_print: bool) {
         self.pretty_print = pretty_print;
     }
+
+    /// Sets [`Configure::unknown_fields`] to `unknown_fields`.
+    pub fn set_unknown_fields(&mut self, unknown_fields: UnknownFields) {
+        self.unknown_fields = unknown_fields;
+    }
 }
 
 impl Configure for Config {
     fn pretty_print(&self) -> bool {
         self.pretty_print
     }
+
+    fn unknown_fields(&self) -> UnknownFields {
+        self.unknown_fields
+    }
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
             pretty_print: false,
+            unknown_fields: UnknownFields::Preserve,
         }
     }
 }
@@ -90,4 +131,12 @@ mod test {
         config.set_pretty_print(false);
         assert_eq!(config.pretty_print(), false);
     }
+
+    #[test]
+    fn config_preserves_unknown_fields_by_default() {
+        let mut config = Config::default();
+        assert_eq!(config.unknown_fields(), UnknownFields::Preserve);
+        config.set_unknown_fields(UnknownFields::Strict);
+        assert_eq!(config.unknown_fields(), UnknownFields::Strict);
+    }
 }
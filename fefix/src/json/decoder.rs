@@ -1,11 +1,12 @@
-use super::{Config, Configure, DecodeError};
+use super::{Config, Configure, DecodeError, UnknownFields};
 use crate::dict;
 use crate::dict::FieldLocation;
 use crate::dict::IsFieldDefinition;
 use crate::tagvalue::{FieldAccess, RepeatingGroup};
 use crate::Dictionary;
 use crate::FixValue;
-use serde::{Deserialize, Serialize};
+use serde::ser::SerializeMap;
+use serde::{Deserialize, Serialize, Serializer};
 use std::borrow::{Borrow, Cow};
 use std::collections::HashMap;
 
@@ -141,6 +142,52 @@ impl<'a> Message<'a> {
             fields: self.internal.std_header.iter(),
         }
     }
+
+    /// Creates an [`Iterator`] over the fields in `location` that aren't in
+    /// the [`Dictionary`]. It's always empty unless
+    /// [`UnknownFields::Preserve`] is used. Unknown fields within repeating
+    /// groups stay inside their entries.
+    pub fn unknown_fields(&self, location: FieldLocation) -> MessageFieldsIter<'a> {
+        let fields = match location {
+            FieldLocation::Header => &self.internal.unknown_header,
+            FieldLocation::Body => &self.internal.unknown_body,
+            FieldLocation::Trailer => &self.internal.unknown_trailer,
+        };
+        MessageFieldsIter {
+            fields: fields.iter(),
+        }
+    }
+
+    /// Encodes `self` as FIX JSON again, including the fields kept by
+    /// [`UnknownFields::Preserve`]. The order of fields isn't preserved.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix::json::{Config, Decoder, UnknownFields};
+    /// use fefix::Dictionary;
+    ///
+    /// let mut config = Config::default();
+    /// config.set_unknown_fields(UnknownFields::Preserve);
+    /// let mut decoder = Decoder::with_config(Dictionary::fix44(), config);
+    /// let data = br#"{"Header":{"BeginString":"FIX.4.4","MsgType":"0"},"Body":{"VenueRef":"X1"},"Trailer":{}}"#;
+    /// let message = decoder.decode(&data[..]).unwrap();
+    /// let json = message.to_json();
+    /// assert_eq!(
+    ///     serde_json::from_str::<serde_json::Value>(&json).unwrap()["Body"]["VenueRef"],
+    ///     "X1"
+    /// );
+    /// ```
+    pub fn to_json(&self) -> String {
+        let internal = self.internal;
+        let message = MessageOut {
+            std_header: Section(&internal.std_header, &internal.unknown_header),
+            body: Section(&internal.body, &internal.unknown_body),
+            std_trailer: Section(&internal.std_trailer, &internal.unknown_trailer),
+        };
+        // Serializing strings and maps with string keys can't fail.
+        serde_json::to_string(&message).unwrap()
+    }
 }
 
 #[derive(Debug)]
@@ -203,18 +250,22 @@ where
         &mut self.config
     }
 
-    fn message_builder<'a>(&'a mut self) -> &'a mut MessageInternal<'a> {
-        self.message_builder.clear();
+    fn message_builder<'a>(
+        message_builder: &'a mut MessageInternal<'static>,
+    ) -> &'a mut MessageInternal<'a> {
+        message_builder.clear();
         unsafe {
             std::mem::transmute::<&'a mut MessageInternal<'static>, &'a mut MessageInternal<'a>>(
-                &mut self.message_builder,
+                message_builder,
             )
         }
     }
 
+    /// Decodes the FIX JSON message `data`. Fields that aren't in the
+    /// [`Dictionary`] are handled according to [`Configure::unknown_fields`].
     pub fn decode<'a>(&'a mut self, data: &'a [u8]) -> Result<Message<'a>, DecodeError> {
         let mut deserilizer = serde_json::Deserializer::from_slice(data);
-        let msg = self.message_builder();
+        let msg = Self::message_builder(&mut self.message_builder);
         MessageInternal::deserialize_in_place(&mut deserilizer, msg).map_err(|err| {
             if err.is_syntax() || err.is_eof() || err.is_io() {
                 DecodeError::Syntax
@@ -222,10 +273,74 @@ where
                 DecodeError::Schema
             }
         })?;
+        let policy = self.config.unknown_fields();
+        let dictionaries = &self.dictionaries;
+        let begin_string = msg.field_raw("BeginString", FieldLocation::Header);
+        let dict = begin_string
+            .and_then(|begin_string| dictionaries.get(begin_string))
+            .or_else(|| dictionaries.values().next())
+            .unwrap();
+        let MessageInternal {
+            std_header,
+            body,
+            std_trailer,
+            unknown_header,
+            unknown_body,
+            unknown_trailer,
+        } = msg;
+        retain_known(dict, policy, std_header, unknown_header)?;
+        retain_known(dict, policy, body, unknown_body)?;
+        retain_known(dict, policy, std_trailer, unknown_trailer)?;
         Ok(Message { internal: msg })
     }
 }
 
+// Applies `policy` to all fields of `component` that aren't in `dict`;
+// preserved fields are moved to `unknown`.
+fn retain_known<'a>(
+    dict: &Dictionary,
+    policy: UnknownFields,
+    component: &mut Component<'a>,
+    unknown: &mut Component<'a>,
+) -> Result<(), DecodeError> {
+    let names: Vec<&'a str> = component
+        .keys()
+        .filter(|name| !is_known(dict, name))
+        .copied()
+        .collect();
+    for name in names {
+        match policy {
+            UnknownFields::Strict => return Err(DecodeError::InvalidData),
+            UnknownFields::Preserve => {
+                let value = component.remove(name).unwrap();
+                unknown.insert(name, value);
+            }
+            UnknownFields::Drop => {
+                component.remove(name);
+            }
+        }
+    }
+    if policy != UnknownFields::Preserve {
+        for value in component.values_mut() {
+            if let FieldOrGroup::Group(entries) = value {
+                for entry in entries.iter_mut() {
+                    retain_known(dict, policy, entry, &mut Component::new())?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn is_known(dict: &Dictionary, name: &str) -> bool {
+    dict.field_by_name(name).is_some()
+        || name
+            .parse()
+            .ok()
+            .and_then(|tag| dict.field_by_tag(tag))
+            .is_some()
+}
+
 type Component<'a> = HashMap<&'a str, FieldOrGroup<'a>>;
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
@@ -244,6 +359,39 @@ struct MessageInternal<'a> {
     body: Component<'a>,
     #[serde(borrow, rename = "Trailer")]
     std_trailer: Component<'a>,
+    #[serde(skip)]
+    unknown_header: Component<'a>,
+    #[serde(skip)]
+    unknown_body: Component<'a>,
+    #[serde(skip)]
+    unknown_trailer: Component<'a>,
+}
+
+// The serialized form of a [`Message`], which merges unknown fields back into
+// their sections.
+#[derive(Serialize)]
+struct MessageOut<'b, 'a> {
+    #[serde(rename = "Header")]
+    std_header: Section<'b, 'a>,
+    #[serde(rename = "Body")]
+    body: Section<'b, 'a>,
+    #[serde(rename = "Trailer")]
+    std_trailer: Section<'b, 'a>,
+}
+
+struct Section<'b, 'a>(&'b Component<'a>, &'b Component<'a>);
+
+impl<'b, 'a> Serialize for Section<'b, 'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = serializer.serialize_map(Some(self.0.len() + self.1.len()))?;
+        for (name, value) in self.0.iter().chain(self.1.iter()) {
+            map.serialize_entry(name, value)?;
+        }
+        map.end()
+    }
 }
 
 impl<'a> std::ops::Drop for MessageInternal<'a> {
@@ -257,6 +405,9 @@ impl<'a> MessageInternal<'a> {
         self.std_header.clear();
         self.body.clear();
         self.std_trailer.clear();
+        self.unknown_header.clear();
+        self.unknown_body.clear();
+        self.unknown_trailer.clear();
     }
 
     fn field_raw(&self, name: &str, location: FieldLocation) -> Option<&str> {
@@ -318,6 +469,52 @@ mod test {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn unknown_fields_policies() {
+        let data = br#"{"Header":{"BeginString":"FIX.4.4","MsgType":"0","9999":"A"},"Body":{"TestReqID":"X","VenueRef":"B"},"Trailer":{}}"#;
+        let mut config = Config::default();
+        config.set_unknown_fields(UnknownFields::Strict);
+        let mut decoder = Decoder::with_config(dict_fix44(), config.clone());
+        assert!(matches!(
+            decoder.decode(&data[..]),
+            Err(DecodeError::InvalidData)
+        ));
+
+        config.set_unknown_fields(UnknownFields::Drop);
+        let mut decoder = Decoder::with_config(dict_fix44(), config.clone());
+        let message = decoder.decode(&data[..]).unwrap();
+        assert_eq!(message.unknown_fields(FieldLocation::Body).count(), 0);
+        let json: serde_json::Value = serde_json::from_str(&message.to_json()).unwrap();
+        assert!(json["Body"].get("VenueRef").is_none());
+        assert_eq!(json["Body"]["TestReqID"], "X");
+
+        config.set_unknown_fields(UnknownFields::Preserve);
+        let mut decoder = Decoder::with_config(dict_fix44(), config);
+        let message = decoder.decode(&data[..]).unwrap();
+        let unknown: Vec<&str> = message
+            .unknown_fields(FieldLocation::Header)
+            .map(|(name, _)| name)
+            .collect();
+        assert_eq!(unknown, vec!["9999"]);
+        let json: serde_json::Value = serde_json::from_str(&message.to_json()).unwrap();
+        let expected: serde_json::Value = serde_json::from_slice(&data[..]).unwrap();
+        assert_eq!(json, expected);
+    }
+
+    #[test]
+    fn unknown_fields_within_groups_are_dropped() {
+        let data = br#"{"Header":{"BeginString":"FIX.4.4","MsgType":"W"},"Body":{"NoMDEntries":[{"MDEntryType":"0","Custom":"1"}]},"Trailer":{}}"#;
+        let mut config = Config::default();
+        config.set_unknown_fields(UnknownFields::Drop);
+        let mut decoder = Decoder::with_config(dict_fix44(), config);
+        let message = decoder.decode(&data[..]).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&message.to_json()).unwrap();
+        assert_eq!(
+            json["Body"]["NoMDEntries"],
+            serde_json::json!([{"MDEntryType": "0"}])
+        );
+    }
+
     #[test]
     fn invalid_json() {
         let mut encoder = encoder_fix44();
@@ -6,6 +6,7 @@ use crate::FixValue;
 pub struct Encoder {
     buffer: Vec<u8>,
     has_message: bool,
+    is_first_field: bool,
 }
 
 impl Encoder {
@@ -13,12 +14,14 @@ impl Encoder {
         Self {
             buffer: Vec::new(),
             has_message: false,
+            is_first_field: true,
         }
     }
 
     pub fn start_message(&mut self) -> encoder_states::Initial {
         self.buffer.clear();
         self.has_message = true;
+        self.is_first_field = true;
         encoder_states::Initial { encoder: self }
     }
 }
@@ -36,9 +39,7 @@ pub mod encoder_states {
 
     impl<'a> Initial<'a> {
         pub fn with_header(self) -> StdHeader<'a> {
-            self.encoder
-                .buffer
-                .extend_from_slice(br#"{"StandardHeader":{"#);
+            self.encoder.buffer.extend_from_slice(br#"{"Header":{"#);
             StdHeader {
                 encoder: self.encoder,
             }
@@ -57,13 +58,19 @@ pub mod encoder_states {
             T: FixValue<'a>,
             F: IsFieldDefinition,
         {
-            debug_assert!(field.name().is_ascii());
             let encoder = self.encoder_mut();
-            encoder.buffer.extend_from_slice(br#"""#);
-            field.name().as_bytes().serialize(&mut encoder.buffer);
-            encoder.buffer.extend_from_slice(br#"":""#);
-            value.serialize(&mut encoder.buffer);
-            encoder.buffer.extend_from_slice(br#"""#);
+            if !encoder.is_first_field {
+                encoder.buffer.push(b',');
+            }
+            encoder.is_first_field = false;
+            let mut value_bytes = Vec::new();
+            value.serialize(&mut value_bytes);
+            // Both names and values must be escaped. Serializing a string
+            // can't fail.
+            serde_json::to_writer(&mut encoder.buffer, field.name()).unwrap();
+            encoder.buffer.push(b':');
+            let value = String::from_utf8_lossy(&value_bytes[..]);
+            serde_json::to_writer(&mut encoder.buffer, value.as_ref()).unwrap();
             self
         }
     }
@@ -78,6 +85,7 @@ pub mod encoder_states {
     impl<'a> StdHeader<'a> {
         pub fn with_body(self) -> Body<'a> {
             self.encoder.buffer.extend_from_slice(br#"},"Body":{"#);
+            self.encoder.is_first_field = true;
             Body {
                 encoder: self.encoder,
             }
@@ -107,9 +115,8 @@ pub mod encoder_states {
 
     impl<'a> Body<'a> {
         pub fn with_trailer(self) -> StdTrailer<'a> {
-            self.encoder
-                .buffer
-                .extend_from_slice(br#"},"StandardTrailer":{"#);
+            self.encoder.buffer.extend_from_slice(br#"},"Trailer":{"#);
+            self.encoder.is_first_field = true;
             StdTrailer {
                 encoder: self.encoder,
             }
@@ -175,4 +182,22 @@ mod test {
         let json = serde_json::from_str::<serde_json::Value>(message);
         assert!(json.is_ok());
     }
+
+    #[test]
+    fn fields_are_separated_and_escaped() {
+        use crate::definitions::fix44;
+        let mut encoder = Encoder::new();
+        let message = encoder
+            .start_message()
+            .with_header()
+            .set(fix44::BEGIN_STRING, "FIX.4.4")
+            .set(fix44::MSG_TYPE, "0")
+            .with_body()
+            .set(fix44::TEST_REQ_ID, "say \"hi\"")
+            .with_trailer()
+            .done();
+        let json = serde_json::from_str::<serde_json::Value>(message).unwrap();
+        assert_eq!(json["Header"]["MsgType"], "0");
+        assert_eq!(json["Body"]["TestReqID"], "say \"hi\"");
+    }
 }
@@ -0,0 +1,126 @@
+use super::Transcoder;
+use crate::tagvalue;
+use crate::Dictionary;
+use std::io;
+
+/// A streaming writer of decoded tag-value messages in the [JSON
+/// Lines](https://jsonlines.org/) format, i.e. one FIX JSON object per line.
+///
+/// Each message is written as [`Transcoder::to_json`] encodes it, with field
+/// names taken from the [`Dictionary`]. Control characters within field values
+/// are escaped, so a line never contains more than one message. This is the
+/// format that log shippers and columnar databases (e.g. Logstash,
+/// ClickHouse's `JSONEachRow`) ingest without further parsing rules.
+///
+/// [`JsonLinesWriter`] doesn't buffer by itself; wrap `W` in a
+/// [`std::io::BufWriter`] when writing to files or sockets.
+///
+/// # Examples
+///
+/// ```
+/// use fefix::json::JsonLinesWriter;
+/// use fefix::tagvalue::{Config, Decoder};
+/// use fefix::Dictionary;
+///
+/// let mut decoder = Decoder::<Config>::new(Dictionary::fix44());
+/// decoder.config_mut().set_separator(b'|');
+/// let mut writer = JsonLinesWriter::new(Dictionary::fix44(), Vec::new());
+/// let data = b"8=FIX.4.4|9=27|35=0|49=A|56=B|34=12|112=X|10=061|";
+/// let message = decoder.decode(&data[..]).unwrap();
+/// writer.write(&message).unwrap();
+///
+/// assert_eq!(
+///     writer.into_inner(),
+///     br#"{"Header":{"BeginString":"FIX.4.4","MsgType":"0","SenderCompID":"A","TargetCompID":"B","MsgSeqNum":"12"},"Body":{"TestReqID":"X"},"Trailer":{}}
+/// "#
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct JsonLinesWriter<W> {
+    transcoder: Transcoder,
+    writer: W,
+    lines: u64,
+}
+
+impl<W> JsonLinesWriter<W>
+where
+    W: io::Write,
+{
+    /// Creates a new [`JsonLinesWriter`] that resolves field names with `dict`
+    /// and writes to `writer`.
+    pub fn new(dict: Dictionary, writer: W) -> Self {
+        Self::with_transcoder(Transcoder::new(dict), writer)
+    }
+
+    /// Creates a new [`JsonLinesWriter`] that encodes messages with
+    /// `transcoder` and writes to `writer`.
+    pub fn with_transcoder(transcoder: Transcoder, writer: W) -> Self {
+        Self {
+            transcoder,
+            writer,
+            lines: 0,
+        }
+    }
+
+    /// Writes `message` as a single line, including the trailing `\n`.
+    pub fn write<'a, T>(&mut self, message: &'a tagvalue::Message<'a, T>) -> io::Result<()>
+    where
+        T: AsRef<[u8]>,
+    {
+        let json = self.transcoder.to_json(message);
+        self.writer.write_all(json.as_bytes())?;
+        self.writer.write_all(b"\n")?;
+        self.lines += 1;
+        Ok(())
+    }
+
+    /// Flushes the underlying writer.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    /// Returns the number of lines that `self` has written so far.
+    pub fn lines(&self) -> u64 {
+        self.lines
+    }
+
+    /// Returns an immutable reference to the underlying writer.
+    pub fn get_ref(&self) -> &W {
+        &self.writer
+    }
+
+    /// Returns a mutable reference to the underlying writer.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.writer
+    }
+
+    /// Consumes `self` and returns the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tagvalue::test_decoder;
+
+    #[test]
+    fn one_message_per_line() {
+        let mut decoder = test_decoder();
+        let mut writer = JsonLinesWriter::new(Dictionary::fix44(), Vec::new());
+        let data = b"8=FIX.4.4|9=27|35=0|49=A|56=B|34=12|112=X|10=061|";
+        writer.write(&decoder.decode(&data[..]).unwrap()).unwrap();
+        let data = b"8=FIX.4.4|9=36|35=0|49=A|56=B|34=13|112=line\nbreak|10=157|";
+        writer.write(&decoder.decode(&data[..]).unwrap()).unwrap();
+        assert_eq!(writer.lines(), 2);
+        let output = String::from_utf8(writer.into_inner()).unwrap();
+        let lines: Vec<serde_json::Value> = output
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["Header"]["MsgSeqNum"], "12");
+        assert_eq!(lines[1]["Body"]["TestReqID"], "line\nbreak");
+    }
+}
@@ -6,12 +6,16 @@ use std::fmt;
 mod config;
 mod decoder;
 mod encoder;
+mod json_lines;
+mod transcoder;
 
-pub use config::{Config, Configure};
+pub use config::{Config, Configure, UnknownFields};
 pub use decoder::{
     Decoder, FieldOrGroup, Message, MessageFieldsIter, MessageGroup, MessageGroupEntry,
 };
 pub use encoder::Encoder;
+pub use json_lines::JsonLinesWriter;
+pub use transcoder::Transcoder;
 
 #[doc(inline)]
 pub use encoder::encoder_states;
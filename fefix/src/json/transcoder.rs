@@ -0,0 +1,435 @@
+use super::DecodeError;
+use crate::tagvalue;
+use crate::utils::{IntMap, IntSet};
+use crate::{Dictionary, TagU16};
+use serde::de::{self, Deserialize, Deserializer, MapAccess, SeqAccess, Visitor};
+use std::borrow::Cow;
+use std::fmt;
+
+const BEGIN_STRING: u16 = 8;
+const BODY_LENGTH: u16 = 9;
+const CHECK_SUM: u16 = 10;
+const MSG_TYPE: u16 = 35;
+
+/// A converter between the FIX tag-value encoding and the FIX JSON encoding,
+/// as defined by the official FIX JSON Encoding Specification[^1].
+///
+/// JSON messages have three top-level objects, i.e. `"Header"`, `"Body"`, and
+/// `"Trailer"`, with field names as keys and field values as strings.
+/// Repeating groups are arrays of objects, keyed by the name of their
+/// `NumInGroup` field. `BodyLength <9>` and `CheckSum <10>` are omitted, as
+/// they're specific to tag-value. Field names and message structure come from
+/// a [`Dictionary`]; fields which are unknown to the [`Dictionary`] use their
+/// tag as name.
+///
+/// [^1]: [FIX JSON Encoding: Online reference.](https://www.fixtrading.org/standards/json-online/)
+///
+/// # Examples
+///
+/// ```
+/// use fefix::json::Transcoder;
+/// use fefix::tagvalue::{Config, Decoder, Encoder};
+/// use fefix::Dictionary;
+///
+/// let mut transcoder = Transcoder::new(Dictionary::fix44());
+/// let mut decoder = Decoder::<Config>::new(Dictionary::fix44());
+/// decoder.config_mut().set_separator(b'|');
+/// let data = b"8=FIX.4.4|9=27|35=0|49=A|56=B|34=12|112=X|10=061|";
+/// let message = decoder.decode(data).unwrap();
+///
+/// let json = transcoder.to_json(&message).to_string();
+/// assert_eq!(
+///     json,
+///     r#"{"Header":{"BeginString":"FIX.4.4","MsgType":"0","SenderCompID":"A","TargetCompID":"B","MsgSeqNum":"12"},"Body":{"TestReqID":"X"},"Trailer":{}}"#
+/// );
+///
+/// let mut encoder = Encoder::<Config>::default();
+/// encoder.config_mut().set_separator(b'|');
+/// let mut buffer = Vec::new();
+/// let tagvalue = transcoder
+///     .to_tagvalue(json.as_bytes(), &mut encoder, &mut buffer)
+///     .unwrap();
+/// assert_eq!(tagvalue, b"8=FIX.4.4|9=000027|35=0|49=A|56=B|34=12|112=X|10=253|");
+/// ```
+#[derive(Debug, Clone)]
+pub struct Transcoder {
+    dict: Dictionary,
+    header_tags: IntSet<u16>,
+    trailer_tags: IntSet<u16>,
+    group_members: IntMap<u16, IntSet<u16>>,
+    buffer: Vec<u8>,
+}
+
+impl Transcoder {
+    /// Creates a new [`Transcoder`] which takes field names and message
+    /// structure from `dict`.
+    pub fn new(dict: Dictionary) -> Self {
+        let mut header_tags = IntSet::default();
+        let mut trailer_tags = IntSet::default();
+        if let Some(component) = dict.component_by_name("StandardHeader") {
+            tagvalue::add_tags(&mut header_tags, component.items());
+        }
+        if let Some(component) = dict.component_by_name("StandardTrailer") {
+            tagvalue::add_tags(&mut trailer_tags, component.items());
+        }
+        Self {
+            group_members: tagvalue::group_members(&dict),
+            dict,
+            header_tags,
+            trailer_tags,
+            buffer: Vec::new(),
+        }
+    }
+
+    /// Returns an immutable reference to the [`Dictionary`] used by `self`.
+    pub fn dict(&self) -> &Dictionary {
+        &self.dict
+    }
+
+    /// Encodes a decoded tag-value `message` as FIX JSON.
+    ///
+    /// Fields are sorted into `"Header"`, `"Body"`, and `"Trailer"` according
+    /// to the `StandardHeader` and `StandardTrailer` components of the
+    /// [`Dictionary`], and otherwise keep their original order.
+    pub fn to_json<'a, T>(&mut self, message: &'a tagvalue::Message<'a, T>) -> &str
+    where
+        T: AsRef<[u8]>,
+    {
+        let fields: Vec<(TagU16, &[u8])> = message
+            .fields()
+            .filter(|(tag, _)| tag.get() != BODY_LENGTH && tag.get() != CHECK_SUM)
+            .collect();
+        let mut header = Vec::new();
+        let mut body = Vec::new();
+        let mut trailer = Vec::new();
+        let mut i = 0;
+        while i < fields.len() {
+            let (tag, value) = fields[i];
+            i += 1;
+            let node = self.parse_node(tag, value, &fields[..], &mut i);
+            if self.header_tags.contains(&tag.get()) {
+                header.push((tag, node));
+            } else if self.trailer_tags.contains(&tag.get()) {
+                trailer.push((tag, node));
+            } else {
+                body.push((tag, node));
+            }
+        }
+        let mut buffer = std::mem::take(&mut self.buffer);
+        buffer.clear();
+        buffer.extend_from_slice(br#"{"Header":"#);
+        self.write_component(&mut buffer, &header[..]);
+        buffer.extend_from_slice(br#","Body":"#);
+        self.write_component(&mut buffer, &body[..]);
+        buffer.extend_from_slice(br#","Trailer":"#);
+        self.write_component(&mut buffer, &trailer[..]);
+        buffer.push(b'}');
+        self.buffer = buffer;
+        // Only valid UTF-8 was written.
+        std::str::from_utf8(&self.buffer[..]).unwrap()
+    }
+
+    /// Decodes the FIX JSON message `data` and re-encodes it in the tag-value
+    /// format with `encoder`, into `buffer`.
+    ///
+    /// `"Header"` must contain both `BeginString` and `MsgType`; all other
+    /// fields are written in their original order, header first and trailer
+    /// last. Fields must be named according to the [`Dictionary`] of `self`,
+    /// or by their tag.
+    pub fn to_tagvalue<'a, C>(
+        &self,
+        data: &[u8],
+        encoder: &'a mut tagvalue::Encoder<C>,
+        buffer: &'a mut Vec<u8>,
+    ) -> Result<&'a [u8], DecodeError>
+    where
+        C: tagvalue::Configure,
+    {
+        let message: JsonComponent =
+            serde_json::from_slice(data).map_err(|err| match err.classify() {
+                serde_json::error::Category::Data => DecodeError::Schema,
+                _ => DecodeError::Syntax,
+            })?;
+        let section = |name: &str| {
+            message.0.iter().find_map(|(key, value)| match value {
+                JsonValue::Component(component) if key == name => Some(component),
+                _ => None,
+            })
+        };
+        let header = section("Header").ok_or(DecodeError::Schema)?;
+        let body = section("Body").ok_or(DecodeError::Schema)?;
+        let trailer = section("Trailer");
+        let mut fields = Vec::new();
+        for component in [Some(header), Some(body), trailer].iter().flatten() {
+            self.flatten(component, &mut fields)?;
+        }
+        let field_value = |wanted: u16| {
+            fields
+                .iter()
+                .find(|(tag, _)| tag.get() == wanted)
+                .map(|(_, value)| value.as_bytes())
+                .ok_or(DecodeError::Schema)
+        };
+        let begin_string = field_value(BEGIN_STRING)?;
+        let msg_type = field_value(MSG_TYPE)?;
+        buffer.clear();
+        let mut msg = encoder.start_message(begin_string, buffer, msg_type);
+        for (tag, value) in fields.iter() {
+            match tag.get() {
+                BEGIN_STRING | BODY_LENGTH | MSG_TYPE | CHECK_SUM => {}
+                _ => msg.set_any(*tag, value.as_bytes()),
+            }
+        }
+        Ok(msg.wrap())
+    }
+
+    fn parse_node<'a>(
+        &self,
+        tag: TagU16,
+        value: &'a [u8],
+        fields: &[(TagU16, &'a [u8])],
+        i: &mut usize,
+    ) -> Node<'a> {
+        let members = match self.group_members.get(&tag.get()) {
+            Some(members) => members,
+            None => return Node::Field(value),
+        };
+        let num_entries = match std::str::from_utf8(value)
+            .ok()
+            .and_then(|s| s.parse::<usize>().ok())
+        {
+            Some(n) => n,
+            None => return Node::Field(value),
+        };
+        let delimiter = fields.get(*i).map(|field| field.0);
+        let mut entries: Vec<Vec<(TagU16, Node)>> = Vec::new();
+        while num_entries > 0 && *i < fields.len() && members.contains(&fields[*i].0.get()) {
+            let (tag, value) = fields[*i];
+            if Some(tag) == delimiter {
+                if entries.len() == num_entries {
+                    break;
+                }
+                entries.push(Vec::new());
+            }
+            *i += 1;
+            let node = self.parse_node(tag, value, fields, i);
+            entries.last_mut().unwrap().push((tag, node));
+        }
+        Node::Group(entries)
+    }
+
+    fn write_component(&self, buffer: &mut Vec<u8>, items: &[(TagU16, Node)]) {
+        buffer.push(b'{');
+        for (i, (tag, node)) in items.iter().enumerate() {
+            if i > 0 {
+                buffer.push(b',');
+            }
+            match self.dict.field_by_tag(tag.get() as u32) {
+                Some(field) => write_json_str(buffer, field.name()),
+                None => write_json_str(buffer, &tag.to_string()),
+            }
+            buffer.push(b':');
+            match node {
+                Node::Field(value) => write_json_str(buffer, &String::from_utf8_lossy(value)),
+                Node::Group(entries) => {
+                    buffer.push(b'[');
+                    for (j, entry) in entries.iter().enumerate() {
+                        if j > 0 {
+                            buffer.push(b',');
+                        }
+                        self.write_component(buffer, &entry[..]);
+                    }
+                    buffer.push(b']');
+                }
+            }
+        }
+        buffer.push(b'}');
+    }
+
+    fn flatten<'a>(
+        &self,
+        component: &'a JsonComponent,
+        fields: &mut Vec<(TagU16, Cow<'a, str>)>,
+    ) -> Result<(), DecodeError> {
+        for (name, value) in component.0.iter() {
+            let tag = self.tag_by_name(name)?;
+            match value {
+                JsonValue::Field(value) => fields.push((tag, Cow::Borrowed(value.as_str()))),
+                JsonValue::Group(entries) => {
+                    fields.push((tag, Cow::Owned(entries.len().to_string())));
+                    for entry in entries {
+                        self.flatten(entry, fields)?;
+                    }
+                }
+                JsonValue::Component(_) => return Err(DecodeError::Schema),
+            }
+        }
+        Ok(())
+    }
+
+    fn tag_by_name(&self, name: &str) -> Result<TagU16, DecodeError> {
+        match self.dict.field_by_name(name) {
+            Some(field) => Ok(field.tag()),
+            None => name
+                .parse()
+                .ok()
+                .and_then(TagU16::new)
+                .ok_or(DecodeError::InvalidData),
+        }
+    }
+}
+
+// A (possibly nested) field value within a tag-value message.
+enum Node<'a> {
+    Field(&'a [u8]),
+    Group(Vec<Vec<(TagU16, Node<'a>)>>),
+}
+
+fn write_json_str(buffer: &mut Vec<u8>, s: &str) {
+    // Serializing a string can't fail.
+    serde_json::to_writer(buffer, s).unwrap();
+}
+
+// An object within a FIX JSON message. Unlike `serde_json::Value`, it
+// preserves the order of keys, which matters for tag-value encoding.
+struct JsonComponent(Vec<(String, JsonValue)>);
+
+enum JsonValue {
+    Field(String),
+    Group(Vec<JsonComponent>),
+    Component(JsonComponent),
+}
+
+impl<'de> Deserialize<'de> for JsonComponent {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        match JsonValue::deserialize(deserializer)? {
+            JsonValue::Component(component) => Ok(component),
+            _ => Err(de::Error::custom("expected a JSON object")),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for JsonValue {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(JsonValueVisitor)
+    }
+}
+
+struct JsonValueVisitor;
+
+impl<'de> Visitor<'de> for JsonValueVisitor {
+    type Value = JsonValue;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a string, an array of objects, or an object")
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(JsonValue::Field(value.to_string()))
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut entries = Vec::new();
+        while let Some(entry) = seq.next_element::<JsonComponent>()? {
+            entries.push(entry);
+        }
+        Ok(JsonValue::Group(entries))
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut items = Vec::new();
+        while let Some(key) = map.next_key::<String>()? {
+            items.push((key, map.next_value::<JsonValue>()?));
+        }
+        Ok(JsonValue::Component(JsonComponent(items)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tagvalue::{test_decoder, Config, Encoder};
+
+    const MESSAGE_SIMPLE: &str = include_str!("test_data/message_simple.json");
+
+    fn transcoder() -> Transcoder {
+        Transcoder::new(Dictionary::fix44())
+    }
+
+    fn encoder() -> Encoder<Config> {
+        let mut encoder = Encoder::<Config>::default();
+        encoder.config_mut().set_separator(b'|');
+        encoder
+    }
+
+    #[test]
+    fn json_with_groups_survives_round_trip() {
+        let mut transcoder = transcoder();
+        let mut encoder = encoder();
+        let mut decoder = test_decoder();
+        let mut buffer = Vec::new();
+        let tagvalue = transcoder
+            .to_tagvalue(MESSAGE_SIMPLE.as_bytes(), &mut encoder, &mut buffer)
+            .unwrap();
+        let message = decoder.decode(tagvalue).unwrap();
+        let json = transcoder.to_json(&message);
+        let expected: serde_json::Value = serde_json::from_str(MESSAGE_SIMPLE).unwrap();
+        let actual: serde_json::Value = serde_json::from_str(json).unwrap();
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn groups_are_flattened_in_order() {
+        let transcoder = transcoder();
+        let mut encoder = encoder();
+        let mut buffer = Vec::new();
+        let tagvalue = transcoder
+            .to_tagvalue(MESSAGE_SIMPLE.as_bytes(), &mut encoder, &mut buffer)
+            .unwrap();
+        let tagvalue = std::str::from_utf8(tagvalue).unwrap();
+        assert!(tagvalue.contains("|268=2|269=0|270=1.50|271=75|273=21:14:38.688|269=1|"));
+    }
+
+    #[test]
+    fn unknown_fields_use_tags_as_names() {
+        let mut transcoder = transcoder();
+        let mut decoder = test_decoder();
+        let data = b"8=FIX.4.4|9=20|35=0|9999=a\"b|112=X|10=104|";
+        let message = decoder.decode(&data[..]).unwrap();
+        let json: serde_json::Value = serde_json::from_str(transcoder.to_json(&message)).unwrap();
+        assert_eq!(json["Body"]["9999"], "a\"b");
+        assert_eq!(json["Body"]["TestReqID"], "X");
+    }
+
+    #[test]
+    fn header_is_mandatory() {
+        let transcoder = transcoder();
+        let mut encoder = encoder();
+        let mut buffer = Vec::new();
+        let data = br#"{"Body":{},"Trailer":{}}"#;
+        assert!(matches!(
+            transcoder.to_tagvalue(&data[..], &mut encoder, &mut buffer),
+            Err(DecodeError::Schema)
+        ));
+        let data = br#"{"Header":{"BeginString":"FIX.4.4","MsgType":"0"},"Body":{"Foo":"1"}}"#;
+        assert!(matches!(
+            transcoder.to_tagvalue(&data[..], &mut encoder, &mut buffer),
+            Err(DecodeError::InvalidData)
+        ));
+    }
+}
@@ -32,6 +32,13 @@
 //! - `fix40`, `fix41`, `fix42`, `fix43`, `fix44`, `fix50`, `fix50sp1`,
 //! `fix50sp2`, `fixt11` – Ergonomic utilities for the respective FIX versions.
 //! - `fixs` – FIX-over-TLS support.
+//! - `std` (enabled by default) – Everything that needs an operating system:
+//!   [`std::io`] integrations, the [`session`] layer, XML parsing of
+//!   [`Dictionary`] specifications, the encodings other than [`tagvalue`], and
+//!   all the `utils-*` integrations save `utils-chrono`, which `std` enables
+//!   for the [`session`] layer but also works without it. With
+//!   `default-features = false`, FerrumFIX is `#![no_std]` and only requires
+//!   `alloc`.
 //!
 //! # FAQ
 //!
@@ -74,6 +81,15 @@
 //! generation utilities that you call in your `build.rs`. The [`definitions`]
 //! module includes the code generation artifacts for common FIX versions.
 //!
+//! If you'd rather vendor generated code than write a build script, the
+//! `fefix-codegen` binary (feature `codegen`) does the same from the command
+//! line:
+//!
+//! ```text
+//! $ cargo install fefix --features codegen --bin fefix-codegen
+//! $ fefix-codegen VENUE44.xml --out-dir src/ --module-name venue --messages
+//! ```
+//!
 //! # External resources
 //!
 //! - [`https://fixtrading.org/standards`](https://fixtrading.org/standards).
@@ -101,11 +117,24 @@
 )]
 // Only enables the `doc_cfg` feature when its feature is defined.
 #![cfg_attr(doc_cfg, feature(doc_cfg))]
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+#[cfg_attr(not(feature = "std"), macro_use)]
+extern crate alloc;
 
+// Allows `#[derive(FixMessage)]` to refer to `fefix` from within `fefix`.
+#[allow(unused_extern_crates)]
+extern crate self as fefix;
+
+#[cfg(feature = "app")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "app")))]
+pub mod app;
 mod buffer;
+mod digits;
 mod fefix_core;
 mod fix_value;
 pub mod fix_values;
+mod scan;
 mod utils;
 #[cfg(feature = "codegen")]
 #[cfg_attr(doc_cfg, doc(cfg(feature = "codegen")))]
@@ -113,14 +142,22 @@ pub use fefix_core::codegen;
 pub use fefix_core::dict;
 pub use fefix_core::TagU16;
 pub mod definitions;
+#[cfg(feature = "fixml-encoding")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "fixml-encoding")))]
+pub mod fixml;
 #[cfg(feature = "json-encoding")]
 #[cfg_attr(doc_cfg, doc(cfg(feature = "json-encoding")))]
 pub mod json;
 pub mod prelude;
+#[cfg(feature = "sbe-encoding")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "sbe-encoding")))]
+pub mod sbe;
+#[cfg(feature = "std")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "std")))]
 pub mod session;
 pub mod tagvalue;
 
-pub use buffer::Buffer;
+pub use buffer::{Buffer, CapacityError, SliceBuffer};
 pub use dict::Dictionary;
 // We don't derive macros to pollute the docs.
 #[doc(hidden)]
@@ -141,3 +178,66 @@ pub enum OptError<E> {
     /// Data is present but there's some other error.
     Other(E),
 }
+
+/// Conversions from [`OptResult`] to [`Option`], for code that treats missing
+/// data as normal rather than as an error.
+///
+/// # Examples
+///
+/// ```
+/// use fefix::prelude::*;
+/// use fefix::tagvalue::{Config, Configure, Decoder};
+/// use fefix::OptResultExt;
+///
+/// let mut decoder = Decoder::<Config>::new(Dictionary::fix44());
+/// decoder.config_mut().set_separator(b'|');
+/// let message = decoder.decode(&b"8=FIX.4.4|9=5|35=0|10=000|"[..]).unwrap();
+/// let test_req_id = message.fv::<&str, _>(fix44::TEST_REQ_ID);
+/// assert_eq!(test_req_id.clone().into_opt(), None);
+/// assert_eq!(test_req_id.transpose_opt(), Ok(None));
+/// ```
+pub trait OptResultExt<T, E> {
+    /// Turns [`OptError::None`] into [`None`] and everything else into
+    /// [`Some`].
+    fn into_opt(self) -> Option<Result<T, E>>;
+
+    /// Turns [`OptError::None`] into `Ok(None)`, e.g. to propagate the other
+    /// errors with `?`.
+    fn transpose_opt(self) -> Result<Option<T>, E>;
+}
+
+impl<T, E> OptResultExt<T, E> for OptResult<T, E> {
+    fn into_opt(self) -> Option<Result<T, E>> {
+        match self {
+            Ok(value) => Some(Ok(value)),
+            Err(OptError::Other(err)) => Some(Err(err)),
+            Err(OptError::None) => None,
+        }
+    }
+
+    fn transpose_opt(self) -> Result<Option<T>, E> {
+        self.into_opt().transpose()
+    }
+}
+
+/// Conversions to [`OptResult`], e.g. from the return types of `_opt` getters.
+pub trait IntoOptResult<T, E> {
+    /// Turns missing data into [`OptError::None`].
+    fn into_opt_result(self) -> OptResult<T, E>;
+}
+
+impl<T, E> IntoOptResult<T, E> for Option<Result<T, E>> {
+    fn into_opt_result(self) -> OptResult<T, E> {
+        match self {
+            Some(Ok(value)) => Ok(value),
+            Some(Err(err)) => Err(OptError::Other(err)),
+            None => Err(OptError::None),
+        }
+    }
+}
+
+impl<T, E> IntoOptResult<T, E> for Result<Option<T>, E> {
+    fn into_opt_result(self) -> OptResult<T, E> {
+        self.transpose().into_opt_result()
+    }
+}
@@ -4,6 +4,8 @@ pub use crate::dict::Dictionary;
 pub use crate::dict::IsFieldDefinition;
 pub use crate::tagvalue::FieldAccess;
 pub use crate::FixValue;
+pub use crate::IntoOptResult;
+pub use crate::OptResultExt;
 pub use crate::TagU16;
 
 #[cfg(feature = "fix40")]
@@ -0,0 +1,372 @@
+use super::schema::{BlockDef, FieldDef, GroupDef};
+use super::value::{read_field, read_uint};
+use super::{DecodeError, MessageDef, Schema, Value};
+
+/// An SBE decoder, which interprets messages according to a [`Schema`].
+///
+/// Decoding is zero-copy: [`Message`] and its groups are flyweights over the
+/// original data, and values are only read when accessed. The whole message
+/// structure is validated upfront, so that accessors never fail on
+/// malformed data.
+///
+/// # Examples
+///
+/// ```
+/// use fefix::sbe::{Decoder, Encoder, Schema, Value};
+///
+/// let xml = r#"
+///     <sbe:messageSchema xmlns:sbe="http://fixprotocol.io/2016/sbe" id="1">
+///         <types>
+///             <composite name="messageHeader">
+///                 <type name="blockLength" primitiveType="uint16"/>
+///                 <type name="templateId" primitiveType="uint16"/>
+///                 <type name="schemaId" primitiveType="uint16"/>
+///                 <type name="version" primitiveType="uint16"/>
+///             </composite>
+///         </types>
+///         <sbe:message name="Heartbeat" id="1">
+///             <field name="timestamp" id="1" type="uint64"/>
+///         </sbe:message>
+///     </sbe:messageSchema>
+/// "#;
+/// let schema = Schema::from_xml(xml).unwrap();
+/// let encoder = Encoder::new(schema.clone());
+/// let mut buffer = Vec::new();
+/// let mut msg = encoder.start_message("Heartbeat", &mut buffer).unwrap();
+/// msg.set("timestamp", 1_600_000_000u64).unwrap();
+/// let data = msg.wrap().unwrap();
+/// assert_eq!(data.len(), 16);
+///
+/// let decoder = Decoder::new(schema);
+/// let message = decoder.decode(data).unwrap();
+/// assert_eq!(message.name(), "Heartbeat");
+/// assert_eq!(message.field("timestamp"), Some(Value::UInt(1_600_000_000)));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Decoder {
+    schema: Schema,
+}
+
+impl Decoder {
+    /// Creates a new [`Decoder`] for messages of `schema`.
+    pub fn new(schema: Schema) -> Self {
+        Self { schema }
+    }
+
+    /// Returns an immutable reference to the [`Schema`] used by `self`.
+    pub fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    /// Decodes the message at the start of `data`. Any bytes after the end of
+    /// the message are ignored; see [`Message::len`].
+    pub fn decode<'a>(&'a self, data: &'a [u8]) -> Result<Message<'a>, DecodeError> {
+        let schema = &self.schema;
+        let header = &schema.header;
+        if data.len() < header.size {
+            return Err(DecodeError::Incomplete);
+        }
+        let read = |field: &FieldDef| {
+            read_uint(
+                &data[field.offset..field.offset + field.size()],
+                schema.byte_order(),
+            )
+        };
+        if read(&header.schema_id) != schema.id() as u64 {
+            return Err(DecodeError::InvalidSchemaId);
+        }
+        let def = schema
+            .message_by_id(read(&header.template_id) as u16)
+            .ok_or(DecodeError::InvalidTemplateId)?;
+        let block = Block {
+            schema,
+            def: &def.block,
+            data,
+            start: header.size,
+            block_length: read(&header.block_length) as usize,
+            version: read(&header.version) as u16,
+        };
+        let end = block.validate()?;
+        Ok(Message { def, block, end })
+    }
+}
+
+/// A decoded SBE message, which borrows data from its [`Decoder`] and the
+/// original bytes.
+#[derive(Debug, Copy, Clone)]
+pub struct Message<'a> {
+    def: &'a MessageDef,
+    block: Block<'a>,
+    end: usize,
+}
+
+impl<'a> Message<'a> {
+    /// Returns the name of the message type of `self`.
+    pub fn name(&self) -> &'a str {
+        self.def.name()
+    }
+
+    /// Returns the template ID of `self`.
+    pub fn template_id(&self) -> u16 {
+        self.def.id()
+    }
+
+    /// Returns the schema version that `self` was encoded with, i.e. its
+    /// *acting version*.
+    pub fn version(&self) -> u16 {
+        self.block.version
+    }
+
+    /// Returns the length of `self` in bytes, header included.
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> usize {
+        self.end
+    }
+
+    /// Returns the value of the fixed-length field `name`. [`None`] is
+    /// returned if the field doesn't exist, is null, or is not present in the
+    /// acting version of `self`.
+    pub fn field(&self, name: &str) -> Option<Value<'a>> {
+        self.block.field(name)
+    }
+
+    /// Returns the repeating group `name`, if present.
+    pub fn group(&self, name: &str) -> Option<Group<'a>> {
+        self.block.group(name)
+    }
+
+    /// Returns the contents of the variable-length data field `name`, if
+    /// present.
+    pub fn data(&self, name: &str) -> Option<&'a [u8]> {
+        self.block.data(name)
+    }
+}
+
+/// A repeating group within a [`Message`] or another [`GroupEntry`].
+#[derive(Debug, Copy, Clone)]
+pub struct Group<'a> {
+    entry: Block<'a>,
+    len: usize,
+}
+
+impl<'a> Group<'a> {
+    /// Returns the number of entries of `self`.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if `self` has no entries; `false` otherwise.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns an [`Iterator`] over all entries of `self`.
+    pub fn entries(&self) -> GroupEntries<'a> {
+        GroupEntries {
+            next: self.entry,
+            remaining: self.len,
+        }
+    }
+}
+
+/// An [`Iterator`] over the entries of a [`Group`].
+#[derive(Debug, Clone)]
+pub struct GroupEntries<'a> {
+    next: Block<'a>,
+    remaining: usize,
+}
+
+impl<'a> Iterator for GroupEntries<'a> {
+    type Item = GroupEntry<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        let entry = self.next;
+        self.next.start = entry.end();
+        Some(GroupEntry { block: entry })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+/// A single entry of a [`Group`].
+#[derive(Debug, Copy, Clone)]
+pub struct GroupEntry<'a> {
+    block: Block<'a>,
+}
+
+impl<'a> GroupEntry<'a> {
+    /// Returns the value of the fixed-length field `name`. See
+    /// [`Message::field`].
+    pub fn field(&self, name: &str) -> Option<Value<'a>> {
+        self.block.field(name)
+    }
+
+    /// Returns the nested repeating group `name`, if present.
+    pub fn group(&self, name: &str) -> Option<Group<'a>> {
+        self.block.group(name)
+    }
+
+    /// Returns the contents of the variable-length data field `name`, if
+    /// present.
+    pub fn data(&self, name: &str) -> Option<&'a [u8]> {
+        self.block.data(name)
+    }
+}
+
+// A fixed-length block, followed by its groups and data fields.
+#[derive(Debug, Copy, Clone)]
+struct Block<'a> {
+    schema: &'a Schema,
+    def: &'a BlockDef,
+    data: &'a [u8],
+    start: usize,
+    block_length: usize,
+    version: u16,
+}
+
+impl<'a> Block<'a> {
+    fn field(&self, name: &str) -> Option<Value<'a>> {
+        let field = self.def.field(name)?;
+        if field.since_version > self.version || field.offset + field.size() > self.block_length {
+            return None;
+        }
+        let block = &self.data[self.start..self.start + self.block_length];
+        read_field(field, block, self.schema.byte_order())
+    }
+
+    fn group(&self, name: &str) -> Option<Group<'a>> {
+        let mut pos = self.start + self.block_length;
+        for group in self.def.groups.iter() {
+            if group.since_version > self.version {
+                if group.name == name {
+                    return None;
+                }
+                continue;
+            }
+            let (group_value, end) = self.read_group(group, pos);
+            if group.name == name {
+                return Some(group_value);
+            }
+            pos = end;
+        }
+        None
+    }
+
+    fn data(&self, name: &str) -> Option<&'a [u8]> {
+        let mut pos = self.groups_end();
+        for data in self.def.data.iter() {
+            if data.since_version > self.version {
+                continue;
+            }
+            let start = pos + data.length.size();
+            let len = self.uint(&data.length, pos) as usize;
+            if data.name == name {
+                return Some(&self.data[start..start + len]);
+            }
+            pos = start + len;
+        }
+        None
+    }
+
+    // The position right after the last group of `self`.
+    fn groups_end(&self) -> usize {
+        let mut pos = self.start + self.block_length;
+        for group in self.present_groups() {
+            pos = self.read_group(group, pos).1;
+        }
+        pos
+    }
+
+    // The position right after `self` and all of its groups and data.
+    fn end(&self) -> usize {
+        let mut pos = self.groups_end();
+        for data in self.def.data.iter() {
+            if data.since_version <= self.version {
+                pos += data.length.size() + self.uint(&data.length, pos) as usize;
+            }
+        }
+        pos
+    }
+
+    // Returns the group that starts at `pos`, and where it ends.
+    fn read_group(&self, group: &'a GroupDef, pos: usize) -> (Group<'a>, usize) {
+        let entry = Block {
+            def: &group.block,
+            start: pos + group.dimension_size,
+            block_length: self.uint(&group.block_length, pos) as usize,
+            ..*self
+        };
+        let group = Group {
+            entry,
+            len: self.uint(&group.num_in_group, pos) as usize,
+        };
+        let end = group
+            .entries()
+            .last()
+            .map_or(entry.start, |entry| entry.block.end());
+        (group, end)
+    }
+
+    fn present_groups(&self) -> impl Iterator<Item = &'a GroupDef> {
+        let version = self.version;
+        self.def
+            .groups
+            .iter()
+            .filter(move |group| group.since_version <= version)
+    }
+
+    fn uint(&self, field: &FieldDef, pos: usize) -> u64 {
+        let start = pos + field.offset;
+        read_uint(
+            &self.data[start..start + field.size()],
+            self.schema.byte_order(),
+        )
+    }
+
+    // Like `end`, but checks all bounds.
+    fn validate(&self) -> Result<usize, DecodeError> {
+        let mut pos = self.start + self.block_length;
+        if pos > self.data.len() {
+            return Err(DecodeError::Incomplete);
+        }
+        for group in self.present_groups() {
+            if pos + group.dimension_size > self.data.len() {
+                return Err(DecodeError::Incomplete);
+            }
+            let block_length = self.uint(&group.block_length, pos) as usize;
+            let num_entries = self.uint(&group.num_in_group, pos) as usize;
+            pos += group.dimension_size;
+            if block_length.saturating_mul(num_entries) > self.data.len() - pos {
+                return Err(DecodeError::Incomplete);
+            }
+            for _ in 0..num_entries {
+                let entry = Block {
+                    def: &group.block,
+                    start: pos,
+                    block_length,
+                    ..*self
+                };
+                pos = entry.validate()?;
+            }
+        }
+        for data in self.def.data.iter() {
+            if data.since_version > self.version {
+                continue;
+            }
+            if pos + data.length.size() > self.data.len() {
+                return Err(DecodeError::Incomplete);
+            }
+            pos += data.length.size() + self.uint(&data.length, pos) as usize;
+            if pos > self.data.len() {
+                return Err(DecodeError::Incomplete);
+            }
+        }
+        Ok(pos)
+    }
+}
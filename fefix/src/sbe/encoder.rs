@@ -0,0 +1,280 @@
+use super::schema::{BlockDef, FieldDef, GroupDef};
+use super::value::{write_field, write_null, write_uint};
+use super::{EncodeError, Schema, Value};
+use crate::Buffer;
+
+/// An SBE encoder, which serializes messages according to a [`Schema`].
+///
+/// SBE requires fixed-length fields first, then repeating groups, then
+/// variable-length data fields, in schema order. Fixed-length fields can be
+/// set at any time, while groups and data fields must be written in schema
+/// order; groups and data fields which are skipped are written as empty.
+/// Fields which are never set are null (if optional) or zero.
+///
+/// # Examples
+///
+/// Please refer to [`Decoder`](super::Decoder).
+#[derive(Debug, Clone)]
+pub struct Encoder {
+    schema: Schema,
+}
+
+impl Encoder {
+    /// Creates a new [`Encoder`] for messages of `schema`.
+    pub fn new(schema: Schema) -> Self {
+        Self { schema }
+    }
+
+    /// Returns an immutable reference to the [`Schema`] used by `self`.
+    pub fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    /// Starts encoding a message of type `name`, appending it to `buffer`.
+    pub fn start_message<'a, B>(
+        &'a self,
+        name: &str,
+        buffer: &'a mut B,
+    ) -> Result<EncoderHandle<'a, B>, EncodeError>
+    where
+        B: Buffer,
+    {
+        let schema = &self.schema;
+        let def = schema
+            .message_by_name(name)
+            .ok_or(EncodeError::UnknownMessage)?;
+        let header = &schema.header;
+        let start = buffer.len();
+        buffer.resize(start + header.size, 0);
+        {
+            let bytes = &mut buffer.as_mut_slice()[start..];
+            let mut write = |field: &FieldDef, n: u64| {
+                let field_start = field.offset;
+                write_uint(
+                    &mut bytes[field_start..field_start + field.size()],
+                    n,
+                    schema.byte_order(),
+                );
+            };
+            write(&header.block_length, def.block_length() as u64);
+            write(&header.template_id, def.id() as u64);
+            write(&header.schema_id, schema.id() as u64);
+            write(&header.version, schema.version() as u64);
+        }
+        let mut handle = EncoderHandle {
+            schema,
+            buffer,
+            start,
+            frames: Vec::new(),
+        };
+        let block_start = handle.append_block(&def.block, def.block_length());
+        handle.frames.push(Frame {
+            def: &def.block,
+            block_start: Some(block_start),
+            next_var: 0,
+            group: None,
+        });
+        Ok(handle)
+    }
+}
+
+/// An ongoing SBE message encoding, started by [`Encoder::start_message`].
+#[derive(Debug)]
+pub struct EncoderHandle<'a, B> {
+    schema: &'a Schema,
+    buffer: &'a mut B,
+    start: usize,
+    // The message itself and any open groups, innermost last.
+    frames: Vec<Frame<'a>>,
+}
+
+#[derive(Debug)]
+struct Frame<'a> {
+    def: &'a BlockDef,
+    // The start of the current block, if any. Groups have no current block
+    // until their first entry.
+    block_start: Option<usize>,
+    // The next group or data field to write, indexing groups first.
+    next_var: usize,
+    // The definition of the group and its remaining entries.
+    group: Option<(&'a GroupDef, usize)>,
+}
+
+impl<'a, B> EncoderHandle<'a, B>
+where
+    B: Buffer,
+{
+    /// Sets the fixed-length field `name` of the current message or group
+    /// entry to `value`.
+    pub fn set<'b, V>(&mut self, name: &str, value: V) -> Result<(), EncodeError>
+    where
+        V: Into<Value<'b>>,
+    {
+        let frame = self.current()?;
+        let block_start = frame.block_start.ok_or(EncodeError::InvalidOrder)?;
+        let field = frame.def.field(name).ok_or(EncodeError::UnknownField)?;
+        let byte_order = self.schema.byte_order();
+        write_field(
+            field,
+            &mut self.buffer.as_mut_slice()[block_start..],
+            value.into(),
+            byte_order,
+        )
+    }
+
+    /// Starts the repeating group `name` with `num_entries` entries. Every
+    /// entry must be started with [`EncoderHandle::next_entry`], and the group
+    /// must be closed with [`EncoderHandle::end_group`].
+    pub fn begin_group(&mut self, name: &str, num_entries: usize) -> Result<(), EncodeError> {
+        let frame = self.current()?;
+        frame.block_start.ok_or(EncodeError::InvalidOrder)?;
+        let def = frame.def;
+        let index = def
+            .groups
+            .iter()
+            .position(|group| group.name == name)
+            .ok_or(EncodeError::UnknownField)?;
+        self.skip_to(index)?;
+        let group = &def.groups[index];
+        self.write_dimension(group, num_entries)?;
+        self.current()?.next_var = index + 1;
+        self.frames.push(Frame {
+            def: &group.block,
+            block_start: None,
+            next_var: 0,
+            group: Some((group, num_entries)),
+        });
+        Ok(())
+    }
+
+    /// Starts the next entry of the current repeating group.
+    pub fn next_entry(&mut self) -> Result<(), EncodeError> {
+        let (group, remaining) = self.current()?.group.ok_or(EncodeError::InvalidOrder)?;
+        if remaining == 0 {
+            return Err(EncodeError::InvalidOrder);
+        }
+        self.finish_block()?;
+        let block_start = self.append_block(&group.block, group.block.block_length);
+        let frame = self.current()?;
+        frame.block_start = Some(block_start);
+        frame.next_var = 0;
+        frame.group = Some((group, remaining - 1));
+        Ok(())
+    }
+
+    /// Closes the current repeating group, after all of its entries.
+    pub fn end_group(&mut self) -> Result<(), EncodeError> {
+        match self.current()?.group {
+            Some((_, 0)) => {}
+            _ => return Err(EncodeError::InvalidOrder),
+        }
+        self.finish_block()?;
+        self.frames.pop();
+        Ok(())
+    }
+
+    /// Writes the variable-length data field `name` of the current message or
+    /// group entry.
+    pub fn set_data(&mut self, name: &str, data: &[u8]) -> Result<(), EncodeError> {
+        let frame = self.current()?;
+        frame.block_start.ok_or(EncodeError::InvalidOrder)?;
+        let def = frame.def;
+        let index = def
+            .data
+            .iter()
+            .position(|data| data.name == name)
+            .ok_or(EncodeError::UnknownField)?;
+        let var_index = def.groups.len() + index;
+        self.skip_to(var_index)?;
+        self.write_data(&def.data[index].length, data)?;
+        self.current()?.next_var = var_index + 1;
+        Ok(())
+    }
+
+    /// Completes the message and returns its contents.
+    pub fn wrap(mut self) -> Result<&'a [u8], EncodeError> {
+        if self.frames.len() != 1 {
+            return Err(EncodeError::InvalidOrder);
+        }
+        self.finish_block()?;
+        let start = self.start;
+        let buffer: &'a B = self.buffer;
+        Ok(&buffer.as_slice()[start..])
+    }
+
+    fn current(&mut self) -> Result<&mut Frame<'a>, EncodeError> {
+        self.frames.last_mut().ok_or(EncodeError::InvalidOrder)
+    }
+
+    // Appends a new block of `block_length` bytes, with all fields set to
+    // null.
+    fn append_block(&mut self, def: &BlockDef, block_length: usize) -> usize {
+        let start = self.buffer.len();
+        self.buffer.resize(start + block_length, 0);
+        let block = &mut self.buffer.as_mut_slice()[start..];
+        for field in def.fields.iter() {
+            write_null(field, block, self.schema.byte_order());
+        }
+        start
+    }
+
+    // Writes empty groups and data fields, up to `var_index`.
+    fn skip_to(&mut self, var_index: usize) -> Result<(), EncodeError> {
+        let frame = self.current()?;
+        if frame.next_var > var_index {
+            return Err(EncodeError::InvalidOrder);
+        }
+        let def = frame.def;
+        for i in frame.next_var..var_index {
+            match def.groups.get(i) {
+                Some(group) => self.write_dimension(group, 0)?,
+                None => self.write_data(&def.data[i - def.groups.len()].length, &[])?,
+            }
+        }
+        self.current()?.next_var = var_index;
+        Ok(())
+    }
+
+    // Writes any groups and data fields which are left in the current block.
+    fn finish_block(&mut self) -> Result<(), EncodeError> {
+        let frame = self.current()?;
+        if frame.block_start.is_none() {
+            return Ok(());
+        }
+        let len = frame.def.groups.len() + frame.def.data.len();
+        self.skip_to(len)
+    }
+
+    fn write_dimension(&mut self, group: &GroupDef, num_entries: usize) -> Result<(), EncodeError> {
+        let start = self.buffer.len();
+        self.buffer.resize(start + group.dimension_size, 0);
+        let byte_order = self.schema.byte_order();
+        let dimension = &mut self.buffer.as_mut_slice()[start..];
+        write_field(
+            &group.block_length,
+            dimension,
+            Value::UInt(group.block.block_length as u64),
+            byte_order,
+        )?;
+        write_field(
+            &group.num_in_group,
+            dimension,
+            Value::UInt(num_entries as u64),
+            byte_order,
+        )
+    }
+
+    fn write_data(&mut self, length: &FieldDef, data: &[u8]) -> Result<(), EncodeError> {
+        let start = self.buffer.len();
+        self.buffer.resize(start + length.size(), 0);
+        let byte_order = self.schema.byte_order();
+        write_field(
+            length,
+            &mut self.buffer.as_mut_slice()[start..],
+            Value::UInt(data.len() as u64),
+            byte_order,
+        )?;
+        self.buffer.extend_from_slice(data);
+        Ok(())
+    }
+}
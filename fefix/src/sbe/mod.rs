@@ -0,0 +1,273 @@
+//! Encoding and decoding of FIX messages using Simple Binary Encoding (SBE).
+//!
+//! Message layouts come from an SBE message [`Schema`], parsed from its
+//! standard XML representation. [`Decoder`] provides zero-copy access to
+//! messages through runtime flyweights, and [`Encoder`] writes messages
+//! directly into a [`Buffer`](crate::Buffer). SBE messages can be framed
+//! with SOFH, e.g. for CME MDP 3.0 and iLink 3.
+//!
+//! Please refer to <https://www.fixtrading.org/standards/sbe/> for more
+//! information.
+
+use std::error::Error;
+use std::fmt;
+
+mod decoder;
+mod encoder;
+mod schema;
+mod value;
+
+pub use decoder::{Decoder, Group, GroupEntries, GroupEntry, Message};
+pub use encoder::{Encoder, EncoderHandle};
+pub use schema::{ByteOrder, MessageDef, PrimitiveType, Schema};
+pub use value::Value;
+
+/// The error type that can arise when parsing an SBE message [`Schema`].
+#[derive(Clone, Debug)]
+pub enum ParseSchemaError {
+    /// The input is not a valid XML message schema.
+    InvalidFormat,
+    /// The message schema is inconsistent, e.g. it refers to unknown types.
+    InvalidData(String),
+}
+
+impl fmt::Display for ParseSchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidFormat => write!(f, "Invalid SBE message schema format"),
+            Self::InvalidData(msg) => write!(f, "Invalid SBE message schema: {}", msg),
+        }
+    }
+}
+
+impl Error for ParseSchemaError {}
+
+/// The type returned in the event of an error when decoding an SBE message.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The message is truncated, or its lengths are inconsistent.
+    Incomplete,
+    /// The `schemaId` of the message is not the ID of the [`Schema`].
+    InvalidSchemaId,
+    /// The `templateId` of the message is unknown to the [`Schema`].
+    InvalidTemplateId,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let err = match self {
+            Self::Incomplete => "The message is truncated.",
+            Self::InvalidSchemaId => "The message belongs to a different schema.",
+            Self::InvalidTemplateId => "Unrecognized message template.",
+        };
+        write!(f, "{}", err)
+    }
+}
+
+impl Error for DecodeError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        None
+    }
+}
+
+/// The type returned in the event of an error when encoding an SBE message.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum EncodeError {
+    /// The message type is unknown to the [`Schema`].
+    UnknownMessage,
+    /// The field, group, or data field is unknown to the [`Schema`].
+    UnknownField,
+    /// The value can't be represented by the type of the field.
+    InvalidValue,
+    /// Groups or data fields are out of schema order, or group entries don't
+    /// match their declared number.
+    InvalidOrder,
+}
+
+impl fmt::Display for EncodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let err = match self {
+            Self::UnknownMessage => "Unknown message type.",
+            Self::UnknownField => "Unknown field.",
+            Self::InvalidValue => "The value doesn't fit the type of the field.",
+            Self::InvalidOrder => "Groups or data fields are out of order.",
+        };
+        write!(f, "{}", err)
+    }
+}
+
+impl Error for EncodeError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const SCHEMA: &str = include_str!("test_data/orders.xml");
+
+    fn schema() -> Schema {
+        Schema::from_xml(SCHEMA).unwrap()
+    }
+
+    fn new_order(buffer: &mut Vec<u8>) -> &[u8] {
+        let encoder = Encoder::new(schema());
+        let mut msg = encoder.start_message("NewOrder", buffer).unwrap();
+        msg.set("orderId", 1234u64).unwrap();
+        msg.set("symbol", "EURUSD").unwrap();
+        msg.set("side", "2").unwrap();
+        msg.set("price.mantissa", 11_050i64).unwrap();
+        msg.set("quantity", 1_000_000u32).unwrap();
+        msg.set("flags", 0b10u8).unwrap();
+        msg.begin_group("fills", 2).unwrap();
+        msg.next_entry().unwrap();
+        msg.set("fillPrice", 11_049i64).unwrap();
+        msg.set("fillQty", 400_000u32).unwrap();
+        msg.set_data("venue", b"XLON").unwrap();
+        msg.next_entry().unwrap();
+        msg.set("fillPrice", 11_051i64).unwrap();
+        msg.set("fillQty", 600_000u32).unwrap();
+        msg.end_group().unwrap();
+        msg.set_data("text", b"hello").unwrap();
+        let len = msg.wrap().unwrap().len();
+        &buffer[buffer.len() - len..]
+    }
+
+    #[test]
+    fn messages_survive_round_trip() {
+        let mut buffer = Vec::new();
+        let data = new_order(&mut buffer);
+        let decoder = Decoder::new(schema());
+        let message = decoder.decode(data).unwrap();
+        assert_eq!(message.name(), "NewOrder");
+        assert_eq!(message.version(), 1);
+        assert_eq!(message.len(), data.len());
+        assert_eq!(message.field("orderId"), Some(Value::UInt(1234)));
+        assert_eq!(message.field("symbol"), Some(Value::Bytes(b"EURUSD")));
+        assert_eq!(message.field("side"), Some(Value::Bytes(b"2")));
+        assert_eq!(message.field("price.mantissa"), Some(Value::Int(11_050)));
+        assert_eq!(message.field("price.exponent"), Some(Value::Int(-4)));
+        assert_eq!(message.field("flags"), Some(Value::UInt(2)));
+        assert_eq!(message.field("expireTime"), None);
+        assert_eq!(message.field("foo"), None);
+        let fills = message.group("fills").unwrap();
+        assert_eq!(fills.len(), 2);
+        let fills: Vec<(Option<i64>, Option<&[u8]>)> = fills
+            .entries()
+            .map(|fill| {
+                (
+                    fill.field("fillPrice").and_then(|v| v.as_i64()),
+                    fill.data("venue"),
+                )
+            })
+            .collect();
+        assert_eq!(
+            fills,
+            vec![
+                (Some(11_049), Some(&b"XLON"[..])),
+                (Some(11_051), Some(&b""[..]))
+            ]
+        );
+        assert_eq!(message.data("text"), Some(&b"hello"[..]));
+    }
+
+    #[test]
+    fn wire_format_follows_the_schema() {
+        let mut buffer = Vec::new();
+        let encoder = Encoder::new(schema());
+        let mut msg = encoder.start_message("Cancel", &mut buffer).unwrap();
+        msg.set("orderId", 0x0102u64).unwrap();
+        assert_eq!(
+            msg.wrap().unwrap(),
+            &[16, 0, 2, 0, 42, 0, 1, 0, 2, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0][..]
+        );
+    }
+
+    #[test]
+    fn big_endian_schemas_are_supported() {
+        let schema = Schema::from_xml(&SCHEMA.replace("littleEndian", "bigEndian")).unwrap();
+        let mut buffer = Vec::new();
+        let encoder = Encoder::new(schema.clone());
+        let mut msg = encoder.start_message("Cancel", &mut buffer).unwrap();
+        msg.set("orderId", 0x0102u64).unwrap();
+        let data = msg.wrap().unwrap();
+        assert_eq!(&data[..4], &[0, 16, 0, 2]);
+        assert_eq!(&data[14..16], &[1, 2]);
+        let decoder = Decoder::new(schema);
+        let message = decoder.decode(data).unwrap();
+        assert_eq!(message.field("orderId"), Some(Value::UInt(0x0102)));
+    }
+
+    #[test]
+    fn older_and_newer_versions_are_decoded() {
+        let mut buffer = Vec::new();
+        let data = new_order(&mut buffer).to_vec();
+        let decoder = Decoder::new(schema());
+        // Version 0 doesn't have `expireTime`, nor the last 8 bytes of the
+        // block.
+        let mut old = data.clone();
+        old[0] = 30;
+        old[6] = 0;
+        old.drain(8 + 30..8 + 38);
+        let message = decoder.decode(&old[..]).unwrap();
+        assert_eq!(message.field("expireTime"), None);
+        assert_eq!(message.field("flags"), Some(Value::UInt(2)));
+        assert_eq!(message.data("text"), Some(&b"hello"[..]));
+        // Newer versions can add fields at the end of the block.
+        let mut new = data;
+        new[0] = 40;
+        new.insert(8 + 38, 0xFF);
+        new.insert(8 + 38, 0xFF);
+        let message = decoder.decode(&new[..]).unwrap();
+        assert_eq!(message.group("fills").unwrap().len(), 2);
+        assert_eq!(message.data("text"), Some(&b"hello"[..]));
+    }
+
+    #[test]
+    fn truncated_messages_are_refused() {
+        let mut buffer = Vec::new();
+        let data = new_order(&mut buffer);
+        let decoder = Decoder::new(schema());
+        for len in 0..data.len() {
+            assert_eq!(
+                decoder.decode(&data[..len]).map(|_| ()),
+                Err(DecodeError::Incomplete)
+            );
+        }
+        let mut other_schema = data.to_vec();
+        other_schema[4] = 43;
+        assert_eq!(
+            decoder.decode(&other_schema[..]).map(|_| ()),
+            Err(DecodeError::InvalidSchemaId)
+        );
+    }
+
+    #[test]
+    fn encoding_rules_are_enforced() {
+        let encoder = Encoder::new(schema());
+        let mut buffer = Vec::new();
+        assert_eq!(
+            encoder.start_message("Foo", &mut buffer).map(|_| ()),
+            Err(EncodeError::UnknownMessage)
+        );
+        let mut msg = encoder.start_message("NewOrder", &mut buffer).unwrap();
+        assert_eq!(msg.set("foo", 1u8), Err(EncodeError::UnknownField));
+        assert_eq!(msg.set("quantity", -1i32), Err(EncodeError::InvalidValue));
+        assert_eq!(
+            msg.set("symbol", "TOO LONG!"),
+            Err(EncodeError::InvalidValue)
+        );
+        assert_eq!(
+            msg.set("price.exponent", -2i8),
+            Err(EncodeError::InvalidValue)
+        );
+        msg.set_data("text", b"").unwrap();
+        assert_eq!(msg.begin_group("fills", 0), Err(EncodeError::InvalidOrder));
+        let mut msg = encoder.start_message("NewOrder", &mut buffer).unwrap();
+        msg.begin_group("fills", 1).unwrap();
+        assert_eq!(msg.end_group(), Err(EncodeError::InvalidOrder));
+        assert_eq!(msg.wrap().map(|_| ()), Err(EncodeError::InvalidOrder));
+    }
+}
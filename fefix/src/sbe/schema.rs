@@ -0,0 +1,772 @@
+use super::{ParseSchemaError, Value};
+use std::collections::HashMap;
+
+type ParseResult<T> = Result<T, ParseSchemaError>;
+
+const PRIMITIVE_TYPE_NAMES: &[&str] = &[
+    "char", "int8", "int16", "int32", "int64", "uint8", "uint16", "uint32", "uint64", "float",
+    "double",
+];
+
+/// The byte order of all multi-byte values within SBE messages.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ByteOrder {
+    /// Least significant byte first. This is the default.
+    LittleEndian,
+    /// Most significant byte first.
+    BigEndian,
+}
+
+/// The primitive types that SBE values are built upon.
+///
+/// Please note that [`PrimitiveType`] is marked with `#[non_exhaustive]`,
+/// which future-proofs the enumeration type in case more variants are added.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum PrimitiveType {
+    /// A single byte, usually within a fixed-length string.
+    Char,
+    /// A signed 8-bit integer.
+    Int8,
+    /// A signed 16-bit integer.
+    Int16,
+    /// A signed 32-bit integer.
+    Int32,
+    /// A signed 64-bit integer.
+    Int64,
+    /// An unsigned 8-bit integer.
+    UInt8,
+    /// An unsigned 16-bit integer.
+    UInt16,
+    /// An unsigned 32-bit integer.
+    UInt32,
+    /// An unsigned 64-bit integer.
+    UInt64,
+    /// A single-precision IEEE 754 floating point number.
+    Float,
+    /// A double-precision IEEE 754 floating point number.
+    Double,
+}
+
+impl PrimitiveType {
+    /// Returns the [`PrimitiveType`] called `name` in SBE schemas (e.g.
+    /// `uint32`), if any.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "char" => Self::Char,
+            "int8" => Self::Int8,
+            "int16" => Self::Int16,
+            "int32" => Self::Int32,
+            "int64" => Self::Int64,
+            "uint8" => Self::UInt8,
+            "uint16" => Self::UInt16,
+            "uint32" => Self::UInt32,
+            "uint64" => Self::UInt64,
+            "float" => Self::Float,
+            "double" => Self::Double,
+            _ => return None,
+        })
+    }
+
+    /// Returns the number of bytes that a single value of type `self` takes.
+    pub fn size(&self) -> usize {
+        match self {
+            Self::Char | Self::Int8 | Self::UInt8 => 1,
+            Self::Int16 | Self::UInt16 => 2,
+            Self::Int32 | Self::UInt32 | Self::Float => 4,
+            Self::Int64 | Self::UInt64 | Self::Double => 8,
+        }
+    }
+
+    /// Returns the range of integers that can be represented by `self`, if
+    /// `self` is an integer type.
+    pub(crate) fn int_range(&self) -> Option<(i128, i128)> {
+        let range = match self {
+            Self::Int8 => (i8::MIN as i128, i8::MAX as i128),
+            Self::Int16 => (i16::MIN as i128, i16::MAX as i128),
+            Self::Int32 => (i32::MIN as i128, i32::MAX as i128),
+            Self::Int64 => (i64::MIN as i128, i64::MAX as i128),
+            Self::UInt8 => (0, u8::MAX as i128),
+            Self::UInt16 => (0, u16::MAX as i128),
+            Self::UInt32 => (0, u32::MAX as i128),
+            Self::UInt64 => (0, u64::MAX as i128),
+            Self::Char | Self::Float | Self::Double => return None,
+        };
+        Some(range)
+    }
+
+    /// The null value of optional fields of type `self`, as defined by the
+    /// specification, unless overridden by the schema. `char` has none, as
+    /// all-NUL strings are null.
+    fn default_null(&self) -> Option<Value<'static>> {
+        Some(match self {
+            Self::Char => return None,
+            Self::Int8 => Value::Int(i8::MIN as i64),
+            Self::Int16 => Value::Int(i16::MIN as i64),
+            Self::Int32 => Value::Int(i32::MIN as i64),
+            Self::Int64 => Value::Int(i64::MIN),
+            Self::UInt8 => Value::UInt(u8::MAX as u64),
+            Self::UInt16 => Value::UInt(u16::MAX as u64),
+            Self::UInt32 => Value::UInt(u32::MAX as u64),
+            Self::UInt64 => Value::UInt(u64::MAX),
+            Self::Float | Self::Double => Value::Float(f64::NAN),
+        })
+    }
+
+    /// Parses `s`, e.g. the `nullValue` of a type, as a number of type `self`.
+    fn parse_number(&self, s: &str) -> Option<Value<'static>> {
+        let s = s.trim();
+        match self {
+            Self::Char => None,
+            Self::Float | Self::Double => s.parse().ok().map(Value::Float),
+            Self::Int8 | Self::Int16 | Self::Int32 | Self::Int64 => s.parse().ok().map(Value::Int),
+            Self::UInt8 | Self::UInt16 | Self::UInt32 | Self::UInt64 => {
+                s.parse().ok().map(Value::UInt)
+            }
+        }
+    }
+}
+
+/// An SBE message schema, i.e. the collection of message layouts that both
+/// counterparties agree upon.
+///
+/// Schemas are parsed from their standard XML representation. Fields can
+/// have primitive types, simple types, enums, sets, and composites; the
+/// members of composite fields are exposed as fields themselves, using
+/// dot-separated names (e.g. `price.mantissa`).
+///
+/// # Examples
+///
+/// ```
+/// use fefix::sbe::{ByteOrder, Schema};
+///
+/// let xml = r#"
+///     <sbe:messageSchema xmlns:sbe="http://fixprotocol.io/2016/sbe"
+///                        package="example" id="1" version="0">
+///         <types>
+///             <composite name="messageHeader">
+///                 <type name="blockLength" primitiveType="uint16"/>
+///                 <type name="templateId" primitiveType="uint16"/>
+///                 <type name="schemaId" primitiveType="uint16"/>
+///                 <type name="version" primitiveType="uint16"/>
+///             </composite>
+///         </types>
+///         <sbe:message name="Heartbeat" id="1">
+///             <field name="timestamp" id="1" type="uint64"/>
+///         </sbe:message>
+///     </sbe:messageSchema>
+/// "#;
+/// let schema = Schema::from_xml(xml).unwrap();
+/// assert_eq!(schema.byte_order(), ByteOrder::LittleEndian);
+/// assert_eq!(schema.message_by_name("Heartbeat").unwrap().block_length(), 8);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Schema {
+    package: String,
+    id: u16,
+    version: u16,
+    byte_order: ByteOrder,
+    pub(crate) header: Header,
+    messages: Vec<MessageDef>,
+    messages_by_name: HashMap<String, usize>,
+    messages_by_id: HashMap<u16, usize>,
+}
+
+impl Schema {
+    /// Parses the XML representation of an SBE message schema.
+    pub fn from_xml(input: &str) -> Result<Self, ParseSchemaError> {
+        let document =
+            roxmltree::Document::parse(input).map_err(|_| ParseSchemaError::InvalidFormat)?;
+        SchemaReader::new(document.root_element())?.read()
+    }
+
+    /// Returns the `package` of `self`.
+    pub fn package(&self) -> &str {
+        self.package.as_str()
+    }
+
+    /// Returns the unique ID of `self`, i.e. the `schemaId` of all messages.
+    pub fn id(&self) -> u16 {
+        self.id
+    }
+
+    /// Returns the version of `self`.
+    pub fn version(&self) -> u16 {
+        self.version
+    }
+
+    /// Returns the [`ByteOrder`] of all messages of `self`.
+    pub fn byte_order(&self) -> ByteOrder {
+        self.byte_order
+    }
+
+    /// Returns the [`MessageDef`] called `name`, if any.
+    pub fn message_by_name(&self, name: &str) -> Option<&MessageDef> {
+        self.messages_by_name
+            .get(name)
+            .map(|index| &self.messages[*index])
+    }
+
+    /// Returns the [`MessageDef`] with template ID `id`, if any.
+    pub fn message_by_id(&self, id: u16) -> Option<&MessageDef> {
+        self.messages_by_id
+            .get(&id)
+            .map(|index| &self.messages[*index])
+    }
+
+    /// Returns an [`Iterator`] over all messages of `self`.
+    pub fn iter_messages(&self) -> impl Iterator<Item = &MessageDef> {
+        self.messages.iter()
+    }
+}
+
+/// The layout of a message within a [`Schema`].
+#[derive(Debug, Clone)]
+pub struct MessageDef {
+    name: String,
+    id: u16,
+    pub(crate) block: BlockDef,
+}
+
+impl MessageDef {
+    /// Returns the name of `self`.
+    pub fn name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    /// Returns the template ID of `self`.
+    pub fn id(&self) -> u16 {
+        self.id
+    }
+
+    /// Returns the length in bytes of the fixed-length fields of `self`.
+    pub fn block_length(&self) -> usize {
+        self.block.block_length
+    }
+
+    /// Returns an [`Iterator`] over the names of all fixed-length fields of
+    /// `self`, in wire order.
+    pub fn field_names(&self) -> impl Iterator<Item = &str> {
+        self.block.fields.iter().map(|field| field.name.as_str())
+    }
+}
+
+// The fixed-length block of a message or group entry, followed by repeating
+// groups and variable-length data fields.
+#[derive(Debug, Clone)]
+pub(crate) struct BlockDef {
+    pub block_length: usize,
+    pub fields: Vec<FieldDef>,
+    pub groups: Vec<GroupDef>,
+    pub data: Vec<DataDef>,
+}
+
+impl BlockDef {
+    pub fn field(&self, name: &str) -> Option<&FieldDef> {
+        self.fields.iter().find(|field| field.name == name)
+    }
+}
+
+// A primitive value within a block, possibly a single member of a composite.
+#[derive(Debug, Clone)]
+pub(crate) struct FieldDef {
+    pub name: String,
+    pub offset: usize,
+    pub primitive: PrimitiveType,
+    pub length: usize,
+    pub presence: Presence,
+    pub since_version: u16,
+}
+
+impl FieldDef {
+    pub fn size(&self) -> usize {
+        match self.presence {
+            Presence::Constant(_) => 0,
+            _ => self.primitive.size() * self.length,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) enum Presence {
+    Required,
+    Optional(Option<Value<'static>>),
+    Constant(String),
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct GroupDef {
+    pub name: String,
+    pub since_version: u16,
+    pub block_length: FieldDef,
+    pub num_in_group: FieldDef,
+    pub dimension_size: usize,
+    pub block: BlockDef,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct DataDef {
+    pub name: String,
+    pub since_version: u16,
+    pub length: FieldDef,
+}
+
+// The `messageHeader` composite, which precedes all messages.
+#[derive(Debug, Clone)]
+pub(crate) struct Header {
+    pub block_length: FieldDef,
+    pub template_id: FieldDef,
+    pub schema_id: FieldDef,
+    pub version: FieldDef,
+    pub size: usize,
+}
+
+// An entry of `<types>`, before flattening.
+#[derive(Debug, Clone)]
+enum TypeDef<'a> {
+    Encoded {
+        primitive: PrimitiveType,
+        length: usize,
+        presence: Option<&'a str>,
+        null_value: Option<&'a str>,
+        constant: Option<&'a str>,
+    },
+    Composite(Vec<(&'a str, Option<usize>, TypeRef<'a>)>),
+    // Enums and sets are encoded as their underlying type.
+    Encoding(TypeRef<'a>),
+}
+
+impl<'a> TypeDef<'a> {
+    fn encoded(primitive: PrimitiveType) -> Self {
+        Self::Encoded {
+            primitive,
+            length: 1,
+            presence: None,
+            null_value: None,
+            constant: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum TypeRef<'a> {
+    Inline(Box<TypeDef<'a>>),
+    Named(&'a str),
+}
+
+struct SchemaReader<'a> {
+    root: roxmltree::Node<'a, 'a>,
+    types: HashMap<&'a str, TypeDef<'a>>,
+}
+
+impl<'a> SchemaReader<'a> {
+    fn new(root: roxmltree::Node<'a, 'a>) -> ParseResult<Self> {
+        if root.tag_name().name() != "messageSchema" {
+            return Err(ParseSchemaError::InvalidFormat);
+        }
+        let mut types = HashMap::new();
+        // Primitive types can be referred to directly.
+        for name in PRIMITIVE_TYPE_NAMES.iter() {
+            let primitive = PrimitiveType::from_name(name).unwrap();
+            types.insert(*name, TypeDef::encoded(primitive));
+        }
+        for node in root.children().filter(|n| n.tag_name().name() == "types") {
+            for child in node.children().filter(|n| n.is_element()) {
+                types.insert(attribute(child, "name")?, type_def(child)?);
+            }
+        }
+        Ok(Self { root, types })
+    }
+
+    fn read(&self) -> ParseResult<Schema> {
+        let byte_order = match self.root.attribute("byteOrder") {
+            None | Some("littleEndian") => ByteOrder::LittleEndian,
+            Some("bigEndian") => ByteOrder::BigEndian,
+            Some(other) => {
+                return Err(ParseSchemaError::InvalidData(format!(
+                    "Invalid byte order '{}'",
+                    other
+                )))
+            }
+        };
+        let header_type = self.root.attribute("headerType").unwrap_or("messageHeader");
+        let (header, size) = self.composite(header_type)?;
+        let header_member = |name: &str| {
+            header
+                .iter()
+                .find(|field| field.name == name)
+                .cloned()
+                .ok_or_else(|| missing_member(header_type, name))
+        };
+        let header = Header {
+            block_length: header_member("blockLength")?,
+            template_id: header_member("templateId")?,
+            schema_id: header_member("schemaId")?,
+            version: header_member("version")?,
+            size,
+        };
+        let mut messages = Vec::new();
+        for node in self
+            .root
+            .children()
+            .filter(|n| n.tag_name().name() == "message")
+        {
+            messages.push(MessageDef {
+                name: attribute(node, "name")?.to_string(),
+                id: number(node, "id")?,
+                block: self.block(node)?,
+            });
+        }
+        Ok(Schema {
+            package: self.root.attribute("package").unwrap_or("").to_string(),
+            id: number(self.root, "id")?,
+            version: optional_number(self.root, "version")?.unwrap_or(0),
+            byte_order,
+            header,
+            messages_by_name: messages
+                .iter()
+                .enumerate()
+                .map(|(i, message)| (message.name.clone(), i))
+                .collect(),
+            messages_by_id: messages
+                .iter()
+                .enumerate()
+                .map(|(i, message)| (message.id, i))
+                .collect(),
+            messages,
+        })
+    }
+
+    fn block(&self, node: roxmltree::Node) -> ParseResult<BlockDef> {
+        let mut fields = Vec::new();
+        let mut groups = Vec::new();
+        let mut data = Vec::new();
+        let mut offset = 0;
+        for child in node.children().filter(|n| n.is_element()) {
+            let name = attribute(child, "name")?;
+            let since_version = optional_number(child, "sinceVersion")?.unwrap_or(0);
+            match child.tag_name().name() {
+                "field" => {
+                    if !groups.is_empty() || !data.is_empty() {
+                        return Err(ParseSchemaError::InvalidData(format!(
+                            "Field '{}' follows groups or data",
+                            name
+                        )));
+                    }
+                    offset = optional_number(child, "offset")?.unwrap_or(offset);
+                    let ty = self.named(attribute(child, "type")?)?;
+                    let start = fields.len();
+                    offset += self.flatten(name, ty, offset, &mut fields)?;
+                    for field in fields[start..].iter_mut() {
+                        field.since_version = since_version;
+                        match child.attribute("presence") {
+                            Some("optional") => {
+                                field.presence = Presence::Optional(field.primitive.default_null())
+                            }
+                            Some("constant") => {
+                                let value = child.text().unwrap_or("").trim().to_string();
+                                field.presence = Presence::Constant(value);
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+                "group" => {
+                    if !data.is_empty() {
+                        return Err(ParseSchemaError::InvalidData(format!(
+                            "Group '{}' follows data",
+                            name
+                        )));
+                    }
+                    let dimension_type = child
+                        .attribute("dimensionType")
+                        .unwrap_or("groupSizeEncoding");
+                    let (dimension, dimension_size) = self.composite(dimension_type)?;
+                    let member = |member: &str| {
+                        dimension
+                            .iter()
+                            .find(|field| field.name == member)
+                            .cloned()
+                            .ok_or_else(|| missing_member(dimension_type, member))
+                    };
+                    let block = self.block(child)?;
+                    groups.push(GroupDef {
+                        name: name.to_string(),
+                        since_version,
+                        block_length: member("blockLength")?,
+                        num_in_group: member("numInGroup")?,
+                        dimension_size,
+                        block,
+                    });
+                }
+                "data" => {
+                    let data_type = attribute(child, "type")?;
+                    let (encoding, _) = self.composite(data_type)?;
+                    let length = encoding
+                        .into_iter()
+                        .find(|field| field.name == "length")
+                        .ok_or_else(|| missing_member(data_type, "length"))?;
+                    data.push(DataDef {
+                        name: name.to_string(),
+                        since_version,
+                        length,
+                    });
+                }
+                _ => {}
+            }
+        }
+        let block_length = fields
+            .iter()
+            .map(|field: &FieldDef| field.offset + field.size())
+            .max()
+            .unwrap_or(0);
+        let block_length = optional_number(node, "blockLength")?.unwrap_or(block_length);
+        Ok(BlockDef {
+            block_length,
+            fields,
+            groups,
+            data,
+        })
+    }
+
+    // Returns the members of the composite called `name`, and its size.
+    fn composite(&self, name: &str) -> ParseResult<(Vec<FieldDef>, usize)> {
+        let ty = self.named(name)?;
+        if !matches!(ty, TypeDef::Composite(_)) {
+            return Err(ParseSchemaError::InvalidData(format!(
+                "'{}' is not a composite",
+                name
+            )));
+        }
+        let mut fields = Vec::new();
+        let size = self.flatten("", ty, 0, &mut fields)?;
+        Ok((fields, size))
+    }
+
+    fn resolve<'b>(&'b self, ty: &'b TypeRef<'a>) -> ParseResult<&'b TypeDef<'a>> {
+        match ty {
+            TypeRef::Inline(ty) => Ok(ty),
+            TypeRef::Named(name) => self.named(name),
+        }
+    }
+
+    fn named(&self, name: &str) -> ParseResult<&TypeDef<'a>> {
+        self.types
+            .get(name)
+            .ok_or_else(|| ParseSchemaError::InvalidData(format!("Unknown type '{}'", name)))
+    }
+
+    // Flattens `ty` into primitive fields, starting at `offset`. Returns the
+    // size of `ty`.
+    fn flatten(
+        &self,
+        name: &str,
+        ty: &TypeDef,
+        offset: usize,
+        fields: &mut Vec<FieldDef>,
+    ) -> ParseResult<usize> {
+        match ty {
+            TypeDef::Encoded {
+                primitive,
+                length,
+                presence,
+                null_value,
+                constant,
+            } => {
+                let presence = match presence {
+                    Some("optional") => Presence::Optional(match null_value {
+                        Some(null_value) => {
+                            Some(primitive.parse_number(null_value).ok_or_else(|| {
+                                ParseSchemaError::InvalidData(format!(
+                                    "Invalid null value '{}'",
+                                    null_value
+                                ))
+                            })?)
+                        }
+                        None => primitive.default_null(),
+                    }),
+                    Some("constant") => Presence::Constant(constant.unwrap_or("").to_string()),
+                    _ => Presence::Required,
+                };
+                if let Presence::Constant(value) = &presence {
+                    if *primitive != PrimitiveType::Char && primitive.parse_number(value).is_none()
+                    {
+                        return Err(ParseSchemaError::InvalidData(format!(
+                            "Invalid constant '{}'",
+                            value
+                        )));
+                    }
+                }
+                let field = FieldDef {
+                    name: name.to_string(),
+                    offset,
+                    primitive: *primitive,
+                    length: *length,
+                    presence,
+                    since_version: 0,
+                };
+                let size = field.size();
+                fields.push(field);
+                Ok(size)
+            }
+            TypeDef::Encoding(encoding) => {
+                self.flatten(name, self.resolve(encoding)?, offset, fields)
+            }
+            TypeDef::Composite(members) => {
+                let mut size = 0;
+                for (member, member_offset, member_ty) in members {
+                    let member_offset = member_offset.unwrap_or(size);
+                    let member_name = if name.is_empty() {
+                        member.to_string()
+                    } else {
+                        format!("{}.{}", name, member)
+                    };
+                    let member_size = self.flatten(
+                        &member_name,
+                        self.resolve(member_ty)?,
+                        offset + member_offset,
+                        fields,
+                    )?;
+                    size = size.max(member_offset + member_size);
+                }
+                Ok(size)
+            }
+        }
+    }
+}
+
+fn type_def<'a>(node: roxmltree::Node<'a, '_>) -> ParseResult<TypeDef<'a>> {
+    match node.tag_name().name() {
+        "type" => {
+            let primitive = attribute(node, "primitiveType")?;
+            Ok(TypeDef::Encoded {
+                primitive: PrimitiveType::from_name(primitive).ok_or_else(|| {
+                    ParseSchemaError::InvalidData(format!("Unknown primitive type '{}'", primitive))
+                })?,
+                length: optional_number(node, "length")?.unwrap_or(1),
+                presence: node.attribute("presence"),
+                null_value: node.attribute("nullValue"),
+                constant: node.text().map(str::trim),
+            })
+        }
+        "composite" => {
+            let mut members = Vec::new();
+            for child in node.children().filter(|n| n.is_element()) {
+                let member = match child.tag_name().name() {
+                    "ref" => TypeRef::Named(attribute(child, "type")?),
+                    _ => TypeRef::Inline(Box::new(type_def(child)?)),
+                };
+                members.push((
+                    attribute(child, "name")?,
+                    optional_number(child, "offset")?,
+                    member,
+                ));
+            }
+            Ok(TypeDef::Composite(members))
+        }
+        "enum" | "set" => {
+            let encoding = attribute(node, "encodingType")?;
+            Ok(TypeDef::Encoding(
+                match PrimitiveType::from_name(encoding) {
+                    Some(primitive) => TypeRef::Inline(Box::new(TypeDef::encoded(primitive))),
+                    None => TypeRef::Named(encoding),
+                },
+            ))
+        }
+        other => Err(ParseSchemaError::InvalidData(format!(
+            "Unknown type kind '{}'",
+            other
+        ))),
+    }
+}
+
+fn attribute<'a>(node: roxmltree::Node<'a, '_>, name: &str) -> ParseResult<&'a str> {
+    node.attribute(name).ok_or_else(|| {
+        ParseSchemaError::InvalidData(format!(
+            "Missing attribute '{}' in <{}>",
+            name,
+            node.tag_name().name()
+        ))
+    })
+}
+
+fn optional_number<T>(node: roxmltree::Node, name: &str) -> ParseResult<Option<T>>
+where
+    T: std::str::FromStr,
+{
+    match node.attribute(name) {
+        Some(s) => s.trim().parse().map(Some).map_err(|_| {
+            ParseSchemaError::InvalidData(format!("Invalid number '{}' for '{}'", s, name))
+        }),
+        None => Ok(None),
+    }
+}
+
+fn number<T>(node: roxmltree::Node, name: &str) -> ParseResult<T>
+where
+    T: std::str::FromStr,
+{
+    optional_number(node, name)?.ok_or_else(|| {
+        ParseSchemaError::InvalidData(format!(
+            "Missing attribute '{}' in <{}>",
+            name,
+            node.tag_name().name()
+        ))
+    })
+}
+
+fn missing_member(composite: &str, member: &str) -> ParseSchemaError {
+    ParseSchemaError::InvalidData(format!(
+        "Composite '{}' has no '{}' member",
+        composite, member
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const SCHEMA: &str = include_str!("test_data/orders.xml");
+
+    #[test]
+    fn offsets_and_block_lengths_are_computed() {
+        let schema = Schema::from_xml(SCHEMA).unwrap();
+        assert_eq!(schema.id(), 42);
+        assert_eq!(schema.version(), 1);
+        assert_eq!(schema.header.size, 8);
+        let order = schema.message_by_name("NewOrder").unwrap();
+        assert_eq!(order.id(), 1);
+        let names: Vec<&str> = order.field_names().collect();
+        assert_eq!(
+            names,
+            vec![
+                "orderId",
+                "symbol",
+                "side",
+                "price.mantissa",
+                "price.exponent",
+                "quantity",
+                "flags",
+                "expireTime"
+            ]
+        );
+        let offsets: Vec<usize> = order.block.fields.iter().map(|f| f.offset).collect();
+        assert_eq!(offsets, vec![0, 8, 16, 17, 25, 25, 29, 30]);
+        assert_eq!(order.block_length(), 38);
+        assert_eq!(order.block.groups[0].block.block_length, 12);
+        assert_eq!(order.block.groups[0].dimension_size, 4);
+    }
+
+    #[test]
+    fn unknown_types_are_refused() {
+        let xml = SCHEMA.replace(r#"type="Side""#, r#"type="Foo""#);
+        assert!(matches!(
+            Schema::from_xml(&xml),
+            Err(ParseSchemaError::InvalidData(_))
+        ));
+        assert!(matches!(
+            Schema::from_xml("<foo/>"),
+            Err(ParseSchemaError::InvalidFormat)
+        ));
+    }
+}
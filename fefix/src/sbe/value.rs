@@ -0,0 +1,267 @@
+use super::schema::{FieldDef, Presence};
+use super::{ByteOrder, EncodeError, PrimitiveType};
+
+/// A single value within an SBE message.
+///
+/// Integers are widened to 64 bits and `float`s to `f64`. Fixed-length
+/// arrays of `char`, `int8`, and `uint8` are [`Value::Bytes`]; trailing NUL
+/// padding of `char` arrays is stripped.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Value<'a> {
+    /// A signed integer.
+    Int(i64),
+    /// An unsigned integer.
+    UInt(u64),
+    /// A floating point number.
+    Float(f64),
+    /// A fixed-length string or byte array.
+    Bytes(&'a [u8]),
+}
+
+impl<'a> Value<'a> {
+    /// Returns `self` as an `i64`, if it's an integer within range.
+    pub fn as_i64(&self) -> Option<i64> {
+        use std::convert::TryFrom;
+        match *self {
+            Self::Int(n) => Some(n),
+            Self::UInt(n) => i64::try_from(n).ok(),
+            _ => None,
+        }
+    }
+
+    /// Returns `self` as a `u64`, if it's an integer within range.
+    pub fn as_u64(&self) -> Option<u64> {
+        use std::convert::TryFrom;
+        match *self {
+            Self::Int(n) => u64::try_from(n).ok(),
+            Self::UInt(n) => Some(n),
+            _ => None,
+        }
+    }
+
+    /// Returns `self` as an `f64`, if it's a number.
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            Self::Int(n) => Some(n as f64),
+            Self::UInt(n) => Some(n as f64),
+            Self::Float(n) => Some(n),
+            Self::Bytes(_) => None,
+        }
+    }
+
+    /// Returns `self` as a byte slice, if it's [`Value::Bytes`].
+    pub fn as_bytes(&self) -> Option<&'a [u8]> {
+        match *self {
+            Self::Bytes(bytes) => Some(bytes),
+            _ => None,
+        }
+    }
+
+    fn as_i128(&self) -> Option<i128> {
+        match *self {
+            Self::Int(n) => Some(n as i128),
+            Self::UInt(n) => Some(n as i128),
+            _ => None,
+        }
+    }
+
+    // Like `PartialEq`, but NaN equals NaN.
+    fn is_same(&self, other: &Value) -> bool {
+        match (self, other) {
+            (Self::Float(a), Value::Float(b)) => a == b || (a.is_nan() && b.is_nan()),
+            (a, b) => a == b,
+        }
+    }
+}
+
+macro_rules! impl_from {
+    ($variant:ident, $target:ty, $($t:ty),*) => {
+        $(
+            impl<'a> From<$t> for Value<'a> {
+                fn from(value: $t) -> Self {
+                    Self::$variant(value as $target)
+                }
+            }
+        )*
+    };
+}
+
+impl_from!(Int, i64, i8, i16, i32, i64);
+impl_from!(UInt, u64, u8, u16, u32, u64);
+impl_from!(Float, f64, f32, f64);
+
+impl<'a> From<&'a [u8]> for Value<'a> {
+    fn from(value: &'a [u8]) -> Self {
+        Self::Bytes(value)
+    }
+}
+
+impl<'a> From<&'a str> for Value<'a> {
+    fn from(value: &'a str) -> Self {
+        Self::Bytes(value.as_bytes())
+    }
+}
+
+/// Reads an unsigned integer of `bytes.len()` bytes.
+pub(crate) fn read_uint(bytes: &[u8], byte_order: ByteOrder) -> u64 {
+    let mut buffer = [0u8; 8];
+    match byte_order {
+        ByteOrder::LittleEndian => buffer[..bytes.len()].copy_from_slice(bytes),
+        ByteOrder::BigEndian => {
+            for (i, byte) in bytes.iter().rev().enumerate() {
+                buffer[i] = *byte;
+            }
+        }
+    }
+    u64::from_le_bytes(buffer)
+}
+
+/// Writes the lowest `bytes.len()` bytes of `n`.
+pub(crate) fn write_uint(bytes: &mut [u8], n: u64, byte_order: ByteOrder) {
+    let len = bytes.len();
+    let le = n.to_le_bytes();
+    match byte_order {
+        ByteOrder::LittleEndian => bytes.copy_from_slice(&le[..len]),
+        ByteOrder::BigEndian => {
+            for (i, byte) in bytes.iter_mut().rev().enumerate() {
+                *byte = le[i];
+            }
+        }
+    }
+}
+
+/// Reads `field` from `block`, returning [`None`] if it's null.
+pub(crate) fn read_field<'a>(
+    field: &'a FieldDef,
+    block: &'a [u8],
+    byte_order: ByteOrder,
+) -> Option<Value<'a>> {
+    let primitive = field.primitive;
+    if let Presence::Constant(constant) = &field.presence {
+        return Some(constant_value(primitive, constant));
+    }
+    let bytes = &block[field.offset..field.offset + field.size()];
+    let value = match primitive {
+        PrimitiveType::Char => {
+            let end = bytes.iter().rposition(|b| *b != 0).map_or(0, |i| i + 1);
+            Value::Bytes(&bytes[..end])
+        }
+        PrimitiveType::Int8 | PrimitiveType::UInt8 if field.length != 1 => Value::Bytes(bytes),
+        _ => read_number(primitive, bytes, byte_order),
+    };
+    match &field.presence {
+        Presence::Optional(Some(null)) if value.is_same(null) => None,
+        Presence::Optional(None) if value == Value::Bytes(&[]) => None,
+        _ => Some(value),
+    }
+}
+
+/// Writes `value` as `field` into `block`.
+pub(crate) fn write_field(
+    field: &FieldDef,
+    block: &mut [u8],
+    value: Value,
+    byte_order: ByteOrder,
+) -> Result<(), EncodeError> {
+    if let Presence::Constant(_) = field.presence {
+        return Err(EncodeError::InvalidValue);
+    }
+    let bytes = &mut block[field.offset..field.offset + field.size()];
+    let primitive = field.primitive;
+    let is_array = primitive == PrimitiveType::Char || field.length != 1;
+    match value {
+        Value::Bytes(value) if is_array => {
+            if value.len() > bytes.len() {
+                return Err(EncodeError::InvalidValue);
+            }
+            bytes.fill(0);
+            bytes[..value.len()].copy_from_slice(value);
+            Ok(())
+        }
+        _ if is_array => Err(EncodeError::InvalidValue),
+        value => {
+            let raw = match primitive {
+                PrimitiveType::Float => {
+                    let n = value.as_f64().ok_or(EncodeError::InvalidValue)?;
+                    (n as f32).to_bits() as u64
+                }
+                PrimitiveType::Double => value.as_f64().ok_or(EncodeError::InvalidValue)?.to_bits(),
+                _ => {
+                    let (min, max) = primitive.int_range().ok_or(EncodeError::InvalidValue)?;
+                    let n = value.as_i128().ok_or(EncodeError::InvalidValue)?;
+                    if n < min || n > max {
+                        return Err(EncodeError::InvalidValue);
+                    }
+                    n as u64
+                }
+            };
+            write_uint(bytes, raw, byte_order);
+            Ok(())
+        }
+    }
+}
+
+/// Writes the null value of `field` (or zeros, for required fields) into
+/// `block`.
+pub(crate) fn write_null(field: &FieldDef, block: &mut [u8], byte_order: ByteOrder) {
+    match &field.presence {
+        Presence::Optional(Some(null)) if field.length == 1 => {
+            // Null values are always valid for their fields.
+            write_field(field, block, *null, byte_order).ok();
+        }
+        Presence::Constant(_) => {}
+        _ => block[field.offset..field.offset + field.size()].fill(0),
+    }
+}
+
+fn read_number(primitive: PrimitiveType, bytes: &[u8], byte_order: ByteOrder) -> Value<'static> {
+    let raw = read_uint(bytes, byte_order);
+    match primitive {
+        PrimitiveType::Int8 => Value::Int(raw as i8 as i64),
+        PrimitiveType::Int16 => Value::Int(raw as i16 as i64),
+        PrimitiveType::Int32 => Value::Int(raw as i32 as i64),
+        PrimitiveType::Int64 => Value::Int(raw as i64),
+        PrimitiveType::Float => Value::Float(f32::from_bits(raw as u32) as f64),
+        PrimitiveType::Double => Value::Float(f64::from_bits(raw)),
+        _ => Value::UInt(raw),
+    }
+}
+
+fn constant_value(primitive: PrimitiveType, constant: &str) -> Value<'_> {
+    let constant = constant.trim();
+    match primitive {
+        PrimitiveType::Char => Value::Bytes(constant.as_bytes()),
+        PrimitiveType::Float | PrimitiveType::Double => {
+            Value::Float(constant.parse().unwrap_or_default())
+        }
+        PrimitiveType::Int8
+        | PrimitiveType::Int16
+        | PrimitiveType::Int32
+        | PrimitiveType::Int64 => Value::Int(constant.parse().unwrap_or_default()),
+        _ => Value::UInt(constant.parse().unwrap_or_default()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn integers_respect_byte_order() {
+        let mut bytes = [0u8; 4];
+        write_uint(&mut bytes, 0x01020304, ByteOrder::BigEndian);
+        assert_eq!(bytes, [1, 2, 3, 4]);
+        assert_eq!(read_uint(&bytes, ByteOrder::BigEndian), 0x01020304);
+        write_uint(&mut bytes, 0x01020304, ByteOrder::LittleEndian);
+        assert_eq!(bytes, [4, 3, 2, 1]);
+        assert_eq!(read_uint(&bytes, ByteOrder::LittleEndian), 0x01020304);
+    }
+
+    #[test]
+    fn conversions_check_range() {
+        assert_eq!(Value::Int(-1).as_u64(), None);
+        assert_eq!(Value::UInt(u64::MAX).as_i64(), None);
+        assert_eq!(Value::from(7u8).as_i64(), Some(7));
+        assert_eq!(Value::from("AB").as_bytes(), Some(&b"AB"[..]));
+    }
+}
@@ -0,0 +1,247 @@
+//! Byte scanning primitives for the hot loops of tag-value decoding, i.e.
+//! locating separators and `=` signs, and computing checksums.
+//!
+//! With the `simd` feature, SSE2/AVX2 (`x86_64`, with runtime detection of
+//! AVX2) and NEON (`aarch64`) code paths are used; otherwise, and on all other
+//! targets, everything falls back to plain byte-at-a-time loops. All code paths
+//! give identical results.
+
+/// Returns the index of the first occurrence of `needle` in `haystack`.
+#[inline]
+pub fn find_byte(haystack: &[u8], needle: u8) -> Option<usize> {
+    #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+    {
+        if is_x86_feature_detected!("avx2") {
+            // SAFETY: AVX2 support was just detected.
+            unsafe { x86_64::find_byte_avx2(haystack, needle) }
+        } else {
+            // SAFETY: SSE2 is part of the `x86_64` baseline.
+            unsafe { x86_64::find_byte_sse2(haystack, needle) }
+        }
+    }
+    #[cfg(all(feature = "simd", target_arch = "aarch64"))]
+    {
+        // SAFETY: NEON is part of the `aarch64` baseline.
+        unsafe { aarch64::find_byte_neon(haystack, needle) }
+    }
+    #[cfg(not(all(feature = "simd", any(target_arch = "x86_64", target_arch = "aarch64"))))]
+    {
+        scalar::find_byte(haystack, needle)
+    }
+}
+
+/// Returns the sum of all bytes in `data`, modulo 256.
+#[inline]
+pub fn checksum(data: &[u8]) -> u8 {
+    #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+    {
+        if is_x86_feature_detected!("avx2") {
+            // SAFETY: AVX2 support was just detected.
+            unsafe { x86_64::checksum_avx2(data) }
+        } else {
+            // SAFETY: SSE2 is part of the `x86_64` baseline.
+            unsafe { x86_64::checksum_sse2(data) }
+        }
+    }
+    #[cfg(all(feature = "simd", target_arch = "aarch64"))]
+    {
+        // SAFETY: NEON is part of the `aarch64` baseline.
+        unsafe { aarch64::checksum_neon(data) }
+    }
+    #[cfg(not(all(feature = "simd", any(target_arch = "x86_64", target_arch = "aarch64"))))]
+    {
+        scalar::checksum(data)
+    }
+}
+
+mod scalar {
+    #[allow(dead_code)]
+    pub fn find_byte(haystack: &[u8], needle: u8) -> Option<usize> {
+        haystack.iter().position(|byte| *byte == needle)
+    }
+
+    #[allow(dead_code)]
+    pub fn checksum(data: &[u8]) -> u8 {
+        data.iter().fold(0u8, |sum, byte| sum.wrapping_add(*byte))
+    }
+}
+
+#[cfg(all(feature = "simd", target_arch = "x86_64"))]
+mod x86_64 {
+    use super::scalar;
+    use core::arch::x86_64::*;
+
+    #[target_feature(enable = "sse2")]
+    pub unsafe fn find_byte_sse2(haystack: &[u8], needle: u8) -> Option<usize> {
+        const LANES: usize = 16;
+        let mut i = 0;
+        // SAFETY: SSE2 is enabled for this function and all loads are
+        // unaligned and within the bounds of `haystack`.
+        unsafe {
+            let needles = _mm_set1_epi8(needle as i8);
+            while i + LANES <= haystack.len() {
+                let chunk = _mm_loadu_si128(haystack.as_ptr().add(i) as *const __m128i);
+                let mask = _mm_movemask_epi8(_mm_cmpeq_epi8(chunk, needles));
+                if mask != 0 {
+                    return Some(i + mask.trailing_zeros() as usize);
+                }
+                i += LANES;
+            }
+        }
+        scalar::find_byte(&haystack[i..], needle).map(|pos| pos + i)
+    }
+
+    #[target_feature(enable = "avx2")]
+    pub unsafe fn find_byte_avx2(haystack: &[u8], needle: u8) -> Option<usize> {
+        const LANES: usize = 32;
+        let mut i = 0;
+        // SAFETY: AVX2 is enabled for this function and all loads are
+        // unaligned and within the bounds of `haystack`.
+        unsafe {
+            let needles = _mm256_set1_epi8(needle as i8);
+            while i + LANES <= haystack.len() {
+                let chunk = _mm256_loadu_si256(haystack.as_ptr().add(i) as *const __m256i);
+                let mask = _mm256_movemask_epi8(_mm256_cmpeq_epi8(chunk, needles));
+                if mask != 0 {
+                    return Some(i + mask.trailing_zeros() as usize);
+                }
+                i += LANES;
+            }
+            find_byte_sse2(&haystack[i..], needle).map(|pos| pos + i)
+        }
+    }
+
+    #[target_feature(enable = "sse2")]
+    pub unsafe fn checksum_sse2(data: &[u8]) -> u8 {
+        const LANES: usize = 16;
+        let mut i = 0;
+        let mut sum = 0u64;
+        // SAFETY: SSE2 is enabled for this function and all loads are
+        // unaligned and within the bounds of `data`.
+        unsafe {
+            let zero = _mm_setzero_si128();
+            let mut acc = zero;
+            while i + LANES <= data.len() {
+                let chunk = _mm_loadu_si128(data.as_ptr().add(i) as *const __m128i);
+                // Horizontal sums of each 8-byte half, as two `u64` lanes.
+                acc = _mm_add_epi64(acc, _mm_sad_epu8(chunk, zero));
+                i += LANES;
+            }
+            let mut lanes = [0u64; 2];
+            _mm_storeu_si128(lanes.as_mut_ptr() as *mut __m128i, acc);
+            sum = sum.wrapping_add(lanes[0]).wrapping_add(lanes[1]);
+        }
+        (sum as u8).wrapping_add(scalar::checksum(&data[i..]))
+    }
+
+    #[target_feature(enable = "avx2")]
+    pub unsafe fn checksum_avx2(data: &[u8]) -> u8 {
+        const LANES: usize = 32;
+        let mut i = 0;
+        let mut sum = 0u64;
+        // SAFETY: AVX2 is enabled for this function and all loads are
+        // unaligned and within the bounds of `data`.
+        unsafe {
+            let zero = _mm256_setzero_si256();
+            let mut acc = zero;
+            while i + LANES <= data.len() {
+                let chunk = _mm256_loadu_si256(data.as_ptr().add(i) as *const __m256i);
+                acc = _mm256_add_epi64(acc, _mm256_sad_epu8(chunk, zero));
+                i += LANES;
+            }
+            let mut lanes = [0u64; 4];
+            _mm256_storeu_si256(lanes.as_mut_ptr() as *mut __m256i, acc);
+            for lane in lanes.iter() {
+                sum = sum.wrapping_add(*lane);
+            }
+            (sum as u8).wrapping_add(checksum_sse2(&data[i..]))
+        }
+    }
+}
+
+#[cfg(all(feature = "simd", target_arch = "aarch64"))]
+mod aarch64 {
+    use super::scalar;
+    use core::arch::aarch64::*;
+
+    #[target_feature(enable = "neon")]
+    pub unsafe fn find_byte_neon(haystack: &[u8], needle: u8) -> Option<usize> {
+        const LANES: usize = 16;
+        let mut i = 0;
+        // SAFETY: NEON is enabled for this function and all loads are within
+        // the bounds of `haystack`.
+        unsafe {
+            let needles = vdupq_n_u8(needle);
+            while i + LANES <= haystack.len() {
+                let chunk = vld1q_u8(haystack.as_ptr().add(i));
+                if vmaxvq_u8(vceqq_u8(chunk, needles)) != 0 {
+                    // There's a match somewhere in the chunk.
+                    return scalar::find_byte(&haystack[i..i + LANES], needle).map(|pos| pos + i);
+                }
+                i += LANES;
+            }
+        }
+        scalar::find_byte(&haystack[i..], needle).map(|pos| pos + i)
+    }
+
+    #[target_feature(enable = "neon")]
+    pub unsafe fn checksum_neon(data: &[u8]) -> u8 {
+        const LANES: usize = 16;
+        let mut i = 0;
+        let mut sum = 0u64;
+        // SAFETY: NEON is enabled for this function and all loads are within
+        // the bounds of `data`.
+        unsafe {
+            while i + LANES <= data.len() {
+                let chunk = vld1q_u8(data.as_ptr().add(i));
+                sum = sum.wrapping_add(vaddlvq_u8(chunk) as u64);
+                i += LANES;
+            }
+        }
+        (sum as u8).wrapping_add(scalar::checksum(&data[i..]))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use quickcheck_macros::quickcheck;
+
+    #[test]
+    fn find_byte_at_every_position() {
+        // Long enough to exercise the 32-byte and 16-byte loops and the tail.
+        for len in 0..100 {
+            let mut data = vec![b'a'; len];
+            assert_eq!(find_byte(&data[..], b'|'), None);
+            for pos in 0..len {
+                data[pos] = b'|';
+                assert_eq!(find_byte(&data[..], b'|'), Some(pos));
+                data[pos] = b'a';
+            }
+        }
+    }
+
+    #[test]
+    fn find_byte_returns_first_occurrence() {
+        let data = b"8=FIX.4.4|9=000042|35=0|49=SENDER|56=TARGET|34=1|52=20210101-00:00:00|";
+        assert_eq!(find_byte(&data[..], b'|'), Some(9));
+        assert_eq!(find_byte(&data[40..], b'='), Some(6));
+    }
+
+    #[quickcheck]
+    fn find_byte_is_consistent_with_scalar(data: Vec<u8>, needle: u8) -> bool {
+        find_byte(&data[..], needle) == scalar::find_byte(&data[..], needle)
+    }
+
+    #[quickcheck]
+    fn checksum_is_consistent_with_scalar(data: Vec<u8>) -> bool {
+        checksum(&data[..]) == scalar::checksum(&data[..])
+    }
+
+    #[test]
+    fn checksum_of_large_buffer_wraps_around() {
+        let data = vec![0xffu8; 4099];
+        assert_eq!(checksum(&data[..]), scalar::checksum(&data[..]));
+        assert_eq!(checksum(&data[..]), (4099u32 * 0xff % 256) as u8);
+    }
+}
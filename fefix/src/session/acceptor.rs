@@ -0,0 +1,668 @@
+use super::auth::Opaque;
+use super::{
+    Application, Config, Configure, Credentials, LogonAuthenticator, SeqNumbers, Session,
+    SessionBuilder, SessionEvent, SessionState,
+};
+use crate::definitions::fix44;
+use crate::tagvalue::{Decoder, EncoderHandle, FieldAccess, RawDecoder};
+use crate::Dictionary;
+use std::collections::{HashMap, VecDeque};
+use std::time::Instant;
+
+/// An opaque identifier for a connection accepted by an [`Acceptor`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct ConnectionId(u64);
+
+/// An action that the caller of an [`Acceptor`] must carry out on the
+/// transport layer.
+///
+/// Please note that [`AcceptorAction`] is marked with `#[non_exhaustive]`,
+/// which future-proofs the enumeration type in case more variants are added.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum AcceptorAction {
+    /// Write `data` to `connection`, as-is.
+    Transmit {
+        /// The recipient connection.
+        connection: ConnectionId,
+        /// A FIX message.
+        data: Vec<u8>,
+    },
+    /// Close `connection`. [`Acceptor::disconnected`] must not be called
+    /// afterwards.
+    Close {
+        /// The connection to close.
+        connection: ConnectionId,
+    },
+}
+
+/// The acceptor side of FIX sessions, i.e. the side that listens for
+/// connections from its counterparties.
+///
+/// Every counterparty must be registered in advance via
+/// [`Acceptor::add_counterparty`], with its own [`SessionBuilder`] and,
+/// optionally, the [`Credentials`] it must log on with (or any other
+/// [`LogonAuthenticator`], see [`Acceptor::set_authenticator`]). New
+/// connections are assigned to a counterparty based on the `SenderCompID
+/// <49>` of their first message, which must be a `Logon <A>`; connections from
+/// unknown counterparties, with wrong credentials or an `EncryptMethod <98>`
+/// other than *None*, or from counterparties that are already logged on are
+/// closed without any reply. Seq. numbers carry over from one connection to
+/// the next.
+///
+/// Just like [`Session`], [`Acceptor`] doesn't do any I/O: the caller must
+/// carry out all [`AcceptorAction`]'s, returned by
+/// [`Acceptor::next_action`].
+///
+/// # Examples
+///
+/// ```
+/// use fefix::session::{Acceptor, AcceptorAction, Application, Credentials, SessionBuilder};
+/// use std::time::Instant;
+///
+/// struct Ignore;
+///
+/// impl Application for Ignore {
+///     fn on_message(&mut self, _comp_id: &str, _message: &[u8]) {}
+/// }
+///
+/// let mut acceptor = Acceptor::new(Ignore);
+/// let mut builder: SessionBuilder = SessionBuilder::default();
+/// builder.set_sender_comp_id("SELLSIDE");
+/// acceptor.add_counterparty("BUYSIDE", builder, Some(Credentials::new("user", "pass")));
+///
+/// let connection = acceptor.accept();
+/// acceptor.feed(connection, b"8=FIX.4.4\x019=5\x0135=0\x0110=163\x01", Instant::now());
+/// assert_eq!(acceptor.next_action(), Some(AcceptorAction::Close { connection }));
+/// ```
+#[derive(Debug)]
+pub struct Acceptor<A, C = Config>
+where
+    C: Configure,
+{
+    application: A,
+    counterparties: HashMap<String, Counterparty<C>>,
+    connections: HashMap<ConnectionId, Connection>,
+    next_connection_id: u64,
+    raw_decoder: RawDecoder,
+    decoder: Decoder,
+    actions: VecDeque<AcceptorAction>,
+}
+
+#[derive(Debug)]
+struct Counterparty<C>
+where
+    C: Configure,
+{
+    builder: SessionBuilder<C>,
+    authenticator: Option<Opaque<dyn LogonAuthenticator>>,
+    seq_numbers: Option<SeqNumbers>,
+    session: Option<(ConnectionId, Session<C>)>,
+}
+
+#[derive(Debug)]
+enum Connection {
+    /// Still waiting for the first message, i.e. the `Logon <A>`.
+    Pending(Vec<u8>),
+    /// Assigned to the counterparty with this CompID.
+    Established(String),
+}
+
+impl<A, C> Acceptor<A, C>
+where
+    A: Application,
+    C: Configure,
+{
+    /// Creates a new [`Acceptor`] without any counterparties.
+    pub fn new(application: A) -> Self {
+        Self {
+            application,
+            counterparties: HashMap::new(),
+            connections: HashMap::new(),
+            next_connection_id: 0,
+            raw_decoder: RawDecoder::new(),
+            decoder: Decoder::new(Dictionary::fix44()),
+            actions: VecDeque::new(),
+        }
+    }
+
+    /// Registers the counterparty with CompID `comp_id`. Its sessions will be
+    /// created from `builder`, whose `TargetCompID <56>` is set to `comp_id`.
+    /// If `credentials` are given, its `Logon <A>` messages must carry the
+    /// same `Username <553>` and `Password <554>`.
+    pub fn add_counterparty<S>(
+        &mut self,
+        comp_id: S,
+        mut builder: SessionBuilder<C>,
+        credentials: Option<Credentials>,
+    ) where
+        S: Into<String>,
+    {
+        let comp_id = comp_id.into();
+        builder.set_target_comp_id(comp_id.as_str());
+        self.counterparties.insert(
+            comp_id,
+            Counterparty {
+                builder,
+                authenticator: credentials.map(|credentials| {
+                    Opaque(Box::new(credentials) as Box<dyn LogonAuthenticator>)
+                }),
+                seq_numbers: None,
+                session: None,
+            },
+        );
+    }
+
+    /// Replaces the [`Credentials`] of the counterparty `comp_id` with
+    /// `authenticator`, which validates all of its `Logon <A>` messages from
+    /// now on. Returns `false` if `comp_id` is not a registered counterparty.
+    pub fn set_authenticator<T>(&mut self, comp_id: &str, authenticator: T) -> bool
+    where
+        T: LogonAuthenticator + 'static,
+    {
+        match self.counterparties.get_mut(comp_id) {
+            Some(counterparty) => {
+                counterparty.authenticator = Some(Opaque(Box::new(authenticator)));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns an immutable reference to the [`Application`] of `self`.
+    pub fn application(&self) -> &A {
+        &self.application
+    }
+
+    /// Returns a mutable reference to the [`Application`] of `self`.
+    pub fn application_mut(&mut self) -> &mut A {
+        &mut self.application
+    }
+
+    /// Returns the current [`Session`] with the counterparty `comp_id`, if
+    /// any.
+    pub fn session(&self, comp_id: &str) -> Option<&Session<C>> {
+        self.counterparties
+            .get(comp_id)?
+            .session
+            .as_ref()
+            .map(|(_, session)| session)
+    }
+
+    /// Removes and returns the oldest [`AcceptorAction`] that is yet to be
+    /// carried out by the caller, if any.
+    pub fn next_action(&mut self) -> Option<AcceptorAction> {
+        self.actions.pop_front()
+    }
+
+    /// Notifies `self` of a new inbound connection, and returns its
+    /// [`ConnectionId`].
+    pub fn accept(&mut self) -> ConnectionId {
+        let connection = ConnectionId(self.next_connection_id);
+        self.next_connection_id += 1;
+        self.connections
+            .insert(connection, Connection::Pending(Vec::new()));
+        connection
+    }
+
+    /// Notifies `self` that `connection` was closed by the counterparty or by
+    /// a transport error.
+    pub fn disconnected(&mut self, connection: ConnectionId) {
+        if let Some(Connection::Established(comp_id)) = self.connections.remove(&connection) {
+            self.end_session(comp_id.as_str(), "Connection closed");
+        }
+    }
+
+    /// Processes `data` from `connection`.
+    pub fn feed(&mut self, connection: ConnectionId, data: &[u8], now: Instant) {
+        let comp_id = match self.connections.get_mut(&connection) {
+            Some(Connection::Established(comp_id)) => comp_id.clone(),
+            Some(Connection::Pending(buffer)) => {
+                buffer.extend_from_slice(data);
+                match self.raw_decoder.message_len(&buffer[..]) {
+                    Ok(Some(len)) if len <= buffer.len() => {}
+                    Ok(_) => return,
+                    Err(_) => {
+                        self.reject(connection);
+                        return;
+                    }
+                }
+                if self.assign(connection, now).is_none() {
+                    self.reject(connection);
+                }
+                return;
+            }
+            None => return,
+        };
+        if let Some(counterparty) = self.counterparties.get_mut(comp_id.as_str()) {
+            if let Some((_, session)) = counterparty.session.as_mut() {
+                session.feed(data, now);
+            }
+        }
+        self.drain_session_events(comp_id.as_str());
+    }
+
+    /// Checks the timers of all sessions. Call it periodically, e.g. once per
+    /// second.
+    pub fn tick(&mut self, now: Instant) {
+        let comp_ids: Vec<String> = self
+            .counterparties
+            .iter()
+            .filter(|(_, counterparty)| counterparty.session.is_some())
+            .map(|(comp_id, _)| comp_id.clone())
+            .collect();
+        for comp_id in comp_ids {
+            if let Some((_, session)) = self
+                .counterparties
+                .get_mut(comp_id.as_str())
+                .and_then(|c| c.session.as_mut())
+            {
+                session.tick(now);
+            }
+            self.drain_session_events(comp_id.as_str());
+        }
+    }
+
+    /// Sends an application message to the counterparty `comp_id`. See
+    /// [`Session::send`].
+    pub fn send<F>(
+        &mut self,
+        comp_id: &str,
+        msg_type: &[u8],
+        now: Instant,
+        f: F,
+    ) -> Result<(), SessionState>
+    where
+        F: FnOnce(&mut EncoderHandle<Vec<u8>>),
+    {
+        let session = self
+            .counterparties
+            .get_mut(comp_id)
+            .and_then(|counterparty| counterparty.session.as_mut())
+            .ok_or(SessionState::Disconnected)?;
+        session.1.send(msg_type, now, f)?;
+        self.drain_session_events(comp_id);
+        Ok(())
+    }
+
+    /// Logs out of the current session with the counterparty `comp_id`, if
+    /// any.
+    pub fn logout(&mut self, comp_id: &str, text: Option<&str>, now: Instant) {
+        if let Some((_, session)) = self
+            .counterparties
+            .get_mut(comp_id)
+            .and_then(|counterparty| counterparty.session.as_mut())
+        {
+            session.logout(text, now);
+        }
+        self.drain_session_events(comp_id);
+    }
+
+    /// Inspects the first message of a pending `connection` and, if it's a
+    /// valid `Logon <A>`, starts a new session with its sender. Returns the
+    /// counterparty's CompID on success.
+    fn assign(&mut self, connection: ConnectionId, now: Instant) -> Option<String> {
+        let buffer = match self.connections.remove(&connection) {
+            Some(Connection::Pending(buffer)) => buffer,
+            _ => return None,
+        };
+        let len = self.raw_decoder.message_len(&buffer[..]).ok()??;
+        let logon = self.decoder.decode(&buffer[..len]).ok()?;
+        if logon.fv_raw(fix44::MSG_TYPE) != Some(b"A") {
+            return None;
+        }
+        let comp_id: &str = logon.fv(fix44::SENDER_COMP_ID).ok()?;
+        let counterparty = match self.counterparties.get_mut(comp_id) {
+            Some(counterparty) => counterparty,
+            None => {
+                trace_event!(
+                    warn,
+                    comp_id,
+                    "Refusing Logon <A> from unknown counterparty"
+                );
+                return None;
+            }
+        };
+        if counterparty.session.is_some() {
+            trace_event!(warn, comp_id, "Refusing Logon <A>: already logged on");
+            return None;
+        }
+        match logon.fv(fix44::ENCRYPT_METHOD) {
+            Ok(fix44::EncryptMethod::None) | Err(_) => {}
+            Ok(_) => {
+                trace_event!(
+                    warn,
+                    comp_id,
+                    "Refusing Logon <A>: unsupported EncryptMethod"
+                );
+                return None;
+            }
+        }
+        if let Some(Opaque(authenticator)) = counterparty.authenticator.as_mut() {
+            if let Err(_reason) = authenticator.authenticate(comp_id, &logon) {
+                trace_event!(warn, comp_id, "Refusing Logon <A>: {}", _reason);
+                return None;
+            }
+        }
+        trace_event!(info, comp_id, "Accepting connection");
+        let comp_id = comp_id.to_string();
+        let mut builder = counterparty.builder.clone();
+        if let Some(seq_numbers) = counterparty.seq_numbers {
+            builder.set_seq_numbers(seq_numbers.next_inbound(), seq_numbers.next_outbound());
+        }
+        let mut session = builder.build();
+        session.feed(&buffer[..], now);
+        counterparty.session = Some((connection, session));
+        self.connections
+            .insert(connection, Connection::Established(comp_id.clone()));
+        self.drain_session_events(comp_id.as_str());
+        Some(comp_id)
+    }
+
+    fn reject(&mut self, connection: ConnectionId) {
+        trace_event!(warn, connection = ?connection, "Closing connection without session");
+        self.connections.remove(&connection);
+        self.actions.push_back(AcceptorAction::Close { connection });
+    }
+
+    /// Drops the current session with `comp_id`, if any.
+    fn end_session(&mut self, comp_id: &str, reason: &str) {
+        if let Some(counterparty) = self.counterparties.get_mut(comp_id) {
+            if let Some((connection, session)) = counterparty.session.take() {
+                counterparty.seq_numbers = Some(session.seq_numbers());
+                self.connections.remove(&connection);
+                self.application.on_disconnect(comp_id, reason);
+            }
+        }
+    }
+
+    fn drain_session_events(&mut self, comp_id: &str) {
+        while let Some((connection, event)) = self
+            .counterparties
+            .get_mut(comp_id)
+            .and_then(|counterparty| counterparty.session.as_mut())
+            .and_then(|(connection, session)| Some((*connection, session.next_event()?)))
+        {
+            match event {
+                SessionEvent::Outbound(data) => {
+                    self.actions
+                        .push_back(AcceptorAction::Transmit { connection, data });
+                }
+                SessionEvent::Application(data) => {
+                    self.application.on_message(comp_id, &data[..]);
+                }
+                SessionEvent::LoggedOn => self.application.on_logon(comp_id),
+                SessionEvent::Disconnect { reason } => {
+                    self.actions.push_back(AcceptorAction::Close { connection });
+                    self.end_session(comp_id, reason.as_str());
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::session::{Initiator, InitiatorAction};
+    use crate::tagvalue::Message;
+
+    #[derive(Debug, Default)]
+    struct Recorder {
+        events: Vec<String>,
+    }
+
+    impl Application for Recorder {
+        fn on_message(&mut self, comp_id: &str, _message: &[u8]) {
+            self.events.push(format!("message from {}", comp_id));
+        }
+
+        fn on_logon(&mut self, comp_id: &str) {
+            self.events.push(format!("logon {}", comp_id));
+        }
+
+        fn on_disconnect(&mut self, comp_id: &str, _reason: &str) {
+            self.events.push(format!("disconnect {}", comp_id));
+        }
+    }
+
+    fn acceptor() -> Acceptor<Recorder> {
+        let mut acceptor = Acceptor::new(Recorder::default());
+        let mut builder: SessionBuilder = SessionBuilder::default();
+        builder.set_sender_comp_id("SELLSIDE");
+        acceptor.add_counterparty("BUYSIDE", builder, Some(Credentials::new("user", "pass")));
+        acceptor
+    }
+
+    fn initiator(credentials: Credentials) -> Initiator<Recorder> {
+        let mut builder: SessionBuilder = SessionBuilder::default();
+        builder.set_sender_comp_id("BUYSIDE");
+        builder.set_target_comp_id("SELLSIDE");
+        let mut initiator = Initiator::new(builder, Recorder::default());
+        initiator.set_credentials(Some(credentials));
+        initiator
+    }
+
+    /// Moves data back and forth between `initiator` and `acceptor` until
+    /// there's nothing left to do. Returns `true` if the connection is still
+    /// open.
+    fn pump(
+        initiator: &mut Initiator<Recorder>,
+        acceptor: &mut Acceptor<Recorder>,
+        connection: ConnectionId,
+        now: Instant,
+    ) -> bool {
+        let mut open = true;
+        loop {
+            let mut idle = true;
+            while let Some(action) = initiator.next_action() {
+                idle = false;
+                match action {
+                    InitiatorAction::Transmit(data) if open => {
+                        acceptor.feed(connection, &data[..], now)
+                    }
+                    InitiatorAction::Close if open => {
+                        open = false;
+                        acceptor.disconnected(connection);
+                    }
+                    _ => {}
+                }
+            }
+            while let Some(action) = acceptor.next_action() {
+                idle = false;
+                match action {
+                    AcceptorAction::Transmit { data, .. } if open => initiator.feed(&data[..], now),
+                    AcceptorAction::Close { .. } if open => {
+                        open = false;
+                        initiator.disconnected(now);
+                    }
+                    _ => {}
+                }
+            }
+            if idle {
+                return open;
+            }
+        }
+    }
+
+    #[cfg(feature = "utils-tracing")]
+    #[test]
+    fn tracing_events_never_include_credentials() {
+        use std::fmt::{self, Write};
+        use std::sync::{Arc, Mutex};
+        use tracing::field::{Field, Visit};
+        use tracing::span::{Attributes, Id, Record};
+        use tracing::{Event, Metadata};
+
+        /// Records the messages and fields of all events and spans.
+        #[derive(Debug, Default)]
+        struct Capture(Arc<Mutex<String>>);
+
+        impl Visit for Capture {
+            fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+                let mut output = self.0.lock().unwrap();
+                writeln!(output, "{}={:?}", field.name(), value).unwrap();
+            }
+        }
+
+        impl tracing::Subscriber for Capture {
+            fn enabled(&self, _metadata: &Metadata) -> bool {
+                true
+            }
+
+            fn new_span(&self, span: &Attributes) -> Id {
+                span.record(&mut Capture(self.0.clone()));
+                Id::from_u64(1)
+            }
+
+            fn record(&self, _span: &Id, values: &Record) {
+                values.record(&mut Capture(self.0.clone()));
+            }
+
+            fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+            fn event(&self, event: &Event) {
+                event.record(&mut Capture(self.0.clone()));
+            }
+
+            fn enter(&self, _span: &Id) {}
+
+            fn exit(&self, _span: &Id) {}
+        }
+
+        let output = Arc::new(Mutex::new(String::new()));
+        let subscriber = Capture(output.clone());
+        tracing::subscriber::with_default(subscriber, || {
+            let now = Instant::now();
+            let mut acceptor = acceptor();
+            for password in ["s3cr3t!", "pass"] {
+                let mut initiator = initiator(Credentials::new("user", password));
+                initiator.next_action();
+                let connection = acceptor.accept();
+                initiator.connected(now);
+                pump(&mut initiator, &mut acceptor, connection, now);
+            }
+        });
+        let output = output.lock().unwrap();
+        assert!(output.contains("Refusing Logon <A>: wrong credentials"));
+        assert!(output.contains("Session state changed"));
+        assert!(!output.contains("s3cr3t!"));
+        assert!(!output.contains("pass"));
+    }
+
+    #[test]
+    fn initiator_and_acceptor_exchange_messages() {
+        let now = Instant::now();
+        let mut acceptor = acceptor();
+        let mut initiator = initiator(Credentials::new("user", "pass"));
+        assert_eq!(initiator.next_action(), Some(InitiatorAction::Connect));
+        let connection = acceptor.accept();
+        initiator.connected(now);
+        assert!(pump(&mut initiator, &mut acceptor, connection, now));
+        assert_eq!(
+            acceptor.session("BUYSIDE").map(|s| s.state()),
+            Some(SessionState::Active)
+        );
+        initiator
+            .send(b"D", now, |msg| msg.set(fix44::CL_ORD_ID, "ORDER"))
+            .unwrap();
+        acceptor
+            .send("BUYSIDE", b"8", now, |msg| {
+                msg.set(fix44::CL_ORD_ID, "ORDER")
+            })
+            .unwrap();
+        assert!(pump(&mut initiator, &mut acceptor, connection, now));
+        assert_eq!(
+            acceptor.application().events,
+            vec!["logon BUYSIDE", "message from BUYSIDE"]
+        );
+        assert_eq!(
+            initiator.application().events,
+            vec!["logon SELLSIDE", "message from SELLSIDE"]
+        );
+    }
+
+    #[test]
+    fn wrong_credentials_are_refused() {
+        let now = Instant::now();
+        let mut acceptor = acceptor();
+        let mut initiator = initiator(Credentials::new("user", "wrong"));
+        initiator.next_action();
+        let connection = acceptor.accept();
+        initiator.connected(now);
+        assert!(!pump(&mut initiator, &mut acceptor, connection, now));
+        assert!(acceptor.session("BUYSIDE").is_none());
+        assert!(acceptor.application().events.is_empty());
+        assert!(initiator.reconnect_at().is_some());
+    }
+
+    #[test]
+    fn signed_logons_are_authenticated() {
+        use crate::session::SignedFields;
+
+        // A toy signature, in place of e.g. HMAC-SHA256.
+        fn sign(fields: &SignedFields) -> Vec<u8> {
+            let mut signature = fields.prehash(b'|');
+            signature.reverse();
+            signature
+        }
+
+        let now = Instant::now();
+        let mut acceptor = acceptor();
+        assert!(
+            acceptor.set_authenticator("BUYSIDE", |_comp_id: &str, logon: &Message<&[u8]>| {
+                let fields = SignedFields::from_message(logon).ok_or("missing fields")?;
+                if logon.fv_raw(fix44::RAW_DATA) == Some(&sign(&fields)[..]) {
+                    Ok(())
+                } else {
+                    Err("bad signature".to_string())
+                }
+            })
+        );
+        assert!(!acceptor.set_authenticator("UNKNOWN", Credentials::new("user", "pass")));
+        for (signed, logged_on) in [(false, false), (true, true)] {
+            let mut credentials = Credentials::new("user", "pass");
+            credentials.set_new_password(Some("pass2".to_string()));
+            let mut initiator = initiator(credentials);
+            if signed {
+                initiator.set_logon_signer(|fields: &SignedFields| sign(fields));
+            }
+            initiator.next_action();
+            let connection = acceptor.accept();
+            initiator.connected(now);
+            assert_eq!(
+                pump(&mut initiator, &mut acceptor, connection, now),
+                logged_on
+            );
+        }
+        assert_eq!(acceptor.application().events, vec!["logon BUYSIDE"]);
+    }
+
+    #[test]
+    fn seq_numbers_carry_over_between_connections() {
+        let now = Instant::now();
+        let mut acceptor = acceptor();
+        let mut initiator = initiator(Credentials::new("user", "pass"));
+        initiator.next_action();
+        let connection = acceptor.accept();
+        initiator.connected(now);
+        pump(&mut initiator, &mut acceptor, connection, now);
+        acceptor.disconnected(connection);
+        initiator.disconnected(now);
+        assert_eq!(
+            acceptor.application().events,
+            vec!["logon BUYSIDE", "disconnect BUYSIDE"]
+        );
+        let later = now + std::time::Duration::from_secs(5);
+        initiator.tick(later);
+        assert_eq!(initiator.next_action(), Some(InitiatorAction::Connect));
+        let connection = acceptor.accept();
+        initiator.connected(later);
+        assert!(pump(&mut initiator, &mut acceptor, connection, later));
+        let session = acceptor.session("BUYSIDE").unwrap();
+        assert_eq!(session.state(), SessionState::Active);
+        assert_eq!(session.seq_numbers().next_inbound(), 3);
+        assert_eq!(session.seq_numbers().next_outbound(), 3);
+    }
+}
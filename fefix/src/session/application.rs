@@ -0,0 +1,101 @@
+use std::fmt;
+
+/// The application layer of an [`Initiator`](super::Initiator) or
+/// [`Acceptor`](super::Acceptor), which receives callbacks for session-level
+/// happenings and inbound application messages.
+///
+/// All callbacks receive `comp_id`, i.e. the CompID of the counterparty, so
+/// that the same [`Application`] can serve multiple sessions.
+pub trait Application {
+    /// An in-sequence, inbound application message.
+    fn on_message(&mut self, comp_id: &str, message: &[u8]);
+
+    /// The logon exchange with the counterparty is complete.
+    #[inline]
+    fn on_logon(&mut self, comp_id: &str) {
+        let _ = comp_id;
+    }
+
+    /// The session with the counterparty is over, with a human-readable
+    /// `reason`.
+    #[inline]
+    fn on_disconnect(&mut self, comp_id: &str, reason: &str) {
+        let _ = (comp_id, reason);
+    }
+}
+
+/// The `Username <553>` and `Password <554>` that initiators send with their
+/// `Logon <A>` messages, and that acceptors expect. Initiators can also
+/// request a password change via `NewPassword <925>`.
+///
+/// Passwords are never shown by the [`Debug`](fmt::Debug) implementation.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Credentials {
+    username: String,
+    password: String,
+    new_password: Option<String>,
+}
+
+impl Credentials {
+    /// Creates new [`Credentials`].
+    pub fn new<S1, S2>(username: S1, password: S2) -> Self
+    where
+        S1: Into<String>,
+        S2: Into<String>,
+    {
+        Self {
+            username: username.into(),
+            password: password.into(),
+            new_password: None,
+        }
+    }
+
+    /// Returns the `Username <553>` of `self`.
+    pub fn username(&self) -> &str {
+        self.username.as_str()
+    }
+
+    /// Returns the `Password <554>` of `self`.
+    pub fn password(&self) -> &str {
+        self.password.as_str()
+    }
+
+    /// Returns the `NewPassword <925>` of `self`, if any.
+    pub fn new_password(&self) -> Option<&str> {
+        self.new_password.as_deref()
+    }
+
+    /// Sets the `NewPassword <925>` that initiators send along with
+    /// [`Credentials::password`], to change the latter. None by default.
+    pub fn set_new_password(&mut self, new_password: Option<String>) {
+        self.new_password = new_password;
+    }
+}
+
+impl fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Credentials")
+            .field("username", &self.username)
+            .field("password", &"<redacted>")
+            .field(
+                "new_password",
+                &self.new_password.as_ref().map(|_| "<redacted>"),
+            )
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn debug_output_hides_passwords() {
+        let mut credentials = Credentials::new("alice", "hunter2");
+        credentials.set_new_password(Some("hunter3".to_string()));
+        let debug = format!("{:?}", credentials);
+        assert!(debug.contains("alice"));
+        assert!(!debug.contains("hunter2"));
+        assert!(!debug.contains("hunter3"));
+    }
+}
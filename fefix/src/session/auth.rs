@@ -0,0 +1,231 @@
+use super::Credentials;
+use crate::definitions::fix44;
+use crate::tagvalue::{FieldAccess, Message};
+use std::fmt;
+
+/// Validates inbound `Logon <A>` messages on the acceptor side, in addition
+/// to the checks of the session layer. See
+/// [`Acceptor::set_authenticator`](super::Acceptor::set_authenticator).
+///
+/// [`Credentials`] implement [`LogonAuthenticator`] by checking
+/// `Username <553>` and `Password <554>`, and so do closures with the same
+/// signature as [`LogonAuthenticator::authenticate`]. Venues that rely on
+/// `RawData <96>` signatures can recompute them via
+/// [`SignedFields::from_message`].
+///
+/// # Examples
+///
+/// ```
+/// use fefix::definitions::fix44;
+/// use fefix::session::LogonAuthenticator;
+/// use fefix::tagvalue::{Config, Configure, Decoder, FieldAccess, Message};
+/// use fefix::Dictionary;
+///
+/// let mut authenticator = |_comp_id: &str, logon: &Message<&[u8]>| {
+///     match logon.fv_raw(fix44::RAW_DATA) {
+///         Some(b"letmein") => Ok(()),
+///         _ => Err("Bad signature".to_string()),
+///     }
+/// };
+/// let mut decoder = Decoder::<Config>::new(Dictionary::fix44());
+/// decoder.config_mut().set_separator(b'|');
+/// let logon = decoder.decode(&b"8=FIX.4.4|9=21|35=A|95=7|96=letmein|10=000|"[..]).unwrap();
+/// assert_eq!(authenticator.authenticate("BUYSIDE", &logon), Ok(()));
+/// ```
+pub trait LogonAuthenticator: Send {
+    /// Returns `Ok(())` if and only if `logon`, i.e. a `Logon <A>` from the
+    /// counterparty `comp_id`, is authentic. Otherwise, returns a
+    /// human-readable reason, which is never sent to the counterparty but
+    /// might be logged: it must not contain any secrets.
+    fn authenticate(&mut self, comp_id: &str, logon: &Message<&[u8]>) -> Result<(), String>;
+}
+
+impl LogonAuthenticator for Credentials {
+    fn authenticate(&mut self, _comp_id: &str, logon: &Message<&[u8]>) -> Result<(), String> {
+        let username: Option<&str> = logon.fv(fix44::USERNAME).ok();
+        let password: Option<&str> = logon.fv(fix44::PASSWORD).ok();
+        if username == Some(self.username()) && password == Some(self.password()) {
+            Ok(())
+        } else {
+            Err("wrong credentials".to_string())
+        }
+    }
+}
+
+impl<F> LogonAuthenticator for F
+where
+    F: for<'a> FnMut(&str, &Message<'a, &'a [u8]>) -> Result<(), String> + Send,
+{
+    fn authenticate(&mut self, comp_id: &str, logon: &Message<&[u8]>) -> Result<(), String> {
+        self(comp_id, logon)
+    }
+}
+
+/// Computes `RawData <96>` signatures for outbound `Logon <A>` messages on the
+/// initiator side. See
+/// [`Initiator::set_logon_signer`](super::Initiator::set_logon_signer) and
+/// [`Session::logon_signed`](super::Session::logon_signed).
+///
+/// Closures that take [`SignedFields`] and return the signature implement
+/// [`LogonSigner`]. This crate doesn't implement any signature algorithm
+/// itself: HMAC-SHA256, as required by several crypto venues, is available in
+/// dedicated crates.
+pub trait LogonSigner: Send {
+    /// Returns the value of `RawData <96>` for a `Logon <A>` made of `fields`.
+    fn sign(&mut self, fields: &SignedFields) -> Vec<u8>;
+}
+
+impl<F> LogonSigner for F
+where
+    F: for<'a> FnMut(&SignedFields<'a>) -> Vec<u8> + Send,
+{
+    fn sign(&mut self, fields: &SignedFields) -> Vec<u8> {
+        self(fields)
+    }
+}
+
+/// The fields of a `Logon <A>` that `RawData <96>` signatures usually cover.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SignedFields<'a> {
+    sending_time: &'a str,
+    msg_type: &'a str,
+    msg_seq_num: u64,
+    sender_comp_id: &'a str,
+    target_comp_id: &'a str,
+    password: Option<&'a str>,
+}
+
+impl<'a> SignedFields<'a> {
+    pub(crate) fn new(
+        sending_time: &'a str,
+        msg_seq_num: u64,
+        sender_comp_id: &'a str,
+        target_comp_id: &'a str,
+        password: Option<&'a str>,
+    ) -> Self {
+        Self {
+            sending_time,
+            msg_type: "A",
+            msg_seq_num,
+            sender_comp_id,
+            target_comp_id,
+            password,
+        }
+    }
+
+    /// Extracts [`SignedFields`] from `logon`, e.g. to check its signature on
+    /// the acceptor side. Returns [`None`] if any of the mandatory header
+    /// fields is missing or invalid.
+    pub fn from_message(logon: &'a Message<'a, &'a [u8]>) -> Option<Self> {
+        Some(Self {
+            sending_time: logon.fv(fix44::SENDING_TIME).ok()?,
+            msg_type: logon.fv(fix44::MSG_TYPE).ok()?,
+            msg_seq_num: logon.fv(fix44::MSG_SEQ_NUM).ok()?,
+            sender_comp_id: logon.fv(fix44::SENDER_COMP_ID).ok()?,
+            target_comp_id: logon.fv(fix44::TARGET_COMP_ID).ok()?,
+            password: logon.fv(fix44::PASSWORD).ok(),
+        })
+    }
+
+    /// Returns `SendingTime <52>`, exactly as encoded.
+    pub fn sending_time(&self) -> &'a str {
+        self.sending_time
+    }
+
+    /// Returns `MsgType <35>`, i.e. `A`.
+    pub fn msg_type(&self) -> &'a str {
+        self.msg_type
+    }
+
+    /// Returns `MsgSeqNum <34>`.
+    pub fn msg_seq_num(&self) -> u64 {
+        self.msg_seq_num
+    }
+
+    /// Returns `SenderCompID <49>`.
+    pub fn sender_comp_id(&self) -> &'a str {
+        self.sender_comp_id
+    }
+
+    /// Returns `TargetCompID <56>`.
+    pub fn target_comp_id(&self) -> &'a str {
+        self.target_comp_id
+    }
+
+    /// Returns `Password <554>`, if any.
+    pub fn password(&self) -> Option<&'a str> {
+        self.password
+    }
+
+    /// Returns the fields of `self` joined by `separator`, in the order
+    /// `SendingTime <52>`, `MsgType <35>`, `MsgSeqNum <34>`, `SenderCompID
+    /// <49>`, `TargetCompID <56>`, and `Password <554>` (if any). This is the
+    /// most common input of `RawData <96>` signatures, usually with SOH as
+    /// `separator`.
+    pub fn prehash(&self, separator: u8) -> Vec<u8> {
+        let msg_seq_num = self.msg_seq_num.to_string();
+        let mut prehash = Vec::new();
+        let fields = [
+            self.sending_time,
+            self.msg_type,
+            msg_seq_num.as_str(),
+            self.sender_comp_id,
+            self.target_comp_id,
+        ];
+        for (i, field) in fields.iter().chain(self.password.iter()).enumerate() {
+            if i > 0 {
+                prehash.push(separator);
+            }
+            prehash.extend_from_slice(field.as_bytes());
+        }
+        prehash
+    }
+}
+
+/// A boxed [`LogonAuthenticator`] or [`LogonSigner`], which can't implement
+/// [`Debug`](fmt::Debug) on its own.
+pub(crate) struct Opaque<T: ?Sized>(pub Box<T>);
+
+impl<T: ?Sized> fmt::Debug for Opaque<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Opaque")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tagvalue::test_decoder;
+
+    const LOGON: &[u8] = b"8=FIX.4.4|9=88|35=A|34=1|49=BUYSIDE|52=20230101-00:00:00.000|56=SELLSIDE|553=user|554=pass|98=0|108=30|10=000|";
+
+    #[test]
+    fn credentials_check_username_and_password() {
+        let mut decoder = test_decoder();
+        let logon = decoder.decode(LOGON).unwrap();
+        assert_eq!(
+            Credentials::new("user", "pass").authenticate("BUYSIDE", &logon),
+            Ok(())
+        );
+        assert!(Credentials::new("user", "wrong")
+            .authenticate("BUYSIDE", &logon)
+            .is_err());
+    }
+
+    #[test]
+    fn prehash_of_signed_fields() {
+        let mut decoder = test_decoder();
+        let logon = decoder.decode(LOGON).unwrap();
+        let fields = SignedFields::from_message(&logon).unwrap();
+        assert_eq!(fields.msg_seq_num(), 1);
+        assert_eq!(
+            fields.prehash(b'|'),
+            b"20230101-00:00:00.000|A|1|BUYSIDE|SELLSIDE|pass".to_vec()
+        );
+        let unsigned = SignedFields::new("20230101-00:00:00.000", 2, "A", "B", None);
+        assert_eq!(
+            unsigned.prehash(b'|'),
+            b"20230101-00:00:00.000|A|2|A|B".to_vec()
+        );
+    }
+}
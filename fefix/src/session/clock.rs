@@ -0,0 +1,86 @@
+use chrono::{DateTime, Utc};
+use std::fmt::Debug;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// A source of time for all time-dependent session logic, i.e. `SendingTime
+/// <52>` stamping, heartbeat timers, and schedule checks (see
+/// [`Scheduler::tick`](super::Scheduler::tick)).
+///
+/// [`SystemClock`] is the default, while [`ManualClock`] allows for deterministic
+/// simulations of timeouts and schedule transitions.
+pub trait Clock: Debug + Send + Sync {
+    /// Returns the current monotonic time, for timers.
+    fn now(&self) -> Instant;
+
+    /// Returns the current UTC wall-clock time, for timestamps and schedules.
+    fn utc_now(&self) -> DateTime<Utc>;
+}
+
+/// The [`Clock`] of the operating system.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn utc_now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// A [`Clock`] that only moves when told so. All clones share the same time,
+/// so tests can keep a handle and advance the clock of a session.
+///
+/// # Examples
+///
+/// ```
+/// use chrono::{TimeZone, Utc};
+/// use fefix::session::{Clock, ManualClock};
+/// use std::time::Duration;
+///
+/// let clock = ManualClock::new(Utc.ymd(2021, 6, 1).and_hms(9, 0, 0));
+/// let start = clock.now();
+/// let handle = clock.clone();
+/// handle.advance(Duration::from_secs(30));
+/// assert_eq!(clock.now() - start, Duration::from_secs(30));
+/// assert_eq!(clock.utc_now(), Utc.ymd(2021, 6, 1).and_hms(9, 0, 30));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ManualClock {
+    time: Arc<Mutex<(Instant, DateTime<Utc>)>>,
+}
+
+impl ManualClock {
+    /// Creates a new [`ManualClock`] that starts at `utc`.
+    pub fn new(utc: DateTime<Utc>) -> Self {
+        Self {
+            time: Arc::new(Mutex::new((Instant::now(), utc))),
+        }
+    }
+
+    /// Moves both the monotonic and the wall-clock time forward by `duration`.
+    pub fn advance(&self, duration: Duration) {
+        let mut time = self.time.lock().unwrap();
+        time.0 += duration;
+        time.1 = time.1 + chrono::Duration::from_std(duration).unwrap();
+    }
+
+    /// Changes the wall-clock time to `utc`, e.g. to simulate clock
+    /// adjustments. The monotonic time is unaffected.
+    pub fn set_utc(&self, utc: DateTime<Utc>) {
+        self.time.lock().unwrap().1 = utc;
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Instant {
+        self.time.lock().unwrap().0
+    }
+
+    fn utc_now(&self) -> DateTime<Utc> {
+        self.time.lock().unwrap().1
+    }
+}
@@ -0,0 +1,403 @@
+use super::{Config, Configure, Session, SessionEvent, SessionState};
+use crate::tagvalue::{EncoderHandle, RawDecoder};
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt;
+use std::time::Instant;
+
+/// Uniquely identifies a [`Session`] within an [`Engine`], from our own point
+/// of view: `SenderCompID <49>` is our CompID and `TargetCompID <56>` is the
+/// counterparty's, just like in outbound messages.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SessionKey {
+    begin_string: String,
+    sender_comp_id: String,
+    target_comp_id: String,
+}
+
+impl SessionKey {
+    /// Creates a new [`SessionKey`].
+    pub fn new<S1, S2, S3>(begin_string: S1, sender_comp_id: S2, target_comp_id: S3) -> Self
+    where
+        S1: Into<String>,
+        S2: Into<String>,
+        S3: Into<String>,
+    {
+        Self {
+            begin_string: begin_string.into(),
+            sender_comp_id: sender_comp_id.into(),
+            target_comp_id: target_comp_id.into(),
+        }
+    }
+
+    /// Returns the [`SessionKey`] of `session`.
+    pub fn of<C>(session: &Session<C>) -> Self
+    where
+        C: Configure,
+    {
+        Self::new(
+            session.begin_string(),
+            session.sender_comp_id(),
+            session.target_comp_id(),
+        )
+    }
+
+    /// Returns the `BeginString <8>` of `self`.
+    pub fn begin_string(&self) -> &str {
+        self.begin_string.as_str()
+    }
+
+    /// Returns our own CompID.
+    pub fn sender_comp_id(&self) -> &str {
+        self.sender_comp_id.as_str()
+    }
+
+    /// Returns the counterparty's CompID.
+    pub fn target_comp_id(&self) -> &str {
+        self.target_comp_id.as_str()
+    }
+}
+
+impl fmt::Display for SessionKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}->{}",
+            self.begin_string, self.sender_comp_id, self.target_comp_id
+        )
+    }
+}
+
+/// The error type returned by [`Engine::feed`].
+///
+/// Please note that [`RoutingError`] is marked with `#[non_exhaustive]`,
+/// which future-proofs the enumeration type in case more variants are added.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum RoutingError {
+    /// The frame couldn't be decoded, or it lacks `SenderCompID <49>` or
+    /// `TargetCompID <56>`.
+    Garbled,
+    /// No [`Session`] of the [`Engine`] has this [`SessionKey`].
+    UnknownSession(SessionKey),
+}
+
+impl fmt::Display for RoutingError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RoutingError::Garbled => write!(f, "Garbled frame without session header"),
+            RoutingError::UnknownSession(key) => write!(f, "Unknown session {}", key),
+        }
+    }
+}
+
+impl std::error::Error for RoutingError {}
+
+/// Owns many [`Session`]'s, keyed by [`SessionKey`], i.e. by their
+/// `BeginString <8>`, `SenderCompID <49>`, and `TargetCompID <56>`.
+///
+/// [`Engine::feed`] routes inbound frames to the right [`Session`] according
+/// to their header fields, while [`Engine::next_event`] aggregates the
+/// [`SessionEvent`]'s of all sessions, each tagged with its [`SessionKey`].
+/// Just like [`Session`], [`Engine`] doesn't do any I/O: it's up to the caller
+/// to map [`SessionKey`]'s to transport connections.
+///
+/// # Examples
+///
+/// ```
+/// use fefix::session::{Engine, SessionBuilder, SessionEvent, SessionKey, SessionState};
+/// use std::time::Instant;
+///
+/// fn session(sender_comp_id: &str, target_comp_id: &str) -> fefix::session::Session {
+///     let mut builder: SessionBuilder = SessionBuilder::default();
+///     builder.set_sender_comp_id(sender_comp_id);
+///     builder.set_target_comp_id(target_comp_id);
+///     builder.build()
+/// }
+///
+/// let now = Instant::now();
+/// let mut engine = Engine::new();
+/// engine.insert(session("GATEWAY", "BUYSIDE1"));
+/// engine.insert(session("GATEWAY", "BUYSIDE2"));
+///
+/// let mut counterparty = session("BUYSIDE2", "GATEWAY");
+/// counterparty.logon(now);
+/// let logon = match counterparty.next_event() {
+///     Some(SessionEvent::Outbound(logon)) => logon,
+///     _ => unreachable!(),
+/// };
+/// let key = engine.feed(&logon[..], now).unwrap();
+/// assert_eq!(key, SessionKey::new("FIX.4.4", "GATEWAY", "BUYSIDE2"));
+/// assert_eq!(engine.session(&key).unwrap().state(), SessionState::Active);
+/// assert!(matches!(
+///     engine.next_event(),
+///     Some((k, SessionEvent::Outbound(_))) if k == key
+/// ));
+/// ```
+#[derive(Debug)]
+pub struct Engine<C = Config>
+where
+    C: Configure,
+{
+    sessions: BTreeMap<SessionKey, Session<C>>,
+    decoder: RawDecoder,
+    events: VecDeque<(SessionKey, SessionEvent)>,
+}
+
+impl<C> Engine<C>
+where
+    C: Configure,
+{
+    /// Creates a new [`Engine`] without any sessions.
+    pub fn new() -> Self {
+        Self {
+            sessions: BTreeMap::new(),
+            decoder: RawDecoder::new(),
+            events: VecDeque::new(),
+        }
+    }
+
+    /// Adds `session` to `self`, and returns the [`Session`] that previously
+    /// had the same [`SessionKey`], if any. Pending events of the replaced
+    /// [`Session`] are dropped.
+    pub fn insert(&mut self, session: Session<C>) -> Option<Session<C>> {
+        let key = SessionKey::of(&session);
+        self.sessions.insert(key, session)
+    }
+
+    /// Removes the [`Session`] with `key` from `self` and returns it, if any.
+    /// Events that it produced are still returned by [`Engine::next_event`].
+    pub fn remove(&mut self, key: &SessionKey) -> Option<Session<C>> {
+        self.drain_session_events(key);
+        self.sessions.remove(key)
+    }
+
+    /// Returns the [`Session`] with `key`, if any.
+    pub fn session(&self, key: &SessionKey) -> Option<&Session<C>> {
+        self.sessions.get(key)
+    }
+
+    /// Returns the [`Session`] with `key`, if any. Its events are collected by
+    /// [`Engine::next_event`] as well.
+    pub fn session_mut(&mut self, key: &SessionKey) -> Option<&mut Session<C>> {
+        self.sessions.get_mut(key)
+    }
+
+    /// Returns an iterator over the [`SessionKey`]'s of all sessions, in
+    /// order.
+    pub fn keys(&self) -> impl Iterator<Item = &SessionKey> {
+        self.sessions.keys()
+    }
+
+    /// Returns the number of sessions in `self`.
+    pub fn len(&self) -> usize {
+        self.sessions.len()
+    }
+
+    /// Returns `true` if and only if `self` has no sessions.
+    pub fn is_empty(&self) -> bool {
+        self.sessions.is_empty()
+    }
+
+    /// Feeds `frame`, i.e. one full inbound FIX message, to the [`Session`]
+    /// it's addressed to, and returns the [`SessionKey`] of the latter.
+    ///
+    /// Since `frame` comes from the counterparty, its `SenderCompID <49>` is
+    /// matched against [`SessionKey::target_comp_id`] and vice versa.
+    pub fn feed(&mut self, frame: &[u8], now: Instant) -> Result<SessionKey, RoutingError> {
+        let key = {
+            let frame = self
+                .decoder
+                .decode(frame)
+                .map_err(|_| RoutingError::Garbled)?;
+            let header = self.decoder.peek_header(&frame);
+            let begin_string = header_field(Some(header.begin_string()))?;
+            let sender_comp_id = header_field(header.sender_comp_id())?;
+            let target_comp_id = header_field(header.target_comp_id())?;
+            SessionKey::new(begin_string, target_comp_id, sender_comp_id)
+        };
+        match self.sessions.get_mut(&key) {
+            Some(session) => session.feed(frame, now),
+            None => {
+                trace_event!(warn, key = %key, "Dropping frame for unknown session");
+                return Err(RoutingError::UnknownSession(key));
+            }
+        }
+        self.drain_session_events(&key);
+        Ok(key)
+    }
+
+    /// Checks the timers of all sessions. Call it periodically, e.g. once per
+    /// second.
+    pub fn tick(&mut self, now: Instant) {
+        for session in self.sessions.values_mut() {
+            session.tick(now);
+        }
+        self.drain_all_events();
+    }
+
+    /// Sends an application message over the [`Session`] with `key`. See
+    /// [`Session::send`].
+    pub fn send<F>(
+        &mut self,
+        key: &SessionKey,
+        msg_type: &[u8],
+        now: Instant,
+        f: F,
+    ) -> Result<(), SessionState>
+    where
+        F: FnOnce(&mut EncoderHandle<Vec<u8>>),
+    {
+        self.sessions
+            .get_mut(key)
+            .ok_or(SessionState::Disconnected)?
+            .send(msg_type, now, f)?;
+        self.drain_session_events(key);
+        Ok(())
+    }
+
+    /// Removes and returns the oldest [`SessionEvent`] of any session, together
+    /// with the [`SessionKey`] of the latter, if any. Events of the same
+    /// session are always returned in order.
+    pub fn next_event(&mut self) -> Option<(SessionKey, SessionEvent)> {
+        if self.events.is_empty() {
+            // Sessions might have been used directly via `session_mut`.
+            self.drain_all_events();
+        }
+        self.events.pop_front()
+    }
+
+    fn drain_session_events(&mut self, key: &SessionKey) {
+        if let Some(session) = self.sessions.get_mut(key) {
+            while let Some(event) = session.next_event() {
+                self.events.push_back((key.clone(), event));
+            }
+        }
+    }
+
+    fn drain_all_events(&mut self) {
+        for (key, session) in self.sessions.iter_mut() {
+            while let Some(event) = session.next_event() {
+                self.events.push_back((key.clone(), event));
+            }
+        }
+    }
+}
+
+impl<C> Default for Engine<C>
+where
+    C: Configure,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn header_field(value: Option<&[u8]>) -> Result<&str, RoutingError> {
+    value
+        .and_then(|value| std::str::from_utf8(value).ok())
+        .ok_or(RoutingError::Garbled)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::session::SessionBuilder;
+
+    fn session(sender_comp_id: &str, target_comp_id: &str) -> Session {
+        let mut builder: SessionBuilder = SessionBuilder::default();
+        builder.set_sender_comp_id(sender_comp_id);
+        builder.set_target_comp_id(target_comp_id);
+        builder.build()
+    }
+
+    fn outbound(session: &mut Session) -> Vec<Vec<u8>> {
+        let mut messages = vec![];
+        while let Some(event) = session.next_event() {
+            if let SessionEvent::Outbound(data) = event {
+                messages.push(data);
+            }
+        }
+        messages
+    }
+
+    fn engine() -> Engine {
+        let mut engine = Engine::new();
+        assert!(engine.insert(session("GATEWAY", "ALPHA")).is_none());
+        assert!(engine.insert(session("GATEWAY", "BETA")).is_none());
+        engine
+    }
+
+    #[test]
+    fn frames_are_routed_by_header() {
+        let now = Instant::now();
+        let mut engine = engine();
+        let mut alpha = session("ALPHA", "GATEWAY");
+        let mut beta = session("BETA", "GATEWAY");
+        alpha.logon(now);
+        beta.logon(now);
+        let alpha_key = SessionKey::new("FIX.4.4", "GATEWAY", "ALPHA");
+        let beta_key = SessionKey::new("FIX.4.4", "GATEWAY", "BETA");
+        for logon in outbound(&mut beta) {
+            assert_eq!(engine.feed(&logon[..], now), Ok(beta_key.clone()));
+        }
+        assert_eq!(
+            engine.session(&alpha_key).unwrap().state(),
+            SessionState::AwaitingLogon
+        );
+        assert_eq!(
+            engine.session(&beta_key).unwrap().state(),
+            SessionState::Active
+        );
+        for logon in outbound(&mut alpha) {
+            assert_eq!(engine.feed(&logon[..], now), Ok(alpha_key.clone()));
+        }
+        let keys: Vec<SessionKey> = std::iter::from_fn(|| engine.next_event())
+            .filter(|(_, event)| *event == SessionEvent::LoggedOn)
+            .map(|(key, _)| key)
+            .collect();
+        assert_eq!(keys, vec![beta_key, alpha_key]);
+    }
+
+    #[test]
+    fn unknown_sessions_are_reported() {
+        let now = Instant::now();
+        let mut engine = engine();
+        let mut gamma = session("GAMMA", "GATEWAY");
+        gamma.logon(now);
+        let logon = outbound(&mut gamma).remove(0);
+        assert_eq!(
+            engine.feed(&logon[..], now),
+            Err(RoutingError::UnknownSession(SessionKey::new(
+                "FIX.4.4", "GATEWAY", "GAMMA"
+            )))
+        );
+        assert_eq!(
+            engine.feed(b"8=FIX.4.4\x019=5\x0135=0\x0110=163\x01", now),
+            Err(RoutingError::Garbled)
+        );
+        assert_eq!(engine.next_event(), None);
+    }
+
+    #[test]
+    fn events_of_direct_session_access_are_collected() {
+        let now = Instant::now();
+        let mut engine = engine();
+        let key = SessionKey::new("FIX.4.4", "GATEWAY", "ALPHA");
+        engine.session_mut(&key).unwrap().logon(now);
+        assert!(matches!(
+            engine.next_event(),
+            Some((k, SessionEvent::Outbound(_))) if k == key
+        ));
+        assert_eq!(
+            engine.send(&key, b"D", now, |_msg| {}),
+            Err(SessionState::LogonSent)
+        );
+        let removed = engine.remove(&key).unwrap();
+        assert_eq!(removed.target_comp_id(), "ALPHA");
+        assert_eq!(engine.len(), 1);
+        assert_eq!(
+            engine.send(&key, b"D", now, |_msg| {}),
+            Err(SessionState::Disconnected)
+        );
+    }
+}
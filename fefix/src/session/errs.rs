@@ -33,3 +33,34 @@ pub fn production_env() -> String {
 pub fn missing_field(name: &str, tag: u32) -> String {
     format!("Missing mandatory field {}({})", name, tag)
 }
+
+pub fn begin_string(expected: &str) -> String {
+    format!("Invalid BeginString(8), expected value {}", expected)
+}
+
+pub fn orig_sending_time() -> String {
+    "OrigSendingTime(122) is later than SendingTime(52)".to_string()
+}
+
+pub fn comp_id() -> String {
+    "Invalid SenderCompID(49) or TargetCompID(56)".to_string()
+}
+
+pub fn first_message_not_logon() -> String {
+    "First message is not a Logon(A)".to_string()
+}
+
+pub fn new_seq_no(seq_number: u64) -> String {
+    format!(
+        "Invalid NewSeqNo(36), expected value greater than or equal to {}",
+        seq_number
+    )
+}
+
+pub fn test_request_timeout() -> String {
+    "No response to TestRequest(1)".to_string()
+}
+
+pub fn logout_timeout() -> String {
+    "No response to Logout(5)".to_string()
+}
@@ -0,0 +1,189 @@
+use std::time::{Duration, Instant};
+
+/// An action that must be taken to keep a FIX connection alive, as determined
+/// by [`HeartbeatTimers::poll`].
+///
+/// Please note that [`HeartbeatAction`] is marked with `#[non_exhaustive]`,
+/// which future-proofs the enumeration type in case more variants are added.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum HeartbeatAction {
+    /// Nothing was sent for a whole heartbeat interval, so a `Heartbeat <0>`
+    /// must be sent.
+    SendHeartbeat,
+    /// Nothing was received for 1.2 heartbeat intervals, so a `TestRequest
+    /// <1>` must be sent.
+    SendTestRequest,
+    /// A `TestRequest <1>` was sent and still nothing was received for 2
+    /// heartbeat intervals: the connection is dead.
+    Disconnect,
+}
+
+/// Keeps track of the last-sent and last-received times of a FIX connection
+/// against its heartbeat interval, i.e. `HeartBtInt <108>`.
+///
+/// [`HeartbeatTimers`] doesn't read the system clock, nor sends messages: the
+/// current time is always supplied by the caller, who must then act upon
+/// [`HeartbeatTimers::poll`]. This makes it fully deterministic.
+///
+/// # Examples
+///
+/// ```
+/// use fefix::session::{HeartbeatAction, HeartbeatTimers};
+/// use std::time::{Duration, Instant};
+///
+/// let start = Instant::now();
+/// let secs = |n| start + Duration::from_secs(n);
+/// let mut timers = HeartbeatTimers::new(Duration::from_secs(30), start);
+/// assert_eq!(timers.poll(secs(10)), None);
+/// assert_eq!(timers.poll(secs(30)), Some(HeartbeatAction::SendHeartbeat));
+/// timers.on_sent(secs(30));
+/// assert_eq!(timers.poll(secs(36)), Some(HeartbeatAction::SendTestRequest));
+/// timers.on_test_request_sent(secs(36));
+/// assert_eq!(timers.poll(secs(50)), None);
+/// assert_eq!(timers.poll(secs(60)), Some(HeartbeatAction::Disconnect));
+/// ```
+#[derive(Debug, Clone)]
+pub struct HeartbeatTimers {
+    heartbeat: Duration,
+    last_sent: Instant,
+    last_received: Instant,
+    test_request_sent: Option<Instant>,
+}
+
+impl HeartbeatTimers {
+    /// Creates new [`HeartbeatTimers`] with a heartbeat interval of
+    /// `heartbeat`, as if something had just been sent and received at `now`.
+    pub fn new(heartbeat: Duration, now: Instant) -> Self {
+        Self {
+            heartbeat,
+            last_sent: now,
+            last_received: now,
+            test_request_sent: None,
+        }
+    }
+
+    /// Returns the heartbeat interval of `self`.
+    pub fn heartbeat(&self) -> Duration {
+        self.heartbeat
+    }
+
+    /// Changes the heartbeat interval of `self`, e.g. after `Logon <A>`
+    /// negotiations.
+    pub fn set_heartbeat(&mut self, heartbeat: Duration) {
+        self.heartbeat = heartbeat;
+    }
+
+    /// Returns the time at which anything was last sent.
+    pub fn last_sent(&self) -> Instant {
+        self.last_sent
+    }
+
+    /// Returns the time at which anything was last received.
+    pub fn last_received(&self) -> Instant {
+        self.last_received
+    }
+
+    /// Returns `true` if a `TestRequest <1>` was sent and nothing was received
+    /// since then; `false` otherwise.
+    pub fn is_test_request_pending(&self) -> bool {
+        self.test_request_sent.is_some()
+    }
+
+    /// Must be called whenever any message is sent.
+    pub fn on_sent(&mut self, now: Instant) {
+        self.last_sent = now;
+    }
+
+    /// Must be called whenever any message is received. This also settles any
+    /// pending `TestRequest <1>`.
+    pub fn on_received(&mut self, now: Instant) {
+        self.last_received = now;
+        self.test_request_sent = None;
+    }
+
+    /// Must be called when a `TestRequest <1>` is sent, instead of
+    /// [`HeartbeatTimers::on_sent`].
+    pub fn on_test_request_sent(&mut self, now: Instant) {
+        self.last_sent = now;
+        self.test_request_sent = Some(now);
+    }
+
+    /// Returns the most urgent [`HeartbeatAction`] that is due at `now`, if
+    /// any. Once the action is taken and `self` is notified about it, the next
+    /// action (if any) is returned.
+    pub fn poll(&self, now: Instant) -> Option<HeartbeatAction> {
+        let silence = now.saturating_duration_since(self.last_received);
+        if self.test_request_sent.is_some() {
+            if silence >= self.heartbeat * 2 {
+                return Some(HeartbeatAction::Disconnect);
+            }
+        } else if silence >= self.test_request_threshold() {
+            return Some(HeartbeatAction::SendTestRequest);
+        }
+        if now.saturating_duration_since(self.last_sent) >= self.heartbeat {
+            Some(HeartbeatAction::SendHeartbeat)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the earliest time at which [`HeartbeatTimers::poll`] might
+    /// return an action, e.g. for scheduling wake-ups.
+    pub fn next_deadline(&self) -> Instant {
+        let receive_deadline = if self.test_request_sent.is_some() {
+            self.last_received + self.heartbeat * 2
+        } else {
+            self.last_received + self.test_request_threshold()
+        };
+        receive_deadline.min(self.last_sent + self.heartbeat)
+    }
+
+    fn test_request_threshold(&self) -> Duration {
+        self.heartbeat + self.heartbeat / 5
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn secs(start: Instant, n: u64) -> Instant {
+        start + Duration::from_secs(n)
+    }
+
+    #[test]
+    fn inbound_traffic_settles_test_requests() {
+        let start = Instant::now();
+        let mut timers = HeartbeatTimers::new(Duration::from_secs(10), start);
+        timers.on_test_request_sent(secs(start, 12));
+        assert!(timers.is_test_request_pending());
+        timers.on_received(secs(start, 15));
+        assert!(!timers.is_test_request_pending());
+        assert_eq!(
+            timers.poll(secs(start, 22)),
+            Some(HeartbeatAction::SendHeartbeat)
+        );
+        timers.on_sent(secs(start, 22));
+        assert_eq!(timers.poll(secs(start, 26)), None);
+        assert_eq!(
+            timers.poll(secs(start, 27)),
+            Some(HeartbeatAction::SendTestRequest)
+        );
+    }
+
+    #[test]
+    fn next_deadline_is_the_earliest_threshold() {
+        let start = Instant::now();
+        let mut timers = HeartbeatTimers::new(Duration::from_secs(10), start);
+        assert_eq!(timers.next_deadline(), secs(start, 10));
+        timers.on_sent(secs(start, 5));
+        assert_eq!(timers.next_deadline(), secs(start, 12));
+        timers.on_test_request_sent(secs(start, 12));
+        assert_eq!(timers.next_deadline(), secs(start, 20));
+        assert_eq!(
+            timers.poll(timers.next_deadline()),
+            Some(HeartbeatAction::Disconnect)
+        );
+    }
+}
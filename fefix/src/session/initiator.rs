@@ -0,0 +1,533 @@
+use super::auth::Opaque;
+use super::{
+    Application, Configure, Credentials, LogonSigner, SeqNumbers, Session, SessionBuilder,
+    SessionEvent,
+};
+use crate::definitions::fix44;
+use crate::session::{Config, SessionState};
+use crate::tagvalue::EncoderHandle;
+use futures::{select, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, FutureExt};
+use futures_timer::Delay;
+use std::collections::VecDeque;
+use std::future::Future;
+use std::io;
+use std::time::{Duration, Instant};
+
+/// How an [`Initiator`] reconnects after a failed connection attempt or a
+/// dropped session.
+///
+/// The delay before every reconnection attempt starts at
+/// [`ReconnectPolicy::set_initial_backoff`] and doubles after every
+/// consecutive failure, up to [`ReconnectPolicy::set_max_backoff`]. A
+/// successful logon resets it.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ReconnectPolicy {
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    max_attempts: Option<u32>,
+}
+
+impl ReconnectPolicy {
+    /// Sets the delay before the first reconnection attempt. One second by
+    /// default.
+    pub fn set_initial_backoff(&mut self, backoff: Duration) {
+        self.initial_backoff = backoff;
+    }
+
+    /// Sets the maximum delay between reconnection attempts. One minute by
+    /// default.
+    pub fn set_max_backoff(&mut self, backoff: Duration) {
+        self.max_backoff = backoff;
+    }
+
+    /// Sets the maximum number of consecutive failures, after which the
+    /// [`Initiator`] gives up. No limit by default.
+    pub fn set_max_attempts(&mut self, max_attempts: Option<u32>) {
+        self.max_attempts = max_attempts;
+    }
+
+    /// Returns the delay before the next reconnection attempt after
+    /// `failures` consecutive failures, or [`None`] if the [`Initiator`] must
+    /// give up.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix::session::ReconnectPolicy;
+    /// use std::time::Duration;
+    ///
+    /// let mut policy = ReconnectPolicy::default();
+    /// policy.set_max_attempts(Some(8));
+    /// assert_eq!(policy.backoff(0), Some(Duration::from_secs(1)));
+    /// assert_eq!(policy.backoff(3), Some(Duration::from_secs(8)));
+    /// assert_eq!(policy.backoff(7), Some(Duration::from_secs(60)));
+    /// assert_eq!(policy.backoff(8), None);
+    /// ```
+    pub fn backoff(&self, failures: u32) -> Option<Duration> {
+        if let Some(max_attempts) = self.max_attempts {
+            if failures >= max_attempts {
+                return None;
+            }
+        }
+        let factor = 1u32.checked_shl(failures).unwrap_or(u32::MAX);
+        let backoff = self
+            .initial_backoff
+            .checked_mul(factor)
+            .unwrap_or(self.max_backoff);
+        Some(backoff.min(self.max_backoff))
+    }
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(60),
+            max_attempts: None,
+        }
+    }
+}
+
+/// An action that the caller of an [`Initiator`] must carry out on the
+/// transport layer.
+///
+/// Please note that [`InitiatorAction`] is marked with `#[non_exhaustive]`,
+/// which future-proofs the enumeration type in case more variants are added.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum InitiatorAction {
+    /// Open a new connection to the counterparty, then call
+    /// [`Initiator::connected`] or [`Initiator::connection_failed`].
+    Connect,
+    /// Write these bytes to the current connection, as-is.
+    Transmit(Vec<u8>),
+    /// Close the current connection. [`Initiator::disconnected`] must not be
+    /// called afterwards.
+    Close,
+    /// The [`Initiator`] won't reconnect anymore, either because of
+    /// [`Initiator::logout`] or because the [`ReconnectPolicy`] gave up.
+    Stopped,
+}
+
+/// The initiator side of a FIX session, i.e. the side that connects to the
+/// counterparty and sends the first `Logon <A>`.
+///
+/// [`Initiator`] bundles a [`Session`] with logon [`Credentials`] and a
+/// [`ReconnectPolicy`], and delivers inbound application messages to an
+/// [`Application`]. Seq. numbers carry over from one connection to the next.
+/// Just like [`Session`], [`Initiator`] doesn't do any I/O: the caller must
+/// carry out all [`InitiatorAction`]'s, returned by
+/// [`Initiator::next_action`]. [`Initiator::run`] does exactly that over any
+/// asynchronous transport.
+///
+/// `SessionBuilder::set_resend_from_store` is not supported yet: inbound
+/// `ResendRequest <2>` messages are always answered with gap fills.
+///
+/// # Examples
+///
+/// ```
+/// use fefix::session::{Application, Initiator, InitiatorAction, SessionBuilder};
+/// use std::time::Instant;
+///
+/// #[derive(Default)]
+/// struct Printer;
+///
+/// impl Application for Printer {
+///     fn on_message(&mut self, comp_id: &str, message: &[u8]) {
+///         println!("{}: {:?}", comp_id, message);
+///     }
+/// }
+///
+/// let mut builder: SessionBuilder = SessionBuilder::default();
+/// builder.set_sender_comp_id("BUYSIDE");
+/// builder.set_target_comp_id("SELLSIDE");
+/// let mut initiator = Initiator::new(builder, Printer);
+/// assert_eq!(initiator.next_action(), Some(InitiatorAction::Connect));
+/// initiator.connected(Instant::now());
+/// assert!(matches!(initiator.next_action(), Some(InitiatorAction::Transmit(_))));
+/// ```
+#[derive(Debug)]
+pub struct Initiator<A, C = Config>
+where
+    C: Configure,
+{
+    builder: SessionBuilder<C>,
+    application: A,
+    credentials: Option<Credentials>,
+    logon_signer: Option<Opaque<dyn LogonSigner>>,
+    reconnect_policy: ReconnectPolicy,
+    session: Option<Session<C>>,
+    seq_numbers: Option<SeqNumbers>,
+    failures: u32,
+    logged_on: bool,
+    reconnect_at: Option<Instant>,
+    stopped: bool,
+    actions: VecDeque<InitiatorAction>,
+}
+
+impl<A, C> Initiator<A, C>
+where
+    A: Application,
+    C: Configure,
+{
+    /// Creates a new [`Initiator`], which will create its sessions from
+    /// `builder`. The first [`InitiatorAction`] is always
+    /// [`InitiatorAction::Connect`].
+    pub fn new(builder: SessionBuilder<C>, application: A) -> Self {
+        let mut actions = VecDeque::new();
+        actions.push_back(InitiatorAction::Connect);
+        Self {
+            builder,
+            application,
+            credentials: None,
+            logon_signer: None,
+            reconnect_policy: ReconnectPolicy::default(),
+            session: None,
+            seq_numbers: None,
+            failures: 0,
+            logged_on: false,
+            reconnect_at: None,
+            stopped: false,
+            actions,
+        }
+    }
+
+    /// Sets the [`Credentials`] to send with every `Logon <A>`. None by
+    /// default.
+    pub fn set_credentials(&mut self, credentials: Option<Credentials>) {
+        self.credentials = credentials;
+    }
+
+    /// Sets the [`LogonSigner`] that computes the `RawData <96>` signature of
+    /// every `Logon <A>`. None by default.
+    pub fn set_logon_signer<S>(&mut self, signer: S)
+    where
+        S: LogonSigner + 'static,
+    {
+        self.logon_signer = Some(Opaque(Box::new(signer)));
+    }
+
+    /// Sets the [`ReconnectPolicy`] of `self`.
+    pub fn set_reconnect_policy(&mut self, policy: ReconnectPolicy) {
+        self.reconnect_policy = policy;
+    }
+
+    /// Returns an immutable reference to the [`Application`] of `self`.
+    pub fn application(&self) -> &A {
+        &self.application
+    }
+
+    /// Returns a mutable reference to the [`Application`] of `self`.
+    pub fn application_mut(&mut self) -> &mut A {
+        &mut self.application
+    }
+
+    /// Returns the [`Session`] over the current connection, if any.
+    pub fn session(&self) -> Option<&Session<C>> {
+        self.session.as_ref()
+    }
+
+    /// Returns the instant of the next reconnection attempt, if one is
+    /// scheduled. [`Initiator::tick`] must be called at that time.
+    pub fn reconnect_at(&self) -> Option<Instant> {
+        self.reconnect_at
+    }
+
+    /// Removes and returns the oldest [`InitiatorAction`] that is yet to be
+    /// carried out by the caller, if any.
+    pub fn next_action(&mut self) -> Option<InitiatorAction> {
+        self.actions.pop_front()
+    }
+
+    /// Notifies `self` that the connection requested via
+    /// [`InitiatorAction::Connect`] is open, which starts the logon exchange.
+    pub fn connected(&mut self, now: Instant) {
+        let mut builder = self.builder.clone();
+        if let Some(seq_numbers) = self.seq_numbers {
+            builder.set_seq_numbers(seq_numbers.next_inbound(), seq_numbers.next_outbound());
+        }
+        let mut session = builder.build();
+        trace_event!(info, failures = self.failures, "Connection established");
+        let credentials = self.credentials.as_ref();
+        let add_credentials = |msg: &mut EncoderHandle<Vec<u8>>| {
+            if let Some(credentials) = credentials {
+                msg.set(fix44::USERNAME, credentials.username());
+                msg.set(fix44::PASSWORD, credentials.password());
+                if let Some(new_password) = credentials.new_password() {
+                    msg.set(fix44::NEW_PASSWORD, new_password);
+                }
+            }
+        };
+        match self.logon_signer.as_mut() {
+            Some(Opaque(signer)) => {
+                let password = credentials.map(|credentials| credentials.password());
+                session.logon_signed(now, password, &mut **signer, add_credentials);
+            }
+            None => session.logon_with(now, add_credentials),
+        }
+        self.session = Some(session);
+        self.logged_on = false;
+        self.drain_session_events(now);
+    }
+
+    /// Notifies `self` that the connection requested via
+    /// [`InitiatorAction::Connect`] couldn't be opened.
+    pub fn connection_failed(&mut self, now: Instant) {
+        self.failures += 1;
+        trace_event!(warn, failures = self.failures, "Connection attempt failed");
+        self.schedule_reconnect(now);
+    }
+
+    /// Notifies `self` that the current connection was closed by the
+    /// counterparty or by a transport error.
+    pub fn disconnected(&mut self, now: Instant) {
+        self.end_session("Connection closed", now);
+    }
+
+    /// Processes `data` from the current connection.
+    pub fn feed(&mut self, data: &[u8], now: Instant) {
+        if let Some(session) = self.session.as_mut() {
+            session.feed(data, now);
+            self.drain_session_events(now);
+        }
+    }
+
+    /// Checks session timers and the reconnection schedule. Call it
+    /// periodically, e.g. once per second.
+    pub fn tick(&mut self, now: Instant) {
+        if let Some(session) = self.session.as_mut() {
+            session.tick(now);
+            self.drain_session_events(now);
+        } else if let Some(reconnect_at) = self.reconnect_at {
+            if now >= reconnect_at {
+                self.reconnect_at = None;
+                self.actions.push_back(InitiatorAction::Connect);
+            }
+        }
+    }
+
+    /// Sends an application message. See [`Session::send`].
+    pub fn send<F>(&mut self, msg_type: &[u8], now: Instant, f: F) -> Result<(), SessionState>
+    where
+        F: FnOnce(&mut EncoderHandle<Vec<u8>>),
+    {
+        let session = self.session.as_mut().ok_or(SessionState::Disconnected)?;
+        session.send(msg_type, now, f)?;
+        self.drain_session_events(now);
+        Ok(())
+    }
+
+    /// Logs out of the current session, if any, and stops reconnecting.
+    pub fn logout(&mut self, text: Option<&str>, now: Instant) {
+        self.stopped = true;
+        self.reconnect_at = None;
+        match self.session.as_mut() {
+            Some(session) if session.state() == SessionState::Active => {
+                session.logout(text, now);
+                self.drain_session_events(now);
+            }
+            Some(_) => {
+                self.actions.push_back(InitiatorAction::Close);
+                self.disconnected(now);
+            }
+            None => self.actions.push_back(InitiatorAction::Stopped),
+        }
+    }
+
+    /// Drives `self` until it stops, carrying out all [`InitiatorAction`]'s
+    /// over asynchronous connections opened by `connect`. Timers are checked
+    /// once per second.
+    ///
+    /// This function is executor-agnostic; Tokio users can adapt their
+    /// sockets via `tokio-util`'s `compat` module.
+    pub async fn run<F, Fut, I, O>(&mut self, mut connect: F) -> io::Result<()>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = io::Result<(I, O)>>,
+        I: AsyncRead + Unpin,
+        O: AsyncWrite + Unpin,
+    {
+        let mut buffer = vec![0; 4096];
+        let mut connection: Option<(I, O)> = None;
+        loop {
+            while let Some(action) = self.next_action() {
+                match action {
+                    InitiatorAction::Connect => match connect().await {
+                        Ok(io) => {
+                            connection = Some(io);
+                            self.connected(self.builder.clock().now());
+                        }
+                        Err(_) => self.connection_failed(self.builder.clock().now()),
+                    },
+                    InitiatorAction::Transmit(data) => {
+                        if let Some((_, output)) = connection.as_mut() {
+                            if output.write_all(&data[..]).await.is_err() {
+                                connection = None;
+                                self.disconnected(self.builder.clock().now());
+                            }
+                        }
+                    }
+                    InitiatorAction::Close => {
+                        if let Some((_, mut output)) = connection.take() {
+                            output.close().await.ok();
+                        }
+                    }
+                    InitiatorAction::Stopped => return Ok(()),
+                }
+            }
+            let mut tick = Delay::new(Duration::from_secs(1)).fuse();
+            let read_result = match connection.as_mut() {
+                Some((input, _)) => {
+                    let mut read = input.read(&mut buffer[..]).fuse();
+                    select! {
+                        result = read => Some(result),
+                        () = tick => None,
+                    }
+                }
+                None => {
+                    tick.await;
+                    None
+                }
+            };
+            match read_result {
+                Some(Ok(len)) if len > 0 => self.feed(&buffer[..len], self.builder.clock().now()),
+                // EOF or I/O error.
+                Some(_) => {
+                    connection = None;
+                    self.disconnected(self.builder.clock().now());
+                }
+                None => self.tick(self.builder.clock().now()),
+            }
+        }
+    }
+
+    /// Drops the current session, if any, and schedules the next connection.
+    fn end_session(&mut self, reason: &str, now: Instant) {
+        if let Some(session) = self.session.take() {
+            // Connections that never get to log on count as failures.
+            if !self.logged_on {
+                self.failures += 1;
+            }
+            self.seq_numbers = Some(session.seq_numbers());
+            self.application
+                .on_disconnect(session.target_comp_id(), reason);
+            self.schedule_reconnect(now);
+        }
+    }
+
+    fn schedule_reconnect(&mut self, now: Instant) {
+        if self.stopped {
+            self.actions.push_back(InitiatorAction::Stopped);
+            return;
+        }
+        match self.reconnect_policy.backoff(self.failures) {
+            Some(backoff) => {
+                trace_event!(info, backoff = ?backoff, "Scheduling reconnection");
+                self.reconnect_at = Some(now + backoff);
+            }
+            None => {
+                trace_event!(warn, failures = self.failures, "Giving up reconnecting");
+                self.stopped = true;
+                self.actions.push_back(InitiatorAction::Stopped);
+            }
+        }
+    }
+
+    fn drain_session_events(&mut self, now: Instant) {
+        while let Some(session) = self.session.as_mut() {
+            let event = match session.next_event() {
+                Some(event) => event,
+                None => break,
+            };
+            match event {
+                SessionEvent::Outbound(data) => {
+                    self.actions.push_back(InitiatorAction::Transmit(data));
+                }
+                SessionEvent::Application(data) => {
+                    self.application
+                        .on_message(session.target_comp_id(), &data[..]);
+                }
+                SessionEvent::LoggedOn => {
+                    self.logged_on = true;
+                    self.failures = 0;
+                    self.application.on_logon(session.target_comp_id());
+                }
+                SessionEvent::Disconnect { reason } => {
+                    self.actions.push_back(InitiatorAction::Close);
+                    self.end_session(reason.as_str(), now);
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[derive(Debug, Default)]
+    struct Ignore;
+
+    impl Application for Ignore {
+        fn on_message(&mut self, _comp_id: &str, _message: &[u8]) {}
+    }
+
+    fn initiator() -> Initiator<Ignore> {
+        let mut initiator = Initiator::new(SessionBuilder::default(), Ignore);
+        let mut policy = ReconnectPolicy::default();
+        policy.set_max_attempts(Some(2));
+        initiator.set_reconnect_policy(policy);
+        assert_eq!(initiator.next_action(), Some(InitiatorAction::Connect));
+        initiator
+    }
+
+    #[test]
+    fn backoff_doubles_until_max() {
+        let mut policy = ReconnectPolicy::default();
+        policy.set_initial_backoff(Duration::from_millis(500));
+        policy.set_max_backoff(Duration::from_secs(3));
+        let backoffs: Vec<Option<Duration>> = (0..5).map(|i| policy.backoff(i)).collect();
+        assert_eq!(
+            backoffs,
+            vec![
+                Some(Duration::from_millis(500)),
+                Some(Duration::from_secs(1)),
+                Some(Duration::from_secs(2)),
+                Some(Duration::from_secs(3)),
+                Some(Duration::from_secs(3)),
+            ]
+        );
+        assert_eq!(policy.backoff(u32::MAX), Some(Duration::from_secs(3)));
+    }
+
+    #[test]
+    fn failed_connections_are_retried_until_the_policy_gives_up() {
+        let now = Instant::now();
+        let mut initiator = initiator();
+        initiator.connection_failed(now);
+        assert_eq!(initiator.reconnect_at(), Some(now + Duration::from_secs(2)));
+        initiator.tick(now + Duration::from_secs(1));
+        assert_eq!(initiator.next_action(), None);
+        initiator.tick(now + Duration::from_secs(2));
+        assert_eq!(initiator.next_action(), Some(InitiatorAction::Connect));
+        initiator.connection_failed(now);
+        assert_eq!(initiator.next_action(), Some(InitiatorAction::Stopped));
+        assert_eq!(initiator.reconnect_at(), None);
+    }
+
+    #[test]
+    fn logout_before_logon_stops_the_initiator() {
+        let now = Instant::now();
+        let mut initiator = initiator();
+        initiator.connected(now);
+        assert!(matches!(
+            initiator.next_action(),
+            Some(InitiatorAction::Transmit(_))
+        ));
+        initiator.logout(None, now);
+        assert_eq!(initiator.next_action(), Some(InitiatorAction::Close));
+        assert_eq!(initiator.next_action(), Some(InitiatorAction::Stopped));
+        assert!(initiator.session().is_none());
+    }
+}
@@ -0,0 +1,569 @@
+use super::{Clock, SystemClock};
+use chrono::{DateTime, TimeZone, Utc};
+use std::convert::TryFrom;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// An audit trail of all the traffic of a FIX session, as required by most
+/// regulators.
+///
+/// Unlike [`MessageStore`](super::MessageStore), which only keeps outbound
+/// messages for retransmission, a [`MessageLog`] records all raw messages in
+/// both directions, together with session events (logons, disconnections,
+/// etc.). Call [`MessageLog::on_incoming`] before feeding a frame to the
+/// [`Session`](super::Session), and [`MessageLog::on_outgoing`] for each
+/// [`SessionEvent::Outbound`](super::SessionEvent::Outbound).
+pub trait MessageLog {
+    /// The error type returned by all fallible operations.
+    type Error;
+
+    /// Records the inbound raw `message`.
+    fn on_incoming(&mut self, message: &[u8]) -> Result<(), Self::Error>;
+
+    /// Records the outbound raw `message`.
+    fn on_outgoing(&mut self, message: &[u8]) -> Result<(), Self::Error>;
+
+    /// Records a human-readable description of a session event.
+    fn on_event(&mut self, text: &str) -> Result<(), Self::Error>;
+}
+
+/// The kind of a [`CaptureRecord`].
+///
+/// Please note that [`RecordKind`] is marked with `#[non_exhaustive]`, which
+/// future-proofs the enumeration type in case more variants are added.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum RecordKind {
+    /// An inbound message, i.e. [`MessageLog::on_incoming`].
+    Incoming,
+    /// An outbound message, i.e. [`MessageLog::on_outgoing`].
+    Outgoing,
+    /// A session event, i.e. [`MessageLog::on_event`].
+    Event,
+}
+
+impl RecordKind {
+    fn to_byte(self) -> u8 {
+        match self {
+            Self::Incoming => 0,
+            Self::Outgoing => 1,
+            Self::Event => 2,
+        }
+    }
+
+    fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            0 => Some(Self::Incoming),
+            1 => Some(Self::Outgoing),
+            2 => Some(Self::Event),
+            _ => None,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Incoming => "IN ",
+            Self::Outgoing => "OUT",
+            Self::Event => "EVT",
+        }
+    }
+}
+
+/// A [`MessageLog`] that writes human-readable flat files, one record per
+/// line, and starts a new file once the current one is too large or too old.
+///
+/// Each line is made of a UTC timestamp with microsecond precision, `IN `,
+/// `OUT` or `EVT`, and the raw FIX message (or event text), e.g.:
+///
+/// ```text
+/// 20230101-12:00:00.000000 IN  8=FIX.4.4|9=5|35=0|10=163|
+/// ```
+///
+/// Files are named `<prefix>.<n>.log`, where `n` starts at 1 and increases
+/// with each rotation. Reopening a directory appends to the last file.
+///
+/// # Examples
+///
+/// ```no_run
+/// use fefix::session::{MessageLog, RotatingFileLog};
+///
+/// let mut log = RotatingFileLog::open("logs", "FIX.4.4-BUYSIDE-SELLSIDE").unwrap();
+/// log.set_max_size(Some(64 * 1024 * 1024));
+/// log.on_event("Connected").unwrap();
+/// log.on_outgoing(b"8=FIX.4.4\x019=5\x0135=0\x0110=163\x01").unwrap();
+/// ```
+#[derive(Debug)]
+pub struct RotatingFileLog {
+    dir: PathBuf,
+    prefix: String,
+    index: u64,
+    file: BufWriter<File>,
+    size: u64,
+    opened_at: DateTime<Utc>,
+    max_size: Option<u64>,
+    max_age: Option<Duration>,
+    clock: Arc<dyn Clock>,
+}
+
+impl RotatingFileLog {
+    /// Opens the last log file named after `prefix` within `dir`, or creates
+    /// the first one. `dir` is created if it doesn't exist. No rotation by
+    /// default.
+    pub fn open<P, S>(dir: P, prefix: S) -> io::Result<Self>
+    where
+        P: AsRef<Path>,
+        S: Into<String>,
+    {
+        let dir = dir.as_ref().to_path_buf();
+        let prefix = prefix.into();
+        fs::create_dir_all(&dir)?;
+        let mut index = 1;
+        for entry in fs::read_dir(&dir)? {
+            let name = entry?.file_name();
+            let n = name
+                .to_str()
+                .and_then(|name| name.strip_prefix(prefix.as_str()))
+                .and_then(|name| name.strip_prefix('.'))
+                .and_then(|name| name.strip_suffix(".log"))
+                .and_then(|n| n.parse::<u64>().ok());
+            if let Some(n) = n {
+                index = index.max(n);
+            }
+        }
+        let clock: Arc<dyn Clock> = Arc::new(SystemClock);
+        let (file, size) = open_log_file(&dir, &prefix, index)?;
+        Ok(Self {
+            opened_at: clock.utc_now(),
+            dir,
+            prefix,
+            index,
+            file,
+            size,
+            max_size: None,
+            max_age: None,
+            clock,
+        })
+    }
+
+    /// Starts a new file before any record that would make the current one
+    /// larger than `max_size` bytes. Records larger than `max_size` still get
+    /// a file of their own.
+    pub fn set_max_size(&mut self, max_size: Option<u64>) {
+        self.max_size = max_size;
+    }
+
+    /// Starts a new file before any record that comes more than `max_age`
+    /// after the current file was opened, e.g. daily.
+    pub fn set_max_age(&mut self, max_age: Option<Duration>) {
+        self.max_age = max_age;
+    }
+
+    /// Sets the [`Clock`] that stamps records and drives time-based rotation.
+    /// [`SystemClock`] by default.
+    pub fn set_clock<K>(&mut self, clock: K)
+    where
+        K: Clock + 'static,
+    {
+        self.clock = Arc::new(clock);
+        self.opened_at = self.clock.utc_now();
+    }
+
+    /// Returns the path of the file that is currently written to.
+    pub fn path(&self) -> PathBuf {
+        log_file_path(&self.dir, &self.prefix, self.index)
+    }
+
+    /// Forces all buffered records to disk.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+
+    /// Closes the current file and starts a new one.
+    pub fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        self.index += 1;
+        let (file, size) = open_log_file(&self.dir, &self.prefix, self.index)?;
+        self.file = file;
+        self.size = size;
+        self.opened_at = self.clock.utc_now();
+        Ok(())
+    }
+
+    fn write_record(&mut self, kind: RecordKind, data: &[u8]) -> io::Result<()> {
+        let now = self.clock.utc_now();
+        let timestamp = now.format("%Y%m%d-%H:%M:%S%.6f").to_string();
+        // Timestamp, space, label, space, data, newline.
+        let len = (timestamp.len() + 5 + data.len() + 1) as u64;
+        let too_large = match self.max_size {
+            Some(max_size) => self.size > 0 && self.size + len > max_size,
+            None => false,
+        };
+        let too_old = match self.max_age {
+            Some(max_age) => match (now - self.opened_at).to_std() {
+                Ok(age) => age >= max_age,
+                Err(_) => false,
+            },
+            None => false,
+        };
+        if too_large || too_old {
+            self.rotate()?;
+        }
+        self.file.write_all(timestamp.as_bytes())?;
+        self.file.write_all(b" ")?;
+        self.file.write_all(kind.label().as_bytes())?;
+        self.file.write_all(b" ")?;
+        self.file.write_all(data)?;
+        self.file.write_all(b"\n")?;
+        self.size += len;
+        Ok(())
+    }
+}
+
+impl MessageLog for RotatingFileLog {
+    type Error = io::Error;
+
+    fn on_incoming(&mut self, message: &[u8]) -> Result<(), Self::Error> {
+        self.write_record(RecordKind::Incoming, message)
+    }
+
+    fn on_outgoing(&mut self, message: &[u8]) -> Result<(), Self::Error> {
+        self.write_record(RecordKind::Outgoing, message)
+    }
+
+    fn on_event(&mut self, text: &str) -> Result<(), Self::Error> {
+        self.write_record(RecordKind::Event, text.as_bytes())
+    }
+}
+
+impl Drop for RotatingFileLog {
+    fn drop(&mut self) {
+        self.file.flush().ok();
+    }
+}
+
+fn log_file_path(dir: &Path, prefix: &str, index: u64) -> PathBuf {
+    dir.join(format!("{}.{}.log", prefix, index))
+}
+
+fn open_log_file(dir: &Path, prefix: &str, index: u64) -> io::Result<(BufWriter<File>, u64)> {
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(log_file_path(dir, prefix, index))?;
+    let size = file.metadata()?.len();
+    Ok((BufWriter::new(file), size))
+}
+
+/// The magic bytes at the start of all capture files.
+const CAPTURE_MAGIC: &[u8; 8] = b"FEFIXCAP";
+const CAPTURE_VERSION: u16 = 1;
+// Timestamp (8 bytes), kind (1 byte), and data length (4 bytes).
+const CAPTURE_RECORD_HEADER_LEN: usize = 13;
+
+/// A [`MessageLog`] that writes a compact, timestamped binary capture, in the
+/// spirit of pcap files, for exact replay via [`CaptureReader`].
+///
+/// A capture starts with the 8 magic bytes `FEFIXCAP` and a little-endian
+/// `u16` format version (currently 1). Each record is then made of:
+///
+/// - the UTC timestamp, as little-endian `i64` nanoseconds since the Unix
+///   epoch;
+/// - the [`RecordKind`], as one byte: 0 (incoming), 1 (outgoing) or 2
+///   (event);
+/// - the data length, as little-endian `u32`;
+/// - the raw data.
+///
+/// # Examples
+///
+/// ```
+/// use fefix::session::{CaptureLog, CaptureReader, MessageLog, RecordKind};
+///
+/// let mut log = CaptureLog::new(Vec::new()).unwrap();
+/// log.on_incoming(b"8=FIX.4.4\x019=5\x0135=0\x0110=163\x01").unwrap();
+/// log.on_event("Disconnected").unwrap();
+/// let capture = log.into_inner().unwrap();
+///
+/// let mut records = CaptureReader::new(&capture[..]).unwrap();
+/// let record = records.next().unwrap().unwrap();
+/// assert_eq!(record.kind(), RecordKind::Incoming);
+/// assert_eq!(record.data(), b"8=FIX.4.4\x019=5\x0135=0\x0110=163\x01");
+/// assert_eq!(records.next().unwrap().unwrap().data(), b"Disconnected");
+/// assert!(records.next().is_none());
+/// ```
+#[derive(Debug)]
+pub struct CaptureLog<W = BufWriter<File>>
+where
+    W: Write,
+{
+    writer: W,
+    clock: Arc<dyn Clock>,
+}
+
+impl CaptureLog {
+    /// Creates a new capture file at `path`, or truncates the existing one.
+    pub fn create<P>(path: P) -> io::Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        Self::new(BufWriter::new(File::create(path)?))
+    }
+}
+
+impl<W> CaptureLog<W>
+where
+    W: Write,
+{
+    /// Creates a new [`CaptureLog`] that writes to `writer`, starting with the
+    /// capture header.
+    pub fn new(mut writer: W) -> io::Result<Self> {
+        writer.write_all(CAPTURE_MAGIC)?;
+        writer.write_all(&CAPTURE_VERSION.to_le_bytes())?;
+        Ok(Self {
+            writer,
+            clock: Arc::new(SystemClock),
+        })
+    }
+
+    /// Sets the [`Clock`] that stamps records. [`SystemClock`] by default.
+    pub fn set_clock<K>(&mut self, clock: K)
+    where
+        K: Clock + 'static,
+    {
+        self.clock = Arc::new(clock);
+    }
+
+    /// Forces all buffered records to the underlying writer.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    /// Flushes `self` and returns the underlying writer.
+    pub fn into_inner(mut self) -> io::Result<W> {
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+
+    fn write_record(&mut self, kind: RecordKind, data: &[u8]) -> io::Result<()> {
+        let len = u32::try_from(data.len())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "Record is too large"))?;
+        let nanos = self.clock.utc_now().timestamp_nanos();
+        let mut header = [0u8; CAPTURE_RECORD_HEADER_LEN];
+        header[..8].copy_from_slice(&nanos.to_le_bytes());
+        header[8] = kind.to_byte();
+        header[9..].copy_from_slice(&len.to_le_bytes());
+        self.writer.write_all(&header)?;
+        self.writer.write_all(data)
+    }
+}
+
+impl<W> MessageLog for CaptureLog<W>
+where
+    W: Write,
+{
+    type Error = io::Error;
+
+    fn on_incoming(&mut self, message: &[u8]) -> Result<(), Self::Error> {
+        self.write_record(RecordKind::Incoming, message)
+    }
+
+    fn on_outgoing(&mut self, message: &[u8]) -> Result<(), Self::Error> {
+        self.write_record(RecordKind::Outgoing, message)
+    }
+
+    fn on_event(&mut self, text: &str) -> Result<(), Self::Error> {
+        self.write_record(RecordKind::Event, text.as_bytes())
+    }
+}
+
+/// A record of a capture, as read by [`CaptureReader`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CaptureRecord {
+    timestamp: DateTime<Utc>,
+    kind: RecordKind,
+    data: Vec<u8>,
+}
+
+impl CaptureRecord {
+    /// Returns when `self` was recorded.
+    pub fn timestamp(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+
+    /// Returns the [`RecordKind`] of `self`.
+    pub fn kind(&self) -> RecordKind {
+        self.kind
+    }
+
+    /// Returns the raw message (or event text) of `self`.
+    pub fn data(&self) -> &[u8] {
+        &self.data[..]
+    }
+
+    /// Returns the raw message (or event text) of `self`, by value.
+    pub fn into_data(self) -> Vec<u8> {
+        self.data
+    }
+}
+
+/// An [`Iterator`] over the [`CaptureRecord`]s written by [`CaptureLog`], in
+/// order. A truncated last record, e.g. after a crash, ends the iteration
+/// with an [`io::ErrorKind::UnexpectedEof`] error.
+#[derive(Debug)]
+pub struct CaptureReader<R> {
+    reader: R,
+    done: bool,
+}
+
+impl<R> CaptureReader<R>
+where
+    R: Read,
+{
+    /// Reads the capture header from `reader`, and fails unless it's a
+    /// supported capture.
+    pub fn new(mut reader: R) -> io::Result<Self> {
+        let mut header = [0u8; 10];
+        reader.read_exact(&mut header)?;
+        let version = u16::from_le_bytes([header[8], header[9]]);
+        if &header[..8] != CAPTURE_MAGIC || version != CAPTURE_VERSION {
+            return Err(invalid_data("Not a supported capture"));
+        }
+        Ok(Self {
+            reader,
+            done: false,
+        })
+    }
+
+    fn read_record(&mut self) -> io::Result<Option<CaptureRecord>> {
+        let mut header = [0u8; CAPTURE_RECORD_HEADER_LEN];
+        let mut filled = 0;
+        while filled < header.len() {
+            match self.reader.read(&mut header[filled..]) {
+                Ok(0) if filled == 0 => return Ok(None),
+                Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+                Ok(n) => filled += n,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(err),
+            }
+        }
+        let mut nanos = [0u8; 8];
+        nanos.copy_from_slice(&header[..8]);
+        let nanos = i64::from_le_bytes(nanos);
+        let kind =
+            RecordKind::from_byte(header[8]).ok_or_else(|| invalid_data("Invalid record kind"))?;
+        let len = u32::from_le_bytes([header[9], header[10], header[11], header[12]]);
+        let mut data = vec![0u8; len as usize];
+        self.reader.read_exact(&mut data[..])?;
+        Ok(Some(CaptureRecord {
+            timestamp: Utc.timestamp_nanos(nanos),
+            kind,
+            data,
+        }))
+    }
+}
+
+impl<R> Iterator for CaptureReader<R>
+where
+    R: Read,
+{
+    type Item = io::Result<CaptureRecord>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let record = self.read_record().transpose();
+        if !matches!(record, Some(Ok(_))) {
+            self.done = true;
+        }
+        record
+    }
+}
+
+fn invalid_data(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::session::ManualClock;
+
+    fn clock() -> ManualClock {
+        ManualClock::new(Utc.ymd(2023, 1, 1).and_hms(12, 0, 0))
+    }
+
+    fn temp_dir() -> PathBuf {
+        std::env::temp_dir().join(format!("fefix-{}", uuid::Uuid::new_v4()))
+    }
+
+    #[test]
+    fn rotating_file_log_rotates_by_size_and_age() {
+        let dir = temp_dir();
+        let clock = clock();
+        let mut log = RotatingFileLog::open(&dir, "FIX").unwrap();
+        log.set_clock(clock.clone());
+        log.set_max_size(Some(120));
+        log.set_max_age(Some(Duration::from_secs(3600)));
+        log.on_incoming(b"8=FIX.4.4|9=5|35=0|10=163|").unwrap();
+        log.on_outgoing(b"8=FIX.4.4|9=5|35=0|10=163|").unwrap();
+        // Too large for the first file.
+        log.on_event("Logout").unwrap();
+        assert_eq!(log.path(), dir.join("FIX.2.log"));
+        clock.advance(Duration::from_secs(3600));
+        log.on_event("Reconnected").unwrap();
+        assert_eq!(log.path(), dir.join("FIX.3.log"));
+        drop(log);
+        assert_eq!(
+            fs::read_to_string(dir.join("FIX.1.log")).unwrap(),
+            "20230101-12:00:00.000000 IN  8=FIX.4.4|9=5|35=0|10=163|\n\
+             20230101-12:00:00.000000 OUT 8=FIX.4.4|9=5|35=0|10=163|\n"
+        );
+        assert_eq!(
+            fs::read_to_string(dir.join("FIX.3.log")).unwrap(),
+            "20230101-13:00:00.000000 EVT Reconnected\n"
+        );
+        // Reopening appends to the last file.
+        let mut log = RotatingFileLog::open(&dir, "FIX").unwrap();
+        log.on_event("Restarted").unwrap();
+        assert_eq!(log.path(), dir.join("FIX.3.log"));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn capture_roundtrip() {
+        let clock = clock();
+        let mut log = CaptureLog::new(Vec::new()).unwrap();
+        log.set_clock(clock.clone());
+        log.on_outgoing(b"8=FIX.4.4|9=5|35=0|10=163|").unwrap();
+        clock.advance(Duration::from_millis(1500));
+        log.on_incoming(b"8=FIX.4.4|9=5|35=1|10=164|").unwrap();
+        let capture = log.into_inner().unwrap();
+        let records: Vec<CaptureRecord> = CaptureReader::new(&capture[..])
+            .unwrap()
+            .map(|record| record.unwrap())
+            .collect();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].kind(), RecordKind::Outgoing);
+        assert_eq!(records[1].kind(), RecordKind::Incoming);
+        assert_eq!(records[1].data(), b"8=FIX.4.4|9=5|35=1|10=164|");
+        assert_eq!(
+            records[1].timestamp() - records[0].timestamp(),
+            chrono::Duration::milliseconds(1500)
+        );
+    }
+
+    #[test]
+    fn capture_reader_detects_truncation_and_bad_headers() {
+        let mut log = CaptureLog::new(Vec::new()).unwrap();
+        log.on_event("Connected").unwrap();
+        let capture = log.into_inner().unwrap();
+        let mut records = CaptureReader::new(&capture[..capture.len() - 1]).unwrap();
+        let err = records.next().unwrap().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+        assert!(records.next().is_none());
+        assert!(CaptureReader::new(&b"8=FIX.4.4|"[..]).is_err());
+    }
+}
@@ -0,0 +1,401 @@
+use super::SeqNumbers;
+use std::collections::BTreeMap;
+use std::convert::Infallible;
+
+/// Persistent storage of outbound messages and seq. numbers of a FIX session.
+///
+/// A [`MessageStore`] allows a FIX session to recover after a reconnect, so
+/// that `ResendRequest <2>` messages can still be serviced and seq. numbers
+/// don't restart from 1.
+pub trait MessageStore {
+    /// The error type returned by all fallible operations.
+    type Error;
+
+    /// Saves the outbound `message` with `MsgSeqNum <34>` equal to `seq_num`.
+    /// Any previously stored message with the same `seq_num` is overwritten.
+    fn store(&mut self, seq_num: u64, message: &[u8]) -> Result<(), Self::Error>;
+
+    /// Retrieves the outbound message with `MsgSeqNum <34>` equal to
+    /// `seq_num`, if any.
+    fn fetch(&mut self, seq_num: u64) -> Result<Option<&[u8]>, Self::Error>;
+
+    /// Returns the seq. numbers that were last persisted via
+    /// [`MessageStore::set_seq_numbers`].
+    fn seq_numbers(&self) -> SeqNumbers;
+
+    /// Persists `seq_numbers`.
+    fn set_seq_numbers(&mut self, seq_numbers: SeqNumbers) -> Result<(), Self::Error>;
+
+    /// Deletes all stored messages and resets seq. numbers to 1.
+    fn reset(&mut self) -> Result<(), Self::Error>;
+}
+
+/// A [`MessageStore`] that keeps everything in memory. Its contents are lost
+/// when it's dropped.
+#[derive(Debug, Clone, Default)]
+pub struct InMemoryStore {
+    messages: BTreeMap<u64, Vec<u8>>,
+    seq_numbers: SeqNumbers,
+}
+
+impl InMemoryStore {
+    /// Creates a new, empty [`InMemoryStore`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of stored messages.
+    pub fn len(&self) -> usize {
+        self.messages.len()
+    }
+
+    /// Returns `true` if and only if no message is stored.
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+}
+
+impl MessageStore for InMemoryStore {
+    type Error = Infallible;
+
+    fn store(&mut self, seq_num: u64, message: &[u8]) -> Result<(), Self::Error> {
+        self.messages.insert(seq_num, message.to_vec());
+        Ok(())
+    }
+
+    fn fetch(&mut self, seq_num: u64) -> Result<Option<&[u8]>, Self::Error> {
+        Ok(self.messages.get(&seq_num).map(|msg| &msg[..]))
+    }
+
+    fn seq_numbers(&self) -> SeqNumbers {
+        self.seq_numbers
+    }
+
+    fn set_seq_numbers(&mut self, seq_numbers: SeqNumbers) -> Result<(), Self::Error> {
+        self.seq_numbers = seq_numbers;
+        Ok(())
+    }
+
+    fn reset(&mut self) -> Result<(), Self::Error> {
+        self.messages.clear();
+        self.seq_numbers = SeqNumbers::default();
+        Ok(())
+    }
+}
+
+#[cfg(feature = "utils-memmap2")]
+pub use mmap_store::MmapStore;
+
+#[cfg(feature = "utils-memmap2")]
+mod mmap_store {
+    use super::*;
+    use memmap2::Mmap;
+    use nohash_hasher::IntMap;
+    use std::convert::TryInto;
+    use std::fs::{self, File, OpenOptions};
+    use std::io::{self, Write};
+    use std::path::{Path, PathBuf};
+
+    const MESSAGES_FILENAME: &str = "messages";
+    const SEQ_NUMBERS_FILENAME: &str = "seqnums";
+    // Seq. numbers are written here first and then renamed over
+    // `SEQ_NUMBERS_FILENAME`, so a crash never leaves a partial file behind.
+    const SEQ_NUMBERS_TMP_FILENAME: &str = "seqnums.tmp";
+    // Each record starts with its seq. number and length, both little-endian.
+    const RECORD_HEADER_LEN: usize = 12;
+
+    /// A [`MessageStore`] backed by files in a directory. Messages are appended
+    /// to a log file, which is memory-mapped for reading.
+    ///
+    /// An incomplete trailing record, e.g. the result of a crash mid-write, is
+    /// discarded when the store is opened. Seq. numbers are replaced
+    /// atomically, so they're either the old or the new ones after a crash.
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "utils-memmap2")))]
+    #[derive(Debug)]
+    pub struct MmapStore {
+        dir: PathBuf,
+        file: File,
+        len: u64,
+        mmap: Option<Mmap>,
+        mmap_is_stale: bool,
+        // Offsets and lengths of message contents within the log file.
+        index: IntMap<u64, (usize, usize)>,
+        seq_numbers: SeqNumbers,
+    }
+
+    impl MmapStore {
+        /// Opens the [`MmapStore`] inside `dir`, creating it if it doesn't
+        /// exist yet.
+        pub fn open<P>(dir: P) -> io::Result<Self>
+        where
+            P: AsRef<Path>,
+        {
+            let dir = dir.as_ref().to_path_buf();
+            fs::create_dir_all(&dir)?;
+            let file = OpenOptions::new()
+                .read(true)
+                .append(true)
+                .create(true)
+                .open(dir.join(MESSAGES_FILENAME))?;
+            let seq_numbers = read_seq_numbers(&dir.join(SEQ_NUMBERS_FILENAME))?;
+            let mut store = Self {
+                dir,
+                file,
+                len: 0,
+                mmap: None,
+                mmap_is_stale: true,
+                index: IntMap::default(),
+                seq_numbers,
+            };
+            store.rebuild_index()?;
+            Ok(store)
+        }
+
+        /// Returns the directory of `self`.
+        pub fn dir(&self) -> &Path {
+            &self.dir
+        }
+
+        fn rebuild_index(&mut self) -> io::Result<()> {
+            self.remap()?;
+            self.index.clear();
+            let data = self.mmap.as_ref().map(|mmap| &mmap[..]).unwrap_or(&[]);
+            let mut i = 0;
+            while data.len() - i >= RECORD_HEADER_LEN {
+                let seq_num = u64::from_le_bytes(data[i..i + 8].try_into().unwrap());
+                let len = u32::from_le_bytes(data[i + 8..i + 12].try_into().unwrap()) as usize;
+                let start = i + RECORD_HEADER_LEN;
+                if data.len() - start < len {
+                    break;
+                }
+                self.index.insert(seq_num, (start, len));
+                i = start + len;
+            }
+            if i < data.len() {
+                self.mmap = None;
+                self.file.set_len(i as u64)?;
+                self.remap()?;
+            }
+            Ok(())
+        }
+
+        fn remap(&mut self) -> io::Result<()> {
+            self.len = self.file.metadata()?.len();
+            self.mmap = if self.len == 0 {
+                None
+            } else {
+                // SAFETY: the log file is append-only, so already mapped
+                // regions are never modified by `self`.
+                Some(unsafe { Mmap::map(&self.file)? })
+            };
+            self.mmap_is_stale = false;
+            Ok(())
+        }
+    }
+
+    impl MessageStore for MmapStore {
+        type Error = io::Error;
+
+        fn store(&mut self, seq_num: u64, message: &[u8]) -> Result<(), Self::Error> {
+            let len: u32 = message
+                .len()
+                .try_into()
+                .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "Message too long"))?;
+            let mut record = Vec::with_capacity(RECORD_HEADER_LEN + message.len());
+            record.extend_from_slice(&seq_num.to_le_bytes());
+            record.extend_from_slice(&len.to_le_bytes());
+            record.extend_from_slice(message);
+            if let Err(err) = self.file.write_all(&record[..]) {
+                // Partial writes would shift all later records; the (already
+                // stale) memory map never extends beyond `self.len`.
+                self.file.set_len(self.len)?;
+                return Err(err);
+            }
+            let start = self.len as usize + RECORD_HEADER_LEN;
+            self.index.insert(seq_num, (start, message.len()));
+            self.len += record.len() as u64;
+            self.mmap_is_stale = true;
+            Ok(())
+        }
+
+        fn fetch(&mut self, seq_num: u64) -> Result<Option<&[u8]>, Self::Error> {
+            let (start, len) = match self.index.get(&seq_num) {
+                Some(location) => *location,
+                None => return Ok(None),
+            };
+            if self.mmap_is_stale {
+                self.remap()?;
+            }
+            Ok(self.mmap.as_ref().map(|mmap| &mmap[start..start + len]))
+        }
+
+        fn seq_numbers(&self) -> SeqNumbers {
+            self.seq_numbers
+        }
+
+        fn set_seq_numbers(&mut self, seq_numbers: SeqNumbers) -> Result<(), Self::Error> {
+            let mut bytes = [0u8; 16];
+            bytes[..8].copy_from_slice(&seq_numbers.next_inbound().to_le_bytes());
+            bytes[8..].copy_from_slice(&seq_numbers.next_outbound().to_le_bytes());
+            let tmp_path = self.dir.join(SEQ_NUMBERS_TMP_FILENAME);
+            let mut tmp = File::create(&tmp_path)?;
+            tmp.write_all(&bytes[..])?;
+            tmp.sync_all()?;
+            drop(tmp);
+            fs::rename(&tmp_path, self.dir.join(SEQ_NUMBERS_FILENAME))?;
+            // The rename itself is only durable once the directory is synced.
+            #[cfg(unix)]
+            File::open(&self.dir)?.sync_all()?;
+            self.seq_numbers = seq_numbers;
+            Ok(())
+        }
+
+        fn reset(&mut self) -> Result<(), Self::Error> {
+            self.mmap = None;
+            self.file.set_len(0)?;
+            self.index.clear();
+            self.set_seq_numbers(SeqNumbers::default())?;
+            self.remap()
+        }
+    }
+
+    fn read_seq_numbers(path: &Path) -> io::Result<SeqNumbers> {
+        let bytes = match fs::read(path) {
+            Ok(bytes) => bytes,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                return Ok(SeqNumbers::default());
+            }
+            Err(err) => return Err(err),
+        };
+        if bytes.len() != 16 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Invalid seq. numbers file",
+            ));
+        }
+        Ok(SeqNumbers {
+            next_inbound: u64::from_le_bytes(bytes[..8].try_into().unwrap()),
+            next_outbound: u64::from_le_bytes(bytes[8..].try_into().unwrap()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn store_and_fetch<S>(store: &mut S)
+    where
+        S: MessageStore,
+        S::Error: std::fmt::Debug,
+    {
+        store.store(1, b"foo").unwrap();
+        store.store(2, b"bar").unwrap();
+        assert_eq!(store.fetch(1).unwrap(), Some(&b"foo"[..]));
+        assert_eq!(store.fetch(2).unwrap(), Some(&b"bar"[..]));
+        assert_eq!(store.fetch(3).unwrap(), None);
+        store.store(2, b"spam").unwrap();
+        assert_eq!(store.fetch(2).unwrap(), Some(&b"spam"[..]));
+        store
+            .set_seq_numbers(SeqNumbers {
+                next_inbound: 5,
+                next_outbound: 3,
+            })
+            .unwrap();
+        assert_eq!(store.seq_numbers().next_inbound(), 5);
+        store.reset().unwrap();
+        assert_eq!(store.fetch(1).unwrap(), None);
+        assert_eq!(store.seq_numbers().next_outbound(), 1);
+    }
+
+    #[test]
+    fn in_memory_store() {
+        store_and_fetch(&mut InMemoryStore::new());
+    }
+
+    #[cfg(feature = "utils-memmap2")]
+    fn temp_dir() -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("fefix-{}", uuid::Uuid::new_v4()))
+    }
+
+    #[cfg(feature = "utils-memmap2")]
+    #[test]
+    fn mmap_store() {
+        let dir = temp_dir();
+        store_and_fetch(&mut MmapStore::open(&dir).unwrap());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[cfg(feature = "utils-memmap2")]
+    #[test]
+    fn mmap_store_survives_reopening() {
+        let dir = temp_dir();
+        {
+            let mut store = MmapStore::open(&dir).unwrap();
+            store.store(1, b"foo").unwrap();
+            store.store(2, b"bar").unwrap();
+            store
+                .set_seq_numbers(SeqNumbers {
+                    next_inbound: 4,
+                    next_outbound: 3,
+                })
+                .unwrap();
+        }
+        let mut store = MmapStore::open(&dir).unwrap();
+        assert_eq!(store.fetch(1).unwrap(), Some(&b"foo"[..]));
+        assert_eq!(store.fetch(2).unwrap(), Some(&b"bar"[..]));
+        assert_eq!(store.seq_numbers().next_inbound(), 4);
+        assert_eq!(store.seq_numbers().next_outbound(), 3);
+        drop(store);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[cfg(feature = "utils-memmap2")]
+    #[test]
+    fn mmap_store_discards_incomplete_record() {
+        let dir = temp_dir();
+        {
+            let mut store = MmapStore::open(&dir).unwrap();
+            store.store(1, b"foo").unwrap();
+        }
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(dir.join("messages"))
+            .unwrap();
+        std::io::Write::write_all(&mut file, &[2, 0, 0]).unwrap();
+        drop(file);
+        let mut store = MmapStore::open(&dir).unwrap();
+        assert_eq!(store.fetch(1).unwrap(), Some(&b"foo"[..]));
+        store.store(2, b"bar").unwrap();
+        assert_eq!(store.fetch(2).unwrap(), Some(&b"bar"[..]));
+        drop(store);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[cfg(feature = "utils-memmap2")]
+    #[test]
+    fn mmap_store_ignores_interrupted_seq_numbers_update() {
+        let dir = temp_dir();
+        {
+            let mut store = MmapStore::open(&dir).unwrap();
+            store
+                .set_seq_numbers(SeqNumbers {
+                    next_inbound: 7,
+                    next_outbound: 9,
+                })
+                .unwrap();
+        }
+        assert!(!dir.join("seqnums.tmp").exists());
+        // A crash before the rename leaves a partial temporary file.
+        std::fs::write(dir.join("seqnums.tmp"), [1, 2, 3]).unwrap();
+        let mut store = MmapStore::open(&dir).unwrap();
+        assert_eq!(store.seq_numbers().next_inbound(), 7);
+        assert_eq!(store.seq_numbers().next_outbound(), 9);
+        store.set_seq_numbers(SeqNumbers::default()).unwrap();
+        drop(store);
+        let store = MmapStore::open(&dir).unwrap();
+        assert_eq!(store.seq_numbers().next_inbound(), 1);
+        drop(store);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
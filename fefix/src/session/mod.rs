@@ -5,21 +5,61 @@
 //! The above is a conceptual view of the FIX Session layer, complete with its
 //! state machine and transitions between initiator and acceptor.
 
+mod acceptor;
+mod application;
+mod auth;
 pub mod backends;
+mod clock;
 mod config;
 mod connection;
+mod engine;
 mod errs;
 mod event_loop;
 mod heartbeat_rule;
+mod heartbeat_timers;
+mod initiator;
+mod message_log;
+mod message_store;
+mod outbound_queue;
+mod reject;
+mod replay;
 mod resend_request_range;
+mod resend_responder;
+#[cfg(feature = "utils-chrono")]
+mod scheduler;
 mod seq_numbers;
+mod session_config;
+mod state_machine;
+pub mod testkit;
+mod throttle;
 
+pub use acceptor::{Acceptor, AcceptorAction, ConnectionId};
+pub use application::{Application, Credentials};
+pub use auth::{LogonAuthenticator, LogonSigner, SignedFields};
+pub use clock::{Clock, ManualClock, SystemClock};
 pub use config::{Config, Configure};
 pub use connection::*;
+pub use engine::{Engine, RoutingError, SessionKey};
 pub use event_loop::*;
 pub use heartbeat_rule::HeartbeatRule;
+pub use heartbeat_timers::{HeartbeatAction, HeartbeatTimers};
+pub use initiator::{Initiator, InitiatorAction, ReconnectPolicy};
+pub use message_log::{
+    CaptureLog, CaptureReader, CaptureRecord, MessageLog, RecordKind, RotatingFileLog,
+};
+pub use message_store::*;
+pub use outbound_queue::{Backpressure, OutboundQueue, QueuedMessage};
+pub use reject::{BusinessMessageReject, Reject};
+pub use replay::{replay, ReplayError, ReplayReport};
 pub use resend_request_range::ResendRequestRange;
+pub use resend_responder::ResendResponder;
+#[cfg(feature = "utils-chrono")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "utils-chrono")))]
+pub use scheduler::{Schedule, Scheduler, SchedulerEvent, SessionTimeZone, SessionTimeZoneOffset};
 pub use seq_numbers::{SeqNumberError, SeqNumbers};
+pub use session_config::{SessionConfig, SessionConfigError};
+pub use state_machine::{Session, SessionBuilder, SessionEvent, SessionState};
+pub use throttle::Throttle;
 
 use crate::tagvalue::Message;
 use std::ops::Range;
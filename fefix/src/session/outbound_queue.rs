@@ -0,0 +1,232 @@
+use std::collections::VecDeque;
+use std::fmt;
+
+/// A message that was taken out of an [`OutboundQueue`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QueuedMessage<T> {
+    /// A FIX message that was already encoded (and sequenced) by a
+    /// [`Session`](super::Session), to be written to the transport layer
+    /// as-is.
+    Admin(Vec<u8>),
+    /// An application message that is yet to be sent, e.g. via
+    /// [`Session::send`](super::Session::send).
+    Application(T),
+}
+
+/// The error type returned by [`OutboundQueue::push_application`] when the
+/// application lane is full. It gives back the refused message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Backpressure<T>(pub T);
+
+impl<T> fmt::Display for Backpressure<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Outbound queue is full")
+    }
+}
+
+impl<T> std::error::Error for Backpressure<T> where T: fmt::Debug {}
+
+/// An outbound queue with two priority lanes: one for admin messages (e.g.
+/// `Heartbeat <0>`, `TestRequest <1>`, resend responses) and one for
+/// application messages.
+///
+/// The admin lane is unbounded and always has priority, so that admin traffic
+/// is never starved by bursts of application messages: a slow counterparty
+/// would otherwise disconnect us for missing heartbeats. The application lane
+/// is bounded instead. Once it's full, [`OutboundQueue::push_application`]
+/// refuses new messages until the lane drains down to its low watermark (see
+/// [`OutboundQueue::set_low_watermark`]), which lets producers wait for
+/// [`OutboundQueue::is_backpressured`] to turn `false` instead of flapping at
+/// full capacity.
+///
+/// Since `MsgSeqNum <34>` is assigned when messages are encoded, the
+/// application lane should hold messages that are not encoded yet (any `T`,
+/// e.g. an order), which are only handed over to the [`Session`](super::Session)
+/// once the transport is ready for them. Admin messages, on the other hand,
+/// are the [`SessionEvent::Outbound`](super::SessionEvent::Outbound) frames
+/// of the [`Session`](super::Session). This way, frames are always written in
+/// `MsgSeqNum <34>` order.
+///
+/// # Examples
+///
+/// ```
+/// use fefix::session::{Backpressure, OutboundQueue, QueuedMessage};
+///
+/// let mut queue = OutboundQueue::new(2);
+/// queue.push_application("order 1").unwrap();
+/// queue.push_application("order 2").unwrap();
+/// assert_eq!(queue.push_application("order 3"), Err(Backpressure("order 3")));
+/// assert!(queue.is_backpressured());
+///
+/// queue.push_admin(b"8=FIX.4.4\x019=5\x0135=0\x0110=163\x01".to_vec());
+/// assert!(matches!(queue.pop(), Some(QueuedMessage::Admin(_))));
+/// assert_eq!(queue.pop(), Some(QueuedMessage::Application("order 1")));
+/// assert!(!queue.is_backpressured());
+/// ```
+#[derive(Debug, Clone)]
+pub struct OutboundQueue<T = Vec<u8>> {
+    admin: VecDeque<Vec<u8>>,
+    application: VecDeque<T>,
+    capacity: usize,
+    low_watermark: usize,
+    backpressured: bool,
+}
+
+impl<T> OutboundQueue<T> {
+    /// Creates a new, empty [`OutboundQueue`] with room for `capacity`
+    /// application messages. The low watermark is half of `capacity`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is 0.
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "OutboundQueue capacity must be positive");
+        Self {
+            admin: VecDeque::new(),
+            application: VecDeque::new(),
+            capacity,
+            low_watermark: capacity / 2,
+            backpressured: false,
+        }
+    }
+
+    /// Returns the maximum number of application messages in `self`.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Sets the number of application messages below which (inclusive)
+    /// backpressure is released, once the application lane has been full. It
+    /// must be lower than [`OutboundQueue::capacity`]; otherwise, it's capped
+    /// to `capacity - 1`.
+    pub fn set_low_watermark(&mut self, low_watermark: usize) {
+        self.low_watermark = low_watermark.min(self.capacity - 1);
+        self.update_backpressure();
+    }
+
+    /// Returns `true` if and only if the application lane refuses new
+    /// messages.
+    pub fn is_backpressured(&self) -> bool {
+        self.backpressured
+    }
+
+    /// Enqueues the admin message `frame`, which is never refused.
+    pub fn push_admin(&mut self, frame: Vec<u8>) {
+        self.admin.push_back(frame);
+    }
+
+    /// Enqueues the application message `message`, unless `self` is
+    /// backpressured.
+    pub fn push_application(&mut self, message: T) -> Result<(), Backpressure<T>> {
+        if self.backpressured {
+            return Err(Backpressure(message));
+        }
+        self.application.push_back(message);
+        if self.application.len() >= self.capacity {
+            trace_event!(debug, "Outbound queue is full, applying backpressure");
+            self.backpressured = true;
+        }
+        Ok(())
+    }
+
+    /// Removes and returns the oldest admin message if there's any, or else
+    /// the oldest application message, if any.
+    pub fn pop(&mut self) -> Option<QueuedMessage<T>> {
+        if let Some(frame) = self.admin.pop_front() {
+            return Some(QueuedMessage::Admin(frame));
+        }
+        let message = self.application.pop_front()?;
+        self.update_backpressure();
+        Some(QueuedMessage::Application(message))
+    }
+
+    /// Returns the number of admin messages in `self`.
+    pub fn admin_len(&self) -> usize {
+        self.admin.len()
+    }
+
+    /// Returns the number of application messages in `self`.
+    pub fn application_len(&self) -> usize {
+        self.application.len()
+    }
+
+    /// Returns the total number of messages in `self`.
+    pub fn len(&self) -> usize {
+        self.admin.len() + self.application.len()
+    }
+
+    /// Returns `true` if and only if `self` has no messages.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Removes all messages from `self`, e.g. after a disconnection, and
+    /// returns the application messages that were never sent.
+    pub fn clear(&mut self) -> Vec<T> {
+        self.admin.clear();
+        self.backpressured = false;
+        self.application.drain(..).collect()
+    }
+
+    fn update_backpressure(&mut self) {
+        if self.backpressured && self.application.len() <= self.low_watermark {
+            trace_event!(debug, "Outbound queue drained, releasing backpressure");
+            self.backpressured = false;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn admin_messages_are_never_starved() {
+        let mut queue = OutboundQueue::new(100);
+        for i in 0..100 {
+            queue.push_application(i).unwrap();
+        }
+        assert_eq!(queue.pop(), Some(QueuedMessage::Application(0)));
+        queue.push_admin(b"heartbeat".to_vec());
+        assert!(queue.push_application(100).is_err());
+        queue.push_admin(b"resend".to_vec());
+        assert_eq!(
+            queue.pop(),
+            Some(QueuedMessage::Admin(b"heartbeat".to_vec()))
+        );
+        assert_eq!(queue.pop(), Some(QueuedMessage::Admin(b"resend".to_vec())));
+        assert_eq!(queue.pop(), Some(QueuedMessage::Application(1)));
+        assert_eq!(queue.len(), 98);
+    }
+
+    #[test]
+    fn backpressure_is_released_at_low_watermark() {
+        let mut queue = OutboundQueue::new(4);
+        queue.set_low_watermark(1);
+        for i in 0..4 {
+            queue.push_application(i).unwrap();
+        }
+        assert_eq!(queue.push_application(4), Err(Backpressure(4)));
+        queue.pop();
+        queue.pop();
+        assert!(queue.is_backpressured());
+        assert!(queue.push_application(4).is_err());
+        queue.pop();
+        assert!(!queue.is_backpressured());
+        queue.push_application(4).unwrap();
+        assert_eq!(queue.application_len(), 2);
+    }
+
+    #[test]
+    fn clear_returns_unsent_application_messages() {
+        let mut queue = OutboundQueue::new(2);
+        queue.push_admin(b"heartbeat".to_vec());
+        queue.push_application("a").unwrap();
+        queue.push_application("b").unwrap();
+        assert!(queue.is_backpressured());
+        assert_eq!(queue.clear(), vec!["a", "b"]);
+        assert!(queue.is_empty());
+        assert!(!queue.is_backpressured());
+        assert_eq!(queue.pop(), None);
+    }
+}
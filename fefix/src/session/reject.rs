@@ -0,0 +1,401 @@
+use crate::definitions::fix44;
+use crate::tagvalue::{Configure, DecodeError, EncoderHandle, FieldAccess, Message, Violation};
+use crate::{Buffer, TagU16};
+
+/// The contents of a session-level `Reject <3>` message, which refers to an
+/// inbound message that failed validation.
+///
+/// [`Reject`] only deals with the body of the message, so that it can be sent
+/// with any [`Session`](super::Session) or encoder. See
+/// [`Session::reject`](super::Session::reject).
+///
+/// # Examples
+///
+/// ```
+/// use fefix::definitions::fix44;
+/// use fefix::session::Reject;
+/// use fefix::tagvalue::{Config, DecodeError, Encoder};
+/// use fefix::TagU16;
+///
+/// let err = DecodeError::FieldPresence {
+///     tag: TagU16::new(55).unwrap(),
+/// };
+/// let reject = Reject::from_decode_error(7, &err).unwrap();
+/// assert_eq!(
+///     reject.reason(),
+///     fix44::SessionRejectReason::RequiredTagMissing
+/// );
+///
+/// let mut encoder = Encoder::<Config>::default();
+/// encoder.config_mut().set_separator(b'|');
+/// let mut buffer = Vec::new();
+/// let mut msg = encoder.start_message(b"FIX.4.4", &mut buffer, b"3");
+/// reject.encode(&mut msg);
+/// assert!(msg.wrap().starts_with(b"8=FIX.4.4|9=000056|35=3|45=7|371=55|373=1|58=Mandatory tag <55> is missing|"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reject {
+    ref_seq_num: u64,
+    ref_tag: Option<TagU16>,
+    ref_msg_type: Option<Vec<u8>>,
+    reason: fix44::SessionRejectReason,
+    text: Option<String>,
+}
+
+impl Reject {
+    /// Creates a new [`Reject`] of the inbound message with `MsgSeqNum <34>`
+    /// `ref_seq_num`.
+    pub fn new(ref_seq_num: u64, reason: fix44::SessionRejectReason) -> Self {
+        Self {
+            ref_seq_num,
+            ref_tag: None,
+            ref_msg_type: None,
+            reason,
+            text: None,
+        }
+    }
+
+    /// Creates a new [`Reject`] of `offender`, with `RefSeqNum <45>` and
+    /// `RefMsgType <372>` taken from it.
+    pub fn for_message<T>(offender: &Message<T>, reason: fix44::SessionRejectReason) -> Self
+    where
+        T: AsRef<[u8]> + Clone,
+    {
+        let mut reject = Self::new(offender.fv(fix44::MSG_SEQ_NUM).unwrap_or(0), reason);
+        reject.ref_msg_type = offender
+            .fv_raw(fix44::MSG_TYPE)
+            .map(|msg_type| msg_type.to_vec());
+        reject
+    }
+
+    /// Creates a new [`Reject`] that describes `err`, which arose while
+    /// decoding the inbound message with `MsgSeqNum <34>` `ref_seq_num`.
+    ///
+    /// Returns [`None`] if `err` doesn't call for a `Reject <3>`. Most
+    /// notably, messages with a wrong `BodyLength <9>` or `CheckSum <10>`
+    /// are garbled and must be ignored instead.
+    pub fn from_decode_error(ref_seq_num: u64, err: &DecodeError) -> Option<Self> {
+        use fix44::SessionRejectReason as Reason;
+        let reason = match err {
+            DecodeError::FieldPresence { .. } => Reason::RequiredTagMissing,
+            DecodeError::Invalid { tag: Some(_), .. } => Reason::IncorrectDataFormatForValue,
+            DecodeError::Invalid { tag: None, .. } => Reason::InvalidTagNumber,
+            DecodeError::DuplicateTag { .. } => Reason::TagAppearsMoreThanOnce,
+            DecodeError::TooManyFields { .. } => Reason::Other,
+            _ => return None,
+        };
+        let mut reject = Self::new(ref_seq_num, reason);
+        reject.ref_tag = err.tag();
+        reject.text = Some(err.to_string());
+        Some(reject)
+    }
+
+    /// Creates a new [`Reject`] of `offender` that describes `violation`, as
+    /// found by a [`Validator`](crate::tagvalue::Validator).
+    ///
+    /// Returns [`None`] for [`Violation::UnsupportedBeginString`], which
+    /// calls for a `Logout <5>` instead.
+    pub fn for_violation<T>(offender: &Message<T>, violation: &Violation) -> Option<Self>
+    where
+        T: AsRef<[u8]> + Clone,
+    {
+        use fix44::SessionRejectReason as Reason;
+        let reason = match violation {
+            Violation::UnsupportedBeginString { .. } => return None,
+            Violation::MissingMsgType | Violation::MissingRequiredField { .. } => {
+                Reason::RequiredTagMissing
+            }
+            Violation::UnknownMsgType { .. } => Reason::InvalidMsgtype,
+            Violation::UnknownTag { .. } => Reason::UndefinedTag,
+            Violation::InvalidEnumValue { .. } => Reason::ValueIsIncorrect,
+            Violation::TagNotDefinedForMsgType { .. } => Reason::TagNotDefinedForThisMessageType,
+            Violation::IncorrectNumInGroupCount { .. } => {
+                Reason::IncorrectNumingroupCountForRepeatingGroup
+            }
+            Violation::IncorrectGroupDelimiter { .. } | Violation::GroupFieldOutOfPlace { .. } => {
+                Reason::RepeatingGroupFieldsOutOfOrder
+            }
+        };
+        let mut reject = Self::for_message(offender, reason);
+        reject.ref_tag = Some(violation.tag());
+        reject.text = Some(violation.to_string());
+        Some(reject)
+    }
+
+    /// Returns the `RefSeqNum <45>` of `self`.
+    pub fn ref_seq_num(&self) -> u64 {
+        self.ref_seq_num
+    }
+
+    /// Returns the `RefTagID <371>` of `self`, if any.
+    pub fn ref_tag(&self) -> Option<TagU16> {
+        self.ref_tag
+    }
+
+    /// Returns the `RefMsgType <372>` of `self`, if any.
+    pub fn ref_msg_type(&self) -> Option<&[u8]> {
+        self.ref_msg_type.as_deref()
+    }
+
+    /// Returns the `SessionRejectReason <373>` of `self`.
+    pub fn reason(&self) -> fix44::SessionRejectReason {
+        self.reason
+    }
+
+    /// Returns the `Text <58>` of `self`, if any.
+    pub fn text(&self) -> Option<&str> {
+        self.text.as_deref()
+    }
+
+    /// Sets `RefTagID <371>` to `tag`.
+    pub fn set_ref_tag(&mut self, tag: TagU16) {
+        self.ref_tag = Some(tag);
+    }
+
+    /// Sets `RefMsgType <372>` to `msg_type`.
+    pub fn set_ref_msg_type(&mut self, msg_type: &[u8]) {
+        self.ref_msg_type = Some(msg_type.to_vec());
+    }
+
+    /// Sets `Text <58>` to `text`.
+    pub fn set_text<S>(&mut self, text: S)
+    where
+        S: Into<String>,
+    {
+        self.text = Some(text.into());
+    }
+
+    /// Writes all fields of `self` to `msg`, which must be a `Reject <3>`
+    /// with its standard header already in place.
+    pub fn encode<B, C>(&self, msg: &mut EncoderHandle<B, C>)
+    where
+        B: Buffer,
+        C: Configure,
+    {
+        msg.set(fix44::REF_SEQ_NUM, self.ref_seq_num);
+        if let Some(tag) = self.ref_tag {
+            msg.set(fix44::REF_TAG_ID, u32::from(tag.get()));
+        }
+        if let Some(msg_type) = &self.ref_msg_type {
+            msg.set(fix44::REF_MSG_TYPE, &msg_type[..]);
+        }
+        msg.set(fix44::SESSION_REJECT_REASON, self.reason);
+        if let Some(text) = &self.text {
+            msg.set(fix44::TEXT, text.as_str());
+        }
+    }
+}
+
+/// The contents of a `BusinessMessageReject <j>` message, which refers to an
+/// application message that can't be processed even though it's valid at the
+/// session level, e.g. because of an unsupported message type.
+///
+/// Unlike `Reject <3>`, `BusinessMessageReject <j>` is an application message;
+/// use [`BusinessMessageReject::encode`] within
+/// [`Session::send`](super::Session::send) to send it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BusinessMessageReject {
+    ref_seq_num: Option<u64>,
+    ref_msg_type: Vec<u8>,
+    ref_id: Option<String>,
+    reason: fix44::BusinessRejectReason,
+    text: Option<String>,
+}
+
+impl BusinessMessageReject {
+    /// Creates a new [`BusinessMessageReject`] of an inbound message of type
+    /// `ref_msg_type`.
+    pub fn new(ref_msg_type: &[u8], reason: fix44::BusinessRejectReason) -> Self {
+        Self {
+            ref_seq_num: None,
+            ref_msg_type: ref_msg_type.to_vec(),
+            ref_id: None,
+            reason,
+            text: None,
+        }
+    }
+
+    /// Creates a new [`BusinessMessageReject`] of `offender`, with `RefSeqNum
+    /// <45>` and `RefMsgType <372>` taken from it.
+    pub fn for_message<T>(offender: &Message<T>, reason: fix44::BusinessRejectReason) -> Self
+    where
+        T: AsRef<[u8]> + Clone,
+    {
+        let mut reject = Self::new(offender.fv_raw(fix44::MSG_TYPE).unwrap_or_default(), reason);
+        reject.ref_seq_num = offender.fv(fix44::MSG_SEQ_NUM).ok();
+        reject
+    }
+
+    /// Returns the `RefSeqNum <45>` of `self`, if any.
+    pub fn ref_seq_num(&self) -> Option<u64> {
+        self.ref_seq_num
+    }
+
+    /// Returns the `RefMsgType <372>` of `self`.
+    pub fn ref_msg_type(&self) -> &[u8] {
+        &self.ref_msg_type[..]
+    }
+
+    /// Returns the `BusinessRejectRefID <379>` of `self`, if any.
+    pub fn ref_id(&self) -> Option<&str> {
+        self.ref_id.as_deref()
+    }
+
+    /// Returns the `BusinessRejectReason <380>` of `self`.
+    pub fn reason(&self) -> fix44::BusinessRejectReason {
+        self.reason
+    }
+
+    /// Returns the `Text <58>` of `self`, if any.
+    pub fn text(&self) -> Option<&str> {
+        self.text.as_deref()
+    }
+
+    /// Sets `RefSeqNum <45>` to `ref_seq_num`.
+    pub fn set_ref_seq_num(&mut self, ref_seq_num: u64) {
+        self.ref_seq_num = Some(ref_seq_num);
+    }
+
+    /// Sets `BusinessRejectRefID <379>` to `ref_id`, i.e. the business-level
+    /// ID (e.g. `ClOrdID <11>`) of the rejected message.
+    pub fn set_ref_id<S>(&mut self, ref_id: S)
+    where
+        S: Into<String>,
+    {
+        self.ref_id = Some(ref_id.into());
+    }
+
+    /// Sets `Text <58>` to `text`.
+    pub fn set_text<S>(&mut self, text: S)
+    where
+        S: Into<String>,
+    {
+        self.text = Some(text.into());
+    }
+
+    /// Writes all fields of `self` to `msg`, which must be a
+    /// `BusinessMessageReject <j>` with its standard header already in place.
+    pub fn encode<B, C>(&self, msg: &mut EncoderHandle<B, C>)
+    where
+        B: Buffer,
+        C: Configure,
+    {
+        if let Some(ref_seq_num) = self.ref_seq_num {
+            msg.set(fix44::REF_SEQ_NUM, ref_seq_num);
+        }
+        msg.set(fix44::REF_MSG_TYPE, &self.ref_msg_type[..]);
+        if let Some(ref_id) = &self.ref_id {
+            msg.set(fix44::BUSINESS_REJECT_REF_ID, ref_id.as_str());
+        }
+        msg.set(fix44::BUSINESS_REJECT_REASON, self.reason);
+        if let Some(text) = &self.text {
+            msg.set(fix44::TEXT, text.as_str());
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::dict::IsFieldDefinition;
+    use crate::tagvalue::{test_decoder, Config, Encoder};
+
+    fn encode<F>(msg_type: &[u8], f: F) -> Vec<u8>
+    where
+        F: FnOnce(&mut EncoderHandle<Vec<u8>>),
+    {
+        let mut encoder = Encoder::<Config>::default();
+        encoder.config_mut().set_separator(b'|');
+        let mut buffer = Vec::new();
+        let mut msg = encoder.start_message(b"FIX.4.4", &mut buffer, msg_type);
+        f(&mut msg);
+        msg.wrap().to_vec()
+    }
+
+    #[test]
+    fn garbled_messages_are_not_rejected() {
+        let err = DecodeError::CheckSum {
+            offset: 0,
+            declared: 1,
+            actual: 2,
+        };
+        assert_eq!(Reject::from_decode_error(1, &err), None);
+        let err = DecodeError::DuplicateTag {
+            offset: 20,
+            tag: fix44::TEST_REQ_ID.tag(),
+        };
+        let reject = Reject::from_decode_error(1, &err).unwrap();
+        assert_eq!(reject.ref_tag(), Some(fix44::TEST_REQ_ID.tag()));
+        assert_eq!(
+            reject.reason(),
+            fix44::SessionRejectReason::TagAppearsMoreThanOnce
+        );
+    }
+
+    #[test]
+    fn violations_are_rejected() {
+        let mut decoder = test_decoder();
+        let offender = decoder
+            .decode(&b"8=FIX.4.4|9=28|35=V|34=42|146=2|55=EUR/USD|10=000|"[..])
+            .unwrap();
+        let violation = Violation::IncorrectNumInGroupCount {
+            tag: fix44::NO_RELATED_SYM.tag(),
+            declared: 2,
+            actual: 1,
+        };
+        let reject = Reject::for_violation(&offender, &violation).unwrap();
+        assert_eq!(reject.ref_seq_num(), 42);
+        assert_eq!(reject.ref_tag(), Some(fix44::NO_RELATED_SYM.tag()));
+        assert_eq!(reject.ref_msg_type(), Some(&b"V"[..]));
+        assert_eq!(
+            reject.reason(),
+            fix44::SessionRejectReason::IncorrectNumingroupCountForRepeatingGroup
+        );
+        assert_eq!(
+            reject.text(),
+            Some("Incorrect NumInGroup count for repeating group <146>: declared 2, found 1")
+        );
+        let violation = Violation::UnsupportedBeginString {
+            begin_string: b"FIX.4.3".to_vec(),
+        };
+        assert_eq!(Reject::for_violation(&offender, &violation), None);
+    }
+
+    #[test]
+    fn rejects_refer_to_the_offender() {
+        let mut decoder = test_decoder();
+        let offender = decoder
+            .decode(&b"8=FIX.4.4|9=18|35=D|34=42|11=ABC|10=000|"[..])
+            .unwrap();
+        let mut reject =
+            Reject::for_message(&offender, fix44::SessionRejectReason::ValueIsIncorrect);
+        reject.set_ref_tag(fix44::CL_ORD_ID.tag());
+        let mut business_reject = BusinessMessageReject::for_message(
+            &offender,
+            fix44::BusinessRejectReason::UnsupportedMessageType,
+        );
+        business_reject.set_ref_id("ABC");
+        business_reject.set_text("Nope");
+
+        let data = encode(b"3", |msg| reject.encode(msg));
+        let msg = decoder.decode(&data[..]).unwrap();
+        assert_eq!(msg.fv(fix44::REF_SEQ_NUM), Ok(42u64));
+        assert_eq!(msg.fv(fix44::REF_TAG_ID), Ok(11u32));
+        assert_eq!(msg.fv(fix44::REF_MSG_TYPE), Ok("D"));
+        assert_eq!(
+            msg.fv(fix44::SESSION_REJECT_REASON),
+            Ok(fix44::SessionRejectReason::ValueIsIncorrect)
+        );
+
+        let data = encode(b"j", |msg| business_reject.encode(msg));
+        let msg = decoder.decode(&data[..]).unwrap();
+        assert_eq!(msg.fv(fix44::REF_SEQ_NUM), Ok(42u64));
+        assert_eq!(msg.fv(fix44::REF_MSG_TYPE), Ok("D"));
+        assert_eq!(msg.fv(fix44::BUSINESS_REJECT_REF_ID), Ok("ABC"));
+        assert_eq!(
+            msg.fv(fix44::BUSINESS_REJECT_REASON),
+            Ok(fix44::BusinessRejectReason::UnsupportedMessageType)
+        );
+        assert_eq!(msg.fv(fix44::TEXT), Ok("Nope"));
+    }
+}
@@ -0,0 +1,446 @@
+use super::{
+    CaptureRecord, Clock, Configure, ManualClock, RecordKind, Session, SessionBuilder,
+    SessionEvent, SessionState,
+};
+use crate::tagvalue::{Config as TagValueConfig, Decoder};
+use std::collections::VecDeque;
+use std::fmt;
+
+/// Standard header fields that [`Session`] populates on its own.
+const HEADER_TAGS: &[u16] = &[8, 35, 34, 49, 52, 56];
+/// Fields that can't be reproduced exactly, because they depend on sub-second
+/// timing: `SendingTime <52>` and `OrigSendingTime <122>`. `BodyLength <9>` and
+/// `CheckSum <10>` are implicitly ignored too.
+const UNSTABLE_TAGS: &[u16] = &[52, 122];
+/// Fields of `Logon <A>` that [`Session::logon_with`] populates on its own.
+const LOGON_TAGS: &[u16] = &[98, 108, 1137];
+
+/// The error type returned by [`replay`], i.e. the first divergence between
+/// a capture and the replayed [`Session`]. `index` is the position of the
+/// offending record within the capture.
+///
+/// Please note that [`ReplayError`] is marked with `#[non_exhaustive]`, which
+/// future-proofs the enumeration type in case more variants are added.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ReplayError {
+    /// The replayed [`Session`] sent `actual` where the capture has
+    /// `expected`.
+    Mismatch {
+        index: usize,
+        expected: Vec<u8>,
+        actual: Vec<u8>,
+    },
+    /// The capture has the admin message `expected`, which the replayed
+    /// [`Session`] didn't send.
+    Missing { index: usize, expected: Vec<u8> },
+    /// The replayed [`Session`] sent `actual`, which isn't in the capture.
+    /// `index` is the number of records.
+    Unexpected { index: usize, actual: Vec<u8> },
+    /// The replayed [`Session`] was in the `actual` state at a checkpoint that
+    /// requires the `expected` state.
+    State {
+        index: usize,
+        expected: SessionState,
+        actual: SessionState,
+    },
+    /// The outbound record couldn't be decoded.
+    Garbled { index: usize },
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReplayError::Mismatch {
+                index,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "Record {}: expected {}, but the session sent {}",
+                index,
+                String::from_utf8_lossy(expected),
+                String::from_utf8_lossy(actual)
+            ),
+            ReplayError::Missing { index, expected } => write!(
+                f,
+                "Record {}: the session didn't send {}",
+                index,
+                String::from_utf8_lossy(expected)
+            ),
+            ReplayError::Unexpected { index, actual } => write!(
+                f,
+                "Record {}: the session unexpectedly sent {}",
+                index,
+                String::from_utf8_lossy(actual)
+            ),
+            ReplayError::State {
+                index,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "Record {}: expected state {}, but the session is {}",
+                index, expected, actual
+            ),
+            ReplayError::Garbled { index } => write!(f, "Record {}: garbled message", index),
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+/// The outcome of a successful [`replay`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplayReport {
+    records: usize,
+    state: SessionState,
+    events: Vec<SessionEvent>,
+}
+
+impl ReplayReport {
+    /// Returns the number of replayed records.
+    pub fn records(&self) -> usize {
+        self.records
+    }
+
+    /// Returns the [`SessionState`] of the replayed [`Session`] at the end of
+    /// the capture.
+    pub fn state(&self) -> SessionState {
+        self.state
+    }
+
+    /// Returns all the [`SessionEvent`]s of the replayed [`Session`], in
+    /// order, except [`SessionEvent::Outbound`].
+    pub fn events(&self) -> &[SessionEvent] {
+        &self.events[..]
+    }
+}
+
+/// Feeds a capture (see [`CaptureLog`](super::CaptureLog)) back through a
+/// [`Session`] built by `builder`, whose [`Clock`](super::Clock) is replaced by
+/// a [`ManualClock`] that follows the timestamps of the records. This makes
+/// it possible to reproduce production incidents deterministically, e.g. to
+/// regression-test a fix.
+///
+/// Each record is handled in order, after a [`Session::tick`] at its
+/// timestamp:
+///
+/// - Incoming messages are fed to the [`Session`].
+/// - Outgoing messages must match those sent by the [`Session`], in order,
+///   regardless of `SendingTime <52>` and `OrigSendingTime <122>`. Outgoing
+///   messages that the [`Session`] can't send on its own (i.e. application
+///   messages, and an initiator's `Logon <A>` and `Logout <5>`) are re-sent
+///   via [`Session::send`], [`Session::logon_with`], and [`Session::logout`].
+/// - Events whose text is a [`SessionState`], e.g. as logged via
+///   `log.on_event(&session.state().to_string())` after state changes, are
+///   checkpoints: the [`Session`] must be in that state. Other events are
+///   ignored.
+///
+/// `builder` must be configured like the original [`Session`], seq. numbers
+/// included.
+///
+/// # Examples
+///
+/// ```
+/// use fefix::session::{replay, CaptureLog, CaptureReader, MessageLog, SessionBuilder};
+///
+/// let mut builder: SessionBuilder = SessionBuilder::default();
+/// builder.set_sender_comp_id("ACCEPTOR");
+/// builder.set_target_comp_id("INITIATOR");
+///
+/// let mut log = CaptureLog::new(Vec::new()).unwrap();
+/// log.on_event("AwaitingLogon").unwrap();
+/// let capture = log.into_inner().unwrap();
+/// let records = CaptureReader::new(&capture[..])
+///     .unwrap()
+///     .collect::<Result<Vec<_>, _>>()
+///     .unwrap();
+/// let report = replay(builder, records).unwrap();
+/// assert_eq!(report.records(), 1);
+/// ```
+pub fn replay<C, I>(mut builder: SessionBuilder<C>, records: I) -> Result<ReplayReport, ReplayError>
+where
+    C: Configure,
+    I: IntoIterator<Item = CaptureRecord>,
+{
+    let mut records = records.into_iter().peekable();
+    let start = match records.peek() {
+        Some(record) => record.timestamp(),
+        None => builder.clock().utc_now(),
+    };
+    let clock = ManualClock::new(start);
+    builder.set_clock(clock.clone());
+    let mut decoder = Decoder::<TagValueConfig>::new(builder.dictionary().clone());
+    decoder.config_mut().set_decode_assoc(false);
+    let mut replay = Replay {
+        session: builder.build(),
+        decoder,
+        sent: VecDeque::new(),
+        events: Vec::new(),
+    };
+    let mut last = start;
+    let mut index = 0;
+    for record in records {
+        if let Ok(elapsed) = (record.timestamp() - last).to_std() {
+            clock.advance(elapsed);
+            last = record.timestamp();
+        }
+        let now = clock.now();
+        replay.session.tick(now);
+        replay.drain();
+        match record.kind() {
+            RecordKind::Incoming => {
+                replay.session.feed(record.data(), now);
+                replay.drain();
+            }
+            RecordKind::Outgoing => replay.check_outgoing(index, record.data(), now)?,
+            _ => replay.check_state(index, record.data())?,
+        }
+        index += 1;
+    }
+    if let Some(actual) = replay.sent.pop_front() {
+        return Err(ReplayError::Unexpected { index, actual });
+    }
+    Ok(ReplayReport {
+        records: index,
+        state: replay.session.state(),
+        events: replay.events,
+    })
+}
+
+struct Replay<C>
+where
+    C: Configure,
+{
+    session: Session<C>,
+    decoder: Decoder<TagValueConfig>,
+    // Outbound messages that weren't matched with the capture yet.
+    sent: VecDeque<Vec<u8>>,
+    events: Vec<SessionEvent>,
+}
+
+impl<C> Replay<C>
+where
+    C: Configure,
+{
+    fn drain(&mut self) {
+        while let Some(event) = self.session.next_event() {
+            match event {
+                SessionEvent::Outbound(bytes) => self.sent.push_back(bytes),
+                event => self.events.push(event),
+            }
+        }
+    }
+
+    fn check_outgoing(
+        &mut self,
+        index: usize,
+        expected: &[u8],
+        now: std::time::Instant,
+    ) -> Result<(), ReplayError> {
+        let fields = self
+            .fields(expected)
+            .ok_or(ReplayError::Garbled { index })?;
+        if self.sent.is_empty() {
+            self.resend(&fields, now);
+            self.drain();
+        }
+        let actual = match self.sent.pop_front() {
+            Some(actual) => actual,
+            None => {
+                return Err(ReplayError::Missing {
+                    index,
+                    expected: expected.to_vec(),
+                })
+            }
+        };
+        let matches = match self.fields(&actual[..]) {
+            Some(actual_fields) => stable(&actual_fields) == stable(&fields),
+            None => false,
+        };
+        if matches {
+            Ok(())
+        } else {
+            Err(ReplayError::Mismatch {
+                index,
+                expected: expected.to_vec(),
+                actual,
+            })
+        }
+    }
+
+    /// Makes the session send the message made of `fields`, if it's one that
+    /// it doesn't send on its own.
+    fn resend(&mut self, fields: &[(u16, Vec<u8>)], now: std::time::Instant) {
+        let msg_type = match fields.iter().find(|(tag, _)| *tag == 35) {
+            Some((_, msg_type)) => msg_type.clone(),
+            None => return,
+        };
+        let body = fields
+            .iter()
+            .filter(|(tag, _)| !HEADER_TAGS.contains(tag))
+            .map(|(tag, value)| (crate::TagU16::new(*tag).unwrap(), value));
+        match (&msg_type[..], self.session.state()) {
+            (b"A", SessionState::AwaitingLogon) => self.session.logon_with(now, |msg| {
+                for (tag, value) in body.filter(|(tag, _)| !LOGON_TAGS.contains(&tag.get())) {
+                    msg.set_any(tag, &value[..]);
+                }
+            }),
+            (b"5", SessionState::Active) => {
+                let text = fields
+                    .iter()
+                    .find(|(tag, _)| *tag == 58)
+                    .and_then(|(_, text)| std::str::from_utf8(text).ok());
+                self.session.logout(text, now);
+            }
+            (b"0" | b"1" | b"2" | b"3" | b"4" | b"5" | b"A", _) => {}
+            (msg_type, _) => {
+                self.session
+                    .send(msg_type, now, |msg| {
+                        for (tag, value) in body {
+                            msg.set_any(tag, &value[..]);
+                        }
+                    })
+                    .ok();
+            }
+        }
+    }
+
+    fn check_state(&self, index: usize, text: &[u8]) -> Result<(), ReplayError> {
+        let expected = SessionState::ALL
+            .iter()
+            .find(|state| state.to_string().as_bytes() == text);
+        match expected {
+            Some(expected) if *expected != self.session.state() => Err(ReplayError::State {
+                index,
+                expected: *expected,
+                actual: self.session.state(),
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    fn fields(&mut self, message: &[u8]) -> Option<Vec<(u16, Vec<u8>)>> {
+        let message = self.decoder.decode(message).ok()?;
+        Some(
+            message
+                .fields()
+                .map(|(tag, value)| (tag.get(), value.to_vec()))
+                .collect(),
+        )
+    }
+}
+
+fn stable(fields: &[(u16, Vec<u8>)]) -> Vec<&(u16, Vec<u8>)> {
+    fields
+        .iter()
+        .filter(|(tag, _)| !UNSTABLE_TAGS.contains(tag))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::definitions::fix44;
+    use crate::session::{CaptureLog, CaptureReader, MessageLog};
+    use chrono::{TimeZone, Utc};
+    use std::time::Duration;
+
+    fn builder(sender: &str, target: &str, clock: &ManualClock) -> SessionBuilder {
+        let mut builder = SessionBuilder::default();
+        builder.set_sender_comp_id(sender);
+        builder.set_target_comp_id(target);
+        builder.set_clock(clock.clone());
+        builder
+    }
+
+    fn deliver(
+        from: &mut Session,
+        to: &mut Session,
+        clock: &ManualClock,
+        log: &mut CaptureLog<Vec<u8>>,
+    ) {
+        while let Some(event) = from.next_event() {
+            if let SessionEvent::Outbound(bytes) = event {
+                if from.sender_comp_id() == "ACCEPTOR" {
+                    log.on_outgoing(&bytes[..]).unwrap();
+                } else {
+                    log.on_incoming(&bytes[..]).unwrap();
+                }
+                to.feed(&bytes[..], clock.now());
+            }
+        }
+    }
+
+    /// Runs a short session between an initiator and an acceptor, and returns
+    /// the capture of the acceptor.
+    fn capture(clock: &ManualClock) -> Vec<CaptureRecord> {
+        let mut initiator = builder("INITIATOR", "ACCEPTOR", clock).build();
+        let mut acceptor = builder("ACCEPTOR", "INITIATOR", clock).build();
+        let mut log = CaptureLog::new(Vec::new()).unwrap();
+        log.set_clock(clock.clone());
+        initiator.logon(clock.now());
+        deliver(&mut initiator, &mut acceptor, clock, &mut log);
+        deliver(&mut acceptor, &mut initiator, clock, &mut log);
+        log.on_event(&acceptor.state().to_string()).unwrap();
+        initiator
+            .send(b"D", clock.now(), |msg| msg.set(fix44::CL_ORD_ID, "1"))
+            .unwrap();
+        deliver(&mut initiator, &mut acceptor, clock, &mut log);
+        acceptor
+            .send(b"8", clock.now(), |msg| msg.set(fix44::CL_ORD_ID, "1"))
+            .unwrap();
+        deliver(&mut acceptor, &mut initiator, clock, &mut log);
+        // The acceptor's heartbeat is due.
+        clock.advance(Duration::from_secs(30));
+        acceptor.tick(clock.now());
+        deliver(&mut acceptor, &mut initiator, clock, &mut log);
+        let capture = log.into_inner().unwrap();
+        CaptureReader::new(&capture[..])
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn replay_reproduces_the_session() {
+        let clock = ManualClock::new(Utc.ymd(2023, 1, 1).and_hms(12, 0, 0));
+        let records = capture(&clock);
+        assert_eq!(records.len(), 6);
+        let replay_clock = ManualClock::new(Utc.ymd(2024, 1, 1).and_hms(0, 0, 0));
+        let report = replay(builder("ACCEPTOR", "INITIATOR", &replay_clock), records).unwrap();
+        assert_eq!(report.records(), 6);
+        assert_eq!(report.state(), SessionState::Active);
+        assert_eq!(report.events()[0], SessionEvent::LoggedOn);
+        assert!(matches!(report.events()[1], SessionEvent::Application(_)));
+    }
+
+    #[test]
+    fn replay_detects_divergences() {
+        let clock = ManualClock::new(Utc.ymd(2023, 1, 1).and_hms(12, 0, 0));
+        let records = capture(&clock);
+        let mut builder = builder("ACCEPTOR", "INITIATOR", &clock);
+        builder.set_seq_numbers(1, 5);
+        assert!(matches!(
+            replay(builder, records.clone()),
+            Err(ReplayError::Mismatch { index: 1, .. })
+        ));
+        // Without the heartbeat at the end.
+        let records = records[..5].to_vec();
+        let mut builder: SessionBuilder = SessionBuilder::default();
+        builder.set_sender_comp_id("ACCEPTOR");
+        builder.set_target_comp_id("INITIATOR");
+        builder.set_heartbeat_rule(crate::session::HeartbeatRule::Exact(Duration::from_secs(
+            60,
+        )));
+        match replay(builder, records) {
+            Err(ReplayError::Mismatch {
+                index: 1, actual, ..
+            }) => {
+                assert!(String::from_utf8_lossy(&actual).contains("35=5"))
+            }
+            result => panic!("Unexpected result {:?}", result),
+        }
+    }
+}
@@ -0,0 +1,273 @@
+use super::MessageStore;
+use crate::definitions::fix44;
+use crate::dict::IsFieldDefinition;
+use crate::fix_values::{TimePrecision, Timestamp};
+use crate::tagvalue::{Configure, Decoder, Encoder, FieldAccess};
+use crate::{Dictionary, FixValue};
+
+/// The logic to answer inbound `ResendRequest <2>` messages, based on the
+/// outbound messages saved in a [`MessageStore`].
+///
+/// For each seq. number within the requested range, [`ResendResponder`]
+/// either:
+///
+/// - resends the stored application message (or `Reject <3>`), with
+///   `PossDupFlag <43>` set, `OrigSendingTime <122>` set to the original
+///   `SendingTime <52>`, and a brand new `SendingTime <52>`;
+/// - or, for admin messages and messages which are missing from the store,
+///   skips it with a `SequenceReset <4>` in *GapFill* mode. Consecutive skipped
+///   seq. numbers are collapsed into a single `SequenceReset <4>`, which also
+///   carries `PossDupFlag <43>` and an `OrigSendingTime <122>` equal to its
+///   `SendingTime <52>`.
+///
+/// Like [`Session`](super::Session), [`ResendResponder`] doesn't do any I/O
+/// and only produces messages for the caller to send.
+///
+/// # Examples
+///
+/// ```
+/// use fefix::fix_values::Timestamp;
+/// use fefix::session::{InMemoryStore, MessageStore, ResendResponder};
+///
+/// let mut store = InMemoryStore::new();
+/// // `Heartbeat <0>` messages are never resent.
+/// store.store(1, b"8=FIX.4.4\x019=41\x0135=0\x0149=A\x0156=B\x0134=1\x0152=20210101-00:00:00\x0110=109\x01").unwrap();
+/// let mut responder = ResendResponder::new("FIX.4.4", "A", "B");
+/// let messages = responder.respond(&mut store, 1, 1, Timestamp::utc_now()).unwrap();
+/// assert_eq!(messages.len(), 1);
+/// assert!(messages[0].windows(5).any(|w| w == b"\x0135=4"));
+/// ```
+#[derive(Debug)]
+pub struct ResendResponder {
+    begin_string: String,
+    sender_comp_id: String,
+    target_comp_id: String,
+    decoder: Decoder,
+    encoder: Encoder,
+}
+
+impl ResendResponder {
+    /// Creates a new [`ResendResponder`]. `begin_string`, `sender_comp_id`,
+    /// and `target_comp_id` are used for `SequenceReset <4>` messages.
+    pub fn new<S1, S2, S3>(begin_string: S1, sender_comp_id: S2, target_comp_id: S3) -> Self
+    where
+        S1: Into<String>,
+        S2: Into<String>,
+        S3: Into<String>,
+    {
+        Self {
+            begin_string: begin_string.into(),
+            sender_comp_id: sender_comp_id.into(),
+            target_comp_id: target_comp_id.into(),
+            decoder: Decoder::new(Dictionary::fix44()),
+            encoder: Encoder::default(),
+        }
+    }
+
+    /// Sets the precision of `SendingTime <52>` in retransmitted messages.
+    /// [`TimePrecision::Millis`] by default. `OrigSendingTime <122>` is always
+    /// copied verbatim.
+    pub fn set_timestamp_precision(&mut self, precision: TimePrecision) {
+        self.encoder.config_mut().set_timestamp_precision(precision);
+    }
+
+    /// Answers a `ResendRequest <2>` for the seq. numbers `begin..=end`,
+    /// fetching messages from `store`. `sending_time` is the `SendingTime
+    /// <52>` of all returned messages, which must be sent in order.
+    ///
+    /// Please note that `end` must be an actual seq. number: the special value
+    /// 0 for `EndSeqNo <16>` (i.e. "infinity") must be converted by the caller
+    /// to the last sent seq. number.
+    pub fn respond<S>(
+        &mut self,
+        store: &mut S,
+        begin: u64,
+        end: u64,
+        sending_time: Timestamp,
+    ) -> Result<Vec<Vec<u8>>, S::Error>
+    where
+        S: MessageStore,
+    {
+        let mut sending_time_bytes = Vec::new();
+        let precision = self.encoder.config().timestamp_precision();
+        sending_time.serialize_with(&mut sending_time_bytes, precision);
+        let sending_time = &sending_time_bytes[..];
+        let mut messages = Vec::new();
+        let mut gap_start = None;
+        for seq_num in begin..=end {
+            let stored = store.fetch(seq_num)?.map(|msg| msg.to_vec());
+            match stored.and_then(|msg| self.restamp(&msg[..], sending_time)) {
+                Some(msg) => {
+                    if let Some(gap_start) = gap_start.take() {
+                        messages.push(self.gap_fill(gap_start, seq_num, sending_time));
+                    }
+                    messages.push(msg);
+                }
+                None => {
+                    gap_start.get_or_insert(seq_num);
+                }
+            }
+        }
+        if let Some(gap_start) = gap_start {
+            messages.push(self.gap_fill(gap_start, end + 1, sending_time));
+        }
+        Ok(messages)
+    }
+
+    /// Re-encodes `stored` for retransmission. Returns [`None`] if `stored`
+    /// must be gap filled instead.
+    fn restamp(&mut self, stored: &[u8], sending_time: &[u8]) -> Option<Vec<u8>> {
+        let message = self.decoder.decode(stored).ok()?;
+        let msg_type = message.fv_raw(fix44::MSG_TYPE)?;
+        if is_admin(msg_type) {
+            return None;
+        }
+        let begin_string = message.fv_raw(fix44::BEGIN_STRING)?;
+        let mut buffer = Vec::new();
+        let mut msg = self
+            .encoder
+            .start_message(begin_string, &mut buffer, msg_type);
+        let mut has_sending_time = false;
+        for (tag, value) in message.fields() {
+            if tag == fix44::SENDING_TIME.tag() {
+                has_sending_time = true;
+                msg.set(fix44::SENDING_TIME, sending_time);
+                msg.set(fix44::POSS_DUP_FLAG, true);
+                msg.set(fix44::ORIG_SENDING_TIME, value);
+            } else if ![
+                fix44::BEGIN_STRING.tag(),
+                fix44::BODY_LENGTH.tag(),
+                fix44::MSG_TYPE.tag(),
+                fix44::POSS_DUP_FLAG.tag(),
+                fix44::ORIG_SENDING_TIME.tag(),
+                fix44::CHECK_SUM.tag(),
+            ]
+            .contains(&tag)
+            {
+                msg.set_any(tag, value);
+            }
+        }
+        if !has_sending_time {
+            msg.set(fix44::SENDING_TIME, sending_time);
+            msg.set(fix44::POSS_DUP_FLAG, true);
+            msg.set(fix44::ORIG_SENDING_TIME, sending_time);
+        }
+        Some(msg.wrap().to_vec())
+    }
+
+    /// Encodes a `SequenceReset <4>` that skips `msg_seq_num..new_seq_no`.
+    fn gap_fill(&mut self, msg_seq_num: u64, new_seq_no: u64, sending_time: &[u8]) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        let mut msg = self
+            .encoder
+            .start_message(self.begin_string.as_bytes(), &mut buffer, b"4");
+        msg.set(fix44::SENDER_COMP_ID, self.sender_comp_id.as_str());
+        msg.set(fix44::TARGET_COMP_ID, self.target_comp_id.as_str());
+        msg.set(fix44::MSG_SEQ_NUM, msg_seq_num);
+        msg.set(fix44::SENDING_TIME, sending_time);
+        msg.set(fix44::POSS_DUP_FLAG, true);
+        msg.set(fix44::ORIG_SENDING_TIME, sending_time);
+        msg.set(fix44::GAP_FILL_FLAG, true);
+        msg.set(fix44::NEW_SEQ_NO, new_seq_no);
+        msg.wrap().to_vec()
+    }
+}
+
+/// Admin messages which must never be resent. Refer to specs. §4.8 for more
+/// information; notably, `Reject <3>` is not among them.
+fn is_admin(msg_type: &[u8]) -> bool {
+    matches!(msg_type, b"0" | b"1" | b"2" | b"4" | b"5" | b"A")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fix_values::{Date, Time};
+    use crate::session::InMemoryStore;
+    use crate::tagvalue::Message;
+
+    fn sending_time() -> Timestamp {
+        Timestamp::new(
+            Date::new(2021, 1, 1).unwrap(),
+            Time::from_hmsm(12, 30, 0, 0).unwrap(),
+        )
+    }
+
+    fn store_message(store: &mut InMemoryStore, seq_num: u64, msg_type: &[u8]) {
+        let mut encoder = Encoder::<crate::tagvalue::Config>::default();
+        let mut buffer = Vec::new();
+        let mut msg = encoder.start_message(b"FIX.4.4", &mut buffer, msg_type);
+        msg.set(fix44::SENDER_COMP_ID, "A");
+        msg.set(fix44::TARGET_COMP_ID, "B");
+        msg.set(fix44::MSG_SEQ_NUM, seq_num);
+        msg.set(fix44::SENDING_TIME, "20200101-00:00:00.000");
+        if msg_type == b"D" {
+            msg.set(fix44::CL_ORD_ID, "ORDER");
+        }
+        store.store(seq_num, msg.wrap()).unwrap();
+    }
+
+    fn with_message<F>(data: &[u8], f: F)
+    where
+        F: FnOnce(Message<&[u8]>),
+    {
+        let mut decoder = Decoder::<crate::tagvalue::Config>::new(Dictionary::fix44());
+        decoder.config_mut().set_verify_checksum(true);
+        f(decoder.decode(data).unwrap());
+    }
+
+    #[test]
+    fn app_messages_are_restamped() {
+        let mut store = InMemoryStore::new();
+        store_message(&mut store, 1, b"D");
+        let mut responder = ResendResponder::new("FIX.4.4", "A", "B");
+        let messages = responder.respond(&mut store, 1, 1, sending_time()).unwrap();
+        assert_eq!(messages.len(), 1);
+        with_message(&messages[0][..], |msg| {
+            assert_eq!(msg.fv(fix44::MSG_TYPE), Ok(b"D" as &[u8]));
+            assert_eq!(msg.fv(fix44::MSG_SEQ_NUM), Ok(1u64));
+            assert_eq!(msg.fv(fix44::POSS_DUP_FLAG), Ok(true));
+            assert_eq!(
+                msg.fv(fix44::ORIG_SENDING_TIME),
+                Ok("20200101-00:00:00.000")
+            );
+            assert_eq!(msg.fv(fix44::SENDING_TIME), Ok("20210101-12:30:00.000"));
+            assert_eq!(msg.fv(fix44::CL_ORD_ID), Ok("ORDER"));
+        });
+    }
+
+    #[test]
+    fn admin_messages_and_holes_are_collapsed_into_gap_fills() {
+        let mut store = InMemoryStore::new();
+        store_message(&mut store, 1, b"A");
+        store_message(&mut store, 2, b"0");
+        store_message(&mut store, 4, b"D");
+        store_message(&mut store, 5, b"3");
+        store_message(&mut store, 6, b"1");
+        let mut responder = ResendResponder::new("FIX.4.4", "A", "B");
+        let messages = responder.respond(&mut store, 1, 7, sending_time()).unwrap();
+        let summary: Vec<(Vec<u8>, u64, Option<u64>)> = messages
+            .iter()
+            .map(|data| {
+                let mut summary = None;
+                with_message(&data[..], |msg| {
+                    summary = Some((
+                        msg.fv_raw(fix44::MSG_TYPE).unwrap().to_vec(),
+                        msg.fv(fix44::MSG_SEQ_NUM).unwrap(),
+                        msg.fv(fix44::NEW_SEQ_NO).ok(),
+                    ));
+                });
+                summary.unwrap()
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (b"4".to_vec(), 1, Some(4)),
+                (b"D".to_vec(), 4, None),
+                (b"3".to_vec(), 5, None),
+                (b"4".to_vec(), 6, Some(8)),
+            ]
+        );
+    }
+}
@@ -0,0 +1,654 @@
+use chrono::{
+    DateTime, Datelike, Duration, FixedOffset, Local, LocalResult, NaiveDate, NaiveDateTime,
+    NaiveTime, Offset, TimeZone, Timelike, Utc, Weekday,
+};
+use chrono_tz::Tz as NamedTz;
+use std::collections::VecDeque;
+use std::fmt;
+
+const SECONDS_IN_DAY: i64 = 24 * 60 * 60;
+const SECONDS_IN_WEEK: i64 = 7 * SECONDS_IN_DAY;
+
+/// The trading hours of a FIX session, i.e. when the session is expected to
+/// be connected.
+///
+/// All times are local to the timezone of the [`Scheduler`]. End times are
+/// exclusive, and a window can wrap around midnight (or the end of the week),
+/// e.g. a daily window from 22:00 to 06:00. A window which starts and ends at
+/// the same time lasts for the whole day (or week), with a single reset point.
+///
+/// Times are wall-clock times, so windows follow daylight saving time
+/// transitions. A start time which is skipped when clocks are turned forward
+/// opens the window as soon as local clocks go past it; a start time which
+/// occurs twice when clocks are turned back opens the window at its first
+/// occurrence.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Schedule {
+    /// The session is never interrupted.
+    Always,
+    /// The session starts and ends every day at the same times.
+    Daily {
+        /// Local time of day at which the session starts.
+        start: NaiveTime,
+        /// Local time of day at which the session ends.
+        end: NaiveTime,
+    },
+    /// The session starts and ends once a week.
+    Weekly {
+        /// Local day of week at which the session starts.
+        start_day: Weekday,
+        /// Local time of day at which the session starts.
+        start: NaiveTime,
+        /// Local day of week at which the session ends.
+        end_day: Weekday,
+        /// Local time of day at which the session ends.
+        end: NaiveTime,
+    },
+}
+
+impl Schedule {
+    // Returns the duration of the cycle and the offsets within the cycle of
+    // the start and end of the session window.
+    fn offsets(&self) -> Option<(i64, i64, i64)> {
+        match self {
+            Self::Always => None,
+            Self::Daily { start, end } => Some((
+                SECONDS_IN_DAY,
+                start.num_seconds_from_midnight() as i64,
+                end.num_seconds_from_midnight() as i64,
+            )),
+            Self::Weekly {
+                start_day,
+                start,
+                end_day,
+                end,
+            } => Some((
+                SECONDS_IN_WEEK,
+                week_offset(*start_day, *start),
+                week_offset(*end_day, *end),
+            )),
+        }
+    }
+}
+
+/// An event produced by a [`Scheduler`], to be handled by the caller.
+///
+/// Please note that [`SchedulerEvent`] is marked with `#[non_exhaustive]`,
+/// which future-proofs the enumeration type in case more variants are added.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum SchedulerEvent {
+    /// The session window just opened: the session should be established.
+    ShouldConnect,
+    /// The session window just closed: the session should be logged out and
+    /// disconnected.
+    ShouldDisconnect,
+    /// A new session window just started and seq. numbers should be reset to
+    /// 1 before connecting.
+    ShouldResetSeqNums,
+}
+
+/// A *sans-IO* helper that follows the trading calendar of a venue.
+///
+/// [`Scheduler`] performs no I/O and no timekeeping on its own: the caller
+/// periodically calls [`Scheduler::tick`] with the current time and then
+/// drains [`SchedulerEvent`]'s via [`Scheduler::next_event`].
+///
+/// The very first call to [`Scheduler::tick`] emits
+/// [`SchedulerEvent::ShouldConnect`] if the session window is open, but never
+/// [`SchedulerEvent::ShouldResetSeqNums`]: the caller must compare
+/// [`Scheduler::session_start`] against its own records (e.g. persisted
+/// seq. numbers) to know whether a reset is needed after a restart.
+///
+/// The timezone can be any [`chrono::TimeZone`] implementor: a [`FixedOffset`]
+/// (the default), [`Utc`], [`chrono::Local`], or a timezone with daylight
+/// saving time rules such as `chrono_tz::Tz`.
+///
+/// # Examples
+///
+/// ```
+/// use chrono::{FixedOffset, NaiveTime, TimeZone, Utc};
+/// use fefix::session::{Schedule, Scheduler, SchedulerEvent};
+///
+/// let schedule = Schedule::Daily {
+///     start: NaiveTime::from_hms(8, 0, 0),
+///     end: NaiveTime::from_hms(17, 0, 0),
+/// };
+/// // UTC+1.
+/// let mut scheduler = Scheduler::new(schedule, FixedOffset::east(3600));
+/// scheduler.set_reset_on_new_session(true);
+///
+/// scheduler.tick(Utc.ymd(2021, 3, 1).and_hms(6, 0, 0));
+/// assert_eq!(scheduler.next_event(), None);
+/// scheduler.tick(Utc.ymd(2021, 3, 1).and_hms(7, 0, 0));
+/// assert_eq!(scheduler.next_event(), Some(SchedulerEvent::ShouldResetSeqNums));
+/// assert_eq!(scheduler.next_event(), Some(SchedulerEvent::ShouldConnect));
+/// scheduler.tick(Utc.ymd(2021, 3, 1).and_hms(16, 0, 0));
+/// assert_eq!(scheduler.next_event(), Some(SchedulerEvent::ShouldDisconnect));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Scheduler<Tz = FixedOffset>
+where
+    Tz: TimeZone,
+{
+    schedule: Schedule,
+    timezone: Tz,
+    reset_on_logon: bool,
+    reset_on_new_session: bool,
+    last_window: Option<Window>,
+    events: VecDeque<SchedulerEvent>,
+}
+
+impl<Tz> Scheduler<Tz>
+where
+    Tz: TimeZone,
+{
+    /// Creates a new [`Scheduler`] that follows `schedule` in `timezone`.
+    pub fn new(schedule: Schedule, timezone: Tz) -> Self {
+        Self {
+            schedule,
+            timezone,
+            reset_on_logon: false,
+            reset_on_new_session: false,
+            last_window: None,
+            events: VecDeque::new(),
+        }
+    }
+
+    /// Returns the [`Schedule`] of `self`.
+    pub fn schedule(&self) -> Schedule {
+        self.schedule
+    }
+
+    /// Returns the timezone of `self`.
+    pub fn timezone(&self) -> &Tz {
+        &self.timezone
+    }
+
+    /// Returns `true` if every `Logon <A>` should carry `ResetSeqNumFlag
+    /// <141>`, as required by some venues. The [`Scheduler`] itself doesn't
+    /// act upon this flag. `false` by default.
+    pub fn reset_on_logon(&self) -> bool {
+        self.reset_on_logon
+    }
+
+    /// Sets whether every `Logon <A>` should carry `ResetSeqNumFlag <141>`.
+    pub fn set_reset_on_logon(&mut self, reset: bool) {
+        self.reset_on_logon = reset;
+    }
+
+    /// Returns `true` if [`SchedulerEvent::ShouldResetSeqNums`] is emitted
+    /// at the start of every session window. `false` by default.
+    pub fn reset_on_new_session(&self) -> bool {
+        self.reset_on_new_session
+    }
+
+    /// Sets whether [`SchedulerEvent::ShouldResetSeqNums`] is emitted at the
+    /// start of every session window.
+    pub fn set_reset_on_new_session(&mut self, reset: bool) {
+        self.reset_on_new_session = reset;
+    }
+
+    /// Returns `true` if and only if the session window is open at `now`.
+    pub fn is_in_session(&self, now: DateTime<Utc>) -> bool {
+        self.window(now) != Window::Closed
+    }
+
+    /// Returns the start time of the session window that is open at `now`,
+    /// if any. [`Schedule::Always`] has no start time.
+    pub fn session_start(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self.window(now) {
+            Window::Open(start) => start,
+            Window::Closed => None,
+        }
+    }
+
+    fn window(&self, now: DateTime<Utc>) -> Window {
+        let (cycle, start, end) = match self.schedule.offsets() {
+            Some(offsets) => offsets,
+            None => return Window::Open(None),
+        };
+        let local = now.with_timezone(&self.timezone);
+        let offset = match self.schedule {
+            Schedule::Weekly { .. } => week_offset(local.weekday(), local.time()),
+            _ => local.num_seconds_from_midnight() as i64,
+        };
+        let since_start = (offset - start).rem_euclid(cycle);
+        let window = match (end - start).rem_euclid(cycle) {
+            0 => cycle,
+            window => window,
+        };
+        if since_start >= window {
+            return Window::Closed;
+        }
+        let start =
+            local.naive_local().with_nanosecond(0).unwrap() - Duration::seconds(since_start);
+        let start = match self.timezone.from_local_datetime(&start) {
+            LocalResult::Single(start) => start.with_timezone(&Utc),
+            // Clocks were turned back and `start` occurred twice.
+            LocalResult::Ambiguous(earliest, _) => earliest.with_timezone(&Utc),
+            // Clocks were turned forward and skipped `start`.
+            LocalResult::None => first_instant_at_or_after(&self.timezone, start),
+        };
+        Window::Open(Some(start))
+    }
+
+    /// Checks the session window at `now` and records new
+    /// [`SchedulerEvent`]'s, if any.
+    pub fn tick(&mut self, now: DateTime<Utc>) {
+        let window = self.window(now);
+        match (self.last_window.replace(window), window) {
+            (None, Window::Open(_)) => self.events.push_back(SchedulerEvent::ShouldConnect),
+            (Some(Window::Closed), Window::Open(_)) => self.on_new_session(),
+            (Some(Window::Open(previous)), Window::Open(start)) if previous != start => {
+                // Back-to-back session windows, or ticks are too far apart.
+                self.events.push_back(SchedulerEvent::ShouldDisconnect);
+                self.on_new_session();
+            }
+            (Some(Window::Open(_)), Window::Closed) => {
+                self.events.push_back(SchedulerEvent::ShouldDisconnect)
+            }
+            _ => {}
+        }
+    }
+
+    /// Removes and returns the oldest [`SchedulerEvent`] that is yet to be
+    /// handled by the caller, if any.
+    pub fn next_event(&mut self) -> Option<SchedulerEvent> {
+        self.events.pop_front()
+    }
+
+    fn on_new_session(&mut self) {
+        if self.reset_on_new_session {
+            self.events.push_back(SchedulerEvent::ShouldResetSeqNums);
+        }
+        self.events.push_back(SchedulerEvent::ShouldConnect);
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Window {
+    Closed,
+    // The start time of the current window, if any.
+    Open(Option<DateTime<Utc>>),
+}
+
+/// The timezone of the trading hours of a
+/// [`SessionConfig`](super::SessionConfig), which implements
+/// [`chrono::TimeZone`].
+///
+/// # Examples
+///
+/// ```
+/// use chrono::{TimeZone, Utc};
+/// use fefix::session::SessionTimeZone;
+///
+/// let new_york = SessionTimeZone::Named(chrono_tz::America::New_York);
+/// let summer = Utc.ymd(2021, 7, 1).and_hms(12, 0, 0);
+/// assert_eq!(summer.with_timezone(&new_york).to_string(), "2021-07-01 08:00:00 -04:00");
+/// assert_eq!(new_york.name(), "America/New_York");
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SessionTimeZone {
+    /// A timezone from the IANA timezone database, as bundled by
+    /// [`chrono_tz`].
+    Named(NamedTz),
+    /// The local timezone of the host, as per [`chrono::Local`].
+    Local,
+}
+
+impl SessionTimeZone {
+    /// Returns the name of `self`, e.g. `America/New_York`, or `Local`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            SessionTimeZone::Named(tz) => tz.name(),
+            SessionTimeZone::Local => "Local",
+        }
+    }
+
+    fn with_offset(&self, offset: FixedOffset) -> SessionTimeZoneOffset {
+        SessionTimeZoneOffset {
+            zone: *self,
+            offset,
+        }
+    }
+}
+
+impl TimeZone for SessionTimeZone {
+    type Offset = SessionTimeZoneOffset;
+
+    fn from_offset(offset: &SessionTimeZoneOffset) -> Self {
+        offset.zone
+    }
+
+    fn offset_from_local_date(&self, local: &NaiveDate) -> LocalResult<SessionTimeZoneOffset> {
+        match self {
+            SessionTimeZone::Named(tz) => tz.offset_from_local_date(local).map(|o| o.fix()),
+            SessionTimeZone::Local => Local.offset_from_local_date(local),
+        }
+        .map(|offset| self.with_offset(offset))
+    }
+
+    fn offset_from_local_datetime(
+        &self,
+        local: &NaiveDateTime,
+    ) -> LocalResult<SessionTimeZoneOffset> {
+        match self {
+            SessionTimeZone::Named(tz) => tz.offset_from_local_datetime(local).map(|o| o.fix()),
+            SessionTimeZone::Local => Local.offset_from_local_datetime(local),
+        }
+        .map(|offset| self.with_offset(offset))
+    }
+
+    fn offset_from_utc_date(&self, utc: &NaiveDate) -> SessionTimeZoneOffset {
+        let offset = match self {
+            SessionTimeZone::Named(tz) => tz.offset_from_utc_date(utc).fix(),
+            SessionTimeZone::Local => Local.offset_from_utc_date(utc),
+        };
+        self.with_offset(offset)
+    }
+
+    fn offset_from_utc_datetime(&self, utc: &NaiveDateTime) -> SessionTimeZoneOffset {
+        let offset = match self {
+            SessionTimeZone::Named(tz) => tz.offset_from_utc_datetime(utc).fix(),
+            SessionTimeZone::Local => Local.offset_from_utc_datetime(utc),
+        };
+        self.with_offset(offset)
+    }
+}
+
+/// The UTC offset of a [`SessionTimeZone`] at some instant.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SessionTimeZoneOffset {
+    zone: SessionTimeZone,
+    offset: FixedOffset,
+}
+
+impl SessionTimeZoneOffset {
+    /// Returns the [`SessionTimeZone`] of `self`.
+    pub fn zone(&self) -> SessionTimeZone {
+        self.zone
+    }
+}
+
+impl Offset for SessionTimeZoneOffset {
+    fn fix(&self) -> FixedOffset {
+        self.offset
+    }
+}
+
+impl fmt::Display for SessionTimeZoneOffset {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.offset, f)
+    }
+}
+
+fn week_offset(day: Weekday, time: NaiveTime) -> i64 {
+    day.num_days_from_monday() as i64 * SECONDS_IN_DAY + time.num_seconds_from_midnight() as i64
+}
+
+/// Returns the first instant (with a precision of one second) at which local
+/// clocks in `timezone` read `local` or later.
+fn first_instant_at_or_after<Tz>(timezone: &Tz, local: NaiveDateTime) -> DateTime<Utc>
+where
+    Tz: TimeZone,
+{
+    // UTC offsets are always less than a day.
+    let mut before = local - Duration::days(1);
+    let mut after = local + Duration::days(1);
+    while after - before > Duration::seconds(1) {
+        let middle = before + Duration::seconds((after - before).num_seconds() / 2);
+        if timezone.from_utc_datetime(&middle).naive_local() < local {
+            before = middle;
+        } else {
+            after = middle;
+        }
+    }
+    DateTime::from_utc(after, Utc)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn utc(day: u32, hour: u32, min: u32) -> DateTime<Utc> {
+        // 2021-03-01 is a Monday.
+        Utc.ymd(2021, 3, day).and_hms(hour, min, 0)
+    }
+
+    /// New York time, with the daylight saving time rules in effect since
+    /// 2007: EDT from 02:00 of the second Sunday of March until 02:00 of the
+    /// first Sunday of November, EST otherwise.
+    #[derive(Debug, Copy, Clone)]
+    struct UsEastern;
+
+    impl UsEastern {
+        fn edt() -> FixedOffset {
+            FixedOffset::west(4 * 3600)
+        }
+
+        fn est() -> FixedOffset {
+            FixedOffset::west(5 * 3600)
+        }
+    }
+
+    impl TimeZone for UsEastern {
+        type Offset = FixedOffset;
+
+        fn from_offset(_offset: &FixedOffset) -> Self {
+            UsEastern
+        }
+
+        fn offset_from_local_date(&self, local: &NaiveDate) -> LocalResult<FixedOffset> {
+            self.offset_from_local_datetime(&local.and_hms(12, 0, 0))
+        }
+
+        fn offset_from_local_datetime(&self, local: &NaiveDateTime) -> LocalResult<FixedOffset> {
+            let is_valid =
+                |offset: FixedOffset| self.offset_from_utc_datetime(&(*local - offset)) == offset;
+            match (is_valid(Self::edt()), is_valid(Self::est())) {
+                (true, true) => LocalResult::Ambiguous(Self::edt(), Self::est()),
+                (true, false) => LocalResult::Single(Self::edt()),
+                (false, true) => LocalResult::Single(Self::est()),
+                (false, false) => LocalResult::None,
+            }
+        }
+
+        fn offset_from_utc_date(&self, utc: &NaiveDate) -> FixedOffset {
+            self.offset_from_utc_datetime(&utc.and_hms(12, 0, 0))
+        }
+
+        fn offset_from_utc_datetime(&self, utc: &NaiveDateTime) -> FixedOffset {
+            let year = utc.year();
+            let dst_start =
+                NaiveDate::from_weekday_of_month(year, 3, Weekday::Sun, 2).and_hms(7, 0, 0);
+            let dst_end =
+                NaiveDate::from_weekday_of_month(year, 11, Weekday::Sun, 1).and_hms(6, 0, 0);
+            if *utc >= dst_start && *utc < dst_end {
+                Self::edt()
+            } else {
+                Self::est()
+            }
+        }
+    }
+
+    fn events<Tz: TimeZone>(scheduler: &mut Scheduler<Tz>) -> Vec<SchedulerEvent> {
+        std::iter::from_fn(|| scheduler.next_event()).collect()
+    }
+
+    #[test]
+    fn overnight_daily_session() {
+        let schedule = Schedule::Daily {
+            start: NaiveTime::from_hms(22, 0, 0),
+            end: NaiveTime::from_hms(6, 0, 0),
+        };
+        let scheduler = Scheduler::new(schedule, FixedOffset::east(0));
+        assert!(scheduler.is_in_session(utc(1, 23, 0)));
+        assert!(scheduler.is_in_session(utc(2, 5, 59)));
+        assert!(!scheduler.is_in_session(utc(2, 6, 0)));
+        assert_eq!(scheduler.session_start(utc(2, 5, 0)), Some(utc(1, 22, 0)));
+        assert_eq!(scheduler.session_start(utc(2, 12, 0)), None);
+    }
+
+    #[test]
+    fn weekly_session_in_other_timezone() {
+        // Sunday 17:00 to Friday 17:00, New York time. Early March is still
+        // EST, i.e. UTC-5.
+        let schedule = Schedule::Weekly {
+            start_day: Weekday::Sun,
+            start: NaiveTime::from_hms(17, 0, 0),
+            end_day: Weekday::Fri,
+            end: NaiveTime::from_hms(17, 0, 0),
+        };
+        let scheduler = Scheduler::new(schedule, UsEastern);
+        assert!(scheduler.is_in_session(utc(3, 12, 0)));
+        assert!(scheduler.is_in_session(utc(5, 21, 59)));
+        assert!(!scheduler.is_in_session(utc(5, 22, 0)));
+        assert!(!scheduler.is_in_session(utc(7, 21, 0)));
+        assert_eq!(
+            scheduler.session_start(utc(3, 12, 0)),
+            Some(utc(1, 22, 0) - Duration::days(1))
+        );
+    }
+
+    #[test]
+    fn restart_within_session_doesnt_reset() {
+        let schedule = Schedule::Daily {
+            start: NaiveTime::from_hms(8, 0, 0),
+            end: NaiveTime::from_hms(17, 0, 0),
+        };
+        let mut scheduler = Scheduler::new(schedule, FixedOffset::east(0));
+        scheduler.set_reset_on_new_session(true);
+        scheduler.tick(utc(1, 9, 0));
+        assert_eq!(events(&mut scheduler), vec![SchedulerEvent::ShouldConnect]);
+        scheduler.tick(utc(1, 10, 0));
+        assert_eq!(events(&mut scheduler), vec![]);
+        scheduler.tick(utc(1, 18, 0));
+        assert_eq!(
+            events(&mut scheduler),
+            vec![SchedulerEvent::ShouldDisconnect]
+        );
+        scheduler.tick(utc(2, 8, 0));
+        assert_eq!(
+            events(&mut scheduler),
+            vec![
+                SchedulerEvent::ShouldResetSeqNums,
+                SchedulerEvent::ShouldConnect
+            ]
+        );
+    }
+
+    #[test]
+    fn full_day_session_resets_daily() {
+        let schedule = Schedule::Daily {
+            start: NaiveTime::from_hms(0, 0, 0),
+            end: NaiveTime::from_hms(0, 0, 0),
+        };
+        let mut scheduler = Scheduler::new(schedule, FixedOffset::east(0));
+        scheduler.set_reset_on_new_session(true);
+        scheduler.tick(utc(1, 23, 59));
+        events(&mut scheduler);
+        scheduler.tick(utc(2, 0, 1));
+        assert_eq!(
+            events(&mut scheduler),
+            vec![
+                SchedulerEvent::ShouldDisconnect,
+                SchedulerEvent::ShouldResetSeqNums,
+                SchedulerEvent::ShouldConnect
+            ]
+        );
+    }
+
+    #[test]
+    fn always_connects_once() {
+        let mut scheduler = Scheduler::new(Schedule::Always, FixedOffset::east(0));
+        scheduler.tick(utc(1, 0, 0));
+        scheduler.tick(utc(9, 0, 0));
+        assert_eq!(events(&mut scheduler), vec![SchedulerEvent::ShouldConnect]);
+    }
+
+    #[test]
+    fn daily_session_follows_spring_forward() {
+        // Clocks go from 02:00 EST to 03:00 EDT on 2021-03-14.
+        let schedule = Schedule::Daily {
+            start: NaiveTime::from_hms(8, 0, 0),
+            end: NaiveTime::from_hms(17, 0, 0),
+        };
+        let mut scheduler = Scheduler::new(schedule, UsEastern);
+        scheduler.set_reset_on_new_session(true);
+        assert_eq!(
+            scheduler.session_start(utc(13, 14, 0)),
+            Some(utc(13, 13, 0))
+        );
+        assert_eq!(
+            scheduler.session_start(utc(14, 14, 0)),
+            Some(utc(14, 12, 0))
+        );
+        scheduler.tick(utc(13, 21, 59));
+        events(&mut scheduler);
+        scheduler.tick(utc(13, 22, 0));
+        assert_eq!(
+            events(&mut scheduler),
+            vec![SchedulerEvent::ShouldDisconnect]
+        );
+        scheduler.tick(utc(14, 11, 59));
+        assert_eq!(events(&mut scheduler), vec![]);
+        scheduler.tick(utc(14, 12, 0));
+        assert_eq!(
+            events(&mut scheduler),
+            vec![
+                SchedulerEvent::ShouldResetSeqNums,
+                SchedulerEvent::ShouldConnect
+            ]
+        );
+        scheduler.tick(utc(14, 21, 0));
+        assert_eq!(
+            events(&mut scheduler),
+            vec![SchedulerEvent::ShouldDisconnect]
+        );
+    }
+
+    #[test]
+    fn skipped_start_time_opens_at_transition() {
+        let schedule = Schedule::Daily {
+            start: NaiveTime::from_hms(2, 30, 0),
+            end: NaiveTime::from_hms(5, 0, 0),
+        };
+        let scheduler = Scheduler::new(schedule, UsEastern);
+        // 01:59 EST, then 03:00 EDT.
+        assert!(!scheduler.is_in_session(utc(14, 6, 59)));
+        assert!(scheduler.is_in_session(utc(14, 7, 0)));
+        assert_eq!(scheduler.session_start(utc(14, 8, 0)), Some(utc(14, 7, 0)));
+    }
+
+    #[test]
+    fn daily_session_follows_fall_back() {
+        // Clocks go from 02:00 EDT to 01:00 EST on 2021-11-07.
+        let nov = |day: u32, hour: u32, min: u32| Utc.ymd(2021, 11, day).and_hms(hour, min, 0);
+        let schedule = Schedule::Daily {
+            start: NaiveTime::from_hms(8, 0, 0),
+            end: NaiveTime::from_hms(17, 0, 0),
+        };
+        let scheduler = Scheduler::new(schedule, UsEastern);
+        assert_eq!(scheduler.session_start(nov(6, 14, 0)), Some(nov(6, 12, 0)));
+        assert_eq!(scheduler.session_start(nov(7, 14, 0)), Some(nov(7, 13, 0)));
+        assert!(scheduler.is_in_session(nov(7, 21, 59)));
+        assert!(!scheduler.is_in_session(nov(7, 22, 0)));
+    }
+
+    #[test]
+    fn repeated_start_time_opens_at_first_occurrence() {
+        let schedule = Schedule::Daily {
+            start: NaiveTime::from_hms(1, 30, 0),
+            end: NaiveTime::from_hms(4, 0, 0),
+        };
+        let scheduler = Scheduler::new(schedule, UsEastern);
+        let nov = |hour: u32, min: u32| Utc.ymd(2021, 11, 7).and_hms(hour, min, 0);
+        // 01:45 EDT and 02:30 EST.
+        assert_eq!(scheduler.session_start(nov(5, 45)), Some(nov(5, 30)));
+        assert_eq!(scheduler.session_start(nov(7, 30)), Some(nov(5, 30)));
+        // 04:00 EST.
+        assert!(!scheduler.is_in_session(nov(9, 0)));
+    }
+}
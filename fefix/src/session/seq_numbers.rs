@@ -1,8 +1,33 @@
 use std::cmp::Ordering;
+use std::fmt;
 use std::num::NonZeroU64;
 
 /// A tracker for seq. numbers inside a FIX session.
-#[derive(Debug, Copy, Clone)]
+///
+/// [`SeqNumbers`] only does bookkeeping: it's up to the caller to act upon
+/// errors, e.g. by sending a `ResendRequest <2>` on
+/// [`SeqNumberError::Recover`]. Persistence is also left to the caller, see
+/// [`MessageStore::set_seq_numbers`](super::MessageStore::set_seq_numbers).
+///
+/// # Examples
+///
+/// ```
+/// use fefix::session::{SeqNumberError, SeqNumbers};
+///
+/// let mut seq_numbers = SeqNumbers::default();
+/// assert_eq!(seq_numbers.take_outbound(), 1);
+/// assert_eq!(seq_numbers.next_outbound(), 2);
+///
+/// seq_numbers.on_inbound(1).unwrap();
+/// assert_eq!(
+///     seq_numbers.on_inbound(5),
+///     Err(SeqNumberError::Recover { expected: 2, received: 5 })
+/// );
+/// // `SequenceReset <4>` in GapFill mode, with `MsgSeqNum <34>` equal to 2.
+/// seq_numbers.gap_fill(2, 10).unwrap();
+/// assert_eq!(seq_numbers.next_inbound(), 10);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub struct SeqNumbers {
     pub next_inbound: u64,
     pub next_outbound: u64,
@@ -35,12 +60,77 @@ impl SeqNumbers {
         self.next_outbound += 1;
     }
 
+    /// Returns the seq. number of the next outbound message and increments it,
+    /// in a single step. This is the preferred way to assign `MsgSeqNum <34>`
+    /// to new outbound messages, as it's impossible to forget the increment.
+    pub fn take_outbound(&mut self) -> u64 {
+        let seq_num = self.next_outbound;
+        self.next_outbound += 1;
+        seq_num
+    }
+
+    /// Checks `inbound` against the expected seq. number of the next inbound
+    /// message, without modifying `self`.
     pub fn validate_inbound(&self, inbound: u64) -> Result<(), SeqNumberError> {
         match inbound.cmp(&self.next_inbound) {
             Ordering::Equal => Ok(()),
-            Ordering::Less => Err(SeqNumberError::TooLow),
-            Ordering::Greater => Err(SeqNumberError::Recover),
+            Ordering::Less => Err(SeqNumberError::TooLow {
+                expected: self.next_inbound,
+                received: inbound,
+            }),
+            Ordering::Greater => Err(SeqNumberError::Recover {
+                expected: self.next_inbound,
+                received: inbound,
+            }),
+        }
+    }
+
+    /// Validates `inbound` like [`SeqNumbers::validate_inbound`] and, if it's
+    /// the expected seq. number, increments the next inbound seq. number.
+    pub fn on_inbound(&mut self, inbound: u64) -> Result<(), SeqNumberError> {
+        self.validate_inbound(inbound)?;
+        self.incr_inbound();
+        Ok(())
+    }
+
+    /// Applies a `SequenceReset <4>` with `NewSeqNo <36>` equal to
+    /// `new_seq_no`, which becomes the expected seq. number of the next
+    /// inbound message.
+    ///
+    /// In *Reset* mode `MsgSeqNum <34>` is ignored, so this method can be
+    /// called directly. Seq. numbers can never decrease: a `new_seq_no` lower
+    /// than [`SeqNumbers::next_inbound`] results in
+    /// [`SeqNumberError::InvalidNewSeqNo`] and leaves `self` unchanged.
+    pub fn sequence_reset(&mut self, new_seq_no: u64) -> Result<(), SeqNumberError> {
+        if new_seq_no < self.next_inbound {
+            return Err(SeqNumberError::InvalidNewSeqNo {
+                expected: self.next_inbound,
+                new_seq_no,
+            });
         }
+        self.next_inbound = new_seq_no;
+        Ok(())
+    }
+
+    /// Applies a `SequenceReset <4>` in *GapFill* mode, with `MsgSeqNum <34>`
+    /// equal to `msg_seq_num` and `NewSeqNo <36>` equal to `new_seq_no`.
+    ///
+    /// Unlike *Reset* mode, `msg_seq_num` must be the expected inbound seq.
+    /// number. `new_seq_no` must then be strictly greater than `msg_seq_num`.
+    /// `self` is unchanged in case of errors.
+    pub fn gap_fill(&mut self, msg_seq_num: u64, new_seq_no: u64) -> Result<(), SeqNumberError> {
+        self.validate_inbound(msg_seq_num)?;
+        let mut next = *self;
+        next.incr_inbound();
+        next.sequence_reset(new_seq_no)?;
+        *self = next;
+        Ok(())
+    }
+
+    /// Resets both seq. numbers to 1, e.g. after a `Logon <A>` with
+    /// `ResetSeqNumFlag <141>`.
+    pub fn reset(&mut self) {
+        *self = Self::default();
     }
 }
 
@@ -55,9 +145,127 @@ impl Default for SeqNumbers {
     }
 }
 
-#[derive(Debug, Clone)]
+/// The reason why an inbound seq. number was refused by [`SeqNumbers`].
+///
+/// Please note that [`SeqNumberError`] is marked with `#[non_exhaustive]`,
+/// which future-proofs the enumeration type in case more variants are added.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum SeqNumberError {
-    Recover,
-    TooLow,
+    /// `MsgSeqNum <34>` is higher than expected. Some messages were lost and
+    /// they must be recovered via `ResendRequest <2>`.
+    Recover {
+        /// The expected seq. number.
+        expected: u64,
+        /// The seq. number of the inbound message.
+        received: u64,
+    },
+    /// `MsgSeqNum <34>` is lower than expected. Unless `PossDupFlag <43>` is
+    /// set, this is a serious error and the session should be terminated.
+    TooLow {
+        /// The expected seq. number.
+        expected: u64,
+        /// The seq. number of the inbound message.
+        received: u64,
+    },
+    /// `MsgSeqNum <34>` is missing.
     NoSeqNum,
+    /// A `SequenceReset <4>` attempted to decrease the inbound seq. number.
+    InvalidNewSeqNo {
+        /// The minimum legal value for `NewSeqNo <36>`.
+        expected: u64,
+        /// The value of `NewSeqNo <36>`.
+        new_seq_no: u64,
+    },
+}
+
+impl fmt::Display for SeqNumberError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Recover { expected, received } => write!(
+                f,
+                "MsgSeqNum too high, expected {} but received {}",
+                expected, received
+            ),
+            Self::TooLow { expected, received } => write!(
+                f,
+                "MsgSeqNum too low, expected {} but received {}",
+                expected, received
+            ),
+            Self::NoSeqNum => write!(f, "Missing MsgSeqNum"),
+            Self::InvalidNewSeqNo {
+                expected,
+                new_seq_no,
+            } => write!(
+                f,
+                "NewSeqNo {} is lower than the expected MsgSeqNum {}",
+                new_seq_no, expected
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SeqNumberError {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn inbound_validation() {
+        let mut seq_numbers = SeqNumbers::default();
+        assert_eq!(seq_numbers.on_inbound(1), Ok(()));
+        assert_eq!(
+            seq_numbers.on_inbound(1),
+            Err(SeqNumberError::TooLow {
+                expected: 2,
+                received: 1
+            })
+        );
+        assert_eq!(
+            seq_numbers.on_inbound(3),
+            Err(SeqNumberError::Recover {
+                expected: 2,
+                received: 3
+            })
+        );
+        assert_eq!(seq_numbers.next_inbound(), 2);
+    }
+
+    #[test]
+    fn sequence_reset_cant_decrease_seq_numbers() {
+        let mut seq_numbers = SeqNumbers::default();
+        seq_numbers.sequence_reset(10).unwrap();
+        assert_eq!(seq_numbers.next_inbound(), 10);
+        // Equal values are legal, albeit pointless.
+        seq_numbers.sequence_reset(10).unwrap();
+        assert_eq!(
+            seq_numbers.sequence_reset(9),
+            Err(SeqNumberError::InvalidNewSeqNo {
+                expected: 10,
+                new_seq_no: 9
+            })
+        );
+        assert_eq!(seq_numbers.next_inbound(), 10);
+    }
+
+    #[test]
+    fn gap_fill_requires_expected_msg_seq_num() {
+        let mut seq_numbers = SeqNumbers::default();
+        assert!(seq_numbers.gap_fill(2, 5).is_err());
+        // `NewSeqNo <36>` must point past the gap fill itself.
+        assert!(seq_numbers.gap_fill(1, 1).is_err());
+        assert_eq!(seq_numbers, SeqNumbers::default());
+        seq_numbers.gap_fill(1, 2).unwrap();
+        assert_eq!(seq_numbers.next_inbound(), 2);
+    }
+
+    #[test]
+    fn take_outbound_increments() {
+        let mut seq_numbers = SeqNumbers::default();
+        assert_eq!(seq_numbers.take_outbound(), 1);
+        assert_eq!(seq_numbers.take_outbound(), 2);
+        seq_numbers.reset();
+        assert_eq!(seq_numbers.take_outbound(), 1);
+    }
 }
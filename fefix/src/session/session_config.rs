@@ -0,0 +1,788 @@
+use super::{Configure, SessionBuilder};
+use crate::dict::ParseDictionaryError;
+use crate::Dictionary;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::Duration;
+
+#[cfg(feature = "utils-chrono")]
+use super::{Schedule, Scheduler, SessionTimeZone};
+#[cfg(feature = "utils-chrono")]
+use chrono::{NaiveTime, Weekday};
+
+/// The settings of a single FIX session, as found in the `[SESSION]` sections of
+/// QuickFIX configuration files. This allows reusing existing configuration
+/// files when migrating from QuickFIX.
+///
+/// The most common settings are parsed and validated as soon as the file is
+/// read, while all others are still available with [`SessionConfig::get`].
+/// Settings within `[DEFAULT]` apply to all sessions, unless overridden, and
+/// setting names are case-insensitive.
+///
+/// |**Settings**                                          |**Meaning**                            |
+/// |------------------------------------------------------|---------------------------------------|
+/// |`BeginString`, `SenderCompID`, `TargetCompID`         |Session identifiers (mandatory)        |
+/// |`ConnectionType`                                      |`initiator` or `acceptor` (mandatory)  |
+/// |`HeartBtInt`                                          |Heartbeat interval, in seconds         |
+/// |`StartTime`, `EndTime`, `StartDay`, `EndDay`          |Trading hours                          |
+/// |`TimeZone`, `UseLocalTime`                            |Timezone of the trading hours          |
+/// |`NonStopSession`                                      |`Y` if the session is never interrupted|
+/// |`ResetOnLogon`, `ResetOnLogout`, `ResetOnDisconnect`  |`Y` to reset seq. numbers              |
+/// |`DataDictionary`                                      |Path to a QuickFIX dictionary          |
+/// |`TransportDataDictionary`, `AppDataDictionary`        |Paths to FIXT dictionaries             |
+///
+/// # Examples
+///
+/// ```
+/// use fefix::session::SessionConfig;
+/// use std::time::Duration;
+///
+/// let sessions = SessionConfig::parse_quickfix_ini(
+///     "
+///     [DEFAULT]
+///     ConnectionType=initiator
+///     HeartBtInt=30
+///     ; Acme's test environment.
+///     [SESSION]
+///     BeginString=FIX.4.4
+///     SenderCompID=ME
+///     TargetCompID=ACME
+///     ResetOnLogon=Y
+///     SocketConnectHost=127.0.0.1
+///     ",
+/// )
+/// .unwrap();
+/// assert_eq!(sessions.len(), 1);
+/// let session = &sessions[0];
+/// assert_eq!(session.target_comp_id(), "ACME");
+/// assert_eq!(session.heartbeat(), Some(Duration::from_secs(30)));
+/// assert!(session.is_initiator());
+/// assert!(session.reset_on_logon());
+/// assert_eq!(session.get("socketconnecthost"), Some("127.0.0.1"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct SessionConfig {
+    settings: Settings,
+    begin_string: String,
+    sender_comp_id: String,
+    target_comp_id: String,
+    is_initiator: bool,
+    heartbeat: Option<Duration>,
+    reset_on_logon: bool,
+    reset_on_logout: bool,
+    reset_on_disconnect: bool,
+    // Only needed to build a `Schedule`.
+    #[cfg_attr(not(feature = "utils-chrono"), allow(dead_code))]
+    non_stop: bool,
+    // Seconds since midnight.
+    #[cfg_attr(not(feature = "utils-chrono"), allow(dead_code))]
+    times: Option<(u32, u32)>,
+    // Days since Monday.
+    #[cfg_attr(not(feature = "utils-chrono"), allow(dead_code))]
+    days: Option<(u32, u32)>,
+    time_zone: Option<String>,
+    use_local_time: bool,
+    #[cfg(feature = "utils-chrono")]
+    session_time_zone: SessionTimeZone,
+}
+
+// Maps uppercase setting names to their values and line numbers.
+type Settings = HashMap<String, (String, usize)>;
+
+#[derive(Debug, Copy, Clone)]
+enum Section {
+    Default,
+    Session,
+    Ignored,
+}
+
+impl SessionConfig {
+    /// Reads the QuickFIX configuration file at `path` and returns the settings
+    /// of all of its sessions, in order. See
+    /// [`SessionConfig::parse_quickfix_ini`].
+    pub fn from_quickfix_ini<P>(path: P) -> Result<Vec<Self>, SessionConfigError>
+    where
+        P: AsRef<Path>,
+    {
+        let input = fs::read_to_string(path)?;
+        Self::parse_quickfix_ini(input)
+    }
+
+    /// Parses the contents of a QuickFIX configuration file and returns the
+    /// settings of all of its sessions, in order.
+    ///
+    /// Blank lines and comments (starting with `#` or `;`) are ignored, and so
+    /// are sections other than `[DEFAULT]` and `[SESSION]`.
+    pub fn parse_quickfix_ini<S>(input: S) -> Result<Vec<Self>, SessionConfigError>
+    where
+        S: AsRef<str>,
+    {
+        let mut defaults = Settings::new();
+        // The settings and section header line number of each session.
+        let mut sessions: Vec<(Settings, usize)> = Vec::new();
+        let mut section = Section::Ignored;
+        for (i, line) in input.as_ref().lines().enumerate() {
+            let line_number = i + 1;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+                continue;
+            }
+            if line.starts_with('[') && line.ends_with(']') {
+                section = match line[1..line.len() - 1].trim().to_ascii_uppercase().as_str() {
+                    "DEFAULT" => Section::Default,
+                    "SESSION" => {
+                        sessions.push((Settings::new(), line_number));
+                        Section::Session
+                    }
+                    _ => Section::Ignored,
+                };
+                continue;
+            }
+            let (key, value) = line
+                .split_once('=')
+                .ok_or(SessionConfigError::Syntax { line: line_number })?;
+            let settings = match section {
+                Section::Default => &mut defaults,
+                Section::Session => &mut sessions.last_mut().unwrap().0,
+                Section::Ignored => continue,
+            };
+            settings.insert(
+                key.trim().to_ascii_uppercase(),
+                (value.trim().to_string(), line_number),
+            );
+        }
+        sessions
+            .into_iter()
+            .map(|(mut settings, line)| {
+                for (key, value) in defaults.iter() {
+                    settings.entry(key.clone()).or_insert_with(|| value.clone());
+                }
+                Self::from_settings(settings, line)
+            })
+            .collect()
+    }
+
+    fn from_settings(settings: Settings, line: usize) -> Result<Self, SessionConfigError> {
+        let required = |key: &'static str| {
+            settings
+                .get(&key.to_ascii_uppercase())
+                .map(|(value, _)| value.clone())
+                .ok_or(SessionConfigError::MissingSetting { line, key })
+        };
+        let begin_string = required("BeginString")?;
+        let sender_comp_id = required("SenderCompID")?;
+        let target_comp_id = required("TargetCompID")?;
+        let is_initiator = match required("ConnectionType")?.to_ascii_lowercase().as_str() {
+            "initiator" => true,
+            "acceptor" => false,
+            _ => return Err(invalid(&settings, "ConnectionType")),
+        };
+        let heartbeat = parse_optional(&settings, "HeartBtInt", |value| {
+            value.parse().ok().map(Duration::from_secs)
+        })?;
+        let start_time = parse_optional(&settings, "StartTime", parse_time)?;
+        let end_time = parse_optional(&settings, "EndTime", parse_time)?;
+        // A timezone after `StartTime` and `EndTime` takes precedence over
+        // `TimeZone`, just like in QuickFIX/J.
+        let (start_time, start_zone) = start_time.unzip();
+        let (end_time, end_zone) = end_time.unzip();
+        let (start_zone, end_zone) = (start_zone.flatten(), end_zone.flatten());
+        if start_zone.is_some() && end_zone.is_some() && start_zone != end_zone {
+            return Err(invalid(&settings, "EndTime"));
+        }
+        let time_zone_key = match (&start_zone, &end_zone) {
+            (Some(_), _) => "StartTime",
+            (None, Some(_)) => "EndTime",
+            (None, None) => "TimeZone",
+        };
+        let time_zone = start_zone
+            .or(end_zone)
+            .or_else(|| settings.get("TIMEZONE").map(|(value, _)| value.clone()));
+        if time_zone.as_deref() == Some("") {
+            return Err(invalid(&settings, "TimeZone"));
+        }
+        let use_local_time = parse_flag(&settings, "UseLocalTime")?;
+        #[cfg(feature = "utils-chrono")]
+        let session_time_zone = match (&time_zone, use_local_time) {
+            (Some(name), _) => name
+                .parse()
+                .map(SessionTimeZone::Named)
+                .map_err(|_| invalid(&settings, time_zone_key))?,
+            (None, true) => SessionTimeZone::Local,
+            (None, false) => SessionTimeZone::Named(chrono_tz::UTC),
+        };
+        #[cfg(not(feature = "utils-chrono"))]
+        let _ = time_zone_key;
+        let start_day = parse_optional(&settings, "StartDay", parse_day)?;
+        let end_day = parse_optional(&settings, "EndDay", parse_day)?;
+        let non_stop = parse_flag(&settings, "NonStopSession")?;
+        let times = both(start_time, end_time, line, ("StartTime", "EndTime"))?;
+        let days = both(start_day, end_day, line, ("StartDay", "EndDay"))?;
+        if days.is_some() && times.is_none() {
+            return Err(SessionConfigError::MissingSetting {
+                line,
+                key: "StartTime",
+            });
+        }
+        Ok(Self {
+            begin_string,
+            sender_comp_id,
+            target_comp_id,
+            is_initiator,
+            heartbeat,
+            reset_on_logon: parse_flag(&settings, "ResetOnLogon")?,
+            reset_on_logout: parse_flag(&settings, "ResetOnLogout")?,
+            reset_on_disconnect: parse_flag(&settings, "ResetOnDisconnect")?,
+            non_stop,
+            times,
+            days,
+            time_zone,
+            use_local_time,
+            #[cfg(feature = "utils-chrono")]
+            session_time_zone,
+            settings,
+        })
+    }
+
+    /// Returns the raw value of the setting called `key`, e.g.
+    /// `SocketConnectPort`, if present. `key` is case-insensitive.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.settings
+            .get(&key.to_ascii_uppercase())
+            .map(|(value, _)| value.as_str())
+    }
+
+    /// Returns the value of `BeginString`, e.g. `FIX.4.4`.
+    pub fn begin_string(&self) -> &str {
+        self.begin_string.as_str()
+    }
+
+    /// Returns the value of `SenderCompID`.
+    pub fn sender_comp_id(&self) -> &str {
+        self.sender_comp_id.as_str()
+    }
+
+    /// Returns the value of `TargetCompID`.
+    pub fn target_comp_id(&self) -> &str {
+        self.target_comp_id.as_str()
+    }
+
+    /// Returns `true` if `ConnectionType` is `initiator`; `false` if it is
+    /// `acceptor`.
+    pub fn is_initiator(&self) -> bool {
+        self.is_initiator
+    }
+
+    /// Returns the value of `HeartBtInt`, if present.
+    pub fn heartbeat(&self) -> Option<Duration> {
+        self.heartbeat
+    }
+
+    /// Returns `true` if `ResetOnLogon` is `Y`. `false` by default.
+    pub fn reset_on_logon(&self) -> bool {
+        self.reset_on_logon
+    }
+
+    /// Returns `true` if `ResetOnLogout` is `Y`. `false` by default.
+    pub fn reset_on_logout(&self) -> bool {
+        self.reset_on_logout
+    }
+
+    /// Returns `true` if `ResetOnDisconnect` is `Y`. `false` by default.
+    pub fn reset_on_disconnect(&self) -> bool {
+        self.reset_on_disconnect
+    }
+
+    /// Returns the name of the timezone of the trading hours, e.g.
+    /// `America/New_York`, if any. It comes from `StartTime` and `EndTime`
+    /// (e.g. `StartTime=08:00:00 US/Eastern`) or, failing that, from
+    /// `TimeZone`.
+    pub fn time_zone(&self) -> Option<&str> {
+        self.time_zone.as_deref()
+    }
+
+    /// Returns `true` if `UseLocalTime` is `Y`, i.e. trading hours are in the
+    /// local timezone of the host unless [`SessionConfig::time_zone`] is set.
+    /// `false` by default.
+    pub fn use_local_time(&self) -> bool {
+        self.use_local_time
+    }
+
+    /// Returns the path of `DataDictionary`, if present.
+    pub fn data_dictionary(&self) -> Option<&Path> {
+        self.get("DataDictionary").map(Path::new)
+    }
+
+    /// Returns the path of `TransportDataDictionary`, if present. Only used by
+    /// FIXT sessions.
+    pub fn transport_data_dictionary(&self) -> Option<&Path> {
+        self.get("TransportDataDictionary").map(Path::new)
+    }
+
+    /// Returns the path of `AppDataDictionary`, if present. Only used by FIXT
+    /// sessions.
+    pub fn app_data_dictionary(&self) -> Option<&Path> {
+        self.get("AppDataDictionary").map(Path::new)
+    }
+
+    /// Reads the data dictionaries of `self`, if any, and merges them into a
+    /// [`Dictionary`]. Returns [`None`] if `UseDataDictionary` is `N` or if no
+    /// dictionary is configured at all.
+    ///
+    /// `TransportDataDictionary` and `AppDataDictionary` take precedence over
+    /// `DataDictionary`, and relative paths are resolved against the current
+    /// working directory, just like QuickFIX does.
+    pub fn load_dictionary(&self) -> Result<Option<Dictionary>, SessionConfigError> {
+        if !parse_flag_or(&self.settings, "UseDataDictionary", true)? {
+            return Ok(None);
+        }
+        let dict = match (
+            self.transport_data_dictionary(),
+            self.app_data_dictionary(),
+            self.data_dictionary(),
+        ) {
+            (Some(transport), Some(application), _) => {
+                Dictionary::from_quickfix_specs(read_path(transport)?, read_path(application)?)?
+            }
+            (_, _, Some(path)) => Dictionary::from_quickfix_spec(read_path(path)?)?,
+            _ => return Ok(None),
+        };
+        Ok(Some(dict))
+    }
+
+    /// Creates a new [`SessionBuilder`] with the `BeginString`,
+    /// `SenderCompID`, `TargetCompID`, and `HeartBtInt` of `self`. The
+    /// dictionary, if any, must be set separately (see
+    /// [`SessionConfig::load_dictionary`]).
+    pub fn session_builder<C>(&self) -> SessionBuilder<C>
+    where
+        C: Configure,
+    {
+        let mut builder = SessionBuilder::default();
+        builder.set_begin_string(self.begin_string.as_str());
+        builder.set_sender_comp_id(self.sender_comp_id.as_str());
+        builder.set_target_comp_id(self.target_comp_id.as_str());
+        if let Some(heartbeat) = self.heartbeat {
+            builder.set_heartbeat(heartbeat);
+        }
+        builder
+    }
+
+    /// Returns the trading hours of `self`. Sessions without `StartTime` and
+    /// `EndTime` are never interrupted, just like those with `NonStopSession`.
+    #[cfg(feature = "utils-chrono")]
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "utils-chrono")))]
+    pub fn schedule(&self) -> Schedule {
+        let (start, end) = match self.times {
+            Some((start, end)) if !self.non_stop => (time_of_day(start), time_of_day(end)),
+            _ => return Schedule::Always,
+        };
+        match self.days {
+            Some((start_day, end_day)) => Schedule::Weekly {
+                start_day: weekday(start_day),
+                start,
+                end_day: weekday(end_day),
+                end,
+            },
+            None => Schedule::Daily { start, end },
+        }
+    }
+
+    /// Returns the timezone of the trading hours of `self`: the one named by
+    /// [`SessionConfig::time_zone`], as found in the IANA timezone database
+    /// bundled by [`chrono_tz`]; the local timezone if `UseLocalTime` is `Y`;
+    /// UTC otherwise.
+    #[cfg(feature = "utils-chrono")]
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "utils-chrono")))]
+    pub fn session_time_zone(&self) -> SessionTimeZone {
+        self.session_time_zone
+    }
+
+    /// Creates a new [`Scheduler`] that follows [`SessionConfig::schedule`]
+    /// in [`SessionConfig::session_time_zone`]. Just like QuickFIX, seq. numbers are
+    /// reset at the start of every session window, and `ResetOnLogon` is
+    /// honored.
+    #[cfg(feature = "utils-chrono")]
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "utils-chrono")))]
+    pub fn scheduler(&self) -> Scheduler<SessionTimeZone> {
+        let schedule = self.schedule();
+        let mut scheduler = Scheduler::new(schedule, self.session_time_zone);
+        scheduler.set_reset_on_logon(self.reset_on_logon);
+        scheduler.set_reset_on_new_session(schedule != Schedule::Always);
+        scheduler
+    }
+}
+
+/// The type returned in the event of an error while loading a
+/// [`SessionConfig`].
+///
+/// Please note that [`SessionConfigError`] is marked with `#[non_exhaustive]`,
+/// which future-proofs the enumeration type in case more variants are added.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub enum SessionConfigError {
+    /// A line is neither a section header, a setting, nor a comment.
+    Syntax {
+        /// The line number, starting from 1.
+        line: usize,
+    },
+    /// A mandatory setting is missing from a `[SESSION]` section, as well as
+    /// from `[DEFAULT]`.
+    MissingSetting {
+        /// The line number of the `[SESSION]` section header, starting from 1.
+        line: usize,
+        /// The name of the missing setting.
+        key: &'static str,
+    },
+    /// A setting has an invalid value.
+    InvalidSetting {
+        /// The line number of the setting, starting from 1.
+        line: usize,
+        /// The name of the setting.
+        key: &'static str,
+    },
+    /// A data dictionary couldn't be parsed.
+    Dictionary(ParseDictionaryError),
+    /// I/O-related error while reading a file.
+    Io(io::ErrorKind),
+}
+
+impl fmt::Display for SessionConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Syntax { line } => write!(f, "Invalid syntax at line {}", line),
+            Self::MissingSetting { line, key } => write!(
+                f,
+                "Missing setting '{}' in the session at line {}",
+                key, line
+            ),
+            Self::InvalidSetting { line, key } => {
+                write!(f, "Invalid value for '{}' at line {}", key, line)
+            }
+            Self::Dictionary(err) => write!(f, "{}", err),
+            Self::Io(kind) => write!(f, "I/O error: {:?}", kind),
+        }
+    }
+}
+
+impl std::error::Error for SessionConfigError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Dictionary(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for SessionConfigError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err.kind())
+    }
+}
+
+impl From<ParseDictionaryError> for SessionConfigError {
+    fn from(err: ParseDictionaryError) -> Self {
+        Self::Dictionary(err)
+    }
+}
+
+fn invalid(settings: &Settings, key: &'static str) -> SessionConfigError {
+    let line = settings
+        .get(&key.to_ascii_uppercase())
+        .map_or(0, |(_, line)| *line);
+    SessionConfigError::InvalidSetting { line, key }
+}
+
+fn parse_optional<T, F>(
+    settings: &Settings,
+    key: &'static str,
+    parse: F,
+) -> Result<Option<T>, SessionConfigError>
+where
+    F: Fn(&str) -> Option<T>,
+{
+    match settings.get(&key.to_ascii_uppercase()) {
+        Some((value, _)) => parse(value).map(Some).ok_or_else(|| invalid(settings, key)),
+        None => Ok(None),
+    }
+}
+
+// Settings like `StartTime` and `EndTime` only make sense together.
+fn both<T>(
+    start: Option<T>,
+    end: Option<T>,
+    line: usize,
+    keys: (&'static str, &'static str),
+) -> Result<Option<(T, T)>, SessionConfigError> {
+    match (start, end) {
+        (Some(start), Some(end)) => Ok(Some((start, end))),
+        (None, None) => Ok(None),
+        (Some(_), None) => Err(SessionConfigError::MissingSetting { line, key: keys.1 }),
+        (None, Some(_)) => Err(SessionConfigError::MissingSetting { line, key: keys.0 }),
+    }
+}
+
+fn parse_flag(settings: &Settings, key: &'static str) -> Result<bool, SessionConfigError> {
+    parse_flag_or(settings, key, false)
+}
+
+fn parse_flag_or(
+    settings: &Settings,
+    key: &'static str,
+    default: bool,
+) -> Result<bool, SessionConfigError> {
+    let flag = parse_optional(settings, key, |value| match value {
+        "Y" | "y" => Some(true),
+        "N" | "n" => Some(false),
+        _ => None,
+    })?;
+    Ok(flag.unwrap_or(default))
+}
+
+// Parses `HH:MM:SS`, optionally followed by a timezone name (e.g. `08:00:00
+// US/Eastern`), into seconds since midnight and the timezone name.
+fn parse_time(value: &str) -> Option<(u32, Option<String>)> {
+    let (time, time_zone) = match value.split_once(char::is_whitespace) {
+        Some((time, time_zone)) => (time, Some(time_zone.trim().to_string())),
+        None => (value, None),
+    };
+    let mut parts = time.split(':').map(|part| part.parse::<u32>().ok());
+    let (hours, minutes, seconds) = (parts.next()??, parts.next()??, parts.next()??);
+    if parts.next().is_some() || hours > 23 || minutes > 59 || seconds > 59 {
+        return None;
+    }
+    Some((hours * 3600 + minutes * 60 + seconds, time_zone))
+}
+
+// Parses a day of week into days since Monday. Just like QuickFIX, only the
+// first two letters matter, e.g. `Mo`, `Mon`, and `Monday` are all valid.
+fn parse_day(value: &str) -> Option<u32> {
+    const DAYS: [&str; 7] = ["MO", "TU", "WE", "TH", "FR", "SA", "SU"];
+    let prefix = value.get(..2)?.to_ascii_uppercase();
+    DAYS.iter().position(|day| *day == prefix).map(|i| i as u32)
+}
+
+fn read_path(path: &Path) -> Result<String, SessionConfigError> {
+    Ok(fs::read_to_string(path)?)
+}
+
+#[cfg(feature = "utils-chrono")]
+fn time_of_day(seconds: u32) -> NaiveTime {
+    NaiveTime::from_num_seconds_from_midnight(seconds, 0)
+}
+
+#[cfg(feature = "utils-chrono")]
+fn weekday(days_since_monday: u32) -> Weekday {
+    const WEEKDAYS: [Weekday; 7] = [
+        Weekday::Mon,
+        Weekday::Tue,
+        Weekday::Wed,
+        Weekday::Thu,
+        Weekday::Fri,
+        Weekday::Sat,
+        Weekday::Sun,
+    ];
+    WEEKDAYS[days_since_monday as usize]
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::session::{Config, SessionState};
+
+    const CFG: &str = "
+# Shared by all sessions.
+[DEFAULT]
+ConnectionType=initiator
+HeartBtInt=30
+StartTime=08:00:00
+EndTime=17:30:00
+FileStorePath=store
+
+[SESSION]
+BeginString=FIX.4.2
+SenderCompID=ME
+TargetCompID=VENUE1
+ResetOnLogon=Y
+
+[SESSION]
+BeginString=FIXT.1.1
+SenderCompID=ME
+TargetCompID=VENUE2
+heartbtint=10
+StartDay=sunday
+EndDay=Fri
+NonStopSession=N
+";
+
+    #[test]
+    fn defaults_are_inherited_and_overridden() {
+        let sessions = SessionConfig::parse_quickfix_ini(CFG).unwrap();
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(sessions[0].begin_string(), "FIX.4.2");
+        assert_eq!(sessions[0].heartbeat(), Some(Duration::from_secs(30)));
+        assert!(sessions[0].reset_on_logon());
+        assert_eq!(sessions[1].target_comp_id(), "VENUE2");
+        assert_eq!(sessions[1].heartbeat(), Some(Duration::from_secs(10)));
+        assert!(!sessions[1].reset_on_logon());
+        assert_eq!(sessions[1].get("FileStorePath"), Some("store"));
+        assert_eq!(sessions[1].get("SocketConnectHost"), None);
+    }
+
+    #[test]
+    fn session_builder_uses_session_identifiers() {
+        let sessions = SessionConfig::parse_quickfix_ini(CFG).unwrap();
+        let session = sessions[0].session_builder::<Config>().build();
+        assert_eq!(session.state(), SessionState::AwaitingLogon);
+        assert_eq!(session.sender_comp_id(), "ME");
+        assert_eq!(session.target_comp_id(), "VENUE1");
+    }
+
+    #[test]
+    fn missing_and_invalid_settings_are_reported() {
+        let err = SessionConfig::parse_quickfix_ini(
+            "[SESSION]\nBeginString=FIX.4.4\nSenderCompID=A\nTargetCompID=B\n",
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            SessionConfigError::MissingSetting {
+                line: 1,
+                key: "ConnectionType"
+            }
+        ));
+        let err = SessionConfig::parse_quickfix_ini(
+            "[DEFAULT]\nConnectionType=acceptor\nHeartBtInt=soon\n[SESSION]\nBeginString=FIX.4.4\nSenderCompID=A\nTargetCompID=B\n",
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            SessionConfigError::InvalidSetting {
+                line: 3,
+                key: "HeartBtInt"
+            }
+        ));
+        let err = SessionConfig::parse_quickfix_ini("[SESSION]\nnonsense\n").unwrap_err();
+        assert!(matches!(err, SessionConfigError::Syntax { line: 2 }));
+    }
+
+    #[test]
+    fn dictionaries_are_optional() {
+        let sessions = SessionConfig::parse_quickfix_ini(CFG).unwrap();
+        assert!(sessions[0].load_dictionary().unwrap().is_none());
+        let sessions = SessionConfig::parse_quickfix_ini(
+            "[SESSION]\nConnectionType=acceptor\nBeginString=FIX.4.4\nSenderCompID=A\nTargetCompID=B\nDataDictionary=does/not/exist.xml\n",
+        )
+        .unwrap();
+        assert!(matches!(
+            sessions[0].load_dictionary(),
+            Err(SessionConfigError::Io(io::ErrorKind::NotFound))
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "utils-chrono")]
+    fn schedules_follow_quickfix_semantics() {
+        let sessions = SessionConfig::parse_quickfix_ini(CFG).unwrap();
+        let start = NaiveTime::from_hms(8, 0, 0);
+        let end = NaiveTime::from_hms(17, 30, 0);
+        assert_eq!(sessions[0].schedule(), Schedule::Daily { start, end });
+        assert_eq!(
+            sessions[1].schedule(),
+            Schedule::Weekly {
+                start_day: Weekday::Sun,
+                start,
+                end_day: Weekday::Fri,
+                end
+            }
+        );
+        let scheduler = sessions[0].scheduler();
+        assert!(scheduler.reset_on_logon());
+        assert!(scheduler.reset_on_new_session());
+        assert_eq!(scheduler.timezone().name(), "UTC");
+    }
+
+    fn with_times(settings: &str) -> Result<Vec<SessionConfig>, SessionConfigError> {
+        SessionConfig::parse_quickfix_ini(format!(
+            "[SESSION]\nConnectionType=acceptor\nBeginString=FIX.4.4\nSenderCompID=A\nTargetCompID=B\n{}",
+            settings
+        ))
+    }
+
+    #[test]
+    fn time_zone_setting_and_suffix() {
+        let sessions = with_times("StartTime=08:00:00\nEndTime=17:00:00\nTimeZone=UTC\n").unwrap();
+        assert_eq!(sessions[0].time_zone(), Some("UTC"));
+        assert!(!sessions[0].use_local_time());
+        let sessions =
+            with_times("StartTime=08:00:00 GMT\nEndTime=17:00:00 GMT\nTimeZone=Europe/Nowhere\n")
+                .unwrap();
+        assert_eq!(sessions[0].time_zone(), Some("GMT"));
+        assert!(matches!(
+            with_times("StartTime=08:00:00 UTC\nEndTime=17:00:00 GMT\n"),
+            Err(SessionConfigError::InvalidSetting {
+                line: 7,
+                key: "EndTime"
+            })
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "utils-chrono")]
+    fn unknown_time_zones_are_reported() {
+        assert!(matches!(
+            with_times("StartTime=08:00:00\nEndTime=17:00:00\nTimeZone=Europe/Nowhere\n"),
+            Err(SessionConfigError::InvalidSetting {
+                line: 8,
+                key: "TimeZone"
+            })
+        ));
+        assert!(matches!(
+            with_times("StartTime=08:00:00 Europe/Nowhere\nEndTime=17:00:00\n"),
+            Err(SessionConfigError::InvalidSetting {
+                line: 6,
+                key: "StartTime"
+            })
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "utils-chrono")]
+    fn use_local_time_setting() {
+        let sessions =
+            with_times("StartTime=08:00:00\nEndTime=17:00:00\nUseLocalTime=Y\n").unwrap();
+        assert!(sessions[0].use_local_time());
+        assert_eq!(sessions[0].time_zone(), None);
+        assert_eq!(sessions[0].scheduler().timezone().name(), "Local");
+        // `TimeZone` takes precedence.
+        let sessions =
+            with_times("StartTime=08:00:00\nEndTime=17:00:00\nUseLocalTime=Y\nTimeZone=UTC\n")
+                .unwrap();
+        assert_eq!(sessions[0].scheduler().timezone().name(), "UTC");
+        assert!(matches!(
+            with_times("UseLocalTime=maybe\n"),
+            Err(SessionConfigError::InvalidSetting {
+                line: 6,
+                key: "UseLocalTime"
+            })
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "utils-chrono")]
+    fn scheduler_follows_named_time_zone() {
+        use chrono::{TimeZone, Utc};
+
+        let sessions =
+            with_times("StartTime=08:00:00 US/Eastern\nEndTime=17:00:00 US/Eastern\n").unwrap();
+        let scheduler = sessions[0].scheduler();
+        assert_eq!(scheduler.timezone().name(), "US/Eastern");
+        // EST, then EDT.
+        let start = |day| scheduler.session_start(Utc.ymd(2021, 3, day).and_hms(15, 0, 0));
+        assert_eq!(start(12), Some(Utc.ymd(2021, 3, 12).and_hms(13, 0, 0)));
+        assert_eq!(start(15), Some(Utc.ymd(2021, 3, 15).and_hms(12, 0, 0)));
+    }
+}
@@ -0,0 +1,1766 @@
+use super::{
+    errs, Clock, Config, Configure, Environment, HeartbeatAction, HeartbeatRule, HeartbeatTimers,
+    LogonSigner, MessageStore, Reject, ResendResponder, SeqNumbers, SignedFields, Throttle,
+};
+use crate::definitions::fix44;
+use crate::dict::IsFieldDefinition;
+use crate::fix_values::{TimePrecision, Timestamp};
+use crate::tagvalue::{
+    Configure as _, Decoder, Encoder, EncoderHandle, FieldAccess, Message, RawDecoder,
+};
+use crate::{Dictionary, TagU16};
+use std::cmp::Ordering;
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+const FIXT_BEGIN_STRING: &str = "FIXT.1.1";
+const DEFAULT_APPL_VER_ID: u16 = 1137;
+
+/// The state of a [`Session`] within the FIX session lifecycle.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SessionState {
+    /// No `Logon <A>` message has been exchanged yet.
+    AwaitingLogon,
+    /// A `Logon <A>` was sent and the counterparty's response is pending.
+    LogonSent,
+    /// The logon exchange was successful and application messages can flow.
+    Active,
+    /// A `Logout <5>` was sent and the counterparty's response is pending.
+    LogoutSent,
+    /// The session is over and the transport should be closed.
+    Disconnected,
+}
+
+impl SessionState {
+    pub(crate) const ALL: [SessionState; 5] = [
+        SessionState::AwaitingLogon,
+        SessionState::LogonSent,
+        SessionState::Active,
+        SessionState::LogoutSent,
+        SessionState::Disconnected,
+    ];
+}
+
+impl fmt::Display for SessionState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+/// An event produced by a [`Session`], to be handled by the caller.
+///
+/// Please note that [`SessionEvent`] is marked with `#[non_exhaustive]`,
+/// which future-proofs the enumeration type in case more variants are added.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SessionEvent {
+    /// A FIX message that must be written to the transport layer, as-is.
+    Outbound(Vec<u8>),
+    /// An in-sequence, inbound application message.
+    Application(Vec<u8>),
+    /// The logon exchange is complete.
+    LoggedOn,
+    /// The counterparty requested the retransmission of messages
+    /// `begin..=end`, which were skipped via a `SequenceReset <4>` in
+    /// *GapFill* mode.
+    GapFilled {
+        /// The first `MsgSeqNum <34>` of the requested range.
+        begin: u64,
+        /// The last `MsgSeqNum <34>` of the requested range.
+        end: u64,
+    },
+    /// The counterparty requested the retransmission of messages
+    /// `begin..=end`, which must be answered via [`Session::resend`]. Only
+    /// produced if [`SessionBuilder::set_resend_from_store`] is enabled.
+    ResendRequested {
+        /// The first `MsgSeqNum <34>` of the requested range.
+        begin: u64,
+        /// The last `MsgSeqNum <34>` of the requested range.
+        end: u64,
+    },
+    /// The inbound data couldn't be decoded and was ignored.
+    Garbled,
+    /// An application message was delayed by the [`Throttle`] of the
+    /// [`Session`]. It will be sent by [`Session::tick`], after any other
+    /// delayed messages.
+    Throttled {
+        /// The number of delayed messages, this one included.
+        queued: usize,
+        /// How long it takes for the next delayed message to be sent, i.e.
+        /// when [`Session::tick`] should be called next.
+        retry_after: Duration,
+    },
+    /// The session is over and the transport should be closed.
+    Disconnect {
+        /// A human-readable explanation.
+        reason: String,
+    },
+}
+
+/// A builder for [`Session`].
+#[derive(Debug, Clone)]
+pub struct SessionBuilder<C = Config>
+where
+    C: Configure,
+{
+    config: C,
+    dict: Dictionary,
+    begin_string: String,
+    environment: Environment,
+    heartbeat: Duration,
+    heartbeat_rule: HeartbeatRule,
+    seq_numbers: SeqNumbers,
+    persisted_seq_numbers: Option<SeqNumbers>,
+    resend_from_store: bool,
+    sender_comp_id: String,
+    target_comp_id: String,
+    clock: Arc<dyn Clock>,
+    timestamp_precision: TimePrecision,
+    throttle: Option<Throttle>,
+    appl_ver_dictionaries: Vec<(String, Dictionary)>,
+    default_appl_ver_id: Option<String>,
+}
+
+impl<C> SessionBuilder<C>
+where
+    C: Configure,
+{
+    /// Sets the [`Configure`] implementor of the [`Session`].
+    pub fn set_config(&mut self, config: C) {
+        self.config = config;
+    }
+
+    /// Sets the [`Dictionary`] used to decode inbound messages. FIX 4.4 by
+    /// default.
+    pub fn set_dictionary(&mut self, dict: Dictionary) {
+        self.dict = dict;
+    }
+
+    pub(crate) fn dictionary(&self) -> &Dictionary {
+        &self.dict
+    }
+
+    pub fn set_begin_string<S>(&mut self, begin_string: S)
+    where
+        S: Into<String>,
+    {
+        self.begin_string = begin_string.into();
+    }
+
+    pub fn set_environment(&mut self, env: Environment) {
+        self.environment = env;
+    }
+
+    /// Sets the heartbeat interval that an initiator proposes in its
+    /// `Logon <A>` message. 30 seconds by default.
+    pub fn set_heartbeat(&mut self, heartbeat: Duration) {
+        self.heartbeat = heartbeat;
+    }
+
+    /// Sets the [`Clock`] of the [`Session`], which stamps `SendingTime <52>`
+    /// and starts its timers. [`SystemClock`](super::SystemClock) by default.
+    pub fn set_clock<K>(&mut self, clock: K)
+    where
+        K: Clock + 'static,
+    {
+        self.clock = Arc::new(clock);
+    }
+
+    /// Returns the [`Clock`] of the [`Session`].
+    pub fn clock(&self) -> &dyn Clock {
+        &*self.clock
+    }
+
+    /// Sets the precision of `SendingTime <52>` in all outbound messages,
+    /// retransmissions included. [`TimePrecision::Millis`] by default.
+    pub fn set_timestamp_precision(&mut self, precision: TimePrecision) {
+        self.timestamp_precision = precision;
+    }
+
+    /// Sets the [`HeartbeatRule`] that an acceptor enforces on inbound
+    /// `Logon <A>` messages. [`HeartbeatRule::Any`] by default.
+    pub fn set_heartbeat_rule(&mut self, rule: HeartbeatRule) {
+        self.heartbeat_rule = rule;
+    }
+
+    /// Sets the expected seq. numbers of the next inbound and outbound
+    /// messages to those last persisted in `store`, e.g. after a restart. See
+    /// [`Session::persist_seq_numbers`].
+    pub fn restore_seq_numbers<S>(&mut self, store: &S)
+    where
+        S: MessageStore,
+    {
+        self.seq_numbers = store.seq_numbers();
+        self.persisted_seq_numbers = Some(self.seq_numbers);
+    }
+
+    /// Chooses how inbound `ResendRequest <2>` messages are answered. If
+    /// `false` (the default), the whole requested range is skipped with a
+    /// single `SequenceReset <4>` in *GapFill* mode and
+    /// [`SessionEvent::GapFilled`] is produced. If `true`,
+    /// [`SessionEvent::ResendRequested`] is produced instead, and the caller
+    /// is expected to answer via [`Session::resend`].
+    pub fn set_resend_from_store(&mut self, resend_from_store: bool) {
+        self.resend_from_store = resend_from_store;
+    }
+
+    /// Sets the expected seq. numbers of the next inbound and outbound
+    /// messages.
+    ///
+    /// # Panics
+    ///
+    /// This method will panic if either `inbound` or `outbound` is zero.
+    pub fn set_seq_numbers(&mut self, inbound: u64, outbound: u64) {
+        if inbound == 0 || outbound == 0 {
+            panic!("FIX sequence numbers must be strictly positive");
+        }
+        self.seq_numbers = SeqNumbers {
+            next_inbound: inbound,
+            next_outbound: outbound,
+        };
+    }
+
+    /// Limits the rate of outbound application messages (i.e. those sent via
+    /// [`Session::send`]) according to `throttle`. Messages in excess are
+    /// delayed rather than refused, and [`SessionEvent::Throttled`] is
+    /// produced for each of them. Admin messages are never throttled. No
+    /// throttling by default.
+    pub fn set_throttle(&mut self, throttle: Option<Throttle>) {
+        self.throttle = throttle;
+    }
+
+    /// Registers `dict` as the application-layer [`Dictionary`] of inbound
+    /// `FIXT.1.1` messages with `ApplVerID <1128>` equal to `appl_ver_id`. The
+    /// [`Dictionary`] given to [`SessionBuilder::set_dictionary`] should then
+    /// be the `FIXT.1.1` transport dictionary. See
+    /// [`Decoder::add_appl_ver_dictionary`].
+    pub fn add_appl_ver_dictionary<S>(&mut self, appl_ver_id: S, dict: Dictionary)
+    where
+        S: Into<String>,
+    {
+        self.appl_ver_dictionaries.push((appl_ver_id.into(), dict));
+    }
+
+    /// Sets the `DefaultApplVerID <1137>` that `FIXT.1.1` sessions propose in
+    /// their `Logon <A>` messages, and that applies to inbound messages
+    /// without `ApplVerID <1128>`. If the counterparty's `Logon <A>` carries
+    /// `DefaultApplVerID <1137>`, it takes precedence for inbound messages.
+    /// None by default.
+    pub fn set_default_appl_ver_id(&mut self, appl_ver_id: Option<String>) {
+        self.default_appl_ver_id = appl_ver_id;
+    }
+
+    pub fn set_sender_comp_id<S>(&mut self, sender_comp_id: S)
+    where
+        S: Into<String>,
+    {
+        self.sender_comp_id = sender_comp_id.into();
+    }
+
+    pub fn set_target_comp_id<S>(&mut self, target_comp_id: S)
+    where
+        S: Into<String>,
+    {
+        self.target_comp_id = target_comp_id.into();
+    }
+
+    pub fn build(self) -> Session<C> {
+        let now = self.clock.now();
+        let mut encoder: Encoder = Encoder::default();
+        encoder
+            .config_mut()
+            .set_timestamp_precision(self.timestamp_precision);
+        let mut decoder = Decoder::new(self.dict);
+        for (appl_ver_id, dict) in self.appl_ver_dictionaries {
+            decoder.add_appl_ver_dictionary(&appl_ver_id, dict);
+        }
+        decoder.set_default_appl_ver_id(self.default_appl_ver_id.as_deref());
+        Session {
+            config: self.config,
+            begin_string: self.begin_string,
+            environment: self.environment,
+            heartbeat_rule: self.heartbeat_rule,
+            seq_numbers: self.seq_numbers,
+            persisted_seq_numbers: self.persisted_seq_numbers,
+            resend_from_store: self.resend_from_store,
+            sender_comp_id: self.sender_comp_id,
+            target_comp_id: self.target_comp_id,
+            clock: self.clock,
+            state: SessionState::AwaitingLogon,
+            decoder,
+            raw_decoder: RawDecoder::new(),
+            encoder,
+            inbound: Vec::new(),
+            outbound: Vec::new(),
+            events: VecDeque::new(),
+            timers: HeartbeatTimers::new(self.heartbeat, now),
+            test_request_counter: 0,
+            logout_sent: None,
+            resend_target: None,
+            throttle: self.throttle,
+            throttled: VecDeque::new(),
+            default_appl_ver_id: self.default_appl_ver_id,
+        }
+    }
+}
+
+impl<C> Default for SessionBuilder<C>
+where
+    C: Configure,
+{
+    fn default() -> Self {
+        Self {
+            config: C::default(),
+            dict: Dictionary::fix44(),
+            begin_string: "FIX.4.4".to_string(),
+            environment: Environment::Testing,
+            heartbeat: Duration::from_secs(30),
+            heartbeat_rule: HeartbeatRule::Any,
+            seq_numbers: SeqNumbers::default(),
+            persisted_seq_numbers: None,
+            resend_from_store: false,
+            sender_comp_id: "ABC".to_string(),
+            target_comp_id: "XYZ".to_string(),
+            clock: Arc::new(super::SystemClock),
+            timestamp_precision: TimePrecision::Millis,
+            throttle: None,
+            appl_ver_dictionaries: Vec::new(),
+            default_appl_ver_id: None,
+        }
+    }
+}
+
+/// A *sans-IO* FIX session state machine.
+///
+/// [`Session`] takes care of the logon exchange, heartbeats and test requests,
+/// sequence number validation, resend requests, and logout. It performs no
+/// I/O on its own: the caller feeds inbound bytes via [`Session::feed`],
+/// periodically checks timers via [`Session::tick`], and drains
+/// [`SessionEvent`]'s via [`Session::next_event`]. This makes it suitable for
+/// any transport, both synchronous and asynchronous.
+///
+/// Acceptors simply wait for an inbound `Logon <A>`, while initiators must
+/// call [`Session::logon`] first.
+///
+/// # Examples
+///
+/// ```
+/// use fefix::session::{SessionBuilder, SessionEvent, SessionState};
+/// use std::time::Instant;
+///
+/// let mut initiator: SessionBuilder = SessionBuilder::default();
+/// initiator.set_sender_comp_id("INITIATOR");
+/// initiator.set_target_comp_id("ACCEPTOR");
+/// let mut initiator = initiator.build();
+/// let mut acceptor: SessionBuilder = SessionBuilder::default();
+/// acceptor.set_sender_comp_id("ACCEPTOR");
+/// acceptor.set_target_comp_id("INITIATOR");
+/// let mut acceptor = acceptor.build();
+///
+/// let now = Instant::now();
+/// initiator.logon(now);
+/// while let Some(SessionEvent::Outbound(bytes)) = initiator.next_event() {
+///     acceptor.feed(&bytes[..], now);
+/// }
+/// assert_eq!(acceptor.state(), SessionState::Active);
+/// ```
+#[derive(Debug)]
+pub struct Session<C = Config>
+where
+    C: Configure,
+{
+    config: C,
+    begin_string: String,
+    environment: Environment,
+    heartbeat_rule: HeartbeatRule,
+    seq_numbers: SeqNumbers,
+    persisted_seq_numbers: Option<SeqNumbers>,
+    resend_from_store: bool,
+    sender_comp_id: String,
+    target_comp_id: String,
+    clock: Arc<dyn Clock>,
+    state: SessionState,
+    decoder: Decoder,
+    raw_decoder: RawDecoder,
+    encoder: Encoder,
+    inbound: Vec<u8>,
+    outbound: Vec<u8>,
+    events: VecDeque<SessionEvent>,
+    timers: HeartbeatTimers,
+    test_request_counter: u64,
+    logout_sent: Option<Instant>,
+    resend_target: Option<u64>,
+    throttle: Option<Throttle>,
+    // Delayed application messages, i.e. their `MsgType <35>` and all fields
+    // after it.
+    throttled: VecDeque<(Vec<u8>, Vec<u8>)>,
+    default_appl_ver_id: Option<String>,
+}
+
+impl<C> Session<C>
+where
+    C: Configure,
+{
+    /// Returns an immutable reference to the [`Configure`] implementor used by
+    /// `self`.
+    pub fn config(&self) -> &C {
+        &self.config
+    }
+
+    /// Returns the current [`SessionState`] of `self`.
+    pub fn state(&self) -> SessionState {
+        self.state
+    }
+
+    /// Returns the current [`SeqNumbers`] of `self`.
+    pub fn seq_numbers(&self) -> SeqNumbers {
+        self.seq_numbers
+    }
+
+    /// Returns the number of application messages that were delayed by the
+    /// [`Throttle`] of `self` and are yet to be sent. See
+    /// [`SessionBuilder::set_throttle`].
+    pub fn throttled_len(&self) -> usize {
+        self.throttled.len()
+    }
+
+    /// Returns the [`Clock`] used by `self`.
+    pub fn clock(&self) -> &dyn Clock {
+        &*self.clock
+    }
+
+    /// Returns the `BeginString <8>` of all messages of `self`.
+    pub fn begin_string(&self) -> &str {
+        self.begin_string.as_str()
+    }
+
+    /// Returns the application version of inbound `FIXT.1.1` messages without
+    /// `ApplVerID <1128>`, i.e. the counterparty's `DefaultApplVerID <1137>`
+    /// once logged on. See [`SessionBuilder::set_default_appl_ver_id`].
+    pub fn default_appl_ver_id(&self) -> Option<&str> {
+        self.decoder.default_appl_ver_id()
+    }
+
+    fn is_fixt(&self) -> bool {
+        self.begin_string == FIXT_BEGIN_STRING
+    }
+
+    /// Returns the `SenderCompID <49>` of outbound messages, i.e. our own
+    /// CompID.
+    pub fn sender_comp_id(&self) -> &str {
+        self.sender_comp_id.as_str()
+    }
+
+    /// Returns the `TargetCompID <56>` of outbound messages, i.e. the
+    /// counterparty's CompID.
+    pub fn target_comp_id(&self) -> &str {
+        self.target_comp_id.as_str()
+    }
+
+    /// Persists the current [`SeqNumbers`] of `self` to `store`, but only if
+    /// they changed since the last call. Returns `true` if and only if
+    /// `store` was written to.
+    ///
+    /// This is the persistence hook for seq. numbers: call it after
+    /// [`Session::feed`], [`Session::send`], and the like, so that a new
+    /// [`Session`] can resume where the old one left off via
+    /// [`SessionBuilder::restore_seq_numbers`].
+    pub fn persist_seq_numbers<S>(&mut self, store: &mut S) -> Result<bool, S::Error>
+    where
+        S: MessageStore,
+    {
+        if self.persisted_seq_numbers == Some(self.seq_numbers) {
+            return Ok(false);
+        }
+        store.set_seq_numbers(self.seq_numbers)?;
+        self.persisted_seq_numbers = Some(self.seq_numbers);
+        Ok(true)
+    }
+
+    /// Answers a `ResendRequest <2>` for messages `begin..=end` (see
+    /// [`SessionEvent::ResendRequested`]) with the outbound messages saved in
+    /// `store`, as explained in [`ResendResponder`]. All outbound messages
+    /// must thus be saved to `store` by the caller, with their
+    /// `MsgSeqNum <34>`.
+    pub fn resend<S>(
+        &mut self,
+        store: &mut S,
+        begin: u64,
+        end: u64,
+        now: Instant,
+    ) -> Result<(), S::Error>
+    where
+        S: MessageStore,
+    {
+        let mut responder = ResendResponder::new(
+            self.begin_string.as_str(),
+            self.sender_comp_id.as_str(),
+            self.target_comp_id.as_str(),
+        );
+        responder.set_timestamp_precision(self.encoder.config().timestamp_precision());
+        let sending_time = Timestamp::from_utc(self.clock.utc_now());
+        let messages = responder.respond(store, begin, end, sending_time)?;
+        if !messages.is_empty() {
+            self.timers.on_sent(now);
+        }
+        self.events
+            .extend(messages.into_iter().map(SessionEvent::Outbound));
+        Ok(())
+    }
+
+    /// Returns the heartbeat interval of `self`. For acceptors, this is the
+    /// interval chosen by the counterparty.
+    pub fn heartbeat(&self) -> Duration {
+        self.timers.heartbeat()
+    }
+
+    /// Removes and returns the oldest [`SessionEvent`] that is yet to be
+    /// handled by the caller, if any.
+    pub fn next_event(&mut self) -> Option<SessionEvent> {
+        self.events.pop_front()
+    }
+
+    /// Initiates the logon exchange by sending a `Logon <A>` message.
+    pub fn logon(&mut self, now: Instant) {
+        self.logon_with(now, |_msg| {});
+    }
+
+    /// Like [`Session::logon`], but `f` can add more fields to the `Logon
+    /// <A>` message, e.g. `Username <553>` and `Password <554>`.
+    pub fn logon_with<F>(&mut self, now: Instant, f: F)
+    where
+        F: FnOnce(&mut EncoderHandle<Vec<u8>>),
+    {
+        if self.state != SessionState::AwaitingLogon {
+            return;
+        }
+        let msg_seq_num = self.seq_numbers.take_outbound();
+        let sending_time = Timestamp::from_utc(self.clock.utc_now());
+        self.send_logon(msg_seq_num, sending_time, now, f);
+    }
+
+    /// Like [`Session::logon_with`], but `signer` also computes `RawData <96>`
+    /// (and `RawDataLength <95>`) from the [`SignedFields`] of the `Logon
+    /// <A>`, where `password` is the `Password <554>` that `f` will add, if
+    /// any.
+    pub fn logon_signed<S, F>(&mut self, now: Instant, password: Option<&str>, signer: &mut S, f: F)
+    where
+        S: LogonSigner + ?Sized,
+        F: FnOnce(&mut EncoderHandle<Vec<u8>>),
+    {
+        if self.state != SessionState::AwaitingLogon {
+            return;
+        }
+        let msg_seq_num = self.seq_numbers.take_outbound();
+        let sending_time = Timestamp::from_utc(self.clock.utc_now());
+        // The signature must cover `SendingTime <52>` exactly as encoded.
+        let mut sending_time_bytes = Vec::new();
+        crate::FixValue::serialize_with(
+            &sending_time,
+            &mut sending_time_bytes,
+            self.encoder.config().timestamp_precision(),
+        );
+        let raw_data = signer.sign(&SignedFields::new(
+            std::str::from_utf8(&sending_time_bytes[..]).unwrap_or_default(),
+            msg_seq_num,
+            self.sender_comp_id.as_str(),
+            self.target_comp_id.as_str(),
+            password,
+        ));
+        self.send_logon(msg_seq_num, sending_time, now, |msg| {
+            f(msg);
+            msg.set_data_field(fix44::RAW_DATA_LENGTH, fix44::RAW_DATA, &raw_data[..]);
+        });
+    }
+
+    fn send_logon<F>(&mut self, msg_seq_num: u64, sending_time: Timestamp, now: Instant, f: F)
+    where
+        F: FnOnce(&mut EncoderHandle<Vec<u8>>),
+    {
+        #[cfg(feature = "utils-tracing")]
+        let _span = self.span().entered();
+        let heartbeat = self.timers.heartbeat().as_secs();
+        trace_event!(info, heart_bt_int = heartbeat, "Sending Logon <A>");
+        let default_appl_ver_id = self.logon_appl_ver_id();
+        self.encode_at(b"A", msg_seq_num, sending_time, now, |msg| {
+            msg.set(fix44::ENCRYPT_METHOD, fix44::EncryptMethod::None);
+            msg.set(fix44::HEART_BT_INT, heartbeat);
+            if let Some(appl_ver_id) = default_appl_ver_id.as_deref() {
+                msg.set_any(default_appl_ver_id_tag(), appl_ver_id);
+            }
+            f(msg);
+        });
+        self.set_state(SessionState::LogonSent);
+    }
+
+    /// Initiates the logout exchange by sending a `Logout <5>` message, with an
+    /// optional `text` explanation.
+    pub fn logout(&mut self, text: Option<&str>, now: Instant) {
+        if self.state != SessionState::Active {
+            return;
+        }
+        #[cfg(feature = "utils-tracing")]
+        let _span = self.span().entered();
+        trace_event!(info, "Sending Logout <5>");
+        self.send_logout(text, now);
+        self.set_state(SessionState::LogoutSent);
+        self.logout_sent = Some(now);
+    }
+
+    /// Sends an application message of type `msg_type`. The standard header is
+    /// populated by `self`, while all other fields must be added by `f`.
+    ///
+    /// Returns the current [`SessionState`] as an error if the session is not
+    /// [`SessionState::Active`].
+    ///
+    /// If `self` has a [`Throttle`] and the message exceeds its rate, the
+    /// message is delayed instead: see [`SessionEvent::Throttled`]. Delayed
+    /// messages are only assigned their `MsgSeqNum <34>` and `SendingTime
+    /// <52>` when they're actually sent, and are dropped if the session ends
+    /// in the meantime.
+    pub fn send<F>(&mut self, msg_type: &[u8], now: Instant, f: F) -> Result<(), SessionState>
+    where
+        F: FnOnce(&mut EncoderHandle<Vec<u8>>),
+    {
+        if self.state != SessionState::Active {
+            return Err(self.state);
+        }
+        let throttled = match self.throttle.as_mut() {
+            // Delayed messages must go first.
+            Some(throttle) => !self.throttled.is_empty() || throttle.try_acquire(now).is_err(),
+            None => false,
+        };
+        if throttled {
+            let fields = self.encode_fields(msg_type, f);
+            self.throttled.push_back((msg_type.to_vec(), fields));
+            let retry_after = self
+                .throttle
+                .as_mut()
+                .map_or(Duration::ZERO, |throttle| throttle.available_in(now));
+            trace_event!(
+                debug,
+                queued = self.throttled.len(),
+                "Throttle exceeded, delaying message"
+            );
+            self.events.push_back(SessionEvent::Throttled {
+                queued: self.throttled.len(),
+                retry_after,
+            });
+        } else {
+            self.send_message(msg_type, now, f);
+        }
+        Ok(())
+    }
+
+    /// Sends a `Reject <3>` message, e.g. in response to an inbound message
+    /// that failed validation. See [`Reject::from_decode_error`].
+    ///
+    /// Returns the current [`SessionState`] as an error if the session is not
+    /// [`SessionState::Active`].
+    pub fn reject(&mut self, reject: &Reject, now: Instant) -> Result<(), SessionState> {
+        if self.state != SessionState::Active {
+            return Err(self.state);
+        }
+        trace_event!(warn, reject = ?reject, "Sending Reject <3>");
+        self.send_message(b"3", now, |msg| reject.encode(msg));
+        Ok(())
+    }
+
+    /// Processes `data` from the transport layer, which might contain any
+    /// number of (possibly incomplete) FIX messages.
+    pub fn feed(&mut self, data: &[u8], now: Instant) {
+        #[cfg(feature = "utils-tracing")]
+        let _span = self.span().entered();
+        self.inbound.extend_from_slice(data);
+        while self.state != SessionState::Disconnected {
+            if self.inbound.len() >= 2 && !self.inbound.starts_with(b"8=") {
+                self.skip_garbled_data();
+                continue;
+            }
+            match self.raw_decoder.message_len(&self.inbound[..]) {
+                Ok(Some(len)) if len <= self.inbound.len() => {
+                    let frame: Vec<u8> = self.inbound.drain(..len).collect();
+                    self.on_frame(frame, now);
+                }
+                Ok(_) => break,
+                Err(_) => self.skip_garbled_data(),
+            }
+        }
+    }
+
+    /// Checks heartbeat-related timers and sends `Heartbeat <0>` or
+    /// `TestRequest <1>` messages if needed.
+    pub fn tick(&mut self, now: Instant) {
+        #[cfg(feature = "utils-tracing")]
+        let _span = self.span().entered();
+        match self.state {
+            SessionState::Active => {
+                self.send_throttled(now);
+                while let Some(action) = self.timers.poll(now) {
+                    match action {
+                        HeartbeatAction::SendHeartbeat => {
+                            self.send_message(b"0", now, |_msg| {});
+                        }
+                        HeartbeatAction::SendTestRequest => {
+                            trace_event!(debug, "Counterparty is silent, sending TestRequest <1>");
+                            self.test_request_counter += 1;
+                            let test_req_id = self.test_request_counter.to_string();
+                            self.send_message(b"1", now, |msg| {
+                                msg.set(fix44::TEST_REQ_ID, test_req_id.as_str());
+                            });
+                            self.timers.on_test_request_sent(now);
+                        }
+                        HeartbeatAction::Disconnect => {
+                            self.logout_and_disconnect(errs::test_request_timeout(), now);
+                            return;
+                        }
+                    }
+                }
+            }
+            SessionState::LogoutSent => {
+                let logout_sent = self.logout_sent.unwrap_or(now);
+                if now.saturating_duration_since(logout_sent) >= self.timers.heartbeat() {
+                    self.disconnect(errs::logout_timeout());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Skips garbled inbound data until the next plausible message.
+    fn skip_garbled_data(&mut self) {
+        let skip = self
+            .inbound
+            .windows(2)
+            .skip(1)
+            .position(|window| window == b"8=")
+            .map(|i| i + 1)
+            .unwrap_or_else(|| self.inbound.len());
+        trace_event!(warn, skipped = skip, "Skipped garbled inbound data");
+        self.inbound.drain(..skip);
+        self.events.push_back(SessionEvent::Garbled);
+    }
+
+    fn on_frame(&mut self, frame: Vec<u8>, now: Instant) {
+        let inbound = match self.decoder.decode(&frame[..]) {
+            Ok(msg) => Inbound::new(&msg),
+            Err(_) => {
+                trace_event!(warn, "Ignored undecodable inbound message");
+                self.events.push_back(SessionEvent::Garbled);
+                return;
+            }
+        };
+        self.timers.on_received(now);
+        let msg_type = inbound.msg_type.as_slice();
+        trace_event!(
+            trace,
+            msg_type = %String::from_utf8_lossy(msg_type),
+            msg_seq_num = ?inbound.msg_seq_num,
+            "Received FIX message"
+        );
+        match self.state {
+            SessionState::Disconnected => return,
+            SessionState::AwaitingLogon | SessionState::LogonSent if msg_type != b"A" => {
+                self.disconnect(errs::first_message_not_logon());
+                return;
+            }
+            _ => {}
+        }
+        if inbound.begin_string != self.begin_string.as_bytes() {
+            self.logout_and_disconnect(errs::begin_string(&self.begin_string), now);
+            return;
+        }
+        if inbound.sender_comp_id.as_deref() != Some(self.target_comp_id.as_bytes())
+            || inbound.target_comp_id.as_deref() != Some(self.sender_comp_id.as_bytes())
+        {
+            self.send_reject(
+                &inbound,
+                None,
+                fix44::SessionRejectReason::CompidProblem,
+                errs::comp_id(),
+                now,
+            );
+            self.logout_and_disconnect(errs::comp_id(), now);
+            return;
+        }
+        if inbound.test_message_indicator
+            && self.config.verify_test_indicator()
+            && !self.environment.allows_testing()
+        {
+            self.logout_and_disconnect(errs::production_env(), now);
+            return;
+        }
+        let msg_seq_num = match inbound.msg_seq_num {
+            Some(msg_seq_num) => msg_seq_num,
+            None => {
+                self.logout_and_disconnect(
+                    errs::missing_field(
+                        fix44::MSG_SEQ_NUM.name(),
+                        fix44::MSG_SEQ_NUM.tag().get().into(),
+                    ),
+                    now,
+                );
+                return;
+            }
+        };
+        if msg_type == b"4" && !inbound.gap_fill_flag {
+            // SequenceReset-Reset ignores `MsgSeqNum <34>` altogether.
+            self.on_sequence_reset(&inbound, now);
+            return;
+        }
+        if msg_type == b"A" && inbound.reset_seq_num_flag {
+            if self.state == SessionState::AwaitingLogon {
+                self.seq_numbers.reset();
+            } else {
+                self.seq_numbers.next_inbound = 1;
+            }
+        }
+        if inbound.poss_dup_flag
+            && msg_type != b"4"
+            && !self.check_orig_sending_time(&inbound, msg_seq_num, now)
+        {
+            return;
+        }
+        match msg_seq_num.cmp(&self.seq_numbers.next_inbound()) {
+            Ordering::Equal => {}
+            Ordering::Less => {
+                if inbound.poss_dup_flag {
+                    // A retransmission of a message we already processed.
+                    trace_event!(debug, msg_seq_num, "Ignored duplicate inbound message");
+                } else {
+                    let text = errs::msg_seq_num(self.seq_numbers.next_inbound());
+                    self.logout_and_disconnect(text, now);
+                }
+                return;
+            }
+            Ordering::Greater => {
+                // Admin messages that must be processed even when out of
+                // sequence. Refer to specs. §4.8 for more information.
+                match msg_type {
+                    b"A" => self.on_logon(&inbound, now),
+                    b"2" => self.on_resend_request(&inbound, now),
+                    b"5" => {
+                        self.on_logout(now);
+                        return;
+                    }
+                    _ => {}
+                }
+                self.request_resend(msg_seq_num, now);
+                return;
+            }
+        }
+        self.seq_numbers.incr_inbound();
+        match msg_type {
+            b"A" => self.on_logon(&inbound, now),
+            b"0" => {}
+            b"3" => {
+                trace_event!(warn, msg_seq_num, "Received Reject <3>");
+            }
+            b"1" => {
+                let test_req_id = inbound.test_req_id.clone().unwrap_or_default();
+                self.send_message(b"0", now, |msg| {
+                    msg.set(fix44::TEST_REQ_ID, &test_req_id[..]);
+                });
+            }
+            b"2" => self.on_resend_request(&inbound, now),
+            b"4" => self.on_sequence_reset(&inbound, now),
+            b"5" => self.on_logout(now),
+            _ => self.events.push_back(SessionEvent::Application(frame)),
+        }
+        if let Some(resend_target) = self.resend_target {
+            if self.seq_numbers.next_inbound() > resend_target {
+                self.resend_target = None;
+            }
+        }
+    }
+
+    /// Validates `OrigSendingTime <122>` of a possibly duplicated message,
+    /// i.e. with `PossDupFlag <43>`. It must be present and no later than
+    /// `SendingTime <52>`. Returns `false` if the message must be dropped.
+    fn check_orig_sending_time(
+        &mut self,
+        inbound: &Inbound,
+        msg_seq_num: u64,
+        now: Instant,
+    ) -> bool {
+        match (&inbound.orig_sending_time, &inbound.sending_time) {
+            (None, _) => {
+                self.send_reject(
+                    inbound,
+                    Some(fix44::ORIG_SENDING_TIME.tag()),
+                    fix44::SessionRejectReason::RequiredTagMissing,
+                    errs::missing_field(
+                        fix44::ORIG_SENDING_TIME.name(),
+                        fix44::ORIG_SENDING_TIME.tag().get().into(),
+                    ),
+                    now,
+                );
+                // Rejected messages still consume their seq. number.
+                if msg_seq_num == self.seq_numbers.next_inbound() {
+                    self.seq_numbers.incr_inbound();
+                }
+                false
+            }
+            (Some(orig_sending_time), Some(sending_time)) if orig_sending_time > sending_time => {
+                self.send_reject(
+                    inbound,
+                    Some(fix44::ORIG_SENDING_TIME.tag()),
+                    fix44::SessionRejectReason::SendingtimeAccuracyProblem,
+                    errs::orig_sending_time(),
+                    now,
+                );
+                self.logout_and_disconnect(errs::orig_sending_time(), now);
+                false
+            }
+            _ => true,
+        }
+    }
+
+    fn on_logon(&mut self, inbound: &Inbound, now: Instant) {
+        match self.state {
+            SessionState::AwaitingLogon => {
+                let heartbeat = Duration::from_secs(inbound.heart_bt_int.unwrap_or(0));
+                if let Err(text) = self.heartbeat_rule.validate(&heartbeat) {
+                    trace_event!(warn, reason = %text, "Refusing Logon <A>");
+                    self.logout_and_disconnect(text, now);
+                    return;
+                }
+                trace_event!(
+                    info,
+                    heart_bt_int = heartbeat.as_secs(),
+                    "Accepting Logon <A>"
+                );
+                self.timers.set_heartbeat(heartbeat);
+                self.adopt_default_appl_ver_id(inbound);
+                let reset_seq_num_flag = inbound.reset_seq_num_flag;
+                let default_appl_ver_id = self.logon_appl_ver_id();
+                self.send_message(b"A", now, |msg| {
+                    msg.set(fix44::ENCRYPT_METHOD, fix44::EncryptMethod::None);
+                    msg.set(fix44::HEART_BT_INT, heartbeat.as_secs());
+                    if reset_seq_num_flag {
+                        msg.set(fix44::RESET_SEQ_NUM_FLAG, true);
+                    }
+                    if let Some(appl_ver_id) = default_appl_ver_id.as_deref() {
+                        msg.set_any(default_appl_ver_id_tag(), appl_ver_id);
+                    }
+                });
+            }
+            SessionState::LogonSent => {
+                trace_event!(info, "Logon <A> was acknowledged");
+                self.adopt_default_appl_ver_id(inbound);
+            }
+            _ => return,
+        }
+        self.set_state(SessionState::Active);
+        self.events.push_back(SessionEvent::LoggedOn);
+    }
+
+    /// Returns the `DefaultApplVerID <1137>` of outbound `Logon <A>` messages,
+    /// if any.
+    fn logon_appl_ver_id(&self) -> Option<String> {
+        if self.is_fixt() {
+            self.default_appl_ver_id.clone()
+        } else {
+            None
+        }
+    }
+
+    /// Decodes inbound messages without `ApplVerID <1128>` according to the
+    /// counterparty's `DefaultApplVerID <1137>`, if any.
+    fn adopt_default_appl_ver_id(&mut self, inbound: &Inbound) {
+        if !self.is_fixt() {
+            return;
+        }
+        let appl_ver_id = inbound
+            .default_appl_ver_id
+            .as_ref()
+            .and_then(|id| std::str::from_utf8(&id[..]).ok());
+        if let Some(appl_ver_id) = appl_ver_id {
+            trace_event!(debug, appl_ver_id, "Using DefaultApplVerID <1137>");
+            self.decoder.set_default_appl_ver_id(Some(appl_ver_id));
+            if self.default_appl_ver_id.is_none() {
+                self.default_appl_ver_id = Some(appl_ver_id.to_string());
+            }
+        }
+    }
+
+    fn on_resend_request(&mut self, inbound: &Inbound, now: Instant) {
+        let begin = inbound.begin_seq_no.unwrap_or(0);
+        let last_sent = self.seq_numbers.next_outbound() - 1;
+        let end = match inbound.end_seq_no {
+            // Zero means "infinity".
+            Some(end) if end != 0 && end < last_sent => end,
+            _ => last_sent,
+        };
+        if begin == 0 || begin > end {
+            return;
+        }
+        trace_event!(info, begin, end, "Counterparty requested a resend");
+        if self.resend_from_store {
+            self.events
+                .push_back(SessionEvent::ResendRequested { begin, end });
+            return;
+        }
+        // Taken before `SendingTime <52>`, which can't be earlier.
+        let orig_sending_time = Timestamp::from_utc(self.clock.utc_now());
+        self.encode(b"4", begin, now, |msg| {
+            msg.set(fix44::POSS_DUP_FLAG, true);
+            msg.set_timestamp(fix44::ORIG_SENDING_TIME, orig_sending_time);
+            msg.set(fix44::GAP_FILL_FLAG, true);
+            msg.set(fix44::NEW_SEQ_NO, end + 1);
+        });
+        self.events
+            .push_back(SessionEvent::GapFilled { begin, end });
+    }
+
+    fn on_sequence_reset(&mut self, inbound: &Inbound, now: Instant) {
+        let next_inbound = self.seq_numbers.next_inbound();
+        let result = inbound
+            .new_seq_no
+            .map(|new_seq_no| self.seq_numbers.sequence_reset(new_seq_no));
+        match result {
+            Some(Ok(())) => {}
+            _ => {
+                self.send_reject(
+                    inbound,
+                    Some(fix44::NEW_SEQ_NO.tag()),
+                    fix44::SessionRejectReason::ValueIsIncorrect,
+                    errs::new_seq_no(next_inbound),
+                    now,
+                );
+            }
+        }
+    }
+
+    fn on_logout(&mut self, now: Instant) {
+        if self.state != SessionState::LogoutSent {
+            self.send_logout(None, now);
+        }
+        self.disconnect("Logout".to_string());
+    }
+
+    fn request_resend(&mut self, msg_seq_num: u64, now: Instant) {
+        if self.resend_target.is_some() {
+            return;
+        }
+        let begin = self.seq_numbers.next_inbound();
+        trace_event!(
+            warn,
+            expected = begin,
+            received = msg_seq_num,
+            "Sequence gap detected, sending ResendRequest <2>"
+        );
+        self.send_message(b"2", now, |msg| {
+            msg.set(fix44::BEGIN_SEQ_NO, begin);
+            msg.set(fix44::END_SEQ_NO, 0u64);
+        });
+        self.resend_target = Some(msg_seq_num);
+    }
+
+    fn send_reject(
+        &mut self,
+        offender: &Inbound,
+        ref_tag: Option<TagU16>,
+        reason: fix44::SessionRejectReason,
+        text: String,
+        now: Instant,
+    ) {
+        trace_event!(
+            warn,
+            ref_seq_num = ?offender.msg_seq_num,
+            reason = ?reason,
+            text = %text,
+            "Sending Reject <3>"
+        );
+        let mut reject = Reject::new(offender.msg_seq_num.unwrap_or(0), reason);
+        if let Some(ref_tag) = ref_tag {
+            reject.set_ref_tag(ref_tag);
+        }
+        reject.set_ref_msg_type(&offender.msg_type[..]);
+        reject.set_text(text);
+        self.send_message(b"3", now, |msg| reject.encode(msg));
+    }
+
+    fn send_logout(&mut self, text: Option<&str>, now: Instant) {
+        self.send_message(b"5", now, |msg| {
+            if let Some(text) = text {
+                msg.set(fix44::TEXT, text);
+            }
+        });
+    }
+
+    fn logout_and_disconnect(&mut self, text: String, now: Instant) {
+        self.send_logout(Some(text.as_str()), now);
+        self.disconnect(text);
+    }
+
+    fn disconnect(&mut self, reason: String) {
+        trace_event!(info, reason = %reason, "Disconnecting");
+        self.set_state(SessionState::Disconnected);
+        self.events.push_back(SessionEvent::Disconnect { reason });
+    }
+
+    fn set_state(&mut self, state: SessionState) {
+        trace_event!(debug, from = ?self.state, to = ?state, "Session state changed");
+        self.state = state;
+    }
+
+    #[cfg(feature = "utils-tracing")]
+    fn span(&self) -> tracing::Span {
+        tracing::debug_span!(
+            "fix_session",
+            sender_comp_id = %self.sender_comp_id,
+            target_comp_id = %self.target_comp_id
+        )
+    }
+
+    /// Sends as many delayed application messages as the [`Throttle`]
+    /// allows.
+    fn send_throttled(&mut self, now: Instant) {
+        while !self.throttled.is_empty() {
+            let acquired = match self.throttle.as_mut() {
+                Some(throttle) => throttle.try_acquire(now).is_ok(),
+                None => false,
+            };
+            if !acquired {
+                return;
+            }
+            if let Some((msg_type, fields)) = self.throttled.pop_front() {
+                self.send_message(&msg_type[..], now, |msg| msg.raw(&fields[..]));
+            }
+        }
+    }
+
+    /// Encodes the fields added by `f`, i.e. everything after `MsgType <35>`
+    /// and before `CheckSum <10>`, to be sent later.
+    fn encode_fields<F>(&mut self, msg_type: &[u8], f: F) -> Vec<u8>
+    where
+        F: FnOnce(&mut EncoderHandle<Vec<u8>>),
+    {
+        let separator = self.encoder.config().separator();
+        self.outbound.clear();
+        let mut msg = self
+            .encoder
+            .start_message(b"", &mut self.outbound, msg_type);
+        f(&mut msg);
+        let bytes = msg.wrap();
+        // `BeginString <8>` and `BodyLength <9>` come first, then `MsgType
+        // <35>`.
+        let mut start = 0;
+        for _ in 0..3 {
+            start += bytes[start..]
+                .iter()
+                .position(|byte| *byte == separator)
+                .map_or(0, |i| i + 1);
+        }
+        let end = bytes.len() - b"10=000".len() - 1;
+        bytes[start..end].to_vec()
+    }
+
+    fn send_message<F>(&mut self, msg_type: &[u8], now: Instant, f: F)
+    where
+        F: FnOnce(&mut EncoderHandle<Vec<u8>>),
+    {
+        let msg_seq_num = self.seq_numbers.take_outbound();
+        self.encode(msg_type, msg_seq_num, now, f);
+    }
+
+    fn encode<F>(&mut self, msg_type: &[u8], msg_seq_num: u64, now: Instant, f: F)
+    where
+        F: FnOnce(&mut EncoderHandle<Vec<u8>>),
+    {
+        let sending_time = Timestamp::from_utc(self.clock.utc_now());
+        self.encode_at(msg_type, msg_seq_num, sending_time, now, f);
+    }
+
+    fn encode_at<F>(
+        &mut self,
+        msg_type: &[u8],
+        msg_seq_num: u64,
+        sending_time: Timestamp,
+        now: Instant,
+        f: F,
+    ) where
+        F: FnOnce(&mut EncoderHandle<Vec<u8>>),
+    {
+        self.outbound.clear();
+        let mut msg =
+            self.encoder
+                .start_message(self.begin_string.as_bytes(), &mut self.outbound, msg_type);
+        msg.set(fix44::SENDER_COMP_ID, self.sender_comp_id.as_str());
+        msg.set(fix44::TARGET_COMP_ID, self.target_comp_id.as_str());
+        msg.set(fix44::MSG_SEQ_NUM, msg_seq_num);
+        msg.set_timestamp(fix44::SENDING_TIME, sending_time);
+        f(&mut msg);
+        let bytes = msg.wrap().to_vec();
+        trace_event!(
+            trace,
+            msg_type = %String::from_utf8_lossy(msg_type),
+            msg_seq_num,
+            "Sent FIX message"
+        );
+        self.timers.on_sent(now);
+        self.events.push_back(SessionEvent::Outbound(bytes));
+    }
+}
+
+/// Owned information about an inbound message, as needed by the session
+/// layer.
+#[derive(Debug, Clone, Default)]
+struct Inbound {
+    begin_string: Vec<u8>,
+    msg_type: Vec<u8>,
+    msg_seq_num: Option<u64>,
+    sender_comp_id: Option<Vec<u8>>,
+    target_comp_id: Option<Vec<u8>>,
+    poss_dup_flag: bool,
+    sending_time: Option<Timestamp>,
+    orig_sending_time: Option<Timestamp>,
+    test_message_indicator: bool,
+    heart_bt_int: Option<u64>,
+    test_req_id: Option<Vec<u8>>,
+    begin_seq_no: Option<u64>,
+    end_seq_no: Option<u64>,
+    new_seq_no: Option<u64>,
+    gap_fill_flag: bool,
+    reset_seq_num_flag: bool,
+    default_appl_ver_id: Option<Vec<u8>>,
+}
+
+fn default_appl_ver_id_tag() -> TagU16 {
+    TagU16::new(DEFAULT_APPL_VER_ID).unwrap()
+}
+
+impl Inbound {
+    fn new(msg: &Message<&[u8]>) -> Self {
+        let raw = |field| msg.fv_raw(field).map(|value| value.to_vec());
+        Self {
+            begin_string: raw(fix44::BEGIN_STRING).unwrap_or_default(),
+            msg_type: raw(fix44::MSG_TYPE).unwrap_or_default(),
+            msg_seq_num: msg.fv(fix44::MSG_SEQ_NUM).ok(),
+            sender_comp_id: raw(fix44::SENDER_COMP_ID),
+            target_comp_id: raw(fix44::TARGET_COMP_ID),
+            poss_dup_flag: msg.fv(fix44::POSS_DUP_FLAG).unwrap_or(false),
+            sending_time: msg.fv(fix44::SENDING_TIME).ok(),
+            orig_sending_time: msg.fv(fix44::ORIG_SENDING_TIME).ok(),
+            test_message_indicator: msg.fv(fix44::TEST_MESSAGE_INDICATOR).unwrap_or(false),
+            heart_bt_int: msg.fv(fix44::HEART_BT_INT).ok(),
+            test_req_id: raw(fix44::TEST_REQ_ID),
+            begin_seq_no: msg.fv(fix44::BEGIN_SEQ_NO).ok(),
+            end_seq_no: msg.fv(fix44::END_SEQ_NO).ok(),
+            new_seq_no: msg.fv(fix44::NEW_SEQ_NO).ok(),
+            gap_fill_flag: msg.fv(fix44::GAP_FILL_FLAG).unwrap_or(false),
+            reset_seq_num_flag: msg.fv(fix44::RESET_SEQ_NUM_FLAG).unwrap_or(false),
+            default_appl_ver_id: msg
+                .fv_raw_by_tag(default_appl_ver_id_tag())
+                .map(|value| value.to_vec()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tagvalue::Config as TagValueConfig;
+
+    fn initiator() -> Session {
+        let mut builder = SessionBuilder::default();
+        builder.set_sender_comp_id("INITIATOR");
+        builder.set_target_comp_id("ACCEPTOR");
+        builder.set_heartbeat(Duration::from_secs(30));
+        builder.build()
+    }
+
+    fn acceptor() -> Session {
+        let mut builder = SessionBuilder::default();
+        builder.set_sender_comp_id("ACCEPTOR");
+        builder.set_target_comp_id("INITIATOR");
+        builder.build()
+    }
+
+    /// Delivers all outbound messages from `from` to `to`, and returns all
+    /// other events of `from`.
+    fn deliver(from: &mut Session, to: &mut Session, now: Instant) -> Vec<SessionEvent> {
+        let mut events = Vec::new();
+        while let Some(event) = from.next_event() {
+            match event {
+                SessionEvent::Outbound(bytes) => to.feed(&bytes[..], now),
+                event => events.push(event),
+            }
+        }
+        events
+    }
+
+    fn outbound_messages(session: &mut Session) -> Vec<Vec<u8>> {
+        let mut messages = Vec::new();
+        while let Some(event) = session.next_event() {
+            if let SessionEvent::Outbound(bytes) = event {
+                messages.push(bytes);
+            }
+        }
+        messages
+    }
+
+    fn msg_type_of(bytes: &[u8]) -> Vec<u8> {
+        let mut decoder = Decoder::<TagValueConfig>::new(Dictionary::fix44());
+        let msg = decoder.decode(bytes).unwrap();
+        msg.fv_raw(fix44::MSG_TYPE).unwrap().to_vec()
+    }
+
+    fn logged_on(now: Instant) -> (Session, Session) {
+        let mut initiator = initiator();
+        let mut acceptor = acceptor();
+        initiator.logon(now);
+        deliver(&mut initiator, &mut acceptor, now);
+        deliver(&mut acceptor, &mut initiator, now);
+        (initiator, acceptor)
+    }
+
+    #[test]
+    fn seq_numbers_survive_restarts() {
+        let now = Instant::now();
+        let mut store = crate::session::InMemoryStore::new();
+        let (mut initiator, _acceptor) = logged_on(now);
+        assert!(initiator.persist_seq_numbers(&mut store).unwrap());
+        assert!(!initiator.persist_seq_numbers(&mut store).unwrap());
+        assert_eq!(store.seq_numbers(), initiator.seq_numbers());
+        let mut builder: SessionBuilder = SessionBuilder::default();
+        builder.restore_seq_numbers(&store);
+        let mut restarted = builder.build();
+        assert_eq!(restarted.seq_numbers().next_outbound(), 2);
+        assert!(!restarted.persist_seq_numbers(&mut store).unwrap());
+    }
+
+    #[test]
+    fn throttled_messages_are_delayed_in_order() {
+        let now = Instant::now();
+        let mut builder = SessionBuilder::default();
+        builder.set_sender_comp_id("INITIATOR");
+        builder.set_target_comp_id("ACCEPTOR");
+        let mut throttle = Throttle::new(10, Duration::from_secs(1));
+        throttle.set_burst(1);
+        builder.set_throttle(Some(throttle));
+        let mut initiator = builder.build();
+        let mut acceptor = acceptor();
+        initiator.logon(now);
+        deliver(&mut initiator, &mut acceptor, now);
+        deliver(&mut acceptor, &mut initiator, now);
+        assert_eq!(initiator.next_event(), Some(SessionEvent::LoggedOn));
+        for cl_ord_id in ["1", "2", "3"] {
+            initiator
+                .send(b"D", now, |msg| msg.set(fix44::CL_ORD_ID, cl_ord_id))
+                .unwrap();
+        }
+        assert_eq!(initiator.throttled_len(), 2);
+        let events = deliver(&mut initiator, &mut acceptor, now);
+        assert_eq!(
+            events,
+            vec![
+                SessionEvent::Throttled {
+                    queued: 1,
+                    retry_after: Duration::from_millis(100)
+                },
+                SessionEvent::Throttled {
+                    queued: 2,
+                    retry_after: Duration::from_millis(100)
+                },
+            ]
+        );
+        initiator.tick(now + Duration::from_millis(50));
+        assert!(initiator.next_event().is_none());
+        initiator.tick(now + Duration::from_millis(200));
+        assert_eq!(initiator.throttled_len(), 1);
+        initiator.tick(now + Duration::from_millis(300));
+        assert_eq!(initiator.throttled_len(), 0);
+        deliver(&mut initiator, &mut acceptor, now);
+        let mut decoder = Decoder::<TagValueConfig>::new(Dictionary::fix44());
+        let mut cl_ord_ids = vec![];
+        while let Some(event) = acceptor.next_event() {
+            if let SessionEvent::Application(bytes) = event {
+                let msg = decoder.decode(&bytes[..]).unwrap();
+                let cl_ord_id: &str = msg.fv(fix44::CL_ORD_ID).unwrap();
+                cl_ord_ids.push(cl_ord_id.to_string());
+                assert_eq!(msg.fv_raw(fix44::SENDER_COMP_ID), Some(&b"INITIATOR"[..]));
+            }
+        }
+        assert_eq!(cl_ord_ids, vec!["1", "2", "3"]);
+        assert_eq!(acceptor.seq_numbers().next_inbound(), 5);
+    }
+
+    #[test]
+    fn logon_exchange() {
+        let now = Instant::now();
+        let mut initiator = initiator();
+        let mut acceptor = acceptor();
+        initiator.logon(now);
+        assert_eq!(initiator.state(), SessionState::LogonSent);
+        let events = deliver(&mut initiator, &mut acceptor, now);
+        assert!(events.is_empty());
+        assert_eq!(acceptor.state(), SessionState::Active);
+        let events = deliver(&mut acceptor, &mut initiator, now);
+        assert_eq!(events, vec![SessionEvent::LoggedOn]);
+        assert_eq!(initiator.state(), SessionState::Active);
+        assert_eq!(initiator.seq_numbers().next_inbound(), 2);
+        assert_eq!(initiator.seq_numbers().next_outbound(), 2);
+    }
+
+    #[test]
+    fn first_message_must_be_logon() {
+        let now = Instant::now();
+        let (mut initiator, _) = logged_on(now);
+        let mut acceptor = acceptor();
+        initiator.send(b"D", now, |_msg| {}).unwrap();
+        deliver(&mut initiator, &mut acceptor, now);
+        assert_eq!(acceptor.state(), SessionState::Disconnected);
+    }
+
+    #[test]
+    fn application_messages_are_forwarded() {
+        let now = Instant::now();
+        let (mut initiator, mut acceptor) = logged_on(now);
+        initiator
+            .send(b"D", now, |msg| msg.set(fix44::CL_ORD_ID, "FOOBAR"))
+            .unwrap();
+        deliver(&mut initiator, &mut acceptor, now);
+        match acceptor.next_event() {
+            Some(SessionEvent::Application(bytes)) => assert_eq!(msg_type_of(&bytes), b"D"),
+            event => panic!("Unexpected event {:?}", event),
+        }
+    }
+
+    #[test]
+    fn application_messages_before_logon_are_refused() {
+        let now = Instant::now();
+        let mut initiator = initiator();
+        assert_eq!(
+            initiator.send(b"D", now, |_msg| {}),
+            Err(SessionState::AwaitingLogon)
+        );
+    }
+
+    #[test]
+    fn sending_time_comes_from_the_clock() {
+        use crate::session::ManualClock;
+        use chrono::{TimeZone, Utc};
+
+        let clock = ManualClock::new(Utc.ymd(2021, 6, 1).and_hms(9, 0, 0));
+        let mut builder = SessionBuilder::<Config>::default();
+        builder.set_clock(clock.clone());
+        let mut session = builder.build();
+        clock.advance(Duration::from_millis(1500));
+        session.logon(session.clock().now());
+        let messages = outbound_messages(&mut session);
+        let mut decoder = Decoder::<TagValueConfig>::new(Dictionary::fix44());
+        decoder.config_mut().set_separator(0x1);
+        let msg = decoder.decode(&messages[0][..]).unwrap();
+        assert_eq!(
+            msg.fv_raw(fix44::SENDING_TIME),
+            Some(&b"20210601-09:00:01.500"[..])
+        );
+    }
+
+    #[test]
+    fn sending_time_precision_is_configurable() {
+        use crate::session::ManualClock;
+        use chrono::{TimeZone, Utc};
+
+        let clock = ManualClock::new(Utc.ymd(2021, 6, 1).and_hms(9, 0, 0));
+        let mut builder = SessionBuilder::<Config>::default();
+        builder.set_clock(clock.clone());
+        builder.set_timestamp_precision(TimePrecision::Micros);
+        let mut session = builder.build();
+        clock.advance(Duration::from_micros(1_500_250));
+        session.logon(session.clock().now());
+        let messages = outbound_messages(&mut session);
+        let mut decoder = Decoder::<TagValueConfig>::new(Dictionary::fix44());
+        decoder.config_mut().set_separator(0x1);
+        let msg = decoder.decode(&messages[0][..]).unwrap();
+        assert_eq!(
+            msg.fv_raw(fix44::SENDING_TIME),
+            Some(&b"20210601-09:00:01.500250"[..])
+        );
+    }
+
+    #[test]
+    fn heartbeat_is_sent_when_due() {
+        let now = Instant::now();
+        let (mut initiator, _) = logged_on(now);
+        initiator.tick(now + Duration::from_secs(10));
+        assert!(outbound_messages(&mut initiator).is_empty());
+        initiator.tick(now + Duration::from_secs(30));
+        let messages = outbound_messages(&mut initiator);
+        assert_eq!(messages.len(), 1);
+        assert_eq!(msg_type_of(&messages[0]), b"0");
+    }
+
+    #[test]
+    fn test_request_and_disconnect_on_silence() {
+        let now = Instant::now();
+        let (mut initiator, _) = logged_on(now);
+        initiator.tick(now + Duration::from_secs(36));
+        let messages = outbound_messages(&mut initiator);
+        assert!(messages.iter().any(|msg| msg_type_of(msg) == b"1"));
+        initiator.tick(now + Duration::from_secs(66));
+        assert_eq!(initiator.state(), SessionState::Disconnected);
+    }
+
+    #[test]
+    fn test_request_is_answered_with_heartbeat() {
+        let now = Instant::now();
+        let (mut initiator, mut acceptor) = logged_on(now);
+        initiator.tick(now + Duration::from_secs(36));
+        deliver(&mut initiator, &mut acceptor, now + Duration::from_secs(36));
+        let messages = outbound_messages(&mut acceptor);
+        assert_eq!(messages.len(), 1);
+        assert_eq!(msg_type_of(&messages[0]), b"0");
+        initiator.feed(&messages[0][..], now + Duration::from_secs(37));
+        initiator.tick(now + Duration::from_secs(66));
+        assert_eq!(initiator.state(), SessionState::Active);
+    }
+
+    #[test]
+    fn high_seq_num_triggers_resend_request_and_gap_fill() {
+        let now = Instant::now();
+        let (mut initiator, mut acceptor) = logged_on(now);
+        initiator.send(b"D", now, |_msg| {}).unwrap();
+        // The first application message is lost.
+        outbound_messages(&mut initiator);
+        initiator.send(b"D", now, |_msg| {}).unwrap();
+        deliver(&mut initiator, &mut acceptor, now);
+        let messages = outbound_messages(&mut acceptor);
+        assert_eq!(messages.len(), 1);
+        assert_eq!(msg_type_of(&messages[0]), b"2");
+        initiator.feed(&messages[0][..], now);
+        let mut gap_fill = None;
+        while let Some(event) = initiator.next_event() {
+            match event {
+                SessionEvent::Outbound(bytes) => gap_fill = Some(bytes),
+                SessionEvent::GapFilled { begin, end } => assert_eq!((begin, end), (2, 3)),
+                event => panic!("Unexpected event {:?}", event),
+            }
+        }
+        let gap_fill = gap_fill.unwrap();
+        assert_eq!(msg_type_of(&gap_fill), b"4");
+        acceptor.feed(&gap_fill[..], now);
+        assert_eq!(acceptor.seq_numbers().next_inbound(), 4);
+        assert_eq!(acceptor.state(), SessionState::Active);
+    }
+
+    #[test]
+    fn resend_requests_are_answered_from_store() {
+        use crate::session::InMemoryStore;
+        // Saves all outbound messages of `session` to `store`.
+        fn store_outbound(session: &mut Session, store: &mut InMemoryStore) -> Vec<Vec<u8>> {
+            let messages = outbound_messages(session);
+            for bytes in messages.iter() {
+                let mut decoder = Decoder::<TagValueConfig>::new(Dictionary::fix44());
+                let msg = decoder.decode(&bytes[..]).unwrap();
+                store
+                    .store(msg.fv(fix44::MSG_SEQ_NUM).unwrap(), &bytes[..])
+                    .unwrap();
+            }
+            messages
+        }
+        let now = Instant::now();
+        let mut store = InMemoryStore::new();
+        let mut initiator = {
+            let mut builder = SessionBuilder::default();
+            builder.set_sender_comp_id("INITIATOR");
+            builder.set_target_comp_id("ACCEPTOR");
+            builder.set_resend_from_store(true);
+            builder.build()
+        };
+        let mut acceptor = acceptor();
+        initiator.logon(now);
+        for bytes in store_outbound(&mut initiator, &mut store) {
+            acceptor.feed(&bytes[..], now);
+        }
+        deliver(&mut acceptor, &mut initiator, now);
+        initiator.send(b"D", now, |_msg| {}).unwrap();
+        // The first application message is lost.
+        store_outbound(&mut initiator, &mut store);
+        initiator.send(b"D", now, |_msg| {}).unwrap();
+        for bytes in store_outbound(&mut initiator, &mut store) {
+            acceptor.feed(&bytes[..], now);
+        }
+        deliver(&mut acceptor, &mut initiator, now);
+        assert_eq!(
+            initiator.next_event(),
+            Some(SessionEvent::ResendRequested { begin: 2, end: 3 })
+        );
+        initiator.resend(&mut store, 2, 3, now).unwrap();
+        deliver(&mut initiator, &mut acceptor, now);
+        let mut num_app_messages = 0;
+        while let Some(event) = acceptor.next_event() {
+            if let SessionEvent::Application(bytes) = event {
+                let mut decoder = Decoder::<TagValueConfig>::new(Dictionary::fix44());
+                let msg = decoder.decode(&bytes[..]).unwrap();
+                assert_eq!(msg.fv(fix44::POSS_DUP_FLAG), Ok(true));
+                num_app_messages += 1;
+            }
+        }
+        assert_eq!(num_app_messages, 2);
+        assert_eq!(acceptor.seq_numbers().next_inbound(), 4);
+    }
+
+    #[test]
+    fn low_seq_num_without_poss_dup_disconnects() {
+        let now = Instant::now();
+        let (mut initiator, mut acceptor) = logged_on(now);
+        initiator.send(b"D", now, |_msg| {}).unwrap();
+        let messages = outbound_messages(&mut initiator);
+        acceptor.feed(&messages[0][..], now);
+        acceptor.feed(&messages[0][..], now);
+        assert_eq!(acceptor.state(), SessionState::Disconnected);
+    }
+
+    #[test]
+    fn poss_dup_without_orig_sending_time_is_rejected() {
+        let now = Instant::now();
+        let (mut initiator, mut acceptor) = logged_on(now);
+        initiator
+            .send(b"D", now, |msg| msg.set(fix44::POSS_DUP_FLAG, true))
+            .unwrap();
+        deliver(&mut initiator, &mut acceptor, now);
+        let messages = outbound_messages(&mut acceptor);
+        assert_eq!(messages.len(), 1);
+        assert_eq!(msg_type_of(&messages[0]), b"3");
+        assert_eq!(acceptor.seq_numbers().next_inbound(), 3);
+        assert_eq!(acceptor.state(), SessionState::Active);
+    }
+
+    #[test]
+    fn poss_dup_with_late_orig_sending_time_disconnects() {
+        let now = Instant::now();
+        let (mut initiator, mut acceptor) = logged_on(now);
+        initiator
+            .send(b"D", now, |msg| {
+                msg.set(fix44::POSS_DUP_FLAG, true);
+                msg.set(fix44::ORIG_SENDING_TIME, "29991231-00:00:00.000");
+            })
+            .unwrap();
+        deliver(&mut initiator, &mut acceptor, now);
+        let messages = outbound_messages(&mut acceptor);
+        assert_eq!(msg_type_of(&messages[0]), b"3");
+        assert_eq!(msg_type_of(&messages[1]), b"5");
+        assert_eq!(acceptor.state(), SessionState::Disconnected);
+    }
+
+    #[test]
+    fn duplicates_of_processed_messages_are_ignored() {
+        use crate::session::{InMemoryStore, ResendResponder};
+
+        let now = Instant::now();
+        let (mut initiator, mut acceptor) = logged_on(now);
+        initiator.send(b"D", now, |_msg| {}).unwrap();
+        let messages = outbound_messages(&mut initiator);
+        acceptor.feed(&messages[0][..], now);
+        assert!(matches!(
+            acceptor.next_event(),
+            Some(SessionEvent::Application(_))
+        ));
+        let mut store = InMemoryStore::new();
+        store.store(2, &messages[0][..]).unwrap();
+        let mut responder = ResendResponder::new("FIX.4.4", "INITIATOR", "ACCEPTOR");
+        let resent = responder
+            .respond(&mut store, 2, 2, Timestamp::utc_now())
+            .unwrap();
+        acceptor.feed(&resent[0][..], now);
+        assert_eq!(acceptor.next_event(), None);
+        assert_eq!(acceptor.state(), SessionState::Active);
+        assert_eq!(acceptor.seq_numbers().next_inbound(), 3);
+    }
+
+    #[test]
+    fn gap_fills_carry_orig_sending_time() {
+        let now = Instant::now();
+        let (mut initiator, mut acceptor) = logged_on(now);
+        initiator.send(b"D", now, |_msg| {}).unwrap();
+        outbound_messages(&mut initiator);
+        initiator.send(b"D", now, |_msg| {}).unwrap();
+        deliver(&mut initiator, &mut acceptor, now);
+        let resend_request = outbound_messages(&mut acceptor);
+        initiator.feed(&resend_request[0][..], now);
+        let gap_fill = outbound_messages(&mut initiator);
+        let mut decoder = Decoder::<TagValueConfig>::new(Dictionary::fix44());
+        let msg = decoder.decode(&gap_fill[0][..]).unwrap();
+        assert_eq!(msg.fv(fix44::POSS_DUP_FLAG), Ok(true));
+        let orig_sending_time: Timestamp = msg.fv(fix44::ORIG_SENDING_TIME).unwrap();
+        let sending_time: Timestamp = msg.fv(fix44::SENDING_TIME).unwrap();
+        assert!(orig_sending_time <= sending_time);
+    }
+
+    #[test]
+    fn logout_exchange() {
+        let now = Instant::now();
+        let (mut initiator, mut acceptor) = logged_on(now);
+        initiator.logout(Some("Bye"), now);
+        assert_eq!(initiator.state(), SessionState::LogoutSent);
+        deliver(&mut initiator, &mut acceptor, now);
+        assert_eq!(acceptor.state(), SessionState::Disconnected);
+        deliver(&mut acceptor, &mut initiator, now);
+        assert_eq!(initiator.state(), SessionState::Disconnected);
+    }
+
+    #[test]
+    fn garbled_data_is_skipped() {
+        let now = Instant::now();
+        let (mut initiator, mut acceptor) = logged_on(now);
+        initiator.send(b"D", now, |_msg| {}).unwrap();
+        let mut data = b"garbage".to_vec();
+        data.extend_from_slice(&outbound_messages(&mut initiator)[0][..]);
+        acceptor.feed(&data[..], now);
+        assert_eq!(acceptor.next_event(), Some(SessionEvent::Garbled));
+        assert!(matches!(
+            acceptor.next_event(),
+            Some(SessionEvent::Application(_))
+        ));
+    }
+
+    #[test]
+    fn fixt_sessions_agree_on_default_appl_ver_id() {
+        let now = Instant::now();
+        let mut venue_dict = Dictionary::fix44().to_builder();
+        venue_dict
+            .add_custom_field(5001, "VenueDataLen", crate::dict::FixDatatype::Length)
+            .unwrap();
+        venue_dict
+            .add_custom_field(5002, "VenueData", crate::dict::FixDatatype::Data)
+            .unwrap();
+        let mut builder = SessionBuilder::default();
+        builder.set_begin_string("FIXT.1.1");
+        builder.set_sender_comp_id("INITIATOR");
+        builder.set_target_comp_id("ACCEPTOR");
+        builder.set_default_appl_ver_id(Some("9".to_string()));
+        let mut initiator = builder.build();
+        let mut builder = SessionBuilder::default();
+        builder.set_begin_string("FIXT.1.1");
+        builder.set_sender_comp_id("ACCEPTOR");
+        builder.set_target_comp_id("INITIATOR");
+        builder.add_appl_ver_dictionary("9", venue_dict.build());
+        let mut acceptor = builder.build();
+        assert_eq!(acceptor.default_appl_ver_id(), None);
+        initiator.logon(now);
+        deliver(&mut initiator, &mut acceptor, now);
+        assert_eq!(
+            deliver(&mut acceptor, &mut initiator, now),
+            vec![SessionEvent::LoggedOn]
+        );
+        assert_eq!(acceptor.default_appl_ver_id(), Some("9"));
+        assert_eq!(initiator.default_appl_ver_id(), Some("9"));
+        initiator
+            .send(b"B", now, |msg| {
+                msg.set_any(TagU16::new(5001).unwrap(), 3u32);
+                msg.set_any(TagU16::new(5002).unwrap(), &b"a\x01b"[..]);
+            })
+            .unwrap();
+        deliver(&mut initiator, &mut acceptor, now);
+        // A FIX 4.4 decoder would choke on the data field.
+        assert!(matches!(
+            acceptor.next_event(),
+            Some(SessionEvent::Application(_))
+        ));
+    }
+}
@@ -0,0 +1,403 @@
+//! A scriptable mock counterparty, for acceptance tests of FIX engines.
+//!
+//! [`MockCounterparty`] is a regular [`Session`] (usually an acceptor) whose
+//! outbound messages go through a script of [`Fault`]s before they're
+//! delivered to the engine under test: messages can be dropped (which creates
+//! sequence gaps), duplicated, delayed (e.g. slow heartbeats), or sent with an
+//! incorrect checksum. Like [`Session`], it performs no I/O on its own, so it
+//! works with any transport.
+//!
+//! # Examples
+//!
+//! ```
+//! use fefix::session::testkit::{exchange, Fault, MockCounterparty};
+//! use fefix::session::{SessionBuilder, SessionState};
+//! use std::time::Instant;
+//!
+//! let mut engine: SessionBuilder = SessionBuilder::default();
+//! engine.set_sender_comp_id("ENGINE");
+//! engine.set_target_comp_id("MOCK");
+//! let mut engine = engine.build();
+//! let mut mock = SessionBuilder::default();
+//! mock.set_sender_comp_id("MOCK");
+//! mock.set_target_comp_id("ENGINE");
+//! let mut mock = MockCounterparty::new(mock.build());
+//!
+//! let now = Instant::now();
+//! engine.logon(now);
+//! exchange(&mut engine, &mut mock, now);
+//! assert_eq!(engine.state(), SessionState::Active);
+//!
+//! // The next message from the mock never reaches the engine.
+//! mock.inject(Fault::Drop);
+//! mock.send(b"B", now, |_| {}).unwrap();
+//! mock.send(b"B", now, |_| {}).unwrap();
+//! exchange(&mut engine, &mut mock, now);
+//! // Logon <A>, then ResendRequest <2>.
+//! assert_eq!(mock.received_msg_types(), vec![b"A".to_vec(), b"2".to_vec()]);
+//! ```
+
+use super::{Session, SessionEvent, SessionState};
+use crate::tagvalue::EncoderHandle;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// A misbehavior of [`MockCounterparty`], applied to one of its outbound
+/// messages.
+///
+/// Please note that [`Fault`] is marked with `#[non_exhaustive]`, which
+/// future-proofs the enumeration type in case more variants are added.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Fault {
+    /// The message is never delivered, so that the engine under test detects
+    /// a sequence gap with the next one.
+    Drop,
+    /// The message is delivered twice in a row, without `PossDupFlag <43>`.
+    Duplicate,
+    /// The message is delivered with an incorrect `CheckSum <10>`.
+    CorruptChecksum,
+    /// The message (and all the following ones, so that order is preserved)
+    /// is delivered after the given delay, e.g. to simulate slow heartbeats.
+    Delay(Duration),
+}
+
+#[derive(Debug, Clone)]
+struct Rule {
+    msg_type: Option<Vec<u8>>,
+    fault: Fault,
+    // The number of messages that are still affected, or `None` for all of
+    // them.
+    remaining: Option<usize>,
+}
+
+/// A mock counterparty that wraps a [`Session`] and injects [`Fault`]s into
+/// its outbound messages. See the [module-level documentation](self).
+#[derive(Debug)]
+pub struct MockCounterparty {
+    session: Session,
+    rules: Vec<Rule>,
+    outbound: VecDeque<(Instant, Vec<u8>)>,
+    events: VecDeque<SessionEvent>,
+    received: Vec<Vec<u8>>,
+}
+
+impl MockCounterparty {
+    /// Creates a new [`MockCounterparty`] on top of `session`, without any
+    /// [`Fault`]s.
+    pub fn new(session: Session) -> Self {
+        Self {
+            session,
+            rules: Vec::new(),
+            outbound: VecDeque::new(),
+            events: VecDeque::new(),
+            received: Vec::new(),
+        }
+    }
+
+    /// Returns the underlying [`Session`].
+    pub fn session(&self) -> &Session {
+        &self.session
+    }
+
+    /// Returns the underlying [`Session`], mutably.
+    pub fn session_mut(&mut self) -> &mut Session {
+        &mut self.session
+    }
+
+    /// Returns the [`SessionState`] of the underlying [`Session`].
+    pub fn state(&self) -> SessionState {
+        self.session.state()
+    }
+
+    /// Applies `fault` to the next outbound message, whatever its type.
+    pub fn inject(&mut self, fault: Fault) {
+        self.rules.push(Rule {
+            msg_type: None,
+            fault,
+            remaining: Some(1),
+        });
+    }
+
+    /// Applies `fault` to the next `times` outbound messages of type
+    /// `msg_type`, or to all of them if `times` is [`None`]. E.g.
+    /// `inject_for(b"0", Fault::Delay(delay), None)` slows down all
+    /// heartbeats.
+    ///
+    /// When several [`Fault`]s apply to the same message, the oldest wins.
+    pub fn inject_for(&mut self, msg_type: &[u8], fault: Fault, times: Option<usize>) {
+        self.rules.push(Rule {
+            msg_type: Some(msg_type.to_vec()),
+            fault,
+            remaining: times,
+        });
+    }
+
+    /// Removes all pending [`Fault`]s.
+    pub fn clear_faults(&mut self) {
+        self.rules.clear();
+    }
+
+    /// Feeds `data` from the engine under test to the underlying [`Session`].
+    pub fn feed(&mut self, data: &[u8], now: Instant) {
+        self.received.push(data.to_vec());
+        self.session.feed(data, now);
+        self.drain(now);
+    }
+
+    /// Checks the timers of the underlying [`Session`]. See [`Session::tick`].
+    pub fn tick(&mut self, now: Instant) {
+        self.session.tick(now);
+        self.drain(now);
+    }
+
+    /// Sends an application message via the underlying [`Session`]. See
+    /// [`Session::send`].
+    pub fn send<F>(&mut self, msg_type: &[u8], now: Instant, f: F) -> Result<(), SessionState>
+    where
+        F: FnOnce(&mut EncoderHandle<Vec<u8>>),
+    {
+        self.session.send(msg_type, now, f)?;
+        self.drain(now);
+        Ok(())
+    }
+
+    /// Queues `frame` for delivery as-is, bypassing both the underlying
+    /// [`Session`] and all [`Fault`]s, e.g. to send garbage or messages with
+    /// arbitrary seq. numbers.
+    pub fn send_raw(&mut self, frame: &[u8], now: Instant) {
+        let ready_at = self.ready_at(now);
+        self.outbound.push_back((ready_at, frame.to_vec()));
+    }
+
+    /// Returns the next frame that must be delivered to the engine under test
+    /// at `now`, if any.
+    pub fn poll_outbound(&mut self, now: Instant) -> Option<Vec<u8>> {
+        match self.outbound.front() {
+            Some((ready_at, _)) if *ready_at <= now => {
+                self.outbound.pop_front().map(|(_, frame)| frame)
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns when the next delayed frame becomes ready, if any.
+    pub fn next_delivery(&self) -> Option<Instant> {
+        self.outbound.front().map(|(ready_at, _)| *ready_at)
+    }
+
+    /// Returns the next event of the underlying [`Session`], other than
+    /// [`SessionEvent::Outbound`]. See [`Session::next_event`].
+    pub fn next_event(&mut self) -> Option<SessionEvent> {
+        self.events.pop_front()
+    }
+
+    /// Returns all frames received from the engine under test since the last
+    /// call to [`MockCounterparty::received_msg_types`], in order.
+    pub fn received(&self) -> &[Vec<u8>] {
+        &self.received[..]
+    }
+
+    /// Returns the `MsgType <35>` of all frames received from the engine
+    /// under test since the last call, in order.
+    pub fn received_msg_types(&mut self) -> Vec<Vec<u8>> {
+        self.received
+            .drain(..)
+            .map(|frame| msg_type(&frame[..]).to_vec())
+            .collect()
+    }
+
+    fn drain(&mut self, now: Instant) {
+        while let Some(event) = self.session.next_event() {
+            match event {
+                SessionEvent::Outbound(frame) => self.deliver(frame, now),
+                event => self.events.push_back(event),
+            }
+        }
+    }
+
+    fn deliver(&mut self, mut frame: Vec<u8>, now: Instant) {
+        let mut ready_at = self.ready_at(now);
+        match self.take_fault(msg_type(&frame[..])) {
+            None => {}
+            Some(Fault::Drop) => {
+                trace_event!(debug, "Dropping outbound message");
+                return;
+            }
+            Some(Fault::Duplicate) => self.outbound.push_back((ready_at, frame.clone())),
+            Some(Fault::CorruptChecksum) => corrupt_checksum(&mut frame[..]),
+            Some(Fault::Delay(delay)) => ready_at = ready_at.max(now + delay),
+        }
+        self.outbound.push_back((ready_at, frame));
+    }
+
+    /// Frames are never delivered out of order, so no frame can be ready
+    /// before the last one.
+    fn ready_at(&self, now: Instant) -> Instant {
+        match self.outbound.back() {
+            Some((ready_at, _)) => now.max(*ready_at),
+            None => now,
+        }
+    }
+
+    fn take_fault(&mut self, msg_type: &[u8]) -> Option<Fault> {
+        let i = self.rules.iter().position(|rule| match &rule.msg_type {
+            Some(rule_msg_type) => rule_msg_type == msg_type,
+            None => true,
+        })?;
+        let rule = &mut self.rules[i];
+        let fault = rule.fault;
+        if let Some(remaining) = rule.remaining.as_mut() {
+            *remaining -= 1;
+            if *remaining == 0 {
+                self.rules.remove(i);
+            }
+        }
+        Some(fault)
+    }
+}
+
+/// Shuttles frames between `engine` and `mock` until neither has anything
+/// left to deliver at `now`, and returns all other events of `engine`, in
+/// order. Frames that `mock` delays past `now` are not delivered.
+pub fn exchange(
+    engine: &mut Session,
+    mock: &mut MockCounterparty,
+    now: Instant,
+) -> Vec<SessionEvent> {
+    let mut events = Vec::new();
+    loop {
+        let mut idle = true;
+        while let Some(event) = engine.next_event() {
+            match event {
+                SessionEvent::Outbound(frame) => {
+                    idle = false;
+                    mock.feed(&frame[..], now);
+                }
+                event => events.push(event),
+            }
+        }
+        while let Some(frame) = mock.poll_outbound(now) {
+            idle = false;
+            engine.feed(&frame[..], now);
+        }
+        if idle {
+            return events;
+        }
+    }
+}
+
+fn msg_type(frame: &[u8]) -> &[u8] {
+    let start = frame
+        .windows(4)
+        .position(|window| window == b"\x0135=")
+        .map(|i| i + 4)
+        .unwrap_or(frame.len());
+    let len = frame[start..]
+        .iter()
+        .position(|byte| *byte == b'\x01')
+        .unwrap_or(frame.len() - start);
+    &frame[start..start + len]
+}
+
+/// Replaces `CheckSum <10>` at the end of `frame` with a different, but
+/// well-formed, value.
+fn corrupt_checksum(frame: &mut [u8]) {
+    if frame.len() < 7 || &frame[frame.len() - 7..frame.len() - 4] != b"10=" {
+        return;
+    }
+    let digits = frame.len() - 4..frame.len() - 1;
+    let checksum = std::str::from_utf8(&frame[digits.clone()])
+        .ok()
+        .and_then(|checksum| checksum.parse::<u16>().ok())
+        .unwrap_or(0);
+    let corrupted = format!("{:03}", (checksum + 1) % 256);
+    frame[digits].copy_from_slice(corrupted.as_bytes());
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::session::SessionBuilder;
+
+    fn logged_on(now: Instant) -> (Session, MockCounterparty) {
+        let mut engine = SessionBuilder::default();
+        engine.set_sender_comp_id("ENGINE");
+        engine.set_target_comp_id("MOCK");
+        let mut engine = engine.build();
+        let mut mock = SessionBuilder::default();
+        mock.set_sender_comp_id("MOCK");
+        mock.set_target_comp_id("ENGINE");
+        let mut mock = MockCounterparty::new(mock.build());
+        engine.logon(now);
+        assert_eq!(
+            exchange(&mut engine, &mut mock, now),
+            vec![SessionEvent::LoggedOn]
+        );
+        mock.received_msg_types();
+        (engine, mock)
+    }
+
+    #[test]
+    fn dropped_messages_trigger_resend_requests() {
+        let now = Instant::now();
+        let (mut engine, mut mock) = logged_on(now);
+        mock.inject(Fault::Drop);
+        mock.send(b"B", now, |_| {}).unwrap();
+        mock.send(b"B", now, |_| {}).unwrap();
+        exchange(&mut engine, &mut mock, now);
+        assert_eq!(mock.received_msg_types(), vec![b"2".to_vec()]);
+        assert_eq!(mock.next_event(), Some(SessionEvent::LoggedOn));
+        assert_eq!(
+            mock.next_event(),
+            Some(SessionEvent::GapFilled { begin: 2, end: 3 })
+        );
+        assert_eq!(engine.state(), SessionState::Active);
+    }
+
+    #[test]
+    fn heartbeats_can_be_delayed() {
+        let now = Instant::now();
+        let (mut engine, mut mock) = logged_on(now);
+        let delay = Duration::from_secs(10);
+        mock.inject_for(b"0", Fault::Delay(delay), None);
+        let later = now + Duration::from_secs(30);
+        mock.tick(later);
+        assert_eq!(mock.poll_outbound(later), None);
+        assert_eq!(mock.next_delivery(), Some(later + delay));
+        // Later messages wait for the delayed heartbeat.
+        mock.send(b"B", later, |_| {}).unwrap();
+        assert_eq!(mock.poll_outbound(later), None);
+        exchange(&mut engine, &mut mock, later + delay);
+        assert_eq!(engine.seq_numbers().next_inbound(), 4);
+    }
+
+    #[test]
+    fn duplicates_and_corrupt_checksums() {
+        let now = Instant::now();
+        let (_engine, mut mock) = logged_on(now);
+        mock.inject(Fault::Duplicate);
+        mock.inject(Fault::CorruptChecksum);
+        mock.send(b"B", now, |_| {}).unwrap();
+        mock.send(b"B", now, |_| {}).unwrap();
+        let first = mock.poll_outbound(now).unwrap();
+        assert_eq!(mock.poll_outbound(now), Some(first.clone()));
+        let second = mock.poll_outbound(now).unwrap();
+        let stated = |frame: &[u8]| -> u32 {
+            std::str::from_utf8(&frame[frame.len() - 4..frame.len() - 1])
+                .unwrap()
+                .parse()
+                .unwrap()
+        };
+        let actual = |frame: &[u8]| -> u32 {
+            frame[..frame.len() - 7]
+                .iter()
+                .map(|byte| u32::from(*byte))
+                .sum::<u32>()
+                % 256
+        };
+        assert_eq!(stated(&first[..]), actual(&first[..]));
+        assert_ne!(stated(&second[..]), actual(&second[..]));
+        assert_eq!(mock.poll_outbound(now), None);
+    }
+}
@@ -0,0 +1,159 @@
+use std::time::{Duration, Instant};
+
+/// A token bucket that limits the rate of outbound application messages, as
+/// required by most venues.
+///
+/// [`Throttle`] allows `messages` messages per `interval` on average, with
+/// bursts of up to [`Throttle::burst`] messages. Attach it to a
+/// [`Session`](super::Session) via
+/// [`SessionBuilder::set_throttle`](super::SessionBuilder::set_throttle), or
+/// use it on its own.
+///
+/// # Examples
+///
+/// ```
+/// use fefix::session::Throttle;
+/// use std::time::{Duration, Instant};
+///
+/// let mut throttle = Throttle::new(10, Duration::from_secs(1));
+/// throttle.set_burst(2);
+/// let now = Instant::now();
+/// assert!(throttle.try_acquire(now).is_ok());
+/// assert!(throttle.try_acquire(now).is_ok());
+/// assert_eq!(throttle.try_acquire(now), Err(Duration::from_millis(100)));
+/// assert!(throttle.try_acquire(now + Duration::from_millis(100)).is_ok());
+/// ```
+#[derive(Debug, Clone)]
+pub struct Throttle {
+    period: Duration,
+    burst: u32,
+    tokens: u32,
+    last_refill: Option<Instant>,
+}
+
+impl Throttle {
+    /// Creates a new [`Throttle`] that allows `messages` messages every
+    /// `interval`, in bursts of up to `messages` messages.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `messages` is 0.
+    pub fn new(messages: u32, interval: Duration) -> Self {
+        assert!(messages > 0, "Throttle must allow at least one message");
+        Self {
+            period: interval / messages,
+            burst: messages,
+            tokens: messages,
+            last_refill: None,
+        }
+    }
+
+    /// Returns the maximum number of messages that can be sent back-to-back.
+    pub fn burst(&self) -> u32 {
+        self.burst
+    }
+
+    /// Sets the maximum number of messages that can be sent back-to-back,
+    /// which is at least 1. The bucket starts full.
+    pub fn set_burst(&mut self, burst: u32) {
+        self.burst = burst.max(1);
+        self.tokens = self.burst;
+    }
+
+    /// Returns the average time between two messages.
+    pub fn period(&self) -> Duration {
+        self.period
+    }
+
+    /// Consumes a token if there's any, i.e. if a message can be sent at
+    /// `now`. Otherwise, returns how long it takes for the next token to
+    /// become available.
+    pub fn try_acquire(&mut self, now: Instant) -> Result<(), Duration> {
+        let available_in = self.available_in(now);
+        if available_in.is_zero() {
+            self.tokens -= 1;
+            Ok(())
+        } else {
+            Err(available_in)
+        }
+    }
+
+    /// Returns how long it takes for a token to become available, i.e. zero
+    /// if a message can be sent at `now`. No token is consumed.
+    pub fn available_in(&mut self, now: Instant) -> Duration {
+        self.refill(now);
+        if self.tokens > 0 {
+            Duration::ZERO
+        } else {
+            let last_refill = self.last_refill.unwrap_or(now);
+            self.period - now.saturating_duration_since(last_refill)
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let last_refill = match self.last_refill {
+            Some(last_refill) => last_refill,
+            None => {
+                self.last_refill = Some(now);
+                return;
+            }
+        };
+        if self.period.is_zero() {
+            self.tokens = self.burst;
+            return;
+        }
+        let elapsed = now.saturating_duration_since(last_refill);
+        let new_tokens = elapsed.as_nanos() / self.period.as_nanos();
+        if new_tokens == 0 {
+            return;
+        }
+        let tokens = u128::from(self.tokens) + new_tokens;
+        if tokens >= u128::from(self.burst) {
+            self.tokens = self.burst;
+            self.last_refill = Some(now);
+        } else {
+            self.tokens = tokens as u32;
+            // Keep the remainder, so that no time is lost between refills.
+            self.last_refill = Some(last_refill + self.period * new_tokens as u32);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn average_rate_is_respected() {
+        let mut throttle = Throttle::new(5, Duration::from_secs(1));
+        let start = Instant::now();
+        let mut sent = 0;
+        for millis in 0..2000 {
+            if throttle
+                .try_acquire(start + Duration::from_millis(millis))
+                .is_ok()
+            {
+                sent += 1;
+            }
+        }
+        // A full burst at the start, then one message every 200ms.
+        assert_eq!(sent, 5 + 9);
+    }
+
+    #[test]
+    fn idle_time_doesnt_exceed_burst() {
+        let mut throttle = Throttle::new(100, Duration::from_secs(1));
+        throttle.set_burst(3);
+        let now = Instant::now();
+        assert!(throttle.try_acquire(now).is_ok());
+        let later = now + Duration::from_secs(60);
+        for _ in 0..3 {
+            assert!(throttle.try_acquire(later).is_ok());
+        }
+        assert_eq!(throttle.try_acquire(later), Err(Duration::from_millis(10)));
+        assert_eq!(
+            throttle.try_acquire(later + Duration::from_millis(4)),
+            Err(Duration::from_millis(6))
+        );
+    }
+}
@@ -0,0 +1,193 @@
+use crate::definitions::fix44;
+use crate::dict::IsFieldDefinition;
+use crate::fix_values::{Date, Timestamp};
+use crate::{FixValue, TagU16};
+use alloc::vec::Vec;
+
+// `YYYYMMDD-HH:MM:SS.sss`.
+const SENDING_TIME_LEN: usize = 21;
+
+/// A field which is serialized once and then copied as-is into any number of
+/// messages with [`EncoderHandle::set_cached`](super::EncoderHandle::set_cached).
+///
+/// This is meant for fields that never change within a session, e.g.
+/// `SenderCompID <49>`, `TargetCompID <56>`, or static flags, on hot paths
+/// where even integer formatting is too slow.
+///
+/// # Examples
+///
+/// ```
+/// use fefix::definitions::fix44;
+/// use fefix::dict::IsFieldDefinition;
+/// use fefix::tagvalue::{CachedField, Config, Encoder};
+///
+/// let sender_comp_id = CachedField::new(fix44::SENDER_COMP_ID.tag(), "SENDER", b'|');
+/// assert_eq!(sender_comp_id.as_bytes(), b"49=SENDER|");
+///
+/// let mut encoder = Encoder::<Config>::default();
+/// encoder.config_mut().set_separator(b'|');
+/// let mut buffer = Vec::new();
+/// let mut msg = encoder.start_message(b"FIX.4.4", &mut buffer, b"0");
+/// msg.set_cached(&sender_comp_id);
+/// let data = msg.wrap();
+/// assert!(data.starts_with(b"8=FIX.4.4|9=000015|35=0|49=SENDER|10="));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CachedField {
+    tag: TagU16,
+    separator: u8,
+    bytes: Vec<u8>,
+}
+
+impl CachedField {
+    /// Serializes a field with `tag` and `value`, terminated by `separator`.
+    /// The latter must be the same as that of the
+    /// [`Encoder`](super::Encoder) which will write it.
+    pub fn new<'a, T>(tag: TagU16, value: T, separator: u8) -> Self
+    where
+        T: FixValue<'a>,
+    {
+        let mut bytes = Vec::new();
+        tag.serialize(&mut bytes);
+        bytes.push(b'=');
+        value.serialize(&mut bytes);
+        bytes.push(separator);
+        Self {
+            tag,
+            separator,
+            bytes,
+        }
+    }
+
+    /// Returns the tag of `self`.
+    pub fn tag(&self) -> TagU16 {
+        self.tag
+    }
+
+    /// Returns the separator that terminates `self`.
+    pub fn separator(&self) -> u8 {
+        self.separator
+    }
+
+    /// Returns the serialized value of `self`, without tag and separator.
+    pub fn value(&self) -> &[u8] {
+        // Tags never contain '='.
+        let value_start = self.bytes.iter().position(|byte| *byte == b'=').unwrap() + 1;
+        &self.bytes[value_start..self.bytes.len() - 1]
+    }
+
+    /// Returns the whole serialized field, i.e. `tag=value` followed by the
+    /// separator.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes[..]
+    }
+}
+
+/// A cached `SendingTime <52>` with millisecond precision, which is only
+/// serialized again when the millisecond changes.
+///
+/// Messages sent in bursts usually share the same `SendingTime <52>`, so most
+/// of them are stamped with a plain copy.
+///
+/// # Examples
+///
+/// ```
+/// use fefix::fix_values::{Date, Time, Timestamp};
+/// use fefix::tagvalue::CachedSendingTime;
+///
+/// let mut sending_time = CachedSendingTime::new(b'|');
+/// let date = Date::new(2021, 1, 1).unwrap();
+/// let timestamp = Timestamp::new(date, Time::from_hms_nano(12, 30, 0, 1_500_000).unwrap());
+/// assert_eq!(
+///     sending_time.update(&timestamp).as_bytes(),
+///     b"52=20210101-12:30:00.001|"
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct CachedSendingTime {
+    field: CachedField,
+    // The date and the time, truncated to milliseconds, of `field`.
+    key: Option<(Date, [u32; 4])>,
+}
+
+impl CachedSendingTime {
+    /// Creates a new [`CachedSendingTime`] for an [`Encoder`](super::Encoder)
+    /// with `separator`.
+    pub fn new(separator: u8) -> Self {
+        let placeholder = [b'0'; SENDING_TIME_LEN];
+        Self {
+            field: CachedField::new(fix44::SENDING_TIME.tag(), &placeholder[..], separator),
+            key: None,
+        }
+    }
+
+    /// Returns the `SendingTime <52>` field for `timestamp`, which is
+    /// serialized only if its millisecond differs from that of the previous
+    /// call.
+    pub fn update(&mut self, timestamp: &Timestamp) -> &CachedField {
+        let time = timestamp.time();
+        let key = (
+            timestamp.date(),
+            [time.hour(), time.minute(), time.second(), time.milli()],
+        );
+        if self.key != Some(key) {
+            let len = self.field.bytes.len();
+            let value = &mut self.field.bytes[len - 1 - SENDING_TIME_LEN..len - 1];
+            value[..8].copy_from_slice(&timestamp.date().to_bytes());
+            value[9..].copy_from_slice(&time.to_bytes());
+            value[8] = b'-';
+            self.key = Some(key);
+        }
+        &self.field
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fix_values::{Time, TimePrecision};
+    use crate::tagvalue::{Config, Encoder};
+
+    fn timestamp(milli: u32, nanos: u32) -> Timestamp {
+        Timestamp::new(
+            Date::new(2021, 6, 1).unwrap(),
+            Time::from_hms_nano(9, 0, 0, milli * 1_000_000 + nanos).unwrap(),
+        )
+    }
+
+    #[test]
+    fn cached_fields_match_regular_setters() {
+        let mut encoder = Encoder::<Config>::default();
+        encoder.config_mut().set_separator(b'|');
+        let target_comp_id = CachedField::new(fix44::TARGET_COMP_ID.tag(), "TARGET", b'|');
+        let heart_bt_int = CachedField::new(fix44::HEART_BT_INT.tag(), 30u32, b'|');
+        assert_eq!(heart_bt_int.value(), b"30");
+        let mut sending_time = CachedSendingTime::new(b'|');
+
+        let mut cached = Vec::new();
+        let mut msg = encoder.start_message(b"FIX.4.4", &mut cached, b"A");
+        msg.set_cached(&target_comp_id);
+        msg.set_cached(sending_time.update(&timestamp(7, 0)));
+        msg.set_cached(&heart_bt_int);
+        let cached = msg.wrap().to_vec();
+
+        let mut regular = Vec::new();
+        let mut msg = encoder.start_message(b"FIX.4.4", &mut regular, b"A");
+        msg.set(fix44::TARGET_COMP_ID, "TARGET");
+        msg.set_with(fix44::SENDING_TIME, timestamp(7, 0), TimePrecision::Millis);
+        msg.set(fix44::HEART_BT_INT, 30u32);
+        assert_eq!(cached, msg.wrap());
+    }
+
+    #[test]
+    fn sending_time_changes_with_the_millisecond() {
+        let mut sending_time = CachedSendingTime::new(b'|');
+        let first = sending_time.update(&timestamp(1, 0)).clone();
+        assert_eq!(first.value(), b"20210601-09:00:00.001");
+        assert_eq!(sending_time.update(&timestamp(1, 999_999)), &first);
+        assert_eq!(
+            sending_time.update(&timestamp(2, 0)).value(),
+            b"20210601-09:00:00.002"
+        );
+    }
+}
@@ -1,3 +1,5 @@
+use crate::fix_values::TimePrecision;
+
 const SOH: u8 = 0x1;
 const DEFAULT_MAX_MESSAGE_SIZE: usize = 0xffff;
 
@@ -46,11 +48,24 @@ pub trait Configure: Clone + Default {
 
     /// The maximum allowed size for any single FIX message. No restrictions are
     /// imposed when it is `None`.
+    ///
+    /// Decoders enforce this limit as soon as `BodyLength <9>` is known, so
+    /// that a malicious or corrupt value can't result in huge allocations.
     #[inline]
     fn max_message_size(&self) -> Option<usize> {
         Some(DEFAULT_MAX_MESSAGE_SIZE)
     }
 
+    /// The maximum allowed number of fields in any single FIX message,
+    /// `BeginString <8>` and `BodyLength <9>` included. No restrictions are
+    /// imposed when it is `None`, which is the default.
+    ///
+    /// This setting has no effect when encoding FIX messages.
+    #[inline]
+    fn max_fields(&self) -> Option<usize> {
+        None
+    }
+
     /// Determines wheather or not `CheckSum(10)` should be verified.
     ///
     /// This setting has no effect when encoding FIX messages.
@@ -59,12 +74,67 @@ pub trait Configure: Clone + Default {
         true
     }
 
+    /// How `CheckSum <10>` is verified. See [`Verification`]. It is
+    /// [`Verification::Enforce`] if [`Configure::verify_checksum`] is `true`
+    /// and [`Verification::Skip`] otherwise.
+    ///
+    /// This setting has no effect when encoding FIX messages.
+    #[inline]
+    fn checksum_verification(&self) -> Verification {
+        if self.verify_checksum() {
+            Verification::Enforce
+        } else {
+            Verification::Skip
+        }
+    }
+
+    /// How `BodyLength <9>` is verified. See [`Verification`]. It is
+    /// [`Verification::Enforce`] by default.
+    ///
+    /// This setting has no effect when encoding FIX messages.
+    #[inline]
+    fn body_length_verification(&self) -> Verification {
+        Verification::Enforce
+    }
+
+    /// What to do when a tag appears more than once outside of repeating
+    /// groups. See [`DuplicateTagPolicy`]. It is
+    /// [`DuplicateTagPolicy::KeepLast`] by default.
+    ///
+    /// This setting has no effect when encoding FIX messages.
+    #[inline]
+    fn duplicate_tag_policy(&self) -> DuplicateTagPolicy {
+        DuplicateTagPolicy::KeepLast
+    }
+
+    /// The only value of `BeginString <8>` that decoders accept, e.g.
+    /// `FIXT.1.1`. Messages with any other value are rejected with
+    /// [`DecodeError::BeginString`](super::DecodeError::BeginString). All
+    /// values are accepted when it is `None`, which is the default.
+    ///
+    /// This setting has no effect when encoding FIX messages.
+    #[inline]
+    fn expected_begin_string(&self) -> Option<&[u8]> {
+        None
+    }
+
     /// Determines wheather or not the decoder needs to have access to
     /// associative FIX fields.
     #[inline]
     fn should_decode_associative(&self) -> bool {
         true
     }
+
+    /// The number of fractional second digits of `UTCTimestamp` fields
+    /// written by [`EncoderHandle::set_timestamp`](super::EncoderHandle::set_timestamp),
+    /// e.g. `SendingTime <52>`. It is [`TimePrecision::Millis`] by default.
+    ///
+    /// This setting has no effect when decoding FIX messages, which accept
+    /// all precisions.
+    #[inline]
+    fn timestamp_precision(&self) -> TimePrecision {
+        TimePrecision::Millis
+    }
 }
 
 /// A `struct` that has settable fields and implements [`Configure`].
@@ -77,8 +147,13 @@ pub trait Configure: Clone + Default {
 pub struct Config {
     separator: u8,
     max_message_size: Option<usize>,
-    verify_checksum: bool,
+    max_fields: Option<usize>,
+    duplicate_tag_policy: DuplicateTagPolicy,
+    expected_begin_string: Option<&'static [u8]>,
+    checksum_verification: Verification,
+    body_length_verification: Verification,
     should_decode_associative: bool,
+    timestamp_precision: TimePrecision,
 }
 
 impl Config {
@@ -98,7 +173,11 @@ impl Config {
     /// ```
     pub fn set_separator(&mut self, separator: u8) {
         self.separator = separator;
-        self.verify_checksum = separator == SOH;
+        self.checksum_verification = if separator == SOH {
+            Verification::Enforce
+        } else {
+            Verification::Skip
+        };
     }
 
     /// Changes the value of [`Configure::max_message_size`].
@@ -106,7 +185,24 @@ impl Config {
         self.max_message_size = max_message_size;
     }
 
-    /// Turns on or off `CheckSum <10>` verification. On by default.
+    /// Changes the value of [`Configure::max_fields`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix::tagvalue::{Config, Configure};
+    ///
+    /// let config = &mut Config::default();
+    /// assert_eq!(config.max_fields(), None);
+    /// config.set_max_fields(Some(256));
+    /// assert_eq!(config.max_fields(), Some(256));
+    /// ```
+    pub fn set_max_fields(&mut self, max_fields: Option<usize>) {
+        self.max_fields = max_fields;
+    }
+
+    /// Turns on or off `CheckSum <10>` verification. On by default. See
+    /// [`Config::set_checksum_verification`] for finer control.
     ///
     /// # Examples
     ///
@@ -119,7 +215,72 @@ impl Config {
     /// assert_eq!(config.verify_checksum(), false);
     /// ```
     pub fn set_verify_checksum(&mut self, verify: bool) {
-        self.verify_checksum = verify;
+        self.checksum_verification = if verify {
+            Verification::Enforce
+        } else {
+            Verification::Skip
+        };
+    }
+
+    /// Changes the value of [`Configure::checksum_verification`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix::tagvalue::{Config, Configure, Verification};
+    ///
+    /// let config = &mut Config::default();
+    /// assert_eq!(config.checksum_verification(), Verification::Enforce);
+    /// config.set_checksum_verification(Verification::WarnOnly);
+    /// assert_eq!(config.checksum_verification(), Verification::WarnOnly);
+    /// assert_eq!(config.verify_checksum(), true);
+    /// ```
+    pub fn set_checksum_verification(&mut self, verification: Verification) {
+        self.checksum_verification = verification;
+    }
+
+    /// Changes the value of [`Configure::body_length_verification`].
+    pub fn set_body_length_verification(&mut self, verification: Verification) {
+        self.body_length_verification = verification;
+    }
+
+    /// Changes the value of [`Configure::duplicate_tag_policy`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix::tagvalue::{Config, Configure, DuplicateTagPolicy};
+    ///
+    /// let config = &mut Config::default();
+    /// assert_eq!(config.duplicate_tag_policy(), DuplicateTagPolicy::KeepLast);
+    /// config.set_duplicate_tag_policy(DuplicateTagPolicy::Reject);
+    /// assert_eq!(config.duplicate_tag_policy(), DuplicateTagPolicy::Reject);
+    /// ```
+    pub fn set_duplicate_tag_policy(&mut self, policy: DuplicateTagPolicy) {
+        self.duplicate_tag_policy = policy;
+    }
+
+    /// Changes the value of [`Configure::expected_begin_string`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix::tagvalue::{Config, Configure, DecodeError, RawDecoder};
+    ///
+    /// let mut decoder = RawDecoder::<Config>::new();
+    /// decoder.config_mut().set_separator(b'|');
+    /// decoder.config_mut().set_expected_begin_string(Some(b"FIXT.1.1"));
+    /// assert!(decoder.decode(b"8=FIXT.1.1|9=5|35=0|10=000|").is_ok());
+    /// assert_eq!(
+    ///     decoder.decode(b"8=FIX.4.4|9=5|35=0|10=000|").unwrap_err(),
+    ///     DecodeError::BeginString {
+    ///         expected: b"FIXT.1.1".to_vec(),
+    ///         found: b"FIX.4.4".to_vec(),
+    ///     }
+    /// );
+    /// ```
+    pub fn set_expected_begin_string(&mut self, begin_string: Option<&'static [u8]>) {
+        self.expected_begin_string = begin_string;
     }
 
     /// Enables or disables random access of fields within a
@@ -130,6 +291,27 @@ impl Config {
     pub fn set_decode_assoc(&mut self, should: bool) {
         self.should_decode_associative = should;
     }
+
+    /// Changes the precision of timestamps written by
+    /// [`EncoderHandle::set_timestamp`](super::EncoderHandle::set_timestamp).
+    /// It is [`TimePrecision::Millis`] by default; MiFID II clock
+    /// synchronization rules, for example, require
+    /// [`TimePrecision::Micros`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix::fix_values::TimePrecision;
+    /// use fefix::tagvalue::{Config, Configure};
+    ///
+    /// let config = &mut Config::default();
+    /// assert_eq!(config.timestamp_precision(), TimePrecision::Millis);
+    /// config.set_timestamp_precision(TimePrecision::Micros);
+    /// assert_eq!(config.timestamp_precision(), TimePrecision::Micros);
+    /// ```
+    pub fn set_timestamp_precision(&mut self, precision: TimePrecision) {
+        self.timestamp_precision = precision;
+    }
 }
 
 impl Configure for Config {
@@ -140,7 +322,17 @@ impl Configure for Config {
 
     #[inline]
     fn verify_checksum(&self) -> bool {
-        self.verify_checksum
+        self.checksum_verification != Verification::Skip
+    }
+
+    #[inline]
+    fn checksum_verification(&self) -> Verification {
+        self.checksum_verification
+    }
+
+    #[inline]
+    fn body_length_verification(&self) -> Verification {
+        self.body_length_verification
     }
 
     #[inline]
@@ -148,19 +340,90 @@ impl Configure for Config {
         self.max_message_size
     }
 
+    #[inline]
+    fn max_fields(&self) -> Option<usize> {
+        self.max_fields
+    }
+
+    #[inline]
+    fn duplicate_tag_policy(&self) -> DuplicateTagPolicy {
+        self.duplicate_tag_policy
+    }
+
+    #[inline]
+    fn expected_begin_string(&self) -> Option<&[u8]> {
+        self.expected_begin_string
+    }
+
     #[inline]
     fn should_decode_associative(&self) -> bool {
         self.should_decode_associative
     }
+
+    #[inline]
+    fn timestamp_precision(&self) -> TimePrecision {
+        self.timestamp_precision
+    }
+}
+
+/// The behavior of decoders when a tag appears more than once outside of
+/// repeating groups (or twice within the same group entry), which the FIX
+/// specification forbids. Venues don't always agree on how to handle it, so
+/// the choice is left to the user.
+///
+/// Duplicate tags are only detected when associative decoding is enabled (see
+/// [`Config::set_decode_assoc`]). Whatever the policy, the tags in question
+/// can be inspected with
+/// [`Message::duplicate_tags`](super::Message::duplicate_tags).
+///
+/// Please note that [`DuplicateTagPolicy`] is marked with `#[non_exhaustive]`,
+/// which future-proofs the enumeration type in case more variants are added.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum DuplicateTagPolicy {
+    /// Fails decoding with
+    /// [`DecodeError::DuplicateTag`](super::DecodeError::DuplicateTag).
+    Reject,
+    /// Keeps the value of the first occurrence and ignores all others.
+    KeepFirst,
+    /// Keeps the value of the last occurrence.
+    #[default]
+    KeepLast,
+}
+
+/// How decoders treat messages which fail an integrity check, i.e. a wrong
+/// `CheckSum <10>` or `BodyLength <9>`. Some venues are known to send wrong
+/// values on certain messages, which may have to be tolerated.
+///
+/// Please note that [`Verification`] is marked with `#[non_exhaustive]`,
+/// which future-proofs the enumeration type in case more variants are added.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Verification {
+    /// Fails decoding with [`DecodeError::CheckSum`](super::DecodeError::CheckSum)
+    /// or [`DecodeError::Length`](super::DecodeError::Length).
+    #[default]
+    Enforce,
+    /// Decodes the message anyway, but reports the failure with
+    /// [`RawFrame::warnings`](super::RawFrame::warnings) and
+    /// [`Message::warnings`](super::Message::warnings).
+    WarnOnly,
+    /// Doesn't perform the check at all.
+    Skip,
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
             max_message_size: Some(DEFAULT_MAX_MESSAGE_SIZE),
+            max_fields: None,
+            duplicate_tag_policy: DuplicateTagPolicy::KeepLast,
+            expected_begin_string: None,
             separator: SOH,
-            verify_checksum: true,
+            checksum_verification: Verification::Enforce,
+            body_length_verification: Verification::Enforce,
             should_decode_associative: true,
+            timestamp_precision: TimePrecision::Millis,
         }
     }
 }
@@ -198,4 +461,14 @@ mod test {
         config.set_verify_checksum(true);
         assert_eq!(config.verify_checksum(), true);
     }
+
+    #[test]
+    fn custom_separators_skip_checksum_verification() {
+        let mut config = Config::default();
+        config.set_separator(b'|');
+        assert_eq!(config.checksum_verification(), Verification::Skip);
+        config.set_separator(SOH);
+        assert_eq!(config.checksum_verification(), Verification::Enforce);
+        assert_eq!(config.body_length_verification(), Verification::Enforce);
+    }
 }
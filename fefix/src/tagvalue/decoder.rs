@@ -1,16 +1,25 @@
 use super::{
-    Config, Configure, DecodeError, FieldAccess, RawDecoder, RawDecoderBuffered, RawFrame,
-    RepeatingGroup,
+    Config, Configure, DecodeError, DecoderStats, DuplicateTagPolicy, FieldAccess, MessageFmt,
+    RawDecoder, RawDecoderBuffered, RawFrame, RepeatingGroup,
 };
 use crate::dict;
 use crate::dict::IsFieldDefinition;
+use crate::scan;
+use crate::utils::{IntMap, IntSet};
 use crate::FixValue;
 use crate::TagU16;
 use crate::{dict::FixDatatype, Dictionary};
-use nohash_hasher::IntMap;
-use std::collections::HashMap;
-use std::fmt::Debug;
-use std::marker::PhantomData;
+use crate::{IntoOptResult, OptResult};
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::fmt::Debug;
+use core::marker::PhantomData;
+use hashbrown::HashMap;
+#[cfg(feature = "std")]
+use std::io;
+
+const FIXT_BEGIN_STRING: &[u8] = b"FIXT.1.1";
+const APPL_VER_ID_TAG: u16 = 1128;
 
 // Number of bytes before the start of the `BeginString` field:
 //
@@ -28,16 +37,33 @@ const BEGIN_STRING_OFFSET: usize = 2;
 /// allows for random (i.e. non-sequential) reads on a FIX message.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub enum FieldLocator {
+    /// A field within a repeating group entry. Nested groups only refer to
+    /// their innermost group.
     WithinGroup {
+        /// The tag of the field.
         tag: TagU16,
+        /// The position of the group's `NumInGroup` field within the message,
+        /// as returned by [`Message::fields`].
         index_of_group_tag: u32,
+        /// The zero-based index of the group entry.
         entry_index: u32,
     },
+    /// A field outside of any repeating group.
     TopLevel {
+        /// The tag of the field.
         tag: TagU16,
     },
 }
 
+impl FieldLocator {
+    /// Returns the tag of the field located by `self`.
+    pub fn tag(&self) -> TagU16 {
+        match self {
+            Self::WithinGroup { tag, .. } | Self::TopLevel { tag } => *tag,
+        }
+    }
+}
+
 /// FIX message decoder.
 ///
 /// One should create a [`Decoder`] per stream of FIX messages.
@@ -46,10 +72,49 @@ pub struct Decoder<C = Config>
 where
     C: Configure,
 {
-    dict: Dictionary,
     builder: MessageBuilder<'static>,
     raw_decoder: RawDecoder<C>,
+    tables: DictionaryTables,
+    // Application-layer dictionaries of FIXT.1.1 messages, by `ApplVerID
+    // <1128>`.
+    appl_ver_tables: Vec<(Vec<u8>, DictionaryTables)>,
+    default_appl_ver_id: Option<Vec<u8>>,
+    // The index within `appl_ver_tables` of the dictionary in use for the
+    // current message, if any.
+    active_appl_ver: Option<usize>,
+    is_fixt: bool,
+    stats: DecoderStats,
+}
+
+/// The lookup tables that a [`Decoder`] derives from a [`Dictionary`].
+#[derive(Debug, Clone)]
+struct DictionaryTables {
+    dict: Dictionary,
     tag_lookup: IntMap<u16, FixDatatype>,
+    group_members: IntMap<u16, IntSet<u16>>,
+}
+
+impl DictionaryTables {
+    fn new(dict: Dictionary) -> Self {
+        Self {
+            tag_lookup: dict
+                .iter_fields()
+                .filter_map(|field| {
+                    let fix_type = field.data_type().basetype();
+                    if fix_type == FixDatatype::Length
+                        || fix_type == FixDatatype::NumInGroup
+                        || fix_type == FixDatatype::Data
+                    {
+                        Some((field.tag().get(), fix_type))
+                    } else {
+                        None
+                    }
+                })
+                .collect(),
+            group_members: group_members(&dict),
+            dict,
+        }
+    }
 }
 
 impl<C> Decoder<C>
@@ -66,7 +131,6 @@ where
     /// messages.
     pub fn with_config(dict: Dictionary, config: C) -> Self {
         Self {
-            dict: dict.clone(),
             builder: MessageBuilder {
                 state: DecoderState {
                     group_information: Vec::new(),
@@ -76,28 +140,109 @@ where
                 raw: b"",
                 field_locators: Vec::new(),
                 fields: HashMap::new(),
+                duplicate_tags: Vec::new(),
+                hidden_duplicates: Vec::new(),
+                warnings: Vec::new(),
                 i_first_cell: 0,
                 i_last_cell: 0,
                 len_end_body: 0,
                 len_end_trailer: 0,
                 len_end_header: 0,
                 bytes: b"",
+                dict: dict.clone(),
             },
             raw_decoder: RawDecoder::with_config(config),
-            tag_lookup: dict
-                .iter_fields()
-                .filter_map(|field| {
-                    let fix_type = field.data_type().basetype();
-                    if fix_type == FixDatatype::Length || fix_type == FixDatatype::NumInGroup {
-                        Some((field.tag().get(), fix_type))
-                    } else {
-                        None
-                    }
-                })
-                .collect(),
+            tables: DictionaryTables::new(dict),
+            appl_ver_tables: Vec::new(),
+            default_appl_ver_id: None,
+            active_appl_ver: None,
+            is_fixt: false,
+            stats: DecoderStats::default(),
+        }
+    }
+
+    /// Registers `dict` as the application-layer [`Dictionary`] of all
+    /// `FIXT.1.1` messages with `ApplVerID <1128>` equal to `appl_ver_id`,
+    /// e.g. `9` for FIX 5.0 SP2. It replaces any previous [`Dictionary`] for
+    /// the same `appl_ver_id`.
+    ///
+    /// The [`Dictionary`] given to [`Decoder::new`] remains in use for
+    /// messages with any other `BeginString <8>`, for `FIXT.1.1` messages with
+    /// an unknown application version, and for all fields that come before
+    /// `ApplVerID <1128>` or that the application-layer [`Dictionary`]
+    /// doesn't define. It should thus be the `FIXT.1.1` transport dictionary.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix::dict::FixDatatype;
+    /// use fefix::tagvalue::{Config, Configure, Decoder};
+    /// use fefix::Dictionary;
+    ///
+    /// let mut builder = Dictionary::fix44().to_builder();
+    /// builder.add_custom_field(5001, "VenueDataLen", FixDatatype::Length).unwrap();
+    /// builder.add_custom_field(5002, "VenueData", FixDatatype::Data).unwrap();
+    /// let mut decoder = Decoder::<Config>::new(Dictionary::fix44());
+    /// decoder.config_mut().set_separator(b'|');
+    /// decoder.add_appl_ver_dictionary("9", builder.build());
+    ///
+    /// let data = b"8=FIXT.1.1|9=28|35=B|1128=9|5001=3|5002=a|b|10=000|";
+    /// let message = decoder.decode(&data[..]).unwrap();
+    /// assert_eq!(message.fv_raw_by_tag(fefix::TagU16::new(5002).unwrap()), Some(&b"a|b"[..]));
+    /// ```
+    pub fn add_appl_ver_dictionary(&mut self, appl_ver_id: &str, dict: Dictionary) {
+        let tables = DictionaryTables::new(dict);
+        match self
+            .appl_ver_tables
+            .iter_mut()
+            .find(|(id, _)| id == appl_ver_id.as_bytes())
+        {
+            Some((_, existing)) => *existing = tables,
+            None => self
+                .appl_ver_tables
+                .push((appl_ver_id.as_bytes().to_vec(), tables)),
         }
     }
 
+    /// Sets the application version of `FIXT.1.1` messages without
+    /// `ApplVerID <1128>`, as agreed upon via `DefaultApplVerID <1137>` during
+    /// the logon exchange. None by default.
+    pub fn set_default_appl_ver_id(&mut self, appl_ver_id: Option<&str>) {
+        self.default_appl_ver_id = appl_ver_id.map(|id| id.as_bytes().to_vec());
+    }
+
+    /// Returns the application version of `FIXT.1.1` messages without
+    /// `ApplVerID <1128>`. See [`Decoder::set_default_appl_ver_id`].
+    pub fn default_appl_ver_id(&self) -> Option<&str> {
+        self.default_appl_ver_id
+            .as_ref()
+            .and_then(|id| core::str::from_utf8(&id[..]).ok())
+    }
+
+    fn appl_ver_index(&self, appl_ver_id: &[u8]) -> Option<usize> {
+        self.appl_ver_tables
+            .iter()
+            .position(|(id, _)| id == appl_ver_id)
+    }
+
+    /// Switches to the application-layer dictionary with index `index`, or
+    /// to the main dictionary.
+    fn set_active_appl_ver(&mut self, index: Option<usize>) {
+        self.active_appl_ver = index;
+        let tables = match index {
+            Some(i) => &self.appl_ver_tables[i].1,
+            None => &self.tables,
+        };
+        self.builder.dict = tables.dict.clone();
+    }
+
+    fn field_type(&self, tag: u16) -> Option<FixDatatype> {
+        self.active_appl_ver
+            .and_then(|i| self.appl_ver_tables[i].1.tag_lookup.get(&tag))
+            .or_else(|| self.tables.tag_lookup.get(&tag))
+            .copied()
+    }
+
     /// Returns an immutable reference to the [`Configure`] used by `self`.
     ///
     /// # Examples
@@ -133,6 +278,74 @@ where
         self.raw_decoder.config_mut()
     }
 
+    #[cfg(feature = "utils-tokio")]
+    pub(crate) fn raw_decoder(&self) -> &RawDecoder<C> {
+        &self.raw_decoder
+    }
+
+    /// Returns the [`DecoderStats`] of all messages that were decoded by
+    /// `self` so far.
+    pub fn stats(&self) -> &DecoderStats {
+        &self.stats
+    }
+
+    /// Resets the [`DecoderStats`] of `self`, e.g. at the start of each
+    /// reporting period.
+    pub fn reset_stats(&mut self) {
+        self.stats = DecoderStats::default();
+    }
+
+    /// Pre-allocates internal storage for messages with up to `num_fields`
+    /// fields.
+    ///
+    /// [`Decoder`] reuses its storage from one message to the next, so it only
+    /// allocates when a message is larger than all previous ones (or when it
+    /// carries integrity failures that are tolerated via
+    /// [`Verification::WarnOnly`](super::Verification::WarnOnly)). Reserving
+    /// enough space upfront avoids such allocations altogether, e.g. to keep
+    /// latency flat from the very first message.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix::tagvalue::{Config, Decoder};
+    /// use fefix::Dictionary;
+    ///
+    /// let mut decoder = Decoder::<Config>::new(Dictionary::fix44());
+    /// decoder.reserve_fields(256);
+    /// assert!(decoder.field_capacity() >= 256);
+    /// ```
+    pub fn reserve_fields(&mut self, num_fields: usize) {
+        let builder = &mut self.builder;
+        let additional = num_fields.saturating_sub(builder.field_locators.len());
+        builder.field_locators.reserve(additional);
+        let additional = num_fields.saturating_sub(builder.fields.len());
+        builder.fields.reserve(additional);
+    }
+
+    /// Returns the number of fields that `self` can decode without allocating.
+    /// See [`Decoder::reserve_fields`].
+    pub fn field_capacity(&self) -> usize {
+        let capacity = self.builder.field_locators.capacity();
+        if self.config().should_decode_associative() {
+            capacity.min(self.builder.fields.capacity())
+        } else {
+            capacity
+        }
+    }
+
+    /// Releases as much internal storage as possible, e.g. after an unusually
+    /// large message.
+    pub fn shrink_to_fit(&mut self) {
+        let builder = &mut self.builder;
+        builder.field_locators.shrink_to_fit();
+        builder.fields.shrink_to_fit();
+        builder.duplicate_tags.shrink_to_fit();
+        builder.hidden_duplicates.shrink_to_fit();
+        builder.warnings.shrink_to_fit();
+        builder.state.group_information.shrink_to_fit();
+    }
+
     /// Turns `self` into a [`DecoderBuffered`] by allocating an internal buffer.
     pub fn buffered(self) -> DecoderBuffered<C> {
         let raw_decoder = self.raw_decoder.clone().buffered();
@@ -142,6 +355,34 @@ where
         }
     }
 
+    /// Returns a [`DecodeStream`] over all FIX messages in `data`, which must
+    /// start at a message boundary. Messages are decoded one at a time, reusing
+    /// the internal state of `self`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix::definitions::fix44;
+    /// use fefix::tagvalue::{Config, Configure, Decoder, FieldAccess};
+    /// use fefix::Dictionary;
+    ///
+    /// let mut decoder = Decoder::<Config>::new(Dictionary::fix44());
+    /// decoder.config_mut().set_separator(b'|');
+    /// let log = b"8=FIX.4.4|9=11|35=0|112=A|10=000|8=FIX.4.4|9=11|35=0|112=B|10=000|";
+    /// let mut stream = decoder.decode_stream(log);
+    /// let message = stream.next().unwrap().unwrap();
+    /// assert_eq!(message.fv(fix44::TEST_REQ_ID), Ok("A"));
+    /// let message = stream.next().unwrap().unwrap();
+    /// assert_eq!(message.fv(fix44::TEST_REQ_ID), Ok("B"));
+    /// assert!(stream.next().is_none());
+    /// ```
+    pub fn decode_stream<'a>(&'a mut self, data: &'a [u8]) -> DecodeStream<'a, C> {
+        DecodeStream {
+            decoder: self,
+            data,
+        }
+    }
+
     /// Decodes `data` and returns an immutable reference to the obtained
     /// message.
     ///
@@ -164,78 +405,125 @@ where
     where
         T: AsRef<[u8]>,
     {
-        let frame = self.raw_decoder.decode(bytes)?;
-        self.from_frame(frame)
+        let len = bytes.as_ref().len();
+        match self.raw_decoder.decode(bytes) {
+            Ok(frame) => self.from_frame(frame),
+            Err(err) => {
+                self.record_error(len, &err);
+                Err(err)
+            }
+        }
     }
 
     fn message_builder_mut<'a>(&'a mut self) -> &'a mut MessageBuilder<'a> {
-        unsafe { std::mem::transmute(&mut self.builder) }
+        unsafe { core::mem::transmute(&mut self.builder) }
     }
 
     fn from_frame<'a, T>(&'a mut self, frame: RawFrame<T>) -> Result<Message<'a, T>, DecodeError>
+    where
+        T: AsRef<[u8]>,
+    {
+        let len = frame.as_bytes().len();
+        match self.store_fields(&frame) {
+            Ok(()) => self.stats.record_message(len, frame.warnings()),
+            Err(err) => {
+                self.record_error(len, &err);
+                return Err(err);
+            }
+        }
+        Ok(Message {
+            builder: self.message_builder_mut(),
+            phantom: PhantomData,
+        })
+    }
+
+    fn record_error(&mut self, len: usize, err: &DecodeError) {
+        trace_event!(debug, error = %err, len, "Couldn't decode FIX message");
+        self.stats.record_error(len, err);
+    }
+
+    fn record_resync(&mut self, skipped: usize) {
+        trace_event!(debug, skipped, "Skipped garbled data");
+        self.stats.record_resync(skipped);
+    }
+
+    fn store_fields<T>(&mut self, frame: &RawFrame<T>) -> Result<(), DecodeError>
     where
         T: AsRef<[u8]>,
     {
         self.builder.clear();
+        self.message_builder_mut().bytes = frame.as_bytes();
+        self.builder.warnings.extend_from_slice(frame.warnings());
         let separator = self.config().separator();
         let payload = frame.payload();
+        if !self.appl_ver_tables.is_empty() {
+            self.is_fixt = frame.begin_string() == FIXT_BEGIN_STRING;
+            let index = match self.default_appl_ver_id.as_ref() {
+                Some(id) if self.is_fixt => self.appl_ver_index(&id[..]),
+                _ => None,
+            };
+            self.set_active_appl_ver(index);
+        }
         self.store_field(
             TagU16::new(8).unwrap(),
             frame.as_bytes(),
             BEGIN_STRING_OFFSET,
             frame.begin_string().len(),
-        );
+            0,
+        )?;
+        let payload_offset = frame.payload_offset();
+        // `BeginString <8>`, `BodyLength <9>`, and `CheckSum <10>` are not
+        // part of the payload, but they still count towards the limit.
+        let mut num_fields = 3;
+        let max_fields = self.config().max_fields();
         let mut i = 0;
         while i < payload.len() {
-            let index_of_next_equal_sign = {
-                let i_eq = (&payload[i..])
-                    .iter()
-                    .copied()
-                    .position(|byte| byte == b'=')
-                    .map(|pos| pos + i);
-                if i_eq.is_none() {
-                    break;
+            num_fields += 1;
+            if let Some(max) = max_fields {
+                if num_fields > max {
+                    return Err(DecodeError::TooManyFields { max });
                 }
-                i_eq.unwrap()
+            }
+            let offset = payload_offset + i;
+            let index_of_next_equal_sign = scan::find_byte(&payload[i..], b'=')
+                .map(|pos| pos + i)
+                .ok_or(DecodeError::Invalid { offset, tag: None })?;
+            let tag_num = parse_tag(&payload[i..index_of_next_equal_sign])
+                .ok_or(DecodeError::Invalid { offset, tag: None })?;
+            let invalid = DecodeError::Invalid {
+                offset,
+                tag: Some(tag_num),
             };
-            let field_value_len = if let Some(len) = self.builder.state.data_field_length {
-                self.builder.state.data_field_length = None;
+            let field_value_start = index_of_next_equal_sign + 1;
+            // Data fields may contain separators, so we must rely on the length
+            // given by the preceding `Length` field instead.
+            let data_field_length = self
+                .builder
+                .state
+                .data_field_length
+                .take()
+                .filter(|_| self.field_type(tag_num.get()) == Some(FixDatatype::Data));
+            let field_value_len = if let Some(len) = data_field_length {
+                let end = field_value_start.checked_add(len);
+                if end.and_then(|end| payload.get(end)) != Some(&separator) {
+                    return Err(invalid);
+                }
                 len
             } else {
-                let len = (&payload[index_of_next_equal_sign + 1..])
-                    .iter()
-                    .copied()
-                    .position(|byte| byte == separator);
-                if len.is_none() {
-                    break;
-                }
-                len.unwrap()
-            };
-            let tag_num = {
-                let mut tag = 0u32;
-                for byte in (&payload[i..index_of_next_equal_sign]).iter().copied() {
-                    tag = tag * 10 + (byte as u32 - b'0' as u32);
-                }
-                if let Some(tag) = TagU16::new(tag as u16) {
-                    tag
-                } else {
-                    break;
-                }
+                scan::find_byte(&payload[field_value_start..], separator).ok_or(invalid)?
             };
             self.store_field(
                 tag_num,
                 frame.payload(),
-                index_of_next_equal_sign + 1,
+                field_value_start,
                 field_value_len,
-            );
+                offset,
+            )?;
             // Equal sign                ~~~
             // Separator                                       ~~~
             i = index_of_next_equal_sign + 1 + field_value_len + 1;
         }
-        Ok(Message {
-            builder: self.message_builder_mut(),
-            phantom: PhantomData::default(),
-        })
+        Ok(())
     }
 
     fn store_field<'a>(
@@ -244,41 +532,58 @@ where
         raw_message: &'a [u8],
         field_value_start: usize,
         field_value_len: usize,
-    ) {
+        offset: usize,
+    ) -> Result<(), DecodeError> {
         let config_assoc = self.config().should_decode_associative();
+        let duplicate_tag_policy = self.config().duplicate_tag_policy();
         let field_value = &raw_message[field_value_start..][..field_value_len];
         if self.builder.state.new_group.is_some() {
             // We are entering a new group, but we still don't know which tag
             // will be the first one in each entry.
             self.builder.state.set_new_group(tag);
-        } else if let Some(group_info) = self.builder.state.group_information.last_mut() {
-            if group_info.current_entry_i >= group_info.num_entries {
-                self.builder.state.group_information.pop();
-            } else if tag == group_info.first_tag_of_every_group_entry {
-                group_info.current_entry_i += 1;
-            }
+        } else {
+            let appl_ver_tables = &self.appl_ver_tables;
+            let active = self
+                .active_appl_ver
+                .map(|i| &appl_ver_tables[i].1.group_members);
+            self.builder
+                .state
+                .update_groups(tag, active, &self.tables.group_members);
         }
         self.message_builder_mut()
             .add_field(
                 tag,
                 &raw_message[field_value_start..][..field_value_len],
                 config_assoc,
+                duplicate_tag_policy,
             )
-            .unwrap();
-        let fix_type = self.tag_lookup.get(&tag.get());
-        if fix_type == Some(&FixDatatype::NumInGroup) {
+            .map_err(|()| DecodeError::DuplicateTag { offset, tag })?;
+        let fix_type = self.field_type(tag.get());
+        if tag.get() == APPL_VER_ID_TAG
+            && self.is_fixt
+            && self.builder.state.group_information.is_empty()
+        {
+            let index = self.appl_ver_index(field_value);
+            self.set_active_appl_ver(index);
+        }
+        if fix_type == Some(FixDatatype::NumInGroup) {
+            let num_entries =
+                usize::deserialize(field_value).map_err(|_| DecodeError::Invalid {
+                    offset,
+                    tag: Some(tag),
+                })?;
             self.builder
                 .state
-                .add_group(tag, self.builder.field_locators.len() - 1, field_value);
-        } else if fix_type == Some(&FixDatatype::Length) {
-            // FIXME
-            let last_field_locator = self.builder.field_locators.last().unwrap();
-            let last_field = self.builder.fields.get(last_field_locator).unwrap();
-            let last_field_value = last_field.1;
-            let s = std::str::from_utf8(last_field_value).unwrap();
-            let data_field_length = str::parse(s).unwrap();
+                .add_group(tag, self.builder.field_locators.len() - 1, num_entries);
+        } else if fix_type == Some(FixDatatype::Length) {
+            let data_field_length =
+                usize::deserialize(field_value).map_err(|_| DecodeError::Invalid {
+                    offset,
+                    tag: Some(tag),
+                })?;
             self.builder.state.data_field_length = Some(data_field_length);
         }
+        Ok(())
     }
 }
 
@@ -350,6 +655,46 @@ where
         self.raw_decoder.clear();
     }
 
+    /// Recovers from a decoding error by discarding garbled data up to the next
+    /// plausible start of message. Returns the number of bytes that were
+    /// skipped. See [`RawDecoderBuffered::resync`] for more information.
+    #[inline]
+    pub fn resync(&mut self) -> usize {
+        let skipped = self.raw_decoder.resync();
+        self.decoder.record_resync(skipped);
+        skipped
+    }
+
+    /// Pre-allocates internal storage for messages with up to `num_fields`
+    /// fields. See [`Decoder::reserve_fields`].
+    pub fn reserve_fields(&mut self, num_fields: usize) {
+        self.decoder.reserve_fields(num_fields);
+    }
+
+    /// Registers the application-layer [`Dictionary`] of `FIXT.1.1` messages
+    /// with `ApplVerID <1128>` equal to `appl_ver_id`. See
+    /// [`Decoder::add_appl_ver_dictionary`].
+    pub fn add_appl_ver_dictionary(&mut self, appl_ver_id: &str, dict: Dictionary) {
+        self.decoder.add_appl_ver_dictionary(appl_ver_id, dict);
+    }
+
+    /// Sets the application version of `FIXT.1.1` messages without
+    /// `ApplVerID <1128>`. See [`Decoder::set_default_appl_ver_id`].
+    pub fn set_default_appl_ver_id(&mut self, appl_ver_id: Option<&str>) {
+        self.decoder.set_default_appl_ver_id(appl_ver_id);
+    }
+
+    /// Returns the [`DecoderStats`] of all messages that were decoded by
+    /// `self` so far. See [`Decoder::stats`].
+    pub fn stats(&self) -> &DecoderStats {
+        self.decoder.stats()
+    }
+
+    /// Resets the [`DecoderStats`] of `self`. See [`Decoder::reset_stats`].
+    pub fn reset_stats(&mut self) {
+        self.decoder.reset_stats();
+    }
+
     #[inline]
     pub fn state(&mut self) -> Result<Option<()>, DecodeError> {
         match self.raw_decoder.current_frame() {
@@ -358,10 +703,58 @@ where
                 Ok(Some(()))
             }
             Ok(None) => Ok(None),
-            Err(e) => Err(e),
+            Err(e) => {
+                self.record_raw_error(&e);
+                Err(e)
+            }
+        }
+    }
+
+    /// Reads from `reader` until a whole message is buffered, then decodes
+    /// it. See [`RawDecoderBuffered::read_from`] for more information.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix::definitions::fix44;
+    /// use fefix::tagvalue::{Config, Configure, Decoder, FieldAccess};
+    /// use fefix::Dictionary;
+    ///
+    /// let mut decoder = Decoder::<Config>::new(Dictionary::fix44());
+    /// decoder.config_mut().set_separator(b'|');
+    /// let mut decoder = decoder.buffered();
+    /// let mut stream = &b"8=FIX.4.4|9=11|35=0|112=A|10=000|"[..];
+    /// let message = decoder.read_from(&mut stream).unwrap().unwrap();
+    /// assert_eq!(message.fv(fix44::TEST_REQ_ID), Ok("A"));
+    /// ```
+    #[cfg(feature = "std")]
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "std")))]
+    pub fn read_from<'a, R>(
+        &'a mut self,
+        reader: &mut R,
+    ) -> Result<Option<Message<'a, &'a [u8]>>, DecodeError>
+    where
+        R: io::Read,
+    {
+        match self.raw_decoder.read_from(reader) {
+            Ok(Some(frame)) => {
+                self.decoder.from_frame(frame)?;
+                Ok(Some(self.message()))
+            }
+            Ok(None) => Ok(None),
+            Err(DecodeError::Io(err)) => Err(DecodeError::Io(err)),
+            Err(err) => {
+                self.record_raw_error(&err);
+                Err(err)
+            }
         }
     }
 
+    fn record_raw_error(&mut self, err: &DecodeError) {
+        let len = self.raw_decoder.current_frame_len().unwrap_or(0);
+        self.decoder.record_error(len, err);
+    }
+
     #[inline]
     pub fn message(&self) -> Message<&[u8]> {
         Message {
@@ -371,6 +764,84 @@ where
     }
 }
 
+/// Decodes all FIX messages in a contiguous byte slice, e.g. a log file, one at
+/// a time. See [`Decoder::decode_stream`].
+///
+/// Message boundaries are determined by `BodyLength <9>`, and whitespace between
+/// messages (e.g. newlines) is ignored. Messages that can't be decoded are
+/// reported as `Err` and skipped, so that decoding resumes with the next
+/// message. Garbled data, e.g. log line prefixes, is also reported as `Err` and
+/// skipped up to the next `8=FIX`.
+///
+/// [`DecodeStream`] is not an [`Iterator`], because each [`Message`] borrows
+/// the internal state of the [`Decoder`] and is thus only valid until the next
+/// call to [`DecodeStream::next`].
+#[derive(Debug)]
+pub struct DecodeStream<'a, C = Config>
+where
+    C: Configure,
+{
+    decoder: &'a mut Decoder<C>,
+    data: &'a [u8],
+}
+
+impl<'a, C> DecodeStream<'a, C>
+where
+    C: Configure,
+{
+    /// Decodes the next FIX message. Returns [`None`] once all data was
+    /// consumed. A truncated message at the end of the data results in
+    /// [`DecodeError::TooShort`] and ends the stream.
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Option<Result<Message<'_, &'a [u8]>, DecodeError>> {
+        let start = self
+            .data
+            .iter()
+            .position(|byte| !byte.is_ascii_whitespace())
+            .unwrap_or(self.data.len());
+        self.data = &self.data[start..];
+        if self.data.is_empty() {
+            return None;
+        }
+        let len = match self.decoder.raw_decoder.message_len(self.data) {
+            Ok(Some(len)) if len <= self.data.len() => len,
+            Ok(_) => {
+                let len = self.data.len();
+                let err = DecodeError::TooShort { len };
+                self.data = &[];
+                self.decoder.record_error(len, &err);
+                return Some(Err(err));
+            }
+            Err(err) => {
+                let skipped = skip_to_begin_string(self.data);
+                self.data = &self.data[skipped..];
+                self.decoder.record_resync(skipped);
+                return Some(Err(err));
+            }
+        };
+        let (frame, rest) = self.data.split_at(len);
+        self.data = rest;
+        Some(self.decoder.decode(frame))
+    }
+
+    /// Returns the data that was not consumed yet, starting with the next
+    /// message (if any).
+    pub fn remainder(&self) -> &'a [u8] {
+        self.data
+    }
+}
+
+/// Returns the index of the next `8=FIX` in `data`, ignoring the very first
+/// byte, or `data.len()` if there's none.
+fn skip_to_begin_string(data: &[u8]) -> usize {
+    const BEGIN_STRING_PREFIX: &[u8] = b"8=FIX";
+    data.windows(BEGIN_STRING_PREFIX.len())
+        .skip(1)
+        .position(|window| window == BEGIN_STRING_PREFIX)
+        .map(|i| i + 1)
+        .unwrap_or_else(|| data.len())
+}
+
 /// A repeating group within a [`Message`].
 #[derive(Debug, Clone)]
 pub struct MessageGroup<'a, T>
@@ -408,21 +879,12 @@ where
     where
         F: IsFieldDefinition,
     {
-        let field_locator_of_group_tag = FieldLocator::TopLevel { tag: field.tag() };
-        let num_in_group = self
-            .group
-            .message
-            .builder
-            .fields
-            .get(&field_locator_of_group_tag)?;
-        let index_of_group_tag = num_in_group.2 as u32;
-        let field_value_str = std::str::from_utf8(num_in_group.1).ok()?;
-        let num_entries = str::parse(field_value_str).unwrap();
-        Some(Ok(MessageGroup {
-            message: self.group.message.clone(),
-            index_of_group_tag,
-            len: num_entries,
-        }))
+        let field_locator_of_group_tag = FieldLocator::WithinGroup {
+            tag: field.tag(),
+            index_of_group_tag: self.group.index_of_group_tag,
+            entry_index: self.entry_index,
+        };
+        self.group.message.group_at(field_locator_of_group_tag)
     }
 
     fn fv_raw<F>(&self, field: &F) -> Option<&[u8]>
@@ -495,8 +957,12 @@ impl<'a, T> Message<'a, T>
 where
     T: AsRef<[u8]>,
 {
-    /// Returns an [`Iterator`] over all fields in `self`, in sequential order
-    /// starting from the very first field.
+    /// Returns an [`Iterator`] over all fields in `self`, in wire order
+    /// starting from the very first field. `BodyLength <9>` and `CheckSum
+    /// <10>` are not included.
+    ///
+    /// Use [`Message::fields_with_locators`] to also learn about the
+    /// repeating group that each field belongs to, if any.
     ///
     /// ```
     /// use fefix::prelude::*;
@@ -505,7 +971,10 @@ where
     ///
     /// let mut decoder = Decoder::<Config>::new(Dictionary::fix44());
     /// decoder.config_mut().set_separator(b'|');
-    /// assert_eq!(decoder.config().separator(), b'|');
+    /// let data = b"8=FIX.4.4|9=17|35=0|34=12|112=X|10=000|";
+    /// let message = decoder.decode(&data[..]).unwrap();
+    /// let tags: Vec<u16> = message.fields().map(|(tag, _)| tag.get()).collect();
+    /// assert_eq!(tags, vec![8, 35, 34, 112]);
     /// ```
     pub fn fields(&'a self) -> Fields<'a, T> {
         Fields {
@@ -514,6 +983,37 @@ where
         }
     }
 
+    /// Like [`Message::fields`], but every field comes together with its
+    /// [`FieldLocator`], which tells whether it belongs to a repeating group
+    /// and in which entry. This allows generic tooling (e.g. loggers and
+    /// converters) to rebuild the structure of messages without any prior
+    /// knowledge about them.
+    ///
+    /// ```
+    /// use fefix::tagvalue::{Config, Configure, Decoder, FieldLocator};
+    /// use fefix::Dictionary;
+    ///
+    /// let mut decoder = Decoder::<Config>::new(Dictionary::fix44());
+    /// decoder.config_mut().set_separator(b'|');
+    /// let data = b"8=FIX.4.4|9=39|35=V|262=A|146=2|55=EUR/USD|55=GBP/USD|10=000|";
+    /// let message = decoder.decode(&data[..]).unwrap();
+    /// let entries: Vec<Option<u32>> = message
+    ///     .fields_with_locators()
+    ///     .filter(|(locator, _)| locator.tag().get() == 55)
+    ///     .map(|(locator, _)| match locator {
+    ///         FieldLocator::WithinGroup { entry_index, .. } => Some(entry_index),
+    ///         _ => None,
+    ///     })
+    ///     .collect();
+    /// assert_eq!(entries, vec![Some(0), Some(1)]);
+    /// ```
+    pub fn fields_with_locators(&'a self) -> FieldsWithLocators<'a, T> {
+        FieldsWithLocators {
+            message: self,
+            i: 0,
+        }
+    }
+
     pub fn as_bytes(&self) -> &[u8] {
         self.builder.bytes
     }
@@ -521,46 +1021,329 @@ where
     pub fn len(&self) -> usize {
         self.builder.field_locators.len()
     }
-}
 
-#[derive(Debug, Copy, Clone)]
-struct DecoderGroupState {
-    first_tag_of_every_group_entry: TagU16,
-    num_entries: usize,
-    current_entry_i: usize,
-    index_of_group_tag: usize,
-}
-
-#[derive(Debug, Copy, Clone)]
-struct DecoderStateNewGroup {
-    tag: TagU16,
-    index_of_group_tag: usize,
-    num_entries: usize,
-}
-
-#[derive(Debug, Clone)]
-struct DecoderState {
-    group_information: Vec<DecoderGroupState>,
-    new_group: Option<DecoderStateNewGroup>,
-    data_field_length: Option<usize>,
-}
-
-impl DecoderState {
-    fn current_field_locator(&self, tag: TagU16) -> FieldLocator {
-        match self.group_information.last() {
-            Some(group_info) => FieldLocator::WithinGroup {
-                tag,
-                index_of_group_tag: group_info.index_of_group_tag as u32,
-                entry_index: group_info.current_entry_i as u32,
-            },
-            None => FieldLocator::TopLevel { tag },
-        }
+    /// Returns the [`Dictionary`] that `self` was decoded with, i.e. the
+    /// application-layer [`Dictionary`] of `FIXT.1.1` messages, if any. See
+    /// [`Decoder::add_appl_ver_dictionary`].
+    pub fn dictionary(&self) -> &Dictionary {
+        &self.builder.dict
+    }
+
+    /// Returns all tags that appeared more than once outside of repeating
+    /// groups, in order of appearance of their duplicates. It is empty for
+    /// all well-formed FIX messages.
+    ///
+    /// [`Configure::duplicate_tag_policy`] determines which occurrence is
+    /// kept.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix::dict::IsFieldDefinition;
+    /// use fefix::tagvalue::{Config, Decoder, DuplicateTagPolicy, FieldAccess};
+    /// use fefix::definitions::fix44;
+    /// use fefix::Dictionary;
+    ///
+    /// let mut decoder = Decoder::<Config>::new(Dictionary::fix44());
+    /// decoder.config_mut().set_separator(b'|');
+    /// decoder
+    ///     .config_mut()
+    ///     .set_duplicate_tag_policy(DuplicateTagPolicy::KeepFirst);
+    /// let data = b"8=FIX.4.4|9=17|35=0|112=A|112=B|10=100|";
+    /// let message = decoder.decode(&data[..]).unwrap();
+    /// assert_eq!(message.duplicate_tags(), &[fix44::TEST_REQ_ID.tag()]);
+    /// assert_eq!(message.fv(fix44::TEST_REQ_ID), Ok("A"));
+    /// ```
+    pub fn duplicate_tags(&self) -> &[TagU16] {
+        &self.builder.duplicate_tags[..]
+    }
+
+    /// Returns all failed integrity checks that were tolerated while decoding
+    /// `self`. See [`RawFrame::warnings`].
+    pub fn warnings(&self) -> &[DecodeError] {
+        &self.builder.warnings[..]
+    }
+
+    /// Returns an [`Iterator`] over every field occurrence in `self`, in no
+    /// particular order. Unlike [`Message::fields_with_locators`], this
+    /// includes the occurrences of duplicate tags that were discarded
+    /// according to [`Configure::duplicate_tag_policy`].
+    pub(crate) fn occurrences(&self) -> impl Iterator<Item = (FieldLocator, &'a [u8])> + 'a {
+        self.builder
+            .field_locators
+            .iter()
+            .chain(self.builder.hidden_duplicates.iter())
+            .copied()
+    }
+
+    /// Returns a copy of `self` over borrowed bytes, regardless of the
+    /// original type parameter `T`.
+    pub(crate) fn as_borrowed(&self) -> Message<'a, &'a [u8]> {
+        Message {
+            builder: self.builder,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Queries `self` for the top-level field with `tag` and returns its raw
+    /// contents. Unlike [`FieldAccess::fv_raw`], no field definition is
+    /// necessary.
+    pub fn fv_raw_by_tag(&self, tag: TagU16) -> Option<&'a [u8]> {
+        let field_locator = FieldLocator::TopLevel { tag };
+        self.builder.fields.get(&field_locator).map(|field| field.1)
+    }
+
+    /// Queries `self` for the top-level field named `name` (e.g. `"ClOrdID"`)
+    /// and returns its raw contents. Field names are resolved through the
+    /// [`Dictionary`] that was used for decoding; unknown names result in
+    /// [`None`].
+    pub fn fv_raw_by_name(&self, name: &str) -> Option<&'a [u8]> {
+        let tag = self.builder.dict.field_by_name(name)?.tag();
+        self.fv_raw_by_tag(tag)
+    }
+
+    /// Queries `self` for the top-level field named `name` (e.g. `"ClOrdID"`)
+    /// and deserializes it. This is the name-based equivalent of
+    /// [`FieldAccess::fv`], mostly meant for exploratory tools and tests: tag
+    /// based getters are faster.
+    ///
+    /// Unknown field names result in
+    /// [`OptError::None`](crate::OptError::None), just like missing fields.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix::prelude::*;
+    /// use fefix::tagvalue::{Config, Configure, Decoder};
+    ///
+    /// let mut decoder = Decoder::<Config>::new(Dictionary::fix44());
+    /// decoder.config_mut().set_separator(b'|');
+    /// let data = b"8=FIX.4.4|9=42|35=0|49=A|56=B|34=12|52=20100304-07:59:30|10=185|";
+    /// let message = decoder.decode(data).unwrap();
+    ///
+    /// assert_eq!(message.fv_by_name::<&str>("SenderCompID"), Ok("A"));
+    /// assert_eq!(message.fv_by_name::<u64>("MsgSeqNum"), Ok(12));
+    /// assert!(message.fv_by_name::<&str>("ClOrdID").is_err());
+    /// ```
+    pub fn fv_by_name<V>(&self, name: &str) -> OptResult<V, V::Error>
+    where
+        V: FixValue<'a>,
+    {
+        self.fv_by_name_opt(name).into_opt_result()
+    }
+
+    /// Like [`Message::fv_by_name`], but missing fields (and unknown names)
+    /// result in [`None`] rather than [`Err`].
+    pub fn fv_by_name_opt<V>(&self, name: &str) -> Option<Result<V, V::Error>>
+    where
+        V: FixValue<'a>,
+    {
+        self.fv_raw_by_name(name).map(V::deserialize)
+    }
+
+    /// Returns a [`MessageFmt`] that renders `self` in a human-readable form,
+    /// with field names and enumeration descriptions taken from `dict`.
+    pub fn display_with(&'a self, dict: &'a Dictionary) -> MessageFmt<'a, T> {
+        MessageFmt::new(self, dict)
+    }
+
+    /// Copies the contents of `self` into a [`MessageOwned`], which doesn't
+    /// borrow from the [`Decoder`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix::definitions::fix44;
+    /// use fefix::prelude::*;
+    /// use fefix::tagvalue::{Config, Configure, Decoder};
+    ///
+    /// let mut decoder = Decoder::<Config>::new(Dictionary::fix44());
+    /// decoder.config_mut().set_separator(b'|');
+    /// let data = b"8=FIX.4.4|9=42|35=0|49=A|56=B|34=12|52=20100304-07:59:30|10=185|";
+    /// let message = decoder.decode(data).unwrap().to_owned();
+    /// // `decoder` can now be reused.
+    /// decoder.decode(data).unwrap();
+    /// assert_eq!(message.message().fv(fix44::SENDER_COMP_ID), Ok("A"));
+    /// ```
+    pub fn to_owned(&self) -> MessageOwned {
+        MessageOwned::new(self.builder.bytes.to_vec(), self.builder)
+    }
+
+    fn group_at(
+        &self,
+        field_locator_of_group_tag: FieldLocator,
+    ) -> Option<Result<MessageGroup<'a, T>, <usize as FixValue<'a>>::Error>> {
+        let num_in_group = self.builder.fields.get(&field_locator_of_group_tag)?;
+        let index_of_group_tag = num_in_group.2 as u32;
+        Some(
+            usize::deserialize(num_in_group.1).map(|num_entries| MessageGroup {
+                message: Message {
+                    builder: self.builder,
+                    phantom: PhantomData,
+                },
+                index_of_group_tag,
+                len: num_entries,
+            }),
+        )
+    }
+}
+
+/// An owned FIX message, obtained with [`Message::to_owned`].
+///
+/// [`MessageOwned`] holds a copy of the original message bytes together with
+/// the decoded field index, so it can be stored or sent across threads. Use
+/// [`MessageOwned::message`] to access its fields.
+#[derive(Debug)]
+pub struct MessageOwned {
+    // All field values point into `bytes`. The lifetime is a lie that is never
+    // exposed: `message` shrinks it back to a borrow of `self`. Fields are
+    // dropped in declaration order, so `builder` goes before `bytes`.
+    builder: MessageBuilder<'static>,
+    bytes: Box<[u8]>,
+}
+
+impl MessageOwned {
+    fn new(bytes: Vec<u8>, original: &MessageBuilder) -> Self {
+        // Converting to a boxed slice may reallocate, so it must happen before
+        // any slice into `bytes` is taken.
+        let bytes = bytes.into_boxed_slice();
+        let builder = original.rebased(&bytes[..]);
+        // SAFETY: `builder` only borrows from the heap allocation of `bytes`
+        // (`rebased` also resets `raw` to a `'static` empty slice). The
+        // allocation stays at the same address when `Self` is moved, and
+        // `bytes` can't be reallocated or mutated because it's a private boxed
+        // slice that no method hands out mutably. `builder` is dropped before
+        // `bytes` and `MessageBuilder` has no `Drop` implementation, so no
+        // dangling slice is ever read.
+        let builder =
+            unsafe { core::mem::transmute::<MessageBuilder<'_>, MessageBuilder<'static>>(builder) };
+        Self { builder, bytes }
+    }
+
+    /// Returns a [`Message`] that borrows from `self`, with all the usual
+    /// getters.
+    pub fn message(&self) -> Message<&[u8]> {
+        Message {
+            builder: &self.builder,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Returns the raw bytes of `self`, i.e. the full FIX message.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes[..]
+    }
+
+    /// Returns the number of fields in `self`.
+    pub fn len(&self) -> usize {
+        self.builder.field_locators.len()
+    }
+
+    /// Returns `true` if and only if `self` has no fields.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Clone for MessageOwned {
+    fn clone(&self) -> Self {
+        Self::new(self.bytes.to_vec(), &self.builder)
+    }
+}
+
+impl PartialEq for MessageOwned {
+    fn eq(&self, other: &Self) -> bool {
+        self.message() == other.message()
+    }
+}
+
+impl Eq for MessageOwned {}
+
+/// Collects the tags of all fields which may appear within every repeating
+/// group in `dict`, indexed by `NumInGroup` tag. Fields that come from
+/// components are included, and so are the `NumInGroup` tags of nested groups.
+pub(crate) fn group_members(dict: &Dictionary) -> IntMap<u16, IntSet<u16>> {
+    fn add_members(members: &mut IntSet<u16>, items: &[dict::LayoutItem]) {
+        for item in items {
+            match item.kind() {
+                dict::LayoutItemKind::Field(field) => {
+                    members.insert(field.tag().get());
+                }
+                dict::LayoutItemKind::Group(field, _items) => {
+                    members.insert(field.tag().get());
+                }
+                dict::LayoutItemKind::Component(component) => {
+                    add_members(members, &component.items().collect::<Vec<_>>());
+                }
+            }
+        }
+    }
+
+    fn find_groups(groups: &mut IntMap<u16, IntSet<u16>>, items: &[dict::LayoutItem]) {
+        for item in items {
+            if let dict::LayoutItemKind::Group(field, group_items) = item.kind() {
+                add_members(groups.entry(field.tag().get()).or_default(), &group_items);
+                find_groups(groups, &group_items);
+            }
+        }
+    }
+
+    let mut groups = IntMap::default();
+    for message in dict.iter_messages() {
+        find_groups(&mut groups, &message.layout().collect::<Vec<_>>());
+    }
+    for component in dict.iter_components() {
+        find_groups(&mut groups, &component.items().collect::<Vec<_>>());
+    }
+    groups
+}
+
+#[derive(Debug, Copy, Clone)]
+struct DecoderGroupState {
+    num_in_group_tag: TagU16,
+    first_tag_of_every_group_entry: TagU16,
+    num_entries: usize,
+    current_entry_i: usize,
+    index_of_group_tag: usize,
+}
+
+#[derive(Debug, Copy, Clone)]
+struct DecoderStateNewGroup {
+    tag: TagU16,
+    index_of_group_tag: usize,
+    num_entries: usize,
+}
+
+#[derive(Debug, Clone)]
+struct DecoderState {
+    group_information: Vec<DecoderGroupState>,
+    new_group: Option<DecoderStateNewGroup>,
+    data_field_length: Option<usize>,
+}
+
+impl DecoderState {
+    fn clear(&mut self) {
+        self.group_information.clear();
+        self.new_group = None;
+        self.data_field_length = None;
+    }
+
+    fn current_field_locator(&self, tag: TagU16) -> FieldLocator {
+        match self.group_information.last() {
+            Some(group_info) => FieldLocator::WithinGroup {
+                tag,
+                index_of_group_tag: group_info.index_of_group_tag as u32,
+                entry_index: group_info.current_entry_i as u32,
+            },
+            None => FieldLocator::TopLevel { tag },
+        }
     }
 
     fn set_new_group(&mut self, tag: TagU16) {
         assert!(self.new_group.is_some());
         let new_group = self.new_group.take().unwrap();
         self.group_information.push(DecoderGroupState {
+            num_in_group_tag: new_group.tag,
             first_tag_of_every_group_entry: tag,
             num_entries: new_group.num_entries,
             current_entry_i: 0,
@@ -568,9 +1351,41 @@ impl DecoderState {
         });
     }
 
-    fn add_group(&mut self, tag: TagU16, index_of_group_tag: usize, field_value: &[u8]) {
-        let field_value_str = std::str::from_utf8(field_value).unwrap();
-        let num_entries = str::parse(field_value_str).unwrap();
+    /// Updates the group information of `self` after reading `tag`. Groups
+    /// are closed, starting from the innermost, until `tag` is found to belong
+    /// to the current group entry or a new entry begins.
+    fn update_groups(
+        &mut self,
+        tag: TagU16,
+        active_group_members: Option<&IntMap<u16, IntSet<u16>>>,
+        group_members: &IntMap<u16, IntSet<u16>>,
+    ) {
+        while let Some(group_info) = self.group_information.last_mut() {
+            if tag == group_info.first_tag_of_every_group_entry {
+                group_info.current_entry_i += 1;
+                if group_info.current_entry_i < group_info.num_entries {
+                    break;
+                }
+                // We've gone past the last group entry, which means this
+                // occurrence of `tag` belongs to some outer context.
+            } else {
+                let num_in_group_tag = group_info.num_in_group_tag.get();
+                let is_member = active_group_members
+                    .and_then(|members| members.get(&num_in_group_tag))
+                    .or_else(|| group_members.get(&num_in_group_tag))
+                    .map(|members| members.contains(&tag.get()))
+                    // If the dictionary has no information about this group,
+                    // there's no way to tell where it ends.
+                    .unwrap_or(true);
+                if is_member {
+                    break;
+                }
+            }
+            self.group_information.pop();
+        }
+    }
+
+    fn add_group(&mut self, tag: TagU16, index_of_group_tag: usize, num_entries: usize) {
         if num_entries > 0 {
             self.new_group = Some(DecoderStateNewGroup {
                 tag,
@@ -587,36 +1402,111 @@ pub struct MessageBuilder<'a> {
     state: DecoderState,
     raw: &'a [u8],
     fields: HashMap<FieldLocator, (TagU16, &'a [u8], usize)>,
-    field_locators: Vec<FieldLocator>,
+    // All fields in wire order, regardless of associative decoding.
+    field_locators: Vec<(FieldLocator, &'a [u8])>,
+    duplicate_tags: Vec<TagU16>,
+    // The occurrences of duplicate tags that were discarded according to the
+    // `DuplicateTagPolicy`, so that `Message::occurrences` can still yield them.
+    hidden_duplicates: Vec<(FieldLocator, &'a [u8])>,
+    warnings: Vec<DecodeError>,
     i_first_cell: usize,
     i_last_cell: usize,
     len_end_header: usize,
     len_end_body: usize,
     len_end_trailer: usize,
     bytes: &'a [u8],
+    dict: Dictionary,
 }
 
 impl<'a> MessageBuilder<'a> {
+    /// Resets `self` for the next message. All internal storage keeps its
+    /// capacity.
     fn clear(&mut self) {
+        self.state.clear();
         self.raw = b"";
+        self.bytes = b"";
         self.fields.clear();
         self.field_locators.clear();
+        self.duplicate_tags.clear();
+        self.hidden_duplicates.clear();
+        self.warnings.clear();
     }
 
+    /// Fails only if `tag` is a duplicate and `policy` is
+    /// [`DuplicateTagPolicy::Reject`].
     fn add_field(
         &mut self,
         tag: TagU16,
         field_value: &'a [u8],
         associative: bool,
-    ) -> Result<(), DecodeError> {
+        policy: DuplicateTagPolicy,
+    ) -> Result<(), ()> {
         let field_locator = self.state.current_field_locator(tag);
         let i = self.field_locators.len();
         if associative {
+            if let Some(field) = self.fields.get_mut(&field_locator) {
+                // Duplicates keep the position of the first occurrence, so
+                // that iteration yields every field only once.
+                self.duplicate_tags.push(tag);
+                match policy {
+                    DuplicateTagPolicy::Reject => return Err(()),
+                    DuplicateTagPolicy::KeepFirst => {
+                        self.hidden_duplicates.push((field_locator, field_value));
+                    }
+                    DuplicateTagPolicy::KeepLast => {
+                        self.hidden_duplicates.push((field_locator, field.1));
+                        field.1 = field_value;
+                        self.field_locators[field.2].1 = field_value;
+                    }
+                }
+                return Ok(());
+            }
             self.fields.insert(field_locator, (tag, field_value, i));
         }
-        self.field_locators.push(field_locator);
+        self.field_locators.push((field_locator, field_value));
         Ok(())
     }
+
+    /// Returns a copy of `self` with all field values pointing into `bytes`
+    /// rather than `self.bytes`. `bytes` must have the same contents as
+    /// `self.bytes`.
+    fn rebased<'b>(&self, bytes: &'b [u8]) -> MessageBuilder<'b> {
+        debug_assert_eq!(self.bytes, bytes);
+        let base = self.bytes.as_ptr() as usize;
+        let rebase = |value: &[u8]| {
+            let start = value.as_ptr() as usize - base;
+            &bytes[start..][..value.len()]
+        };
+        let fields = self
+            .fields
+            .iter()
+            .map(|(locator, (tag, value, i))| (*locator, (*tag, rebase(value), *i)))
+            .collect();
+        MessageBuilder {
+            state: self.state.clone(),
+            raw: b"",
+            fields,
+            field_locators: self
+                .field_locators
+                .iter()
+                .map(|(locator, value)| (*locator, rebase(value)))
+                .collect(),
+            duplicate_tags: self.duplicate_tags.clone(),
+            hidden_duplicates: self
+                .hidden_duplicates
+                .iter()
+                .map(|(locator, value)| (*locator, rebase(value)))
+                .collect(),
+            warnings: self.warnings.clone(),
+            i_first_cell: self.i_first_cell,
+            i_last_cell: self.i_last_cell,
+            len_end_header: self.len_end_header,
+            len_end_body: self.len_end_body,
+            len_end_trailer: self.len_end_trailer,
+            bytes,
+            dict: self.dict.clone(),
+        }
+    }
 }
 
 /// An [`Iterator`] over fields and groups within a FIX message.
@@ -639,14 +1529,37 @@ where
         if self.i == self.message.len() {
             None
         } else {
-            let context = self.message.builder.field_locators[self.i];
-            let field = self.message.builder.fields.get(&context).unwrap();
+            let (locator, value) = self.message.builder.field_locators[self.i];
             self.i += 1;
-            Some((field.0, field.1))
+            Some((locator.tag(), value))
         }
     }
 }
 
+/// An [`Iterator`] over fields within a FIX message, together with their
+/// [`FieldLocator`]. See [`Message::fields_with_locators`].
+#[derive(Debug)]
+pub struct FieldsWithLocators<'a, T>
+where
+    T: AsRef<[u8]>,
+{
+    message: &'a Message<'a, T>,
+    i: usize,
+}
+
+impl<'a, T> Iterator for FieldsWithLocators<'a, T>
+where
+    T: AsRef<[u8]>,
+{
+    type Item = (FieldLocator, &'a [u8]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let field = self.message.builder.field_locators.get(self.i)?;
+        self.i += 1;
+        Some(*field)
+    }
+}
+
 impl<'a, T> FieldAccess for Message<'a, T>
 where
     T: AsRef<[u8]> + Clone,
@@ -657,20 +1570,8 @@ where
     where
         F: IsFieldDefinition,
     {
-        // FIXME
         let field_locator_of_group_tag = FieldLocator::TopLevel { tag: field.tag() };
-        let num_in_group = self.builder.fields.get(&field_locator_of_group_tag)?;
-        let index_of_group_tag = num_in_group.2 as u32;
-        let field_value_str = std::str::from_utf8(num_in_group.1).ok()?;
-        let num_entries = str::parse(field_value_str).unwrap();
-        Some(Ok(MessageGroup {
-            message: Message {
-                builder: self.builder,
-                phantom: PhantomData::default(),
-            },
-            index_of_group_tag,
-            len: num_entries,
-        }))
+        self.group_at(field_locator_of_group_tag)
     }
 
     fn fv_raw<F>(&self, field: &F) -> Option<&[u8]>
@@ -724,10 +1625,38 @@ where
     i: usize,
 }
 
+fn parse_tag(digits: &[u8]) -> Option<TagU16> {
+    if digits.is_empty() || digits.len() > 5 {
+        return None;
+    }
+    let mut tag = 0u32;
+    for byte in digits {
+        if !byte.is_ascii_digit() {
+            return None;
+        }
+        tag = tag * 10 + (byte - b'0') as u32;
+    }
+    if tag > u16::MAX as u32 {
+        None
+    } else {
+        TagU16::new(tag as u16)
+    }
+}
+
+/// A FIX 4.4 [`Decoder`] that uses `|` as separator, for unit tests.
+#[cfg(test)]
+pub(crate) fn test_decoder() -> Decoder<Config> {
+    let mut config = Config::default();
+    config.set_separator(b'|');
+    Decoder::with_config(Dictionary::fix44(), config)
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::{definitions::fix44, tagvalue::Config};
+    #[cfg(feature = "std")]
+    use crate::tagvalue::Verification;
+    use crate::{definitions::fix44, tagvalue::Config, OptError, OptResultExt};
 
     // Use http://www.validfix.com/fix-analyzer.html for testing.
 
@@ -735,21 +1664,276 @@ mod test {
         msg.split("|").collect::<Vec<&str>>().join("\x01")
     }
 
-    fn decoder() -> Decoder<Config> {
-        let dict = Dictionary::fix44();
-        let mut config = Config::default();
-        config.set_separator(b'|');
-        Decoder::with_config(dict, config)
+    #[test]
+    fn decode_stream_yields_every_message() {
+        let log = "8=FIX.4.2|9=5|35=0|10=018|\n8=FIX.4.2|9=40|35=D|49=AFUNDMGR|56=ABROKER|15=USD|59=0|10=091|\r\n";
+        let mut decoder = test_decoder();
+        let mut stream = decoder.decode_stream(log.as_bytes());
+        assert_eq!(
+            stream.next().unwrap().unwrap().fv(fix44::MSG_TYPE),
+            Ok(b"0")
+        );
+        assert_eq!(
+            stream.next().unwrap().unwrap().fv(fix44::MSG_TYPE),
+            Ok(b"D")
+        );
+        assert!(stream.next().is_none());
+        assert!(stream.remainder().is_empty());
+    }
+
+    #[test]
+    fn decode_stream_skips_garbled_data() {
+        let log = "12:00:00 IN 8=FIX.4.2|9=5|35=0|10=018|";
+        let mut decoder = test_decoder();
+        let mut stream = decoder.decode_stream(log.as_bytes());
+        assert!(stream.next().unwrap().is_err());
+        assert_eq!(stream.remainder(), b"8=FIX.4.2|9=5|35=0|10=018|");
+        assert!(stream.next().unwrap().is_ok());
+        assert!(stream.next().is_none());
+    }
+
+    #[test]
+    fn decode_stream_ends_with_truncated_message() {
+        let log = "8=FIX.4.2|9=5|35=0|10=018|8=FIX.4.2|9=5|35=0|10=";
+        let mut decoder = test_decoder();
+        let mut stream = decoder.decode_stream(log.as_bytes());
+        assert!(stream.next().unwrap().is_ok());
+        assert_eq!(
+            stream.next().unwrap().unwrap_err(),
+            DecodeError::TooShort { len: 22 }
+        );
+        assert!(stream.next().is_none());
+    }
+
+    #[test]
+    fn stats_count_stream_health() {
+        let log = "12:00:00 IN 8=FIX.4.2|9=5|35=0|10=018|8=FIX.4.2|9=5|35=0|10=";
+        let mut decoder = test_decoder();
+        let mut stream = decoder.decode_stream(log.as_bytes());
+        while stream.next().is_some() {}
+        let stats = *decoder.stats();
+        assert_eq!(stats.messages_decoded(), 1);
+        assert_eq!(stats.decode_errors(), 1);
+        assert_eq!(stats.resyncs(), 1);
+        assert_eq!(stats.bytes_processed(), log.len() as u64);
+        assert_eq!(stats.max_message_size(), 26);
+        decoder.reset_stats();
+        assert_eq!(decoder.stats(), &DecoderStats::default());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn stats_count_tolerated_checksum_failures() {
+        let mut decoder = test_decoder();
+        decoder
+            .config_mut()
+            .set_checksum_verification(Verification::WarnOnly);
+        let mut decoder = decoder.buffered();
+        let mut stream = &b"8=FIX.4.2|9=5|35=0|10=000|"[..];
+        assert!(decoder.read_from(&mut stream).unwrap().is_some());
+        assert_eq!(decoder.stats().messages_decoded(), 1);
+        assert_eq!(decoder.stats().checksum_failures(), 1);
     }
 
     #[test]
     fn can_parse_simple_message() {
         let message = "8=FIX.4.2|9=40|35=D|49=AFUNDMGR|56=ABROKER|15=USD|59=0|10=091|";
-        let mut decoder = decoder();
+        let mut decoder = test_decoder();
         let result = decoder.decode(message.as_bytes());
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn fields_can_be_accessed_by_name() {
+        let message = "8=FIX.4.2|9=40|35=D|49=AFUNDMGR|56=ABROKER|15=USD|59=0|10=091|";
+        let mut decoder = test_decoder();
+        let message = decoder.decode(message.as_bytes()).unwrap();
+        assert_eq!(message.fv_by_name::<&str>("Currency"), Ok("USD"));
+        assert_eq!(
+            message.fv_raw_by_name("TargetCompID"),
+            message.fv_raw(fix44::TARGET_COMP_ID)
+        );
+        assert_eq!(
+            message.fv_by_name::<&str>("NoSuchField"),
+            Err(OptError::None)
+        );
+        assert!(matches!(
+            message.fv_by_name::<u32>("SenderCompID"),
+            Err(OptError::Other(_))
+        ));
+        let owned = message.to_owned();
+        assert_eq!(
+            owned.message().fv_by_name::<&[u8]>("MsgType"),
+            Ok(&b"D"[..])
+        );
+    }
+
+    #[test]
+    fn fv_ctx_reports_tag_raw_value_and_type() {
+        let mut decoder = test_decoder();
+        let message = decoder
+            .decode(&b"8=FIX.4.4|9=16|35=D|49=A|34=x7|10=000|"[..])
+            .unwrap();
+        assert_eq!(message.fv_ctx::<&str, _>(fix44::SENDER_COMP_ID), Ok("A"));
+        let invalid = message.fv_ctx::<u64, _>(fix44::MSG_SEQ_NUM).unwrap_err();
+        assert_eq!(invalid.tag().get(), 34);
+        assert_eq!(invalid.name(), "MsgSeqNum");
+        assert_eq!(invalid.raw(), Some(&b"x7"[..]));
+        assert_eq!(invalid.type_name(), "u64");
+        assert!(!invalid.is_missing());
+        assert!(matches!(invalid.into_opt_error(), OptError::Other(_)));
+        let missing = message
+            .fv_ctx::<&str, _>(fix44::TARGET_COMP_ID)
+            .unwrap_err();
+        assert!(missing.is_missing());
+        assert_eq!(missing.raw(), None);
+        assert_eq!(
+            missing.to_string(),
+            "Missing TargetCompID <56>, expected as &str"
+        );
+        assert_eq!(missing.into_opt_error(), OptError::None);
+    }
+
+    #[test]
+    fn user_defined_tags_up_to_u16_max() {
+        let tags = [20000, 39999, 65535];
+        let mut encoder = crate::tagvalue::Encoder::<Config>::default();
+        encoder.config_mut().set_separator(b'|');
+        let mut buffer = Vec::new();
+        let mut msg = encoder.start_message(b"FIX.4.4", &mut buffer, b"D");
+        for tag in tags.iter() {
+            msg.set_any(TagU16::new(*tag).unwrap(), *tag as u32);
+        }
+        let data = msg.wrap().to_vec();
+        let mut decoder = test_decoder();
+        let message = decoder.decode(&data[..]).unwrap();
+        for tag in tags.iter() {
+            let tag = TagU16::new(*tag).unwrap();
+            assert_eq!(
+                message.fv_raw_by_tag(tag),
+                Some(tag.get().to_string().as_bytes())
+            );
+        }
+        for invalid in &["65536=x", "99999=x", "065535=x", "0=x"] {
+            let msg = format!("8=FIX.4.4|9={}|35=D|{}|10=000|", invalid.len() + 6, invalid);
+            assert!(matches!(
+                decoder.decode(msg.as_bytes()),
+                Err(DecodeError::Invalid { tag: None, .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn invalid_num_in_group_is_an_error() {
+        let msg = "8=FIX.4.4|9=22|35=V|146=x|55=EUR/USD|10=000|";
+        let mut decoder = test_decoder();
+        assert!(matches!(
+            decoder.decode(msg.as_bytes()),
+            Err(DecodeError::Invalid { tag: Some(tag), .. }) if tag.get() == 146
+        ));
+    }
+
+    #[test]
+    fn max_fields_is_enforced() {
+        let message = "8=FIX.4.2|9=40|35=D|49=AFUNDMGR|56=ABROKER|15=USD|59=0|10=091|";
+        let mut decoder = test_decoder();
+        decoder.config_mut().set_max_fields(Some(8));
+        assert!(decoder.decode(message.as_bytes()).is_ok());
+        decoder.config_mut().set_max_fields(Some(7));
+        assert!(matches!(
+            decoder.decode(message.as_bytes()),
+            Err(DecodeError::TooManyFields { max: 7 })
+        ));
+    }
+
+    #[test]
+    fn duplicate_tags_follow_the_configured_policy() {
+        let message = "8=FIX.4.4|9=22|35=0|112=A|34=2|112=B|10=000|";
+        let mut decoder = test_decoder();
+        let msg = decoder.decode(message.as_bytes()).unwrap();
+        assert_eq!(msg.fv(fix44::TEST_REQ_ID), Ok("B"));
+        assert_eq!(msg.duplicate_tags(), &[fix44::TEST_REQ_ID.tag()]);
+        assert_eq!(msg.fields().count(), 4);
+        decoder
+            .config_mut()
+            .set_duplicate_tag_policy(DuplicateTagPolicy::KeepFirst);
+        let msg = decoder.decode(message.as_bytes()).unwrap();
+        assert_eq!(msg.fv(fix44::TEST_REQ_ID), Ok("A"));
+        decoder
+            .config_mut()
+            .set_duplicate_tag_policy(DuplicateTagPolicy::Reject);
+        assert!(matches!(
+            decoder.decode(message.as_bytes()),
+            Err(DecodeError::DuplicateTag { offset: 31, tag }) if tag == fix44::TEST_REQ_ID.tag()
+        ));
+    }
+
+    #[test]
+    fn well_formed_messages_have_no_duplicate_tags() {
+        let mut decoder = test_decoder();
+        decoder
+            .config_mut()
+            .set_duplicate_tag_policy(DuplicateTagPolicy::Reject);
+        for msg in RANDOM_MESSAGES {
+            let message = decoder.decode(msg.as_bytes()).unwrap();
+            assert!(message.duplicate_tags().is_empty());
+        }
+    }
+
+    #[test]
+    fn fields_are_iterated_in_wire_order_without_associative_decoding() {
+        let message = "8=FIX.4.4|9=22|35=0|112=A|34=2|112=B|10=000|";
+        let mut decoder = test_decoder();
+        decoder.config_mut().set_decode_assoc(false);
+        let msg = decoder.decode(message.as_bytes()).unwrap();
+        let fields: Vec<(u16, &[u8])> = msg.fields().map(|(tag, v)| (tag.get(), v)).collect();
+        assert_eq!(
+            fields,
+            vec![
+                (8, b"FIX.4.4" as &[u8]),
+                (35, b"0"),
+                (112, b"A"),
+                (34, b"2"),
+                (112, b"B"),
+            ]
+        );
+    }
+
+    #[test]
+    fn field_locators_describe_group_entries() {
+        let message = RANDOM_MESSAGES[6];
+        let mut decoder = test_decoder();
+        let msg = decoder.decode(message.as_bytes()).unwrap();
+        let index_of_group_tag = msg
+            .fields()
+            .position(|(tag, _)| tag == fix44::NO_MD_ENTRIES.tag())
+            .unwrap() as u32;
+        let locators: Vec<FieldLocator> = msg
+            .fields_with_locators()
+            .map(|(locator, _)| locator)
+            .filter(|locator| locator.tag() == fix44::MD_ENTRY_PX.tag())
+            .collect();
+        assert_eq!(
+            locators,
+            vec![
+                FieldLocator::WithinGroup {
+                    tag: fix44::MD_ENTRY_PX.tag(),
+                    index_of_group_tag,
+                    entry_index: 0,
+                },
+                FieldLocator::WithinGroup {
+                    tag: fix44::MD_ENTRY_PX.tag(),
+                    index_of_group_tag,
+                    entry_index: 1,
+                },
+            ]
+        );
+        assert!(msg
+            .fields_with_locators()
+            .take(3)
+            .all(|(locator, _)| matches!(locator, FieldLocator::TopLevel { .. })));
+    }
+
     const RANDOM_MESSAGES: &[&str] = &[
         "8=FIX.4.2|9=42|35=0|49=A|56=B|34=12|52=20100304-07:59:30|10=185|",
         "8=FIX.4.2|9=97|35=6|49=BKR|56=IM|34=14|52=20100204-09:18:42|23=115685|28=N|55=SPMI.MI|54=2|44=2200.75|27=S|25=H|10=248|",
@@ -763,7 +1947,7 @@ mod test {
     #[test]
     fn skip_checksum_verification() {
         let message = "8=FIX.FOOBAR|9=5|35=0|10=000|";
-        let mut decoder = decoder();
+        let mut decoder = test_decoder();
         let result = decoder.decode(message.as_bytes());
         assert!(result.is_ok());
     }
@@ -771,7 +1955,7 @@ mod test {
     #[test]
     fn repeating_group_entries() {
         let bytes = b"8=FIX.4.2|9=196|35=X|49=A|56=B|34=12|52=20100318-03:21:11.364|262=A|268=2|279=0|269=0|278=BID|55=EUR/USD|270=1.37215|15=EUR|271=2500000|346=1|279=0|269=1|278=OFFER|55=EUR/USD|270=1.37224|15=EUR|271=2503200|346=1|10=171|";
-        let decoder = &mut decoder();
+        let decoder = &mut test_decoder();
         let message = decoder.decode(bytes).unwrap();
         let group = message.group(fix44::NO_MD_ENTRIES).unwrap();
         assert_eq!(group.len(), 2);
@@ -781,10 +1965,84 @@ mod test {
         );
     }
 
+    #[test]
+    fn repeating_group_entries_iteration() {
+        let bytes =
+            b"8=FIX.4.4|9=58|35=X|268=2|279=0|269=0|270=1.5|279=1|269=1|270=1.75|813=1|10=000|";
+        let decoder = &mut test_decoder();
+        let message = decoder.decode(bytes).unwrap();
+        let group = message.group(fix44::NO_MD_ENTRIES).unwrap();
+        let entry_types: Vec<fix44::MdEntryType> = group
+            .entries()
+            .map(|entry| entry.fv(fix44::MD_ENTRY_TYPE).unwrap())
+            .collect();
+        assert_eq!(
+            entry_types,
+            vec![fix44::MdEntryType::Bid, fix44::MdEntryType::Offer]
+        );
+        assert_eq!(
+            group.entry(1).fv_raw(fix44::MD_ENTRY_PX),
+            Some(b"1.75" as &[u8])
+        );
+        assert_eq!(message.fv_raw(fix44::APPL_QUEUE_DEPTH), Some(b"1" as &[u8]));
+    }
+
+    #[test]
+    fn optional_getters_treat_absence_as_normal() {
+        let bytes =
+            b"8=FIX.4.4|9=58|35=X|268=2|279=0|269=0|270=1.5|279=1|269=1|270=1.75|813=1|10=000|";
+        let decoder = &mut test_decoder();
+        let message = decoder.decode(bytes).unwrap();
+        let group = message.group_opt(fix44::NO_MD_ENTRIES).unwrap().unwrap();
+        assert!(group.entry_opt(1).is_some());
+        assert!(group.entry_opt(2).is_none());
+        assert!(message.group_opt(fix44::NO_RELATED_SYM).is_none());
+        assert_eq!(message.fv_by_name_opt::<u32>("ApplQueueDepth"), Some(Ok(1)));
+        assert_eq!(message.fv_by_name_opt::<u32>("TestReqID"), None);
+        assert_eq!(
+            message
+                .fv::<u32, _>(fix44::APPL_QUEUE_DEPTH)
+                .transpose_opt(),
+            Ok(Some(1))
+        );
+        assert_eq!(
+            message.fv::<&str, _>(fix44::TEST_REQ_ID).transpose_opt(),
+            Ok(None)
+        );
+        assert_eq!(
+            message
+                .fv_opt::<&str, _>(fix44::TEST_REQ_ID)
+                .into_opt_result(),
+            Err(OptError::None)
+        );
+    }
+
+    #[test]
+    fn nested_repeating_groups() {
+        let bytes = b"8=FIX.4.4|9=82|35=8|453=2|448=A|447=D|452=1|802=2|523=X|803=1|523=Y|803=2|448=B|447=D|452=3|54=1|10=000|";
+        let decoder = &mut test_decoder();
+        let message = decoder.decode(bytes).unwrap();
+        let parties = message.group(fix44::NO_PARTY_I_DS).unwrap();
+        assert_eq!(parties.len(), 2);
+        let first_party = parties.entry(0);
+        assert_eq!(first_party.fv_raw(fix44::PARTY_ID), Some(b"A" as &[u8]));
+        let sub_ids = first_party.group(fix44::NO_PARTY_SUB_I_DS).unwrap();
+        assert_eq!(sub_ids.len(), 2);
+        assert_eq!(
+            sub_ids.entry(1).fv_raw(fix44::PARTY_SUB_ID),
+            Some(b"Y" as &[u8])
+        );
+        let second_party = parties.entry(1);
+        assert_eq!(second_party.fv_raw(fix44::PARTY_ID), Some(b"B" as &[u8]));
+        assert_eq!(second_party.fv_raw(fix44::PARTY_ROLE), Some(b"3" as &[u8]));
+        assert!(second_party.group(fix44::NO_PARTY_SUB_I_DS).is_err());
+        assert_eq!(message.fv(fix44::SIDE), Ok(fix44::Side::Buy));
+    }
+
     #[test]
     fn top_level_tag_after_empty_group() {
         let bytes = b"8=FIX.4.4|9=17|35=X|268=0|346=1|10=171|";
-        let mut decoder = decoder();
+        let mut decoder = test_decoder();
         let message = decoder.decode(&bytes).unwrap();
         let group = message.group(fix44::NO_MD_ENTRIES).unwrap();
         assert_eq!(group.len(), 0);
@@ -807,7 +2065,7 @@ mod test {
     fn assortment_of_random_messages_is_ok() {
         for msg_with_vertical_bar in RANDOM_MESSAGES {
             let message = with_soh(msg_with_vertical_bar);
-            let mut codec = decoder();
+            let mut codec = test_decoder();
             codec.config_mut().set_separator(0x1);
             let result = codec.decode(message.as_bytes());
             result.unwrap();
@@ -816,7 +2074,7 @@ mod test {
 
     #[test]
     fn heartbeat_message_fields_are_ok() {
-        let mut codec = decoder();
+        let mut codec = test_decoder();
         let message = codec.decode(RANDOM_MESSAGES[0].as_bytes()).unwrap();
         assert_eq!(message.fv(fix44::MSG_TYPE), Ok(fix44::MsgType::Heartbeat));
         assert_eq!(
@@ -839,24 +2097,31 @@ mod test {
     #[test]
     fn message_must_end_with_separator() {
         let msg = "8=FIX.4.2|9=41|35=D|49=AFUNDMGR|56=ABROKERt|15=USD|59=0|10=127";
-        let mut codec = decoder();
+        let mut codec = test_decoder();
         let result = codec.decode(msg.as_bytes());
-        assert_eq!(result, Err(DecodeError::Invalid));
+        assert!(matches!(result, Err(DecodeError::Length { .. })));
     }
 
     #[test]
     fn message_without_checksum() {
         let msg = "8=FIX.4.4|9=37|35=D|49=AFUNDMGR|56=ABROKERt|15=USD|59=0|";
-        let mut codec = decoder();
+        let mut codec = test_decoder();
         let result = codec.decode(msg.as_bytes());
-        assert_eq!(result, Err(DecodeError::Invalid));
+        assert_eq!(
+            result.map(|_| ()),
+            Err(DecodeError::Length {
+                offset: 10,
+                declared: 37,
+                actual: 34,
+            })
+        );
     }
 
     #[test]
     fn message_with_data_field() {
         let msg =
             "8=FIX.4.4|9=58|35=D|49=AFUNDMGR|56=ABROKERt|15=USD|39=0|93=8|89=foo|\x01bar|10=000|";
-        let mut codec = decoder();
+        let mut codec = test_decoder();
         let result = codec.decode(msg.as_bytes()).unwrap();
         assert_eq!(result.fv(fix44::SIGNATURE_LENGTH), Ok(8));
         assert_eq!(
@@ -865,19 +2130,177 @@ mod test {
         );
     }
 
+    #[test]
+    fn data_field_length_must_be_numeric() {
+        let msg = "8=FIX.4.4|9=17|35=B|95=X|96=foo|10=000|";
+        let mut codec = test_decoder();
+        assert_eq!(
+            codec.decode(msg.as_bytes()).map(|_| ()),
+            Err(DecodeError::Invalid {
+                offset: 20,
+                tag: TagU16::new(95),
+            })
+        );
+    }
+
+    #[test]
+    fn data_field_length_only_applies_to_data_fields() {
+        let msg = "8=FIX.4.4|9=24|35=B|95=3|148=News|96=a|10=000|";
+        let mut codec = test_decoder();
+        let result = codec.decode(msg.as_bytes()).unwrap();
+        assert_eq!(result.fv(fix44::HEADLINE), Ok("News"));
+        assert_eq!(result.fv_raw(fix44::RAW_DATA), Some(b"a" as &[u8]));
+    }
+
+    #[test]
+    fn data_fields_dont_need_associative_decoding() {
+        let msg = "8=FIX.4.4|9=18|35=B|95=4|96=a|\x01b|10=000|";
+        let mut codec = test_decoder();
+        codec.config_mut().set_decode_assoc(false);
+        assert!(codec.decode(msg.as_bytes()).is_ok());
+    }
+
+    fn venue_dictionary() -> Dictionary {
+        let mut builder = Dictionary::fix44().to_builder();
+        builder
+            .add_custom_field(5001, "VenueDataLen", FixDatatype::Length)
+            .unwrap();
+        builder
+            .add_custom_field(5002, "VenueData", FixDatatype::Data)
+            .unwrap();
+        builder.build()
+    }
+
+    #[test]
+    fn appl_ver_id_selects_the_application_dictionary() {
+        let msg = "8=FIXT.1.1|9=28|35=B|1128=9|5001=3|5002=a|b|10=000|";
+        let mut codec = test_decoder();
+        assert!(codec.decode(msg.as_bytes()).is_err());
+        codec.add_appl_ver_dictionary("9", venue_dictionary());
+        let message = codec.decode(msg.as_bytes()).unwrap();
+        assert_eq!(
+            message.fv_raw_by_tag(TagU16::new(5002).unwrap()),
+            Some(b"a|b" as &[u8])
+        );
+        assert_eq!(message.fv_raw_by_name("VenueData"), Some(b"a|b" as &[u8]));
+        assert!(message.dictionary().field_by_tag(5002).is_some());
+        // Other application versions and other FIX versions use the main
+        // dictionary.
+        let msg = "8=FIXT.1.1|9=28|35=B|1128=8|5001=3|5002=a|b|10=000|";
+        assert!(codec.decode(msg.as_bytes()).is_err());
+        let msg = "8=FIX.4.4|9=28|35=B|1128=9|5001=3|5002=a|b|10=000|";
+        assert!(codec.decode(msg.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn default_appl_ver_id_applies_without_appl_ver_id() {
+        let msg = "8=FIXT.1.1|9=21|35=B|5001=3|5002=a|b|10=000|";
+        let mut codec = test_decoder();
+        codec.add_appl_ver_dictionary("9", venue_dictionary());
+        assert!(codec.decode(msg.as_bytes()).is_err());
+        codec.set_default_appl_ver_id(Some("9"));
+        assert_eq!(codec.default_appl_ver_id(), Some("9"));
+        let message = codec.decode(msg.as_bytes()).unwrap();
+        assert_eq!(
+            message.fv_raw_by_tag(TagU16::new(5002).unwrap()),
+            Some(b"a|b" as &[u8])
+        );
+    }
+
     #[test]
     fn message_without_standard_header() {
         let msg = "35=D|49=AFUNDMGR|56=ABROKERt|15=USD|59=0|10=000|";
-        let mut codec = decoder();
+        let mut codec = test_decoder();
         let result = codec.decode(msg.as_bytes());
-        assert_eq!(result, Err(DecodeError::Invalid));
+        assert_eq!(
+            result.map(|_| ()),
+            Err(DecodeError::Invalid {
+                offset: 0,
+                tag: TagU16::new(8),
+            })
+        );
     }
 
     #[test]
     fn detect_incorrect_checksum() {
         let msg = "8=FIX.4.2|9=43|35=D|49=AFUNDMGR|56=ABROKER|15=USD|59=0|10=146|";
-        let mut codec = decoder();
+        let mut codec = test_decoder();
         let result = codec.decode(msg.as_bytes());
-        assert_eq!(result, Err(DecodeError::Invalid));
+        assert_eq!(
+            result.map(|_| ()),
+            Err(DecodeError::Length {
+                offset: 10,
+                declared: 43,
+                actual: 40,
+            })
+        );
+    }
+
+    #[test]
+    fn malformed_body_fields_are_located() {
+        let mut codec = test_decoder();
+        let msg = "8=FIX.4.4|9=16|35=0|4x9=A|56=B|10=000|";
+        let result = codec.decode(msg.as_bytes()).map(|_| ());
+        assert_eq!(
+            result,
+            Err(DecodeError::Invalid {
+                offset: 20,
+                tag: None
+            })
+        );
+        // The data field is shorter than announced by its length field.
+        let msg = "8=FIX.4.4|9=17|35=0|93=9|89=foo|10=000|";
+        let result = codec.decode(msg.as_bytes()).map(|_| ());
+        assert_eq!(
+            result,
+            Err(DecodeError::Invalid {
+                offset: 25,
+                tag: TagU16::new(89),
+            })
+        );
+    }
+
+    #[test]
+    fn owned_message_outlives_decoder_state() {
+        let decoder = &mut test_decoder();
+        let owned = decoder
+            .decode(RANDOM_MESSAGES[6].as_bytes())
+            .unwrap()
+            .to_owned();
+        decoder.decode(RANDOM_MESSAGES[0].as_bytes()).unwrap();
+        assert_eq!(owned.as_bytes(), RANDOM_MESSAGES[6].as_bytes());
+        let message = owned.message();
+        assert_eq!(message.fv(fix44::MD_REQ_ID), Ok("A"));
+        let group = message.group(fix44::NO_MD_ENTRIES).unwrap();
+        assert_eq!(group.len(), 2);
+        assert_eq!(
+            group.entry(1).fv_raw(fix44::MD_ENTRY_ID),
+            Some(b"OFFER" as &[u8])
+        );
+    }
+
+    #[test]
+    fn owned_message_can_be_sent_across_threads() {
+        let decoder = &mut test_decoder();
+        let owned = decoder
+            .decode(RANDOM_MESSAGES[1].as_bytes())
+            .unwrap()
+            .to_owned();
+        let clone = owned.clone();
+        let handle = std::thread::spawn(move || {
+            let message = owned.message();
+            assert_eq!(message.fv(fix44::SYMBOL), Ok("SPMI.MI"));
+            owned
+        });
+        let owned = handle.join().unwrap();
+        assert_eq!(owned, clone);
+        assert_eq!(clone.len(), owned.message().fields().count());
+        assert!(!clone.is_empty());
+    }
+
+    #[test]
+    fn owned_message_is_send_and_static() {
+        fn assert_send_static<T: Send + 'static>() {}
+        assert_send_static::<MessageOwned>();
     }
 }
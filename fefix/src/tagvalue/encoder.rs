@@ -1,12 +1,25 @@
-use super::{Config, Configure, FvWrite};
+use super::{CachedField, Config, Configure, EncodeError, FvWrite};
 use crate::buffer::Buffer;
 use crate::definitions::fix44;
 use crate::dict;
-use crate::dict::IsFieldDefinition;
-use crate::fix_values::CheckSum;
+use crate::dict::{IsFieldDefinition, LayoutItemKind};
+use crate::fix_values::{CheckSum, Timestamp};
+use crate::utils::IntMap;
 use crate::FixValue;
-use crate::TagU16;
-use std::ops::Range;
+use crate::{Dictionary, TagU16};
+use alloc::vec::Vec;
+use core::ops::{Deref, DerefMut, Range};
+use hashbrown::HashMap;
+#[cfg(feature = "std")]
+use std::io::{self, IoSlice};
+
+// Six digits are reserved for `BodyLength <9>`.
+const MAX_BODY_LENGTH: usize = 999_999;
+// `NumInGroup` values are zero-padded to a fixed width, like `BodyLength <9>`,
+// so that they can be written in place once a group ends. Every entry takes at
+// least four bytes, so six digits are enough for any body up to
+// `MAX_BODY_LENGTH`.
+const NUM_IN_GROUP_DIGITS: usize = 6;
 
 /// A buffered, content-agnostic FIX encoder.
 ///
@@ -31,6 +44,87 @@ where
     C: Configure,
 {
     config: C,
+    field_order: Option<FieldOrder>,
+}
+
+/// The canonical position of top-level fields, as used by
+/// [`Encoder::set_canonical_order`].
+#[derive(Debug, Clone)]
+struct FieldOrder {
+    header: IntMap<u16, usize>,
+    trailer: IntMap<u16, usize>,
+    body_by_msg_type: HashMap<Vec<u8>, IntMap<u16, usize>>,
+}
+
+impl FieldOrder {
+    fn new(dict: &Dictionary) -> Self {
+        let positions = |name: &str| {
+            let mut tags = Vec::new();
+            if let Some(component) = dict.component_by_name(name) {
+                add_top_level_tags(&mut tags, component.items());
+            }
+            positions_of(tags)
+        };
+        let mut body_by_msg_type = HashMap::new();
+        for message in dict.iter_messages() {
+            if message.scenario() != "base" {
+                continue;
+            }
+            let mut tags = Vec::new();
+            add_top_level_tags(&mut tags, message.layout());
+            body_by_msg_type.insert(message.msg_type().as_bytes().to_vec(), positions_of(tags));
+        }
+        Self {
+            header: positions("StandardHeader"),
+            trailer: positions("StandardTrailer"),
+            body_by_msg_type,
+        }
+    }
+
+    /// Returns the sorting key of `tag` within a message of `msg_type`. Fields
+    /// that the dictionary doesn't know about go after the body.
+    fn rank(&self, msg_type: &[u8], tag: u16) -> (u8, usize) {
+        if tag == fix44::MSG_TYPE.tag().get() {
+            (0, 0)
+        } else if let Some(i) = self.header.get(&tag) {
+            (1, *i)
+        } else if let Some(i) = self.trailer.get(&tag) {
+            (4, *i)
+        } else {
+            match self
+                .body_by_msg_type
+                .get(msg_type)
+                .and_then(|body| body.get(&tag))
+            {
+                Some(i) => (2, *i),
+                None => (3, 0),
+            }
+        }
+    }
+}
+
+// Collects the tags of all fields and repeating groups in `items`, in order,
+// but not those within groups.
+pub(crate) fn add_top_level_tags<'a>(
+    tags: &mut impl Extend<u16>,
+    items: impl Iterator<Item = dict::LayoutItem<'a>>,
+) {
+    for item in items {
+        match item.kind() {
+            LayoutItemKind::Field(field) | LayoutItemKind::Group(field, _) => {
+                tags.extend(core::iter::once(field.tag().get()))
+            }
+            LayoutItemKind::Component(component) => add_top_level_tags(tags, component.items()),
+        }
+    }
+}
+
+fn positions_of(tags: Vec<u16>) -> IntMap<u16, usize> {
+    let mut positions = IntMap::default();
+    for (i, tag) in tags.into_iter().enumerate() {
+        positions.entry(tag).or_insert(i);
+    }
+    positions
 }
 
 impl<C> Encoder<C>
@@ -50,7 +144,10 @@ where
     /// assert_eq!(encoder.config().separator(), b'|');
     /// ```
     pub fn new(config: C) -> Self {
-        Self { config }
+        Self {
+            config,
+            field_order: None,
+        }
     }
 
     /// Returns an immutable reference to the [`Configure`] implementor used by
@@ -65,16 +162,65 @@ where
         &mut self.config
     }
 
-    pub fn start_message<'a>(
+    /// Enables or disables canonical field ordering. When enabled, top-level
+    /// fields are reordered as `dict` defines them when a message is
+    /// completed, regardless of the order of setter calls: `StandardHeader`
+    /// fields first, then the body fields of the message type, then unknown
+    /// fields, and `StandardTrailer` fields last. Repeating groups move as a
+    /// whole and keep their contents as-is. Fields with the same position,
+    /// e.g. unknown ones, keep their relative order, and raw bytes (see
+    /// [`EncoderHandle::raw`]) stay right after the preceding field.
+    ///
+    /// This is disabled by default, as it costs one copy of the body.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix::definitions::fix44;
+    /// use fefix::tagvalue::{Config, Encoder};
+    /// use fefix::Dictionary;
+    ///
+    /// let mut encoder = Encoder::<Config>::default();
+    /// encoder.config_mut().set_separator(b'|');
+    /// encoder.set_canonical_order(Some(&Dictionary::fix44()));
+    /// let mut buffer = Vec::new();
+    /// let mut msg = encoder.start_message(b"FIX.4.4", &mut buffer, b"0");
+    /// msg.set(fix44::TEST_REQ_ID, "X");
+    /// msg.set(fix44::MSG_SEQ_NUM, 2u32);
+    /// msg.set(fix44::TARGET_COMP_ID, "B");
+    /// msg.set(fix44::SENDER_COMP_ID, "A");
+    /// let data = msg.wrap();
+    /// assert!(data.starts_with(b"8=FIX.4.4|9=000026|35=0|49=A|56=B|34=2|112=X|10="));
+    /// ```
+    pub fn set_canonical_order(&mut self, dict: Option<&Dictionary>) {
+        self.field_order = dict.map(FieldOrder::new);
+    }
+
+    /// Returns `true` if and only if canonical field ordering is enabled. See
+    /// [`Encoder::set_canonical_order`].
+    pub fn canonical_order(&self) -> bool {
+        self.field_order.is_some()
+    }
+
+    /// Starts encoding a new message into `buffer`, which can be any
+    /// [`Buffer`] implementor, e.g. `Vec<u8>`, `bytes::BytesMut`, or a
+    /// [`SliceBuffer`](crate::SliceBuffer).
+    pub fn start_message<'a, B>(
         &'a mut self,
         begin_string: &[u8],
-        buffer: &'a mut Vec<u8>,
+        buffer: &'a mut B,
         msg_type: &[u8],
-    ) -> EncoderHandle<'a, Vec<u8>, C> {
+    ) -> EncoderHandle<'a, B, C>
+    where
+        B: Buffer,
+    {
         let mut state = EncoderHandle {
             raw_encoder: self,
             buffer,
             body_start_i: 0,
+            groups: Vec::new(),
+            field_starts: Vec::new(),
+            group_error: None,
         };
         state.set(fix44::BEGIN_STRING, begin_string);
         // The second field is supposed to be `BodyLength(9)`, but obviously
@@ -94,9 +240,93 @@ where
         // Six digits (~1MB) ought to be enough for every message.
         state.set_any(fix44::BODY_LENGTH.tag(), b"000000" as &[u8]);
         state.body_start_i = state.buffer.len();
+        state.field_starts.clear();
         state.set_any(fix44::MSG_TYPE.tag(), msg_type);
         state
     }
+
+    /// Like [`Encoder::start_message`], but first reserves `capacity` bytes
+    /// within `buffer`, so that the whole message can be encoded straight into
+    /// its final position without reallocations. Completing the message with
+    /// [`EncoderHandle::finalize`] then only patches `BodyLength <9>` in place
+    /// and appends `CheckSum <10>`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix::definitions::fix44;
+    /// use fefix::tagvalue::{Config, Encoder};
+    ///
+    /// let mut buffer = Vec::new();
+    /// let mut encoder = Encoder::<Config>::default();
+    /// encoder.config_mut().set_separator(b'|');
+    /// let mut msg = encoder.start_message_with_capacity(b"FIX.4.4", &mut buffer, b"0", 256);
+    /// msg.set(fix44::TEST_REQ_ID, "X");
+    /// let data = msg.finalize().unwrap();
+    /// assert_eq!(data, b"8=FIX.4.4|9=000011|35=0|112=X|10=166|");
+    /// assert!(buffer.capacity() >= 256);
+    /// ```
+    pub fn start_message_with_capacity<'a, B>(
+        &'a mut self,
+        begin_string: &[u8],
+        buffer: &'a mut B,
+        msg_type: &[u8],
+        capacity: usize,
+    ) -> EncoderHandle<'a, B, C>
+    where
+        B: Buffer,
+    {
+        buffer.reserve(capacity.saturating_sub(buffer.len()));
+        self.start_message(begin_string, buffer, msg_type)
+    }
+
+    /// Starts encoding a new message, of which only the body is written into
+    /// `buffer`. `BeginString <8>` comes from the prebuilt `header`, while
+    /// `BodyLength <9>` and `CheckSum <10>` are computed by
+    /// [`VectoredEncoderHandle::finalize`]. The resulting [`VectoredMessage`]
+    /// can then be written with a single `write_vectored` call, without ever
+    /// copying the body again.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix::definitions::fix44;
+    /// use fefix::tagvalue::{Config, Configure, Encoder, HeaderTemplate};
+    ///
+    /// let mut encoder = Encoder::<Config>::default();
+    /// encoder.config_mut().set_separator(b'|');
+    /// let header = HeaderTemplate::new(b"FIX.4.4", encoder.config().separator());
+    /// let mut buffer = Vec::new();
+    /// let mut msg = encoder.start_vectored_message(&header, &mut buffer, b"0");
+    /// msg.set(fix44::TEST_REQ_ID, "X");
+    /// let msg = msg.finalize().unwrap();
+    /// assert_eq!(msg.body(), b"35=0|112=X|");
+    ///
+    /// let mut output = Vec::new();
+    /// msg.write_to(&mut output).unwrap();
+    /// assert_eq!(output, b"8=FIX.4.4|9=11|35=0|112=X|10=230|");
+    /// ```
+    pub fn start_vectored_message<'a, B>(
+        &'a mut self,
+        header: &'a HeaderTemplate,
+        buffer: &'a mut B,
+        msg_type: &[u8],
+    ) -> VectoredEncoderHandle<'a, B, C>
+    where
+        B: Buffer,
+    {
+        let body_start_i = buffer.len();
+        let mut handle = EncoderHandle {
+            raw_encoder: self,
+            buffer,
+            body_start_i,
+            groups: Vec::new(),
+            field_starts: Vec::new(),
+            group_error: None,
+        };
+        handle.set_any(fix44::MSG_TYPE.tag(), msg_type);
+        VectoredEncoderHandle { handle, header }
+    }
 }
 
 /// A type returned by [`Encoder::start_message`](Encoder::start_message) to
@@ -110,6 +340,21 @@ where
     raw_encoder: &'a mut Encoder<C>,
     buffer: &'a mut B,
     body_start_i: usize,
+    groups: Vec<EncoderGroupState>,
+    // Tags and offsets of top-level fields within the body, only with
+    // canonical ordering.
+    field_starts: Vec<(TagU16, usize)>,
+    // The first misuse of repeating groups, reported by `finalize`.
+    group_error: Option<EncodeError>,
+}
+
+#[derive(Debug, Copy, Clone)]
+struct EncoderGroupState {
+    num_in_group_tag: TagU16,
+    delimiter_tag: TagU16,
+    // The offset of the reserved `NumInGroup` digits.
+    num_in_group_i: usize,
+    num_entries: usize,
 }
 
 impl<'a, B, C> EncoderHandle<'a, B, C>
@@ -126,10 +371,186 @@ where
         self.set_any(field.tag(), value)
     }
 
+    /// Adds a field with an arbitrary `tag` and `value` to the current
+    /// message.
+    ///
+    /// Within a repeating group (see [`EncoderHandle::begin_group`]), the
+    /// first entry must be started by the group delimiter field before any
+    /// other field is added. Otherwise, [`EncoderHandle::finalize`] returns
+    /// [`EncodeError::GroupEntryWithoutDelimiter`] and
+    /// [`EncoderHandle::wrap`] panics.
     pub fn set_any<'b, T>(&mut self, tag: TagU16, value: T)
     where
         T: FixValue<'b>,
     {
+        self.set_any_ref(tag, &value);
+    }
+
+    /// Adds a `Length` field (e.g. `RawDataLength <95>`) followed by its
+    /// `data` field (e.g. `RawData <96>`) to the current message. Data fields
+    /// may legally contain any byte, separators included, so decoders rely on
+    /// `length_field` to know where they end.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix::definitions::fix44;
+    /// use fefix::tagvalue::{Config, Configure, Encoder};
+    ///
+    /// let mut encoder = Encoder::<Config>::default();
+    /// encoder.config_mut().set_separator(b'|');
+    /// let mut buffer = Vec::new();
+    /// let mut msg = encoder.start_message(b"FIX.4.4", &mut buffer, b"B");
+    /// msg.set_data_field(fix44::RAW_DATA_LENGTH, fix44::RAW_DATA, b"a|b");
+    /// let data = msg.wrap();
+    /// assert!(data.starts_with(b"8=FIX.4.4|9=000017|35=B|95=3|96=a|b|10="));
+    /// ```
+    pub fn set_data_field<F1, F2>(&mut self, length_field: &F1, data_field: &F2, data: &[u8])
+    where
+        F1: dict::IsFieldDefinition,
+        F2: dict::IsFieldDefinition,
+    {
+        self.set_any(length_field.tag(), data.len());
+        self.set_any(data_field.tag(), data);
+    }
+
+    /// Like [`EncoderHandle::set`], but `value` is serialized with custom
+    /// `settings`, e.g. a [`TimePrecision`](crate::fix_values::TimePrecision)
+    /// for timestamps.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix::definitions::fix44;
+    /// use fefix::fix_values::{TimePrecision, Timestamp};
+    /// use fefix::tagvalue::{Config, Encoder};
+    /// use fefix::FixValue;
+    ///
+    /// let mut buffer = Vec::new();
+    /// let mut encoder = Encoder::<Config>::default();
+    /// encoder.config_mut().set_separator(b'|');
+    /// let mut msg = encoder.start_message(b"FIX.4.4", &mut buffer, b"0");
+    /// let time = Timestamp::deserialize(b"20210601-09:00:00.123456789").unwrap();
+    /// msg.set_with(fix44::SENDING_TIME, time, TimePrecision::Nanos);
+    /// let data = msg.wrap();
+    /// assert!(data.starts_with(b"8=FIX.4.4|9=000036|35=0|52=20210601-09:00:00.123456789|"));
+    /// ```
+    pub fn set_with<'b, F, T>(&mut self, field: &F, value: T, settings: T::SerializeSettings)
+    where
+        F: dict::IsFieldDefinition,
+        T: FixValue<'b>,
+    {
+        self.count_group_entry(field.tag());
+        field.tag().serialize(self.buffer);
+        self.buffer.extend_from_slice(b"=" as &[u8]);
+        value.serialize_with(self.buffer, settings);
+        self.buffer
+            .extend_from_slice(&[self.raw_encoder.config().separator()]);
+    }
+
+    /// Adds a `UTCTimestamp` `field` to the current message, with the
+    /// precision of [`Configure::timestamp_precision`].
+    pub fn set_timestamp<F>(&mut self, field: &F, timestamp: Timestamp)
+    where
+        F: dict::IsFieldDefinition,
+    {
+        let precision = self.raw_encoder.config().timestamp_precision();
+        self.set_with(field, timestamp, precision);
+    }
+
+    /// Adds a `field` with several space-separated `values` to the current
+    /// message, as mandated by `MultipleCharValue` and `MultipleStringValue`
+    /// fields. See also [`MultipleValues`](crate::fix_values::MultipleValues)
+    /// for decoding.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix::definitions::fix44;
+    /// use fefix::tagvalue::{Config, Encoder};
+    ///
+    /// let mut buffer = Vec::new();
+    /// let mut encoder = Encoder::<Config>::default();
+    /// encoder.config_mut().set_separator(b'|');
+    /// let mut msg = encoder.start_message(b"FIX.4.4", &mut buffer, b"D");
+    /// msg.set_multiple(
+    ///     fix44::EXEC_INST,
+    ///     [fix44::ExecInst::AllOrNone, fix44::ExecInst::Work],
+    /// );
+    /// let data = msg.wrap();
+    /// assert!(data.starts_with(b"8=FIX.4.4|9=000012|35=D|18=G 2|"));
+    /// ```
+    pub fn set_multiple<'b, F, I>(&mut self, field: &F, values: I)
+    where
+        F: dict::IsFieldDefinition,
+        I: IntoIterator,
+        I::Item: FixValue<'b>,
+    {
+        self.count_group_entry(field.tag());
+        field.tag().serialize(self.buffer);
+        self.buffer.extend_from_slice(b"=" as &[u8]);
+        for (i, value) in values.into_iter().enumerate() {
+            if i > 0 {
+                self.buffer.extend_from_slice(b" " as &[u8]);
+            }
+            value.serialize(self.buffer);
+        }
+        self.buffer
+            .extend_from_slice(&[self.raw_encoder.config().separator()]);
+    }
+
+    /// Adds all `fields`, i.e. `(tag, value)` pairs, to the current message in
+    /// iteration order. Values are written as-is. The buffer grows at most
+    /// once, as `fields` is cloned to compute the total length in advance:
+    /// iterators over borrowed data are usually cheap to clone.
+    ///
+    /// Repeating groups are checked like with [`EncoderHandle::set_any`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix::tagvalue::{Config, Encoder};
+    /// use fefix::TagU16;
+    ///
+    /// let fields: Vec<(u16, Vec<u8>)> = vec![(55, b"EUR/USD".to_vec()), (54, b"1".to_vec())];
+    /// let mut buffer = Vec::new();
+    /// let mut encoder = Encoder::<Config>::default();
+    /// encoder.config_mut().set_separator(b'|');
+    /// let mut msg = encoder.start_message(b"FIX.4.4", &mut buffer, b"D");
+    /// msg.extend_from_iter(
+    ///     fields
+    ///         .iter()
+    ///         .map(|(tag, value)| (TagU16::new(*tag).unwrap(), value.as_slice())),
+    /// );
+    /// let data = msg.wrap();
+    /// assert!(data.starts_with(b"8=FIX.4.4|9=000021|35=D|55=EUR/USD|54=1|"));
+    /// ```
+    pub fn extend_from_iter<'b, I>(&mut self, fields: I)
+    where
+        I: Iterator<Item = (TagU16, &'b [u8])> + Clone,
+    {
+        let len: usize = fields
+            .clone()
+            .map(|(tag, value)| tag_len(tag) + value.len() + 2)
+            .sum();
+        self.buffer.reserve(len);
+        let separator = self.raw_encoder.config().separator();
+        for (tag, value) in fields {
+            self.count_group_entry(tag);
+            tag.serialize(self.buffer);
+            self.buffer.extend_from_slice(b"=" as &[u8]);
+            self.buffer.extend_from_slice(value);
+            self.buffer.extend_from_slice(&[separator]);
+        }
+    }
+
+    /// Like [`EncoderHandle::set_any`], but `value` is borrowed, which avoids
+    /// clones for owned types like [`String`].
+    pub(crate) fn set_any_ref<'b, T>(&mut self, tag: TagU16, value: &T)
+    where
+        T: FixValue<'b>,
+    {
+        self.count_group_entry(tag);
         tag.serialize(self.buffer);
         self.buffer.extend_from_slice(b"=" as &[u8]);
         value.serialize(self.buffer);
@@ -137,18 +558,165 @@ where
             .extend_from_slice(&[self.raw_encoder.config().separator()]);
     }
 
+    /// Adds a pre-serialized `field` to the current message, which is a
+    /// plain copy of its bytes. See [`CachedField`].
+    ///
+    /// Repeating groups are checked like with [`EncoderHandle::set_any`].
+    pub fn set_cached(&mut self, field: &CachedField) {
+        debug_assert_eq!(
+            field.separator(),
+            self.raw_encoder.config().separator(),
+            "Cached field with a different separator"
+        );
+        self.count_group_entry(field.tag());
+        self.buffer.extend_from_slice(field.as_bytes());
+    }
+
     pub fn raw(&mut self, raw: &[u8]) {
         self.buffer.extend_from_slice(raw);
     }
 
+    /// Starts a new repeating group, counted by `num_in_group`. Every group
+    /// entry must begin with `delimiter`, which also marks the start of all
+    /// new entries: the number of entries is thus calculated automatically and
+    /// `num_in_group` is written by [`EncoderHandle::end_group`].
+    ///
+    /// Groups can be nested by calling this method again before ending the
+    /// current group. Like `BodyLength <9>`, `num_in_group` is zero-padded to
+    /// a fixed width, so that it can be written in place.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix::definitions::fix44;
+    /// use fefix::tagvalue::{Config, Encoder};
+    ///
+    /// let mut buffer = Vec::new();
+    /// let mut encoder = Encoder::<Config>::default();
+    /// encoder.config_mut().set_separator(b'|');
+    /// let mut msg = encoder.start_message(b"FIX.4.4", &mut buffer, b"X");
+    /// msg.begin_group(fix44::NO_MD_ENTRIES, fix44::MD_UPDATE_ACTION);
+    /// msg.set(fix44::MD_UPDATE_ACTION, fix44::MdUpdateAction::New);
+    /// msg.set(fix44::MD_ENTRY_PX, 150u32);
+    /// msg.set(fix44::MD_UPDATE_ACTION, fix44::MdUpdateAction::Delete);
+    /// msg.end_group();
+    /// let data = msg.wrap();
+    /// assert!(data.starts_with(b"8=FIX.4.4|9=000036|35=X|268=000002|279=0|270=150|279=2|"));
+    /// ```
+    pub fn begin_group<F1, F2>(&mut self, num_in_group: &F1, delimiter: &F2)
+    where
+        F1: IsFieldDefinition,
+        F2: IsFieldDefinition,
+    {
+        self.count_group_entry(num_in_group.tag());
+        num_in_group.tag().serialize(self.buffer);
+        self.buffer.extend_from_slice(b"=" as &[u8]);
+        let num_in_group_i = self.buffer.len();
+        self.buffer.extend_from_slice(&[b'0'; NUM_IN_GROUP_DIGITS]);
+        self.buffer
+            .extend_from_slice(&[self.raw_encoder.config().separator()]);
+        self.groups.push(EncoderGroupState {
+            num_in_group_tag: num_in_group.tag(),
+            delimiter_tag: delimiter.tag(),
+            num_in_group_i,
+            num_entries: 0,
+        });
+    }
+
+    /// Ends the innermost repeating group started by
+    /// [`EncoderHandle::begin_group`] and writes its `NumInGroup` field.
+    ///
+    /// If there's no group to end, [`EncoderHandle::finalize`] returns
+    /// [`EncodeError::NoGroupToEnd`] and [`EncoderHandle::wrap`] panics.
+    pub fn end_group(&mut self) {
+        let group = match self.groups.pop() {
+            Some(group) => group,
+            None => {
+                self.set_group_error(EncodeError::NoGroupToEnd);
+                return;
+            }
+        };
+        let range = group.num_in_group_i..group.num_in_group_i + NUM_IN_GROUP_DIGITS;
+        // The digits are missing only if a fixed-capacity buffer overflowed.
+        if let Some(digits) = self.buffer.as_mut_slice().get_mut(range) {
+            write_padded_digits(digits, group.num_entries);
+        }
+    }
+
     /// Closes the current message writing operation and returns its byte
     /// representation.
+    ///
+    /// # Panics
+    ///
+    /// This method will panic if repeating groups were misused, i.e. in all
+    /// cases in which [`EncoderHandle::finalize`] would return
+    /// [`EncodeError::UnterminatedGroup`],
+    /// [`EncodeError::GroupEntryWithoutDelimiter`] or
+    /// [`EncodeError::NoGroupToEnd`].
     pub fn wrap(mut self) -> &'a [u8] {
+        if let Err(err) = self.check_groups() {
+            panic!("{}", err);
+        }
+        self.reorder_fields();
         self.write_body_length();
         self.write_checksum();
         self.buffer.as_slice()
     }
 
+    /// Like [`EncoderHandle::wrap`], but reports unterminated repeating groups,
+    /// bodies which are too long for the digits reserved for `BodyLength <9>`
+    /// and overflowed fixed-capacity buffers, instead of producing a corrupt
+    /// message.
+    pub fn finalize(mut self) -> Result<&'a [u8], EncodeError> {
+        self.check_complete()?;
+        self.reorder_fields();
+        self.write_body_length();
+        self.write_checksum();
+        // `CheckSum <10>` itself may not fit.
+        self.check_capacity()?;
+        Ok(self.buffer.as_slice())
+    }
+
+    fn check_complete(&self) -> Result<(), EncodeError> {
+        self.check_capacity()?;
+        self.check_groups()?;
+        let len = self.body_length();
+        if len > MAX_BODY_LENGTH {
+            return Err(EncodeError::BodyTooLong {
+                len,
+                max: MAX_BODY_LENGTH,
+            });
+        }
+        Ok(())
+    }
+
+    fn check_groups(&self) -> Result<(), EncodeError> {
+        if let Some(err) = &self.group_error {
+            return Err(err.clone());
+        }
+        if let Some(group) = self.groups.last() {
+            return Err(EncodeError::UnterminatedGroup {
+                tag: group.num_in_group_tag,
+            });
+        }
+        Ok(())
+    }
+
+    fn set_group_error(&mut self, err: EncodeError) {
+        if self.group_error.is_none() {
+            self.group_error = Some(err);
+        }
+    }
+
+    fn check_capacity(&self) -> Result<(), EncodeError> {
+        if self.buffer.has_overflowed() {
+            return Err(EncodeError::CapacityExceeded {
+                capacity: self.buffer.capacity(),
+            });
+        }
+        Ok(())
+    }
+
     fn body_length_writable_range(&self) -> Range<usize> {
         self.body_start_i - 7..self.body_start_i - 1
     }
@@ -158,15 +726,77 @@ where
     }
 
     fn write_body_length(&mut self) {
+        if self.body_start_i < 7 {
+            // Only possible if a fixed-capacity buffer overflowed within the
+            // header, in which case the message is unusable anyway.
+            return;
+        }
         let body_length = self.body_length();
         let body_length_range = self.body_length_writable_range();
-        let slice = &mut self.buffer.as_mut_slice()[body_length_range];
-        slice[0] = to_digit((body_length / 100000) as u8 % 10);
-        slice[1] = to_digit((body_length / 10000) as u8 % 10);
-        slice[2] = to_digit((body_length / 1000) as u8 % 10);
-        slice[3] = to_digit((body_length / 100) as u8 % 10);
-        slice[4] = to_digit((body_length / 10) as u8 % 10);
-        slice[5] = to_digit((body_length / 1) as u8 % 10);
+        write_padded_digits(
+            &mut self.buffer.as_mut_slice()[body_length_range],
+            body_length,
+        );
+    }
+
+    // Sorts the top-level fields of the body by their canonical position, if
+    // enabled.
+    fn reorder_fields(&mut self) {
+        let field_order = match &self.raw_encoder.field_order {
+            Some(field_order) if self.field_starts.len() > 1 => field_order,
+            _ => return,
+        };
+        let separator = self.raw_encoder.config().separator();
+        let start_i = self.field_starts[0].1;
+        let body = self.buffer.as_slice()[start_i..].to_vec();
+        let mut fields: Vec<(TagU16, Range<usize>)> = self
+            .field_starts
+            .iter()
+            .enumerate()
+            .map(|(i, (tag, field_start_i))| {
+                let end_i = match self.field_starts.get(i + 1) {
+                    Some((_, next_start_i)) => *next_start_i,
+                    None => start_i + body.len(),
+                };
+                (*tag, field_start_i - start_i..end_i - start_i)
+            })
+            .collect();
+        // `MsgType <35>` always comes first.
+        let msg_type_field = &body[fields[0].1.clone()];
+        let msg_type = msg_type_field
+            .iter()
+            .position(|byte| *byte == b'=')
+            .map(|i| &msg_type_field[i + 1..])
+            .and_then(|value| {
+                let len = value.iter().position(|byte| *byte == separator)?;
+                Some(&value[..len])
+            })
+            .unwrap_or_default();
+        fields.sort_by_key(|(tag, _)| field_order.rank(msg_type, tag.get()));
+        let slice = &mut self.buffer.as_mut_slice()[start_i..];
+        let mut i = 0;
+        for (_, range) in fields {
+            let len = range.len();
+            slice[i..i + len].copy_from_slice(&body[range]);
+            i += len;
+        }
+    }
+
+    fn count_group_entry(&mut self, tag: TagU16) {
+        if self.groups.is_empty() && self.raw_encoder.field_order.is_some() {
+            self.field_starts.push((tag, self.buffer.len()));
+        }
+        if let Some(group) = self.groups.last_mut() {
+            if tag == group.delimiter_tag {
+                group.num_entries += 1;
+            } else if group.num_entries == 0 {
+                let err = EncodeError::GroupEntryWithoutDelimiter {
+                    tag,
+                    delimiter: group.delimiter_tag,
+                };
+                self.set_group_error(err);
+            }
+        }
     }
 
     fn write_checksum(&mut self) {
@@ -198,6 +828,626 @@ where
     }
 }
 
+/// The prebuilt `BeginString <8>` field and `BodyLength <9>` tag which start
+/// all messages encoded by [`Encoder::start_vectored_message`]. Build it once
+/// per session and reuse it for every message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeaderTemplate {
+    bytes: Vec<u8>,
+    checksum: u8,
+}
+
+impl HeaderTemplate {
+    /// Creates a new [`HeaderTemplate`] for `begin_string` (e.g. `FIX.4.4`).
+    /// `separator` must be the same as the one of the [`Encoder`] that will
+    /// use this template.
+    pub fn new(begin_string: &[u8], separator: u8) -> Self {
+        let mut bytes = Vec::with_capacity(begin_string.len() + 5);
+        bytes.extend_from_slice(b"8=");
+        bytes.extend_from_slice(begin_string);
+        bytes.push(separator);
+        bytes.extend_from_slice(b"9=");
+        let checksum = CheckSum::compute(&bytes[..]).0;
+        Self { bytes, checksum }
+    }
+
+    /// Returns the raw contents of `self`, up to and including `9=`.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes[..]
+    }
+}
+
+/// A type returned by [`Encoder::start_vectored_message`] to encode the body
+/// of a message. All field setters of [`EncoderHandle`] are available through
+/// [`Deref`].
+#[derive(Debug)]
+pub struct VectoredEncoderHandle<'a, B, C = Config>
+where
+    B: Buffer,
+    C: Configure,
+{
+    handle: EncoderHandle<'a, B, C>,
+    header: &'a HeaderTemplate,
+}
+
+impl<'a, B, C> VectoredEncoderHandle<'a, B, C>
+where
+    B: Buffer,
+    C: Configure,
+{
+    /// Completes the current message, with the same checks as
+    /// [`EncoderHandle::finalize`]. The body is left in place within the
+    /// buffer and referenced by the returned [`VectoredMessage`].
+    pub fn finalize(mut self) -> Result<VectoredMessage<'a>, EncodeError> {
+        self.handle.check_complete()?;
+        self.handle.reorder_fields();
+        let separator = self.handle.raw_encoder.config().separator();
+        let body_start_i = self.handle.body_start_i;
+        let body = &self.handle.buffer.as_slice()[body_start_i..];
+        // Up to six digits, plus the separator.
+        let mut body_length = [0u8; 7];
+        let mut body_length_len = 0;
+        let mut divisor = 100_000;
+        while divisor > 0 {
+            let digit = (body.len() / divisor) % 10;
+            if body_length_len > 0 || digit > 0 || divisor == 1 {
+                body_length[body_length_len] = to_digit(digit as u8);
+                body_length_len += 1;
+            }
+            divisor /= 10;
+        }
+        body_length[body_length_len] = separator;
+        body_length_len += 1;
+        let checksum = self
+            .header
+            .checksum
+            .wrapping_add(CheckSum::compute(&body_length[..body_length_len]).0)
+            .wrapping_add(CheckSum::compute(body).0);
+        let trailer = [
+            b'1',
+            b'0',
+            b'=',
+            to_digit(checksum / 100),
+            to_digit((checksum / 10) % 10),
+            to_digit(checksum % 10),
+            separator,
+        ];
+        Ok(VectoredMessage {
+            header: self.header.as_bytes(),
+            body_length,
+            body_length_len,
+            body,
+            trailer,
+        })
+    }
+}
+
+impl<'a, B, C> Deref for VectoredEncoderHandle<'a, B, C>
+where
+    B: Buffer,
+    C: Configure,
+{
+    type Target = EncoderHandle<'a, B, C>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.handle
+    }
+}
+
+impl<'a, B, C> DerefMut for VectoredEncoderHandle<'a, B, C>
+where
+    B: Buffer,
+    C: Configure,
+{
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.handle
+    }
+}
+
+/// A complete FIX message made of separate chunks, as returned by
+/// [`VectoredEncoderHandle::finalize`]: the [`HeaderTemplate`], the value of
+/// `BodyLength <9>`, the body, and `CheckSum <10>`.
+///
+/// Unlike [`EncoderHandle::wrap`], `BodyLength <9>` is not zero-padded.
+#[derive(Debug, Clone)]
+pub struct VectoredMessage<'a> {
+    header: &'a [u8],
+    body_length: [u8; 7],
+    body_length_len: usize,
+    body: &'a [u8],
+    trailer: [u8; 7],
+}
+
+impl<'a> VectoredMessage<'a> {
+    /// Returns all chunks of `self`, in order.
+    pub fn chunks(&self) -> [&[u8]; 4] {
+        [
+            self.header,
+            &self.body_length[..self.body_length_len],
+            self.body,
+            &self.trailer[..],
+        ]
+    }
+
+    /// Returns all chunks of `self`, in order, e.g. for
+    /// [`Write::write_vectored`](std::io::Write::write_vectored).
+    #[cfg(feature = "std")]
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "std")))]
+    pub fn io_slices(&self) -> [IoSlice<'_>; 4] {
+        let [header, body_length, body, trailer] = self.chunks();
+        [
+            IoSlice::new(header),
+            IoSlice::new(body_length),
+            IoSlice::new(body),
+            IoSlice::new(trailer),
+        ]
+    }
+
+    /// Returns the body of `self`, i.e. everything between `BodyLength <9>`
+    /// and `CheckSum <10>`.
+    pub fn body(&self) -> &'a [u8] {
+        self.body
+    }
+
+    /// Returns the total length of `self`, in bytes.
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> usize {
+        self.header.len() + self.body_length_len + self.body.len() + self.trailer.len()
+    }
+
+    /// Writes all of `self` to `writer` with as few
+    /// [`Write::write_vectored`](std::io::Write::write_vectored) calls as
+    /// possible, retrying on partial writes.
+    #[cfg(feature = "std")]
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "std")))]
+    pub fn write_to<W>(&self, writer: &mut W) -> io::Result<()>
+    where
+        W: io::Write,
+    {
+        let mut slices = self.io_slices();
+        let mut slices = &mut slices[..];
+        while !slices.is_empty() {
+            match writer.write_vectored(slices) {
+                Ok(0) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::WriteZero,
+                        "failed to write whole message",
+                    ))
+                }
+                Ok(n) => IoSlice::advance_slices(&mut slices, n),
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(())
+    }
+}
+
 fn to_digit(byte: u8) -> u8 {
     byte + b'0'
 }
+
+// Writes `value` as zero-padded decimal digits that fill all of `digits`.
+fn write_padded_digits(digits: &mut [u8], mut value: usize) {
+    for digit in digits.iter_mut().rev() {
+        *digit = to_digit((value % 10) as u8);
+        value /= 10;
+    }
+}
+
+// The number of decimal digits of `tag`.
+fn tag_len(tag: TagU16) -> usize {
+    let mut tag = tag.get();
+    let mut len = 1;
+    while tag >= 10 {
+        tag /= 10;
+        len += 1;
+    }
+    len
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tagvalue::{test_decoder, FieldAccess};
+    use crate::Dictionary;
+
+    fn encoder() -> Encoder<Config> {
+        let mut encoder = Encoder::<Config>::default();
+        encoder.config_mut().set_separator(b'|');
+        encoder
+    }
+
+    #[test]
+    fn canonical_order_moves_groups_as_a_whole() {
+        let mut encoder = encoder();
+        encoder.set_canonical_order(Some(&Dictionary::fix44()));
+        assert!(encoder.canonical_order());
+        let mut buffer = Vec::new();
+        let mut msg = encoder.start_message(b"FIX.4.4", &mut buffer, b"V");
+        msg.set(fix44::MARKET_DEPTH, 1u32);
+        msg.begin_group(fix44::NO_RELATED_SYM, fix44::SYMBOL);
+        msg.set(fix44::SYMBOL, "EUR/USD");
+        msg.end_group();
+        msg.set_any(TagU16::new(9999).unwrap(), "x");
+        msg.set(fix44::MD_REQ_ID, "R");
+        msg.set(fix44::SENDER_COMP_ID, "A");
+        msg.set(fix44::SIGNATURE_LENGTH, 1usize);
+        msg.set(fix44::SUBSCRIPTION_REQUEST_TYPE, b'0');
+        let data = msg.finalize().unwrap().to_vec();
+        assert!(data.starts_with(
+            b"8=FIX.4.4|9=000062|35=V|49=A|262=R|263=0|264=1|146=000001|55=EUR/USD|9999=x|93=1|10="
+        ));
+        let mut decoder = test_decoder();
+        decoder.config_mut().set_verify_checksum(true);
+        assert!(decoder.decode(&data[..]).is_ok());
+    }
+
+    #[test]
+    fn fields_from_iter_roundtrip() {
+        let fields = [
+            (55, &b"EUR/USD"[..]),
+            (268, b"2"),
+            (279, b"0"),
+            (279, b"1"),
+            (10000, b"x"),
+        ];
+        let fields = fields
+            .iter()
+            .map(|(tag, value)| (TagU16::new(*tag).unwrap(), *value));
+        let expected_len: usize = fields.clone().map(|(t, v)| tag_len(t) + v.len() + 2).sum();
+        assert_eq!(expected_len, 37);
+        let mut buffer = Vec::new();
+        let mut encoder = encoder();
+        let mut msg = encoder.start_message(b"FIX.4.4", &mut buffer, b"X");
+        msg.extend_from_iter(fields);
+        let data = msg.wrap().to_vec();
+        assert!(
+            data.starts_with(b"8=FIX.4.4|9=000042|35=X|55=EUR/USD|268=2|279=0|279=1|10000=x|10=")
+        );
+        let mut decoder = test_decoder();
+        let message = decoder.decode(&data[..]).unwrap();
+        assert_eq!(message.fv(fix44::SYMBOL), Ok("EUR/USD"));
+    }
+
+    #[test]
+    fn data_fields_roundtrip_with_separators() {
+        let mut buffer = Vec::new();
+        let mut encoder = encoder();
+        let mut msg = encoder.start_message(b"FIX.4.4", &mut buffer, b"B");
+        msg.set_data_field(fix44::XML_DATA_LEN, fix44::XML_DATA, b"<a>|b|</a>");
+        msg.set(fix44::HEADLINE, "News");
+        let data = msg.wrap().to_vec();
+        let mut decoder = test_decoder();
+        let message = decoder.decode(&data[..]).unwrap();
+        assert_eq!(message.fv(fix44::XML_DATA_LEN), Ok(10usize));
+        assert_eq!(message.fv_raw(fix44::XML_DATA), Some(&b"<a>|b|</a>"[..]));
+        assert_eq!(message.fv(fix44::HEADLINE), Ok("News"));
+    }
+
+    #[test]
+    fn group_count_is_written_before_entries() {
+        let mut buffer = Vec::new();
+        let mut encoder = encoder();
+        let mut msg = encoder.start_message(b"FIX.4.4", &mut buffer, b"X");
+        msg.begin_group(fix44::NO_MD_ENTRIES, fix44::MD_UPDATE_ACTION);
+        msg.set(fix44::MD_UPDATE_ACTION, fix44::MdUpdateAction::New);
+        msg.set(fix44::MD_ENTRY_ID, "A");
+        msg.set(fix44::MD_UPDATE_ACTION, fix44::MdUpdateAction::Change);
+        msg.set(fix44::MD_ENTRY_ID, "B");
+        msg.set(fix44::MD_UPDATE_ACTION, fix44::MdUpdateAction::Delete);
+        msg.end_group();
+        msg.set(fix44::APPL_QUEUE_DEPTH, 1u32);
+        let data = msg.wrap();
+        assert!(data.starts_with(
+            b"8=FIX.4.4|9=000052|35=X|268=000003|279=0|278=A|279=1|278=B|279=2|813=1|10="
+        ));
+    }
+
+    #[test]
+    fn empty_group() {
+        let mut buffer = Vec::new();
+        let mut encoder = encoder();
+        let mut msg = encoder.start_message(b"FIX.4.4", &mut buffer, b"X");
+        msg.begin_group(fix44::NO_MD_ENTRIES, fix44::MD_UPDATE_ACTION);
+        msg.end_group();
+        let data = msg.wrap();
+        assert!(data.starts_with(b"8=FIX.4.4|9=000016|35=X|268=000000|10="));
+    }
+
+    #[test]
+    fn nested_groups_roundtrip() {
+        let mut buffer = Vec::new();
+        let mut encoder = encoder();
+        let mut msg = encoder.start_message(b"FIX.4.4", &mut buffer, b"8");
+        msg.begin_group(fix44::NO_PARTY_I_DS, fix44::PARTY_ID);
+        msg.set(fix44::PARTY_ID, "A");
+        msg.begin_group(fix44::NO_PARTY_SUB_I_DS, fix44::PARTY_SUB_ID);
+        msg.set(fix44::PARTY_SUB_ID, "X");
+        msg.set(fix44::PARTY_SUB_ID, "Y");
+        msg.end_group();
+        msg.set(fix44::PARTY_ID, "B");
+        msg.end_group();
+        msg.set(fix44::SIDE, fix44::Side::Buy);
+        let data = msg.wrap().to_vec();
+        let mut decoder = test_decoder();
+        let message = decoder.decode(&data[..]).unwrap();
+        let parties = message.group(fix44::NO_PARTY_I_DS).unwrap();
+        assert_eq!(parties.len(), 2);
+        let sub_ids = parties.entry(0).group(fix44::NO_PARTY_SUB_I_DS).unwrap();
+        assert_eq!(sub_ids.len(), 2);
+        assert_eq!(
+            sub_ids.entry(1).fv_raw(fix44::PARTY_SUB_ID),
+            Some(b"Y" as &[u8])
+        );
+        assert_eq!(
+            parties.entry(1).fv_raw(fix44::PARTY_ID),
+            Some(b"B" as &[u8])
+        );
+        assert_eq!(message.fv(fix44::SIDE), Ok(fix44::Side::Buy));
+    }
+
+    #[test]
+    fn group_entry_must_start_with_delimiter() {
+        let mut buffer = Vec::new();
+        let mut encoder = encoder();
+        let mut msg = encoder.start_message(b"FIX.4.4", &mut buffer, b"X");
+        msg.begin_group(fix44::NO_MD_ENTRIES, fix44::MD_UPDATE_ACTION);
+        msg.set(fix44::MD_ENTRY_ID, "A");
+        msg.set(fix44::MD_UPDATE_ACTION, fix44::MdUpdateAction::New);
+        msg.end_group();
+        assert_eq!(
+            msg.finalize(),
+            Err(EncodeError::GroupEntryWithoutDelimiter {
+                tag: fix44::MD_ENTRY_ID.tag(),
+                delimiter: fix44::MD_UPDATE_ACTION.tag(),
+            })
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn wrap_panics_on_group_entry_without_delimiter() {
+        let mut buffer = Vec::new();
+        let mut encoder = encoder();
+        let mut msg = encoder.start_message(b"FIX.4.4", &mut buffer, b"X");
+        msg.begin_group(fix44::NO_MD_ENTRIES, fix44::MD_UPDATE_ACTION);
+        msg.set(fix44::MD_ENTRY_ID, "A");
+        msg.end_group();
+        msg.wrap();
+    }
+
+    #[test]
+    fn ending_a_missing_group_is_an_error() {
+        let mut buffer = Vec::new();
+        let mut encoder = encoder();
+        let mut msg = encoder.start_message(b"FIX.4.4", &mut buffer, b"0");
+        msg.end_group();
+        assert_eq!(msg.finalize(), Err(EncodeError::NoGroupToEnd));
+    }
+
+    #[test]
+    fn large_groups_are_counted_in_place() {
+        let mut buffer = Vec::new();
+        let mut encoder = encoder();
+        let mut msg = encoder.start_message(b"FIX.4.4", &mut buffer, b"W");
+        msg.begin_group(fix44::NO_MD_ENTRIES, fix44::MD_ENTRY_TYPE);
+        for i in 0..3_000u32 {
+            msg.set(fix44::MD_ENTRY_TYPE, fix44::MdEntryType::Bid);
+            msg.set(fix44::MD_ENTRY_PX, i);
+        }
+        msg.end_group();
+        let data = msg.finalize().unwrap().to_vec();
+        let mut decoder = test_decoder();
+        let message = decoder.decode(&data[..]).unwrap();
+        let entries = message.group(fix44::NO_MD_ENTRIES).unwrap();
+        assert_eq!(entries.len(), 3_000);
+        assert_eq!(entries.entry(2_999).fv(fix44::MD_ENTRY_PX), Ok(2_999u32));
+    }
+
+    #[test]
+    fn slice_buffer_overflow_doesnt_panic() {
+        use crate::SliceBuffer;
+        let mut encoder = encoder();
+        for capacity in 0..32 {
+            let mut data = vec![0u8; capacity];
+            let mut buffer = SliceBuffer::new(&mut data[..]);
+            let mut msg = encoder.start_message(b"FIX.4.4", &mut buffer, b"0");
+            msg.set(fix44::TEST_REQ_ID, "foobar");
+            msg.wrap();
+            assert!(buffer.has_overflowed());
+            buffer.clear();
+            let mut msg = encoder.start_message(b"FIX.4.4", &mut buffer, b"0");
+            msg.set(fix44::TEST_REQ_ID, "foobar");
+            assert_eq!(
+                msg.finalize(),
+                Err(EncodeError::CapacityExceeded { capacity })
+            );
+        }
+    }
+
+    #[test]
+    fn finalize_refuses_truncated_checksums() {
+        use crate::SliceBuffer;
+        let mut encoder = encoder();
+        let mut expected = Vec::new();
+        let mut msg = encoder.start_message(b"FIX.4.4", &mut expected, b"0");
+        msg.set(fix44::TEST_REQ_ID, "foobar");
+        let len = msg.finalize().unwrap().len();
+        let mut data = vec![0u8; len];
+        let mut buffer = SliceBuffer::new(&mut data[..len - 1]);
+        let mut msg = encoder.start_message(b"FIX.4.4", &mut buffer, b"0");
+        msg.set(fix44::TEST_REQ_ID, "foobar");
+        assert_eq!(
+            msg.finalize(),
+            Err(EncodeError::CapacityExceeded { capacity: len - 1 })
+        );
+        let mut buffer = SliceBuffer::new(&mut data[..]);
+        let mut msg = encoder.start_message(b"FIX.4.4", &mut buffer, b"0");
+        msg.set(fix44::TEST_REQ_ID, "foobar");
+        assert_eq!(msg.finalize().unwrap(), &expected[..]);
+    }
+
+    #[test]
+    fn reserved_capacity_avoids_reallocations() {
+        let mut buffer = Vec::new();
+        let mut encoder = encoder();
+        let mut msg = encoder.start_message_with_capacity(b"FIX.4.4", &mut buffer, b"B", 4096);
+        let start = msg.buffer.as_slice().as_ptr();
+        for _ in 0..64 {
+            msg.set(fix44::HEADLINE, "News");
+        }
+        assert_eq!(msg.buffer.as_slice().as_ptr(), start);
+        let data = msg.finalize().unwrap();
+        assert_eq!(data.as_ptr(), start);
+    }
+
+    #[test]
+    fn finalize_matches_wrap() {
+        let mut encoder = encoder();
+        let mut encode = |finalize: bool| {
+            let mut buffer = Vec::new();
+            let mut msg = encoder.start_message_with_capacity(b"FIX.4.4", &mut buffer, b"X", 64);
+            msg.begin_group(fix44::NO_MD_ENTRIES, fix44::MD_UPDATE_ACTION);
+            msg.set(fix44::MD_UPDATE_ACTION, fix44::MdUpdateAction::New);
+            msg.end_group();
+            if finalize {
+                msg.finalize().unwrap().to_vec()
+            } else {
+                msg.wrap().to_vec()
+            }
+        };
+        assert_eq!(encode(true), encode(false));
+    }
+
+    #[test]
+    fn finalize_refuses_overlong_bodies() {
+        let mut buffer = Vec::new();
+        let mut encoder = encoder();
+        let mut msg = encoder.start_message(b"FIX.4.4", &mut buffer, b"B");
+        msg.set(fix44::HEADLINE, &[b'a'; 1_000_000][..]);
+        assert_eq!(
+            msg.finalize(),
+            Err(EncodeError::BodyTooLong {
+                len: 1_000_010,
+                max: 999_999
+            })
+        );
+    }
+
+    #[test]
+    fn finalize_refuses_unterminated_groups() {
+        let mut buffer = Vec::new();
+        let mut encoder = encoder();
+        let mut msg = encoder.start_message(b"FIX.4.4", &mut buffer, b"X");
+        msg.begin_group(fix44::NO_MD_ENTRIES, fix44::MD_UPDATE_ACTION);
+        msg.set(fix44::MD_UPDATE_ACTION, fix44::MdUpdateAction::New);
+        assert_eq!(
+            msg.finalize(),
+            Err(EncodeError::UnterminatedGroup {
+                tag: fix44::NO_MD_ENTRIES.tag()
+            })
+        );
+    }
+
+    /// Writes at most `max` bytes per call, to simulate partial writes.
+    #[cfg(feature = "std")]
+    struct Trickle {
+        output: Vec<u8>,
+        max: usize,
+    }
+
+    #[cfg(feature = "std")]
+    impl io::Write for Trickle {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            let n = buf.len().min(self.max);
+            self.output.extend_from_slice(&buf[..n]);
+            Ok(n)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[cfg(feature = "std")]
+    fn encode_snapshot<B>(msg: &mut EncoderHandle<B>)
+    where
+        B: Buffer,
+    {
+        msg.set(fix44::SYMBOL, "EUR/USD");
+        msg.begin_group(fix44::NO_MD_ENTRIES, fix44::MD_ENTRY_TYPE);
+        for i in 0..100u32 {
+            msg.set(fix44::MD_ENTRY_TYPE, fix44::MdEntryType::Bid);
+            msg.set(fix44::MD_ENTRY_PX, i);
+        }
+        msg.end_group();
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn vectored_messages_decode_like_regular_ones() {
+        let mut encoder = encoder();
+        let mut buffer = Vec::new();
+        let mut msg = encoder.start_message(b"FIX.4.4", &mut buffer, b"W");
+        encode_snapshot(&mut msg);
+        let regular = msg.finalize().unwrap().to_vec();
+        let header = HeaderTemplate::new(b"FIX.4.4", b'|');
+        let mut buffer = b"unrelated".to_vec();
+        let mut msg = encoder.start_vectored_message(&header, &mut buffer, b"W");
+        encode_snapshot(&mut msg);
+        let msg = msg.finalize().unwrap();
+        let mut vectored = Vec::new();
+        msg.write_to(&mut vectored).unwrap();
+        assert_eq!(vectored.len(), msg.len());
+        assert_eq!(vectored.len(), regular.len() - 2);
+        let mut decoders = [test_decoder(), test_decoder()];
+        for decoder in decoders.iter_mut() {
+            decoder.config_mut().set_verify_checksum(true);
+        }
+        let [decoder_1, decoder_2] = &mut decoders;
+        let regular = decoder_1.decode(&regular[..]).unwrap();
+        let vectored = decoder_2.decode(&vectored[..]).unwrap();
+        assert_eq!(
+            vectored.fields().skip(2).collect::<Vec<_>>(),
+            regular.fields().skip(2).collect::<Vec<_>>()
+        );
+        assert!(buffer.starts_with(b"unrelated35=W|"));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn vectored_messages_survive_partial_writes() {
+        let mut encoder = encoder();
+        let header = HeaderTemplate::new(b"FIX.4.2", b'|');
+        let mut buffer = Vec::new();
+        let mut msg = encoder.start_vectored_message(&header, &mut buffer, b"0");
+        msg.set(fix44::TEST_REQ_ID, "foobar");
+        let msg = msg.finalize().unwrap();
+        let mut writer = Trickle {
+            output: Vec::new(),
+            max: 3,
+        };
+        msg.write_to(&mut writer).unwrap();
+        assert_eq!(
+            writer.output,
+            b"8=FIX.4.2|9=16|35=0|112=foobar|10=010|" as &[u8]
+        );
+    }
+
+    #[test]
+    fn vectored_messages_refuse_unterminated_groups() {
+        let mut encoder = encoder();
+        let header = HeaderTemplate::new(b"FIX.4.4", b'|');
+        let mut buffer = Vec::new();
+        let mut msg = encoder.start_vectored_message(&header, &mut buffer, b"X");
+        msg.begin_group(fix44::NO_MD_ENTRIES, fix44::MD_UPDATE_ACTION);
+        msg.set(fix44::MD_UPDATE_ACTION, fix44::MdUpdateAction::New);
+        assert_eq!(
+            msg.finalize().unwrap_err(),
+            EncodeError::UnterminatedGroup {
+                tag: fix44::NO_MD_ENTRIES.tag()
+            }
+        );
+    }
+}
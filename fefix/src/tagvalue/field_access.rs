@@ -1,7 +1,11 @@
 use crate::dict::IsFieldDefinition;
 use crate::FixValue;
-use crate::{OptError, OptResult};
-use std::iter::FusedIterator;
+use crate::{IntoOptResult, OptError, OptResult, TagU16};
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::any::type_name;
+use core::fmt;
+use core::iter::FusedIterator;
 
 /// Provides access to entries within a FIX repeating group.
 pub trait RepeatingGroup: Sized {
@@ -20,6 +24,16 @@ pub trait RepeatingGroup: Sized {
     /// `self`.
     fn entry(&self, i: usize) -> Self::Entry;
 
+    /// Returns the `i` -th entry in `self`, or [`None`] if `i` is outside the
+    /// legal range of `self`.
+    fn entry_opt(&self, i: usize) -> Option<Self::Entry> {
+        if i < self.len() {
+            Some(self.entry(i))
+        } else {
+            None
+        }
+    }
+
     /// Creates and returns an [`Iterator`] over the entries in `self`.
     /// Iteration MUST be done in sequential order, i.e. in which they appear in
     /// the original FIX message.
@@ -104,6 +118,8 @@ where
 /// - `l` stands for *lossy*, i.e. invalid field values might not be detected to
 /// improve performance.
 /// - `_opt` stands for *optional*, for better error reporting.
+/// - `_ctx` stands for *context*, i.e. errors are [`FieldError`]s which tell
+///   the tag, raw contents, and target type of the field.
 pub trait FieldAccess {
     /// The type returned by [`FieldAccess::group()`] and [`FieldAccess::group_opt()`].
     type Group: RepeatingGroup;
@@ -114,11 +130,7 @@ pub trait FieldAccess {
     where
         F: IsFieldDefinition,
     {
-        match self.group_opt(field) {
-            Some(Ok(group)) => Ok(group),
-            Some(Err(e)) => Err(OptError::Other(e)),
-            None => Err(OptError::None),
-        }
+        self.group_opt(field).into_opt_result()
     }
 
     /// Queries `self` for a group tagged with `key` which may or may not be
@@ -141,11 +153,7 @@ pub trait FieldAccess {
         V: FixValue<'a>,
         F: IsFieldDefinition,
     {
-        match self.fv_opt(field) {
-            Some(Ok(x)) => Ok(x),
-            Some(Err(err)) => Err(OptError::Other(err)),
-            None => Err(OptError::None),
-        }
+        self.fv_opt(field).into_opt_result()
     }
 
     /// Like [`FieldAccess::fv()`], but with lossy deserialization.
@@ -155,11 +163,7 @@ pub trait FieldAccess {
         V: FixValue<'a>,
         F: IsFieldDefinition,
     {
-        match self.fvl_opt(field) {
-            Some(Ok(x)) => Ok(x),
-            Some(Err(err)) => Err(OptError::Other(err)),
-            None => Err(OptError::None),
-        }
+        self.fvl_opt(field).into_opt_result()
     }
 
     /// Queries `self` for `field` and deserializes it. This
@@ -190,4 +194,166 @@ pub trait FieldAccess {
                 Err(err) => Err(err.into()),
             })
     }
+
+    /// Like [`FieldAccess::fv()`], but failures result in a [`FieldError`]
+    /// that describes what went wrong.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix::definitions::fix44;
+    /// use fefix::tagvalue::{Config, Configure, Decoder, FieldAccess};
+    /// use fefix::Dictionary;
+    ///
+    /// let mut decoder = Decoder::<Config>::new(Dictionary::fix44());
+    /// decoder.config_mut().set_separator(b'|');
+    /// let message = decoder
+    ///     .decode(&b"8=FIX.4.4|9=17|35=D|38=1O0|55=X|10=000|"[..])
+    ///     .unwrap();
+    /// let err = message.fv_ctx::<u64, _>(fix44::ORDER_QTY).unwrap_err();
+    /// assert_eq!(err.tag().get(), 38);
+    /// assert_eq!(err.raw(), Some(&b"1O0"[..]));
+    /// assert_eq!(err.type_name(), "u64");
+    /// assert_eq!(
+    ///     err.to_string(),
+    ///     "Invalid value \"1O0\" of OrderQty <38> as u64: \"Invalid integer digits.\"",
+    /// );
+    /// ```
+    #[inline]
+    fn fv_ctx<'a, V, F>(&'a self, field: &F) -> Result<V, FieldError<V::Error>>
+    where
+        V: FixValue<'a>,
+        F: IsFieldDefinition,
+    {
+        match self.fv_raw(field) {
+            Some(raw) => {
+                V::deserialize(raw).map_err(|err| FieldError::invalid::<V, F>(field, raw, err))
+            }
+            None => Err(FieldError::missing::<V, F>(field)),
+        }
+    }
+
+    /// Like [`FieldAccess::fv_ctx()`], but with lossy deserialization.
+    #[inline]
+    fn fvl_ctx<'a, V, F>(&'a self, field: &F) -> Result<V, FieldError<V::Error>>
+    where
+        V: FixValue<'a>,
+        F: IsFieldDefinition,
+    {
+        match self.fv_raw(field) {
+            Some(raw) => V::deserialize_lossy(raw)
+                .map_err(|err| FieldError::invalid::<V, F>(field, raw, err)),
+            None => Err(FieldError::missing::<V, F>(field)),
+        }
+    }
+}
+
+/// The error type of [`FieldAccess::fv_ctx()`] and [`FieldAccess::fvl_ctx()`]:
+/// a missing field, or one that couldn't be deserialized. Either way, it
+/// tells the tag of the field and the Rust type it was requested as.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FieldError<E> {
+    tag: TagU16,
+    name: String,
+    raw: Option<Vec<u8>>,
+    type_name: &'static str,
+    error: Option<E>,
+}
+
+impl<E> FieldError<E> {
+    fn missing<'a, V, F>(field: &F) -> Self
+    where
+        V: FixValue<'a>,
+        F: IsFieldDefinition,
+    {
+        Self {
+            tag: field.tag(),
+            name: field.name().to_string(),
+            raw: None,
+            type_name: type_name::<V>(),
+            error: None,
+        }
+    }
+
+    fn invalid<'a, V, F>(field: &F, raw: &[u8], error: E) -> Self
+    where
+        V: FixValue<'a>,
+        F: IsFieldDefinition,
+    {
+        Self {
+            tag: field.tag(),
+            name: field.name().to_string(),
+            raw: Some(raw.to_vec()),
+            type_name: type_name::<V>(),
+            error: Some(error),
+        }
+    }
+
+    /// Returns the tag of the field.
+    pub fn tag(&self) -> TagU16 {
+        self.tag
+    }
+
+    /// Returns the name of the field, e.g. `OrderQty`.
+    pub fn name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    /// Returns the raw contents of the field, or [`None`] if the field is
+    /// missing.
+    pub fn raw(&self) -> Option<&[u8]> {
+        self.raw.as_deref()
+    }
+
+    /// Returns the name of the Rust type that the field was requested as, as
+    /// given by [`std::any::type_name`].
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+
+    /// Returns `true` if and only if the field is missing.
+    pub fn is_missing(&self) -> bool {
+        self.raw.is_none()
+    }
+
+    /// Returns the deserialization error, or [`None`] if the field is missing.
+    pub fn error(&self) -> Option<&E> {
+        self.error.as_ref()
+    }
+
+    /// Discards the context of `self`, e.g. to recover the [`OptError`] that
+    /// [`FieldAccess::fv()`] would have returned.
+    pub fn into_opt_error(self) -> OptError<E> {
+        match self.error {
+            Some(error) => OptError::Other(error),
+            None => OptError::None,
+        }
+    }
+}
+
+impl<E> fmt::Display for FieldError<E>
+where
+    E: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (&self.raw, &self.error) {
+            (Some(raw), Some(error)) => write!(
+                f,
+                "Invalid value {:?} of {} <{}> as {}: {:?}",
+                String::from_utf8_lossy(raw),
+                self.name,
+                self.tag,
+                self.type_name,
+                error
+            ),
+            _ => write!(
+                f,
+                "Missing {} <{}>, expected as {}",
+                self.name, self.tag, self.type_name
+            ),
+        }
+    }
 }
+
+#[cfg(feature = "std")]
+impl<E> std::error::Error for FieldError<E> where E: fmt::Debug {}
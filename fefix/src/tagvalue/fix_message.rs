@@ -0,0 +1,209 @@
+use super::{Configure, EncoderHandle, Message};
+use crate::buffer::Buffer;
+use crate::{FixValue, TagU16};
+use core::fmt;
+
+/// A mapping between a Rust `struct` and a FIX message, usually implemented
+/// via `#[derive(FixMessage)]`.
+///
+/// The derive macro maps each `struct` field to a FIX tag with the
+/// `#[fefix(tag = ...)]` attribute. Field types must implement [`FixValue`];
+/// [`Option`] fields are optional, all others are mandatory.
+///
+/// # Examples
+///
+/// ```
+/// use fefix::tagvalue::{Config, Decoder, Encoder, FixMessage};
+/// use fefix::Dictionary;
+///
+/// #[derive(Debug, PartialEq, FixMessage)]
+/// struct NewOrderSingle {
+///     #[fefix(tag = 11)]
+///     cl_ord_id: String,
+///     #[fefix(tag = 38)]
+///     order_qty: u64,
+///     #[fefix(tag = 58)]
+///     text: Option<String>,
+/// }
+///
+/// let order = NewOrderSingle {
+///     cl_ord_id: "ABC".to_string(),
+///     order_qty: 100,
+///     text: None,
+/// };
+/// let mut encoder = Encoder::<Config>::default();
+/// let mut buffer = Vec::new();
+/// let mut msg = encoder.start_message(b"FIX.4.4", &mut buffer, b"D");
+/// order.encode_into(&mut msg);
+/// let data = msg.wrap();
+///
+/// let mut decoder = Decoder::<Config>::new(Dictionary::fix44());
+/// let message = decoder.decode(data).unwrap();
+/// assert_eq!(NewOrderSingle::decode_from(&message), Ok(order));
+/// ```
+pub trait FixMessage<'a>: Sized {
+    /// Builds a new instance of `Self` from the top-level fields of `message`.
+    fn decode_from<T>(message: &Message<'a, T>) -> Result<Self, FixMessageError>
+    where
+        T: AsRef<[u8]>;
+
+    /// Writes all fields of `self` to `msg`, in declaration order. Absent
+    /// optional fields are skipped.
+    fn encode_into<B, C>(&self, msg: &mut EncoderHandle<B, C>)
+    where
+        B: Buffer,
+        C: Configure;
+}
+
+/// The type returned in the event of an error by [`FixMessage::decode_from`].
+///
+/// Please note that [`FixMessageError`] is marked with `#[non_exhaustive]`,
+/// which future-proofs the enumeration type in case more variants are added.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum FixMessageError {
+    /// A mandatory field is missing.
+    MissingField {
+        /// The tag of the missing field.
+        tag: TagU16,
+    },
+    /// A field is present, but its value couldn't be deserialized.
+    InvalidField {
+        /// The tag of the invalid field.
+        tag: TagU16,
+    },
+}
+
+impl fmt::Display for FixMessageError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::MissingField { tag } => write!(f, "Missing mandatory field (tag {})", tag),
+            Self::InvalidField { tag } => write!(f, "Invalid field value (tag {})", tag),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for FixMessageError {}
+
+/// Code which is only meant to be used by `#[derive(FixMessage)]`.
+#[doc(hidden)]
+pub mod __private {
+    use super::*;
+
+    fn tag(tag: u16) -> TagU16 {
+        TagU16::new(tag).expect("FIX tags must be positive")
+    }
+
+    pub fn decode_field<'a, T, V>(
+        message: &Message<'a, T>,
+        tag_u16: u16,
+    ) -> Result<V, FixMessageError>
+    where
+        T: AsRef<[u8]>,
+        V: FixValue<'a>,
+    {
+        decode_optional_field(message, tag_u16)?
+            .ok_or(FixMessageError::MissingField { tag: tag(tag_u16) })
+    }
+
+    pub fn decode_optional_field<'a, T, V>(
+        message: &Message<'a, T>,
+        tag_u16: u16,
+    ) -> Result<Option<V>, FixMessageError>
+    where
+        T: AsRef<[u8]>,
+        V: FixValue<'a>,
+    {
+        let tag = tag(tag_u16);
+        match message.fv_raw_by_tag(tag) {
+            Some(data) => V::deserialize(data)
+                .map(Some)
+                .map_err(|_| FixMessageError::InvalidField { tag }),
+            None => Ok(None),
+        }
+    }
+
+    pub fn encode_field<'b, B, C, V>(msg: &mut EncoderHandle<B, C>, tag_u16: u16, value: &V)
+    where
+        B: Buffer,
+        C: Configure,
+        V: FixValue<'b>,
+    {
+        msg.set_any_ref(tag(tag_u16), value);
+    }
+
+    pub fn encode_optional_field<'b, B, C, V>(
+        msg: &mut EncoderHandle<B, C>,
+        tag_u16: u16,
+        value: &Option<V>,
+    ) where
+        B: Buffer,
+        C: Configure,
+        V: FixValue<'b>,
+    {
+        if let Some(value) = value {
+            encode_field(msg, tag_u16, value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tagvalue::{test_decoder, Config, Encoder};
+
+    #[derive(Debug, PartialEq, crate::tagvalue::FixMessage)]
+    struct Heartbeat<'a> {
+        #[fefix(tag = 112)]
+        test_req_id: Option<&'a str>,
+        #[fefix(tag = 34)]
+        msg_seq_num: u64,
+    }
+
+    #[test]
+    fn struct_with_lifetime_borrows_from_message() {
+        let mut decoder = test_decoder();
+        let data = b"8=FIX.4.4|9=19|35=0|34=7|112=PING|10=027|";
+        let message = decoder.decode(&data[..]).unwrap();
+        let heartbeat = Heartbeat::decode_from(&message).unwrap();
+        assert_eq!(heartbeat.test_req_id, Some("PING"));
+        assert_eq!(heartbeat.msg_seq_num, 7);
+    }
+
+    #[test]
+    fn missing_and_invalid_fields_are_reported() {
+        let mut decoder = test_decoder();
+        let data = b"8=FIX.4.4|9=11|35=0|112=X|10=230|";
+        let message = decoder.decode(&data[..]).unwrap();
+        assert_eq!(
+            Heartbeat::decode_from(&message),
+            Err(FixMessageError::MissingField {
+                tag: TagU16::new(34).unwrap()
+            })
+        );
+        let data = b"8=FIX.4.4|9=10|35=0|34=X|10=184|";
+        let message = decoder.decode(&data[..]).unwrap();
+        assert_eq!(
+            Heartbeat::decode_from(&message),
+            Err(FixMessageError::InvalidField {
+                tag: TagU16::new(34).unwrap()
+            })
+        );
+    }
+
+    #[test]
+    fn absent_optional_fields_are_not_encoded() {
+        let mut encoder = Encoder::<Config>::default();
+        encoder.config_mut().set_separator(b'|');
+        let mut buffer = Vec::new();
+        let mut msg = encoder.start_message(b"FIX.4.4", &mut buffer, b"0");
+        let heartbeat = Heartbeat {
+            test_req_id: None,
+            msg_seq_num: 2,
+        };
+        heartbeat.encode_into(&mut msg);
+        let data = msg.wrap();
+        assert!(data.starts_with(b"8=FIX.4.4|9=000010|35=0|34=2|10="));
+    }
+}
@@ -0,0 +1,221 @@
+use super::{Configure, Encoder, EncoderHandle};
+use crate::buffer::Buffer;
+use crate::definitions::fix44;
+use crate::fix_values::Timestamp;
+use crate::TagU16;
+use alloc::vec::Vec;
+
+/// A reusable template for the `StandardHeader` of outbound messages.
+///
+/// [`HeaderSpec`] keeps track of the session-level fields that must appear in
+/// every message, i.e.
+///
+/// - `BeginString <8>`;
+/// - `SenderCompID <49>`;
+/// - `TargetCompID <56>`;
+/// - `MsgSeqNum <34>`, which is incremented after each message;
+/// - `SendingTime <52>`.
+///
+/// Additional, static header fields (e.g. `SenderSubID <50>`) can be added
+/// with [`HeaderSpec::add_field`].
+///
+/// # Examples
+///
+/// ```
+/// use fefix::definitions::fix44;
+/// use fefix::tagvalue::{Config, Decoder, Encoder, FieldAccess, HeaderSpec};
+/// use fefix::Dictionary;
+///
+/// let mut header = HeaderSpec::new(b"FIX.4.4", "SENDER", "TARGET");
+/// header.set_next_seq_num(42);
+/// let mut encoder = Encoder::<Config>::default();
+/// let mut buffer = Vec::new();
+/// let mut msg = encoder.start_message_with_header(&mut header, &mut buffer, b"0");
+/// let data = msg.wrap();
+/// assert_eq!(header.next_seq_num(), 43);
+///
+/// let mut decoder = Decoder::<Config>::new(Dictionary::fix44());
+/// let msg = decoder.decode(data).unwrap();
+/// assert_eq!(msg.fv(fix44::SENDER_COMP_ID), Ok("SENDER"));
+/// assert_eq!(msg.fv(fix44::MSG_SEQ_NUM), Ok(42));
+/// ```
+#[derive(Debug, Clone)]
+pub struct HeaderSpec {
+    begin_string: Vec<u8>,
+    sender_comp_id: Vec<u8>,
+    target_comp_id: Vec<u8>,
+    next_seq_num: u64,
+    extra_fields: Vec<(TagU16, Vec<u8>)>,
+}
+
+impl HeaderSpec {
+    /// Creates a new [`HeaderSpec`]. The first message will be stamped with
+    /// `MsgSeqNum <34>` equal to 1.
+    pub fn new<S1, S2, S3>(begin_string: S1, sender_comp_id: S2, target_comp_id: S3) -> Self
+    where
+        S1: AsRef<[u8]>,
+        S2: AsRef<[u8]>,
+        S3: AsRef<[u8]>,
+    {
+        Self {
+            begin_string: begin_string.as_ref().to_vec(),
+            sender_comp_id: sender_comp_id.as_ref().to_vec(),
+            target_comp_id: target_comp_id.as_ref().to_vec(),
+            next_seq_num: 1,
+            extra_fields: Vec::new(),
+        }
+    }
+
+    /// Returns the `BeginString <8>` of `self`.
+    pub fn begin_string(&self) -> &[u8] {
+        &self.begin_string[..]
+    }
+
+    /// Returns the `SenderCompID <49>` of `self`.
+    pub fn sender_comp_id(&self) -> &[u8] {
+        &self.sender_comp_id[..]
+    }
+
+    /// Returns the `TargetCompID <56>` of `self`.
+    pub fn target_comp_id(&self) -> &[u8] {
+        &self.target_comp_id[..]
+    }
+
+    /// Returns the `MsgSeqNum <34>` of the next message stamped by `self`.
+    pub fn next_seq_num(&self) -> u64 {
+        self.next_seq_num
+    }
+
+    /// Sets the `MsgSeqNum <34>` of the next message stamped by `self`, e.g.
+    /// after a sequence reset.
+    ///
+    /// # Panics
+    ///
+    /// This method will panic if `seq_num` is 0.
+    pub fn set_next_seq_num(&mut self, seq_num: u64) {
+        assert_ne!(seq_num, 0, "MsgSeqNum <34> must be positive");
+        self.next_seq_num = seq_num;
+    }
+
+    /// Adds a static field to the header template. Fields are written right
+    /// after `SendingTime <52>`, in the same order they were added.
+    pub fn add_field<T>(&mut self, tag: TagU16, value: T)
+    where
+        T: AsRef<[u8]>,
+    {
+        self.extra_fields.push((tag, value.as_ref().to_vec()));
+    }
+
+    /// Writes all header fields to `msg`, with the current UTC time as
+    /// `SendingTime <52>`, and increments the next `MsgSeqNum <34>`.
+    #[cfg(feature = "std")]
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "std")))]
+    pub fn stamp<B, C>(&mut self, msg: &mut EncoderHandle<B, C>)
+    where
+        B: Buffer,
+        C: Configure,
+    {
+        self.stamp_with_sending_time(msg, Timestamp::utc_now());
+    }
+
+    /// Like [`HeaderSpec::stamp`], but with an explicit `sending_time`.
+    pub fn stamp_with_sending_time<B, C>(
+        &mut self,
+        msg: &mut EncoderHandle<B, C>,
+        sending_time: Timestamp,
+    ) where
+        B: Buffer,
+        C: Configure,
+    {
+        msg.set(fix44::SENDER_COMP_ID, &self.sender_comp_id[..]);
+        msg.set(fix44::TARGET_COMP_ID, &self.target_comp_id[..]);
+        msg.set(fix44::MSG_SEQ_NUM, self.next_seq_num);
+        msg.set_timestamp(fix44::SENDING_TIME, sending_time);
+        for (tag, value) in self.extra_fields.iter() {
+            msg.set_any(*tag, &value[..]);
+        }
+        self.next_seq_num += 1;
+    }
+}
+
+impl<C> Encoder<C>
+where
+    C: Configure,
+{
+    /// Like [`Encoder::start_message`], but the `StandardHeader` is
+    /// automatically populated by `header`. See [`HeaderSpec::stamp`].
+    #[cfg(feature = "std")]
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "std")))]
+    pub fn start_message_with_header<'a, B>(
+        &'a mut self,
+        header: &mut HeaderSpec,
+        buffer: &'a mut B,
+        msg_type: &[u8],
+    ) -> EncoderHandle<'a, B, C>
+    where
+        B: Buffer,
+    {
+        let mut msg = self.start_message(header.begin_string(), buffer, msg_type);
+        header.stamp(&mut msg);
+        msg
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::dict::IsFieldDefinition;
+    use crate::fix_values::{Date, Time};
+    use crate::tagvalue::Config;
+    #[cfg(feature = "std")]
+    use crate::tagvalue::{Decoder, FieldAccess};
+    #[cfg(feature = "std")]
+    use crate::Dictionary;
+
+    fn sending_time() -> Timestamp {
+        Timestamp::new(
+            Date::new(2021, 1, 1).unwrap(),
+            Time::from_hmsm(12, 30, 0, 0).unwrap(),
+        )
+    }
+
+    #[test]
+    fn header_fields_are_stamped_in_order() {
+        let mut header = HeaderSpec::new(b"FIX.4.2", b"A", b"B");
+        header.add_field(fix44::SENDER_SUB_ID.tag(), b"desk");
+        let mut encoder = Encoder::<Config>::default();
+        encoder.config_mut().set_separator(b'|');
+        let mut buffer = Vec::new();
+        let mut msg = encoder.start_message(header.begin_string(), &mut buffer, b"0");
+        header.stamp_with_sending_time(&mut msg, sending_time());
+        let data = msg.wrap();
+        assert_eq!(
+            data,
+            b"8=FIX.4.2|9=000053|35=0|49=A|56=B|34=1|52=20210101-12:30:00.000|50=desk|10=020|"
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn seq_num_is_incremented_after_each_message() {
+        let mut header = HeaderSpec::new(b"FIX.4.4", b"A", b"B");
+        let mut encoder = Encoder::<Config>::default();
+        let mut decoder = Decoder::<Config>::new(Dictionary::fix44());
+        let mut buffer = Vec::new();
+        for seq_num in 1..=3u64 {
+            buffer.clear();
+            let msg = encoder.start_message_with_header(&mut header, &mut buffer, b"0");
+            let data = msg.wrap();
+            let msg = decoder.decode(data).unwrap();
+            assert_eq!(msg.fv(fix44::MSG_SEQ_NUM), Ok(seq_num));
+            assert_eq!(msg.fv(fix44::TARGET_COMP_ID), Ok("B"));
+        }
+        assert_eq!(header.next_seq_num(), 4);
+    }
+
+    #[test]
+    #[should_panic]
+    fn seq_num_must_be_positive() {
+        HeaderSpec::new(b"FIX.4.4", b"A", b"B").set_next_seq_num(0);
+    }
+}
@@ -0,0 +1,228 @@
+use super::{Message, MessagePatcher};
+use crate::buffer::Buffer;
+use crate::definitions::fix44;
+use crate::dict::IsFieldDefinition;
+use crate::fix_values::CheckSum;
+use crate::utils::{IntMap, IntSet};
+use crate::FixValue;
+use crate::TagU16;
+
+const MASK: &[u8] = b"***";
+
+/// Hides the values of sensitive fields (e.g. credentials) within FIX messages,
+/// so that they can be logged safely.
+///
+/// By default, [`Masker`] masks `Password <554>`, `NewPassword <925>`, and
+/// `RawData <96>`. Masked values are replaced by `***` and `BodyLength <9>` and
+/// `CheckSum <10>` are adjusted accordingly, as well as the `Length` field of
+/// masked data fields. See [`Masker::set_preserve_length`] for replacing every
+/// byte of masked values instead.
+///
+/// # Examples
+///
+/// ```
+/// use fefix::tagvalue::{Config, Configure, Decoder, Masker};
+/// use fefix::Dictionary;
+///
+/// let mut decoder = Decoder::<Config>::new(Dictionary::fix44());
+/// decoder.config_mut().set_separator(b'|');
+/// let data = b"8=FIX.4.4|9=23|35=A|553=me|554=secret|10=090|";
+/// let message = decoder.decode(&data[..]).unwrap();
+/// let mut buffer = Vec::new();
+/// let masked = Masker::default().mask(&message, &mut buffer);
+/// assert_eq!(masked, b"8=FIX.4.4|9=20|35=A|553=me|554=***|10=079|");
+/// ```
+#[derive(Debug, Clone)]
+pub struct Masker {
+    tags: IntSet<u16>,
+    // Maps the tag of each masked data field to the tag of its `Length` field.
+    data_fields: IntMap<u16, u16>,
+    preserve_length: bool,
+}
+
+impl Masker {
+    /// Creates a new [`Masker`] that doesn't mask any field.
+    pub fn new() -> Self {
+        Self {
+            tags: IntSet::default(),
+            data_fields: IntMap::default(),
+            preserve_length: false,
+        }
+    }
+
+    /// Masks the values of `field`.
+    pub fn add_field<F>(&mut self, field: &F)
+    where
+        F: IsFieldDefinition,
+    {
+        self.tags.insert(field.tag().get());
+    }
+
+    /// Masks the values of `data_field`, which is preceded by `length_field`
+    /// (e.g. `RawDataLength <95>` and `RawData <96>`).
+    pub fn add_data_field<F1, F2>(&mut self, length_field: &F1, data_field: &F2)
+    where
+        F1: IsFieldDefinition,
+        F2: IsFieldDefinition,
+    {
+        self.tags.insert(data_field.tag().get());
+        self.data_fields
+            .insert(data_field.tag().get(), length_field.tag().get());
+    }
+
+    /// Returns `true` if and only if the values of fields with `tag` are
+    /// masked.
+    pub fn is_masked(&self, tag: TagU16) -> bool {
+        self.tags.contains(&tag.get())
+    }
+
+    /// If `preserve_length` is `true`, every byte of masked values is
+    /// replaced by `*`, so that the masked message has the same length as the
+    /// original and only `CheckSum <10>` is recomputed. This leaks the length
+    /// of masked values. Disabled by default.
+    pub fn set_preserve_length(&mut self, preserve_length: bool) {
+        self.preserve_length = preserve_length;
+    }
+
+    /// Writes a copy of `message` to `buffer`, after any existing content, with
+    /// all sensitive values masked. Fields within repeating groups and repeated
+    /// occurrences of the same tag are masked as well.
+    pub fn mask<'b, T, B>(&self, message: &Message<T>, buffer: &'b mut B) -> &'b [u8]
+    where
+        T: AsRef<[u8]>,
+        B: Buffer,
+    {
+        if self.preserve_length {
+            self.mask_in_place(message, buffer)
+        } else {
+            let mut patcher = MessagePatcher::new(message);
+            for tag in self.tags.iter() {
+                patcher.replace_all(TagU16::new(*tag).unwrap(), MASK);
+            }
+            for length_tag in self.data_fields.values() {
+                patcher.replace_all(TagU16::new(*length_tag).unwrap(), &MASK.len().to_bytes());
+            }
+            patcher.encode(buffer)
+        }
+    }
+
+    fn mask_in_place<'b, T, B>(&self, message: &Message<T>, buffer: &'b mut B) -> &'b [u8]
+    where
+        T: AsRef<[u8]>,
+        B: Buffer,
+    {
+        let raw = message.as_bytes();
+        let start = buffer.len();
+        buffer.extend_from_slice(raw);
+        let masked = &mut buffer.as_mut_slice()[start..];
+        // Every occurrence must be masked, including those of duplicate tags
+        // that `Message::fields` doesn't yield.
+        for (locator, value) in message.occurrences() {
+            if self.is_masked(locator.tag()) {
+                // Decoded values always borrow from the original message.
+                let offset = value.as_ptr() as usize - raw.as_ptr() as usize;
+                for byte in masked[offset..][..value.len()].iter_mut() {
+                    *byte = b'*';
+                }
+            }
+        }
+        // `CheckSum <10>` is always three digits long, so it can be recomputed
+        // in place.
+        let end_of_body = masked.len() - 7;
+        let checksum = CheckSum::compute(&masked[..end_of_body]);
+        let digits = checksum.to_bytes();
+        masked[end_of_body + 3..][..3].copy_from_slice(&digits[..]);
+        &buffer.as_slice()[start..]
+    }
+}
+
+impl Default for Masker {
+    fn default() -> Self {
+        let mut masker = Self::new();
+        masker.add_field(fix44::PASSWORD);
+        masker.add_field(fix44::NEW_PASSWORD);
+        masker.add_data_field(fix44::RAW_DATA_LENGTH, fix44::RAW_DATA);
+        masker
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tagvalue::{test_decoder, Config, Decoder, DuplicateTagPolicy};
+
+    fn decoder() -> Decoder<Config> {
+        let mut decoder = test_decoder();
+        decoder.config_mut().set_verify_checksum(true);
+        decoder
+    }
+
+    const LOGON: &[u8] = b"8=FIX.4.4|9=42|35=A|95=5|96=a|b|c|554=secret|925=secret2|10=230|";
+
+    #[test]
+    fn masked_values_are_replaced() {
+        let mut decoder = decoder();
+        let message = decoder.decode(LOGON).unwrap();
+        let mut buffer = Vec::new();
+        let masked = Masker::default().mask(&message, &mut buffer).to_vec();
+        assert_eq!(
+            &masked[..],
+            b"8=FIX.4.4|9=33|35=A|95=3|96=***|554=***|925=***|10=002|" as &[u8]
+        );
+        assert!(decoder.decode(&masked[..]).is_ok());
+    }
+
+    #[test]
+    fn masked_values_can_preserve_length() {
+        let mut decoder = decoder();
+        let message = decoder.decode(LOGON).unwrap();
+        let mut masker = Masker::default();
+        masker.set_preserve_length(true);
+        let mut buffer = b"prefix".to_vec();
+        let masked = masker.mask(&message, &mut buffer).to_vec();
+        assert_eq!(masked.len(), LOGON.len());
+        assert!(masked.starts_with(b"8=FIX.4.4|9=42|35=A|95=5|96=*****|554=******|925=*******|"));
+        assert!(decoder.decode(&masked[..]).is_ok());
+    }
+
+    const DUPLICATES: &[u8] = b"8=FIX.4.4|9=39|35=A|554=first1|925=x|554=second|925=y|10=130|";
+
+    #[test]
+    fn duplicate_tags_are_masked() {
+        let mut decoder = decoder();
+        for policy in [DuplicateTagPolicy::KeepFirst, DuplicateTagPolicy::KeepLast].iter() {
+            decoder.config_mut().set_duplicate_tag_policy(*policy);
+            let message = decoder.decode(DUPLICATES).unwrap();
+            let mut buffer = Vec::new();
+            let masked = Masker::default().mask(&message, &mut buffer).to_vec();
+            assert_eq!(
+                &masked[..],
+                b"8=FIX.4.4|9=37|35=A|554=***|925=***|554=***|925=***|10=178|" as &[u8]
+            );
+            assert!(decoder.decode(&masked[..]).is_ok());
+        }
+    }
+
+    #[test]
+    fn duplicate_tags_are_masked_preserving_length() {
+        let mut decoder = decoder();
+        let mut masker = Masker::default();
+        masker.set_preserve_length(true);
+        for policy in [DuplicateTagPolicy::KeepFirst, DuplicateTagPolicy::KeepLast].iter() {
+            decoder.config_mut().set_duplicate_tag_policy(*policy);
+            let message = decoder.decode(DUPLICATES).unwrap();
+            let mut buffer = Vec::new();
+            let masked = masker.mask(&message, &mut buffer).to_vec();
+            assert!(masked.starts_with(b"8=FIX.4.4|9=39|35=A|554=******|925=*|554=******|925=*|"));
+            assert!(decoder.decode(&masked[..]).is_ok());
+        }
+    }
+
+    #[test]
+    fn unmasked_messages_are_unchanged() {
+        let mut decoder = decoder();
+        let message = decoder.decode(LOGON).unwrap();
+        let mut buffer = Vec::new();
+        assert_eq!(Masker::new().mask(&message, &mut buffer), LOGON);
+    }
+}
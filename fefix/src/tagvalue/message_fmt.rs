@@ -0,0 +1,100 @@
+use super::Message;
+use crate::Dictionary;
+use alloc::string::{String, ToString};
+use core::fmt;
+
+/// A human-readable [`fmt::Display`] implementor for [`Message`], which renders
+/// fields with their names and enumeration descriptions as found in a
+/// [`Dictionary`], e.g. `ClOrdID(11)=ABC | Side(54)=BUY`.
+///
+/// Fields which are unknown to the [`Dictionary`] are rendered with their tag
+/// only, and non-UTF-8 values are rendered lossily.
+///
+/// This `struct` is created by the method [`Message::display_with`].
+///
+/// # Examples
+///
+/// ```
+/// use fefix::tagvalue::{Config, Configure, Decoder};
+/// use fefix::Dictionary;
+///
+/// let dict = Dictionary::fix44();
+/// let mut decoder = Decoder::<Config>::new(dict.clone());
+/// decoder.config_mut().set_separator(b'|');
+/// let data = b"8=FIX.4.4|9=17|35=D|11=ABC|54=1|10=143|";
+/// let message = decoder.decode(data).unwrap();
+/// assert_eq!(
+///     message.display_with(&dict).to_string(),
+///     "BeginString(8)=FIX.4.4 | MsgType(35)=ORDER_SINGLE | ClOrdID(11)=ABC | Side(54)=BUY"
+/// );
+/// ```
+#[derive(Debug)]
+pub struct MessageFmt<'a, T>
+where
+    T: AsRef<[u8]>,
+{
+    message: &'a Message<'a, T>,
+    dict: &'a Dictionary,
+}
+
+impl<'a, T> MessageFmt<'a, T>
+where
+    T: AsRef<[u8]>,
+{
+    /// Creates a new [`MessageFmt`] that renders `message` according to
+    /// `dict`.
+    pub fn new(message: &'a Message<'a, T>, dict: &'a Dictionary) -> Self {
+        Self { message, dict }
+    }
+}
+
+impl<'a, T> fmt::Display for MessageFmt<'a, T>
+where
+    T: AsRef<[u8]>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, (tag, value)) in self.message.fields().enumerate() {
+            if i > 0 {
+                f.write_str(" | ")?;
+            }
+            let value = String::from_utf8_lossy(value);
+            match self.dict.field_by_tag(tag.get() as u32) {
+                Some(field) => {
+                    let description = field.enums().and_then(|mut enums| {
+                        enums
+                            .find(|e| e.value() == value)
+                            .map(|e| e.description().to_string())
+                    });
+                    write!(
+                        f,
+                        "{}({})={}",
+                        field.name(),
+                        tag,
+                        description.as_deref().unwrap_or(&value)
+                    )?;
+                }
+                None => write!(f, "{}={}", tag, value)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::tagvalue::{Config, Decoder};
+    use crate::Dictionary;
+
+    #[test]
+    fn unknown_fields_and_values_are_rendered_verbatim() {
+        let dict = Dictionary::fix44();
+        let mut decoder = Decoder::<Config>::new(dict.clone());
+        decoder.config_mut().set_separator(b'|');
+        let data = b"8=FIX.4.4|9=19|35=0|9999=foo|54=?|10=131|";
+        let message = decoder.decode(&data[..]).unwrap();
+        assert_eq!(
+            message.display_with(&dict).to_string(),
+            "BeginString(8)=FIX.4.4 | MsgType(35)=HEARTBEAT | 9999=foo | Side(54)=?"
+        );
+    }
+}
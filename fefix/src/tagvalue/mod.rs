@@ -31,63 +31,361 @@
 //! - You want to delegate FIX message framing logic.
 //! - You need pre-build field parsing logic and basic features.
 //!
+//! ## Use a [`TokioCodec`] when:
+//!
+//! - You operate on an asynchronous stream of bytes with
+//!   [`tokio-util`](https://docs.rs/tokio-util), e.g. with `Framed`.
+//! - You want to delegate FIX message framing logic.
+//!
+//! ## Use a [`TokioMessageCodec`] when:
+//!
+//! - You operate on an asynchronous stream of bytes with `tokio-util`.
+//! - You want to delegate FIX message framing logic.
+//! - You need field access to messages that outlive the next one, e.g. when
+//!   collected from a `Stream`.
+//!
 //! ## Summary
 //!
-//! |**Decoder type**      |Operates on              |Produces    |
-//! |----------------------|-------------------------|------------|
-//! |[`RawDecoder`]        |`&[u8]`                  |[`RawFrame`]|
-//! |[`Decoder`]           |`&[u8]`                  |[`Message`] |
-//! |[`RawDecoderBuffered`]|`Vec<u8>` internal buffer|[`RawFrame`]|
-//! |[`DecoderBuffered`]   |`Vec<u8>` internal buffer|[`Message`] |
+//! |**Decoder type**      |Operates on              |Produces        |
+//! |----------------------|-------------------------|----------------|
+//! |[`RawDecoder`]        |`&[u8]`                  |[`RawFrame`]    |
+//! |[`Decoder`]           |`&[u8]`                  |[`Message`]     |
+//! |[`RawDecoderBuffered`]|`Vec<u8>` internal buffer|[`RawFrame`]    |
+//! |[`DecoderBuffered`]   |`Vec<u8>` internal buffer|[`Message`]     |
+//! |[`TokioCodec`]        |`BytesMut`               |[`RawFrame`]    |
+//! |[`TokioMessageCodec`] |`BytesMut`               |[`MessageOwned`]|
+//!
+//! # Validation
+//!
+//! Decoders only check FIX message syntax. Use a [`Validator`] to check decoded
+//! [`Message`]s against a [`Dictionary`](crate::Dictionary), e.g. for missing
+//! required fields and invalid enumeration values.
 
 use crate::dict::IsFieldDefinition;
-use crate::FixValue;
-use std::fmt;
-use std::fmt::Debug;
+use crate::{FixValue, TagU16};
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+use core::fmt::Debug;
+#[cfg(feature = "std")]
 use std::io;
 
+mod cached_field;
 mod config;
 mod decoder;
 mod encoder;
 mod field_access;
+mod fix_message;
+mod header_spec;
+mod masker;
+mod message_fmt;
+mod patcher;
 mod raw_decoder;
+mod router;
+mod stats;
 #[cfg(feature = "utils-tokio")]
-mod tokio_decoder;
+mod tokio_codec;
 mod utils;
+mod validator;
 
-pub use config::{Config, Configure};
-pub use decoder::{Decoder, DecoderBuffered, Fields, Message, MessageGroup, MessageGroupEntry};
-pub use encoder::{Encoder, EncoderHandle};
-pub use field_access::{FieldAccess, RepeatingGroup};
-pub use raw_decoder::{RawDecoder, RawDecoderBuffered, RawFrame};
+pub use cached_field::{CachedField, CachedSendingTime};
+pub use config::{Config, Configure, DuplicateTagPolicy, Verification};
+pub use decoder::{
+    DecodeStream, Decoder, DecoderBuffered, FieldLocator, Fields, FieldsWithLocators, Message,
+    MessageGroup, MessageGroupEntry, MessageOwned,
+};
+pub use encoder::{Encoder, EncoderHandle, HeaderTemplate, VectoredEncoderHandle, VectoredMessage};
+pub use fefix_derive::FixMessage;
+pub use field_access::{FieldAccess, FieldError, RepeatingGroup};
+#[doc(hidden)]
+pub use fix_message::__private;
+pub use fix_message::{FixMessage, FixMessageError};
+pub use header_spec::HeaderSpec;
+pub use masker::Masker;
+pub use message_fmt::MessageFmt;
+pub use patcher::MessagePatcher;
+pub use raw_decoder::{PeekedHeader, RawDecoder, RawDecoderBuffered, RawFrame};
+pub use router::MessageRouter;
+pub use stats::DecoderStats;
 #[cfg(feature = "utils-tokio")]
-pub use tokio_decoder::TokioDecoder;
+pub use tokio_codec::{TokioCodec, TokioMessageCodec};
+pub use validator::{Validator, Violation};
+
+#[cfg(feature = "json-encoding")]
+pub(crate) use decoder::group_members;
+#[cfg(test)]
+pub(crate) use decoder::test_decoder;
+#[cfg(feature = "json-encoding")]
+pub(crate) use validator::add_tags;
 
 /// The type returned in the event of an error during message decoding.
-#[derive(Clone, Debug, PartialEq)]
+///
+/// All variants carry enough context to pinpoint the problem within the
+/// offending message: byte offsets are always relative to the start of the
+/// message, i.e. the `8` of `BeginString <8>`.
+///
+/// Please note that [`DecodeError`] is marked with `#[non_exhaustive]`, which
+/// future-proofs the enumeration type in case more variants are added.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum DecodeError {
-    FieldPresence,
-    /// Invalid FIX message syntax.
-    Invalid,
-    Length,
-    CheckSum,
+    /// A mandatory field is missing.
+    FieldPresence {
+        /// The tag of the missing field.
+        tag: TagU16,
+    },
+    /// Invalid FIX message syntax, i.e. a malformed field.
+    Invalid {
+        /// The byte offset of the malformed field.
+        offset: usize,
+        /// The tag of the malformed field, if known.
+        tag: Option<TagU16>,
+    },
+    /// The message is shorter than the shortest possible FIX message.
+    TooShort {
+        /// The length of the message, in bytes.
+        len: usize,
+    },
+    /// The message is longer than
+    /// [`Configure::max_message_size`](crate::tagvalue::Configure::max_message_size).
+    TooLong {
+        /// The length of the message, in bytes, as announced by `BodyLength <9>`.
+        len: usize,
+        /// The maximum allowed length, in bytes.
+        max: usize,
+    },
+    /// A tag appears more than once outside of repeating groups, and
+    /// [`Configure::duplicate_tag_policy`] is [`DuplicateTagPolicy::Reject`].
+    DuplicateTag {
+        /// The byte offset of the second occurrence of the tag.
+        offset: usize,
+        /// The duplicate tag.
+        tag: TagU16,
+    },
+    /// The value of `BeginString <8>` is not
+    /// [`Configure::expected_begin_string`](crate::tagvalue::Configure::expected_begin_string),
+    /// e.g. because the counterparty uses another version of the protocol.
+    BeginString {
+        /// The expected value of `BeginString <8>`.
+        expected: Vec<u8>,
+        /// The actual value of `BeginString <8>`.
+        found: Vec<u8>,
+    },
+    /// The message has more fields than
+    /// [`Configure::max_fields`](crate::tagvalue::Configure::max_fields).
+    TooManyFields {
+        /// The maximum allowed number of fields.
+        max: usize,
+    },
+    /// The value of `BodyLength <9>` doesn't match the actual length of the
+    /// message body.
+    Length {
+        /// The byte offset of the `BodyLength <9>` field.
+        offset: usize,
+        /// The value of `BodyLength <9>`.
+        declared: usize,
+        /// The actual length of the message body, in bytes.
+        actual: usize,
+    },
+    /// The value of `CheckSum <10>` doesn't match the actual checksum of the
+    /// message.
+    CheckSum {
+        /// The byte offset of the `CheckSum <10>` field.
+        offset: usize,
+        /// The value of `CheckSum <10>`.
+        declared: u8,
+        /// The actual checksum of the message.
+        actual: u8,
+    },
+    /// I/O-related error while reading the message.
+    #[cfg(feature = "std")]
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "std")))]
+    Io(io::ErrorKind),
+}
+
+impl DecodeError {
+    /// Returns the byte offset within the message where the error was
+    /// detected, if any.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix::tagvalue::{Config, Configure, DecodeError, Decoder};
+    /// use fefix::Dictionary;
+    ///
+    /// let mut decoder = Decoder::<Config>::new(Dictionary::fix44());
+    /// decoder.config_mut().set_separator(b'|');
+    /// let err = decoder.decode(b"8=FIX.4.4|9=99|35=0|49=A|56=B|10=000|").unwrap_err();
+    /// assert_eq!(err.offset(), Some(10));
+    /// assert!(matches!(err, DecodeError::Length { declared: 99, actual: 15, .. }));
+    /// ```
+    pub fn offset(&self) -> Option<usize> {
+        match self {
+            Self::Invalid { offset, .. }
+            | Self::DuplicateTag { offset, .. }
+            | Self::Length { offset, .. }
+            | Self::CheckSum { offset, .. } => Some(*offset),
+            Self::BeginString { .. } => Some(0),
+            _ => None,
+        }
+    }
+
+    /// Returns the tag of the field that caused the error, if known.
+    pub fn tag(&self) -> Option<TagU16> {
+        match self {
+            Self::FieldPresence { tag } => Some(*tag),
+            Self::Invalid { tag, .. } => *tag,
+            Self::DuplicateTag { tag, .. } => Some(*tag),
+            Self::BeginString { .. } => TagU16::new(8),
+            Self::Length { .. } => TagU16::new(9),
+            Self::CheckSum { .. } => TagU16::new(10),
+            _ => None,
+        }
+    }
 }
 
 impl fmt::Display for DecodeError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "SuperError is here!")
+        match self {
+            Self::FieldPresence { tag } => write!(f, "Mandatory tag <{}> is missing", tag),
+            Self::Invalid {
+                offset,
+                tag: Some(tag),
+            } => write!(f, "Malformed field with tag <{}> at byte {}", tag, offset),
+            Self::Invalid { offset, tag: None } => write!(f, "Malformed field at byte {}", offset),
+            Self::TooShort { len } => write!(f, "Message is too short ({} bytes)", len),
+            Self::TooLong { len, max } => write!(
+                f,
+                "Message is too long ({} bytes, at most {} are allowed)",
+                len, max
+            ),
+            Self::DuplicateTag { offset, tag } => {
+                write!(f, "Duplicate tag <{}> at byte {}", tag, offset)
+            }
+            Self::BeginString { expected, found } => write!(
+                f,
+                "BeginString <8> is {}, but {} was expected",
+                String::from_utf8_lossy(found),
+                String::from_utf8_lossy(expected)
+            ),
+            Self::TooManyFields { max } => {
+                write!(
+                    f,
+                    "Message has too many fields (at most {} are allowed)",
+                    max
+                )
+            }
+            Self::Length {
+                offset,
+                declared,
+                actual,
+            } => write!(
+                f,
+                "BodyLength <9> at byte {} is {}, but the body is {} bytes long",
+                offset, declared, actual
+            ),
+            Self::CheckSum {
+                offset,
+                declared,
+                actual,
+            } => write!(
+                f,
+                "CheckSum <10> at byte {} is {:03}, but the computed checksum is {:03}",
+                offset, declared, actual
+            ),
+            #[cfg(feature = "std")]
+            Self::Io(kind) => write!(f, "I/O error: {:?}", kind),
+        }
     }
 }
 
+#[cfg(feature = "std")]
 impl std::error::Error for DecodeError {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         None
     }
 }
 
+#[cfg(feature = "std")]
 impl From<io::Error> for DecodeError {
-    fn from(_err: io::Error) -> Self {
-        Self::Invalid // FIXME
+    fn from(err: io::Error) -> Self {
+        Self::Io(err.kind())
+    }
+}
+
+/// The type returned in the event of an error when completing a message with
+/// [`EncoderHandle::finalize`] or [`VectoredEncoderHandle::finalize`].
+///
+/// Please note that [`EncodeError`] is marked with `#[non_exhaustive]`, which
+/// future-proofs the enumeration type in case more variants are added.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum EncodeError {
+    /// The message body doesn't fit in the digits reserved for `BodyLength
+    /// <9>`.
+    BodyTooLong {
+        /// The length of the message body, in bytes.
+        len: usize,
+        /// The maximum length of the message body, in bytes.
+        max: usize,
+    },
+    /// A repeating group was started with [`EncoderHandle::begin_group`], but
+    /// never ended.
+    UnterminatedGroup {
+        /// The `NumInGroup` tag of the innermost unterminated group.
+        tag: TagU16,
+    },
+    /// A field was added to a repeating group before its first entry was
+    /// started by the delimiter field.
+    GroupEntryWithoutDelimiter {
+        /// The tag of the misplaced field.
+        tag: TagU16,
+        /// The tag of the delimiter field of the group.
+        delimiter: TagU16,
+    },
+    /// [`EncoderHandle::end_group`] was called outside of any repeating
+    /// group.
+    NoGroupToEnd,
+    /// The message didn't fit into a fixed-capacity [`Buffer`](crate::Buffer),
+    /// e.g. a [`SliceBuffer`](crate::SliceBuffer), and was truncated.
+    CapacityExceeded {
+        /// The capacity of the buffer, in bytes.
+        capacity: usize,
+    },
+}
+
+impl fmt::Display for EncodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BodyTooLong { len, max } => write!(
+                f,
+                "Message body is too long ({} bytes, at most {} are allowed)",
+                len, max
+            ),
+            Self::UnterminatedGroup { tag } => {
+                write!(f, "Repeating group <{}> was never ended", tag)
+            }
+            Self::GroupEntryWithoutDelimiter { tag, delimiter } => write!(
+                f,
+                "Field <{}> precedes the delimiter field <{}> of its repeating group",
+                tag, delimiter
+            ),
+            Self::NoGroupToEnd => write!(f, "No repeating group to end"),
+            Self::CapacityExceeded { capacity } => {
+                write!(
+                    f,
+                    "Message doesn't fit into the buffer ({} bytes)",
+                    capacity
+                )
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for EncodeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        None
     }
 }
 
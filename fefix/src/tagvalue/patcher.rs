@@ -0,0 +1,349 @@
+use super::{FieldLocator, Message};
+use crate::buffer::Buffer;
+use crate::definitions::fix44;
+use crate::dict::IsFieldDefinition;
+use crate::fix_values::CheckSum;
+use crate::FixValue;
+use crate::TagU16;
+use alloc::vec::Vec;
+use core::ops::Range;
+
+/// Edits a decoded [`Message`] and re-encodes it, as needed by FIX routers and
+/// drop-copy bridges, e.g. to rewrite `SenderCompID <49>` or to add
+/// `DeliverToCompID <128>`.
+///
+/// Only top-level fields (i.e. outside of repeating groups) can be edited. All
+/// untouched fields are copied verbatim from the original message, while
+/// `BodyLength <9>` and `CheckSum <10>` are always recomputed.
+///
+/// # Examples
+///
+/// ```
+/// use fefix::definitions::fix44;
+/// use fefix::tagvalue::{Config, Configure, Decoder, MessagePatcher};
+/// use fefix::Dictionary;
+///
+/// let mut decoder = Decoder::<Config>::new(Dictionary::fix44());
+/// decoder.config_mut().set_separator(b'|');
+/// let data = b"8=FIX.4.4|9=30|35=0|49=CLIENT|56=ROUTER|34=5|10=129|";
+/// let message = decoder.decode(&data[..]).unwrap();
+/// let mut patcher = MessagePatcher::new(&message);
+/// patcher.set(fix44::SENDER_COMP_ID, "ROUTER");
+/// patcher.set(fix44::TARGET_COMP_ID, "EXCHANGE");
+/// patcher.insert_after(fix44::TARGET_COMP_ID, fix44::ON_BEHALF_OF_COMP_ID, "CLIENT");
+/// let mut buffer = Vec::new();
+/// let patched = patcher.encode(&mut buffer);
+/// assert_eq!(
+///     patched,
+///     b"8=FIX.4.4|9=43|35=0|49=ROUTER|56=EXCHANGE|115=CLIENT|34=5|10=024|"
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct MessagePatcher<'a> {
+    raw: &'a [u8],
+    separator: u8,
+    begin_string: Option<Vec<u8>>,
+    begin_string_range: Range<usize>,
+    segments: Vec<Segment>,
+}
+
+#[derive(Debug, Clone)]
+enum Segment {
+    // A field of the original message, with its tag, separator included.
+    Original {
+        locator: FieldLocator,
+        range: Range<usize>,
+    },
+    // A new or replaced field, separator included.
+    New {
+        locator: FieldLocator,
+        field: Vec<u8>,
+    },
+}
+
+impl Segment {
+    fn locator(&self) -> FieldLocator {
+        match self {
+            Segment::Original { locator, .. } | Segment::New { locator, .. } => *locator,
+        }
+    }
+}
+
+impl<'a> MessagePatcher<'a> {
+    /// Creates a new [`MessagePatcher`] that starts from the fields of
+    /// `message`, without any edits.
+    pub fn new<T>(message: &'a Message<'a, T>) -> Self
+    where
+        T: AsRef<[u8]>,
+    {
+        let raw = message.as_bytes();
+        let mut begin_string_range = 0..0;
+        let mut separator = 0x1;
+        // Repeated tags must be copied as well, so every occurrence is needed
+        // and not just those that `Message::fields_with_locators` yields.
+        let mut fields: Vec<_> = message.occurrences().collect();
+        // Decoded values always borrow from the original message, so their
+        // addresses follow wire order.
+        fields.sort_unstable_by_key(|(_, value)| value.as_ptr() as usize);
+        let mut segments = Vec::with_capacity(fields.len());
+        for (locator, value) in fields {
+            let start = value.as_ptr() as usize - raw.as_ptr() as usize;
+            let end = start + value.len();
+            debug_assert!(end < raw.len());
+            if locator.tag() == fix44::BEGIN_STRING.tag() {
+                begin_string_range = start..end;
+                separator = raw[end];
+                continue;
+            }
+            // Tags are made of digits only, so the field starts right after
+            // the previous separator.
+            let field_start = raw[..start - 1]
+                .iter()
+                .rposition(|byte| *byte == separator)
+                .map_or(0, |i| i + 1);
+            segments.push(Segment::Original {
+                locator,
+                range: field_start..end + 1,
+            });
+        }
+        Self {
+            raw,
+            separator,
+            begin_string: None,
+            begin_string_range,
+            segments,
+        }
+    }
+
+    /// Sets the value of the top-level `field`, replacing its first occurrence.
+    /// If `field` is not present, it's added at the end of the message.
+    ///
+    /// `BeginString <8>` can be set as well, while `BodyLength <9>` and
+    /// `CheckSum <10>` are ignored because they are always recomputed.
+    pub fn set<'b, F, T>(&mut self, field: &F, value: T)
+    where
+        F: IsFieldDefinition,
+        T: FixValue<'b>,
+    {
+        self.set_any(field.tag(), value)
+    }
+
+    /// Like [`MessagePatcher::set`], but with an arbitrary `tag`.
+    pub fn set_any<'b, T>(&mut self, tag: TagU16, value: T)
+    where
+        T: FixValue<'b>,
+    {
+        if tag == fix44::BEGIN_STRING.tag() {
+            self.begin_string = Some(value.to_bytes());
+        } else if tag != fix44::BODY_LENGTH.tag() && tag != fix44::CHECK_SUM.tag() {
+            let segment = self.new_segment(FieldLocator::TopLevel { tag }, value);
+            match self.position(tag) {
+                Some(i) => self.segments[i] = segment,
+                None => self.segments.push(segment),
+            }
+        }
+    }
+
+    /// Inserts `field` right after the first occurrence of the top-level
+    /// `anchor` field, e.g. to add header fields. If `anchor` is not present,
+    /// `field` is added at the end of the message. Existing occurrences of
+    /// `field` are left untouched.
+    pub fn insert_after<'b, F1, F2, T>(&mut self, anchor: &F1, field: &F2, value: T)
+    where
+        F1: IsFieldDefinition,
+        F2: IsFieldDefinition,
+        T: FixValue<'b>,
+    {
+        let tag = field.tag();
+        let segment = self.new_segment(FieldLocator::TopLevel { tag }, value);
+        match self.position(anchor.tag()) {
+            Some(i) => self.segments.insert(i + 1, segment),
+            None => self.segments.push(segment),
+        }
+    }
+
+    /// Removes the first occurrence of the top-level `field`. Returns `true` if
+    /// `field` was present; `false` otherwise.
+    pub fn remove<F>(&mut self, field: &F) -> bool
+    where
+        F: IsFieldDefinition,
+    {
+        match self.position(field.tag()) {
+            Some(i) => {
+                self.segments.remove(i);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Encodes the patched message into `buffer`, after any existing content,
+    /// and returns it.
+    pub fn encode<'b, B>(&self, buffer: &'b mut B) -> &'b [u8]
+    where
+        B: Buffer,
+    {
+        let start = buffer.len();
+        let begin_string = match &self.begin_string {
+            Some(begin_string) => &begin_string[..],
+            None => &self.raw[self.begin_string_range.clone()],
+        };
+        buffer.extend_from_slice(b"8=");
+        buffer.extend_from_slice(begin_string);
+        buffer.extend_from_slice(&[self.separator]);
+        buffer.extend_from_slice(b"9=");
+        self.body_length().serialize(buffer);
+        buffer.extend_from_slice(&[self.separator]);
+        // Adjacent original fields are copied all at once.
+        let mut pending: Option<Range<usize>> = None;
+        for segment in self.segments.iter() {
+            match segment {
+                Segment::Original { range, .. } => match &mut pending {
+                    Some(pending) if pending.end == range.start => pending.end = range.end,
+                    _ => {
+                        if let Some(pending) = pending.replace(range.clone()) {
+                            buffer.extend_from_slice(&self.raw[pending]);
+                        }
+                    }
+                },
+                Segment::New { field, .. } => {
+                    if let Some(pending) = pending.take() {
+                        buffer.extend_from_slice(&self.raw[pending]);
+                    }
+                    buffer.extend_from_slice(&field[..]);
+                }
+            }
+        }
+        if let Some(pending) = pending {
+            buffer.extend_from_slice(&self.raw[pending]);
+        }
+        let checksum = CheckSum::compute(&buffer.as_slice()[start..]);
+        buffer.extend_from_slice(b"10=");
+        checksum.serialize(buffer);
+        buffer.extend_from_slice(&[self.separator]);
+        &buffer.as_slice()[start..]
+    }
+
+    fn body_length(&self) -> usize {
+        self.segments
+            .iter()
+            .map(|segment| match segment {
+                Segment::Original { range, .. } => range.len(),
+                Segment::New { field, .. } => field.len(),
+            })
+            .sum()
+    }
+
+    /// Replaces the value of every occurrence of `tag`, including those within
+    /// repeating groups.
+    pub(crate) fn replace_all(&mut self, tag: TagU16, value: &[u8]) {
+        for i in 0..self.segments.len() {
+            let locator = self.segments[i].locator();
+            if locator.tag() == tag {
+                self.segments[i] = self.new_segment(locator, value);
+            }
+        }
+    }
+
+    fn position(&self, tag: TagU16) -> Option<usize> {
+        self.segments
+            .iter()
+            .position(|segment| segment.locator() == FieldLocator::TopLevel { tag })
+    }
+
+    fn new_segment<'b, T>(&self, locator: FieldLocator, value: T) -> Segment
+    where
+        T: FixValue<'b>,
+    {
+        let mut field = Vec::new();
+        locator.tag().serialize(&mut field);
+        field.push(b'=');
+        value.serialize(&mut field);
+        field.push(self.separator);
+        Segment::New { locator, field }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tagvalue::{test_decoder, Config, Decoder, DuplicateTagPolicy};
+
+    fn decoder() -> Decoder<Config> {
+        let mut decoder = test_decoder();
+        decoder.config_mut().set_verify_checksum(true);
+        decoder
+    }
+
+    fn patch<F>(data: &[u8], f: F) -> Vec<u8>
+    where
+        F: FnOnce(&mut MessagePatcher),
+    {
+        let mut decoder = decoder();
+        let message = decoder.decode(data).unwrap();
+        let mut patcher = MessagePatcher::new(&message);
+        f(&mut patcher);
+        let mut buffer = Vec::new();
+        patcher.encode(&mut buffer);
+        buffer
+    }
+
+    #[test]
+    fn unpatched_message_is_copied_verbatim() {
+        let data = b"8=FIX.4.2|9=40|35=D|49=AFUNDMGR|56=ABROKER|15=USD|59=0|10=091|";
+        assert_eq!(&patch(data, |_| {})[..], &data[..]);
+    }
+
+    #[test]
+    fn duplicate_tags_are_copied_verbatim() {
+        let data = b"8=FIX.4.4|9=36|35=0|49=A|112=first|56=B|112=second|10=083|";
+        let mut decoder = decoder();
+        for policy in [DuplicateTagPolicy::KeepFirst, DuplicateTagPolicy::KeepLast].iter() {
+            decoder.config_mut().set_duplicate_tag_policy(*policy);
+            let message = decoder.decode(&data[..]).unwrap();
+            let mut buffer = Vec::new();
+            MessagePatcher::new(&message).encode(&mut buffer);
+            assert_eq!(&buffer[..], &data[..]);
+        }
+    }
+
+    #[test]
+    fn patched_message_is_valid() {
+        let data = b"8=FIX.4.2|9=40|35=D|49=AFUNDMGR|56=ABROKER|15=USD|59=0|10=091|";
+        let patched = patch(data, |patcher| {
+            patcher.set(fix44::BEGIN_STRING, "FIX.4.4");
+            patcher.set(fix44::CURRENCY, "EUR");
+            assert!(patcher.remove(fix44::TIME_IN_FORCE));
+            assert!(!patcher.remove(fix44::TIME_IN_FORCE));
+            patcher.set(fix44::TEST_REQ_ID, "X");
+        });
+        assert_eq!(
+            &patched[..],
+            b"8=FIX.4.4|9=41|35=D|49=AFUNDMGR|56=ABROKER|15=EUR|112=X|10=172|" as &[u8]
+        );
+        let mut decoder = decoder();
+        assert!(decoder.decode(&patched[..]).is_ok());
+    }
+
+    #[test]
+    fn data_fields_are_copied_verbatim() {
+        let data = b"8=FIX.4.4|9=24|35=B|95=3|96=a|b|148=Hi|10=031|";
+        let patched = patch(data, |patcher| {
+            patcher.set(fix44::HEADLINE, "Hello");
+        });
+        assert_eq!(
+            &patched[..],
+            b"8=FIX.4.4|9=27|35=B|95=3|96=a|b|148=Hello|10=101|" as &[u8]
+        );
+    }
+
+    #[test]
+    fn group_fields_are_not_patched() {
+        let data = b"8=FIX.4.4|9=39|35=V|262=A|146=2|55=EUR/USD|55=GBP/USD|10=204|";
+        let patched = patch(data, |patcher| {
+            assert!(!patcher.remove(fix44::SYMBOL));
+            patcher.set(fix44::SYMBOL, "X");
+        });
+        assert!(patched.starts_with(b"8=FIX.4.4|9=44|35=V|262=A|146=2|55=EUR/USD|55=GBP/USD|55=X|"));
+    }
+}
@@ -1,5 +1,11 @@
-use crate::tagvalue::{utils, Config, Configure, DecodeError};
-use std::ops::Range;
+use crate::scan;
+use crate::tagvalue::{utils, Config, Configure, DecodeError, Verification};
+use crate::TagU16;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use core::ops::Range;
+#[cfg(feature = "std")]
+use std::io;
 
 /// An immutable view over the contents of a FIX message by a [`RawDecoder`].
 #[derive(Debug)]
@@ -11,6 +17,7 @@ where
     begin_string: Range<usize>,
     payload: Range<usize>,
     payload_offset: usize,
+    warnings: Vec<DecodeError>,
 }
 
 impl<T> RawFrame<T>
@@ -27,6 +34,7 @@ where
             begin_string: self.begin_string,
             payload: self.payload,
             payload_offset: self.payload_offset,
+            warnings: self.warnings,
         }
     }
 
@@ -96,6 +104,85 @@ where
     pub fn payload_offset(&self) -> usize {
         self.payload_offset
     }
+
+    /// Returns all failed integrity checks that were tolerated while decoding
+    /// `self`, because they were set to [`Verification::WarnOnly`]. It is
+    /// empty for all well-formed FIX messages.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix::tagvalue::{Config, DecodeError, RawDecoder, Verification};
+    ///
+    /// let mut decoder = RawDecoder::<Config>::new();
+    /// decoder.config_mut().set_separator(b'|');
+    /// decoder
+    ///     .config_mut()
+    ///     .set_checksum_verification(Verification::WarnOnly);
+    /// let data = b"8=FIX.4.2|9=5|35=0|10=000|";
+    /// let message = decoder.decode(data).unwrap();
+    ///
+    /// assert!(matches!(
+    ///     message.warnings(),
+    ///     [DecodeError::CheckSum { declared: 0, .. }]
+    /// ));
+    /// ```
+    pub fn warnings(&self) -> &[DecodeError] {
+        &self.warnings[..]
+    }
+}
+
+/// Tags of the standard header, which may come in any order right after
+/// `BodyLength <9>`.
+const STANDARD_HEADER_TAGS: &[u16] = &[
+    35, 49, 56, 115, 128, 90, 91, 34, 50, 142, 57, 143, 116, 144, 129, 145, 43, 97, 52, 122, 212,
+    213, 347, 369, 627, 628, 629, 630, 1128, 1129, 1156,
+];
+
+/// Some standard header fields of a [`RawFrame`], as returned by
+/// [`RawDecoder::peek_header`]. Values are borrowed as-is from the frame.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct PeekedHeader<'a> {
+    begin_string: &'a [u8],
+    msg_type: Option<&'a [u8]>,
+    sender_comp_id: Option<&'a [u8]>,
+    target_comp_id: Option<&'a [u8]>,
+    msg_seq_num: Option<&'a [u8]>,
+}
+
+impl<'a> PeekedHeader<'a> {
+    /// Returns the value of `BeginString <8>`.
+    pub fn begin_string(&self) -> &'a [u8] {
+        self.begin_string
+    }
+
+    /// Returns the value of `MsgType <35>`, if any.
+    pub fn msg_type(&self) -> Option<&'a [u8]> {
+        self.msg_type
+    }
+
+    /// Returns the value of `SenderCompID <49>`, if any.
+    pub fn sender_comp_id(&self) -> Option<&'a [u8]> {
+        self.sender_comp_id
+    }
+
+    /// Returns the value of `TargetCompID <56>`, if any.
+    pub fn target_comp_id(&self) -> Option<&'a [u8]> {
+        self.target_comp_id
+    }
+
+    /// Returns the value of `MsgSeqNum <34>`, if any. It's not guaranteed to
+    /// be a valid number.
+    pub fn msg_seq_num(&self) -> Option<&'a [u8]> {
+        self.msg_seq_num
+    }
+
+    fn is_complete(&self) -> bool {
+        self.msg_type.is_some()
+            && self.sender_comp_id.is_some()
+            && self.target_comp_id.is_some()
+            && self.msg_seq_num.is_some()
+    }
 }
 
 /// A bare-bones FIX decoder for low-level message handling.
@@ -148,6 +235,40 @@ where
         &mut self.config
     }
 
+    /// Returns the total length in bytes of the FIX message at the start of
+    /// `data`, as announced by its `BodyLength <9>` field. Returns [`None`] if
+    /// `data` is too short to tell.
+    pub(crate) fn message_len(&self, data: &[u8]) -> Result<Option<usize>, DecodeError> {
+        let separator = self.config().separator();
+        let has_two_separators = scan::find_byte(data, separator)
+            .is_some_and(|i| scan::find_byte(&data[i + 1..], separator).is_some());
+        if data.len() < utils::MIN_FIX_MESSAGE_LEN_IN_BYTES || !has_two_separators {
+            return Ok(None);
+        }
+        let info = HeaderInfo::parse(data, separator)?;
+        let len =
+            info.start_of_body() + info.body_range().len() + utils::FIELD_CHECKSUM_LEN_IN_BYTES;
+        self.check_message_size(len)?;
+        Ok(Some(len))
+    }
+
+    fn check_message_size(&self, len: usize) -> Result<(), DecodeError> {
+        match self.config().max_message_size() {
+            Some(max) if len > max => Err(DecodeError::TooLong { len, max }),
+            _ => Ok(()),
+        }
+    }
+
+    fn check_begin_string(&self, begin_string: &[u8]) -> Result<(), DecodeError> {
+        match self.config().expected_begin_string() {
+            Some(expected) if begin_string != expected => Err(DecodeError::BeginString {
+                expected: expected.to_vec(),
+                found: begin_string.to_vec(),
+            }),
+            _ => Ok(()),
+        }
+    }
+
     /// Does minimal parsing on `data` and returns a [`RawFrame`] if it's valid.
     pub fn decode<T>(&self, src: T) -> Result<RawFrame<T>, DecodeError>
     where
@@ -155,20 +276,201 @@ where
     {
         let data = src.as_ref();
         if data.len() < utils::MIN_FIX_MESSAGE_LEN_IN_BYTES {
-            return Err(DecodeError::Length);
+            return Err(DecodeError::TooShort { len: data.len() });
         }
+        self.check_message_size(data.len())?;
         let info = HeaderInfo::parse(data, self.config().separator())?;
-        utils::verify_body_length(data, info.start_of_body(), info.body_range().len())?;
-        if self.config().verify_checksum() {
-            utils::verify_checksum(data)?;
+        self.check_begin_string(&data[info.begin_string_range()])?;
+        let mut warnings = Vec::new();
+        let mut payload = info.body_range();
+        let end_of_body = data.len() - utils::FIELD_CHECKSUM_LEN_IN_BYTES;
+        let body_length_verification = match self.config().body_length_verification() {
+            // There's no body to speak of, so the message can't be decoded at
+            // all.
+            _ if info.start_of_body() > end_of_body => Verification::Enforce,
+            verification => verification,
+        };
+        if body_length_verification != Verification::Skip {
+            let result = utils::verify_body_length(
+                data,
+                info.body_length_offset(),
+                info.start_of_body(),
+                info.body_range().len(),
+            );
+            tolerate(result, body_length_verification, &mut warnings)?;
+        }
+        if body_length_verification != Verification::Enforce {
+            // `BodyLength <9>` may be wrong, so we trust `CheckSum <10>` to be
+            // the last field instead.
+            payload = info.start_of_body()..end_of_body;
+        }
+        let checksum_verification = self.config().checksum_verification();
+        if checksum_verification != Verification::Skip {
+            let result = utils::verify_checksum(data);
+            tolerate(result, checksum_verification, &mut warnings)?;
         }
         Ok(RawFrame {
             data: src,
             begin_string: info.begin_string_range(),
-            payload: info.body_range(),
-            payload_offset: info.body_range().start,
+            payload_offset: payload.start,
+            payload,
+            warnings,
         })
     }
+
+    /// Scans the standard header of `frame` for `MsgType <35>`,
+    /// `SenderCompID <49>`, `TargetCompID <56>`, and `MsgSeqNum <34>`, e.g. to
+    /// route it, without decoding the whole message.
+    ///
+    /// Scanning stops as soon as all of them are found, or at the first field
+    /// which doesn't belong to the standard header. Missing (or malformed)
+    /// fields are thus [`None`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix::tagvalue::{Config, Configure, RawDecoder};
+    ///
+    /// let mut decoder = RawDecoder::<Config>::new();
+    /// decoder.config_mut().set_separator(b'|');
+    /// let frame = decoder
+    ///     .decode(&b"8=FIX.4.2|9=40|35=D|49=AFUNDMGR|56=ABROKER|15=USD|59=0|10=091|"[..])
+    ///     .unwrap();
+    /// let header = decoder.peek_header(&frame);
+    /// assert_eq!(header.msg_type(), Some(&b"D"[..]));
+    /// assert_eq!(header.sender_comp_id(), Some(&b"AFUNDMGR"[..]));
+    /// assert_eq!(header.target_comp_id(), Some(&b"ABROKER"[..]));
+    /// assert_eq!(header.msg_seq_num(), None);
+    /// ```
+    pub fn peek_header<'a, T>(&self, frame: &'a RawFrame<T>) -> PeekedHeader<'a>
+    where
+        T: AsRef<[u8]>,
+    {
+        let separator = self.config().separator();
+        let payload = frame.payload();
+        let mut header = PeekedHeader {
+            begin_string: frame.begin_string(),
+            msg_type: None,
+            sender_comp_id: None,
+            target_comp_id: None,
+            msg_seq_num: None,
+        };
+        // The length of the next `SecureData <91>` or `XmlData <213>`.
+        let mut data_len: Option<usize> = None;
+        let mut i = 0;
+        while i < payload.len() && !header.is_complete() {
+            let equal_sign = match scan::find_byte(&payload[i..], b'=') {
+                Some(j) => i + j,
+                None => break,
+            };
+            let tag = match parse_tag(&payload[i..equal_sign]) {
+                Some(tag) => tag,
+                None => break,
+            };
+            let value_start = equal_sign + 1;
+            let value_end = match (tag, data_len.take()) {
+                (91, Some(len)) | (213, Some(len)) => value_start.saturating_add(len),
+                _ => match scan::find_byte(&payload[value_start..], separator) {
+                    Some(j) => value_start + j,
+                    None => break,
+                },
+            };
+            if payload.get(value_end) != Some(&separator) {
+                break;
+            }
+            let value = &payload[value_start..value_end];
+            match tag {
+                35 => header.msg_type = Some(value),
+                49 => header.sender_comp_id = Some(value),
+                56 => header.target_comp_id = Some(value),
+                34 => header.msg_seq_num = Some(value),
+                90 | 212 => {
+                    data_len = core::str::from_utf8(value)
+                        .ok()
+                        .and_then(|len| len.parse().ok())
+                }
+                tag if !STANDARD_HEADER_TAGS.contains(&tag) => break,
+                _ => {}
+            }
+            i = value_end + 1;
+        }
+        header
+    }
+
+    /// Returns `true` if and only if `data` is exactly one well-formed FIX
+    /// message, i.e. `BeginString <8>`, `BodyLength <9>`, a body of that
+    /// length, and a correct `CheckSum <10>` as the last field.
+    ///
+    /// This is a cheap alternative to [`RawDecoder::decode`] for taps and
+    /// sniffers that only need to count valid and invalid frames: it neither
+    /// builds a [`RawFrame`] nor allocates. Only the separator of `self` is
+    /// taken into account, while all verification settings are ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix::tagvalue::{Config, Configure, RawDecoder};
+    ///
+    /// let mut decoder = RawDecoder::<Config>::new();
+    /// decoder.config_mut().set_separator(b'|');
+    /// assert!(decoder.verify(b"8=FIX.4.2|9=40|35=D|49=AFUNDMGR|56=ABROKER|15=USD|59=0|10=091|"));
+    /// assert!(!decoder.verify(b"8=FIX.4.2|9=40|35=D|49=AFUNDMGR|56=ABROKER|15=USD|59=0|10=092|"));
+    /// ```
+    pub fn verify(&self, data: &[u8]) -> bool {
+        if data.len() < utils::MIN_FIX_MESSAGE_LEN_IN_BYTES {
+            return false;
+        }
+        let separator = self.config().separator();
+        let info = match HeaderInfo::parse(data, separator) {
+            Ok(info) => info,
+            Err(_) => return false,
+        };
+        let end_of_body = data.len() - utils::FIELD_CHECKSUM_LEN_IN_BYTES;
+        if info.start_of_body() > end_of_body || info.body_range().end != end_of_body {
+            return false;
+        }
+        let trailer = &data[end_of_body..];
+        if &trailer[..3] != b"10=" || trailer[6] != separator {
+            return false;
+        }
+        let mut declared = 0u32;
+        for digit in &trailer[3..6] {
+            if !digit.is_ascii_digit() {
+                return false;
+            }
+            declared = declared * 10 + u32::from(digit - b'0');
+        }
+        declared == u32::from(scan::checksum(&data[..end_of_body]))
+    }
+}
+
+fn parse_tag(digits: &[u8]) -> Option<u16> {
+    if digits.is_empty() || digits.len() > 5 {
+        return None;
+    }
+    let mut tag = 0u32;
+    for digit in digits {
+        if !digit.is_ascii_digit() {
+            return None;
+        }
+        tag = tag * 10 + u32::from(digit - b'0');
+    }
+    u16::try_from(tag).ok()
+}
+
+// Turns a failed check into a warning, if `verification` allows it.
+fn tolerate(
+    result: Result<(), DecodeError>,
+    verification: Verification,
+    warnings: &mut Vec<DecodeError>,
+) -> Result<(), DecodeError> {
+    match result {
+        Err(err) if verification == Verification::WarnOnly => {
+            warnings.push(err);
+            Ok(())
+        }
+        result => result,
+    }
 }
 
 /// A [`RawDecoder`] that can buffer incoming data and read a stream of messages.
@@ -198,19 +500,64 @@ where
         self.decoder.config_mut()
     }
 
+    /// Discards the current [`RawFrame`], if any, together with any decoding
+    /// error.
     pub fn clear(&mut self) {
-        self.buffer.clear();
+        let frame_len = match self.decoder.message_len(self.buffer.as_slice()) {
+            Ok(Some(len)) if len <= self.buffer.len() => len,
+            _ => self.buffer.len(),
+        };
+        self.buffer.drain(..frame_len);
         self.error = None;
     }
 
+    /// Recovers from a decoding error by scanning the internal buffer for the
+    /// next plausible start of message, i.e. `8=FIX`, and discarding everything
+    /// before it. Returns the number of garbled bytes that were skipped.
+    ///
+    /// The leading byte is always skipped, so that repeated calls make progress
+    /// even when the corrupt message itself starts with `8=FIX`. If no start
+    /// of message is found, the whole buffer is discarded except for a trailing
+    /// partial match, which may still turn into a valid message once more data
+    /// is supplied.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix::tagvalue::{Config, Configure, RawDecoder};
+    ///
+    /// let mut config = Config::default();
+    /// config.set_separator(b'|');
+    /// let mut decoder = RawDecoder::with_config(config).buffered();
+    /// let mut stream = &b"garbage!garbage!garbage!8=FIX.4.2|9=5|35=0|10=018|"[..];
+    /// let mut skipped = 0;
+    /// let frame = loop {
+    ///     let buffer = decoder.supply_buffer();
+    ///     let len = buffer.len();
+    ///     buffer.copy_from_slice(&stream[..len]);
+    ///     stream = &stream[len..];
+    ///     match decoder.current_frame() {
+    ///         Ok(Some(frame)) => break frame,
+    ///         Ok(None) => {}
+    ///         Err(_) => skipped += decoder.resync(),
+    ///     }
+    /// };
+    /// assert_eq!(skipped, 24);
+    /// assert_eq!(frame.payload(), b"35=0|");
+    /// ```
+    pub fn resync(&mut self) -> usize {
+        let skip = resync_len(self.buffer.as_slice());
+        self.buffer.drain(..skip);
+        self.error = None;
+        skip
+    }
+
     /// Provides a buffer that must be filled before re-attempting to deserialize
     /// the next [`RawFrame`].
     pub fn supply_buffer(&mut self) -> &mut [u8] {
-        if self.buffer.as_slice().len() < utils::MIN_FIX_MESSAGE_LEN_IN_BYTES {
-            let current_len = self.buffer.as_slice().len();
-            let missing_len = utils::MIN_FIX_MESSAGE_LEN_IN_BYTES - current_len;
-            debug_assert!(missing_len > 0);
-            self.buffer.resize(missing_len, 0);
+        let current_len = self.buffer.as_slice().len();
+        if current_len < utils::MIN_FIX_MESSAGE_LEN_IN_BYTES {
+            self.buffer.resize(utils::MIN_FIX_MESSAGE_LEN_IN_BYTES, 0);
             &mut self.buffer.as_mut_slice()[current_len..]
         } else {
             match HeaderInfo::parse(self.buffer.as_slice(), self.config().separator()) {
@@ -218,8 +565,13 @@ where
                     let start_of_body = info.start_of_body();
                     let body_len = info.body_range().len();
                     let total_len = start_of_body + body_len + utils::FIELD_CHECKSUM_LEN_IN_BYTES;
-                    let current_len = self.buffer.as_slice().len();
-                    self.buffer.resize(total_len, 0);
+                    if let Err(e) = self.decoder.check_message_size(total_len) {
+                        self.error = Some(e);
+                        return &mut [];
+                    }
+                    if total_len > current_len {
+                        self.buffer.resize(total_len, 0);
+                    }
                     &mut self.buffer.as_mut_slice()[current_len..]
                 }
                 Err(e) => {
@@ -230,15 +582,82 @@ where
         }
     }
 
+    /// Reads from `reader` until a whole [`RawFrame`] is buffered, then
+    /// returns it. [`None`] is returned if `reader` would block first (see
+    /// [`io::ErrorKind::WouldBlock`]), in which case the partial frame is kept
+    /// and later calls resume from there. Reading stops at the end of the
+    /// frame, so any extra data is left in `reader`.
+    ///
+    /// Just like with [`RawDecoderBuffered::current_frame`], you must call
+    /// [`RawDecoderBuffered::clear`] before reading the next frame. The end
+    /// of `reader` is reported as [`DecodeError::Io`] with
+    /// [`io::ErrorKind::UnexpectedEof`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix::tagvalue::{Config, Configure, RawDecoder};
+    ///
+    /// let mut config = Config::default();
+    /// config.set_separator(b'|');
+    /// let mut decoder = RawDecoder::with_config(config).buffered();
+    /// let mut stream = &b"8=FIX.4.2|9=5|35=0|10=018|8=FIX.4.2|9=5|35=1|10=019|"[..];
+    /// let frame = decoder.read_from(&mut stream).unwrap().unwrap();
+    /// assert_eq!(frame.payload(), b"35=0|");
+    /// decoder.clear();
+    /// let frame = decoder.read_from(&mut stream).unwrap().unwrap();
+    /// assert_eq!(frame.payload(), b"35=1|");
+    /// ```
+    #[cfg(feature = "std")]
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "std")))]
+    pub fn read_from<'a, R>(
+        &'a mut self,
+        reader: &mut R,
+    ) -> Result<Option<RawFrame<&'a [u8]>>, DecodeError>
+    where
+        R: io::Read,
+    {
+        loop {
+            let len = self.buffer.len();
+            let buffer = self.supply_buffer();
+            if buffer.is_empty() {
+                break;
+            }
+            let result = reader.read(buffer);
+            // `supply_buffer` may have reserved more bytes than were read.
+            self.buffer
+                .truncate(len + result.as_ref().map_or(0, |n| *n));
+            match result {
+                Ok(0) => return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into()),
+                Ok(_) => {}
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => return Ok(None),
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(err.into()),
+            }
+        }
+        self.current_frame()
+    }
+
+    /// Returns the length of the [`RawFrame`] at the start of the internal
+    /// buffer, if complete, regardless of its validity.
+    pub(crate) fn current_frame_len(&self) -> Option<usize> {
+        let data = self.buffer.as_slice();
+        match self.decoder.message_len(data) {
+            Ok(Some(len)) if len <= data.len() => Some(len),
+            _ => None,
+        }
+    }
+
+    /// Returns the [`RawFrame`] at the start of the internal buffer, if
+    /// complete.
     pub fn current_frame<'a>(&'a self) -> Result<Option<RawFrame<&'a [u8]>>, DecodeError> {
         if let Some(err) = self.error.clone() {
             Err(err)
         } else {
-            let data = &self.buffer.as_slice();
-            if data.len() == 0 || data.len() == utils::MIN_FIX_MESSAGE_LEN_IN_BYTES {
-                Ok(None)
-            } else {
-                self.decoder.decode(*data).map(|message| Some(message))
+            let data = self.buffer.as_slice();
+            match self.decoder.message_len(data)? {
+                Some(len) if len <= data.len() => self.decoder.decode(&data[..len]).map(Some),
+                _ => Ok(None),
             }
         }
     }
@@ -275,23 +694,44 @@ impl HeaderInfo {
         start..start + self.body_length
     }
 
+    pub fn body_length_offset(&self) -> usize {
+        // `BodyLength` starts right after the separator of `BeginString`.
+        self.i_sep[0] + 1
+    }
+
     fn parse(data: &[u8], separator: u8) -> Result<Self, DecodeError> {
+        const HEADER_TAGS: [&[u8]; 2] = [b"8", b"9"];
         let mut info = HeaderInfo::empty();
         let mut field_i = 0;
+        let mut field_start = 0;
         let mut i = 0;
+        let invalid = |offset: usize, field_i: usize| DecodeError::Invalid {
+            offset,
+            tag: TagU16::new(8 + field_i as u16),
+        };
         while field_i < 2 && i < data.len() {
             let byte = data[i];
-            if byte == b'=' {
+            if byte == b'=' && info.i_equal_sign[field_i] == 0 {
+                if &data[field_start..i] != HEADER_TAGS[field_i] {
+                    return Err(invalid(field_start, field_i));
+                }
                 info.i_equal_sign[field_i] = i;
                 info.body_length = 0;
             } else if byte == separator {
+                if info.i_equal_sign[field_i] == 0 || info.i_equal_sign[field_i] + 1 == i {
+                    return Err(invalid(field_start, field_i));
+                }
                 info.i_sep[field_i] = i;
                 field_i += 1;
-            } else {
+                field_start = i + 1;
+            } else if field_i == 1 && info.i_equal_sign[1] != 0 {
+                if !byte.is_ascii_digit() {
+                    return Err(invalid(field_start, field_i));
+                }
                 info.body_length = info
                     .body_length
                     .wrapping_mul(10)
-                    .wrapping_add(byte.wrapping_sub(b'0') as usize);
+                    .wrapping_add((byte - b'0') as usize);
             }
             i += 1;
         }
@@ -304,11 +744,29 @@ impl HeaderInfo {
             debug_assert!(info.i_sep[1] < data.len());
             Ok(info)
         } else {
-            Err(DecodeError::Invalid)
+            Err(invalid(field_start, field_i.min(1)))
         }
     }
 }
 
+/// Returns the number of garbled bytes at the start of `data`, up to the next
+/// plausible start of message. See [`RawDecoderBuffered::resync`].
+pub(crate) fn resync_len(data: &[u8]) -> usize {
+    const BEGIN_STRING_PREFIX: &[u8] = b"8=FIX";
+    data.windows(BEGIN_STRING_PREFIX.len())
+        .skip(1)
+        .position(|window| window == BEGIN_STRING_PREFIX)
+        .map(|i| i + 1)
+        .unwrap_or_else(|| {
+            // Keep the longest suffix that might be the start of a message.
+            let partial = (1..BEGIN_STRING_PREFIX.len())
+                .rev()
+                .find(|len| *len < data.len() && data.ends_with(&BEGIN_STRING_PREFIX[..*len]))
+                .unwrap_or(0);
+            data.len() - partial
+        })
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -324,7 +782,56 @@ mod test {
         let decoder = new_decoder();
         assert!(matches!(
             decoder.decode(&[] as &[u8]),
-            Err(DecodeError::Length)
+            Err(DecodeError::TooShort { len: 0 })
+        ));
+    }
+
+    #[test]
+    fn unexpected_begin_string_is_refused() {
+        let mut decoder = new_decoder();
+        decoder
+            .config_mut()
+            .set_expected_begin_string(Some(b"FIXT.1.1"));
+        let err = decoder
+            .decode(b"8=FIX.4.2|9=5|35=0|10=000|" as &[u8])
+            .unwrap_err();
+        assert_eq!(
+            err,
+            DecodeError::BeginString {
+                expected: b"FIXT.1.1".to_vec(),
+                found: b"FIX.4.2".to_vec(),
+            }
+        );
+        assert_eq!(err.offset(), Some(0));
+        assert_eq!(err.tag(), TagU16::new(8));
+        assert_eq!(
+            err.to_string(),
+            "BeginString <8> is FIX.4.2, but FIXT.1.1 was expected"
+        );
+        let frame = decoder
+            .decode(b"8=FIXT.1.1|9=5|35=0|10=000|" as &[u8])
+            .unwrap();
+        assert_eq!(frame.begin_string(), b"FIXT.1.1");
+    }
+
+    #[test]
+    fn huge_body_length_is_refused() {
+        let mut decoder = new_decoder();
+        decoder.config_mut().set_max_message_size(Some(32));
+        let msg = "8=FIX.4.2|9=40|35=D|49=AFUNDMGR|56=ABROKER|15=USD|59=0|10=091|".as_bytes();
+        assert!(matches!(
+            decoder.decode(msg),
+            Err(DecodeError::TooLong { len: 62, max: 32 })
+        ));
+        let msg = "8=?|9=999999999|35=0|10=000|".as_bytes();
+        let mut decoder = decoder.buffered();
+        let buffer = decoder.supply_buffer();
+        let len = buffer.len();
+        buffer.copy_from_slice(&msg[..len]);
+        assert!(decoder.supply_buffer().is_empty());
+        assert!(matches!(
+            decoder.current_frame(),
+            Err(DecodeError::TooLong { max: 32, .. })
         ));
     }
 
@@ -350,7 +857,10 @@ mod test {
     fn message_with_empty_payload_is_invalid() {
         let decoder = new_decoder();
         let msg = "8=?|9=5|10=082|".as_bytes();
-        assert!(matches!(decoder.decode(msg), Err(DecodeError::Length)));
+        assert!(matches!(
+            decoder.decode(msg),
+            Err(DecodeError::TooShort { len: 15 })
+        ));
     }
 
     #[test]
@@ -358,7 +868,60 @@ mod test {
         let mut decoder = new_decoder();
         decoder.config_mut().set_verify_checksum(true);
         let msg = "8=FIX.4.2|9=40|35=D|49=AFUNDMGR|56=ABROKER|15=USD|59=0|10=000|".as_bytes();
-        assert!(matches!(decoder.decode(msg), Err(DecodeError::CheckSum)));
+        assert!(matches!(
+            decoder.decode(msg),
+            Err(DecodeError::CheckSum {
+                offset: 55,
+                declared: 0,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn peek_header_skips_secure_data() {
+        let decoder = new_decoder();
+        let msg = "8=FIX.4.4|9=55|35=0|90=5|91=a|=b||49=A|56=B|52=20100304-07:59:30|34=7|10=000|";
+        let frame = decoder.decode(msg.as_bytes()).unwrap();
+        let header = decoder.peek_header(&frame);
+        assert_eq!(header.begin_string(), b"FIX.4.4");
+        assert_eq!(header.msg_type(), Some(&b"0"[..]));
+        assert_eq!(header.sender_comp_id(), Some(&b"A"[..]));
+        assert_eq!(header.target_comp_id(), Some(&b"B"[..]));
+        assert_eq!(header.msg_seq_num(), Some(&b"7"[..]));
+    }
+
+    #[test]
+    fn peek_header_stops_at_the_body() {
+        let decoder = new_decoder();
+        let msg = "8=FIX.4.4|9=20|35=D|49=A|55=X|56=B|10=000|";
+        let frame = decoder.decode(msg.as_bytes()).unwrap();
+        let header = decoder.peek_header(&frame);
+        assert_eq!(header.sender_comp_id(), Some(&b"A"[..]));
+        assert_eq!(header.target_comp_id(), None);
+    }
+
+    #[test]
+    fn verify_checks_framing_body_length_and_checksum() {
+        let decoder = new_decoder();
+        let valid = "8=FIX.4.2|9=40|35=D|49=AFUNDMGR|56=ABROKER|15=USD|59=0|10=091|";
+        assert!(decoder.verify(valid.as_bytes()));
+        for invalid in &[
+            "8=FIX.4.2|9=40|35=D|49=AFUNDMGR|56=ABROKER|15=USD|59=0|10=092|",
+            "8=FIX.4.2|9=41|35=D|49=AFUNDMGR|56=ABROKER|15=USD|59=0|10=091|",
+            "8=FIX.4.2|9=40|35=D|49=AFUNDMGR|56=ABROKER|15=USD|59=0|11=091|",
+            "8=FIX.4.2|9=40|35=D|49=AFUNDMGR|56=ABROKER|15=USD|59=0|10=091;",
+            "8=FIX.4.2|9=40|35=D|49=AFUNDMGR|56=ABROKER|15=USD|59=0|10=09?|",
+            "9=40|8=FIX.4.2|35=D|49=AFUNDMGR|56=ABROKER|15=USD|59=0|10=091|",
+            "8=|9=0|10=225|",
+            "8=?|9=5|10=082|",
+        ] {
+            assert!(!decoder.verify(invalid.as_bytes()), "{}", invalid);
+        }
+        // Verification settings don't matter.
+        let mut lenient = new_decoder();
+        lenient.config_mut().set_verify_checksum(false);
+        assert!(!lenient.verify(b"8=FIX.4.2|9=40|35=D|49=AFUNDMGR|56=ABROKER|15=USD|59=0|10=000|"));
     }
 
     #[test]
@@ -378,6 +941,45 @@ mod test {
             .is_err());
     }
 
+    #[test]
+    fn body_length_mismatch_reports_both_lengths() {
+        let decoder = new_decoder();
+        let msg = "8=FIX.4.2|9=42|35=D|49=AFUNDMGR|56=ABROKER|15=USD|59=0|10=091|".as_bytes();
+        assert_eq!(
+            decoder.decode(msg).unwrap_err(),
+            DecodeError::Length {
+                offset: 10,
+                declared: 42,
+                actual: 40,
+            }
+        );
+    }
+
+    #[test]
+    fn malformed_header_fields_are_located() {
+        let decoder = new_decoder();
+        let err = decoder
+            .decode("35=D|9=40|49=AFUNDMGR|56=ABROKER|10=091|".as_bytes())
+            .unwrap_err();
+        assert_eq!(
+            err,
+            DecodeError::Invalid {
+                offset: 0,
+                tag: TagU16::new(8),
+            }
+        );
+        let err = decoder
+            .decode("8=FIX.4.2|9=4x|35=D|49=AFUNDMGR|56=ABROKER|10=091|".as_bytes())
+            .unwrap_err();
+        assert_eq!(
+            err,
+            DecodeError::Invalid {
+                offset: 10,
+                tag: TagU16::new(9),
+            }
+        );
+    }
+
     fn new_decoder_buffered() -> RawDecoderBuffered {
         let mut config = Config::default();
         config.set_separator(b'|');
@@ -411,4 +1013,171 @@ mod test {
         }
         assert!(frame.is_some());
     }
+
+    /// Feeds `stream` to `decoder` and collects all payloads, together with
+    /// the total number of bytes skipped by resynchronization.
+    fn decode_stream_with_resync(
+        decoder: &mut RawDecoderBuffered,
+        mut stream: &[u8],
+    ) -> (Vec<Vec<u8>>, usize) {
+        let mut payloads = Vec::new();
+        let mut skipped = 0;
+        loop {
+            let buffer = decoder.supply_buffer();
+            let len = buffer.len().min(stream.len());
+            buffer[..len].copy_from_slice(&stream[..len]);
+            stream = &stream[len..];
+            if len < buffer.len() {
+                return (payloads, skipped);
+            }
+            match decoder.current_frame() {
+                Ok(Some(frame)) => {
+                    payloads.push(frame.payload().to_vec());
+                    decoder.clear();
+                }
+                Ok(None) => {}
+                Err(_) => skipped += decoder.resync(),
+            }
+        }
+    }
+
+    #[test]
+    fn garbled_prefix_is_skipped() {
+        let mut decoder = new_decoder_buffered();
+        let stream =
+            b"\x00\x01garbage|8=FIX.4.2|9=40|35=D|49=AFUNDMGR|56=ABROKER|15=USD|59=0|10=091|";
+        let (payloads, skipped) = decode_stream_with_resync(&mut decoder, stream);
+        assert_eq!(
+            payloads,
+            vec![b"35=D|49=AFUNDMGR|56=ABROKER|15=USD|59=0|".to_vec()]
+        );
+        assert_eq!(skipped, 10);
+    }
+
+    #[test]
+    fn decoding_resumes_after_corrupt_message() {
+        let mut decoder = new_decoder_buffered();
+        decoder.config_mut().set_verify_checksum(true);
+        let mut stream = Vec::new();
+        stream.extend_from_slice(b"8=FIX.4.2|9=40|35=D|49=AFUNDMGR|56=ABROKER|15=USD|59=0|10=091|");
+        // Bad checksum.
+        stream.extend_from_slice(b"8=FIX.4.2|9=40|35=D|49=AFUNDMGR|56=ABROKER|15=USD|59=0|10=000|");
+        stream.extend_from_slice(b"8=FIX.4.2|9=5|35=0|10=018|");
+        let (payloads, skipped) = decode_stream_with_resync(&mut decoder, &stream[..]);
+        assert_eq!(
+            payloads,
+            vec![
+                b"35=D|49=AFUNDMGR|56=ABROKER|15=USD|59=0|".to_vec(),
+                b"35=0|".to_vec()
+            ]
+        );
+        assert_eq!(skipped, 62);
+    }
+
+    #[test]
+    fn resync_keeps_partial_begin_string() {
+        let mut decoder = new_decoder_buffered();
+        decoder
+            .supply_buffer()
+            .copy_from_slice(b"xxxxxxxxxxxxxxxxx8=F");
+        assert!(decoder.supply_buffer().is_empty());
+        assert!(decoder.current_frame().is_err());
+        assert_eq!(decoder.resync(), 17);
+        assert!(decoder.current_frame().unwrap().is_none());
+        assert_eq!(decoder.supply_buffer().len(), 17);
+    }
+
+    #[test]
+    fn wrong_body_length_can_be_tolerated() {
+        let msg = "8=FIX.4.2|9=99|35=0|10=000|".as_bytes();
+        let mut decoder = new_decoder();
+        assert!(matches!(
+            decoder.decode(msg),
+            Err(DecodeError::Length {
+                declared: 99,
+                actual: 5,
+                ..
+            })
+        ));
+        decoder
+            .config_mut()
+            .set_body_length_verification(Verification::WarnOnly);
+        let frame = decoder.decode(msg).unwrap();
+        assert_eq!(frame.payload(), b"35=0|");
+        assert!(matches!(
+            frame.warnings(),
+            [DecodeError::Length { declared: 99, .. }]
+        ));
+        decoder
+            .config_mut()
+            .set_body_length_verification(Verification::Skip);
+        let frame = decoder.decode(msg).unwrap();
+        assert_eq!(frame.payload(), b"35=0|");
+        assert!(frame.warnings().is_empty());
+    }
+
+    #[test]
+    fn wrong_checksum_can_be_tolerated() {
+        let msg = "8=FIX.4.2|9=5|35=0|10=000|".as_bytes();
+        let mut decoder = new_decoder();
+        decoder
+            .config_mut()
+            .set_checksum_verification(Verification::Enforce);
+        assert!(matches!(
+            decoder.decode(msg),
+            Err(DecodeError::CheckSum { declared: 0, .. })
+        ));
+        decoder
+            .config_mut()
+            .set_checksum_verification(Verification::WarnOnly);
+        let frame = decoder.decode(msg).unwrap();
+        assert_eq!(frame.payload(), b"35=0|");
+        assert!(matches!(
+            frame.warnings(),
+            [DecodeError::CheckSum { declared: 0, .. }]
+        ));
+        let msg = "8=FIX.4.2|9=5|35=0|10=018|".as_bytes();
+        assert!(decoder.decode(msg).unwrap().warnings().is_empty());
+    }
+
+    // Yields data in small chunks, then blocks.
+    #[cfg(feature = "std")]
+    struct Trickle<'a> {
+        data: &'a [u8],
+        chunk_len: usize,
+    }
+
+    #[cfg(feature = "std")]
+    impl<'a> io::Read for Trickle<'a> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if self.data.is_empty() {
+                return Err(io::ErrorKind::WouldBlock.into());
+            }
+            let len = self.chunk_len.min(buf.len()).min(self.data.len());
+            buf[..len].copy_from_slice(&self.data[..len]);
+            self.data = &self.data[len..];
+            Ok(len)
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn read_from_resumes_after_would_block() {
+        let msg = b"8=FIX.4.2|9=40|35=D|49=AFUNDMGR|56=ABROKER|15=USD|59=0|10=091|";
+        let mut decoder = new_decoder().buffered();
+        let mut reader = Trickle {
+            data: &msg[..30],
+            chunk_len: 7,
+        };
+        assert!(decoder.read_from(&mut reader).unwrap().is_none());
+        reader.data = &msg[30..];
+        let frame = decoder.read_from(&mut reader).unwrap().unwrap();
+        assert_eq!(frame.as_bytes(), &msg[..]);
+        decoder.clear();
+        assert!(decoder.read_from(&mut reader).unwrap().is_none());
+        assert_eq!(
+            decoder.read_from(&mut &b""[..]).map(|_| ()),
+            Err(DecodeError::Io(io::ErrorKind::UnexpectedEof))
+        );
+    }
 }
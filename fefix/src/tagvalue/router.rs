@@ -0,0 +1,211 @@
+use super::{FixMessage, FixMessageError, Message};
+use crate::definitions::fix44;
+use crate::dict::IsFieldDefinition;
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+use hashbrown::HashMap;
+
+type Handler<'h, R> = Box<dyn for<'a> FnMut(&Message<'a, &'a [u8]>) -> R + 'h>;
+type TypedHandler<'h, R> =
+    Box<dyn for<'a> FnMut(&Message<'a, &'a [u8]>) -> Result<R, FixMessageError> + 'h>;
+
+/// Dispatches decoded messages to user-defined handlers according to their
+/// `MsgType <35>`, so that consumers don't have to `match` on it themselves.
+///
+/// Handlers either receive the decoded [`Message`] as-is (see
+/// [`MessageRouter::add_handler`]) or a typed message that implements
+/// [`FixMessage`] (see [`MessageRouter::add_typed_handler`]). Messages with
+/// any other `MsgType <35>`, or none at all, fall back to the default handler.
+///
+/// # Examples
+///
+/// ```
+/// use fefix::prelude::*;
+/// use fefix::tagvalue::{Config, Configure, Decoder, FixMessage, Message, MessageRouter};
+///
+/// #[derive(FixMessage)]
+/// struct TestRequest {
+///     #[fefix(tag = 112)]
+///     test_req_id: String,
+/// }
+///
+/// let mut router = MessageRouter::new(|_msg: &Message<&[u8]>| "unknown".to_string());
+/// router.add_handler(b"0", |_msg| "heartbeat".to_string());
+/// router.add_typed_handler(b"1", |msg: TestRequest| msg.test_req_id);
+///
+/// let mut decoder = Decoder::<Config>::new(Dictionary::fix44());
+/// decoder.config_mut().set_separator(b'|');
+/// let message = decoder.decode(b"8=FIX.4.4|9=12|35=1|112=XY|10=000|").unwrap();
+/// assert_eq!(router.dispatch(&message), Ok("XY".to_string()));
+/// let message = decoder.decode(b"8=FIX.4.4|9=5|35=0|10=000|").unwrap();
+/// assert_eq!(router.dispatch(&message), Ok("heartbeat".to_string()));
+/// let message = decoder.decode(b"8=FIX.4.4|9=5|35=A|10=000|").unwrap();
+/// assert_eq!(router.dispatch(&message), Ok("unknown".to_string()));
+/// ```
+pub struct MessageRouter<'h, R = ()> {
+    handlers: HashMap<Vec<u8>, TypedHandler<'h, R>>,
+    default_handler: Handler<'h, R>,
+}
+
+impl<'h, R> MessageRouter<'h, R>
+where
+    R: 'h,
+{
+    /// Creates a new [`MessageRouter`] without any handlers, other than the
+    /// `default_handler` for all messages.
+    pub fn new<F>(default_handler: F) -> Self
+    where
+        F: for<'a> FnMut(&Message<'a, &'a [u8]>) -> R + 'h,
+    {
+        Self {
+            handlers: HashMap::new(),
+            default_handler: Box::new(default_handler),
+        }
+    }
+
+    /// Routes all messages with `MsgType <35>` equal to `msg_type` to
+    /// `handler`, replacing any previous handler for the same `msg_type`.
+    pub fn add_handler<F>(&mut self, msg_type: &[u8], mut handler: F)
+    where
+        F: for<'a> FnMut(&Message<'a, &'a [u8]>) -> R + 'h,
+    {
+        self.handlers
+            .insert(msg_type.to_vec(), Box::new(move |msg| Ok(handler(msg))));
+    }
+
+    /// Like [`MessageRouter::add_handler`], but messages are first decoded
+    /// into `M` via [`FixMessage::decode_from`]. Decoding errors are returned
+    /// by [`MessageRouter::dispatch`].
+    pub fn add_typed_handler<M, F>(&mut self, msg_type: &[u8], mut handler: F)
+    where
+        M: for<'a> FixMessage<'a>,
+        F: FnMut(M) -> R + 'h,
+    {
+        self.handlers.insert(
+            msg_type.to_vec(),
+            Box::new(move |msg| M::decode_from(msg).map(&mut handler)),
+        );
+    }
+
+    /// Replaces the handler for all messages that have no other handler.
+    pub fn set_default_handler<F>(&mut self, default_handler: F)
+    where
+        F: for<'a> FnMut(&Message<'a, &'a [u8]>) -> R + 'h,
+    {
+        self.default_handler = Box::new(default_handler);
+    }
+
+    /// Returns `true` if and only if `self` has a handler for `msg_type`,
+    /// other than the default handler.
+    pub fn has_handler(&self, msg_type: &[u8]) -> bool {
+        self.handlers.contains_key(msg_type)
+    }
+
+    /// Calls the handler for the `MsgType <35>` of `message` and returns its
+    /// output.
+    pub fn dispatch<T>(&mut self, message: &Message<T>) -> Result<R, FixMessageError>
+    where
+        T: AsRef<[u8]>,
+    {
+        let message = message.as_borrowed();
+        let handler = message
+            .fv_raw_by_tag(fix44::MSG_TYPE.tag())
+            .and_then(|msg_type| self.handlers.get_mut(msg_type));
+        match handler {
+            Some(handler) => handler(&message),
+            None => Ok((self.default_handler)(&message)),
+        }
+    }
+}
+
+impl<'h, R> fmt::Debug for MessageRouter<'h, R> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let msg_types: Vec<String> = self
+            .handlers
+            .keys()
+            .map(|msg_type| String::from_utf8_lossy(msg_type).into_owned())
+            .collect();
+        f.debug_struct("MessageRouter")
+            .field("msg_types", &msg_types)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tagvalue::test_decoder;
+    use crate::TagU16;
+
+    #[derive(Debug, PartialEq, FixMessage)]
+    struct Heartbeat {
+        #[fefix(tag = 112)]
+        test_req_id: Option<String>,
+    }
+
+    #[derive(Debug, PartialEq, FixMessage)]
+    struct TestRequest {
+        #[fefix(tag = 112)]
+        test_req_id: String,
+    }
+
+    #[test]
+    fn handlers_can_keep_state() {
+        let mut heartbeats = vec![];
+        let mut others = 0;
+        {
+            let mut router = MessageRouter::new(|_msg: &Message<&[u8]>| others += 1);
+            router.add_typed_handler(b"0", |msg: Heartbeat| heartbeats.push(msg));
+            let mut decoder = test_decoder();
+            for data in [
+                &b"8=FIX.4.4|9=5|35=0|10=000|"[..],
+                &b"8=FIX.4.4|9=11|35=0|112=A|10=000|"[..],
+                &b"8=FIX.4.4|9=5|35=5|10=000|"[..],
+            ] {
+                let message = decoder.decode(data).unwrap();
+                router.dispatch(&message).unwrap();
+            }
+        }
+        assert_eq!(
+            heartbeats,
+            vec![
+                Heartbeat { test_req_id: None },
+                Heartbeat {
+                    test_req_id: Some("A".to_string())
+                }
+            ]
+        );
+        assert_eq!(others, 1);
+    }
+
+    #[test]
+    fn typed_decoding_errors_are_returned() {
+        let mut router = MessageRouter::new(|_msg: &Message<&[u8]>| ());
+        router.add_typed_handler(b"1", |_msg: TestRequest| ());
+        let mut decoder = test_decoder();
+        let message = decoder.decode(b"8=FIX.4.4|9=5|35=1|10=000|").unwrap();
+        assert_eq!(
+            router.dispatch(&message),
+            Err(FixMessageError::MissingField {
+                tag: TagU16::new(112).unwrap()
+            })
+        );
+    }
+
+    #[test]
+    fn handlers_can_be_replaced() {
+        let mut router = MessageRouter::new(|_msg: &Message<&[u8]>| 0);
+        assert!(!router.has_handler(b"0"));
+        router.add_handler(b"0", |_msg| 1);
+        router.add_handler(b"0", |_msg| 2);
+        assert!(router.has_handler(b"0"));
+        let mut decoder = test_decoder();
+        let message = decoder.decode(b"8=FIX.4.4|9=5|35=0|10=000|").unwrap();
+        assert_eq!(router.dispatch(&message), Ok(2));
+        router.set_default_handler(|_msg| 3);
+        let message = decoder.decode(b"8=FIX.4.4|9=5|35=A|10=000|").unwrap();
+        assert_eq!(router.dispatch(&message), Ok(3));
+    }
+}
@@ -0,0 +1,140 @@
+use super::DecodeError;
+
+/// Counters about the health of a stream of FIX messages, as seen by a
+/// [`Decoder`](super::Decoder) or [`DecoderBuffered`](super::DecoderBuffered).
+/// See [`Decoder::stats`](super::Decoder::stats).
+///
+/// All counters start at zero and saturate instead of overflowing. They are
+/// cheap to update, so they are always enabled.
+///
+/// # Examples
+///
+/// ```
+/// use fefix::tagvalue::{Config, Configure, Decoder};
+/// use fefix::Dictionary;
+///
+/// let mut decoder = Decoder::<Config>::new(Dictionary::fix44());
+/// decoder.config_mut().set_separator(b'|');
+/// decoder.config_mut().set_verify_checksum(true);
+/// assert!(decoder.decode(b"8=FIX.4.4|9=5|35=0|10=000|").is_err());
+/// assert!(decoder.decode(b"8=FIX.4.4|9=5|35=0|10=020|").is_ok());
+/// let stats = decoder.stats();
+/// assert_eq!(stats.messages_decoded(), 1);
+/// assert_eq!(stats.decode_errors(), 1);
+/// assert_eq!(stats.checksum_failures(), 1);
+/// assert_eq!(stats.bytes_processed(), 52);
+/// assert_eq!(stats.max_message_size(), 26);
+/// ```
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub struct DecoderStats {
+    messages_decoded: u64,
+    decode_errors: u64,
+    bytes_processed: u64,
+    checksum_failures: u64,
+    resyncs: u64,
+    max_message_size: usize,
+}
+
+impl DecoderStats {
+    /// Returns the number of messages that were decoded successfully,
+    /// including those with tolerated integrity failures (see
+    /// [`Verification::WarnOnly`](super::Verification::WarnOnly)).
+    pub fn messages_decoded(&self) -> u64 {
+        self.messages_decoded
+    }
+
+    /// Returns the number of messages that couldn't be decoded, for any
+    /// reason.
+    pub fn decode_errors(&self) -> u64 {
+        self.decode_errors
+    }
+
+    /// Returns the total length, in bytes, of all messages that were either
+    /// decoded or rejected, as well as of all garbled data that was skipped.
+    pub fn bytes_processed(&self) -> u64 {
+        self.bytes_processed
+    }
+
+    /// Returns the number of messages with a wrong `CheckSum <10>`, whether
+    /// they were rejected or not.
+    pub fn checksum_failures(&self) -> u64 {
+        self.checksum_failures
+    }
+
+    /// Returns the number of times garbled data was skipped to recover from a
+    /// decoding error, e.g. via
+    /// [`DecoderBuffered::resync`](super::DecoderBuffered::resync).
+    pub fn resyncs(&self) -> u64 {
+        self.resyncs
+    }
+
+    /// Returns the length, in bytes, of the longest message that was either
+    /// decoded or rejected.
+    pub fn max_message_size(&self) -> usize {
+        self.max_message_size
+    }
+
+    /// Reports all counters of `self` to the global [`metrics`] recorder,
+    /// with `labels` attached (e.g. the name of the feed). Counters are
+    /// reported as absolute values, so this should be called periodically,
+    /// e.g. once per second.
+    #[cfg(feature = "utils-metrics")]
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "utils-metrics")))]
+    pub fn record_metrics(&self, labels: &[metrics::Label]) {
+        metrics::absolute_counter!(
+            "fefix_decoder_messages_decoded_total",
+            self.messages_decoded,
+            labels.iter()
+        );
+        metrics::absolute_counter!(
+            "fefix_decoder_decode_errors_total",
+            self.decode_errors,
+            labels.iter()
+        );
+        metrics::absolute_counter!(
+            "fefix_decoder_processed_bytes_total",
+            self.bytes_processed,
+            labels.iter()
+        );
+        metrics::absolute_counter!(
+            "fefix_decoder_checksum_failures_total",
+            self.checksum_failures,
+            labels.iter()
+        );
+        metrics::absolute_counter!("fefix_decoder_resyncs_total", self.resyncs, labels.iter());
+        metrics::gauge!(
+            "fefix_decoder_max_message_size_bytes",
+            self.max_message_size as f64,
+            labels.iter()
+        );
+    }
+
+    pub(crate) fn record_message(&mut self, len: usize, warnings: &[DecodeError]) {
+        self.messages_decoded = self.messages_decoded.saturating_add(1);
+        self.record_len(len);
+        if warnings
+            .iter()
+            .any(|warning| matches!(warning, DecodeError::CheckSum { .. }))
+        {
+            self.checksum_failures = self.checksum_failures.saturating_add(1);
+        }
+    }
+
+    pub(crate) fn record_error(&mut self, len: usize, err: &DecodeError) {
+        self.decode_errors = self.decode_errors.saturating_add(1);
+        self.record_len(len);
+        if let DecodeError::CheckSum { .. } = err {
+            self.checksum_failures = self.checksum_failures.saturating_add(1);
+        }
+    }
+
+    pub(crate) fn record_resync(&mut self, skipped: usize) {
+        self.resyncs = self.resyncs.saturating_add(1);
+        self.bytes_processed = self.bytes_processed.saturating_add(skipped as u64);
+    }
+
+    fn record_len(&mut self, len: usize) {
+        self.bytes_processed = self.bytes_processed.saturating_add(len as u64);
+        self.max_message_size = self.max_message_size.max(len);
+    }
+}
@@ -0,0 +1,441 @@
+use super::raw_decoder;
+use super::{
+    Config, Configure, DecodeError, Decoder, EncoderHandle, MessageOwned, RawDecoder, RawFrame,
+};
+use crate::Buffer;
+use bytes::{Buf, Bytes, BytesMut};
+use std::io;
+use tokio_util::codec;
+
+/// A [`tokio_util::codec`] implementation for the FIX tag-value encoding.
+///
+/// [`TokioCodec`] takes care of FIX message framing over streams of bytes,
+/// e.g. TCP connections, so that you can use it together with `Framed`. Decoded
+/// messages are [`RawFrame`]'s with `BodyLength <9>` and `CheckSum <10>`
+/// already verified; use a [`TokioMessageCodec`] for field access. Encoding
+/// accepts either an unfinished [`EncoderHandle`], which is completed by
+/// [`EncoderHandle::finalize`], or fully serialized FIX messages, which are
+/// written as-is.
+///
+/// # Examples
+///
+/// ```
+/// use bytes::BytesMut;
+/// use fefix::tagvalue::{Config, TokioCodec};
+/// use tokio_util::codec::Decoder;
+///
+/// let mut codec = TokioCodec::<Config>::new();
+/// codec.config_mut().set_separator(b'|');
+/// let mut src = BytesMut::from(&b"8=FIX.4.2|9=42|35=0|49=A|56=B|34=12|52=20100304-07:59:30|10=185|8=FIX"[..]);
+/// let frame = codec.decode(&mut src).unwrap().unwrap();
+/// assert_eq!(frame.begin_string(), b"FIX.4.2");
+/// assert_eq!(&src[..], b"8=FIX");
+/// assert!(codec.decode(&mut src).unwrap().is_none());
+/// ```
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "utils-tokio")))]
+pub struct TokioCodec<C = Config>
+where
+    C: Configure,
+{
+    raw_decoder: RawDecoder<C>,
+}
+
+impl<C> TokioCodec<C>
+where
+    C: Configure,
+{
+    /// Creates a new [`TokioCodec`] with default configuration options.
+    pub fn new() -> Self {
+        Self::with_config(C::default())
+    }
+
+    /// Creates a new [`TokioCodec`] with `config` as a [`Configure`]
+    /// implementor.
+    pub fn with_config(config: C) -> Self {
+        Self {
+            raw_decoder: RawDecoder::with_config(config),
+        }
+    }
+
+    /// Returns an immutable reference to the [`Configure`] implementor used by
+    /// `self`.
+    pub fn config(&self) -> &C {
+        self.raw_decoder.config()
+    }
+
+    /// Returns a mutable reference to the [`Configure`] implementor used by
+    /// `self`.
+    pub fn config_mut(&mut self) -> &mut C {
+        self.raw_decoder.config_mut()
+    }
+}
+
+impl<C> codec::Decoder for TokioCodec<C>
+where
+    C: Configure,
+{
+    type Item = RawFrame<Bytes>;
+    type Error = DecodeError;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        match split_frame(&self.raw_decoder, src)? {
+            Some(data) => self.raw_decoder.decode(data).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+impl<'a, C> codec::Encoder<&'a [u8]> for TokioCodec<C>
+where
+    C: Configure,
+{
+    type Error = io::Error;
+
+    fn encode(&mut self, item: &'a [u8], dst: &mut BytesMut) -> Result<(), Self::Error> {
+        dst.extend_from_slice(item);
+        Ok(())
+    }
+}
+
+impl<'a, B, C, C2> codec::Encoder<EncoderHandle<'a, B, C2>> for TokioCodec<C>
+where
+    B: Buffer,
+    C: Configure,
+    C2: Configure,
+{
+    type Error = io::Error;
+
+    fn encode(
+        &mut self,
+        item: EncoderHandle<'a, B, C2>,
+        dst: &mut BytesMut,
+    ) -> Result<(), Self::Error> {
+        encode_handle(item, dst)
+    }
+}
+
+/// A [`tokio_util::codec`] implementation for the FIX tag-value encoding that
+/// decodes whole [`MessageOwned`]s.
+///
+/// [`TokioMessageCodec`] frames messages just like [`TokioCodec`], then
+/// decodes them with a [`Decoder`] for field access. Every decoded message is
+/// copied into a [`MessageOwned`], so that it can outlive the next call to
+/// `decode` (e.g. when yielded by a `Stream`). Encoding works exactly like
+/// with [`TokioCodec`].
+///
+/// # Examples
+///
+/// ```
+/// use bytes::BytesMut;
+/// use fefix::definitions::fix44;
+/// use fefix::tagvalue::{Config, Decoder, Encoder, FieldAccess, TokioMessageCodec};
+/// use fefix::Dictionary;
+/// use tokio_util::codec::{Decoder as _, Encoder as _};
+///
+/// let mut codec = TokioMessageCodec::new(Decoder::<Config>::new(Dictionary::fix44()));
+/// let mut encoder = Encoder::<Config>::default();
+/// let mut buffer = Vec::new();
+/// let mut msg = encoder.start_message(b"FIX.4.4", &mut buffer, b"0");
+/// msg.set(fix44::TEST_REQ_ID, "X");
+/// let mut stream = BytesMut::new();
+/// codec.encode(msg, &mut stream).unwrap();
+///
+/// let message = codec.decode(&mut stream).unwrap().unwrap();
+/// assert_eq!(message.message().fv(fix44::TEST_REQ_ID), Ok("X"));
+/// assert!(stream.is_empty());
+/// ```
+#[derive(Debug)]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "utils-tokio")))]
+pub struct TokioMessageCodec<C = Config>
+where
+    C: Configure,
+{
+    decoder: Decoder<C>,
+}
+
+impl<C> TokioMessageCodec<C>
+where
+    C: Configure,
+{
+    /// Creates a new [`TokioMessageCodec`] that decodes messages with
+    /// `decoder`, including its [`Configure`] implementor.
+    pub fn new(decoder: Decoder<C>) -> Self {
+        Self { decoder }
+    }
+
+    /// Returns an immutable reference to the [`Decoder`] used by `self`.
+    pub fn decoder(&self) -> &Decoder<C> {
+        &self.decoder
+    }
+
+    /// Returns a mutable reference to the [`Decoder`] used by `self`, e.g. to
+    /// register application-layer dictionaries or to read its
+    /// [`DecoderStats`](super::DecoderStats).
+    pub fn decoder_mut(&mut self) -> &mut Decoder<C> {
+        &mut self.decoder
+    }
+
+    /// Returns an immutable reference to the [`Configure`] implementor used by
+    /// `self`.
+    pub fn config(&self) -> &C {
+        self.decoder.config()
+    }
+
+    /// Returns a mutable reference to the [`Configure`] implementor used by
+    /// `self`.
+    pub fn config_mut(&mut self) -> &mut C {
+        self.decoder.config_mut()
+    }
+
+    /// Consumes `self` and returns the underlying [`Decoder`].
+    pub fn into_inner(self) -> Decoder<C> {
+        self.decoder
+    }
+}
+
+impl<C> codec::Decoder for TokioMessageCodec<C>
+where
+    C: Configure,
+{
+    type Item = MessageOwned;
+    type Error = DecodeError;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        match split_frame(self.decoder.raw_decoder(), src)? {
+            Some(data) => self
+                .decoder
+                .decode(data)
+                .map(|message| Some(message.to_owned())),
+            None => Ok(None),
+        }
+    }
+}
+
+impl<'a, C> codec::Encoder<&'a [u8]> for TokioMessageCodec<C>
+where
+    C: Configure,
+{
+    type Error = io::Error;
+
+    fn encode(&mut self, item: &'a [u8], dst: &mut BytesMut) -> Result<(), Self::Error> {
+        dst.extend_from_slice(item);
+        Ok(())
+    }
+}
+
+impl<'a, B, C, C2> codec::Encoder<EncoderHandle<'a, B, C2>> for TokioMessageCodec<C>
+where
+    B: Buffer,
+    C: Configure,
+    C2: Configure,
+{
+    type Error = io::Error;
+
+    fn encode(
+        &mut self,
+        item: EncoderHandle<'a, B, C2>,
+        dst: &mut BytesMut,
+    ) -> Result<(), Self::Error> {
+        encode_handle(item, dst)
+    }
+}
+
+/// Splits the first whole FIX message off `src`, if buffered already.
+fn split_frame<C>(
+    raw_decoder: &RawDecoder<C>,
+    src: &mut BytesMut,
+) -> Result<Option<Bytes>, DecodeError>
+where
+    C: Configure,
+{
+    let message_len = match raw_decoder.message_len(&src[..]) {
+        Ok(Some(len)) => len,
+        Ok(None) => return Ok(None),
+        Err(e) => {
+            // The length of the invalid message is unknown (e.g. after
+            // `DecodeError::TooLong`), so skip to the next plausible start of
+            // message instead, just like `RawDecoderBuffered::resync`.
+            let skip = raw_decoder::resync_len(&src[..]);
+            src.advance(skip);
+            return Err(e);
+        }
+    };
+    if src.len() < message_len {
+        src.reserve(message_len - src.len());
+        return Ok(None);
+    }
+    // Invalid messages are consumed anyway, so that decoding can resume from
+    // the next one.
+    Ok(Some(src.split_to(message_len).freeze()))
+}
+
+fn encode_handle<B, C>(handle: EncoderHandle<B, C>, dst: &mut BytesMut) -> io::Result<()>
+where
+    B: Buffer,
+    C: Configure,
+{
+    let data = handle
+        .finalize()
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+    dst.extend_from_slice(data);
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::definitions::fix44;
+    use crate::tagvalue::{test_decoder, EncodeError, Encoder as FixEncoder, FieldAccess};
+    use tokio_util::codec::{Decoder as _, Encoder as _};
+
+    const MESSAGES: &[&[u8]] = &[
+        b"8=FIX.4.2|9=42|35=0|49=A|56=B|34=12|52=20100304-07:59:30|10=185|",
+        b"8=FIX.4.2|9=97|35=6|49=BKR|56=IM|34=14|52=20100204-09:18:42|23=115685|28=N|55=SPMI.MI|54=2|44=2200.75|27=S|25=H|10=248|",
+    ];
+
+    fn codec() -> TokioCodec {
+        let mut codec = TokioCodec::<Config>::new();
+        codec.config_mut().set_separator(b'|');
+        codec
+    }
+
+    #[test]
+    fn decode_byte_by_byte() {
+        let mut codec = codec();
+        let mut src = BytesMut::new();
+        let mut frames = Vec::new();
+        for byte in MESSAGES.concat() {
+            src.extend_from_slice(&[byte]);
+            if let Some(frame) = codec.decode(&mut src).unwrap() {
+                frames.push(frame);
+            }
+        }
+        assert_eq!(frames.len(), MESSAGES.len());
+        for (frame, msg) in frames.iter().zip(MESSAGES) {
+            assert_eq!(frame.as_bytes(), *msg);
+        }
+        assert!(src.is_empty());
+    }
+
+    #[test]
+    fn invalid_message_is_consumed() {
+        let mut codec = codec();
+        codec.config_mut().set_verify_checksum(true);
+        let mut src = BytesMut::new();
+        src.extend_from_slice(b"8=FIX.4.2|9=42|35=0|49=A|56=B|34=12|52=20100304-07:59:30|10=000|");
+        src.extend_from_slice(b"8=FIX.4.2|9=42|35=0|49=A|56=B|34=13|52=20100304-07:59:30|10=023|");
+        assert!(matches!(
+            codec.decode(&mut src).unwrap_err(),
+            DecodeError::CheckSum { declared: 0, .. }
+        ));
+        let frame = codec.decode(&mut src).unwrap().unwrap();
+        assert_eq!(&frame.payload()[..11], b"35=0|49=A|5");
+        assert!(src.is_empty());
+    }
+
+    #[test]
+    fn decoding_resumes_after_invalid_header() {
+        let mut codec = codec();
+        let mut src = BytesMut::from(&b"8=FIX.4.2|9=abc|35=0|10=000|"[..]);
+        src.extend_from_slice(MESSAGES[0]);
+        src.extend_from_slice(&MESSAGES[1][..20]);
+        assert!(codec.decode(&mut src).is_err());
+        let frame = codec.decode(&mut src).unwrap().unwrap();
+        assert_eq!(frame.as_bytes(), MESSAGES[0]);
+        assert!(codec.decode(&mut src).unwrap().is_none());
+        src.extend_from_slice(&MESSAGES[1][20..]);
+        let frame = codec.decode(&mut src).unwrap().unwrap();
+        assert_eq!(frame.as_bytes(), MESSAGES[1]);
+        assert!(src.is_empty());
+    }
+
+    #[test]
+    fn message_too_long() {
+        let mut codec = codec();
+        codec.config_mut().set_max_message_size(Some(64));
+        let mut src = BytesMut::from(MESSAGES[1]);
+        assert_eq!(
+            codec.decode(&mut src).unwrap_err(),
+            DecodeError::TooLong { len: 119, max: 64 }
+        );
+    }
+
+    #[test]
+    fn encode_writes_message_as_is() {
+        let mut codec = codec();
+        let mut dst = BytesMut::new();
+        codec.encode(MESSAGES[0], &mut dst).unwrap();
+        assert_eq!(&dst[..], MESSAGES[0]);
+    }
+
+    #[test]
+    fn encode_finalizes_encoder_handles() {
+        let mut codec = codec();
+        let mut encoder = FixEncoder::<Config>::default();
+        encoder.config_mut().set_separator(b'|');
+        let mut buffer = Vec::new();
+        let mut msg = encoder.start_message(b"FIX.4.4", &mut buffer, b"0");
+        msg.set(fix44::TEST_REQ_ID, "X");
+        let mut dst = BytesMut::from(MESSAGES[0]);
+        codec.encode(msg, &mut dst).unwrap();
+        assert_eq!(
+            &dst[MESSAGES[0].len()..],
+            b"8=FIX.4.4|9=000011|35=0|112=X|10=166|"
+        );
+    }
+
+    #[test]
+    fn encode_rejects_incomplete_messages() {
+        let mut codec = codec();
+        let mut encoder = FixEncoder::<Config>::default();
+        let mut buffer = Vec::new();
+        let mut msg = encoder.start_message(b"FIX.4.4", &mut buffer, b"W");
+        msg.begin_group(fix44::NO_MD_ENTRIES, fix44::MD_ENTRY_TYPE);
+        let mut dst = BytesMut::new();
+        let err = codec.encode(msg, &mut dst).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        assert!(matches!(
+            err.into_inner().unwrap().downcast_ref::<EncodeError>(),
+            Some(EncodeError::UnterminatedGroup { .. })
+        ));
+        assert!(dst.is_empty());
+    }
+
+    #[test]
+    fn message_codec_decodes_fields() {
+        let decoder = test_decoder();
+        let mut codec = TokioMessageCodec::new(decoder);
+        let mut src = BytesMut::new();
+        let mut messages = Vec::new();
+        for chunk in MESSAGES.concat().chunks(7) {
+            src.extend_from_slice(chunk);
+            while let Some(message) = codec.decode(&mut src).unwrap() {
+                messages.push(message);
+            }
+        }
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].message().fv(fix44::MSG_SEQ_NUM), Ok(12));
+        assert_eq!(messages[1].message().fv(fix44::PRICE), Ok("2200.75"));
+        assert_eq!(messages[1].as_bytes(), MESSAGES[1]);
+        assert_eq!(codec.decoder().stats().messages_decoded(), 2);
+    }
+
+    #[test]
+    fn message_codec_reports_invalid_messages() {
+        let mut decoder = test_decoder();
+        decoder.config_mut().set_verify_checksum(true);
+        let mut codec = TokioMessageCodec::new(decoder);
+        let mut src = BytesMut::new();
+        src.extend_from_slice(b"8=FIX.4.2|9=42|35=0|49=A|56=B|34=12|52=20100304-07:59:30|10=000|");
+        src.extend_from_slice(b"8=FIX.4.2|9=42|35=0|49=A|56=B|34=13|52=20100304-07:59:30|10=023|");
+        assert!(matches!(
+            codec.decode(&mut src).unwrap_err(),
+            DecodeError::CheckSum { declared: 0, .. }
+        ));
+        let message = codec.decode(&mut src).unwrap().unwrap();
+        assert_eq!(message.message().fv(fix44::MSG_SEQ_NUM), Ok(13));
+        assert_eq!(codec.decoder().stats().decode_errors(), 1);
+        assert!(src.is_empty());
+    }
+}
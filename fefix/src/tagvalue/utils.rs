@@ -1,7 +1,7 @@
 use crate::fix_values::CheckSum;
 use crate::tagvalue::DecodeError;
-use crate::FixValue;
-use std::convert::TryInto;
+use crate::{FixValue, TagU16};
+use core::convert::TryInto;
 
 // A tag-value message can't possibly be shorter than this.
 //
@@ -24,20 +24,32 @@ pub fn checksum_digits(message: &[u8]) -> [u8; 3] {
 }
 
 pub fn verify_checksum(headerless_msg: &[u8]) -> Result<(), DecodeError> {
-    let msg_contents = &headerless_msg[..headerless_msg.len() - FIELD_CHECKSUM_LEN_IN_BYTES];
-    let nominal_checksum = CheckSum::deserialize_lossy(&checksum_digits(headerless_msg)[..])
-        .map_err(|_| DecodeError::CheckSum)?;
+    let offset = headerless_msg.len() - FIELD_CHECKSUM_LEN_IN_BYTES;
+    let msg_contents = &headerless_msg[..offset];
+    let nominal_checksum =
+        CheckSum::deserialize(&checksum_digits(headerless_msg)[..]).map_err(|_| {
+            DecodeError::Invalid {
+                offset,
+                tag: TagU16::new(10),
+            }
+        })?;
     let actual_checksum = CheckSum::compute(msg_contents);
     if nominal_checksum == actual_checksum {
         Ok(())
     } else {
-        Err(DecodeError::CheckSum)
+        Err(DecodeError::CheckSum {
+            offset,
+            declared: nominal_checksum.0,
+            actual: actual_checksum.0,
+        })
     }
 }
 
 /// Verifies the `BodyLength(9)` field of the FIX message in `data`.
+/// `body_length_offset` is the byte offset of the `BodyLength(9)` field.
 pub fn verify_body_length(
     data: &[u8],
+    body_length_offset: usize,
     start_of_body: usize,
     nominal_body_length: usize,
 ) -> Result<(), DecodeError> {
@@ -47,7 +59,11 @@ pub fn verify_body_length(
         .wrapping_sub(start_of_body);
     let end_of_body = data.len() - FIELD_CHECKSUM_LEN_IN_BYTES;
     if start_of_body > end_of_body || nominal_body_length != body_length {
-        Err(DecodeError::Invalid)
+        Err(DecodeError::Length {
+            offset: body_length_offset,
+            declared: nominal_body_length,
+            actual: end_of_body.saturating_sub(start_of_body),
+        })
     } else {
         debug_assert!(body_length < data.len());
         Ok(())
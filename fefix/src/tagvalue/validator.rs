@@ -0,0 +1,853 @@
+use super::decoder::group_members;
+use super::encoder::add_top_level_tags;
+use super::{FieldAccess, Message, RepeatingGroup};
+use crate::dict::{self, FixDatatype, LayoutItemKind};
+use crate::utils::{IntMap, IntSet};
+use crate::{Dictionary, FixValue, TagU16};
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+use hashbrown::{HashMap, HashSet};
+
+const BEGIN_STRING_TAG: u16 = 8;
+const MSG_TYPE_TAG: u16 = 35;
+// `BodyLength <9>` and `CheckSum <10>` are verified and consumed during
+// framing, so decoded messages never contain them.
+const FRAMING_TAGS: &[u16] = &[9, 10];
+
+/// A single problem found by a [`Validator`] in a decoded FIX message.
+///
+/// Please note that [`Violation`] is marked with `#[non_exhaustive]`, which
+/// future-proofs the enumeration type in case more variants are added.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Violation {
+    /// The `BeginString <8>` of the message doesn't match the version of any
+    /// of the [`Dictionary`]'s of the [`Validator`]. No other checks are
+    /// performed.
+    UnsupportedBeginString {
+        /// The unsupported `BeginString <8>` value.
+        begin_string: Vec<u8>,
+    },
+    /// The message has no `MsgType <35>` field.
+    MissingMsgType,
+    /// The value of `MsgType <35>` is not defined by the [`Dictionary`].
+    UnknownMsgType {
+        /// The unknown `MsgType <35>` value.
+        msg_type: Vec<u8>,
+    },
+    /// A required field is missing, either at the top level or within a
+    /// repeating group entry.
+    MissingRequiredField {
+        /// The tag of the missing field.
+        tag: TagU16,
+    },
+    /// A field is not defined by the [`Dictionary`].
+    UnknownTag {
+        /// The unknown tag.
+        tag: TagU16,
+    },
+    /// A field has a value which is not among its allowed values.
+    InvalidEnumValue {
+        /// The tag of the offending field.
+        tag: TagU16,
+        /// The offending value.
+        value: Vec<u8>,
+    },
+    /// A field is defined by the [`Dictionary`], but not for this message type.
+    TagNotDefinedForMsgType {
+        /// The tag of the offending field.
+        tag: TagU16,
+    },
+    /// The number of entries of a repeating group differs from the value of
+    /// its `NumInGroup` field.
+    IncorrectNumInGroupCount {
+        /// The tag of the `NumInGroup` field.
+        tag: TagU16,
+        /// The value of the `NumInGroup` field.
+        declared: usize,
+        /// The number of entries actually present.
+        actual: usize,
+    },
+    /// The first entry of a repeating group doesn't start with the delimiter
+    /// field, i.e. the first field of the group as defined by the
+    /// [`Dictionary`].
+    IncorrectGroupDelimiter {
+        /// The tag of the `NumInGroup` field.
+        num_in_group_tag: TagU16,
+        /// The tag of the delimiter field.
+        expected: TagU16,
+        /// The tag that was found instead.
+        tag: TagU16,
+    },
+    /// A field which is only defined within a repeating group appears outside
+    /// of it, e.g. a field of a nested group directly within an entry of its
+    /// parent group.
+    GroupFieldOutOfPlace {
+        /// The tag of the offending field.
+        tag: TagU16,
+    },
+}
+
+impl Violation {
+    /// Returns the tag of the field that `self` refers to. This is
+    /// `BeginString <8>` and `MsgType <35>` for the respective violations,
+    /// and the `NumInGroup` field for [`Violation::IncorrectNumInGroupCount`].
+    pub fn tag(&self) -> TagU16 {
+        match self {
+            Self::UnsupportedBeginString { .. } => TagU16::new(BEGIN_STRING_TAG).unwrap(),
+            Self::MissingMsgType | Self::UnknownMsgType { .. } => {
+                TagU16::new(MSG_TYPE_TAG).unwrap()
+            }
+            Self::MissingRequiredField { tag }
+            | Self::UnknownTag { tag }
+            | Self::InvalidEnumValue { tag, .. }
+            | Self::TagNotDefinedForMsgType { tag }
+            | Self::IncorrectNumInGroupCount { tag, .. }
+            | Self::IncorrectGroupDelimiter { tag, .. }
+            | Self::GroupFieldOutOfPlace { tag } => *tag,
+        }
+    }
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnsupportedBeginString { begin_string } => write!(
+                f,
+                "Unsupported BeginString '{}'",
+                String::from_utf8_lossy(begin_string)
+            ),
+            Self::MissingMsgType => write!(f, "Missing MsgType <35>"),
+            Self::UnknownMsgType { msg_type } => {
+                write!(f, "Unknown MsgType '{}'", String::from_utf8_lossy(msg_type))
+            }
+            Self::MissingRequiredField { tag } => write!(f, "Required tag <{}> is missing", tag),
+            Self::UnknownTag { tag } => write!(f, "Undefined tag <{}>", tag),
+            Self::InvalidEnumValue { tag, value } => write!(
+                f,
+                "Value '{}' is incorrect for tag <{}>",
+                String::from_utf8_lossy(value),
+                tag
+            ),
+            Self::TagNotDefinedForMsgType { tag } => {
+                write!(f, "Tag <{}> is not defined for this message type", tag)
+            }
+            Self::IncorrectNumInGroupCount {
+                tag,
+                declared,
+                actual,
+            } => write!(
+                f,
+                "Incorrect NumInGroup count for repeating group <{}>: declared {}, found {}",
+                tag, declared, actual
+            ),
+            Self::IncorrectGroupDelimiter {
+                num_in_group_tag,
+                expected,
+                tag,
+            } => write!(
+                f,
+                "Repeating group <{}> starts with tag <{}> instead of <{}>",
+                num_in_group_tag, tag, expected
+            ),
+            Self::GroupFieldOutOfPlace { tag } => {
+                write!(f, "Tag <{}> appears outside of its repeating group", tag)
+            }
+        }
+    }
+}
+
+/// The tags of a message type, or of the standard header and trailer.
+#[derive(Debug, Clone, Default)]
+struct Tags {
+    all: IntSet<u16>,
+    // Excluding those within repeating groups.
+    top_level: IntSet<u16>,
+}
+
+impl Tags {
+    fn add<'a>(&mut self, items: impl Iterator<Item = dict::LayoutItem<'a>>) {
+        let items: Vec<dict::LayoutItem> = items.collect();
+        add_tags(&mut self.all, items.iter().cloned());
+        add_top_level_tags(&mut self.top_level, items.into_iter());
+    }
+}
+
+#[derive(Debug, Clone)]
+struct GroupDefinition {
+    // The first field of every entry.
+    delimiter: TagU16,
+    // Nested groups only contribute their `NumInGroup` tag.
+    members: IntSet<u16>,
+}
+
+#[derive(Debug, Clone)]
+struct AllowedValues {
+    values: HashSet<Vec<u8>>,
+    // Multiple value fields are space-separated lists of allowed values.
+    is_multiple_value: bool,
+}
+
+/// Checks decoded FIX messages against a [`Dictionary`].
+///
+/// Decoding with [`Decoder`](super::Decoder) is purely syntactic; a
+/// [`Validator`] reports semantic problems, i.e. missing required fields,
+/// unknown tags, invalid enumeration values, fields which don't belong to
+/// the message type, and malformed repeating groups. Every [`Violation`] can
+/// be turned into a `Reject <3>` with
+/// [`Reject::for_violation`](crate::session::Reject::for_violation).
+///
+/// All checks are driven by the [`Dictionary`] of the message's FIX version,
+/// as identified by `BeginString <8>`: fields and required-ness differ
+/// significantly across versions (e.g. FIX 4.0 and 4.1 still use `ClientID
+/// <109>`, which later versions deprecate), so messages are never validated
+/// against a different version. A [`Validator`] can hold one [`Dictionary`]
+/// per version, see [`Validator::add_dictionary`]. `FIXT.1.1` messages are
+/// validated against the [`Dictionary`] given to [`Validator::new`], which
+/// should thus be the application-layer [`Dictionary`] (see
+/// [`Message::dictionary`]).
+///
+/// # Examples
+///
+/// ```
+/// use fefix::tagvalue::{Config, Configure, Decoder, Validator};
+/// use fefix::Dictionary;
+///
+/// let dict = Dictionary::fix44();
+/// let mut decoder = Decoder::<Config>::new(dict.clone());
+/// decoder.config_mut().set_separator(b'|');
+/// let msg = decoder
+///     .decode(b"8=FIX.4.4|9=42|35=0|49=A|56=B|34=12|52=20100304-07:59:30|10=000|")
+///     .unwrap();
+/// let validator = Validator::new(dict);
+/// assert!(validator.validate(&msg).is_empty());
+/// ```
+#[derive(Debug, Clone)]
+pub struct Validator {
+    tables: VersionTables,
+    // Other FIX versions.
+    other_tables: Vec<VersionTables>,
+}
+
+/// The lookup tables that a [`Validator`] derives from the [`Dictionary`] of
+/// a FIX version.
+#[derive(Debug, Clone)]
+struct VersionTables {
+    dict: Dictionary,
+    header_and_trailer_tags: Tags,
+    tags_by_msg_type: HashMap<String, Tags>,
+    groups: IntMap<u16, GroupDefinition>,
+    allowed_values: IntMap<u16, AllowedValues>,
+}
+
+impl Validator {
+    /// Creates a new [`Validator`] which checks messages against `dict`.
+    pub fn new(dict: Dictionary) -> Self {
+        Self {
+            tables: VersionTables::new(dict),
+            other_tables: Vec::new(),
+        }
+    }
+
+    /// Returns an immutable reference to the [`Dictionary`] used by `self`,
+    /// i.e. the one given to [`Validator::new`].
+    pub fn dict(&self) -> &Dictionary {
+        &self.tables.dict
+    }
+
+    /// Checks messages with `BeginString <8>` equal to the version of `dict`
+    /// (see [`Dictionary::get_version`]) against `dict`. It replaces any
+    /// previous [`Dictionary`] for the same version, except the one given to
+    /// [`Validator::new`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix::tagvalue::{Config, Configure, Decoder, Validator, Violation};
+    /// use fefix::Dictionary;
+    ///
+    /// let mut fix43 = Dictionary::fix44().to_builder();
+    /// fix43.set_version("FIX.4.3");
+    /// let mut decoder = Decoder::<Config>::new(Dictionary::fix44());
+    /// decoder.config_mut().set_separator(b'|');
+    /// let msg = decoder
+    ///     .decode(b"8=FIX.4.3|9=42|35=0|49=A|56=B|34=12|52=20100304-07:59:30|10=000|")
+    ///     .unwrap();
+    /// let mut validator = Validator::new(Dictionary::fix44());
+    /// assert_eq!(
+    ///     validator.validate(&msg),
+    ///     vec![Violation::UnsupportedBeginString { begin_string: b"FIX.4.3".to_vec() }]
+    /// );
+    /// validator.add_dictionary(fix43.build());
+    /// assert!(validator.validate(&msg).is_empty());
+    /// ```
+    pub fn add_dictionary(&mut self, dict: Dictionary) {
+        if dict.get_version() == self.tables.dict.get_version() {
+            return;
+        }
+        let tables = VersionTables::new(dict);
+        match self
+            .other_tables
+            .iter_mut()
+            .find(|other| other.dict.get_version() == tables.dict.get_version())
+        {
+            Some(other) => *other = tables,
+            None => self.other_tables.push(tables),
+        }
+    }
+
+    /// Returns the [`Dictionary`] that messages with `begin_string` are
+    /// validated against, if any.
+    pub fn dict_for(&self, begin_string: &[u8]) -> Option<&Dictionary> {
+        self.tables_for(begin_string).map(|tables| &tables.dict)
+    }
+
+    fn tables_for(&self, begin_string: &[u8]) -> Option<&VersionTables> {
+        if begin_string.starts_with(b"FIXT.") {
+            return Some(&self.tables);
+        }
+        core::iter::once(&self.tables)
+            .chain(self.other_tables.iter())
+            .find(|tables| tables.dict.get_version().as_bytes() == begin_string)
+    }
+
+    /// Checks `msg` and returns all [`Violation`]s found, in no particular
+    /// order. An empty [`Vec`] means that `msg` is valid.
+    pub fn validate<T>(&self, msg: &Message<T>) -> Vec<Violation>
+    where
+        T: AsRef<[u8]> + Clone,
+    {
+        let begin_string = msg
+            .fields()
+            .find(|(tag, _)| tag.get() == BEGIN_STRING_TAG)
+            .map(|(_, value)| value)
+            .unwrap_or_default();
+        match self.tables_for(begin_string) {
+            Some(tables) => tables.validate(msg),
+            None => vec![Violation::UnsupportedBeginString {
+                begin_string: begin_string.to_vec(),
+            }],
+        }
+    }
+}
+
+impl VersionTables {
+    fn new(dict: Dictionary) -> Self {
+        let mut header_and_trailer_tags = Tags::default();
+        for name in &["StandardHeader", "StandardTrailer"] {
+            if let Some(component) = dict.component_by_name(name) {
+                header_and_trailer_tags.add(component.items());
+            }
+        }
+        let mut tags_by_msg_type = HashMap::new();
+        for message in dict.iter_messages() {
+            if message.scenario() != "base" {
+                continue;
+            }
+            let mut tags = Tags::default();
+            tags.add(message.layout());
+            tags_by_msg_type.insert(message.msg_type().to_string(), tags);
+        }
+        let mut delimiters = IntMap::default();
+        for message in dict.iter_messages() {
+            add_group_delimiters(&mut delimiters, message.layout());
+        }
+        for component in dict.iter_components() {
+            add_group_delimiters(&mut delimiters, component.items());
+        }
+        let groups = group_members(&dict)
+            .into_iter()
+            .filter_map(|(tag, members)| {
+                let delimiter = *delimiters.get(&tag)?;
+                Some((tag, GroupDefinition { delimiter, members }))
+            })
+            .collect();
+        let allowed_values = dict
+            .iter_fields()
+            .filter_map(|field| {
+                let values = field
+                    .enums()?
+                    .map(|e| e.value().as_bytes().to_vec())
+                    .collect();
+                let is_multiple_value = matches!(
+                    field.data_type().basetype(),
+                    FixDatatype::MultipleCharValue | FixDatatype::MultipleStringValue
+                );
+                Some((
+                    field.tag().get(),
+                    AllowedValues {
+                        values,
+                        is_multiple_value,
+                    },
+                ))
+            })
+            .collect();
+        Self {
+            dict,
+            header_and_trailer_tags,
+            tags_by_msg_type,
+            groups,
+            allowed_values,
+        }
+    }
+
+    fn validate<T>(&self, msg: &Message<T>) -> Vec<Violation>
+    where
+        T: AsRef<[u8]> + Clone,
+    {
+        let mut violations = Vec::new();
+        let msg_type = msg
+            .fields()
+            .find(|(tag, _)| tag.get() == MSG_TYPE_TAG)
+            .map(|(_, value)| value);
+        let message_tags = match msg_type {
+            None => {
+                violations.push(Violation::MissingMsgType);
+                None
+            }
+            Some(msg_type) => {
+                let tags = core::str::from_utf8(msg_type)
+                    .ok()
+                    .and_then(|msg_type| self.tags_by_msg_type.get(msg_type));
+                if tags.is_none() {
+                    violations.push(Violation::UnknownMsgType {
+                        msg_type: msg_type.to_vec(),
+                    });
+                }
+                tags
+            }
+        };
+        for (tag, value) in msg.fields() {
+            if self.dict.field_by_tag(tag.get() as u32).is_none() {
+                violations.push(Violation::UnknownTag { tag });
+                continue;
+            }
+            if let Some(tags) = message_tags {
+                if !tags.all.contains(&tag.get())
+                    && !self.header_and_trailer_tags.all.contains(&tag.get())
+                {
+                    violations.push(Violation::TagNotDefinedForMsgType { tag });
+                }
+            }
+            // An unknown `MsgType <35>` has already been reported above.
+            if tag.get() == MSG_TYPE_TAG {
+                continue;
+            }
+            if let Some(allowed) = self.allowed_values.get(&tag.get()) {
+                let is_valid = if allowed.is_multiple_value {
+                    value
+                        .split(|byte| *byte == b' ')
+                        .all(|value| allowed.values.contains(value))
+                } else {
+                    allowed.values.contains(value)
+                };
+                if !is_valid {
+                    violations.push(Violation::InvalidEnumValue {
+                        tag,
+                        value: value.to_vec(),
+                    });
+                }
+            }
+        }
+        for name in &["StandardHeader", "StandardTrailer"] {
+            if let Some(component) = self.dict.component_by_name(name) {
+                check_required_fields(msg, component.items(), &mut violations);
+            }
+        }
+        let message = msg_type
+            .and_then(|msg_type| core::str::from_utf8(msg_type).ok())
+            .and_then(|msg_type| self.dict.message_by_msgtype(msg_type));
+        if let Some(message) = message {
+            check_required_fields(msg, message.layout(), &mut violations);
+        }
+        self.check_groups(msg, message_tags, &mut violations);
+        violations
+    }
+
+    /// Walks the fields of `msg` in wire order and checks that every
+    /// repeating group has as many entries as declared, that its first entry
+    /// starts with the delimiter field, and that group fields don't appear
+    /// outside of their (parent) group.
+    fn check_groups<T>(
+        &self,
+        msg: &Message<T>,
+        message_tags: Option<&Tags>,
+        violations: &mut Vec<Violation>,
+    ) where
+        T: AsRef<[u8]> + Clone,
+    {
+        let fields: Vec<(TagU16, &[u8])> = msg.fields().collect();
+        let mut i = 0;
+        while let Some((tag, value)) = fields.get(i).copied() {
+            i += 1;
+            if let Some(tags) = message_tags {
+                let header_and_trailer = &self.header_and_trailer_tags;
+                // Fields which are not defined for the message type at all
+                // have already been reported.
+                let is_out_of_place = !tags.top_level.contains(&tag.get())
+                    && !header_and_trailer.top_level.contains(&tag.get())
+                    && (tags.all.contains(&tag.get())
+                        || header_and_trailer.all.contains(&tag.get()));
+                if is_out_of_place {
+                    violations.push(Violation::GroupFieldOutOfPlace { tag });
+                }
+            }
+            if let Some(group) = self.groups.get(&tag.get()) {
+                self.check_group(tag, value, group, &fields[..], &mut i, violations);
+            }
+        }
+    }
+
+    /// Consumes the entries of the repeating group with `NumInGroup` field
+    /// `tag` from `fields`, starting at `i`. Entries exceeding the count are
+    /// consumed too, as long as they start with the delimiter field.
+    fn check_group(
+        &self,
+        tag: TagU16,
+        value: &[u8],
+        group: &GroupDefinition,
+        fields: &[(TagU16, &[u8])],
+        i: &mut usize,
+        violations: &mut Vec<Violation>,
+    ) {
+        // Invalid counts are reported by `check_required_fields`.
+        let declared = match usize::deserialize(value) {
+            Ok(declared) => declared,
+            Err(_) => return,
+        };
+        let mut actual = 0;
+        // Whether the current entry has its delimiter field already; only the
+        // first entry can lack it, otherwise it wouldn't be a new entry.
+        let mut has_delimiter = false;
+        while let Some((member, value)) = fields.get(*i).copied() {
+            if !group.members.contains(&member.get()) {
+                break;
+            }
+            if member == group.delimiter {
+                if has_delimiter || actual == 0 {
+                    actual += 1;
+                }
+                has_delimiter = true;
+            } else if actual == 0 {
+                violations.push(Violation::IncorrectGroupDelimiter {
+                    num_in_group_tag: tag,
+                    expected: group.delimiter,
+                    tag: member,
+                });
+                actual = 1;
+            }
+            *i += 1;
+            if let Some(nested) = self.groups.get(&member.get()) {
+                self.check_group(member, value, nested, fields, i, violations);
+            }
+        }
+        if actual != declared {
+            violations.push(Violation::IncorrectNumInGroupCount {
+                tag,
+                declared,
+                actual,
+            });
+        }
+    }
+}
+
+pub(crate) fn add_tags<'a>(
+    tags: &mut IntSet<u16>,
+    items: impl Iterator<Item = dict::LayoutItem<'a>>,
+) {
+    for item in items {
+        match item.kind() {
+            LayoutItemKind::Field(field) => {
+                tags.insert(field.tag().get());
+            }
+            LayoutItemKind::Component(component) => {
+                add_tags(tags, component.items());
+            }
+            LayoutItemKind::Group(field, items) => {
+                tags.insert(field.tag().get());
+                add_tags(tags, items.into_iter());
+            }
+        }
+    }
+}
+
+// Collects the first field (i.e. the delimiter) of every repeating group in
+// `items`, indexed by `NumInGroup` tag.
+fn add_group_delimiters<'a>(
+    delimiters: &mut IntMap<u16, TagU16>,
+    items: impl Iterator<Item = dict::LayoutItem<'a>>,
+) {
+    for item in items {
+        match item.kind() {
+            LayoutItemKind::Field(_) => {}
+            LayoutItemKind::Component(component) => {
+                add_group_delimiters(delimiters, component.items());
+            }
+            LayoutItemKind::Group(field, items) => {
+                if let Some(delimiter) = first_tag(items.iter().cloned()) {
+                    delimiters.entry(field.tag().get()).or_insert(delimiter);
+                }
+                add_group_delimiters(delimiters, items.into_iter());
+            }
+        }
+    }
+}
+
+fn first_tag<'a>(mut items: impl Iterator<Item = dict::LayoutItem<'a>>) -> Option<TagU16> {
+    items.find_map(|item| match item.kind() {
+        LayoutItemKind::Field(field) | LayoutItemKind::Group(field, _) => Some(field.tag()),
+        LayoutItemKind::Component(component) => first_tag(component.items()),
+    })
+}
+
+fn check_required_fields<'a, A>(
+    access: &A,
+    items: impl Iterator<Item = dict::LayoutItem<'a>>,
+    violations: &mut Vec<Violation>,
+) where
+    A: FieldAccess,
+{
+    for item in items {
+        match item.kind() {
+            LayoutItemKind::Field(field) => {
+                if item.required()
+                    && !FRAMING_TAGS.contains(&field.tag().get())
+                    && access.fv_raw(&field).is_none()
+                {
+                    violations.push(Violation::MissingRequiredField { tag: field.tag() });
+                }
+            }
+            LayoutItemKind::Component(component) => {
+                // Fields of optional components are only required if the
+                // component is present, which is impossible to tell in general.
+                if item.required() {
+                    check_required_fields(access, component.items(), violations);
+                }
+            }
+            LayoutItemKind::Group(field, items) => match access.group_opt(&field) {
+                Some(Ok(group)) => {
+                    for entry in group.entries() {
+                        check_required_fields(&entry, items.iter().cloned(), violations);
+                    }
+                }
+                Some(Err(_)) => {
+                    violations.push(Violation::InvalidEnumValue {
+                        tag: field.tag(),
+                        value: access.fv_raw(&field).unwrap_or_default().to_vec(),
+                    });
+                }
+                None if item.required() => {
+                    violations.push(Violation::MissingRequiredField { tag: field.tag() });
+                }
+                None => {}
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tagvalue::{Config, Decoder};
+
+    /// Validates a FIX 4.4 message with the given body, i.e. everything
+    /// between `BodyLength <9>` and `CheckSum <10>`.
+    fn validate(body: &str) -> Vec<Violation> {
+        let msg = format!("8=FIX.4.4|9={}|{}10=000|", body.len(), body);
+        let dict = Dictionary::fix44();
+        let mut decoder = Decoder::<Config>::new(dict.clone());
+        decoder.config_mut().set_separator(b'|');
+        let msg = decoder.decode(msg.as_bytes()).unwrap();
+        Validator::new(dict).validate(&msg)
+    }
+
+    #[test]
+    fn valid_heartbeat() {
+        let violations = validate("35=0|49=A|56=B|34=12|52=20100304-07:59:30|");
+        assert_eq!(violations, vec![]);
+    }
+
+    #[test]
+    fn missing_required_field() {
+        let violations = validate("35=0|49=A|56=B|52=20100304-07:59:30|");
+        assert_eq!(
+            violations,
+            vec![Violation::MissingRequiredField {
+                tag: TagU16::new(34).unwrap()
+            }]
+        );
+    }
+
+    #[test]
+    fn unknown_tag_and_wrong_msg_type() {
+        let violations = validate("35=0|49=A|56=B|34=12|52=20100304-07:59:30|9999=X|55=AAPL|");
+        assert_eq!(
+            violations,
+            vec![
+                Violation::UnknownTag {
+                    tag: TagU16::new(9999).unwrap()
+                },
+                Violation::TagNotDefinedForMsgType {
+                    tag: TagU16::new(55).unwrap()
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_enum_value() {
+        let violations = validate("35=0|49=A|56=B|34=12|52=20100304-07:59:30|43=X|");
+        assert_eq!(
+            violations,
+            vec![Violation::InvalidEnumValue {
+                tag: TagU16::new(43).unwrap(),
+                value: b"X".to_vec()
+            }]
+        );
+    }
+
+    #[test]
+    fn unknown_msg_type() {
+        let violations = validate("35=ZZ|49=A|56=B|34=12|52=20100304-07:59:30|");
+        assert_eq!(
+            violations,
+            vec![Violation::UnknownMsgType {
+                msg_type: b"ZZ".to_vec()
+            }]
+        );
+    }
+
+    #[test]
+    fn missing_required_group() {
+        let violations =
+            validate("35=V|49=A|56=B|34=12|52=20100304-07:59:30|262=1|263=0|264=0|146=1|55=X|");
+        assert_eq!(
+            violations,
+            vec![Violation::MissingRequiredField {
+                tag: TagU16::new(267).unwrap()
+            }]
+        );
+    }
+
+    const MARKET_DATA_REQUEST: &str =
+        "35=V|49=A|56=B|34=12|52=20100304-07:59:30|262=1|263=0|264=0|267=1|269=0|";
+
+    fn tag(tag: u16) -> TagU16 {
+        TagU16::new(tag).unwrap()
+    }
+
+    #[test]
+    fn valid_nested_groups() {
+        let violations = validate(&format!(
+            "{}146=2|55=X|454=2|455=A|456=4|455=B|456=8|55=Y|",
+            MARKET_DATA_REQUEST
+        ));
+        assert_eq!(violations, vec![]);
+    }
+
+    #[test]
+    fn num_in_group_count_mismatch() {
+        let violations = validate(&format!("{}146=1|55=X|55=Y|", MARKET_DATA_REQUEST));
+        assert_eq!(
+            violations,
+            vec![Violation::IncorrectNumInGroupCount {
+                tag: tag(146),
+                declared: 1,
+                actual: 2
+            }]
+        );
+        let violations = validate(&format!("{}146=3|55=X|55=Y|", MARKET_DATA_REQUEST));
+        assert!(violations.contains(&Violation::IncorrectNumInGroupCount {
+            tag: tag(146),
+            declared: 3,
+            actual: 2
+        }));
+    }
+
+    #[test]
+    fn group_entry_without_delimiter() {
+        let violations = validate(&format!("{}146=1|48=ID|55=X|", MARKET_DATA_REQUEST));
+        assert_eq!(
+            violations,
+            vec![Violation::IncorrectGroupDelimiter {
+                num_in_group_tag: tag(146),
+                expected: tag(55),
+                tag: tag(48)
+            }]
+        );
+    }
+
+    #[test]
+    fn nested_group_field_outside_of_its_parent() {
+        let violations = validate(&format!("{}146=1|55=X|455=A|", MARKET_DATA_REQUEST));
+        assert_eq!(
+            violations,
+            vec![Violation::GroupFieldOutOfPlace { tag: tag(455) }]
+        );
+        assert_eq!(violations[0].tag(), tag(455));
+    }
+
+    fn validate_with(validator: &Validator, begin_string: &str, body: &str) -> Vec<Violation> {
+        let msg = format!("8={}|9={}|{}10=000|", begin_string, body.len(), body);
+        let mut decoder = Decoder::<Config>::new(
+            validator
+                .dict_for(begin_string.as_bytes())
+                .cloned()
+                .unwrap_or_else(Dictionary::fix44),
+        );
+        decoder.config_mut().set_separator(b'|');
+        let msg = decoder.decode(msg.as_bytes()).unwrap();
+        validator.validate(&msg)
+    }
+
+    #[test]
+    fn begin_string_selects_the_dictionary() {
+        let validator = Validator::new(Dictionary::fix44());
+        let heartbeat = "35=0|49=A|56=B|34=12|52=20100304-07:59:30|";
+        assert_eq!(
+            validate_with(&validator, "FIX.4.2", heartbeat),
+            vec![Violation::UnsupportedBeginString {
+                begin_string: b"FIX.4.2".to_vec()
+            }]
+        );
+        assert_eq!(validate_with(&validator, "FIXT.1.1", heartbeat), vec![]);
+        assert_eq!(
+            validator.dict_for(b"FIX.4.4").unwrap().get_version(),
+            "FIX.4.4"
+        );
+        assert!(validator.dict_for(b"FIX.4.2").is_none());
+    }
+
+    #[test]
+    #[cfg(feature = "fix40")]
+    fn fix40_required_fields_differ() {
+        let mut validator = Validator::new(Dictionary::fix44());
+        validator.add_dictionary(Dictionary::fix40());
+        // FIX 4.0 execution reports have `ExecTransType <20>`, `LastShares
+        // <32>` and `LastPx <31>`, but no `ExecType <150>` nor `LeavesQty
+        // <151>`.
+        let execution_report = "35=8|49=A|56=B|34=12|52=20100304-07:59:30|37=1|17=1|20=0|39=0|55=X|54=1|38=100|32=0|31=0|14=0|6=0|";
+        assert_eq!(
+            validate_with(&validator, "FIX.4.0", execution_report),
+            vec![]
+        );
+        let violations = validate_with(&validator, "FIX.4.4", execution_report);
+        assert!(violations.contains(&Violation::MissingRequiredField {
+            tag: TagU16::new(150).unwrap()
+        }));
+    }
+
+    #[test]
+    #[cfg(feature = "fix41")]
+    fn fix41_client_id() {
+        let mut validator = Validator::new(Dictionary::fix44());
+        validator.add_dictionary(Dictionary::fix41());
+        let new_order_single =
+            "35=D|49=A|56=B|34=12|52=20100304-07:59:30|11=1|109=CL|21=1|55=X|54=1|38=100|40=1|";
+        assert_eq!(
+            validate_with(&validator, "FIX.4.1", new_order_single),
+            vec![]
+        );
+    }
+}
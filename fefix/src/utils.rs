@@ -10,3 +10,26 @@ macro_rules! dbglog {
         }
     }}
 }
+
+/// A [`hashbrown::HashMap`] keyed by FIX tags or other integers, which are
+/// their own hash. Unlike [`nohash_hasher::IntMap`], it's available without
+/// `std`.
+pub(crate) type IntMap<K, V> = hashbrown::HashMap<K, V, nohash_hasher::BuildNoHashHasher<K>>;
+
+/// The [`hashbrown::HashSet`] counterpart of [`IntMap`].
+pub(crate) type IntSet<T> = hashbrown::HashSet<T, nohash_hasher::BuildNoHashHasher<T>>;
+
+/// Emits a [`tracing`](https://docs.rs/tracing) event at the given level, e.g.
+/// `trace_event!(debug, skipped, "Skipped garbled data")`, if and only if the
+/// `utils-tracing` feature is enabled. Arguments are not evaluated otherwise,
+/// so they must be free of side effects.
+///
+/// Events must never include raw message contents, which may carry
+/// credentials: `MsgType <35>` and `MsgSeqNum <34>` are fine.
+#[allow(unused_macros)]
+macro_rules! trace_event {
+    ($level:ident, $($arg:tt)+) => {
+        #[cfg(feature = "utils-tracing")]
+        ::tracing::$level!($($arg)+);
+    };
+}
@@ -0,0 +1,12 @@
+//! Fixtures shared by integration tests, which can't see the crate's
+//! `#[cfg(test)]` helpers.
+
+use fefix::tagvalue::{Config, Decoder};
+use fefix::Dictionary;
+
+/// A FIX 4.4 [`Decoder`] that uses `|` as separator.
+pub fn decoder() -> Decoder<Config> {
+    let mut decoder = Decoder::<Config>::new(Dictionary::fix44());
+    decoder.config_mut().set_separator(b'|');
+    decoder
+}
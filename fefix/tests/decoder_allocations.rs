@@ -0,0 +1,79 @@
+//! Checks that `fefix::tagvalue::Decoder` doesn't allocate in steady state.
+//! This lives in its own test binary because it installs a global allocator.
+
+mod common;
+
+use common::decoder;
+use fefix::tagvalue::FieldAccess;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = Cell::new(0);
+}
+
+/// Counts allocations per thread, so that tests can run in parallel.
+struct CountingAllocator;
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|n| n.set(n.get() + 1));
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.with(|n| n.set(n.get() + 1));
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn allocations_during<F>(f: F) -> usize
+where
+    F: FnOnce(),
+{
+    let before = ALLOCATIONS.with(|n| n.get());
+    f();
+    ALLOCATIONS.with(|n| n.get()) - before
+}
+
+const NEW_ORDER_SINGLE: &[u8] = b"8=FIX.4.4|9=122|35=D|34=215|49=CLIENT12|52=20100225-19:41:57.316|56=B|1=Marcel|11=13346|21=1|40=2|44=5|54=1|59=0|60=20100225-19:39:52.020|10=072|";
+const QUOTE_REQUEST: &[u8] =
+    b"8=FIX.4.4|9=56|35=R|131=Q1|146=2|55=EUR/USD|38=1000|55=GBP/USD|38=2000|10=000|";
+const HEARTBEAT: &[u8] = b"8=FIX.4.4|9=5|35=0|10=000|";
+
+#[test]
+fn steady_state_decoding_doesnt_allocate() {
+    let mut decoder = decoder();
+    // Warm-up.
+    for message in [NEW_ORDER_SINGLE, QUOTE_REQUEST, HEARTBEAT] {
+        decoder.decode(message).unwrap();
+    }
+    let allocations = allocations_during(|| {
+        for _ in 0..100 {
+            for message in [HEARTBEAT, QUOTE_REQUEST, NEW_ORDER_SINGLE] {
+                let message = decoder.decode(message).unwrap();
+                assert!(message
+                    .fv_raw(fefix::definitions::fix44::MSG_TYPE)
+                    .is_some());
+            }
+        }
+    });
+    assert_eq!(allocations, 0);
+}
+
+#[test]
+fn reserved_decoders_dont_need_warm_up() {
+    let mut decoder = decoder();
+    decoder.reserve_fields(64);
+    let allocations = allocations_during(|| {
+        decoder.decode(NEW_ORDER_SINGLE).unwrap();
+    });
+    assert_eq!(allocations, 0);
+}
@@ -0,0 +1,105 @@
+use darling::{FromDeriveInput, FromField};
+use proc_macro::TokenStream;
+use proc_macro2::{Ident, Span};
+use quote::quote;
+
+pub fn derive_fix_message(input: TokenStream) -> TokenStream {
+    let ast: syn::DeriveInput = syn::parse(input).unwrap();
+    let darling_context = StructWithTaggedFields::from_derive_input(&ast).unwrap();
+    let identifier = darling_context.ident;
+    let fields = darling_context
+        .data
+        .take_struct()
+        .expect("FixMessage can only be derived for structs with named fields")
+        .fields;
+    let fefix_crate_info = proc_macro_crate::crate_name("fefix").expect("Cargo.toml fefix issues");
+    // `fefix` itself declares `extern crate self as fefix`, which also makes
+    // this work within `fefix`'s own unit tests and doctests.
+    let fefix_crate_name = match fefix_crate_info {
+        proc_macro_crate::FoundCrate::Itself => Ident::new("fefix", Span::call_site()),
+        proc_macro_crate::FoundCrate::Name(s) => Ident::new(s.as_str(), Span::call_site()),
+    };
+    let private = quote! { #fefix_crate_name::tagvalue::__private };
+    let decode_fields = fields.iter().map(|field| {
+        let field_ident = field.ident.as_ref().expect("Unnamed field");
+        let tag = field.tag;
+        if is_option(&field.ty) {
+            quote! { #field_ident: #private::decode_optional_field(message, #tag)? }
+        } else {
+            quote! { #field_ident: #private::decode_field(message, #tag)? }
+        }
+    });
+    let encode_fields = fields.iter().map(|field| {
+        let field_ident = field.ident.as_ref().expect("Unnamed field");
+        let tag = field.tag;
+        if is_option(&field.ty) {
+            quote! { #private::encode_optional_field(msg, #tag, &self.#field_ident); }
+        } else {
+            quote! { #private::encode_field(msg, #tag, &self.#field_ident); }
+        }
+    });
+    // Structs which borrow data (e.g. `&'a str` fields) tie their own lifetime
+    // to that of the message; all others work with any message lifetime.
+    let (_, ty_generics, where_clause) = darling_context.generics.split_for_impl();
+    let mut impl_generics = darling_context.generics.clone();
+    let lifetime = match darling_context.generics.lifetimes().next() {
+        Some(lifetime_def) => lifetime_def.lifetime.clone(),
+        None => {
+            let lifetime = syn::Lifetime::new("'fefix", Span::call_site());
+            impl_generics
+                .params
+                .insert(0, syn::LifetimeDef::new(lifetime.clone()).into());
+            lifetime
+        }
+    };
+    let (impl_generics, _, _) = impl_generics.split_for_impl();
+    let gen = quote! {
+        impl #impl_generics #fefix_crate_name::tagvalue::FixMessage<#lifetime> for #identifier #ty_generics #where_clause {
+            fn decode_from<T>(
+                message: &#fefix_crate_name::tagvalue::Message<#lifetime, T>,
+            ) -> ::core::result::Result<Self, #fefix_crate_name::tagvalue::FixMessageError>
+            where
+                T: ::core::convert::AsRef<[u8]>,
+            {
+                ::core::result::Result::Ok(Self {
+                    #(#decode_fields),*
+                })
+            }
+
+            fn encode_into<B, C>(&self, msg: &mut #fefix_crate_name::tagvalue::EncoderHandle<B, C>)
+            where
+                B: #fefix_crate_name::Buffer,
+                C: #fefix_crate_name::tagvalue::Configure,
+            {
+                #(#encode_fields)*
+            }
+        }
+    };
+    gen.into()
+}
+
+fn is_option(ty: &syn::Type) -> bool {
+    match ty {
+        syn::Type::Path(type_path) if type_path.qself.is_none() => matches!(
+            type_path.path.segments.last(),
+            Some(segment) if segment.ident == "Option"
+        ),
+        _ => false,
+    }
+}
+
+#[derive(Debug, Clone, FromField)]
+#[darling(attributes(fefix))]
+struct TaggedField {
+    ident: Option<syn::Ident>,
+    ty: syn::Type,
+    tag: u16,
+}
+
+#[derive(Debug, Clone, FromDeriveInput)]
+#[darling(attributes(fefix), supports(struct_named))]
+struct StructWithTaggedFields {
+    ident: syn::Ident,
+    generics: syn::Generics,
+    data: darling::ast::Data<darling::util::Ignored, TaggedField>,
+}
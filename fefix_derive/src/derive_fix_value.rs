@@ -56,10 +56,10 @@ pub fn derive_fix_value(input: TokenStream) -> TokenStream {
                 }
             }
 
-            fn deserialize(data: &'a [u8]) -> ::std::result::Result<Self, <Self as FixValue<'a>>::Error> {
+            fn deserialize(data: &'a [u8]) -> ::core::result::Result<Self, <Self as FixValue<'a>>::Error> {
                 match data {
                     #(#deserialize_matching_cases),*,
-                    _ => ::std::result::Result::Err(())
+                    _ => ::core::result::Result::Err(())
                 }
             }
         }
@@ -2,6 +2,7 @@
 
 #![deny(missing_debug_implementations, clippy::useless_conversion)]
 
+mod derive_fix_message;
 mod derive_fix_value;
 
 use proc_macro::TokenStream;
@@ -11,3 +12,10 @@ use proc_macro::TokenStream;
 pub fn derive_fix_value(input: TokenStream) -> TokenStream {
     derive_fix_value::derive_fix_value(input)
 }
+
+/// A *derive macro* for the `FixMessage` trait on `struct`'s with named fields.
+/// Each field must be annotated with its FIX tag, e.g. `#[fefix(tag = 11)]`.
+#[proc_macro_derive(FixMessage, attributes(fefix))]
+pub fn derive_fix_message(input: TokenStream) -> TokenStream {
+    derive_fix_message::derive_fix_message(input)
+}
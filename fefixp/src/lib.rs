@@ -1,16 +1,45 @@
 //! *FIX Performance Session Layer*
 //! ([FIXP](https://www.fixtrading.org/standards/fixp-online/)) support.
+//!
+//! FIXP doesn't mandate any particular message encoding, so this crate works
+//! with typed session messages (see [`Message`]) and leaves their
+//! serialization (e.g. with SBE over SOFH framing) to the caller. The session
+//! layer itself is implemented by [`Session`], a sans-IO state machine.
 
-type SessionId = u128;
+use std::time::Duration;
 
+mod session;
+
+pub use session::{Delivery, Session, SessionBuilder, SessionEvent, SessionState};
+
+/// A FIXP session identifier, typically a UUID.
+pub type SessionId = u128;
+
+/// The delivery guarantees of the messages sent by either side of a FIXP
+/// session.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum FlowType {
+    /// Sequenced messages with guaranteed delivery: gaps are filled with
+    /// `RetransmitRequest` and `Retransmission`.
     Recoverable,
+    /// Sequenced messages that are delivered at most once: gaps are reported
+    /// to the sender with `NotApplied`, and duplicates are discarded.
     Idempotent,
+    /// Application messages without sequence numbers.
     Unsequenced,
+    /// No application messages at all.
     None,
 }
 
+impl FlowType {
+    /// Returns `true` if and only if application messages of `self` carry
+    /// (implicit) sequence numbers, i.e. [`FlowType::Recoverable`] and
+    /// [`FlowType::Idempotent`].
+    pub fn is_sequenced(&self) -> bool {
+        matches!(self, FlowType::Recoverable | FlowType::Idempotent)
+    }
+}
+
 #[derive(Debug, Clone)]
 #[non_exhaustive]
 pub enum MessageType {
@@ -20,9 +49,35 @@ pub enum MessageType {
     Negotiate,
 }
 
-#[derive(Debug, Clone)]
+/// A FIXP session message.
+///
+/// Please note that [`Message`] is marked with `#[non_exhaustive]`, which
+/// future-proofs the enumeration type in case more variants are added.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Message {
+    Negotiate(Negotiate),
+    NegotiationResponse(NegotiationResponse),
+    NegotiationReject(NegotiationReject),
+    Establish(Establish),
+    EstablishmentAck(EstablishmentAck),
+    EstablishmentReject(EstablishmentReject),
+    Sequence(Sequence),
+    RetransmitRequest(RetransmitRequest),
+    Retransmission(Retransmission),
+    RetransmitReject(RetransmitReject),
+    NotApplied(NotApplied),
+    /// Keeps a session alive when the flow of its sender is not sequenced, in
+    /// place of [`Message::Sequence`].
+    UnsequencedHeartbeat,
+    Terminate(Terminate),
+}
+
+/// Announces the sequence number of the next application message of a
+/// [`FlowType::Recoverable`] or [`FlowType::Idempotent`] flow.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Sequence {
-    next_seq_number: u64,
+    pub next_seq_number: u64,
 }
 
 #[derive(Debug, Clone)]
@@ -39,27 +94,161 @@ pub struct MessageTemplate {
     template: Vec<u8>,
 }
 
-#[derive(Debug, Clone)]
+/// Sent by the client to initiate a session.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Negotiate {
-    session_id: SessionId,
-    client_flow: FlowType,
-    credentials: Option<Vec<u8>>,
+    pub session_id: SessionId,
+    /// Nanoseconds since the UNIX epoch.
+    pub timestamp: u64,
+    pub client_flow: FlowType,
+    pub credentials: Option<Vec<u8>>,
 }
 
-#[derive(Debug, Clone)]
+/// Sent by the server to accept a [`Negotiate`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NegotiationResponse {
+    pub session_id: SessionId,
+    /// The [`Negotiate::timestamp`] of the accepted request.
+    pub request_timestamp: u64,
+    pub server_flow: FlowType,
+    pub credentials: Option<Vec<u8>>,
+}
+
+/// The reason why a [`Negotiate`] was rejected.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum NegotiationRejectCode {
+    Unspecified,
+    Credentials,
+    FlowTypeNotSupported,
+    DuplicateId,
+}
+
+/// Sent by the server to reject a [`Negotiate`].
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct NegotiationReject {
-    session_id: SessionId,
-    reason: Option<String>,
+    pub session_id: SessionId,
+    /// The [`Negotiate::timestamp`] of the rejected request.
+    pub request_timestamp: u64,
+    pub code: NegotiationRejectCode,
+    pub reason: Option<String>,
 }
 
-#[derive(Debug, Clone)]
+/// Sent by the client to bind a negotiated session to the transport.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Establish {
-    session_id: SessionId,
-    next_seq_number: u64,
-    credentials: Option<Vec<u8>>,
+    pub session_id: SessionId,
+    /// Nanoseconds since the UNIX epoch.
+    pub timestamp: u64,
+    pub keep_alive_interval: Duration,
+    /// Only present for sequenced client flows (see
+    /// [`FlowType::is_sequenced`]).
+    pub next_seq_number: Option<u64>,
+    pub credentials: Option<Vec<u8>>,
 }
 
-#[derive(Debug, Clone)]
+/// Sent by the server to accept an [`Establish`].
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct EstablishmentAck {
-    next_seq_number: u64,
+    pub session_id: SessionId,
+    /// The [`Establish::timestamp`] of the accepted request.
+    pub request_timestamp: u64,
+    pub keep_alive_interval: Duration,
+    /// Only present for sequenced server flows (see
+    /// [`FlowType::is_sequenced`]).
+    pub next_seq_number: Option<u64>,
+}
+
+/// The reason why an [`Establish`] was rejected.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum EstablishmentRejectCode {
+    Unnegotiated,
+    AlreadyEstablished,
+    SessionBlocked,
+    KeepaliveInterval,
+    Credentials,
+    Unspecified,
+}
+
+/// Sent by the server to reject an [`Establish`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EstablishmentReject {
+    pub session_id: SessionId,
+    /// The [`Establish::timestamp`] of the rejected request.
+    pub request_timestamp: u64,
+    pub code: EstablishmentRejectCode,
+    pub reason: Option<String>,
+}
+
+/// Sent by the receiver of a [`FlowType::Recoverable`] flow to request the
+/// retransmission of missing application messages.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetransmitRequest {
+    pub session_id: SessionId,
+    /// Nanoseconds since the UNIX epoch.
+    pub timestamp: u64,
+    pub from_seq_number: u64,
+    pub count: u64,
+}
+
+/// Sent by the sender of a [`FlowType::Recoverable`] flow right before the
+/// retransmitted application messages.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Retransmission {
+    pub session_id: SessionId,
+    /// The [`RetransmitRequest::timestamp`] of the accepted request.
+    pub request_timestamp: u64,
+    /// The sequence number of the first retransmitted message.
+    pub next_seq_number: u64,
+    pub count: u64,
+}
+
+/// The reason why a [`RetransmitRequest`] was rejected.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum RetransmitRejectCode {
+    OutOfRange,
+    InvalidSession,
+    RequestLimitExceeded,
+}
+
+/// Sent by the sender of a [`FlowType::Recoverable`] flow to reject a
+/// [`RetransmitRequest`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetransmitReject {
+    pub session_id: SessionId,
+    /// The [`RetransmitRequest::timestamp`] of the rejected request.
+    pub request_timestamp: u64,
+    pub code: RetransmitRejectCode,
+    pub reason: Option<String>,
+}
+
+/// Sent by the receiver of a [`FlowType::Idempotent`] flow to report
+/// application messages that were never received, and thus not applied.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NotApplied {
+    pub from_seq_number: u64,
+    pub count: u64,
+}
+
+/// The reason why a session was terminated via [`Terminate`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TerminationCode {
+    /// The session was terminated in an orderly fashion.
+    Finished,
+    UnspecifiedError,
+    /// A `RetransmitRequest` referred to application messages beyond the last
+    /// one sent.
+    ReRequestOutOfBounds,
+    /// A `RetransmitRequest` was received while another one was still being
+    /// handled.
+    ReRequestInProgress,
+}
+
+/// Sent by either side to unbind an established session from the transport,
+/// and by the counterparty in response. The session can then be
+/// re-established on a new transport.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Terminate {
+    pub session_id: SessionId,
+    pub code: TerminationCode,
+    pub reason: Option<String>,
 }
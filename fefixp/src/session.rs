@@ -0,0 +1,1645 @@
+use super::*;
+use std::collections::VecDeque;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// The state of a [`Session`] within the FIXP session lifecycle.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SessionState {
+    /// No `Negotiate` has been exchanged yet.
+    Idle,
+    /// A `Negotiate` was sent and the server's response is pending.
+    NegotiateSent,
+    /// A `Negotiate` was received and the caller must either accept or reject
+    /// it.
+    NegotiateReceived,
+    /// The session was negotiated, but it's not bound to the transport yet.
+    Negotiated,
+    /// An `Establish` was sent and the server's response is pending.
+    EstablishSent,
+    /// An `Establish` was received and the caller must either accept or reject
+    /// it.
+    EstablishReceived,
+    /// The session is bound to the transport and application messages can
+    /// flow.
+    Established,
+    /// A `Terminate` was sent and the counterparty's response is pending.
+    Terminating,
+}
+
+/// An event produced by a [`Session`], to be handled by the caller.
+///
+/// Please note that [`SessionEvent`] is marked with `#[non_exhaustive]`,
+/// which future-proofs the enumeration type in case more variants are added.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SessionEvent {
+    /// A session message that must be encoded and written to the transport
+    /// layer.
+    Outbound(Message),
+    /// The server received a `Negotiate`, which must be either accepted via
+    /// [`Session::accept_negotiation`] or rejected via
+    /// [`Session::reject_negotiation`].
+    NegotiationRequested(Negotiate),
+    /// The client received a `NegotiationResponse`. The server's flow type
+    /// is available via [`Session::counterparty_flow`].
+    Negotiated,
+    /// The client received a `NegotiationReject`.
+    NegotiationRejected {
+        code: NegotiationRejectCode,
+        reason: Option<String>,
+    },
+    /// The server received an `Establish`, which must be either accepted via
+    /// [`Session::accept_establishment`] or rejected via
+    /// [`Session::reject_establishment`].
+    EstablishmentRequested(Establish),
+    /// The handshake is complete, on either side.
+    Established,
+    /// The client received an `EstablishmentReject`.
+    EstablishmentRejected {
+        code: EstablishmentRejectCode,
+        reason: Option<String>,
+    },
+    /// The counterparty requested the retransmission of some application
+    /// messages, which must be either accepted via
+    /// [`Session::accept_retransmission`] or rejected via
+    /// [`Session::reject_retransmission`].
+    RetransmissionRequested(RetransmitRequest),
+    /// The counterparty rejected a `RetransmitRequest`, so the requested
+    /// application messages are lost.
+    RetransmissionRejected {
+        code: RetransmitRejectCode,
+        reason: Option<String>,
+    },
+    /// The counterparty didn't receive some application messages of an
+    /// idempotent flow, which it reported with `NotApplied`. It's up to the
+    /// caller to send them again (as new messages) or not.
+    NotApplied { from_seq_number: u64, count: u64 },
+    /// The session was unbound from the transport, which should be closed. The
+    /// session is negotiated again, so it can be re-established on a new
+    /// transport via [`Session::establish`].
+    Terminated {
+        /// The [`TerminationCode`] of whoever initiated the termination.
+        code: TerminationCode,
+        reason: Option<String>,
+    },
+    /// An inbound session message that is not valid in the current
+    /// [`SessionState`] or that refers to a different session. It was
+    /// ignored.
+    Unexpected(Message),
+}
+
+/// How an inbound application message fits in the flow of the counterparty,
+/// as determined by [`Session::receive_application_message`].
+///
+/// Please note that [`Delivery`] is marked with `#[non_exhaustive]`, which
+/// future-proofs the enumeration type in case more variants are added.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Delivery {
+    /// The flow of the counterparty is not sequenced.
+    Unsequenced,
+    /// A new message with the given sequence number, which should be applied.
+    New(u64),
+    /// A message with the given sequence number that was retransmitted upon
+    /// request, which should be applied.
+    Retransmitted(u64),
+    /// A message with the given sequence number that was already received,
+    /// which must be discarded.
+    Duplicate(u64),
+}
+
+/// A builder for [`Session`].
+#[derive(Debug, Clone)]
+pub struct SessionBuilder {
+    session_id: SessionId,
+    flow: FlowType,
+    credentials: Option<Vec<u8>>,
+    keep_alive_interval: Duration,
+    next_seq_number: u64,
+}
+
+impl SessionBuilder {
+    /// Sets the [`SessionId`] that a client proposes in its `Negotiate`.
+    /// Servers learn it from the counterparty instead.
+    pub fn set_session_id(&mut self, session_id: SessionId) {
+        self.session_id = session_id;
+    }
+
+    /// Sets the [`FlowType`] of the messages sent by `self`.
+    /// [`FlowType::Idempotent`] by default.
+    pub fn set_flow(&mut self, flow: FlowType) {
+        self.flow = flow;
+    }
+
+    /// Sets the credentials included in `Negotiate`, `NegotiationResponse`,
+    /// and `Establish` messages. None by default.
+    pub fn set_credentials<B>(&mut self, credentials: Option<B>)
+    where
+        B: Into<Vec<u8>>,
+    {
+        self.credentials = credentials.map(Into::into);
+    }
+
+    /// Sets the keep-alive interval that a client proposes in its `Establish`
+    /// message. 5 seconds by default.
+    pub fn set_keep_alive_interval(&mut self, interval: Duration) {
+        self.keep_alive_interval = interval;
+    }
+
+    /// Sets the sequence number of the next application message sent by
+    /// `self`, which only matters for sequenced flows (see
+    /// [`FlowType::is_sequenced`]).
+    ///
+    /// # Panics
+    ///
+    /// This method will panic if `next_seq_number` is zero.
+    pub fn set_next_seq_number(&mut self, next_seq_number: u64) {
+        if next_seq_number == 0 {
+            panic!("FIXP sequence numbers must be strictly positive");
+        }
+        self.next_seq_number = next_seq_number;
+    }
+
+    pub fn build(self) -> Session {
+        Session {
+            session_id: self.session_id,
+            flow: self.flow,
+            counterparty_flow: None,
+            credentials: self.credentials,
+            keep_alive_interval: self.keep_alive_interval,
+            next_seq_number: self.next_seq_number,
+            counterparty_next_seq_number: None,
+            highest_received_seq_number: 0,
+            retransmission: None,
+            retransmit_request: None,
+            gaps: VecDeque::new(),
+            last_sent: UNIX_EPOCH,
+            last_received: UNIX_EPOCH,
+            terminate_sent: None,
+            state: SessionState::Idle,
+            events: VecDeque::new(),
+            pending_request: None,
+        }
+    }
+}
+
+impl Default for SessionBuilder {
+    fn default() -> Self {
+        Self {
+            session_id: 0,
+            flow: FlowType::Idempotent,
+            credentials: None,
+            keep_alive_interval: Duration::from_secs(5),
+            next_seq_number: 1,
+        }
+    }
+}
+
+/// A *sans-IO* FIXP session state machine.
+///
+/// [`Session`] takes care of the session establishment handshake, i.e. the
+/// `Negotiate` and `Establish` exchanges, on both the client and the server
+/// side. It performs no I/O on its own: the caller feeds decoded inbound
+/// session messages via [`Session::feed`] and drains [`SessionEvent`]'s via
+/// [`Session::next_event`]. It's the caller's responsibility to encode
+/// [`SessionEvent::Outbound`] messages and frame them, e.g. with SOFH.
+///
+/// Servers simply wait for an inbound `Negotiate`, while clients must call
+/// [`Session::negotiate`] first. A negotiated session can be re-established
+/// on a new transport by calling [`Session::establish`] again.
+///
+/// Application messages are implicitly sequenced in both directions,
+/// according to the [`FlowType`] of their sender. The caller must report every
+/// outbound application message via [`Session::send_application_message`] and
+/// every inbound one via [`Session::receive_application_message`]. Gaps in the
+/// inbound flow, which are detected from `Sequence` messages, result in a
+/// `RetransmitRequest` for recoverable flows and in a `NotApplied` for
+/// idempotent flows.
+///
+/// Once established, [`Session::tick`] must be called periodically to keep the
+/// session alive: whenever nothing was sent for a whole keep-alive interval, a
+/// `Sequence` (or an `UnsequencedHeartbeat`, for flows that aren't sequenced)
+/// is sent. Either side can unbind the session from the transport via
+/// [`Session::terminate`].
+///
+/// # Examples
+///
+/// ```
+/// use fefixp::{SessionBuilder, SessionEvent, SessionState};
+/// use std::time::SystemTime;
+///
+/// let mut client = SessionBuilder::default();
+/// client.set_session_id(42);
+/// let mut client = client.build();
+/// let mut server = SessionBuilder::default().build();
+///
+/// let now = SystemTime::now();
+/// client.negotiate(now);
+/// while let Some(SessionEvent::Outbound(msg)) = client.next_event() {
+///     server.feed(msg, now);
+/// }
+/// assert!(matches!(
+///     server.next_event(),
+///     Some(SessionEvent::NegotiationRequested(_))
+/// ));
+/// server.accept_negotiation(now);
+/// while let Some(SessionEvent::Outbound(msg)) = server.next_event() {
+///     client.feed(msg, now);
+/// }
+/// assert_eq!(client.state(), SessionState::Negotiated);
+/// ```
+#[derive(Debug)]
+pub struct Session {
+    session_id: SessionId,
+    flow: FlowType,
+    counterparty_flow: Option<FlowType>,
+    credentials: Option<Vec<u8>>,
+    keep_alive_interval: Duration,
+    next_seq_number: u64,
+    // The expected sequence number of the next live inbound application
+    // message, once known.
+    counterparty_next_seq_number: Option<u64>,
+    highest_received_seq_number: u64,
+    // The sequence number of the next retransmitted inbound message and the
+    // number of messages left, while a retransmission is in progress.
+    retransmission: Option<(u64, u64)>,
+    // The outstanding `RetransmitRequest` sent by `self`, if any. Only one
+    // request can be outstanding at any given time and other gaps are queued.
+    retransmit_request: Option<RetransmitRequest>,
+    gaps: VecDeque<(u64, u64)>,
+    last_sent: SystemTime,
+    last_received: SystemTime,
+    // The `Terminate` sent by `self` and when, while awaiting a response.
+    terminate_sent: Option<(Terminate, SystemTime)>,
+    state: SessionState,
+    events: VecDeque<SessionEvent>,
+    // The request received by a server and awaiting a decision.
+    pending_request: Option<Message>,
+}
+
+impl Session {
+    /// Returns the current [`SessionState`] of `self`.
+    pub fn state(&self) -> SessionState {
+        self.state
+    }
+
+    /// Returns the [`SessionId`] of `self`. For servers, this is the
+    /// identifier chosen by the counterparty.
+    pub fn session_id(&self) -> SessionId {
+        self.session_id
+    }
+
+    /// Returns the [`FlowType`] of the messages sent by `self`.
+    pub fn flow(&self) -> FlowType {
+        self.flow
+    }
+
+    /// Returns the [`FlowType`] of the messages sent by the counterparty, if
+    /// negotiated.
+    pub fn counterparty_flow(&self) -> Option<FlowType> {
+        self.counterparty_flow
+    }
+
+    /// Returns the keep-alive interval of `self`. For servers, this is the
+    /// interval chosen by the counterparty.
+    pub fn keep_alive_interval(&self) -> Duration {
+        self.keep_alive_interval
+    }
+
+    /// Returns the sequence number of the next outbound application message.
+    pub fn next_seq_number(&self) -> u64 {
+        self.next_seq_number
+    }
+
+    /// Returns the expected sequence number of the next live inbound
+    /// application message, if the counterparty flow is sequenced and it's
+    /// known.
+    pub fn counterparty_next_seq_number(&self) -> Option<u64> {
+        self.counterparty_next_seq_number
+    }
+
+    /// Removes and returns the oldest [`SessionEvent`] that is yet to be
+    /// handled by the caller, if any.
+    pub fn next_event(&mut self) -> Option<SessionEvent> {
+        self.events.pop_front()
+    }
+
+    /// Initiates the negotiation by sending a `Negotiate` message.
+    pub fn negotiate(&mut self, now: SystemTime) {
+        if self.state != SessionState::Idle {
+            return;
+        }
+        self.send(
+            Message::Negotiate(Negotiate {
+                session_id: self.session_id,
+                timestamp: nanos_since_epoch(now),
+                client_flow: self.flow,
+                credentials: self.credentials.clone(),
+            }),
+            now,
+        );
+        self.state = SessionState::NegotiateSent;
+    }
+
+    /// Binds the negotiated session to the transport by sending an
+    /// `Establish` message.
+    pub fn establish(&mut self, now: SystemTime) {
+        if self.state != SessionState::Negotiated {
+            return;
+        }
+        self.send(
+            Message::Establish(Establish {
+                session_id: self.session_id,
+                timestamp: nanos_since_epoch(now),
+                keep_alive_interval: self.keep_alive_interval,
+                next_seq_number: self.sequenced_seq_number(),
+                credentials: self.credentials.clone(),
+            }),
+            now,
+        );
+        self.state = SessionState::EstablishSent;
+    }
+
+    /// Accepts the pending `Negotiate` with a `NegotiationResponse`.
+    pub fn accept_negotiation(&mut self, now: SystemTime) {
+        if let Some(Message::Negotiate(negotiate)) =
+            self.take_pending(SessionState::NegotiateReceived)
+        {
+            self.session_id = negotiate.session_id;
+            self.counterparty_flow = Some(negotiate.client_flow);
+            self.send(
+                Message::NegotiationResponse(NegotiationResponse {
+                    session_id: negotiate.session_id,
+                    request_timestamp: negotiate.timestamp,
+                    server_flow: self.flow,
+                    credentials: self.credentials.clone(),
+                }),
+                now,
+            );
+            self.state = SessionState::Negotiated;
+        }
+    }
+
+    /// Rejects the pending `Negotiate` with a `NegotiationReject`.
+    pub fn reject_negotiation(
+        &mut self,
+        code: NegotiationRejectCode,
+        reason: Option<&str>,
+        now: SystemTime,
+    ) {
+        if let Some(Message::Negotiate(negotiate)) =
+            self.take_pending(SessionState::NegotiateReceived)
+        {
+            self.send(negotiation_reject(&negotiate, code, reason), now);
+            self.state = SessionState::Idle;
+        }
+    }
+
+    /// Accepts the pending `Establish` with an `EstablishmentAck`.
+    pub fn accept_establishment(&mut self, now: SystemTime) {
+        if let Some(Message::Establish(establish)) =
+            self.take_pending(SessionState::EstablishReceived)
+        {
+            self.keep_alive_interval = establish.keep_alive_interval;
+            self.send(
+                Message::EstablishmentAck(EstablishmentAck {
+                    session_id: self.session_id,
+                    request_timestamp: establish.timestamp,
+                    keep_alive_interval: self.keep_alive_interval,
+                    next_seq_number: self.sequenced_seq_number(),
+                }),
+                now,
+            );
+            self.on_established(establish.next_seq_number, now);
+        }
+    }
+
+    /// Rejects the pending `Establish` with an `EstablishmentReject`.
+    pub fn reject_establishment(
+        &mut self,
+        code: EstablishmentRejectCode,
+        reason: Option<&str>,
+        now: SystemTime,
+    ) {
+        if let Some(Message::Establish(establish)) =
+            self.take_pending(SessionState::EstablishReceived)
+        {
+            self.send(establishment_reject(&establish, code, reason), now);
+            self.state = SessionState::Negotiated;
+        }
+    }
+
+    /// Accepts the pending `RetransmitRequest` with a `Retransmission`. The
+    /// caller must write the requested application messages right after it.
+    pub fn accept_retransmission(&mut self, now: SystemTime) {
+        if let Some(Message::RetransmitRequest(request)) =
+            self.take_pending(SessionState::Established)
+        {
+            self.send(
+                Message::Retransmission(Retransmission {
+                    session_id: self.session_id,
+                    request_timestamp: request.timestamp,
+                    next_seq_number: request.from_seq_number,
+                    count: request.count,
+                }),
+                now,
+            );
+        }
+    }
+
+    /// Rejects the pending `RetransmitRequest` with a `RetransmitReject`.
+    pub fn reject_retransmission(
+        &mut self,
+        code: RetransmitRejectCode,
+        reason: Option<&str>,
+        now: SystemTime,
+    ) {
+        if let Some(Message::RetransmitRequest(request)) =
+            self.take_pending(SessionState::Established)
+        {
+            self.send(retransmit_reject(&request, code, reason), now);
+        }
+    }
+
+    /// Assigns a sequence number to an outbound application message, which the
+    /// caller must then write to the transport. It returns `None` if the flow
+    /// of `self` is not sequenced.
+    pub fn send_application_message(&mut self, now: SystemTime) -> Option<u64> {
+        self.last_sent = now;
+        let seq_number = self.sequenced_seq_number()?;
+        self.next_seq_number += 1;
+        Some(seq_number)
+    }
+
+    /// Processes an inbound application message and returns its [`Delivery`]
+    /// status. Application messages carry no sequence number of their own, so
+    /// the caller must report them in the same order they were received.
+    pub fn receive_application_message(&mut self, now: SystemTime) -> Delivery {
+        self.last_received = now;
+        if let Some((seq_number, count)) = self.retransmission {
+            if count > 1 {
+                self.retransmission = Some((seq_number + 1, count - 1));
+            } else {
+                self.retransmission = None;
+                self.retransmit_request = None;
+                self.request_next_gap(now);
+            }
+            return Delivery::Retransmitted(seq_number);
+        }
+        match self.counterparty_flow {
+            Some(flow) if flow.is_sequenced() => {
+                let seq_number = self.counterparty_next_seq_number.unwrap_or(1);
+                self.counterparty_next_seq_number = Some(seq_number + 1);
+                if seq_number <= self.highest_received_seq_number {
+                    Delivery::Duplicate(seq_number)
+                } else {
+                    self.highest_received_seq_number = seq_number;
+                    Delivery::New(seq_number)
+                }
+            }
+            _ => Delivery::Unsequenced,
+        }
+    }
+
+    /// Unbinds the established session from the transport by sending a
+    /// `Terminate` message. The transport should only be closed upon
+    /// [`SessionEvent::Terminated`], i.e. once the counterparty responds or a
+    /// whole keep-alive interval elapses without a response.
+    pub fn terminate(&mut self, code: TerminationCode, reason: Option<&str>, now: SystemTime) {
+        if self.state != SessionState::Established {
+            return;
+        }
+        let terminate = Terminate {
+            session_id: self.session_id,
+            code,
+            reason: reason.map(str::to_string),
+        };
+        self.send(Message::Terminate(terminate.clone()), now);
+        self.terminate_sent = Some((terminate, now));
+        self.state = SessionState::Terminating;
+    }
+
+    /// Checks keep-alive timers and sends `Sequence` or `UnsequencedHeartbeat`
+    /// messages if needed.
+    ///
+    /// The session is terminated if nothing is received from the counterparty
+    /// for two keep-alive intervals, or if a `Terminate` remains unanswered for
+    /// a whole keep-alive interval.
+    pub fn tick(&mut self, now: SystemTime) {
+        let interval = self.keep_alive_interval;
+        match self.state {
+            SessionState::Established => {
+                if elapsed(self.last_received, now) >= interval * 2 {
+                    self.abort(
+                        TerminationCode::UnspecifiedError,
+                        "Keep-alive interval lapsed",
+                        now,
+                    );
+                } else if elapsed(self.last_sent, now) >= interval {
+                    let keepalive = match self.sequenced_seq_number() {
+                        Some(next_seq_number) => Message::Sequence(Sequence { next_seq_number }),
+                        None => Message::UnsequencedHeartbeat,
+                    };
+                    self.send(keepalive, now);
+                }
+            }
+            SessionState::Terminating => {
+                if let Some((terminate, sent)) = self.terminate_sent.clone() {
+                    if elapsed(sent, now) >= interval {
+                        self.unbind(terminate.code, terminate.reason);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Processes an inbound, decoded session `message`.
+    pub fn feed(&mut self, message: Message, now: SystemTime) {
+        self.last_received = now;
+        match (self.state, message) {
+            (SessionState::Idle, Message::Negotiate(negotiate)) => {
+                self.pending_request = Some(Message::Negotiate(negotiate.clone()));
+                self.state = SessionState::NegotiateReceived;
+                self.events
+                    .push_back(SessionEvent::NegotiationRequested(negotiate));
+            }
+            (SessionState::Negotiated, Message::Negotiate(negotiate))
+            | (SessionState::Established, Message::Negotiate(negotiate)) => {
+                self.send(
+                    negotiation_reject(
+                        &negotiate,
+                        NegotiationRejectCode::DuplicateId,
+                        Some("Session already negotiated"),
+                    ),
+                    now,
+                );
+            }
+            (SessionState::NegotiateSent, Message::NegotiationResponse(response))
+                if response.session_id == self.session_id =>
+            {
+                self.counterparty_flow = Some(response.server_flow);
+                self.state = SessionState::Negotiated;
+                self.events.push_back(SessionEvent::Negotiated);
+            }
+            (SessionState::NegotiateSent, Message::NegotiationReject(reject))
+                if reject.session_id == self.session_id =>
+            {
+                self.state = SessionState::Idle;
+                self.events.push_back(SessionEvent::NegotiationRejected {
+                    code: reject.code,
+                    reason: reject.reason,
+                });
+            }
+            (SessionState::Negotiated, Message::Establish(establish))
+                if establish.session_id == self.session_id =>
+            {
+                self.pending_request = Some(Message::Establish(establish.clone()));
+                self.state = SessionState::EstablishReceived;
+                self.events
+                    .push_back(SessionEvent::EstablishmentRequested(establish));
+            }
+            (SessionState::Established, Message::Establish(establish))
+                if establish.session_id == self.session_id =>
+            {
+                self.send(
+                    establishment_reject(
+                        &establish,
+                        EstablishmentRejectCode::AlreadyEstablished,
+                        Some("Session already established"),
+                    ),
+                    now,
+                );
+            }
+            (SessionState::Idle, Message::Establish(establish))
+            | (SessionState::Negotiated, Message::Establish(establish)) => {
+                self.send(
+                    establishment_reject(
+                        &establish,
+                        EstablishmentRejectCode::Unnegotiated,
+                        Some("Session not negotiated"),
+                    ),
+                    now,
+                );
+            }
+            (SessionState::EstablishSent, Message::EstablishmentAck(ack))
+                if ack.session_id == self.session_id =>
+            {
+                self.keep_alive_interval = ack.keep_alive_interval;
+                self.on_established(ack.next_seq_number, now);
+            }
+            (SessionState::EstablishSent, Message::EstablishmentReject(reject))
+                if reject.session_id == self.session_id =>
+            {
+                self.state = SessionState::Negotiated;
+                self.events.push_back(SessionEvent::EstablishmentRejected {
+                    code: reject.code,
+                    reason: reject.reason,
+                });
+            }
+            (SessionState::Established, Message::Sequence(sequence)) if matches!(self.counterparty_flow, Some(flow) if flow.is_sequenced()) =>
+            {
+                self.update_counterparty_seq_number(sequence.next_seq_number, now);
+            }
+            (SessionState::Established, Message::RetransmitRequest(request)) => {
+                let oldest_seq_number = request.from_seq_number;
+                let newest_seq_number = oldest_seq_number.saturating_add(request.count);
+                if request.session_id != self.session_id {
+                    self.send(
+                        retransmit_reject(
+                            &request,
+                            RetransmitRejectCode::InvalidSession,
+                            Some("Unknown session"),
+                        ),
+                        now,
+                    );
+                } else if matches!(self.pending_request, Some(Message::RetransmitRequest(_))) {
+                    self.abort(
+                        TerminationCode::ReRequestInProgress,
+                        "Retransmission already in progress",
+                        now,
+                    );
+                } else if self.flow != FlowType::Recoverable
+                    || oldest_seq_number == 0
+                    || newest_seq_number > self.next_seq_number
+                {
+                    self.send(
+                        retransmit_reject(
+                            &request,
+                            RetransmitRejectCode::OutOfRange,
+                            Some("Sequence numbers out of range"),
+                        ),
+                        now,
+                    );
+                } else {
+                    self.pending_request = Some(Message::RetransmitRequest(request.clone()));
+                    self.events
+                        .push_back(SessionEvent::RetransmissionRequested(request));
+                }
+            }
+            (SessionState::Established, Message::Retransmission(retransmission))
+                if self.is_response_to_retransmit_request(
+                    retransmission.session_id,
+                    retransmission.request_timestamp,
+                ) =>
+            {
+                if retransmission.count == 0 {
+                    self.retransmit_request = None;
+                    self.request_next_gap(now);
+                } else {
+                    self.retransmission =
+                        Some((retransmission.next_seq_number, retransmission.count));
+                }
+            }
+            (SessionState::Established, Message::RetransmitReject(reject))
+                if self.is_response_to_retransmit_request(
+                    reject.session_id,
+                    reject.request_timestamp,
+                ) =>
+            {
+                self.retransmit_request = None;
+                self.events.push_back(SessionEvent::RetransmissionRejected {
+                    code: reject.code,
+                    reason: reject.reason,
+                });
+                self.request_next_gap(now);
+            }
+            (SessionState::Established, Message::NotApplied(not_applied))
+                if self.flow == FlowType::Idempotent =>
+            {
+                self.events.push_back(SessionEvent::NotApplied {
+                    from_seq_number: not_applied.from_seq_number,
+                    count: not_applied.count,
+                });
+            }
+            (SessionState::Established, Message::UnsequencedHeartbeat) if !matches!(self.counterparty_flow, Some(flow) if flow.is_sequenced()) =>
+                {}
+            (SessionState::Established, Message::Terminate(terminate))
+                if terminate.session_id == self.session_id =>
+            {
+                self.send(
+                    Message::Terminate(Terminate {
+                        session_id: self.session_id,
+                        code: TerminationCode::Finished,
+                        reason: None,
+                    }),
+                    now,
+                );
+                self.unbind(terminate.code, terminate.reason);
+            }
+            (SessionState::Terminating, Message::Terminate(terminate))
+                if terminate.session_id == self.session_id =>
+            {
+                if let Some((terminate, _)) = self.terminate_sent.take() {
+                    self.unbind(terminate.code, terminate.reason);
+                }
+            }
+            (_, message) => {
+                self.events.push_back(SessionEvent::Unexpected(message));
+            }
+        }
+    }
+
+    /// Sets the sequence number of the next inbound application message, as
+    /// announced by the counterparty, and detects gaps.
+    fn update_counterparty_seq_number(&mut self, next_seq_number: u64, now: SystemTime) {
+        if let Some(expected) = self.counterparty_next_seq_number {
+            if next_seq_number > expected {
+                let count = next_seq_number - expected;
+                match self.counterparty_flow {
+                    Some(FlowType::Recoverable) => {
+                        self.gaps.push_back((expected, count));
+                        if self.retransmit_request.is_none() {
+                            self.request_next_gap(now);
+                        }
+                    }
+                    Some(FlowType::Idempotent) => {
+                        self.send(
+                            Message::NotApplied(NotApplied {
+                                from_seq_number: expected,
+                                count,
+                            }),
+                            now,
+                        );
+                    }
+                    _ => (),
+                }
+            }
+        }
+        self.counterparty_next_seq_number = Some(next_seq_number);
+    }
+
+    fn on_established(&mut self, counterparty_next_seq_number: Option<u64>, now: SystemTime) {
+        self.state = SessionState::Established;
+        self.last_received = now;
+        self.events.push_back(SessionEvent::Established);
+        if let Some(next_seq_number) = counterparty_next_seq_number {
+            self.update_counterparty_seq_number(next_seq_number, now);
+        }
+        // Gaps left over from a previous transport.
+        if self.retransmit_request.is_none() {
+            self.request_next_gap(now);
+        }
+    }
+
+    /// Terminates the session right away, without waiting for the
+    /// counterparty's response.
+    fn abort(&mut self, code: TerminationCode, reason: &str, now: SystemTime) {
+        self.send(
+            Message::Terminate(Terminate {
+                session_id: self.session_id,
+                code,
+                reason: Some(reason.to_string()),
+            }),
+            now,
+        );
+        self.unbind(code, Some(reason.to_string()));
+    }
+
+    /// Goes back to [`SessionState::Negotiated`] after a `Terminate`. Any
+    /// retransmission in progress is lost together with the transport, so its
+    /// range is requested again once re-established.
+    fn unbind(&mut self, code: TerminationCode, reason: Option<String>) {
+        self.state = SessionState::Negotiated;
+        self.terminate_sent = None;
+        self.pending_request = None;
+        if let Some(request) = self.retransmit_request.take() {
+            let (from_seq_number, count) = self
+                .retransmission
+                .take()
+                .unwrap_or((request.from_seq_number, request.count));
+            self.gaps.push_front((from_seq_number, count));
+        }
+        self.events
+            .push_back(SessionEvent::Terminated { code, reason });
+    }
+
+    fn request_next_gap(&mut self, now: SystemTime) {
+        if let Some((from_seq_number, count)) = self.gaps.pop_front() {
+            let request = RetransmitRequest {
+                session_id: self.session_id,
+                timestamp: nanos_since_epoch(now),
+                from_seq_number,
+                count,
+            };
+            self.retransmit_request = Some(request.clone());
+            self.send(Message::RetransmitRequest(request), now);
+        }
+    }
+
+    fn is_response_to_retransmit_request(
+        &self,
+        session_id: SessionId,
+        request_timestamp: u64,
+    ) -> bool {
+        match &self.retransmit_request {
+            Some(request) => {
+                request.session_id == session_id && request.timestamp == request_timestamp
+            }
+            None => false,
+        }
+    }
+
+    fn sequenced_seq_number(&self) -> Option<u64> {
+        if self.flow.is_sequenced() {
+            Some(self.next_seq_number)
+        } else {
+            None
+        }
+    }
+
+    fn take_pending(&mut self, expected_state: SessionState) -> Option<Message> {
+        if self.state == expected_state {
+            self.pending_request.take()
+        } else {
+            None
+        }
+    }
+
+    fn send(&mut self, message: Message, now: SystemTime) {
+        self.last_sent = now;
+        self.events.push_back(SessionEvent::Outbound(message));
+    }
+}
+
+fn negotiation_reject(
+    negotiate: &Negotiate,
+    code: NegotiationRejectCode,
+    reason: Option<&str>,
+) -> Message {
+    Message::NegotiationReject(NegotiationReject {
+        session_id: negotiate.session_id,
+        request_timestamp: negotiate.timestamp,
+        code,
+        reason: reason.map(str::to_string),
+    })
+}
+
+fn establishment_reject(
+    establish: &Establish,
+    code: EstablishmentRejectCode,
+    reason: Option<&str>,
+) -> Message {
+    Message::EstablishmentReject(EstablishmentReject {
+        session_id: establish.session_id,
+        request_timestamp: establish.timestamp,
+        code,
+        reason: reason.map(str::to_string),
+    })
+}
+
+fn retransmit_reject(
+    request: &RetransmitRequest,
+    code: RetransmitRejectCode,
+    reason: Option<&str>,
+) -> Message {
+    Message::RetransmitReject(RetransmitReject {
+        session_id: request.session_id,
+        request_timestamp: request.timestamp,
+        code,
+        reason: reason.map(str::to_string),
+    })
+}
+
+/// Returns the time elapsed from `since` to `now`, or zero if `now` is earlier.
+fn elapsed(since: SystemTime, now: SystemTime) -> Duration {
+    now.duration_since(since).unwrap_or(Duration::ZERO)
+}
+
+fn nanos_since_epoch(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const SESSION_ID: SessionId = 0xdead_beef;
+
+    fn client() -> Session {
+        let mut builder = SessionBuilder::default();
+        builder.set_session_id(SESSION_ID);
+        builder.set_flow(FlowType::Recoverable);
+        builder.set_credentials(Some(&b"secret"[..]));
+        builder.set_keep_alive_interval(Duration::from_millis(500));
+        builder.build()
+    }
+
+    fn server() -> Session {
+        let mut builder = SessionBuilder::default();
+        builder.set_flow(FlowType::Idempotent);
+        builder.build()
+    }
+
+    fn now() -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(1_600_000_000)
+    }
+
+    /// Feeds all outbound messages of `from` to `to` and returns all other
+    /// events of `from`.
+    fn deliver(from: &mut Session, to: &mut Session) -> Vec<SessionEvent> {
+        let mut events = vec![];
+        while let Some(event) = from.next_event() {
+            match event {
+                SessionEvent::Outbound(msg) => to.feed(msg, now()),
+                event => events.push(event),
+            }
+        }
+        events
+    }
+
+    fn outbound_messages(session: &mut Session) -> Vec<Message> {
+        let mut messages = vec![];
+        while let Some(event) = session.next_event() {
+            if let SessionEvent::Outbound(msg) = event {
+                messages.push(msg);
+            }
+        }
+        messages
+    }
+
+    fn negotiated() -> (Session, Session) {
+        let mut client = client();
+        let mut server = server();
+        client.negotiate(now());
+        deliver(&mut client, &mut server);
+        server.accept_negotiation(now());
+        deliver(&mut server, &mut client);
+        deliver(&mut client, &mut server);
+        (client, server)
+    }
+
+    fn established(client_flow: FlowType, server_flow: FlowType) -> (Session, Session) {
+        let mut client = SessionBuilder::default();
+        client.set_session_id(SESSION_ID);
+        client.set_flow(client_flow);
+        let mut client = client.build();
+        let mut server = SessionBuilder::default();
+        server.set_flow(server_flow);
+        let mut server = server.build();
+        client.negotiate(now());
+        deliver(&mut client, &mut server);
+        server.accept_negotiation(now());
+        deliver(&mut server, &mut client);
+        client.establish(now());
+        deliver(&mut client, &mut server);
+        server.accept_establishment(now());
+        deliver(&mut server, &mut client);
+        client.events.clear();
+        server.events.clear();
+        (client, server)
+    }
+
+    #[test]
+    fn negotiate_and_establish() {
+        let mut client = client();
+        let mut server = server();
+        client.negotiate(now());
+        assert_eq!(client.state(), SessionState::NegotiateSent);
+        deliver(&mut client, &mut server);
+        assert_eq!(server.state(), SessionState::NegotiateReceived);
+        match server.next_event() {
+            Some(SessionEvent::NegotiationRequested(negotiate)) => {
+                assert_eq!(negotiate.session_id, SESSION_ID);
+                assert_eq!(negotiate.credentials.as_deref(), Some(&b"secret"[..]));
+            }
+            event => panic!("unexpected event {:?}", event),
+        }
+        server.accept_negotiation(now());
+        assert_eq!(server.session_id(), SESSION_ID);
+        assert_eq!(server.counterparty_flow(), Some(FlowType::Recoverable));
+        assert!(deliver(&mut server, &mut client).is_empty());
+        assert_eq!(client.state(), SessionState::Negotiated);
+        assert_eq!(client.counterparty_flow(), Some(FlowType::Idempotent));
+        assert_eq!(client.next_event(), Some(SessionEvent::Negotiated));
+
+        client.establish(now());
+        match &outbound_messages(&mut client)[..] {
+            [Message::Establish(establish)] => {
+                assert_eq!(establish.next_seq_number, Some(1));
+                server.feed(Message::Establish(establish.clone()), now());
+            }
+            messages => panic!("unexpected messages {:?}", messages),
+        }
+        assert!(matches!(
+            server.next_event(),
+            Some(SessionEvent::EstablishmentRequested(_))
+        ));
+        server.accept_establishment(now());
+        assert_eq!(server.keep_alive_interval(), Duration::from_millis(500));
+        assert_eq!(
+            deliver(&mut server, &mut client),
+            vec![SessionEvent::Established]
+        );
+        assert_eq!(client.state(), SessionState::Established);
+        assert_eq!(server.state(), SessionState::Established);
+        assert_eq!(client.next_event(), Some(SessionEvent::Established));
+    }
+
+    #[test]
+    fn negotiation_reject() {
+        let mut client = client();
+        let mut server = server();
+        client.negotiate(now());
+        deliver(&mut client, &mut server);
+        server.next_event();
+        server.reject_negotiation(
+            NegotiationRejectCode::Credentials,
+            Some("Bad credentials"),
+            now(),
+        );
+        assert_eq!(server.state(), SessionState::Idle);
+        deliver(&mut server, &mut client);
+        assert_eq!(client.state(), SessionState::Idle);
+        assert_eq!(
+            client.next_event(),
+            Some(SessionEvent::NegotiationRejected {
+                code: NegotiationRejectCode::Credentials,
+                reason: Some("Bad credentials".to_string()),
+            })
+        );
+    }
+
+    #[test]
+    fn establishment_reject_allows_retry() {
+        let (mut client, mut server) = negotiated();
+        client.establish(now());
+        deliver(&mut client, &mut server);
+        server.next_event();
+        server.reject_establishment(EstablishmentRejectCode::KeepaliveInterval, None, now());
+        deliver(&mut server, &mut client);
+        assert_eq!(client.state(), SessionState::Negotiated);
+        assert_eq!(
+            client.next_event(),
+            Some(SessionEvent::EstablishmentRejected {
+                code: EstablishmentRejectCode::KeepaliveInterval,
+                reason: None,
+            })
+        );
+        client.establish(now());
+        deliver(&mut client, &mut server);
+        server.next_event();
+        server.accept_establishment(now());
+        deliver(&mut server, &mut client);
+        assert_eq!(client.state(), SessionState::Established);
+    }
+
+    #[test]
+    fn establish_without_negotiate_is_rejected() {
+        let mut client = client();
+        let mut server = server();
+        server.feed(
+            Message::Establish(Establish {
+                session_id: SESSION_ID,
+                timestamp: 0,
+                keep_alive_interval: Duration::from_secs(1),
+                next_seq_number: None,
+                credentials: None,
+            }),
+            now(),
+        );
+        match &outbound_messages(&mut server)[..] {
+            [Message::EstablishmentReject(reject)] => {
+                assert_eq!(reject.code, EstablishmentRejectCode::Unnegotiated);
+            }
+            messages => panic!("unexpected messages {:?}", messages),
+        }
+        assert_eq!(server.state(), SessionState::Idle);
+        client.establish(now());
+        assert_eq!(client.state(), SessionState::Idle);
+    }
+
+    #[test]
+    fn duplicate_negotiate_is_rejected() {
+        let (client, mut server) = negotiated();
+        let duplicate = Negotiate {
+            session_id: SESSION_ID,
+            timestamp: 0,
+            client_flow: FlowType::Recoverable,
+            credentials: None,
+        };
+        server.feed(Message::Negotiate(duplicate), now());
+        match &outbound_messages(&mut server)[..] {
+            [Message::NegotiationReject(reject)] => {
+                assert_eq!(reject.code, NegotiationRejectCode::DuplicateId);
+            }
+            messages => panic!("unexpected messages {:?}", messages),
+        }
+        assert_eq!(server.state(), SessionState::Negotiated);
+        assert_eq!(client.state(), SessionState::Negotiated);
+    }
+
+    #[test]
+    fn responses_for_other_sessions_are_ignored() {
+        let mut client = client();
+        client.negotiate(now());
+        outbound_messages(&mut client);
+        let response = Message::NegotiationResponse(NegotiationResponse {
+            session_id: SESSION_ID + 1,
+            request_timestamp: 0,
+            server_flow: FlowType::Idempotent,
+            credentials: None,
+        });
+        client.feed(response.clone(), now());
+        assert_eq!(client.state(), SessionState::NegotiateSent);
+        assert_eq!(
+            client.next_event(),
+            Some(SessionEvent::Unexpected(response))
+        );
+    }
+
+    /// Sends three application messages from `client`, of which `server`
+    /// only receives the first one, and feeds the resulting
+    /// `RetransmitRequest` of `server` to `client`.
+    fn request_retransmission(client: &mut Session, server: &mut Session) -> RetransmitRequest {
+        for _ in 1..=3 {
+            client.send_application_message(now());
+        }
+        server.receive_application_message(now());
+        server.feed(Message::Sequence(Sequence { next_seq_number: 4 }), now());
+        match &outbound_messages(server)[..] {
+            [Message::RetransmitRequest(request)] => {
+                assert_eq!((request.from_seq_number, request.count), (2, 2));
+                client.feed(Message::RetransmitRequest(request.clone()), now());
+                request.clone()
+            }
+            messages => panic!("unexpected messages {:?}", messages),
+        }
+    }
+
+    #[test]
+    fn recoverable_gap_is_retransmitted() {
+        let (mut client, mut server) = established(FlowType::Recoverable, FlowType::None);
+        for seq_number in 1..=3 {
+            assert_eq!(client.send_application_message(now()), Some(seq_number));
+        }
+        // Only the first message reaches the server, then a `Sequence`.
+        assert_eq!(server.receive_application_message(now()), Delivery::New(1));
+        server.feed(Message::Sequence(Sequence { next_seq_number: 4 }), now());
+        assert_eq!(server.counterparty_next_seq_number(), Some(4));
+        match &outbound_messages(&mut server)[..] {
+            [Message::RetransmitRequest(request)] => {
+                assert_eq!((request.from_seq_number, request.count), (2, 2));
+                client.feed(Message::RetransmitRequest(request.clone()), now());
+            }
+            messages => panic!("unexpected messages {:?}", messages),
+        }
+        assert!(matches!(
+            client.next_event(),
+            Some(SessionEvent::RetransmissionRequested(_))
+        ));
+        client.accept_retransmission(now());
+        deliver(&mut client, &mut server);
+        assert_eq!(
+            server.receive_application_message(now()),
+            Delivery::Retransmitted(2)
+        );
+        assert_eq!(
+            server.receive_application_message(now()),
+            Delivery::Retransmitted(3)
+        );
+        assert_eq!(server.receive_application_message(now()), Delivery::New(4));
+        assert!(server.next_event().is_none());
+    }
+
+    #[test]
+    fn gaps_are_requested_one_at_a_time() {
+        let (mut client, mut server) = established(FlowType::Recoverable, FlowType::None);
+        for _ in 1..=5 {
+            client.send_application_message(now());
+        }
+        server.feed(Message::Sequence(Sequence { next_seq_number: 3 }), now());
+        server.receive_application_message(now());
+        server.feed(Message::Sequence(Sequence { next_seq_number: 6 }), now());
+        let request = match &outbound_messages(&mut server)[..] {
+            [Message::RetransmitRequest(request)] => request.clone(),
+            messages => panic!("unexpected messages {:?}", messages),
+        };
+        assert_eq!((request.from_seq_number, request.count), (1, 2));
+        server.feed(
+            Message::RetransmitReject(RetransmitReject {
+                session_id: SESSION_ID,
+                request_timestamp: request.timestamp,
+                code: RetransmitRejectCode::RequestLimitExceeded,
+                reason: None,
+            }),
+            now(),
+        );
+        assert_eq!(
+            server.next_event(),
+            Some(SessionEvent::RetransmissionRejected {
+                code: RetransmitRejectCode::RequestLimitExceeded,
+                reason: None,
+            })
+        );
+        match &outbound_messages(&mut server)[..] {
+            [Message::RetransmitRequest(request)] => {
+                assert_eq!((request.from_seq_number, request.count), (4, 2));
+            }
+            messages => panic!("unexpected messages {:?}", messages),
+        }
+    }
+
+    #[test]
+    fn out_of_range_retransmit_request_is_rejected() {
+        let (mut client, _) = established(FlowType::Recoverable, FlowType::None);
+        client.send_application_message(now());
+        client.feed(
+            Message::RetransmitRequest(RetransmitRequest {
+                session_id: SESSION_ID,
+                timestamp: 0,
+                from_seq_number: 1,
+                count: 2,
+            }),
+            now(),
+        );
+        match &outbound_messages(&mut client)[..] {
+            [Message::RetransmitReject(reject)] => {
+                assert_eq!(reject.code, RetransmitRejectCode::OutOfRange);
+            }
+            messages => panic!("unexpected messages {:?}", messages),
+        }
+    }
+
+    #[test]
+    fn idempotent_gap_is_not_applied() {
+        let (mut client, mut server) = established(FlowType::Idempotent, FlowType::Unsequenced);
+        assert_eq!(server.receive_application_message(now()), Delivery::New(1));
+        server.feed(Message::Sequence(Sequence { next_seq_number: 4 }), now());
+        deliver(&mut server, &mut client);
+        assert_eq!(
+            client.next_event(),
+            Some(SessionEvent::NotApplied {
+                from_seq_number: 2,
+                count: 2,
+            })
+        );
+        assert_eq!(server.receive_application_message(now()), Delivery::New(4));
+    }
+
+    #[test]
+    fn idempotent_duplicates_are_detected() {
+        let (_, mut server) = established(FlowType::Idempotent, FlowType::Unsequenced);
+        server.receive_application_message(now());
+        server.receive_application_message(now());
+        server.feed(Message::Sequence(Sequence { next_seq_number: 2 }), now());
+        assert_eq!(
+            server.receive_application_message(now()),
+            Delivery::Duplicate(2)
+        );
+        assert_eq!(server.receive_application_message(now()), Delivery::New(3));
+        assert!(server.next_event().is_none());
+    }
+
+    #[test]
+    fn rejected_retransmission_is_reported() {
+        let (mut client, mut server) = established(FlowType::Recoverable, FlowType::None);
+        request_retransmission(&mut client, &mut server);
+        assert!(matches!(
+            client.next_event(),
+            Some(SessionEvent::RetransmissionRequested(_))
+        ));
+        client.reject_retransmission(
+            RetransmitRejectCode::RequestLimitExceeded,
+            Some("Too many requests"),
+            now(),
+        );
+        assert!(deliver(&mut client, &mut server).is_empty());
+        // The request was already answered.
+        client.accept_retransmission(now());
+        assert!(client.next_event().is_none());
+        assert_eq!(
+            server.next_event(),
+            Some(SessionEvent::RetransmissionRejected {
+                code: RetransmitRejectCode::RequestLimitExceeded,
+                reason: Some("Too many requests".to_string()),
+            })
+        );
+        assert!(server.next_event().is_none());
+        assert_eq!(server.receive_application_message(now()), Delivery::New(4));
+    }
+
+    #[test]
+    fn empty_retransmission_requests_next_gap() {
+        let (_, mut server) = established(FlowType::Recoverable, FlowType::None);
+        server.feed(Message::Sequence(Sequence { next_seq_number: 3 }), now());
+        server.receive_application_message(now());
+        server.feed(Message::Sequence(Sequence { next_seq_number: 6 }), now());
+        let request = match &outbound_messages(&mut server)[..] {
+            [Message::RetransmitRequest(request)] => request.clone(),
+            messages => panic!("unexpected messages {:?}", messages),
+        };
+        server.feed(
+            Message::Retransmission(Retransmission {
+                session_id: SESSION_ID,
+                request_timestamp: request.timestamp,
+                next_seq_number: request.from_seq_number,
+                count: 0,
+            }),
+            now(),
+        );
+        match &outbound_messages(&mut server)[..] {
+            [Message::RetransmitRequest(request)] => {
+                assert_eq!((request.from_seq_number, request.count), (4, 2));
+            }
+            messages => panic!("unexpected messages {:?}", messages),
+        }
+    }
+
+    #[test]
+    fn unrequested_retransmission_is_unexpected() {
+        let (mut client, mut server) = established(FlowType::Recoverable, FlowType::None);
+        let request = request_retransmission(&mut client, &mut server);
+        let retransmission = Message::Retransmission(Retransmission {
+            session_id: SESSION_ID,
+            request_timestamp: request.timestamp + 1,
+            next_seq_number: 2,
+            count: 2,
+        });
+        server.feed(retransmission.clone(), now());
+        assert_eq!(
+            server.next_event(),
+            Some(SessionEvent::Unexpected(retransmission))
+        );
+        assert_eq!(server.receive_application_message(now()), Delivery::New(4));
+    }
+
+    #[test]
+    fn retransmit_request_for_other_session_is_rejected() {
+        let (mut client, _) = established(FlowType::Recoverable, FlowType::None);
+        client.send_application_message(now());
+        client.feed(
+            Message::RetransmitRequest(RetransmitRequest {
+                session_id: SESSION_ID + 1,
+                timestamp: 0,
+                from_seq_number: 1,
+                count: 1,
+            }),
+            now(),
+        );
+        match &outbound_messages(&mut client)[..] {
+            [Message::RetransmitReject(reject)] => {
+                assert_eq!(reject.code, RetransmitRejectCode::InvalidSession);
+                assert_eq!(reject.session_id, SESSION_ID + 1);
+            }
+            messages => panic!("unexpected messages {:?}", messages),
+        }
+        assert_eq!(client.state(), SessionState::Established);
+    }
+
+    #[test]
+    fn concurrent_retransmit_requests_terminate_the_session() {
+        let (mut client, mut server) = established(FlowType::Recoverable, FlowType::None);
+        let request = request_retransmission(&mut client, &mut server);
+        client.next_event();
+        client.feed(Message::RetransmitRequest(request), now());
+        assert_eq!(client.state(), SessionState::Negotiated);
+        let reason = Some("Retransmission already in progress".to_string());
+        match &std::iter::from_fn(|| client.next_event()).collect::<Vec<_>>()[..] {
+            [SessionEvent::Outbound(Message::Terminate(terminate)), SessionEvent::Terminated { code, reason: r }] =>
+            {
+                assert_eq!(terminate.code, TerminationCode::ReRequestInProgress);
+                assert_eq!(*code, TerminationCode::ReRequestInProgress);
+                assert_eq!(*r, reason);
+            }
+            events => panic!("unexpected events {:?}", events),
+        }
+        // The pending request is dropped together with the transport.
+        client.accept_retransmission(now());
+        assert!(client.next_event().is_none());
+    }
+
+    #[test]
+    fn interrupted_retransmission_is_requested_again() {
+        let (mut client, mut server) = established(FlowType::Recoverable, FlowType::None);
+        request_retransmission(&mut client, &mut server);
+        client.next_event();
+        client.accept_retransmission(now());
+        deliver(&mut client, &mut server);
+        assert_eq!(
+            server.receive_application_message(now()),
+            Delivery::Retransmitted(2)
+        );
+        server.terminate(TerminationCode::UnspecifiedError, None, now());
+        deliver(&mut server, &mut client);
+        deliver(&mut client, &mut server);
+        assert_eq!(server.state(), SessionState::Negotiated);
+        server.events.clear();
+        client.establish(now());
+        deliver(&mut client, &mut server);
+        server.accept_establishment(now());
+        match &outbound_messages(&mut server)[..] {
+            [Message::EstablishmentAck(_), Message::RetransmitRequest(request)] => {
+                assert_eq!((request.from_seq_number, request.count), (3, 1));
+            }
+            messages => panic!("unexpected messages {:?}", messages),
+        }
+    }
+
+    #[test]
+    fn idempotent_gap_across_transports_is_not_applied() {
+        let (mut client, mut server) = established(FlowType::Idempotent, FlowType::Unsequenced);
+        for _ in 1..=3 {
+            client.send_application_message(now());
+        }
+        client.terminate(TerminationCode::Finished, None, now());
+        deliver(&mut client, &mut server);
+        deliver(&mut server, &mut client);
+        client.events.clear();
+        client.establish(now());
+        deliver(&mut client, &mut server);
+        server.accept_establishment(now());
+        match &outbound_messages(&mut server)[..] {
+            [Message::EstablishmentAck(_), Message::NotApplied(not_applied)] => {
+                assert_eq!((not_applied.from_seq_number, not_applied.count), (1, 3));
+            }
+            messages => panic!("unexpected messages {:?}", messages),
+        }
+        assert_eq!(server.receive_application_message(now()), Delivery::New(4));
+    }
+
+    #[test]
+    fn not_applied_is_unexpected_for_other_flows() {
+        let (mut client, _) = established(FlowType::Recoverable, FlowType::Idempotent);
+        let not_applied = Message::NotApplied(NotApplied {
+            from_seq_number: 1,
+            count: 1,
+        });
+        client.feed(not_applied.clone(), now());
+        assert_eq!(
+            client.next_event(),
+            Some(SessionEvent::Unexpected(not_applied))
+        );
+    }
+
+    #[test]
+    fn unsequenced_flows_have_no_sequence_numbers() {
+        let (mut client, mut server) = established(FlowType::Unsequenced, FlowType::Idempotent);
+        assert_eq!(client.send_application_message(now()), None);
+        assert_eq!(
+            server.receive_application_message(now()),
+            Delivery::Unsequenced
+        );
+        assert_eq!(server.send_application_message(now()), Some(1));
+        assert_eq!(client.receive_application_message(now()), Delivery::New(1));
+        let sequence = Message::Sequence(Sequence { next_seq_number: 5 });
+        server.feed(sequence.clone(), now());
+        assert_eq!(
+            server.next_event(),
+            Some(SessionEvent::Unexpected(sequence))
+        );
+    }
+
+    #[test]
+    fn keepalive_messages_are_sent_when_idle() {
+        let (mut client, mut server) = established(FlowType::Recoverable, FlowType::Unsequenced);
+        let later = now() + Duration::from_secs(4);
+        client.send_application_message(later);
+        client.tick(now() + Duration::from_secs(5));
+        assert!(client.next_event().is_none());
+        client.tick(now() + Duration::from_secs(9));
+        assert_eq!(
+            outbound_messages(&mut client),
+            vec![Message::Sequence(Sequence { next_seq_number: 2 })]
+        );
+        server.tick(now() + Duration::from_secs(5));
+        let heartbeat = outbound_messages(&mut server);
+        assert_eq!(heartbeat, vec![Message::UnsequencedHeartbeat]);
+        client.feed(heartbeat[0].clone(), now() + Duration::from_secs(5));
+        assert!(client.next_event().is_none());
+    }
+
+    #[test]
+    fn terminate_exchange() {
+        let (mut client, mut server) = established(FlowType::Recoverable, FlowType::Idempotent);
+        client.terminate(TerminationCode::Finished, Some("Bye"), now());
+        assert_eq!(client.state(), SessionState::Terminating);
+        deliver(&mut client, &mut server);
+        assert_eq!(server.state(), SessionState::Negotiated);
+        let terminated = SessionEvent::Terminated {
+            code: TerminationCode::Finished,
+            reason: Some("Bye".to_string()),
+        };
+        assert_eq!(deliver(&mut server, &mut client), vec![terminated.clone()]);
+        assert_eq!(client.state(), SessionState::Negotiated);
+        assert_eq!(client.next_event(), Some(terminated));
+        client.establish(now());
+        deliver(&mut client, &mut server);
+        server.next_event();
+        server.accept_establishment(now());
+        deliver(&mut server, &mut client);
+        assert_eq!(client.state(), SessionState::Established);
+    }
+
+    #[test]
+    fn unanswered_terminate_times_out() {
+        let (mut client, _) = established(FlowType::Idempotent, FlowType::Idempotent);
+        client.terminate(TerminationCode::UnspecifiedError, None, now());
+        outbound_messages(&mut client);
+        client.tick(now() + Duration::from_secs(4));
+        assert_eq!(client.state(), SessionState::Terminating);
+        client.tick(now() + Duration::from_secs(5));
+        assert_eq!(client.state(), SessionState::Negotiated);
+        assert_eq!(
+            client.next_event(),
+            Some(SessionEvent::Terminated {
+                code: TerminationCode::UnspecifiedError,
+                reason: None,
+            })
+        );
+    }
+
+    #[test]
+    fn silent_counterparty_is_terminated() {
+        let (mut client, _) = established(FlowType::Unsequenced, FlowType::Unsequenced);
+        client.tick(now() + Duration::from_secs(9));
+        assert_eq!(client.state(), SessionState::Established);
+        client.tick(now() + Duration::from_secs(10));
+        assert_eq!(client.state(), SessionState::Negotiated);
+        let events: Vec<_> = std::iter::from_fn(|| client.next_event()).collect();
+        match &events[..] {
+            [SessionEvent::Outbound(Message::UnsequencedHeartbeat), SessionEvent::Outbound(Message::Terminate(terminate)), SessionEvent::Terminated { code, .. }] =>
+            {
+                assert_eq!(terminate.code, TerminationCode::UnspecifiedError);
+                assert_eq!(*code, TerminationCode::UnspecifiedError);
+            }
+            events => panic!("unexpected events {:?}", events),
+        }
+    }
+
+    #[test]
+    fn inbound_traffic_keeps_the_session_alive() {
+        let (mut client, _) = established(FlowType::Recoverable, FlowType::Idempotent);
+        client.receive_application_message(now() + Duration::from_secs(6));
+        client.tick(now() + Duration::from_secs(10));
+        assert_eq!(client.state(), SessionState::Established);
+        client.feed(
+            Message::Sequence(Sequence { next_seq_number: 2 }),
+            now() + Duration::from_secs(15),
+        );
+        client.tick(now() + Duration::from_secs(24));
+        assert_eq!(client.state(), SessionState::Established);
+        outbound_messages(&mut client);
+        client.tick(now() + Duration::from_secs(25));
+        assert_eq!(client.state(), SessionState::Negotiated);
+        let events: Vec<_> = std::iter::from_fn(|| client.next_event()).collect();
+        match &events[..] {
+            [SessionEvent::Outbound(Message::Terminate(terminate)), SessionEvent::Terminated { code, reason }] =>
+            {
+                assert_eq!(terminate.code, TerminationCode::UnspecifiedError);
+                assert_eq!(*code, TerminationCode::UnspecifiedError);
+                assert_eq!(reason.as_deref(), Some("Keep-alive interval lapsed"));
+            }
+            events => panic!("unexpected events {:?}", events),
+        }
+        // Nothing else happens until the session is re-established.
+        client.tick(now() + Duration::from_secs(60));
+        assert!(client.next_event().is_none());
+    }
+
+    #[test]
+    fn counterparty_terminate_is_answered() {
+        let (_, mut server) = established(FlowType::Idempotent, FlowType::Idempotent);
+        let other_session = Message::Terminate(Terminate {
+            session_id: SESSION_ID + 1,
+            code: TerminationCode::Finished,
+            reason: None,
+        });
+        server.feed(other_session.clone(), now());
+        assert_eq!(server.state(), SessionState::Established);
+        assert_eq!(
+            server.next_event(),
+            Some(SessionEvent::Unexpected(other_session))
+        );
+        server.feed(
+            Message::Terminate(Terminate {
+                session_id: SESSION_ID,
+                code: TerminationCode::ReRequestOutOfBounds,
+                reason: Some("Bad request".to_string()),
+            }),
+            now(),
+        );
+        assert_eq!(server.state(), SessionState::Negotiated);
+        let events: Vec<_> = std::iter::from_fn(|| server.next_event()).collect();
+        assert_eq!(
+            events,
+            vec![
+                SessionEvent::Outbound(Message::Terminate(Terminate {
+                    session_id: SESSION_ID,
+                    code: TerminationCode::Finished,
+                    reason: None,
+                })),
+                SessionEvent::Terminated {
+                    code: TerminationCode::ReRequestOutOfBounds,
+                    reason: Some("Bad request".to_string()),
+                },
+            ]
+        );
+        // Only established sessions can be terminated.
+        server.terminate(TerminationCode::Finished, None, now());
+        assert_eq!(server.state(), SessionState::Negotiated);
+        assert!(server.next_event().is_none());
+    }
+}
@@ -0,0 +1,611 @@
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+#[cfg(any(feature = "utils-openssl", feature = "utils-rustls"))]
+use std::sync::Arc;
+
+// DER tags.
+const BOOLEAN: u8 = 0x01;
+const INTEGER: u8 = 0x02;
+const OCTET_STRING: u8 = 0x04;
+const OID: u8 = 0x06;
+const UTF8_STRING: u8 = 0x0C;
+const PRINTABLE_STRING: u8 = 0x13;
+const IA5_STRING: u8 = 0x16;
+const BMP_STRING: u8 = 0x1E;
+const SEQUENCE: u8 = 0x30;
+const SET: u8 = 0x31;
+const VERSION: u8 = 0xA0;
+const ISSUER_UNIQUE_ID: u8 = 0x81;
+const SUBJECT_UNIQUE_ID: u8 = 0x82;
+const EXTENSIONS: u8 = 0xA3;
+const DNS_NAME: u8 = 0x82;
+
+// 2.5.4.3 and 2.5.29.17.
+const OID_COMMON_NAME: &[u8] = &[0x55, 0x04, 0x03];
+const OID_SUBJECT_ALT_NAME: &[u8] = &[0x55, 0x1D, 0x11];
+
+/// The reason why a certificate is refused, which aborts the TLS handshake.
+///
+/// With `rustls`, the reason is reported to the local peer as
+/// `TLSError::General`. OpenSSL has no room for custom reasons and only
+/// reports `X509_V_ERR_APPLICATION_VERIFICATION`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CertificateRejection {
+    reason: String,
+}
+
+impl CertificateRejection {
+    /// Creates a new [`CertificateRejection`] with a human-readable `reason`.
+    pub fn new(reason: impl Into<String>) -> Self {
+        Self {
+            reason: reason.into(),
+        }
+    }
+
+    /// Returns the reason of `self`.
+    pub fn reason(&self) -> &str {
+        self.reason.as_str()
+    }
+}
+
+impl fmt::Display for CertificateRejection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Certificate rejected: {}", self.reason)
+    }
+}
+
+impl std::error::Error for CertificateRejection {}
+
+/// The names that an X.509 certificate was issued to, i.e. the Common Name
+/// (CN) of its subject and the DNS names of its Subject Alternative Name (SAN)
+/// extension.
+///
+/// # Examples
+///
+/// ```
+/// use fefixs::CertificateIdentity;
+///
+/// let der = include_bytes!("../test_data/client.der");
+/// let identity = CertificateIdentity::from_der(&der[..]).unwrap();
+/// assert_eq!(identity.common_name(), Some("client-a.example"));
+/// assert_eq!(identity.dns_names().len(), 2);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CertificateIdentity {
+    common_name: Option<String>,
+    dns_names: Vec<String>,
+}
+
+impl CertificateIdentity {
+    /// Reads the names of the DER-encoded certificate `der`. No signature
+    /// and no validity period is checked.
+    pub fn from_der(der: &[u8]) -> Result<Self, CertificateRejection> {
+        parse_certificate(der).ok_or_else(|| CertificateRejection::new("malformed certificate"))
+    }
+
+    /// Returns the CN of the subject, if any.
+    pub fn common_name(&self) -> Option<&str> {
+        self.common_name.as_deref()
+    }
+
+    /// Returns the DNS names of the SAN extension, in order.
+    pub fn dns_names(&self) -> &[String] {
+        &self.dns_names[..]
+    }
+
+    /// Returns an [`Iterator`] over the CN, if any, and then all DNS names.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.common_name
+            .iter()
+            .chain(self.dns_names.iter())
+            .map(String::as_str)
+    }
+}
+
+/// Decides whether the certificate of the remote peer is acceptable, once
+/// its chain has been verified by the TLS library. Closures that take a
+/// [`CertificateIdentity`] and the DER-encoded certificate implement
+/// [`CertificateValidator`].
+///
+/// Acceptors usually validate clients against a [`CounterpartyRegistry`],
+/// while initiators pin venue certificates with [`CertificatePins`].
+pub trait CertificateValidator: Send + Sync {
+    /// Returns [`Ok`] if the certificate is acceptable, otherwise the reason
+    /// to abort the handshake.
+    fn validate(
+        &self,
+        identity: &CertificateIdentity,
+        der: &[u8],
+    ) -> Result<(), CertificateRejection>;
+}
+
+impl<F> CertificateValidator for F
+where
+    F: Fn(&CertificateIdentity, &[u8]) -> Result<(), CertificateRejection> + Send + Sync,
+{
+    fn validate(
+        &self,
+        identity: &CertificateIdentity,
+        der: &[u8],
+    ) -> Result<(), CertificateRejection> {
+        self(identity, der)
+    }
+}
+
+/// Maps certificate names (CN or SAN DNS names) to the CompIDs of the
+/// counterparties they belong to. Client certificates without any registered
+/// name are rejected.
+///
+/// Acceptors should also check that the `SenderCompID <49>` of the `Logon <A>`
+/// equals [`CounterpartyRegistry::comp_id`], so that counterparties can't
+/// impersonate each other.
+///
+/// # Examples
+///
+/// ```
+/// use fefixs::{CertificateIdentity, CertificateValidator, CounterpartyRegistry};
+///
+/// let mut registry = CounterpartyRegistry::new();
+/// registry.add("backup.client-a.example", "CLIENT_A");
+///
+/// let der = include_bytes!("../test_data/client.der");
+/// let identity = CertificateIdentity::from_der(&der[..]).unwrap();
+/// assert_eq!(registry.comp_id(&identity), Some("CLIENT_A"));
+/// assert!(registry.validate(&identity, &der[..]).is_ok());
+/// ```
+#[derive(Debug, Clone)]
+pub struct CounterpartyRegistry {
+    comp_ids: HashMap<String, String>,
+}
+
+impl CounterpartyRegistry {
+    /// Creates a new, empty [`CounterpartyRegistry`].
+    pub fn new() -> Self {
+        Self {
+            comp_ids: HashMap::new(),
+        }
+    }
+
+    /// Registers certificates with CN or SAN DNS name `name` as belonging to
+    /// `comp_id`.
+    pub fn add(&mut self, name: impl Into<String>, comp_id: impl Into<String>) {
+        self.comp_ids.insert(name.into(), comp_id.into());
+    }
+
+    /// Returns the CompID of the first name of `identity` (see
+    /// [`CertificateIdentity::names`]) that is registered in `self`, if any.
+    pub fn comp_id(&self, identity: &CertificateIdentity) -> Option<&str> {
+        identity
+            .names()
+            .find_map(|name| self.comp_ids.get(name))
+            .map(String::as_str)
+    }
+}
+
+impl Default for CounterpartyRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CertificateValidator for CounterpartyRegistry {
+    fn validate(
+        &self,
+        identity: &CertificateIdentity,
+        _der: &[u8],
+    ) -> Result<(), CertificateRejection> {
+        match self.comp_id(identity) {
+            Some(_) => Ok(()),
+            None => Err(CertificateRejection::new(format!(
+                "no counterparty is registered for '{}'",
+                identity.names().next().unwrap_or_default()
+            ))),
+        }
+    }
+}
+
+/// A set of SHA-256 fingerprints of certificates that are known in advance,
+/// e.g. those of a venue. End-entity certificates that aren't pinned are
+/// rejected.
+///
+/// # Examples
+///
+/// ```
+/// use fefixs::{CertificateIdentity, CertificatePins, CertificateValidator};
+///
+/// let der = include_bytes!("../test_data/server.der");
+/// let mut pins = CertificatePins::new();
+/// pins.add_sha256(CertificatePins::fingerprint(&der[..]));
+///
+/// let identity = CertificateIdentity::from_der(&der[..]).unwrap();
+/// assert!(pins.validate(&identity, &der[..]).is_ok());
+/// ```
+#[derive(Debug, Clone)]
+pub struct CertificatePins {
+    fingerprints: HashSet<[u8; 32]>,
+}
+
+impl CertificatePins {
+    /// Creates a new [`CertificatePins`] without any pins, which rejects all
+    /// certificates.
+    pub fn new() -> Self {
+        Self {
+            fingerprints: HashSet::new(),
+        }
+    }
+
+    /// Returns the SHA-256 fingerprint of the DER-encoded certificate `der`.
+    pub fn fingerprint(der: &[u8]) -> [u8; 32] {
+        Sha256::digest(der).into()
+    }
+
+    /// Pins the certificate with SHA-256 fingerprint `fingerprint`.
+    pub fn add_sha256(&mut self, fingerprint: [u8; 32]) {
+        self.fingerprints.insert(fingerprint);
+    }
+
+    /// Returns `true` if the DER-encoded certificate `der` is pinned.
+    pub fn contains(&self, der: &[u8]) -> bool {
+        self.fingerprints.contains(&Self::fingerprint(der))
+    }
+}
+
+impl Default for CertificatePins {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CertificateValidator for CertificatePins {
+    fn validate(
+        &self,
+        identity: &CertificateIdentity,
+        der: &[u8],
+    ) -> Result<(), CertificateRejection> {
+        if self.contains(der) {
+            Ok(())
+        } else {
+            Err(CertificateRejection::new(format!(
+                "the certificate of '{}' isn't pinned",
+                identity.names().next().unwrap_or_default()
+            )))
+        }
+    }
+}
+
+/// Splits the first TLV (tag, length, value) off `data`.
+fn read_tlv(data: &[u8]) -> Option<(u8, &[u8], &[u8])> {
+    let (&tag, rest) = data.split_first()?;
+    let (&first, rest) = rest.split_first()?;
+    let (len, rest) = if first < 0x80 {
+        (first as usize, rest)
+    } else {
+        // Long form, with up to 4 length bytes.
+        let num_bytes = (first & 0x7F) as usize;
+        if num_bytes == 0 || num_bytes > 4 || rest.len() < num_bytes {
+            return None;
+        }
+        let len = rest[..num_bytes]
+            .iter()
+            .fold(0usize, |len, byte| len << 8 | *byte as usize);
+        (len, &rest[num_bytes..])
+    };
+    if rest.len() < len {
+        return None;
+    }
+    Some((tag, &rest[..len], &rest[len..]))
+}
+
+/// Like [`read_tlv`], but fails unless the tag is `expected`.
+fn read_expected(data: &[u8], expected: u8) -> Option<(&[u8], &[u8])> {
+    match read_tlv(data)? {
+        (tag, value, rest) if tag == expected => Some((value, rest)),
+        _ => None,
+    }
+}
+
+fn parse_certificate(der: &[u8]) -> Option<CertificateIdentity> {
+    let (certificate, _) = read_expected(der, SEQUENCE)?;
+    let (tbs, _) = read_expected(certificate, SEQUENCE)?;
+    let mut rest = tbs;
+    if let Some((VERSION, _, after)) = read_tlv(rest) {
+        rest = after;
+    }
+    let (_serial_number, rest) = read_expected(rest, INTEGER)?;
+    let (_signature, rest) = read_expected(rest, SEQUENCE)?;
+    let (_issuer, rest) = read_expected(rest, SEQUENCE)?;
+    let (_validity, rest) = read_expected(rest, SEQUENCE)?;
+    let (subject, rest) = read_expected(rest, SEQUENCE)?;
+    let (_public_key, mut rest) = read_expected(rest, SEQUENCE)?;
+    let common_name = parse_common_name(subject)?;
+    let mut dns_names = Vec::new();
+    while !rest.is_empty() {
+        let (tag, value, after) = read_tlv(rest)?;
+        match tag {
+            ISSUER_UNIQUE_ID | SUBJECT_UNIQUE_ID => {}
+            EXTENSIONS => dns_names = parse_dns_names(value)?,
+            _ => return None,
+        }
+        rest = after;
+    }
+    Some(CertificateIdentity {
+        common_name,
+        dns_names,
+    })
+}
+
+// The last CN wins, as it's usually the most specific one.
+fn parse_common_name(mut name: &[u8]) -> Option<Option<String>> {
+    let mut common_name = None;
+    while !name.is_empty() {
+        let (mut rdn, rest) = read_expected(name, SET)?;
+        while !rdn.is_empty() {
+            let (attribute, after) = read_expected(rdn, SEQUENCE)?;
+            let (oid, value) = read_expected(attribute, OID)?;
+            if oid == OID_COMMON_NAME {
+                common_name = Some(parse_string(value)?);
+            }
+            rdn = after;
+        }
+        name = rest;
+    }
+    Some(common_name)
+}
+
+fn parse_string(data: &[u8]) -> Option<String> {
+    let (tag, value, _) = read_tlv(data)?;
+    match tag {
+        UTF8_STRING | PRINTABLE_STRING | IA5_STRING => {
+            std::str::from_utf8(value).ok().map(str::to_string)
+        }
+        BMP_STRING if value.len() % 2 == 0 => {
+            let units: Vec<u16> = value
+                .chunks_exact(2)
+                .map(|unit| u16::from_be_bytes([unit[0], unit[1]]))
+                .collect();
+            String::from_utf16(&units).ok()
+        }
+        _ => None,
+    }
+}
+
+fn parse_dns_names(extensions: &[u8]) -> Option<Vec<String>> {
+    let (mut extensions, _) = read_expected(extensions, SEQUENCE)?;
+    let mut dns_names = Vec::new();
+    while !extensions.is_empty() {
+        let (extension, rest) = read_expected(extensions, SEQUENCE)?;
+        let (oid, mut value) = read_expected(extension, OID)?;
+        if let Some((BOOLEAN, _critical, after)) = read_tlv(value) {
+            value = after;
+        }
+        if oid == OID_SUBJECT_ALT_NAME {
+            let (value, _) = read_expected(value, OCTET_STRING)?;
+            let (mut names, _) = read_expected(value, SEQUENCE)?;
+            while !names.is_empty() {
+                let (tag, name, after) = read_tlv(names)?;
+                if tag == DNS_NAME {
+                    dns_names.push(std::str::from_utf8(name).ok()?.to_string());
+                }
+                names = after;
+            }
+        }
+        extensions = rest;
+    }
+    Some(dns_names)
+}
+
+/// Makes `context`, an OpenSSL acceptor or connector, require a certificate
+/// from the remote peer and check it with `validator` after the chain has been
+/// verified. Only the end-entity certificate is passed to `validator`.
+#[cfg(feature = "utils-openssl")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "utils-openssl")))]
+pub fn set_openssl_validator(
+    context: &mut openssl::ssl::SslContextBuilder,
+    validator: Arc<dyn CertificateValidator>,
+) {
+    use openssl::ssl::SslVerifyMode;
+    use openssl::x509::X509VerifyResult;
+
+    let mode = SslVerifyMode::PEER | SslVerifyMode::FAIL_IF_NO_PEER_CERT;
+    context.set_verify_callback(mode, move |preverified, store| {
+        if !preverified || store.error_depth() > 0 {
+            return preverified;
+        }
+        let der = match store.current_cert().map(|cert| cert.to_der()) {
+            Some(Ok(der)) => der,
+            _ => return false,
+        };
+        let result = CertificateIdentity::from_der(&der[..])
+            .and_then(|identity| validator.validate(&identity, &der[..]));
+        if result.is_err() {
+            store.set_error(X509VerifyResult::APPLICATION_VERIFICATION);
+        }
+        result.is_ok()
+    });
+}
+
+/// Makes `config` require client certificates signed by `client_roots` and
+/// check them with `validator`, whose [`CertificateRejection`] then aborts the
+/// handshake.
+#[cfg(feature = "utils-rustls")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "utils-rustls")))]
+pub fn set_rustls_acceptor_validator(
+    config: &mut rustls::ServerConfig,
+    client_roots: rustls::RootCertStore,
+    validator: Arc<dyn CertificateValidator>,
+) {
+    config.set_client_certificate_verifier(Arc::new(ValidatingClientVerifier {
+        inner: rustls::AllowAnyAuthenticatedClient::new(client_roots),
+        validator,
+    }));
+}
+
+/// Makes `config` check the certificate of the acceptor with `validator`,
+/// after the usual verification against the root certificates of `config`.
+#[cfg(feature = "utils-rustls")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "utils-rustls")))]
+pub fn set_rustls_initiator_validator(
+    config: &mut rustls::ClientConfig,
+    validator: Arc<dyn CertificateValidator>,
+) {
+    config
+        .dangerous()
+        .set_certificate_verifier(Arc::new(ValidatingServerVerifier {
+            inner: rustls::WebPKIVerifier::new(),
+            validator,
+        }));
+}
+
+#[cfg(feature = "utils-rustls")]
+fn validate_rustls(
+    validator: &dyn CertificateValidator,
+    presented_certs: &[rustls::Certificate],
+) -> Result<(), rustls::TLSError> {
+    let der = match presented_certs.first() {
+        Some(certificate) => &certificate.0[..],
+        None => return Err(rustls::TLSError::NoCertificatesPresented),
+    };
+    CertificateIdentity::from_der(der)
+        .and_then(|identity| validator.validate(&identity, der))
+        .map_err(|rejection| rustls::TLSError::General(rejection.reason))
+}
+
+#[cfg(feature = "utils-rustls")]
+struct ValidatingClientVerifier {
+    inner: Arc<dyn rustls::ClientCertVerifier>,
+    validator: Arc<dyn CertificateValidator>,
+}
+
+#[cfg(feature = "utils-rustls")]
+impl rustls::ClientCertVerifier for ValidatingClientVerifier {
+    fn offer_client_auth(&self) -> bool {
+        self.inner.offer_client_auth()
+    }
+
+    fn client_auth_mandatory(&self, sni: Option<&webpki::DNSName>) -> Option<bool> {
+        self.inner.client_auth_mandatory(sni)
+    }
+
+    fn client_auth_root_subjects(
+        &self,
+        sni: Option<&webpki::DNSName>,
+    ) -> Option<rustls::DistinguishedNames> {
+        self.inner.client_auth_root_subjects(sni)
+    }
+
+    fn verify_client_cert(
+        &self,
+        presented_certs: &[rustls::Certificate],
+        sni: Option<&webpki::DNSName>,
+    ) -> Result<rustls::ClientCertVerified, rustls::TLSError> {
+        let verified = self.inner.verify_client_cert(presented_certs, sni)?;
+        validate_rustls(self.validator.as_ref(), presented_certs)?;
+        Ok(verified)
+    }
+}
+
+#[cfg(feature = "utils-rustls")]
+struct ValidatingServerVerifier {
+    inner: rustls::WebPKIVerifier,
+    validator: Arc<dyn CertificateValidator>,
+}
+
+#[cfg(feature = "utils-rustls")]
+impl rustls::ServerCertVerifier for ValidatingServerVerifier {
+    fn verify_server_cert(
+        &self,
+        roots: &rustls::RootCertStore,
+        presented_certs: &[rustls::Certificate],
+        dns_name: webpki::DNSNameRef,
+        ocsp_response: &[u8],
+    ) -> Result<rustls::ServerCertVerified, rustls::TLSError> {
+        let verified =
+            self.inner
+                .verify_server_cert(roots, presented_certs, dns_name, ocsp_response)?;
+        validate_rustls(self.validator.as_ref(), presented_certs)?;
+        Ok(verified)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const CA: &[u8] = include_bytes!("../test_data/ca.der");
+    const CLIENT: &[u8] = include_bytes!("../test_data/client.der");
+    const SERVER: &[u8] = include_bytes!("../test_data/server.der");
+
+    #[test]
+    fn identities_of_test_certificates() {
+        let identity = CertificateIdentity::from_der(CLIENT).unwrap();
+        assert_eq!(
+            identity.names().collect::<Vec<_>>(),
+            vec![
+                "client-a.example",
+                "client-a.example",
+                "backup.client-a.example"
+            ]
+        );
+        let identity = CertificateIdentity::from_der(SERVER).unwrap();
+        assert_eq!(identity.dns_names(), &["venue.example".to_string()]);
+        let identity = CertificateIdentity::from_der(CA).unwrap();
+        assert_eq!(identity.common_name(), Some("FIXS Test CA"));
+        assert!(identity.dns_names().is_empty());
+    }
+
+    #[test]
+    fn malformed_certificates_are_rejected() {
+        for len in 0..CLIENT.len() {
+            assert!(CertificateIdentity::from_der(&CLIENT[..len]).is_err());
+        }
+        assert!(CertificateIdentity::from_der(b"\x30\x84\xFF\xFF\xFF\xFF").is_err());
+    }
+
+    #[test]
+    fn unknown_counterparties_and_pins() {
+        let identity = CertificateIdentity::from_der(CLIENT).unwrap();
+        let mut registry = CounterpartyRegistry::new();
+        registry.add("venue.example", "VENUE");
+        assert_eq!(
+            registry.validate(&identity, CLIENT),
+            Err(CertificateRejection::new(
+                "no counterparty is registered for 'client-a.example'"
+            ))
+        );
+        let mut pins = CertificatePins::new();
+        pins.add_sha256(CertificatePins::fingerprint(SERVER));
+        assert!(pins.validate(&identity, CLIENT).is_err());
+        assert!(pins.contains(SERVER));
+    }
+
+    #[test]
+    #[cfg(feature = "utils-rustls")]
+    fn rustls_acceptor_rejects_unregistered_clients() {
+        use rustls::ClientCertVerifier;
+
+        let mut roots = rustls::RootCertStore::empty();
+        roots.add(&rustls::Certificate(CA.to_vec())).unwrap();
+        let mut registry = CounterpartyRegistry::new();
+        registry.add("client-a.example", "CLIENT_A");
+        let verifier = |validator: CounterpartyRegistry| ValidatingClientVerifier {
+            inner: rustls::AllowAnyAuthenticatedClient::new(roots.clone()),
+            validator: Arc::new(validator),
+        };
+        let chain = [rustls::Certificate(CLIENT.to_vec())];
+        assert!(verifier(registry).verify_client_cert(&chain, None).is_ok());
+        assert_eq!(
+            verifier(CounterpartyRegistry::new())
+                .verify_client_cert(&chain, None)
+                .err(),
+            Some(rustls::TLSError::General(
+                "no counterparty is registered for 'client-a.example'".to_string()
+            ))
+        );
+        // Not signed by the CA.
+        let chain = [rustls::Certificate(CA.to_vec())];
+        assert!(verifier(CounterpartyRegistry::new())
+            .verify_client_cert(&chain, None)
+            .is_err());
+    }
+}
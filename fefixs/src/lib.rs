@@ -6,12 +6,52 @@
 //! adds a dependency to OpenSSL. The minimal OpenSSL version required is 1.1.1+,
 //! but **this may change in the future** and all users are advised to update
 //! OpenSSL to avoid any issues.
+//!
+//! Users who can't link OpenSSL can enable `utils-rustls` instead, which
+//! provides the same recommended settings on top of
+//! [`rustls`](https://docs.rs/rustls). On top of it, `utils-tokio` provides
+//! asynchronous connections in the [`tokio`](crate::tokio) module.
+//!
+//! # Certificate validation
+//!
+//! Beyond chain verification, acceptors can map client certificates to
+//! counterparties with a [`CounterpartyRegistry`], and initiators can pin the
+//! certificates of venues with [`CertificatePins`]. Both are
+//! [`CertificateValidator`]s, which can abort the TLS handshake with a
+//! [`CertificateRejection`].
+//!
+//! # Profiles
+//!
+//! [`ProfileBuilder`] creates configurations for each [`Profile`], i.e.
+//! server-only authentication, mutual TLS, and pre-shared keys (PSK), with
+//! ciphersuites restricted accordingly.
+
+#![cfg_attr(doc_cfg, feature(doc_cfg))]
 
+mod certificate;
 mod iana_to_openssl;
+mod profile;
+#[cfg(feature = "utils-tokio")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "utils-tokio")))]
+pub mod tokio;
+
+#[cfg(feature = "utils-openssl")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "utils-openssl")))]
+pub use certificate::set_openssl_validator;
+#[cfg(feature = "utils-rustls")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "utils-rustls")))]
+pub use certificate::{set_rustls_acceptor_validator, set_rustls_initiator_validator};
+pub use certificate::{
+    CertificateIdentity, CertificatePins, CertificateRejection, CertificateValidator,
+    CounterpartyRegistry,
+};
+pub use profile::{Profile, ProfileBuilder, ProfileError};
 
 use iana_to_openssl::IANA_TO_OPENSSL;
 #[cfg(feature = "utils-openssl")]
 use openssl::ssl::*;
+#[cfg(feature = "utils-rustls")]
+use std::sync::Arc;
 
 /// Which version of FIX-over-TLS (FIXS) to use.
 #[derive(Debug, Copy, Clone)]
@@ -71,16 +111,34 @@ impl Version {
     /// println!("Supported ciphers: {}", cipherlist);
     /// ```
     pub fn recommended_cs_openssl(&self, psk: bool) -> Vec<String> {
-        self.recommended_cs_iana(psk)
-            .iter()
-            .map(|s| {
-                IANA_TO_OPENSSL
-                    .iter()
-                    .find(|(iana, _openssl)| iana == s)
-                    .map(|(_iana, openssl)| openssl.to_string())
-                    .unwrap()
-            })
-            .collect()
+        iana_to_openssl(&self.recommended_cs_iana(psk)[..])
+    }
+
+    /// Returns a [`Vec`] of the suggested ciphersuites for TLS, according to
+    /// `self` version, that `profile` can use. The ciphersuites are specified
+    /// in IANA format. Unlike [`Version::recommended_cs_iana`], PSK
+    /// ciphersuites are never mixed with certificate-based ones.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefixs::{Profile, Version};
+    ///
+    /// let ciphersuites_iana = Version::V1Draft.recommended_cs_iana_for(Profile::Psk);
+    /// assert!(ciphersuites_iana.iter().any(|cs| cs == &"TLS_DHE_PSK_WITH_AES_128_GCM_SHA256"));
+    /// ```
+    pub fn recommended_cs_iana_for(&self, profile: Profile) -> Vec<String> {
+        let ciphersuites = match (self, profile) {
+            (Version::V1Draft, Profile::Psk) => V1_DRAFT_RECOMMENDED_CIPHERSUITES_PSK_ONLY,
+            (Version::V1Draft, _) => V1_DRAFT_RECOMMENDED_CIPHERSUITES,
+        };
+        ciphersuites.iter().map(|s| s.to_string()).collect()
+    }
+
+    /// Like [`Version::recommended_cs_iana_for`], but the ciphersuites are
+    /// specified in OpenSSL's format.
+    pub fn recommended_cs_openssl_for(&self, profile: Profile) -> Vec<String> {
+        iana_to_openssl(&self.recommended_cs_iana_for(profile)[..])
     }
 
     /// Creates an [`SslConnectorBuilder`] with fhe FIXS recommended settings.
@@ -125,6 +183,90 @@ impl Version {
             .unwrap();
         context
     }
+
+    /// Returns the ciphersuites supported by `rustls` that `self` recommends.
+    /// `rustls` only implements a subset of the FIXS recommended ciphersuites
+    /// for TLS 1.2, which is then complemented by all TLS 1.3 ciphersuites.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefixs::Version;
+    ///
+    /// let ciphersuites = Version::V1Draft.recommended_cs_rustls();
+    /// assert!(ciphersuites
+    ///     .iter()
+    ///     .any(|cs| format!("{:?}", cs.suite) == "TLS_ECDHE_RSA_WITH_AES_128_GCM_SHA256"));
+    /// ```
+    #[cfg(feature = "utils-rustls")]
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "utils-rustls")))]
+    pub fn recommended_cs_rustls(&self) -> Vec<&'static rustls::SupportedCipherSuite> {
+        let recommended = self.recommended_cs_iana(false);
+        rustls::ALL_CIPHERSUITES
+            .iter()
+            .copied()
+            .filter(|cs| {
+                cs.usable_for_version(rustls::ProtocolVersion::TLSv1_3)
+                    || recommended.contains(&format!("{:?}", cs.suite))
+            })
+            .collect()
+    }
+
+    /// Creates a [`rustls::ClientConfig`] with the FIXS recommended settings.
+    /// Root certificates must still be added by the caller.
+    #[cfg(feature = "utils-rustls")]
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "utils-rustls")))]
+    pub fn recommended_rustls_client_config(&self) -> rustls::ClientConfig {
+        let mut config = rustls::ClientConfig::with_ciphersuites(&self.recommended_cs_rustls());
+        config.versions = self.rustls_versions();
+        config.enable_tickets = false;
+        config
+    }
+
+    /// Creates a [`rustls::ServerConfig`] with the FIXS recommended settings.
+    /// Clients must authenticate themselves with a certificate signed by
+    /// `client_roots` if provided, otherwise client authentication is
+    /// disabled. The server certificate must still be set by the caller.
+    #[cfg(feature = "utils-rustls")]
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "utils-rustls")))]
+    pub fn recommended_rustls_server_config(
+        &self,
+        client_roots: Option<rustls::RootCertStore>,
+    ) -> rustls::ServerConfig {
+        let verifier = match client_roots {
+            Some(roots) => rustls::AllowAnyAuthenticatedClient::new(roots),
+            None => rustls::NoClientAuth::new(),
+        };
+        let mut config =
+            rustls::ServerConfig::with_ciphersuites(verifier, &self.recommended_cs_rustls());
+        config.versions = self.rustls_versions();
+        config.ignore_client_order = true;
+        config.set_persistence(Arc::new(rustls::NoServerSessionStorage {}));
+        config
+    }
+
+    #[cfg(feature = "utils-rustls")]
+    fn rustls_versions(&self) -> Vec<rustls::ProtocolVersion> {
+        match self {
+            Version::V1Draft => vec![
+                rustls::ProtocolVersion::TLSv1_3,
+                rustls::ProtocolVersion::TLSv1_2,
+            ],
+        }
+    }
+}
+
+fn iana_to_openssl(ciphersuites: &[String]) -> Vec<String> {
+    ciphersuites
+        .iter()
+        .map(|s| {
+            IANA_TO_OPENSSL
+                .iter()
+                .find(|(iana, _openssl)| iana == s)
+                .map(|(_iana, openssl)| openssl.to_string())
+                .unwrap()
+        })
+        .collect()
 }
 
 const V1_DRAFT_RECOMMENDED_CIPHERSUITES: &[&str] = &[
@@ -176,4 +318,30 @@ mod test {
     fn v1draft_connector_is_ok() {
         Version::V1Draft.recommended_connector_builder();
     }
+
+    #[test]
+    #[cfg(feature = "utils-rustls")]
+    fn v1draft_rustls_ciphersuites_are_recommended_or_tls13() {
+        let recommended = Version::V1Draft.recommended_cs_iana(false);
+        let ciphersuites = Version::V1Draft.recommended_cs_rustls();
+        assert!(ciphersuites
+            .iter()
+            .any(|cs| cs.usable_for_version(rustls::ProtocolVersion::TLSv1_2)));
+        for cs in ciphersuites {
+            assert!(
+                cs.usable_for_version(rustls::ProtocolVersion::TLSv1_3)
+                    || recommended.contains(&format!("{:?}", cs.suite))
+            );
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "utils-rustls")]
+    fn v1draft_rustls_configs_are_ok() {
+        let client = Version::V1Draft.recommended_rustls_client_config();
+        assert!(!client.versions.contains(&rustls::ProtocolVersion::TLSv1_1));
+        let server = Version::V1Draft.recommended_rustls_server_config(None);
+        assert_eq!(server.ciphersuites.len(), client.ciphersuites.len());
+        Version::V1Draft.recommended_rustls_server_config(Some(rustls::RootCertStore::empty()));
+    }
 }
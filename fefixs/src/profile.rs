@@ -0,0 +1,371 @@
+use crate::{CertificateValidator, Version};
+use std::fmt;
+use std::sync::Arc;
+
+/// How the two peers of a FIXS connection authenticate each other.
+///
+/// Please note that [`Profile`] is marked with `#[non_exhaustive]`, which
+/// future-proofs the enumeration type in case more variants are added.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Profile {
+    /// Only the acceptor presents a certificate.
+    ServerAuth,
+    /// Both peers present a certificate, issued by roots they trust.
+    MutualAuth,
+    /// Both peers prove they know a pre-shared key (PSK), and no
+    /// certificates are exchanged. Only TLS 1.2 supports PSK ciphersuites.
+    Psk,
+}
+
+/// The error type returned by [`ProfileBuilder`] when a configuration can't be
+/// built.
+///
+/// Please note that [`ProfileError`] is marked with `#[non_exhaustive]`, which
+/// future-proofs the enumeration type in case more variants are added.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ProfileError {
+    /// The TLS backend doesn't support the [`Profile`], e.g. `rustls` has no
+    /// PSK ciphersuites.
+    Unsupported {
+        /// The requested profile.
+        profile: Profile,
+        /// The name of the TLS backend.
+        backend: &'static str,
+    },
+    /// [`Profile::MutualAuth`] acceptors need at least one root certificate
+    /// for client certificates, see [`ProfileBuilder::add_client_root_der`].
+    MissingClientRoots,
+    /// [`Profile::Psk`] needs at least one key, see [`ProfileBuilder::add_psk`].
+    MissingPsk,
+    /// The TLS backend refused a setting or a certificate.
+    Backend(String),
+}
+
+impl fmt::Display for ProfileError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Unsupported { profile, backend } => {
+                write!(f, "{} doesn't support the {:?} profile", backend, profile)
+            }
+            Self::MissingClientRoots => write!(f, "No root certificates for clients"),
+            Self::MissingPsk => write!(f, "No pre-shared keys"),
+            Self::Backend(err) => write!(f, "TLS backend error: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for ProfileError {}
+
+/// Builds TLS configurations that follow the recommendations of a FIXS
+/// [`Version`] for a [`Profile`], for both OpenSSL and `rustls`.
+/// Ciphersuites are restricted to those that the profile can use, e.g. only
+/// PSK ciphersuites for [`Profile::Psk`].
+///
+/// Local certificates and private keys must still be set by the caller, on the
+/// configuration returned by the builder.
+///
+/// # Examples
+///
+/// ```
+/// use fefixs::{Profile, ProfileBuilder, Version};
+///
+/// let mut builder = ProfileBuilder::new(Version::V1Draft, Profile::Psk);
+/// builder.add_psk(b"CLIENT_A", &[0x42; 32]);
+/// assert!(builder
+///     .cipher_suites_iana()
+///     .iter()
+///     .all(|cs| cs.contains("_PSK_")));
+/// ```
+#[derive(Clone)]
+pub struct ProfileBuilder {
+    version: Version,
+    profile: Profile,
+    client_roots: Vec<Vec<u8>>,
+    psks: Vec<(Vec<u8>, Vec<u8>)>,
+    validator: Option<Arc<dyn CertificateValidator>>,
+}
+
+impl ProfileBuilder {
+    /// Creates a new [`ProfileBuilder`] for `profile` under `version`.
+    pub fn new(version: Version, profile: Profile) -> Self {
+        Self {
+            version,
+            profile,
+            client_roots: Vec::new(),
+            psks: Vec::new(),
+            validator: None,
+        }
+    }
+
+    /// Returns the [`Profile`] of `self`.
+    pub fn profile(&self) -> Profile {
+        self.profile
+    }
+
+    /// Trusts the DER-encoded root certificate `der` for client certificates.
+    /// Only [`Profile::MutualAuth`] acceptors use it.
+    pub fn add_client_root_der(&mut self, der: &[u8]) {
+        self.client_roots.push(der.to_vec());
+    }
+
+    /// Adds a pre-shared `key` for the PSK `identity`. Acceptors accept all
+    /// added keys, while initiators always use the first one. Only
+    /// [`Profile::Psk`] uses it.
+    pub fn add_psk(&mut self, identity: &[u8], key: &[u8]) {
+        self.psks.push((identity.to_vec(), key.to_vec()));
+    }
+
+    /// Checks the certificate of the remote peer with `validator`. Initiators
+    /// always use it, acceptors only with [`Profile::MutualAuth`].
+    pub fn set_validator(&mut self, validator: Arc<dyn CertificateValidator>) {
+        self.validator = Some(validator);
+    }
+
+    /// Returns the ciphersuites that `self` allows, in IANA format.
+    pub fn cipher_suites_iana(&self) -> Vec<String> {
+        self.version.recommended_cs_iana_for(self.profile)
+    }
+
+    /// Creates a [`rustls::ClientConfig`] for initiators. Root certificates
+    /// and, with [`Profile::MutualAuth`], the client certificate must still be
+    /// added by the caller.
+    #[cfg(feature = "utils-rustls")]
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "utils-rustls")))]
+    pub fn rustls_client_config(&self) -> Result<rustls::ClientConfig, ProfileError> {
+        self.check_rustls()?;
+        let mut config = self.version.recommended_rustls_client_config();
+        if let Some(validator) = self.validator.clone() {
+            crate::set_rustls_initiator_validator(&mut config, validator);
+        }
+        Ok(config)
+    }
+
+    /// Creates a [`rustls::ServerConfig`] for acceptors. The server
+    /// certificate must still be set by the caller.
+    #[cfg(feature = "utils-rustls")]
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "utils-rustls")))]
+    pub fn rustls_server_config(&self) -> Result<rustls::ServerConfig, ProfileError> {
+        self.check_rustls()?;
+        if self.profile != Profile::MutualAuth {
+            return Ok(self.version.recommended_rustls_server_config(None));
+        }
+        let mut roots = rustls::RootCertStore::empty();
+        for der in self.client_roots.iter() {
+            roots
+                .add(&rustls::Certificate(der.clone()))
+                .map_err(|err| ProfileError::Backend(format!("{:?}", err)))?;
+        }
+        if roots.is_empty() {
+            return Err(ProfileError::MissingClientRoots);
+        }
+        let mut config = self
+            .version
+            .recommended_rustls_server_config(Some(roots.clone()));
+        if let Some(validator) = self.validator.clone() {
+            crate::set_rustls_acceptor_validator(&mut config, roots, validator);
+        }
+        Ok(config)
+    }
+
+    #[cfg(feature = "utils-rustls")]
+    fn check_rustls(&self) -> Result<(), ProfileError> {
+        match self.profile {
+            Profile::Psk => Err(ProfileError::Unsupported {
+                profile: self.profile,
+                backend: "rustls",
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Creates an [`SslConnectorBuilder`](openssl::ssl::SslConnectorBuilder)
+    /// for initiators. With [`Profile::MutualAuth`], the client certificate
+    /// must still be set by the caller.
+    #[cfg(feature = "utils-openssl")]
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "utils-openssl")))]
+    pub fn openssl_connector_builder(
+        &self,
+    ) -> Result<openssl::ssl::SslConnectorBuilder, ProfileError> {
+        let mut builder = self.version.recommended_connector_builder();
+        if self.profile == Profile::Psk {
+            let (identity, key) = self.psks.first().cloned().ok_or(ProfileError::MissingPsk)?;
+            self.restrict_to_psk(&mut builder)?;
+            builder.set_psk_client_callback(move |_ssl, _hint, identity_out, key_out| {
+                // The identity is a NUL-terminated C string.
+                if identity.len() >= identity_out.len() || key.len() > key_out.len() {
+                    return Ok(0);
+                }
+                identity_out[..identity.len()].copy_from_slice(&identity[..]);
+                identity_out[identity.len()] = 0;
+                key_out[..key.len()].copy_from_slice(&key[..]);
+                Ok(key.len())
+            });
+        } else if let Some(validator) = self.validator.clone() {
+            crate::set_openssl_validator(&mut builder, validator);
+        }
+        Ok(builder)
+    }
+
+    /// Creates an [`SslAcceptorBuilder`](openssl::ssl::SslAcceptorBuilder) for
+    /// acceptors. The server certificate and private key must still be set by
+    /// the caller, except with [`Profile::Psk`].
+    #[cfg(feature = "utils-openssl")]
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "utils-openssl")))]
+    pub fn openssl_acceptor_builder(
+        &self,
+    ) -> Result<openssl::ssl::SslAcceptorBuilder, ProfileError> {
+        use openssl::ssl::SslVerifyMode;
+
+        let mut builder = self.version.recommended_acceptor_builder();
+        match self.profile {
+            Profile::ServerAuth => {}
+            Profile::MutualAuth => {
+                if self.client_roots.is_empty() {
+                    return Err(ProfileError::MissingClientRoots);
+                }
+                for der in self.client_roots.iter() {
+                    let root = openssl::x509::X509::from_der(&der[..]).map_err(backend_error)?;
+                    builder
+                        .cert_store_mut()
+                        .add_cert(root)
+                        .map_err(backend_error)?;
+                }
+                match self.validator.clone() {
+                    Some(validator) => crate::set_openssl_validator(&mut builder, validator),
+                    None => builder
+                        .set_verify(SslVerifyMode::PEER | SslVerifyMode::FAIL_IF_NO_PEER_CERT),
+                }
+            }
+            Profile::Psk => {
+                if self.psks.is_empty() {
+                    return Err(ProfileError::MissingPsk);
+                }
+                self.restrict_to_psk(&mut builder)?;
+                let psks = self.psks.clone();
+                builder.set_psk_server_callback(move |_ssl, identity, key_out| {
+                    let key = psks
+                        .iter()
+                        .find(|(known, _)| Some(&known[..]) == identity)
+                        .map(|(_, key)| key);
+                    match key {
+                        Some(key) if key.len() <= key_out.len() => {
+                            key_out[..key.len()].copy_from_slice(&key[..]);
+                            Ok(key.len())
+                        }
+                        // Zero aborts the handshake.
+                        _ => Ok(0),
+                    }
+                });
+            }
+        }
+        Ok(builder)
+    }
+
+    #[cfg(feature = "utils-openssl")]
+    fn restrict_to_psk(
+        &self,
+        builder: &mut openssl::ssl::SslContextBuilder,
+    ) -> Result<(), ProfileError> {
+        use openssl::ssl::{SslVerifyMode, SslVersion};
+
+        builder
+            .set_max_proto_version(Some(SslVersion::TLS1_2))
+            .map_err(backend_error)?;
+        builder
+            .set_cipher_list(
+                self.version
+                    .recommended_cs_openssl_for(Profile::Psk)
+                    .join(":")
+                    .as_str(),
+            )
+            .map_err(backend_error)?;
+        builder.set_verify(SslVerifyMode::NONE);
+        Ok(())
+    }
+}
+
+impl fmt::Debug for ProfileBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Keys are secret.
+        let identities: Vec<String> = self
+            .psks
+            .iter()
+            .map(|(identity, _)| String::from_utf8_lossy(identity).into_owned())
+            .collect();
+        f.debug_struct("ProfileBuilder")
+            .field("version", &self.version)
+            .field("profile", &self.profile)
+            .field("client_roots", &self.client_roots.len())
+            .field("psk_identities", &identities)
+            .field("validator", &self.validator.is_some())
+            .finish()
+    }
+}
+
+#[cfg(feature = "utils-openssl")]
+fn backend_error(err: openssl::error::ErrorStack) -> ProfileError {
+    ProfileError::Backend(err.to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn profiles_restrict_ciphersuites() {
+        let psk = Version::V1Draft.recommended_cs_iana_for(Profile::Psk);
+        let server_auth = Version::V1Draft.recommended_cs_iana_for(Profile::ServerAuth);
+        assert!(psk.iter().all(|cs| cs.contains("_PSK_")));
+        assert!(server_auth.iter().all(|cs| !cs.contains("_PSK_")));
+        assert_eq!(
+            server_auth,
+            Version::V1Draft.recommended_cs_iana_for(Profile::MutualAuth)
+        );
+        assert_eq!(
+            Version::V1Draft
+                .recommended_cs_openssl_for(Profile::Psk)
+                .len(),
+            psk.len()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "utils-rustls")]
+    fn rustls_configs() {
+        let builder = ProfileBuilder::new(Version::V1Draft, Profile::Psk);
+        assert_eq!(
+            builder.rustls_client_config().err(),
+            Some(ProfileError::Unsupported {
+                profile: Profile::Psk,
+                backend: "rustls"
+            })
+        );
+        let mut builder = ProfileBuilder::new(Version::V1Draft, Profile::MutualAuth);
+        assert_eq!(
+            builder.rustls_server_config().err(),
+            Some(ProfileError::MissingClientRoots)
+        );
+        builder.add_client_root_der(include_bytes!("../test_data/ca.der"));
+        builder.set_validator(Arc::new(crate::CounterpartyRegistry::new()));
+        assert!(builder.rustls_server_config().is_ok());
+        assert!(builder.rustls_client_config().is_ok());
+    }
+
+    #[test]
+    #[cfg(feature = "utils-openssl")]
+    fn openssl_builders() {
+        let mut builder = ProfileBuilder::new(Version::V1Draft, Profile::Psk);
+        assert_eq!(
+            builder.openssl_acceptor_builder().err(),
+            Some(ProfileError::MissingPsk)
+        );
+        builder.add_psk(b"CLIENT_A", &[0x42; 32]);
+        builder.openssl_acceptor_builder().unwrap();
+        builder.openssl_connector_builder().unwrap();
+        let mut builder = ProfileBuilder::new(Version::V1Draft, Profile::MutualAuth);
+        builder.add_client_root_der(include_bytes!("../test_data/ca.der"));
+        builder.openssl_acceptor_builder().unwrap();
+    }
+}
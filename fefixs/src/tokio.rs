@@ -0,0 +1,193 @@
+//! Asynchronous FIXS connections on top of [Tokio](https://docs.rs/tokio) and
+//! [`tokio-rustls`](https://docs.rs/tokio-rustls).
+//!
+//! Both sides return their TLS stream split into a [`Reader`] and a
+//! [`Writer`], which implement the `futures` I/O traits that the session
+//! drivers of `fefix` expect, e.g. `fefix::session::Initiator::run`.
+//! Configurations usually come from
+//! [`Version::recommended_rustls_client_config`](crate::Version::recommended_rustls_client_config)
+//! and
+//! [`Version::recommended_rustls_server_config`](crate::Version::recommended_rustls_server_config),
+//! optionally with a [`CertificateValidator`](crate::CertificateValidator).
+
+use crate::CertificateIdentity;
+use ::tokio::io::{split, ReadHalf, WriteHalf};
+use ::tokio::net::{TcpListener, TcpStream, ToSocketAddrs};
+use rustls::Session;
+use std::io;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio_util::compat::{Compat, TokioAsyncReadCompatExt, TokioAsyncWriteCompatExt};
+
+/// The TLS stream of an initiator.
+pub type ClientStream = tokio_rustls::client::TlsStream<TcpStream>;
+
+/// The TLS stream of an acceptor.
+pub type ServerStream = tokio_rustls::server::TlsStream<TcpStream>;
+
+/// The reading half of a TLS stream, as a [`futures::AsyncRead`](https://docs.rs/futures/0.3/futures/io/trait.AsyncRead.html).
+pub type Reader<S> = Compat<ReadHalf<S>>;
+
+/// The writing half of a TLS stream, as a [`futures::AsyncWrite`](https://docs.rs/futures/0.3/futures/io/trait.AsyncWrite.html).
+pub type Writer<S> = Compat<WriteHalf<S>>;
+
+/// Opens a TCP connection to `addr` and performs the TLS handshake with
+/// `config`. `domain` is the DNS name that the certificate of the acceptor must
+/// be valid for.
+///
+/// The returned halves can be handed over as-is by the `connect` closure of
+/// `fefix::session::Initiator::run`.
+pub async fn connect<A>(
+    addr: A,
+    domain: &str,
+    config: Arc<rustls::ClientConfig>,
+) -> io::Result<(Reader<ClientStream>, Writer<ClientStream>)>
+where
+    A: ToSocketAddrs,
+{
+    let domain = webpki::DNSNameRef::try_from_ascii_str(domain)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "invalid DNS name"))?;
+    let tcp = TcpStream::connect(addr).await?;
+    tcp.set_nodelay(true)?;
+    let stream = tokio_rustls::TlsConnector::from(config)
+        .connect(domain, tcp)
+        .await?;
+    let (reader, writer) = split(stream);
+    Ok((reader.compat(), writer.compat_write()))
+}
+
+/// Waits for the next TCP connection on `listener` and performs the TLS
+/// handshake with `config`. Handshake failures, e.g. because of a
+/// [`CertificateRejection`](crate::CertificateRejection), are returned as
+/// errors; the listener remains usable.
+pub async fn accept(
+    listener: &TcpListener,
+    config: Arc<rustls::ServerConfig>,
+) -> io::Result<Accepted> {
+    let (tcp, peer_addr) = listener.accept().await?;
+    tcp.set_nodelay(true)?;
+    let stream = tokio_rustls::TlsAcceptor::from(config).accept(tcp).await?;
+    let identity = stream
+        .get_ref()
+        .1
+        .get_peer_certificates()
+        .and_then(|certificates| certificates.into_iter().next())
+        .and_then(|certificate| CertificateIdentity::from_der(&certificate.0[..]).ok());
+    let (reader, writer) = split(stream);
+    Ok(Accepted {
+        reader: reader.compat(),
+        writer: writer.compat_write(),
+        peer_addr,
+        identity,
+    })
+}
+
+/// A connection returned by [`accept`].
+#[derive(Debug)]
+pub struct Accepted {
+    reader: Reader<ServerStream>,
+    writer: Writer<ServerStream>,
+    peer_addr: SocketAddr,
+    identity: Option<CertificateIdentity>,
+}
+
+impl Accepted {
+    /// Returns the address of the initiator.
+    pub fn peer_addr(&self) -> SocketAddr {
+        self.peer_addr
+    }
+
+    /// Returns the names in the certificate of the initiator, if it sent one.
+    /// Acceptors can check them against the `SenderCompID <49>` of the `Logon
+    /// <A>` with a [`CounterpartyRegistry`](crate::CounterpartyRegistry).
+    pub fn identity(&self) -> Option<&CertificateIdentity> {
+        self.identity.as_ref()
+    }
+
+    /// Returns the reading and writing halves of the TLS stream.
+    pub fn into_split(self) -> (Reader<ServerStream>, Writer<ServerStream>) {
+        (self.reader, self.writer)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{set_rustls_acceptor_validator, CounterpartyRegistry, Version};
+    use futures::{AsyncReadExt, AsyncWriteExt};
+
+    const CA: &[u8] = include_bytes!("../test_data/ca.der");
+
+    fn certificate_and_key(name: &str) -> (Vec<rustls::Certificate>, rustls::PrivateKey) {
+        let base = concat!(env!("CARGO_MANIFEST_DIR"), "/test_data/");
+        let certificate = std::fs::read(format!("{}{}.der", base, name)).unwrap();
+        let key = std::fs::read(format!("{}{}.key.der", base, name)).unwrap();
+        (
+            vec![rustls::Certificate(certificate)],
+            rustls::PrivateKey(key),
+        )
+    }
+
+    fn roots() -> rustls::RootCertStore {
+        let mut roots = rustls::RootCertStore::empty();
+        roots.add(&rustls::Certificate(CA.to_vec())).unwrap();
+        roots
+    }
+
+    fn configs(comp_ids: &[(&str, &str)]) -> (rustls::ClientConfig, rustls::ServerConfig) {
+        let mut client = Version::V1Draft.recommended_rustls_client_config();
+        client.root_store = roots();
+        let (certificates, key) = certificate_and_key("client");
+        client.set_single_client_cert(certificates, key).unwrap();
+        let mut server = Version::V1Draft.recommended_rustls_server_config(None);
+        let (certificates, key) = certificate_and_key("server");
+        server.set_single_cert(certificates, key).unwrap();
+        let mut registry = CounterpartyRegistry::new();
+        for (name, comp_id) in comp_ids {
+            registry.add(*name, *comp_id);
+        }
+        set_rustls_acceptor_validator(&mut server, roots(), Arc::new(registry));
+        (client, server)
+    }
+
+    #[::tokio::test]
+    async fn mutually_authenticated_roundtrip() {
+        let (client, server) = configs(&[("client-a.example", "CLIENT_A")]);
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let acceptor = ::tokio::spawn(async move {
+            let accepted = accept(&listener, Arc::new(server)).await.unwrap();
+            let identity = accepted.identity().cloned();
+            let (mut reader, _writer) = accepted.into_split();
+            let mut data = Vec::new();
+            reader.read_to_end(&mut data).await.unwrap();
+            (identity, data)
+        });
+        let (_reader, mut writer) = connect(addr, "venue.example", Arc::new(client))
+            .await
+            .unwrap();
+        writer.write_all(b"8=FIX.4.4\x01").await.unwrap();
+        writer.close().await.unwrap();
+        let (identity, data) = acceptor.await.unwrap();
+        assert_eq!(identity.unwrap().common_name(), Some("client-a.example"));
+        assert_eq!(&data[..], b"8=FIX.4.4\x01");
+    }
+
+    #[::tokio::test]
+    async fn unregistered_clients_fail_the_handshake() {
+        let (client, server) = configs(&[]);
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let acceptor =
+            ::tokio::spawn(async move { accept(&listener, Arc::new(server)).await.err() });
+        // The client only notices once it reads the alert.
+        if let Ok((mut reader, _writer)) = connect(addr, "venue.example", Arc::new(client)).await {
+            let mut data = Vec::new();
+            assert!(reader.read_to_end(&mut data).await.is_err());
+        }
+        let err = acceptor.await.unwrap().unwrap();
+        assert!(err
+            .to_string()
+            .contains("no counterparty is registered for 'client-a.example'"));
+    }
+}
@@ -119,12 +119,12 @@ impl PartialEq for EncodingType {
     }
 }
 
-impl std::cmp::Eq for EncodingType {}
+impl core::cmp::Eq for EncodingType {}
 
-impl std::hash::Hash for EncodingType {
+impl core::hash::Hash for EncodingType {
     fn hash<H>(&self, state: &mut H)
     where
-        H: std::hash::Hasher,
+        H: core::hash::Hasher,
     {
         u16::from(*self).hash(state)
     }
@@ -216,6 +216,44 @@ mod test {
         }
     }
 
+    #[test]
+    fn registered_encoding_types_have_registry_values() {
+        let registry = [
+            (EncodingType::Private(0x01), 0x0001),
+            (EncodingType::Private(0xFF), 0x00FF),
+            (EncodingType::Protobuf, 0x4700),
+            (EncodingType::SimpleBinaryEncodingV10BE, 0x5BE0),
+            (EncodingType::Asn1PER, 0xA500),
+            (EncodingType::Asn1BER, 0xA501),
+            (EncodingType::Asn1OER, 0xA502),
+            (EncodingType::SimpleBinaryEncodingV10LE, 0xEB50),
+            (EncodingType::TagValue, 0xF000),
+            (EncodingType::FixmlSchema, 0xF100),
+            (EncodingType::Json, 0xF500),
+            (EncodingType::Fast(0x01), 0xFA01),
+            (EncodingType::Fast(0xFF), 0xFAFF),
+            (EncodingType::Bson, 0xFB00),
+        ];
+        for (etype, value) in registry.iter().copied() {
+            assert_eq!(u16::from(etype), value);
+            let etype_after = EncodingType::from(value);
+            assert!(!matches!(etype_after, EncodingType::Unknown(_)));
+            assert_eq!(format!("{:?}", etype_after), format!("{:?}", etype));
+        }
+    }
+
+    #[test]
+    fn unregistered_values_are_unknown() {
+        for value in &[
+            0x0000, 0x0100, 0x4701, 0x5BE1, 0xA503, 0xF001, 0xFA00, 0xFFFF,
+        ] {
+            assert!(matches!(
+                EncodingType::from(*value),
+                EncodingType::Unknown(x) if x == *value
+            ));
+        }
+    }
+
     #[test]
     fn encoding_types_with_ranges_use_prefix_tagging() {
         assert_eq!(EncodingType::Private(42).to_bytes()[1], 42);
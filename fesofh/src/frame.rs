@@ -1,4 +1,6 @@
 use super::{Error, Header};
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
 use std::io;
 
 const MAX_MESSAGE_SIZE_IN_BYTES: usize = u32::MAX as usize - Header::LENGTH_IN_BYTES;
@@ -76,6 +78,22 @@ where
         self.payload.as_ref()
     }
 
+    /// Consumes `self` and returns its encoding type and payload.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fesofh::Frame;
+    ///
+    /// let frame = Frame::new(0x5BE0, vec![1u8, 2, 3]);
+    /// let (encoding_type, payload) = frame.into_parts();
+    /// assert_eq!(encoding_type, 0x5BE0);
+    /// assert_eq!(payload, vec![1, 2, 3]);
+    /// ```
+    pub fn into_parts(self) -> (u16, T) {
+        (self.encoding_type, self.payload)
+    }
+
     /// Deserializes a [`Frame<&[u8]>`] from `data`. Returns an `Err` if
     /// invalid. Zero-copy.
     ///
@@ -94,10 +112,13 @@ where
     /// ```
     pub fn deserialize(data: &[u8]) -> Result<Frame<&[u8]>, Error> {
         let header = Header::from_bytes(data)?;
-        Ok(Frame::new(
-            header.encoding_type,
-            &data[Header::LENGTH_IN_BYTES..header.nominal_message_length_in_bytes],
-        ))
+        let len = header.nominal_message_length_in_bytes;
+        match data.get(Header::LENGTH_IN_BYTES..len) {
+            Some(payload) => Ok(Frame::new(header.encoding_type, payload)),
+            None => Err(Error::Incomplete {
+                needed: len - data.len(),
+            }),
+        }
     }
 
     /// Serializes `self` to a `Writer`. This requires copying and thus is
@@ -118,18 +139,45 @@ where
     /// frame.serialize(buffer).unwrap();
     /// assert_eq!(&buffer[..], bytes);
     /// ```
+    #[cfg(feature = "std")]
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "std")))]
     pub fn serialize<W>(&self, writer: &mut W) -> io::Result<usize>
     where
         W: io::Write,
     {
-        let nominal_message_length_in_bytes = self.payload().len() + Header::LENGTH_IN_BYTES;
-        let header = Header {
-            nominal_message_length_in_bytes,
-            encoding_type: self.encoding_type,
-        };
+        let header = self.header();
         writer.write_all(&header.to_bytes())?;
         writer.write_all(self.payload())?;
-        Ok(nominal_message_length_in_bytes)
+        Ok(header.nominal_message_length_in_bytes)
+    }
+
+    /// Serializes `self` at the end of `buffer` and returns the number of bytes
+    /// written. Unlike [`Frame::serialize`], it's available without the `std`
+    /// feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fesofh::Frame;
+    ///
+    /// let mut buffer = vec![];
+    /// let len = Frame::new(0x0, &[42u8] as &[u8]).serialize_to_vec(&mut buffer);
+    /// assert_eq!(len, 7);
+    /// assert_eq!(&buffer[..], &[0, 0, 0, 7, 0x0, 0x0, 42]);
+    /// ```
+    pub fn serialize_to_vec(&self, buffer: &mut Vec<u8>) -> usize {
+        let header = self.header();
+        buffer.reserve(header.nominal_message_length_in_bytes);
+        buffer.extend_from_slice(&header.to_bytes());
+        buffer.extend_from_slice(self.payload());
+        header.nominal_message_length_in_bytes
+    }
+
+    fn header(&self) -> Header {
+        Header {
+            nominal_message_length_in_bytes: self.payload().len() + Header::LENGTH_IN_BYTES,
+            encoding_type: self.encoding_type,
+        }
     }
 }
 
@@ -193,6 +241,14 @@ mod test {
         ));
     }
 
+    #[test]
+    fn decode_incomplete_payload() {
+        assert!(matches!(
+            Frame::<&[u8]>::deserialize(&[0, 0, 0, 9, 0, 0, 1]),
+            Err(Error::Incomplete { needed: 2 })
+        ));
+    }
+
     #[test]
     fn decode_empty_message() {
         let frame = Frame::<&[u8]>::deserialize(&[0, 0, 0, 6, 0, 0]).unwrap();
@@ -205,11 +261,22 @@ mod test {
         fn prop(encoding_type: u16, payload: Vec<u8>) -> bool {
             let frame = Frame::<&[u8]>::new(encoding_type, &payload[..]);
             let mut buffer = vec![];
-            frame.serialize(&mut buffer).unwrap();
+            frame.serialize_to_vec(&mut buffer);
             let frame_decoded = Frame::<&[u8]>::deserialize(&buffer[..]).unwrap();
             frame_decoded.encoding_type() == encoding_type
                 && frame_decoded.payload() == &payload[..]
         }
         QuickCheck::new().quickcheck(prop as fn(u16, Vec<u8>) -> bool)
     }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn serialize_is_consistent_with_serialize_to_vec() {
+        let frame = Frame::new(0xF000, b"foobar" as &[u8]);
+        let mut buffer = vec![0xFF];
+        let mut writer = vec![0xFF];
+        assert_eq!(frame.serialize_to_vec(&mut buffer), 12);
+        assert_eq!(frame.serialize(&mut writer).unwrap(), 12);
+        assert_eq!(buffer, writer);
+    }
 }
@@ -0,0 +1,123 @@
+use super::{Error, Frame, Header};
+
+/// An [`Iterator`] over all SOFH-enclosed messages of a byte slice, e.g. a
+/// memory-mapped capture file. Zero-copy.
+///
+/// Contrary to [`SeqDecoder`](super::SeqDecoder), [`Frames`] doesn't buffer
+/// anything: each [`Frame`] borrows its payload from the original slice. The
+/// iteration stops at the end of the slice or right after the first `Err`,
+/// e.g. [`Error::Incomplete`] if the last message is truncated. The bytes that
+/// were not consumed are then available via [`Frames::remainder`].
+///
+/// # Examples
+///
+/// ```
+/// use fesofh::Frames;
+///
+/// let data = &[0u8, 0, 0, 7, 0xF0, 0x00, 42, 0, 0, 0, 6, 0xF5, 0x00] as &[u8];
+/// let mut frames = Frames::new(data);
+/// let frame = frames.next().unwrap().unwrap();
+/// assert_eq!(frame.encoding_type(), 0xF000);
+/// assert_eq!(frame.payload(), &[42]);
+/// let frame = frames.next().unwrap().unwrap();
+/// assert_eq!(frame.encoding_type(), 0xF500);
+/// assert!(frames.next().is_none());
+/// ```
+#[derive(Debug, Clone)]
+pub struct Frames<'a> {
+    data: &'a [u8],
+    failed: bool,
+}
+
+impl<'a> Frames<'a> {
+    /// Creates a new [`Frames`] iterator over the SOFH-enclosed messages of
+    /// `data`, which must start at a message boundary.
+    pub fn new(data: &'a [u8]) -> Self {
+        Self {
+            data,
+            failed: false,
+        }
+    }
+
+    /// Returns the bytes of the original slice that were not consumed yet,
+    /// starting with the header of the next message (if any).
+    pub fn remainder(&self) -> &'a [u8] {
+        self.data
+    }
+}
+
+impl<'a> Iterator for Frames<'a> {
+    type Item = Result<Frame<&'a [u8]>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed || self.data.is_empty() {
+            return None;
+        }
+        match Frame::<&[u8]>::deserialize(self.data) {
+            Ok(frame) => {
+                self.data = &self.data[frame.payload().len() + Header::LENGTH_IN_BYTES..];
+                Some(Ok(frame))
+            }
+            Err(err) => {
+                self.failed = true;
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+impl<'a> core::iter::FusedIterator for Frames<'a> {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn frames(payloads: &[(u16, &[u8])]) -> Vec<u8> {
+        let mut buffer = vec![];
+        for (encoding_type, payload) in payloads {
+            Frame::new(*encoding_type, *payload).serialize_to_vec(&mut buffer);
+        }
+        buffer
+    }
+
+    #[test]
+    fn empty_slice_has_no_frames() {
+        assert!(Frames::new(&[]).next().is_none());
+    }
+
+    #[test]
+    fn payloads_are_borrowed_in_order() {
+        let payloads: &[(u16, &[u8])] = &[(0x5BE0, b"foo"), (0x0, b""), (0xF000, b"bar")];
+        let buffer = frames(payloads);
+        let decoded: Vec<_> = Frames::new(&buffer[..])
+            .map(|frame| frame.unwrap().into_parts())
+            .collect();
+        assert_eq!(&decoded[..], payloads);
+        let first_payload = decoded[0].1.as_ptr();
+        assert_eq!(first_payload, buffer[6..].as_ptr());
+    }
+
+    #[test]
+    fn truncated_frame_stops_iteration() {
+        let buffer = frames(&[(0xF000, b"foo"), (0xF000, b"bar")]);
+        let truncated = &buffer[..buffer.len() - 1];
+        let mut frames = Frames::new(truncated);
+        assert!(frames.next().unwrap().is_ok());
+        assert!(matches!(
+            frames.next(),
+            Some(Err(Error::Incomplete { needed: 1 }))
+        ));
+        assert!(frames.next().is_none());
+        assert_eq!(frames.remainder(), &truncated[9..]);
+    }
+
+    #[test]
+    fn invalid_message_length_stops_iteration() {
+        let mut frames = Frames::new(&[0, 0, 0, 5, 0, 0, 0, 0, 0, 6, 0, 0]);
+        assert!(matches!(
+            frames.next(),
+            Some(Err(Error::InvalidMessageLength))
+        ));
+        assert!(frames.next().is_none());
+    }
+}
@@ -12,10 +12,18 @@
 //!
 //! The preferred way to send and receive SOFH-enclosed messages over wire is with
 //! [`TokioCodec`].
+//! Captured SOFH streams that are already in memory (e.g. pcap dumps) can be
+//! replayed with [`Frames`] instead, without any buffering.
+//!
+//! # `no_std` support
+//!
+//! This crate is `#![no_std]`-compatible, although it still requires
+//! [`alloc`]. Disable the default `std` feature to opt out of libstd, which
+//! leaves out [`ReadFrames`], [`Frame::serialize`], and [`Error::Io`].
 
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 //#![doc(html_root_url = "https://docs.rs/fesofh/")]
 //#![warn(missing_docs, missing_doc_code_examples)]
-
 #![feature(unsafe_block_in_unsafe_fn)]
 #![deny(
     unused,
@@ -34,37 +42,81 @@
 // Only enables the `doc_cfg` feature when its feature is defined.
 #![cfg_attr(doc_cfg, feature(doc_cfg))]
 
+extern crate alloc;
+
 mod encoding_type;
 mod frame;
+mod frames;
 mod seq_decoder;
 #[cfg(feature = "utils-tokio")]
 mod tokio_codec;
 
 pub use encoding_type::EncodingType;
 pub use frame::Frame;
-pub use seq_decoder::{Frames, SeqDecoder};
-use std::convert::TryInto;
-use std::io;
-use thiserror::Error;
+pub use frames::Frames;
+#[cfg(feature = "std")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "std")))]
+pub use seq_decoder::ReadFrames;
+pub use seq_decoder::SeqDecoder;
 #[cfg(feature = "utils-tokio")]
 pub use tokio_codec::TokioCodec;
 
+use core::convert::TryInto;
+use core::fmt;
+#[cfg(feature = "std")]
+use std::io;
+
 /// The type returned in the event of an error when decoding SOFH-enclosed
 /// messages.
-#[derive(Debug, Error)]
+#[derive(Debug)]
 pub enum Error {
     /// The SOFH-enclosed message's length is outside the legal range.
-    #[error("The SOFH-enclosed message's length is outside the legal range.")]
     InvalidMessageLength,
     /// The SOFH-enclosed message is incomplete. More bytes are needed.
-    #[error("The SOFH-enclosed message is incomplete. {needed} more bytes are needed.")]
     Incomplete {
         /// The number of missing bytes to complete the SOFH-enclosed message.
         needed: usize,
     },
     /// I/O-related error.
-    #[error("I/O related error.")]
-    Io(#[from] io::Error),
+    #[cfg(feature = "std")]
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "std")))]
+    Io(io::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidMessageLength => {
+                f.write_str("The SOFH-enclosed message's length is outside the legal range.")
+            }
+            Self::Incomplete { needed } => write!(
+                f,
+                "The SOFH-enclosed message is incomplete. {} more bytes are needed.",
+                needed
+            ),
+            #[cfg(feature = "std")]
+            Self::Io(_) => f.write_str("I/O related error."),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "std")))]
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "std")))]
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
 }
 
 /// The header of a SOFH-enclosed message.
@@ -1,5 +1,7 @@
 use super::frame::Frame;
 use super::Error;
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
 use std::io;
 
 const DEFAULT_CAPACITY: usize = 128;
@@ -53,6 +55,7 @@ impl SeqDecoder {
         let decode_result = Frame::<&[u8]>::deserialize(self.buffer.as_slice());
         match decode_result {
             Ok(_) => &mut [],
+            #[cfg(feature = "std")]
             Err(Error::Io(_)) => panic!("Impossible IO error"),
             Err(Error::Incomplete { needed }) => {
                 self.buffer.resize(self.buffer.as_slice().len() + needed, 0);
@@ -76,24 +79,31 @@ impl SeqDecoder {
         decode_result.unwrap()
     }
 
-    pub fn read_frames<R>(self, reader: R) -> Frames<R>
+    #[cfg(feature = "std")]
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "std")))]
+    pub fn read_frames<R>(self, reader: R) -> ReadFrames<R>
     where
         R: io::Read,
     {
-        Frames {
+        ReadFrames {
             decoder: self,
             reader,
         }
     }
 }
 
+/// Reads [`Frame`]'s from an [`io::Read`] one by one, buffering them in a
+/// [`SeqDecoder`]. See [`SeqDecoder::read_frames`].
+#[cfg(feature = "std")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "std")))]
 #[derive(Debug)]
-pub struct Frames<R> {
+pub struct ReadFrames<R> {
     decoder: SeqDecoder,
     reader: R,
 }
 
-impl<R> Frames<R>
+#[cfg(feature = "std")]
+impl<R> ReadFrames<R>
 where
     R: std::io::Read,
 {
@@ -7,6 +7,13 @@ use tokio_util::codec;
 /// A [`tokio_util`] [`Decoder`](tokio_util::codec::Decoder) and
 /// [`Encoder`](tokio_util::codec::Encoder).
 ///
+/// [`TokioCodec`] yields one [`Frame<Bytes>`] per SOFH-enclosed message, which
+/// can be split into its encoding type and payload via [`Frame::into_parts`].
+/// Frames can be encoded either from a [`Frame`] or from an
+/// `(encoding_type, payload)` pair. Wrap a [`TokioCodec`] in a
+/// [`Framed`](tokio_util::codec::Framed) to consume SOFH-framed FIXP or SBE
+/// traffic as an asynchronous stream.
+///
 /// # Examples
 ///
 /// ```
@@ -39,21 +46,22 @@ impl codec::Decoder for TokioCodec {
     type Error = Error;
 
     fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
-        match Header::from_bytes(&src) {
-            Ok(header) => {
-                let len = header.nominal_message_length_in_bytes;
-                if src.len() >= len {
-                    let mut frame = src.split_to(len);
-                    let payload = frame.split_off(Header::LENGTH_IN_BYTES).freeze();
-                    Ok(Some(Frame::new(header.encoding_type, payload)))
-                } else {
-                    src.reserve(len - src.len());
-                    Ok(None)
-                }
+        let header = match Header::from_bytes(&src) {
+            Ok(header) => header,
+            Err(Error::Incomplete { needed }) => {
+                src.reserve(needed);
+                return Ok(None);
             }
-            Err(Error::InvalidMessageLength) => return Err(Error::InvalidMessageLength),
-            Err(Error::Incomplete { needed: _ }) => Ok(None),
-            Err(Error::Io(_)) => panic!("Unexpected I/O error."),
+            Err(err) => return Err(err),
+        };
+        let len = header.nominal_message_length_in_bytes;
+        if src.len() >= len {
+            let mut frame = src.split_to(len);
+            let payload = frame.split_off(Header::LENGTH_IN_BYTES).freeze();
+            Ok(Some(Frame::new(header.encoding_type, payload)))
+        } else {
+            src.reserve(len - src.len());
+            Ok(None)
         }
     }
 }
@@ -76,3 +84,77 @@ where
         Ok(())
     }
 }
+
+impl<T> codec::Encoder<(u16, T)> for TokioCodec
+where
+    T: AsRef<[u8]>,
+{
+    type Error = io::Error;
+
+    fn encode(&mut self, item: (u16, T), dst: &mut BytesMut) -> Result<(), Self::Error> {
+        let (encoding_type, payload) = item;
+        self.encode(Frame::new(encoding_type, payload), dst)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use futures::executor::block_on;
+    use futures::StreamExt;
+    use tokio_util::codec::{Decoder, Encoder, FramedRead};
+
+    fn encoded(frames: &[(u16, &[u8])]) -> BytesMut {
+        let mut codec = TokioCodec::new();
+        let mut bytes = BytesMut::new();
+        for (encoding_type, payload) in frames {
+            codec
+                .encode((*encoding_type, *payload), &mut bytes)
+                .unwrap();
+        }
+        bytes
+    }
+
+    #[test]
+    fn decode_byte_by_byte() {
+        let bytes = encoded(&[(0x5BE0, b"foobar")]);
+        let mut codec = TokioCodec::new();
+        let mut src = BytesMut::new();
+        for (i, byte) in bytes.iter().enumerate() {
+            src.extend_from_slice(&[*byte]);
+            let frame = codec.decode(&mut src).unwrap();
+            if i == bytes.len() - 1 {
+                let (encoding_type, payload) = frame.unwrap().into_parts();
+                assert_eq!(encoding_type, 0x5BE0);
+                assert_eq!(&payload[..], b"foobar");
+            } else {
+                assert!(frame.is_none());
+            }
+        }
+        assert!(src.is_empty());
+    }
+
+    #[test]
+    fn decode_invalid_message_length() {
+        let mut src = BytesMut::from(&[0u8, 0, 0, 5, 0, 0, 0][..]);
+        assert!(matches!(
+            TokioCodec::new().decode(&mut src),
+            Err(Error::InvalidMessageLength)
+        ));
+    }
+
+    #[test]
+    fn async_stream_of_frames() {
+        let bytes = encoded(&[(0x5BE0, b"sbe"), (0xF500, b"{}"), (0x5BE0, b"")]);
+        let stream = FramedRead::new(&bytes[..], TokioCodec::new());
+        let frames: Vec<(u16, Bytes)> = block_on(stream.map(|f| f.unwrap().into_parts()).collect());
+        assert_eq!(
+            frames,
+            vec![
+                (0x5BE0, Bytes::from_static(b"sbe")),
+                (0xF500, Bytes::from_static(b"{}")),
+                (0x5BE0, Bytes::new()),
+            ]
+        );
+    }
+}